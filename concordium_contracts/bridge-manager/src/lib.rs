@@ -1,12 +1,50 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 use core::ops::Deref;
 
+use bridge_types::{
+    AdminTransferEvent, BlockedAddress, ClaimRequestedEvent, DepositHook, EthAddress,
+    GrantRoleEvent, GrantRoleParams, ProposalEvent, RemoveRoleParams, RevokeRoleEvent, Roles,
+    SetBlockedEvent, SetBlockedParams, SetFeeEvent, SetGovernanceDelayEvent, SetPausedEvent,
+    SetTreasurerEvent, TokenMapEvent, UpgradeEvent, UpgradeParams, MIN_CORRECTION_DELAY,
+};
 use concordium_cis2::*;
 use concordium_std::*;
 
 /// Contract token amount type.
 type ContractTokenAmount = TokenAmountU256;
 
+/// The parameter of `receiveStateUpdate` for the token types of this contract.
+pub type StateUpdate = bridge_types::StateUpdate<ContractTokenAmount, TokenIdU64>;
+/// A deposit operation for the token types of this contract.
+pub type DepositOperation = bridge_types::DepositOperation<ContractTokenAmount, TokenIdU64>;
+/// Tagged event to be serialized for the event log.
+pub type BridgeEvent = bridge_types::BridgeEvent<ContractTokenAmount, TokenIdU64>;
+/// A deposit event for the token types of this contract.
+pub type DepositEvent = bridge_types::DepositEvent<ContractTokenAmount, TokenIdU64>;
+/// A withdraw event for the token types of this contract.
+pub type WithdrawEvent = bridge_types::WithdrawEvent<ContractTokenAmount, TokenIdU64>;
+/// An admin action for the token types of this contract.
+pub type GovernanceAction = bridge_types::GovernanceAction<ContractTokenAmount>;
+/// A proposal event for the token types of this contract.
+pub type ProposalCreatedEvent = bridge_types::ProposalCreatedEvent<ContractTokenAmount>;
+/// The fee for withdrawing a token, for the token types of this contract.
+pub type TokenFee = bridge_types::TokenFee<ContractTokenAmount>;
+/// The parameter of `setTokenFee` for the token types of this contract.
+pub type SetTokenFeeParams = bridge_types::SetTokenFeeParams<ContractTokenAmount>;
+/// The parameter of `correctiveMint` and `correctiveBurn` for the token types
+/// of this contract.
+pub type CorrectionParams = bridge_types::CorrectionParams<ContractTokenAmount>;
+
+/// The basis points of the full withdrawn amount.
+const MAX_BASIS_POINTS: u16 = 10_000;
+
+/// The maximal number of operations removed from `processed_operations` by a
+/// single state update. While an operation is not processed, e.g., a deposit
+/// held in quarantine by the relayer, the later operations are kept in the set.
+/// Once the gap is filled they are removed over the following state updates,
+/// so that no single update runs out of energy.
+const MAX_COMPACTED_OPERATIONS: u64 = 16;
+
 /// The different errors the contract can produce.
 #[derive(Serialize, Debug, PartialEq, Eq, Reject, SchemaType)]
 enum CustomContractError {
@@ -41,6 +79,28 @@ enum CustomContractError {
     FailedUpgradeUnsupportedModuleVersion,
     /// We only support withdraws from accounts, not contracts
     OnlyAccountsCanWithdraw,
+    // Claim fee is too low (smaller than the required withdraw and claim fee)
+    ClaimFeeTooLow,
+    /// The action must be proposed and executed after the governance delay.
+    GovernanceDelayActive,
+    /// There is no proposal with the given id.
+    ProposalNotFound,
+    /// The governance delay of the proposal has not passed yet.
+    ProposalNotReady,
+    /// There is no admin transfer to the sender to accept.
+    NoAdminTransfer,
+    /// The sender or the recipient of a withdrawal is on the blocklist.
+    AddressBlocked,
+    /// The withdrawn amount is smaller than the fee of the token.
+    WithdrawAmountTooLow,
+    /// The fee schedule of a token has more than 10000 basis points.
+    InvalidTokenFee,
+    /// The state has none of the layouts of the current or earlier versions of
+    /// the contract.
+    UnknownStateLayout,
+    /// A corrective burn is from an address that does not have the bridge
+    /// manager as an operator.
+    NotOperator,
 }
 
 type ContractError = Cis2Error<CustomContractError>;
@@ -101,6 +161,42 @@ struct AddressRoleState<S> {
 #[concordium(state_parameter = "S")]
 struct State<S> {
     /// Contract is paused if `paused = true` and unpaused if `paused = false`.
+    paused: bool,
+    roles: StateMap<Address, AddressRoleState<S>, S>,
+    root_mapping: StateMap<EthAddress, ContractAddress, S>,
+    child_mapping: StateMap<ContractAddress, EthAddress, S>,
+    emit_event_index: u64,
+    withdraw_fee: Amount,
+    treasurer_address: AccountAddress,
+    /// The processed operations with an id of at least `processed_watermark`.
+    /// Ids are processed mostly in order, so this stays small.
+    processed_operations: StateSet<u64, S>,
+    /// Fee to be paid, on top of the withdraw fee, for the relayer to
+    /// complete the withdrawal on Ethereum on behalf of the user.
+    claim_fee: Amount,
+    /// All operations with an id below the watermark are processed.
+    processed_watermark: u64,
+    /// Time between proposing an admin action and executing it. While it is
+    /// zero the admin can also configure the contract directly.
+    governance_delay: Duration,
+    /// Id of the next proposal.
+    next_proposal_id: u64,
+    /// Admin actions that are proposed and not yet executed or cancelled.
+    proposals: StateMap<u64, Proposal, S>,
+    /// An executed transfer of the `Admin` role that is not yet accepted.
+    admin_transfer: Option<AdminTransfer>,
+    /// Addresses that cannot withdraw, or cannot be withdrawn to.
+    blocklist: StateSet<BlockedAddress, S>,
+    /// Fees for withdrawing tokens, taken in the token, by the child token.
+    token_fees: StateMap<ContractAddress, TokenFee, S>,
+}
+
+/// The state of the contract as first deployed, which is migrated by
+/// `migrate`. Each later layout appends fields to the one before it, so it is
+/// serialized as the earlier layout followed by the new fields.
+#[derive(Serial, DeserialWithState)]
+#[concordium(state_parameter = "S")]
+struct StateV0<S> {
     paused: bool,
     roles: StateMap<Address, AddressRoleState<S>, S>,
     root_mapping: StateMap<EthAddress, ContractAddress, S>,
@@ -111,6 +207,181 @@ struct State<S> {
     processed_operations: StateSet<u64, S>,
 }
 
+/// The layout of the state after adding the claim fee.
+#[derive(Serial, DeserialWithState)]
+#[concordium(state_parameter = "S")]
+struct StateV1<S> {
+    v0: StateV0<S>,
+    claim_fee: Amount,
+}
+
+/// Deserialize a layout of the state from the root entry. Returns `None`
+/// unless the layout spans the entire entry, so that an earlier layout is never
+/// mistaken for the prefix of a later one.
+fn deserial_exact<S: HasStateApi, T: DeserialWithState<S>>(
+    state_api: &S,
+    root: &mut S::EntryType,
+) -> Option<T> {
+    root.seek(SeekFrom::Start(0)).unwrap_abort();
+    let value = T::deserial_with_state(state_api, root).ok()?;
+    if root.cursor_position() == root.size().unwrap_abort() {
+        Some(value)
+    } else {
+        None
+    }
+}
+
+impl<S: HasStateApi> StateV0<S> {
+    /// Read the state in this layout.
+    fn read(state_api: &S, root: &mut S::EntryType) -> ContractResult<Self> {
+        deserial_exact(state_api, root)
+            .ok_or_else(|| CustomContractError::UnknownStateLayout.into())
+    }
+}
+
+impl<S: HasStateApi> StateV1<S> {
+    /// Read the state in this layout, migrating it from an earlier one if
+    /// needed.
+    fn read(state_api: &S, root: &mut S::EntryType) -> ContractResult<Self> {
+        match deserial_exact(state_api, root) {
+            Some(state) => Ok(state),
+            None => Ok(Self::from_v0(StateV0::read(state_api, root)?)),
+        }
+    }
+
+    /// Withdrawals cannot be claimed until the admin sets the claim fee.
+    fn from_v0(v0: StateV0<S>) -> Self {
+        StateV1 {
+            v0,
+            claim_fee: Amount::zero(),
+        }
+    }
+}
+
+/// The layout of the state after adding the watermark of the processed
+/// operations.
+#[derive(Serial, DeserialWithState)]
+#[concordium(state_parameter = "S")]
+struct StateV2<S> {
+    v1: StateV1<S>,
+    processed_watermark: u64,
+}
+
+impl<S: HasStateApi> StateV2<S> {
+    /// Read the state in this layout, migrating it from an earlier one if
+    /// needed.
+    fn read(state_api: &S, root: &mut S::EntryType) -> ContractResult<Self> {
+        match deserial_exact(state_api, root) {
+            Some(state) => Ok(state),
+            None => Ok(Self::from_v1(StateV1::read(state_api, root)?)),
+        }
+    }
+
+    /// The `StateSender` numbers its operations from 1, so the watermark
+    /// starts there. The processed operations are kept in the set until
+    /// `migrate` moves the watermark past them.
+    fn from_v1(v1: StateV1<S>) -> Self {
+        StateV2 {
+            v1,
+            processed_watermark: 1,
+        }
+    }
+}
+
+/// The layout of the state after adding governance.
+#[derive(Serial, DeserialWithState)]
+#[concordium(state_parameter = "S")]
+struct StateV3<S> {
+    v2: StateV2<S>,
+    governance_delay: Duration,
+    next_proposal_id: u64,
+    proposals: StateMap<u64, Proposal, S>,
+    admin_transfer: Option<AdminTransfer>,
+}
+
+impl<S: HasStateApi> StateV3<S> {
+    /// Read the state in this layout, migrating it from an earlier one if
+    /// needed.
+    fn read(
+        state_api: &S,
+        root: &mut S::EntryType,
+        state_builder: &mut StateBuilder<S>,
+    ) -> ContractResult<Self> {
+        match deserial_exact(state_api, root) {
+            Some(state) => Ok(state),
+            None => Ok(Self::from_v2(
+                StateV2::read(state_api, root)?,
+                state_builder,
+            )),
+        }
+    }
+
+    /// Admin actions take effect immediately until the admin sets a delay.
+    fn from_v2(v2: StateV2<S>, state_builder: &mut StateBuilder<S>) -> Self {
+        StateV3 {
+            v2,
+            governance_delay: Duration::from_millis(0),
+            next_proposal_id: 0,
+            proposals: state_builder.new_map(),
+            admin_transfer: None,
+        }
+    }
+}
+
+/// The layout of the state after adding the blocklist.
+#[derive(Serial, DeserialWithState)]
+#[concordium(state_parameter = "S")]
+struct StateV4<S> {
+    v3: StateV3<S>,
+    blocklist: StateSet<BlockedAddress, S>,
+}
+
+impl<S: HasStateApi> StateV4<S> {
+    /// Read the state in this layout, migrating it from an earlier one if
+    /// needed.
+    fn read(
+        state_api: &S,
+        root: &mut S::EntryType,
+        state_builder: &mut StateBuilder<S>,
+    ) -> ContractResult<Self> {
+        match deserial_exact(state_api, root) {
+            Some(state) => Ok(state),
+            None => Ok(Self::from_v3(
+                StateV3::read(state_api, root, state_builder)?,
+                state_builder,
+            )),
+        }
+    }
+
+    /// No address is blocked.
+    fn from_v3(v3: StateV3<S>, state_builder: &mut StateBuilder<S>) -> Self {
+        StateV4 {
+            v3,
+            blocklist: state_builder.new_set(),
+        }
+    }
+}
+
+/// An admin action that was proposed.
+#[derive(Serialize, SchemaType, Clone)]
+struct Proposal {
+    /// The admin that proposed the action.
+    proposer: Address,
+    /// The proposed action.
+    action: GovernanceAction,
+    /// The time from which the action can be executed.
+    execute_after: Timestamp,
+}
+
+/// A transfer of the `Admin` role, waiting to be accepted.
+#[derive(Serialize, SchemaType, Clone, Copy)]
+struct AdminTransfer {
+    /// The admin that transfers the role.
+    from: Address,
+    /// The address the role is transferred to.
+    to: Address,
+}
+
 /// View function to check if an event index has been processed.
 #[receive(
     contract = "bridge-manager",
@@ -128,7 +399,7 @@ fn contract_is_processed<S: HasStateApi>(
 }
 
 /// Part of the return parameter of the `viewRoles` function.
-#[derive(Serialize, SchemaType, PartialEq)]
+#[derive(Serialize, SchemaType, PartialEq, Clone, Debug)]
 struct ViewRolesState {
     /// Vector of roles.
     roles: Vec<Roles>,
@@ -202,6 +473,244 @@ fn contract_view_token_mappings<S: HasStateApi>(
     })
 }
 
+/// Take at most `limit` entries, skipping the ones with a key before `start`,
+/// and return them together with the key of the next entry, if any. The
+/// entries must be ordered by their serialized key, as the entries of a
+/// `StateMap` are, so that a deleted `start` key is handled as well.
+///
+/// The state has no lookup of the first key from `start`, so the entries
+/// before it are still iterated over, but only their keys are compared. `load`
+/// is only called on the values of the entries on the page.
+fn page<K: Serial + Copy, V, T>(
+    entries: impl Iterator<Item = (K, V)>,
+    start: Option<K>,
+    limit: u32,
+    mut load: impl FnMut(V) -> T,
+) -> (Vec<(K, T)>, Option<K>) {
+    let start = start.map(|key| to_bytes(&key));
+    let mut entries = entries.skip_while(|(key, _)| match &start {
+        Some(start) => to_bytes(key) < *start,
+        None => false,
+    });
+    let page = entries
+        .by_ref()
+        .take(limit as usize)
+        .map(|(key, value)| (key, load(value)))
+        .collect();
+    let next = entries.next().map(|(key, _)| key);
+    (page, next)
+}
+
+/// The parameter type for the contract function `viewRolesPage`.
+#[derive(Serialize, SchemaType)]
+struct ViewRolesPageParams {
+    /// The address to start from, or `None` to start from the beginning.
+    start: Option<Address>,
+    /// The maximal number of addresses to return.
+    limit: u32,
+}
+
+/// Return parameter of the `viewRolesPage` function.
+#[derive(Serialize, SchemaType)]
+struct ViewRolesPage {
+    /// The roles of each address on the page.
+    roles: Vec<(Address, ViewRolesState)>,
+    /// The address to start the next page from, or `None` if this is the last
+    /// page.
+    next: Option<Address>,
+}
+
+/// View function that returns the roles of at most `limit` addresses, starting
+/// from `start`. Meant for monitoring when there are too many addresses for
+/// `viewRoles`.
+///
+/// Only the roles of the addresses on the page are read, but every address
+/// before `start` is still iterated over, so the energy cost grows with the
+/// number of addresses before `start` as well as with `limit`.
+///
+/// It rejects if:
+/// - It fails to parse the parameter.
+#[receive(
+    contract = "bridge-manager",
+    name = "viewRolesPage",
+    parameter = "ViewRolesPageParams",
+    return_value = "ViewRolesPage"
+)]
+fn contract_view_roles_page<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &impl HasHost<State<S>, StateApiType = S>,
+) -> ReceiveResult<ViewRolesPage> {
+    let params: ViewRolesPageParams = ctx.parameter_cursor().get()?;
+    let state = host.state();
+
+    let entries = state
+        .roles
+        .iter()
+        .map(|(address, a_state)| (*address, a_state));
+    let (roles, next) = page(entries, params.start, params.limit, |a_state| {
+        let roles = a_state.roles.iter().map(|x| *x).collect();
+        ViewRolesState { roles }
+    });
+
+    Ok(ViewRolesPage { roles, next })
+}
+
+/// View function that returns the roles of a single address.
+///
+/// It rejects if:
+/// - It fails to parse the parameter.
+#[receive(
+    contract = "bridge-manager",
+    name = "viewAddressRoles",
+    parameter = "Address",
+    return_value = "ViewRolesState"
+)]
+fn contract_view_address_roles<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &impl HasHost<State<S>, StateApiType = S>,
+) -> ReceiveResult<ViewRolesState> {
+    let address: Address = ctx.parameter_cursor().get()?;
+
+    let roles = match host.state().roles.get(&address) {
+        Some(a_state) => a_state.roles.iter().map(|x| *x).collect(),
+        None => Vec::new(),
+    };
+
+    Ok(ViewRolesState { roles })
+}
+
+/// The parameter type for the contract function `viewTokenMappingsPage`.
+#[derive(Serialize, SchemaType)]
+struct ViewTokenMappingsPageParams {
+    /// The root token to start from, or `None` to start from the beginning.
+    start: Option<EthAddress>,
+    /// The maximal number of mappings to return.
+    limit: u32,
+}
+
+/// Return parameter of the `viewTokenMappingsPage` function.
+#[derive(Serialize, SchemaType)]
+struct ViewTokenMappingsPage {
+    /// Token mappings from ethereum address to concordium contract address.
+    root_mappings: Vec<(EthAddress, ContractAddress)>,
+    /// The root token to start the next page from, or `None` if this is the
+    /// last page.
+    next: Option<EthAddress>,
+}
+
+/// View function that returns at most `limit` token mappings, ordered by the
+/// root token, starting from `start`. The mappings from the child tokens are
+/// the inverse of these. Meant for monitoring when there are too many mappings
+/// for `viewTokenMappings`.
+///
+/// Every mapping before `start` is still iterated over, so the energy cost
+/// grows with the number of mappings before `start` as well as with `limit`.
+///
+/// It rejects if:
+/// - It fails to parse the parameter.
+#[receive(
+    contract = "bridge-manager",
+    name = "viewTokenMappingsPage",
+    parameter = "ViewTokenMappingsPageParams",
+    return_value = "ViewTokenMappingsPage"
+)]
+fn contract_view_token_mappings_page<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &impl HasHost<State<S>, StateApiType = S>,
+) -> ReceiveResult<ViewTokenMappingsPage> {
+    let params: ViewTokenMappingsPageParams = ctx.parameter_cursor().get()?;
+    let state = host.state();
+
+    let entries = state
+        .root_mapping
+        .iter()
+        .map(|(eth_address, contract_address)| (*eth_address, *contract_address));
+    let (root_mappings, next) = page(entries, params.start, params.limit, |x| x);
+
+    Ok(ViewTokenMappingsPage {
+        root_mappings,
+        next,
+    })
+}
+
+/// View function that returns the child token mapped to the given root token,
+/// if any.
+///
+/// It rejects if:
+/// - It fails to parse the parameter.
+#[receive(
+    contract = "bridge-manager",
+    name = "viewChildToken",
+    parameter = "EthAddress",
+    return_value = "Option<ContractAddress>"
+)]
+fn contract_view_child_token<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &impl HasHost<State<S>, StateApiType = S>,
+) -> ReceiveResult<Option<ContractAddress>> {
+    let root: EthAddress = ctx.parameter_cursor().get()?;
+
+    Ok(host.state().root_mapping.get(&root).map(|child| *child))
+}
+
+/// View function that returns the root token mapped to the given child token,
+/// if any.
+///
+/// It rejects if:
+/// - It fails to parse the parameter.
+#[receive(
+    contract = "bridge-manager",
+    name = "viewRootToken",
+    parameter = "ContractAddress",
+    return_value = "Option<EthAddress>"
+)]
+fn contract_view_root_token<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &impl HasHost<State<S>, StateApiType = S>,
+) -> ReceiveResult<Option<EthAddress>> {
+    let child: ContractAddress = ctx.parameter_cursor().get()?;
+
+    Ok(host.state().child_mapping.get(&child).map(|root| *root))
+}
+
+/// View function that checks if an address is on the blocklist.
+///
+/// It rejects if:
+/// - It fails to parse the parameter.
+#[receive(
+    contract = "bridge-manager",
+    name = "isBlocked",
+    parameter = "BlockedAddress",
+    return_value = "bool"
+)]
+fn contract_is_blocked<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &impl HasHost<State<S>, StateApiType = S>,
+) -> ReceiveResult<bool> {
+    let address: BlockedAddress = ctx.parameter_cursor().get()?;
+
+    Ok(host.state().is_blocked(&address))
+}
+
+/// View function that returns the fee for withdrawing a token, if it is set.
+///
+/// It rejects if:
+/// - It fails to parse the parameter.
+#[receive(
+    contract = "bridge-manager",
+    name = "viewTokenFee",
+    parameter = "ContractAddress",
+    return_value = "Option<TokenFee>"
+)]
+fn contract_view_token_fee<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &impl HasHost<State<S>, StateApiType = S>,
+) -> ReceiveResult<Option<TokenFee>> {
+    let token: ContractAddress = ctx.parameter_cursor().get()?;
+
+    Ok(host.state().token_fees.get(&token).map(|fee| *fee))
+}
+
 /// Return parameter of the `viewConfiguration` function.
 #[derive(Serialize, SchemaType, PartialEq)]
 struct ViewConfigurationState {
@@ -213,6 +722,13 @@ struct ViewConfigurationState {
     withdraw_fee: Amount,
     /// The address of the treasury receiving the above fees.
     treasurer_address: AccountAddress,
+    /// The additional fee to be paid when requesting the withdrawal to be
+    /// claimed on Ethereum by the relayer.
+    claim_fee: Amount,
+    /// All operations with an id below this are processed.
+    processed_watermark: u64,
+    /// Time between proposing an admin action and executing it.
+    governance_delay: Duration,
 }
 
 /// View function that returns configuration values of the state. Meant for
@@ -233,47 +749,47 @@ fn contract_view_configuration<S: HasStateApi>(
         emit_event_index: state.emit_event_index,
         withdraw_fee: state.withdraw_fee,
         treasurer_address: state.treasurer_address,
+        claim_fee: state.claim_fee,
+        processed_watermark: state.processed_watermark,
+        governance_delay: state.governance_delay,
     })
 }
 
-#[derive(Serialize, PartialEq, Eq, Reject, SchemaType, Clone, Copy)]
-pub enum Roles {
-    Admin,
-    Mapper,
-    StateSyncer,
-}
-
-/// Manual implementation of the `EthAddressSchema` to display it in hex.
-impl schema::SchemaType for EthAddress {
-    fn get_type() -> schema::Type {
-        schema::Type::ByteArray(20)
-    }
+/// Return parameter of the `viewGovernance` function.
+#[derive(Serialize, SchemaType)]
+struct ViewGovernance {
+    /// Time between proposing an admin action and executing it.
+    governance_delay: Duration,
+    /// The proposals that are not yet executed or cancelled, by id.
+    proposals: Vec<(u64, Proposal)>,
+    /// An executed transfer of the `Admin` role that is not yet accepted.
+    admin_transfer: Option<AdminTransfer>,
 }
 
-#[derive(Serialize, Debug, Copy, Clone, PartialEq, Eq)]
-pub struct EthAddress {
-    pub eth_address: [u8; 20],
-}
+/// View function that returns the governance delay, the pending proposals and
+/// the pending admin transfer. Meant for monitoring.
+#[receive(
+    contract = "bridge-manager",
+    name = "viewGovernance",
+    return_value = "ViewGovernance"
+)]
+fn contract_view_governance<S: HasStateApi>(
+    _ctx: &impl HasReceiveContext,
+    host: &impl HasHost<State<S>, StateApiType = S>,
+) -> ReceiveResult<ViewGovernance> {
+    let state = host.state();
 
-#[derive(Serialize, SchemaType)]
-pub struct DepositOperation {
-    pub id: u64,
-    pub user: Address,
-    pub root: EthAddress,
-    pub amount: ContractTokenAmount,
-    pub token_id: TokenIdU64,
-}
+    let proposals = state
+        .proposals
+        .iter()
+        .map(|(id, proposal)| (*id, proposal.clone()))
+        .collect();
 
-#[derive(Serialize, SchemaType)]
-pub struct TokenMapOperation {
-    pub id: u64,
-    pub root: EthAddress,
-    pub child: ContractAddress,
-}
-#[derive(Serialize, SchemaType)]
-pub enum StateUpdate {
-    Deposit(DepositOperation),
-    TokenMap(TokenMapOperation),
+    Ok(ViewGovernance {
+        governance_delay: state.governance_delay,
+        proposals,
+        admin_transfer: state.admin_transfer,
+    })
 }
 
 impl<S: HasStateApi> State<S> {
@@ -288,9 +804,80 @@ impl<S: HasStateApi> State<S> {
             withdraw_fee: Amount::from_micro_ccd(0),
             treasurer_address: treasurer,
             processed_operations: state_builder.new_set(),
+            claim_fee: Amount::from_micro_ccd(0),
+            // The `StateSender` numbers its operations from 1.
+            processed_watermark: 1u64,
+            governance_delay: Duration::from_millis(0),
+            next_proposal_id: 0u64,
+            proposals: state_builder.new_map(),
+            admin_transfer: None,
+            blocklist: state_builder.new_set(),
+            token_fees: state_builder.new_map(),
+        }
+    }
+
+    /// Read the state, migrating it from the layout of an earlier version of
+    /// the contract if needed.
+    fn read(
+        state_api: &S,
+        root: &mut S::EntryType,
+        state_builder: &mut StateBuilder<S>,
+    ) -> ContractResult<Self> {
+        match deserial_exact(state_api, root) {
+            Some(state) => Ok(state),
+            None => Ok(Self::from_v4(
+                StateV4::read(state_api, root, state_builder)?,
+                state_builder,
+            )),
+        }
+    }
+
+    /// No token has a fee.
+    fn from_v4(v4: StateV4<S>, state_builder: &mut StateBuilder<S>) -> Self {
+        let StateV4 { v3, blocklist } = v4;
+        let StateV3 {
+            v2,
+            governance_delay,
+            next_proposal_id,
+            proposals,
+            admin_transfer,
+        } = v3;
+        let StateV2 {
+            v1,
+            processed_watermark,
+        } = v2;
+        let StateV1 { v0, claim_fee } = v1;
+        State {
+            paused: v0.paused,
+            roles: v0.roles,
+            root_mapping: v0.root_mapping,
+            child_mapping: v0.child_mapping,
+            emit_event_index: v0.emit_event_index,
+            withdraw_fee: v0.withdraw_fee,
+            treasurer_address: v0.treasurer_address,
+            processed_operations: v0.processed_operations,
+            claim_fee,
+            processed_watermark,
+            governance_delay,
+            next_proposal_id,
+            proposals,
+            admin_transfer,
+            blocklist,
+            token_fees: state_builder.new_map(),
         }
     }
 
+    /// Whether the address is on the blocklist.
+    fn is_blocked(&self, address: &BlockedAddress) -> bool {
+        self.blocklist.contains(address)
+    }
+
+    /// Whether admin actions must be proposed, because the governance delay
+    /// is not zero.
+    fn has_governance_delay(&self) -> bool {
+        self.governance_delay.millis() > 0
+    }
+
     fn has_role(&self, account: &Address, role: Roles) -> bool {
         return match self.roles.get(account) {
             None => false,
@@ -349,16 +936,77 @@ impl<S: HasStateApi> State<S> {
         self.withdraw_fee = fee;
     }
 
+    fn set_claim_fee(&mut self, fee: Amount) {
+        self.claim_fee = fee;
+    }
+
     fn set_treasurer(&mut self, treasurer: AccountAddress) {
         self.treasurer_address = treasurer;
     }
 
+    fn set_token_fee(&mut self, params: &SetTokenFeeParams) -> ContractResult<()> {
+        ensure!(
+            params.fee.basis_points <= MAX_BASIS_POINTS,
+            ContractError::Custom(CustomContractError::InvalidTokenFee)
+        );
+        self.token_fees.insert(params.token, params.fee);
+        Ok(())
+    }
+
+    /// The fee in tokens for withdrawing the amount of the token. It rejects
+    /// if the fee is larger than the amount.
+    fn token_fee(
+        &self,
+        token: &ContractAddress,
+        amount: ContractTokenAmount,
+    ) -> ContractResult<ContractTokenAmount> {
+        let fee = match self.token_fees.get(token) {
+            Some(fee) => *fee,
+            None => return Ok(TokenAmountU256(0u64.into())),
+        };
+        let basis_points = u64::from(fee.basis_points);
+        let divisor = u64::from(MAX_BASIS_POINTS);
+        // Split the amount before multiplying, so that it cannot overflow.
+        let proportional =
+            amount.0 / divisor * basis_points + amount.0 % divisor * basis_points / divisor;
+        let total = fee
+            .flat
+            .0
+            .checked_add(proportional)
+            .map(|total| total.max(fee.minimum.0));
+        match total {
+            Some(total) if total <= amount.0 => Ok(TokenAmountU256(total)),
+            _ => bail!(ContractError::Custom(
+                CustomContractError::WithdrawAmountTooLow
+            )),
+        }
+    }
+
+    /// Mark an operation as processed. An operation at the watermark moves it,
+    /// while the processed operations above it are only removed from the set
+    /// by `compact_operations`.
     fn set_operation(&mut self, op: u64) {
-        self.processed_operations.insert(op);
+        if op == self.processed_watermark {
+            self.processed_watermark += 1;
+        } else if op > self.processed_watermark {
+            self.processed_operations.insert(op);
+        }
     }
 
     fn has_operation(&self, op: u64) -> bool {
-        self.processed_operations.contains(&op)
+        op < self.processed_watermark || self.processed_operations.contains(&op)
+    }
+
+    /// Move the watermark past the operations that are processed in order,
+    /// removing at most `limit` of them from `processed_operations`. Returns
+    /// the number of operations removed.
+    fn compact_operations(&mut self, limit: u64) -> u64 {
+        let mut removed = 0;
+        while removed < limit && self.processed_operations.remove(&self.processed_watermark) {
+            self.processed_watermark += 1;
+            removed += 1;
+        }
+        removed
     }
 }
 // Contract functions
@@ -403,56 +1051,6 @@ impl From<bool> for HasRoleQueryResponse {
     }
 }
 
-/// Tagged event to be serialized for the event log.
-#[derive(Serialize, SchemaType)]
-pub enum BridgeEvent {
-    // TokenMapEvent
-    TokenMap(TokenMapEvent),
-    Deposit(DepositEvent),
-    Withdraw(WithdrawEvent),
-    GrantRole(GrantRoleEvent),
-    RevokeRole(RevokeRoleEvent),
-}
-
-#[derive(Serialize, SchemaType)]
-pub struct TokenMapEvent {
-    pub id: u64,
-    pub root: EthAddress,
-    pub child: ContractAddress,
-}
-
-#[derive(Serialize, SchemaType)]
-pub struct DepositEvent {
-    pub id: u64,
-    pub contract: ContractAddress,
-    pub amount: ContractTokenAmount,
-    pub token_id: TokenIdU64,
-}
-
-#[derive(Serialize, SchemaType)]
-pub struct WithdrawEvent {
-    pub id: u64,
-    pub contract: ContractAddress,
-    pub amount: ContractTokenAmount,
-    pub ccd_address: Address,
-    pub eth_address: EthAddress,
-    pub token_id: TokenIdU64,
-}
-
-// A GrantRoleEvent introduced by this smart contract.
-#[derive(Serialize, SchemaType)]
-pub struct GrantRoleEvent {
-    /// Address that has been given the role
-    address: Address,
-    role: Roles,
-}
-// A RevokeRoleEvent introduced by this smart contract.
-#[derive(Serialize, SchemaType)]
-pub struct RevokeRoleEvent {
-    /// Address that has been revoked the role
-    address: Address,
-    role: Roles,
-}
 /// Check if an address has a role.
 /// TODO Should this be batched like the rest of the functions ?
 ///
@@ -476,13 +1074,6 @@ fn contract_has_role<S: HasStateApi>(
     Ok(HasRoleQueryResponse::from(has_role))
 }
 
-/// The parameter type for the contract function `grantRole`.
-#[derive(Serialize, SchemaType)]
-pub struct GrantRoleParams {
-    pub address: Address,
-    pub role: Roles,
-}
-
 /// Grant Permission to an address
 ///
 /// It rejects if:
@@ -511,6 +1102,10 @@ fn contract_grant_role<S: HasStateApi>(
         state.has_role(&sender, Roles::Admin),
         ContractError::Unauthorized
     );
+    ensure!(
+        !state.has_governance_delay(),
+        ContractError::Custom(CustomContractError::GovernanceDelayActive)
+    );
 
     state.grant_role(&params.address, params.role, state_builder);
     logger.log(&BridgeEvent::GrantRole(GrantRoleEvent {
@@ -520,13 +1115,6 @@ fn contract_grant_role<S: HasStateApi>(
     Ok(())
 }
 
-/// The parameter type for the contract function `removeRole`.
-#[derive(Serialize, SchemaType)]
-pub struct RemoveRoleParams {
-    pub address: Address,
-    pub role: Roles,
-}
-
 /// Remove Permission to an address
 ///
 /// It rejects if:
@@ -556,6 +1144,10 @@ fn contract_remove_role<S: HasStateApi>(
         state.has_role(&sender, Roles::Admin),
         ContractError::Unauthorized
     );
+    ensure!(
+        !state.has_governance_delay(),
+        ContractError::Custom(CustomContractError::GovernanceDelayActive)
+    );
 
     ensure!(
         state.has_role(&params.address, params.role),
@@ -584,12 +1176,14 @@ pub struct SetWithdrawFeeParams {
 #[receive(
     contract = "bridge-manager",
     name = "setWithdrawFee",
+    enable_logger,
     parameter = "SetWithdrawFeeParams",
     mutable
 )]
 fn contract_set_withdraw_fee<S: HasStateApi>(
     ctx: &impl HasReceiveContext,
     host: &mut impl HasHost<State<S>, StateApiType = S>,
+    logger: &mut impl HasLogger,
 ) -> ContractResult<()> {
     // Parse the parameter.
     let params: SetWithdrawFeeParams = ctx.parameter_cursor().get()?;
@@ -602,8 +1196,63 @@ fn contract_set_withdraw_fee<S: HasStateApi>(
         state.has_role(&sender, Roles::Admin),
         ContractError::Unauthorized
     );
-
+    ensure!(
+        !state.has_governance_delay(),
+        ContractError::Custom(CustomContractError::GovernanceDelayActive)
+    );
+
     state.set_withdraw_fee(params.amount);
+    logger.log(&BridgeEvent::SetWithdrawFee(SetFeeEvent {
+        fee: params.amount,
+    }))?;
+
+    Ok(())
+}
+
+/// The parameter type for the contract function `setClaimFee`.
+#[derive(Serialize, SchemaType)]
+pub struct SetClaimFeeParams {
+    pub amount: Amount,
+}
+
+/// Set the additional fee required for the relayer to claim a withdrawal on
+/// Ethereum on behalf of the user.
+///
+/// It rejects if:
+/// - It fails to parse the parameter.
+/// - The sender does not have the required permission
+#[receive(
+    contract = "bridge-manager",
+    name = "setClaimFee",
+    enable_logger,
+    parameter = "SetClaimFeeParams",
+    mutable
+)]
+fn contract_set_claim_fee<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &mut impl HasHost<State<S>, StateApiType = S>,
+    logger: &mut impl HasLogger,
+) -> ContractResult<()> {
+    // Parse the parameter.
+    let params: SetClaimFeeParams = ctx.parameter_cursor().get()?;
+
+    // Get the sender who invoked this contract function.
+    let sender = ctx.sender();
+
+    let (state, _) = host.state_and_builder();
+    ensure!(
+        state.has_role(&sender, Roles::Admin),
+        ContractError::Unauthorized
+    );
+    ensure!(
+        !state.has_governance_delay(),
+        ContractError::Custom(CustomContractError::GovernanceDelayActive)
+    );
+
+    state.set_claim_fee(params.amount);
+    logger.log(&BridgeEvent::SetClaimFee(SetFeeEvent {
+        fee: params.amount,
+    }))?;
 
     Ok(())
 }
@@ -622,12 +1271,14 @@ pub struct SetTreasurer {
 #[receive(
     contract = "bridge-manager",
     name = "setTreasurer",
+    enable_logger,
     parameter = "SetTreasurer",
     mutable
 )]
 fn contract_set_treasurer<S: HasStateApi>(
     ctx: &impl HasReceiveContext,
     host: &mut impl HasHost<State<S>, StateApiType = S>,
+    logger: &mut impl HasLogger,
 ) -> ContractResult<()> {
     // Parse the parameter.
     let params: SetTreasurer = ctx.parameter_cursor().get()?;
@@ -640,23 +1291,205 @@ fn contract_set_treasurer<S: HasStateApi>(
         state.has_role(&sender, Roles::Admin),
         ContractError::Unauthorized
     );
+    ensure!(
+        !state.has_governance_delay(),
+        ContractError::Custom(CustomContractError::GovernanceDelayActive)
+    );
 
     state.set_treasurer(params.account);
+    logger.log(&BridgeEvent::SetTreasurer(SetTreasurerEvent {
+        treasurer: params.account,
+    }))?;
 
     Ok(())
 }
 
-/// The parameter type for the contract function `upgrade`.
-/// Takes the new module and optionally an entrypoint to call in the new module
-/// after triggering the upgrade. The upgrade is reverted if the entrypoint
-/// fails. This is useful for doing migration in the same transaction triggering
-/// the upgrade.
-#[derive(Serialize, SchemaType)]
-struct UpgradeParams {
-    /// The new module reference.
-    module: ModuleReference,
-    /// Optional entrypoint to call in the new module after upgrade.
-    migrate: Option<(OwnedEntrypointName, OwnedParameter)>,
+/// Set the fee for withdrawing a token, which is taken in the token: the full
+/// withdrawn amount is burned and the fee is minted to the treasurer.
+///
+/// It rejects if:
+/// - It fails to parse the parameter.
+/// - The sender does not have the required permission
+/// - The governance delay is active.
+/// - The fee has more than 10000 basis points.
+#[receive(
+    contract = "bridge-manager",
+    name = "setTokenFee",
+    enable_logger,
+    parameter = "SetTokenFeeParams",
+    error = "ContractError",
+    mutable
+)]
+fn contract_set_token_fee<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &mut impl HasHost<State<S>, StateApiType = S>,
+    logger: &mut impl HasLogger,
+) -> ContractResult<()> {
+    // Parse the parameter.
+    let params: SetTokenFeeParams = ctx.parameter_cursor().get()?;
+
+    // Get the sender who invoked this contract function.
+    let sender = ctx.sender();
+
+    let (state, _) = host.state_and_builder();
+    ensure!(
+        state.has_role(&sender, Roles::Admin),
+        ContractError::Unauthorized
+    );
+    ensure!(
+        !state.has_governance_delay(),
+        ContractError::Custom(CustomContractError::GovernanceDelayActive)
+    );
+
+    state.set_token_fee(&params)?;
+    logger.log(&BridgeEvent::SetTokenFee(params))?;
+
+    Ok(())
+}
+
+/// Propose to mint tokens of a child token to an address to correct the supply
+/// after an incident, e.g., a deposit that was lost. Returns the id of the
+/// proposal, which an admin executes with `execute` once the governance delay,
+/// but at least the `MIN_CORRECTION_DELAY`, has passed. The tokens are then
+/// minted by the `deposit` entrypoint of the token, and a `CorrectiveMint`
+/// event records the incident it belongs to.
+///
+/// It rejects if:
+/// - It fails to parse the parameter.
+/// - The sender does not have the required permission
+/// - The token is not a mapped child token.
+#[receive(
+    contract = "bridge-manager",
+    name = "correctiveMint",
+    enable_logger,
+    parameter = "CorrectionParams",
+    return_value = "u64",
+    error = "ContractError",
+    mutable
+)]
+fn contract_corrective_mint<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &mut impl HasHost<State<S>, StateApiType = S>,
+    logger: &mut impl HasLogger,
+) -> ContractResult<u64> {
+    // Parse the parameter.
+    let params: CorrectionParams = ctx.parameter_cursor().get()?;
+    propose(ctx, host, logger, GovernanceAction::CorrectiveMint(params))
+}
+
+/// Propose to burn tokens of a child token from an address to correct the
+/// supply after an incident. Returns the id of the proposal, which is executed
+/// like that of `correctiveMint`. The tokens are burned by the `withdraw`
+/// entrypoint of the token, which only burns tokens on behalf of an operator,
+/// so the address must have the bridge manager as an operator of the token,
+/// both when the burn is proposed and when it is executed. A `CorrectiveBurn`
+/// event records the incident it belongs to.
+///
+/// It rejects if:
+/// - It fails to parse the parameter.
+/// - The sender does not have the required permission
+/// - The token is not a mapped child token.
+/// - The bridge manager is not an operator of the address.
+#[receive(
+    contract = "bridge-manager",
+    name = "correctiveBurn",
+    enable_logger,
+    parameter = "CorrectionParams",
+    return_value = "u64",
+    error = "ContractError",
+    mutable
+)]
+fn contract_corrective_burn<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &mut impl HasHost<State<S>, StateApiType = S>,
+    logger: &mut impl HasLogger,
+) -> ContractResult<u64> {
+    // Parse the parameter.
+    let params: CorrectionParams = ctx.parameter_cursor().get()?;
+    propose(ctx, host, logger, GovernanceAction::CorrectiveBurn(params))
+}
+
+/// Check that a correction can be made: the token is a mapped child token,
+/// and, if tokens are burned, the bridge manager is an operator of the address
+/// they are burned from.
+fn check_correction<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &impl HasHost<State<S>, StateApiType = S>,
+    params: &CorrectionParams,
+    mint: bool,
+) -> ContractResult<()> {
+    ensure!(
+        host.state().child_mapping.get(&params.token).is_some(),
+        ContractError::Custom(CustomContractError::TokenNotMapped)
+    );
+    if mint {
+        return Ok(());
+    }
+
+    let query = OperatorOfQueryParams {
+        queries: vec![OperatorOfQuery {
+            owner: params.address,
+            address: Address::Contract(ctx.self_address()),
+        }],
+    };
+    let response = host.invoke_contract_read_only(
+        &params.token,
+        &query,
+        EntrypointName::new("operatorOf").unwrap(),
+        Amount { micro_ccd: 0 },
+    )?;
+    let response: OperatorOfQueryResponse = response
+        .ok_or(ContractError::Custom(
+            CustomContractError::InvokeContractError,
+        ))?
+        .get()?;
+    ensure!(
+        response.0.first() == Some(&true),
+        ContractError::Custom(CustomContractError::NotOperator)
+    );
+    Ok(())
+}
+
+/// Mint or burn tokens through the child token, and log the correction.
+fn correct<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &mut impl HasHost<State<S>, StateApiType = S>,
+    logger: &mut impl HasLogger,
+    params: CorrectionParams,
+    mint: bool,
+) -> ContractResult<()> {
+    check_correction(ctx, host, &params, mint)?;
+
+    if mint {
+        let deposit_params = DepositParams {
+            address: params.address,
+            amount: params.amount,
+            token_id: TokenIdU64(0),
+            hook: None,
+        };
+        host.invoke_contract(
+            &params.token,
+            &deposit_params,
+            EntrypointName::new("deposit").unwrap(),
+            Amount { micro_ccd: 0 },
+        )?;
+        logger.log(&BridgeEvent::CorrectiveMint(params))?;
+    } else {
+        let withdraw_params = Cis2WithdrawParams {
+            address: params.address,
+            amount: params.amount,
+            token_id: TokenIdU64(0),
+        };
+        host.invoke_contract(
+            &params.token,
+            &withdraw_params,
+            EntrypointName::new("withdraw").unwrap(),
+            Amount { micro_ccd: 0 },
+        )?;
+        logger.log(&BridgeEvent::CorrectiveBurn(params))?;
+    }
+
+    Ok(())
 }
 
 #[receive(
@@ -664,11 +1497,13 @@ struct UpgradeParams {
     name = "upgrade",
     parameter = "UpgradeParams",
     error = "ContractError",
+    enable_logger,
     mutable
 )]
 fn contract_upgrade<S: HasStateApi>(
     ctx: &impl HasReceiveContext,
     host: &mut impl HasHost<State<S>, StateApiType = S>,
+    logger: &mut impl HasLogger,
 ) -> ContractResult<()> {
     // Get the sender who invoked this contract function.
     let sender = ctx.sender();
@@ -680,10 +1515,27 @@ fn contract_upgrade<S: HasStateApi>(
         state.has_role(&sender, Roles::Admin),
         ContractError::Unauthorized
     );
+    ensure!(
+        !state.has_governance_delay(),
+        ContractError::Custom(CustomContractError::GovernanceDelayActive)
+    );
     // Parse the parameter.
     let params: UpgradeParams = ctx.parameter_cursor().get()?;
+    upgrade(ctx, host, logger, params)
+}
+
+/// Upgrade the contract, and call the migration function if provided.
+fn upgrade<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &mut impl HasHost<State<S>, StateApiType = S>,
+    logger: &mut impl HasLogger,
+    params: UpgradeParams,
+) -> ContractResult<()> {
     // Trigger the upgrade.
     host.upgrade(params.module)?;
+    logger.log(&BridgeEvent::Upgrade(UpgradeEvent {
+        module: params.module,
+    }))?;
     // Call the migration function if provided.
     if let Some((func, parameters)) = params.migrate {
         host.invoke_contract_raw(
@@ -696,6 +1548,316 @@ fn contract_upgrade<S: HasStateApi>(
     Ok(())
 }
 
+/// Migrate the state of an earlier version of the contract, and move the
+/// watermark of the processed operations past at most the given number of
+/// operations that are processed in order. Earlier versions kept every
+/// processed operation in `processed_operations`. Meant to be used as the
+/// `migrate` function of an upgrade, and then called again by the admin until
+/// the watermark stops moving. Calling it on a migrated state only moves the
+/// watermark.
+///
+/// It rejects if:
+/// - Sender is neither the admin nor the contract itself.
+/// - It fails to parse the parameter.
+/// - The state has none of the known layouts.
+#[receive(
+    contract = "bridge-manager",
+    name = "migrate",
+    parameter = "u64",
+    error = "ContractError",
+    low_level
+)]
+fn contract_migrate<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &mut impl HasHost<S, StateApiType = S>,
+) -> ContractResult<()> {
+    // Parse the parameter.
+    let limit: u64 = ctx.parameter_cursor().get()?;
+
+    let state_api = host.state().clone();
+    let mut root = state_api.lookup_entry(&[]).unwrap_abort();
+    let mut state = State::read(&state_api, &mut root, host.state_builder())?;
+
+    // Get the sender who invoked this contract function.
+    let sender = ctx.sender();
+    ensure!(
+        state.has_role(&sender, Roles::Admin) || sender == Address::Contract(ctx.self_address()),
+        ContractError::Unauthorized
+    );
+
+    // Earlier versions started the watermark at 0, which the `StateSender`
+    // never uses as an id.
+    state.processed_watermark = state.processed_watermark.max(1);
+    state.compact_operations(limit);
+    root.seek(SeekFrom::Start(0)).unwrap_abort();
+    state.serial(&mut root).unwrap_abort();
+    Ok(())
+}
+
+/// Propose an admin action, which can be executed with `execute` once the
+/// governance delay has passed. Corrective mints and burns wait for at least
+/// the `MIN_CORRECTION_DELAY`. Returns the id of the proposal.
+///
+/// It rejects if:
+/// - It fails to parse the parameter.
+/// - Sender is not an admin.
+/// - A correction cannot be made, see `correctiveMint` and `correctiveBurn`.
+#[receive(
+    contract = "bridge-manager",
+    name = "propose",
+    parameter = "GovernanceAction",
+    return_value = "u64",
+    error = "ContractError",
+    enable_logger,
+    mutable
+)]
+fn contract_propose<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &mut impl HasHost<State<S>, StateApiType = S>,
+    logger: &mut impl HasLogger,
+) -> ContractResult<u64> {
+    // Parse the parameter.
+    let action: GovernanceAction = ctx.parameter_cursor().get()?;
+    propose(ctx, host, logger, action)
+}
+
+/// Record the proposal of an admin action by the sender, and return its id.
+/// Corrections of the supply are checked when they are proposed, and wait for
+/// at least the `MIN_CORRECTION_DELAY`.
+fn propose<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &mut impl HasHost<State<S>, StateApiType = S>,
+    logger: &mut impl HasLogger,
+    action: GovernanceAction,
+) -> ContractResult<u64> {
+    // Get the sender who invoked this contract function.
+    let sender = ctx.sender();
+
+    ensure!(
+        host.state().has_role(&sender, Roles::Admin),
+        ContractError::Unauthorized
+    );
+
+    let delay = match &action {
+        GovernanceAction::CorrectiveMint(params) => {
+            check_correction(ctx, host, params, true)?;
+            host.state().governance_delay.max(MIN_CORRECTION_DELAY)
+        }
+        GovernanceAction::CorrectiveBurn(params) => {
+            check_correction(ctx, host, params, false)?;
+            host.state().governance_delay.max(MIN_CORRECTION_DELAY)
+        }
+        _ => host.state().governance_delay,
+    };
+
+    let state = host.state_mut();
+    let id = state.next_proposal_id;
+    state.next_proposal_id += 1;
+    let execute_after = ctx.metadata().slot_time().checked_add(delay).unwrap_abort();
+    state.proposals.insert(
+        id,
+        Proposal {
+            proposer: sender,
+            action: action.clone(),
+            execute_after,
+        },
+    );
+    logger.log(&BridgeEvent::ProposalCreated(ProposalCreatedEvent {
+        id,
+        proposer: sender,
+        action,
+        execute_after,
+    }))?;
+    Ok(id)
+}
+
+/// Execute the proposal with the given id.
+///
+/// It rejects if:
+/// - It fails to parse the parameter.
+/// - Sender is not an admin.
+/// - There is no proposal with the id.
+/// - The governance delay of the proposal has not passed.
+/// - The action fails, e.g., a role to remove is not assigned.
+#[receive(
+    contract = "bridge-manager",
+    name = "execute",
+    parameter = "u64",
+    error = "ContractError",
+    enable_logger,
+    mutable
+)]
+fn contract_execute<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &mut impl HasHost<State<S>, StateApiType = S>,
+    logger: &mut impl HasLogger,
+) -> ContractResult<()> {
+    // Parse the parameter.
+    let id: u64 = ctx.parameter_cursor().get()?;
+
+    // Get the sender who invoked this contract function.
+    let sender = ctx.sender();
+
+    let (state, state_builder) = host.state_and_builder();
+    ensure!(
+        state.has_role(&sender, Roles::Admin),
+        ContractError::Unauthorized
+    );
+
+    let proposal = match state.proposals.get(&id) {
+        Some(proposal) => proposal.clone(),
+        None => bail!(ContractError::Custom(CustomContractError::ProposalNotFound)),
+    };
+    ensure!(
+        ctx.metadata().slot_time() >= proposal.execute_after,
+        ContractError::Custom(CustomContractError::ProposalNotReady)
+    );
+    state.proposals.remove(&id);
+    logger.log(&BridgeEvent::ProposalExecuted(ProposalEvent { id }))?;
+
+    match proposal.action {
+        GovernanceAction::SetWithdrawFee(fee) => {
+            state.set_withdraw_fee(fee);
+            logger.log(&BridgeEvent::SetWithdrawFee(SetFeeEvent { fee }))?;
+        }
+        GovernanceAction::SetClaimFee(fee) => {
+            state.set_claim_fee(fee);
+            logger.log(&BridgeEvent::SetClaimFee(SetFeeEvent { fee }))?;
+        }
+        GovernanceAction::SetTreasurer(treasurer) => {
+            state.set_treasurer(treasurer);
+            logger.log(&BridgeEvent::SetTreasurer(SetTreasurerEvent { treasurer }))?;
+        }
+        GovernanceAction::GrantRole(params) => {
+            state.grant_role(&params.address, params.role, state_builder);
+            logger.log(&BridgeEvent::GrantRole(GrantRoleEvent {
+                address: params.address,
+                role: params.role,
+            }))?;
+        }
+        GovernanceAction::RemoveRole(params) => {
+            ensure!(
+                state.has_role(&params.address, params.role),
+                ContractError::Custom(CustomContractError::RoleNotAssigned)
+            );
+            state.remove_role(&params.address, params.role);
+            logger.log(&BridgeEvent::RevokeRole(RevokeRoleEvent {
+                address: params.address,
+                role: params.role,
+            }))?;
+        }
+        GovernanceAction::SetGovernanceDelay(delay) => {
+            state.governance_delay = delay;
+            logger.log(&BridgeEvent::SetGovernanceDelay(SetGovernanceDelayEvent {
+                delay,
+            }))?;
+        }
+        GovernanceAction::TransferAdmin(to) => {
+            let transfer = AdminTransfer {
+                from: proposal.proposer,
+                to,
+            };
+            state.admin_transfer = Some(transfer);
+            logger.log(&BridgeEvent::AdminTransferPending(AdminTransferEvent {
+                from: transfer.from,
+                to: transfer.to,
+            }))?;
+        }
+        GovernanceAction::SetTokenFee(params) => {
+            state.set_token_fee(&params)?;
+            logger.log(&BridgeEvent::SetTokenFee(params))?;
+        }
+        GovernanceAction::CorrectiveMint(params) => correct(ctx, host, logger, params, true)?,
+        GovernanceAction::CorrectiveBurn(params) => correct(ctx, host, logger, params, false)?,
+        GovernanceAction::Upgrade(params) => upgrade(ctx, host, logger, params)?,
+    }
+    Ok(())
+}
+
+/// Cancel the proposal with the given id.
+///
+/// It rejects if:
+/// - It fails to parse the parameter.
+/// - Sender is not an admin.
+/// - There is no proposal with the id.
+#[receive(
+    contract = "bridge-manager",
+    name = "cancel",
+    parameter = "u64",
+    error = "ContractError",
+    enable_logger,
+    mutable
+)]
+fn contract_cancel<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &mut impl HasHost<State<S>, StateApiType = S>,
+    logger: &mut impl HasLogger,
+) -> ContractResult<()> {
+    // Parse the parameter.
+    let id: u64 = ctx.parameter_cursor().get()?;
+
+    // Get the sender who invoked this contract function.
+    let sender = ctx.sender();
+
+    let state = host.state_mut();
+    ensure!(
+        state.has_role(&sender, Roles::Admin),
+        ContractError::Unauthorized
+    );
+    ensure!(
+        state.proposals.remove_and_get(&id).is_some(),
+        ContractError::Custom(CustomContractError::ProposalNotFound)
+    );
+    logger.log(&BridgeEvent::ProposalCancelled(ProposalEvent { id }))?;
+    Ok(())
+}
+
+/// Accept the transfer of the `Admin` role to the sender. The role is revoked
+/// from the admin that proposed the transfer.
+///
+/// It rejects if:
+/// - The pending admin transfer is not to the sender.
+#[receive(
+    contract = "bridge-manager",
+    name = "acceptAdmin",
+    error = "ContractError",
+    enable_logger,
+    mutable
+)]
+fn contract_accept_admin<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &mut impl HasHost<State<S>, StateApiType = S>,
+    logger: &mut impl HasLogger,
+) -> ContractResult<()> {
+    // Get the sender who invoked this contract function.
+    let sender = ctx.sender();
+
+    let (state, state_builder) = host.state_and_builder();
+    let transfer = match state.admin_transfer {
+        Some(transfer) if transfer.to == sender => transfer,
+        _ => bail!(ContractError::Custom(CustomContractError::NoAdminTransfer)),
+    };
+    state.admin_transfer = None;
+    logger.log(&BridgeEvent::AdminTransferAccepted(AdminTransferEvent {
+        from: transfer.from,
+        to: transfer.to,
+    }))?;
+
+    state.grant_role(&transfer.to, Roles::Admin, state_builder);
+    logger.log(&BridgeEvent::GrantRole(GrantRoleEvent {
+        address: transfer.to,
+        role: Roles::Admin,
+    }))?;
+    if transfer.from != transfer.to && state.has_role(&transfer.from, Roles::Admin) {
+        state.remove_role(&transfer.from, Roles::Admin);
+        logger.log(&BridgeEvent::RevokeRole(RevokeRoleEvent {
+            address: transfer.from,
+            role: Roles::Admin,
+        }))?;
+    }
+    Ok(())
+}
+
 /// The parameter type for the contract function `setPaused`.
 #[derive(Serialize, SchemaType)]
 #[repr(transparent)]
@@ -705,38 +1867,92 @@ struct SetPausedParams {
 }
 /// Pause/Unpause this smart contract instance by the admin. All non-admin
 /// state-mutative functions (wrap, unwrap, transfer, updateOperator) cannot be
-/// executed when the contract is paused.
+/// executed when the contract is paused. A pauser can pause, but not unpause,
+/// the contract.
 ///
 /// It rejects if:
-/// - Sender is not the admin of the contract instance.
+/// - Sender is not the admin of the contract instance, and either not a pauser
+///   or unpausing.
 /// - It fails to parse the parameter.
 #[receive(
     contract = "bridge-manager",
     name = "setPaused",
     parameter = "SetPausedParams",
     error = "ContractError",
+    enable_logger,
     mutable
 )]
 fn contract_set_paused<S: HasStateApi>(
     ctx: &impl HasReceiveContext,
     host: &mut impl HasHost<State<S>, StateApiType = S>,
+    logger: &mut impl HasLogger,
 ) -> ContractResult<()> {
     // Get the sender who invoked this contract function.
     let sender = ctx.sender();
 
+    // Parse the parameter.
+    let params: SetPausedParams = ctx.parameter_cursor().get()?;
+
     let (state, _) = host.state_and_builder();
 
-    // Check that only the admin is authorized to set implementors.
+    // Check that only the admin, or a pauser when pausing, is authorized to
+    // change the paused state.
     ensure!(
-        state.has_role(&sender, Roles::Admin),
+        state.has_role(&sender, Roles::Admin)
+            || (params.paused && state.has_role(&sender, Roles::Pauser)),
         ContractError::Unauthorized
     );
 
-    // Parse the parameter.
-    let params: SetPausedParams = ctx.parameter_cursor().get()?;
-
     // Update the paused variable.
     host.state_mut().paused = params.paused;
+    logger.log(&BridgeEvent::SetPaused(SetPausedEvent {
+        paused: params.paused,
+    }))?;
+
+    Ok(())
+}
+
+/// Add an address to, or remove it from, the blocklist. A blocked Concordium
+/// address cannot withdraw, and a blocked Ethereum address cannot be the
+/// recipient of a withdrawal.
+///
+/// It rejects if:
+/// - Sender is not a blocklister.
+/// - It fails to parse the parameter.
+#[receive(
+    contract = "bridge-manager",
+    name = "setBlocked",
+    parameter = "SetBlockedParams",
+    error = "ContractError",
+    enable_logger,
+    mutable
+)]
+fn contract_set_blocked<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &mut impl HasHost<State<S>, StateApiType = S>,
+    logger: &mut impl HasLogger,
+) -> ContractResult<()> {
+    // Get the sender who invoked this contract function.
+    let sender = ctx.sender();
+
+    // Parse the parameter.
+    let params: SetBlockedParams = ctx.parameter_cursor().get()?;
+
+    let state = host.state_mut();
+    ensure!(
+        state.has_role(&sender, Roles::Blocklister),
+        ContractError::Unauthorized
+    );
+
+    if params.blocked {
+        state.blocklist.insert(params.address);
+    } else {
+        state.blocklist.remove(&params.address);
+    }
+    logger.log(&BridgeEvent::SetBlocked(SetBlockedEvent {
+        address: params.address,
+        blocked: params.blocked,
+    }))?;
 
     Ok(())
 }
@@ -746,6 +1962,8 @@ pub struct DepositParams {
     pub address: Address,
     pub amount: ContractTokenAmount,
     pub token_id: TokenIdU64,
+    /// Entrypoint the token invokes if `address` is a contract.
+    pub hook: Option<DepositHook>,
 }
 #[receive(
     contract = "bridge-manager",
@@ -800,6 +2018,7 @@ fn contract_receive_state_update<S: HasStateApi>(
                 address: op.user,
                 amount: op.amount,
                 token_id: op.token_id,
+                hook: op.hook,
             };
 
             let child_token = match state.root_mapping.get(&op.root) {
@@ -819,7 +2038,24 @@ fn contract_receive_state_update<S: HasStateApi>(
                 token_id: deposit_params.token_id,
             }))?;
         }
+        StateUpdate::Skip(op) => {
+            // Operations that are already processed are ignored, unless all
+            // of them are, so that the relayer does not resend the update.
+            let mut skipped = false;
+            for id in op.ids {
+                if !state.has_operation(id) {
+                    state.set_operation(id);
+                    skipped = true;
+                }
+            }
+            ensure!(
+                skipped,
+                ContractError::Custom(CustomContractError::OperationAlreadyProcessed)
+            );
+        }
     }
+    host.state_mut()
+        .compact_operations(MAX_COMPACTED_OPERATIONS);
     Ok(())
 }
 
@@ -838,6 +2074,17 @@ pub struct Cis2WithdrawParams {
     pub amount: ContractTokenAmount,
     pub token_id: TokenIdU64,
 }
+/// Withdraw tokens to Ethereum. The user has to complete the withdrawal on
+/// Ethereum themselves once it is approved. The withdrawn amount is burned, and
+/// the fee of the token, if any, is minted to the treasurer.
+///
+/// It rejects if:
+/// - The contract is paused.
+/// - The sender is not an account.
+/// - It fails to parse the parameter.
+/// - The sender or the Ethereum address is on the blocklist.
+/// - The withdrawn amount is smaller than the fee of the token.
+/// - The attached amount is smaller than the withdraw fee.
 #[receive(
     contract = "bridge-manager",
     name = "withdraw",
@@ -851,6 +2098,44 @@ fn contract_withdraw<S: HasStateApi>(
     host: &mut impl HasHost<State<S>, StateApiType = S>,
     amount: Amount,
     logger: &mut impl HasLogger,
+) -> ContractResult<()> {
+    withdraw(ctx, host, amount, logger, false)
+}
+
+/// Withdraw tokens to Ethereum and request the relayer to complete the
+/// withdrawal on Ethereum once it is approved. This emits a
+/// [`ClaimRequestedEvent`] in addition to the [`WithdrawEvent`].
+///
+/// It rejects if:
+/// - The contract is paused.
+/// - The sender is not an account.
+/// - It fails to parse the parameter.
+/// - The sender or the Ethereum address is on the blocklist.
+/// - The withdrawn amount is smaller than the fee of the token.
+/// - The attached amount is smaller than the withdraw fee plus the claim fee.
+#[receive(
+    contract = "bridge-manager",
+    name = "withdrawWithClaim",
+    parameter = "WithdrawParams",
+    enable_logger,
+    mutable,
+    payable
+)]
+fn contract_withdraw_with_claim<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &mut impl HasHost<State<S>, StateApiType = S>,
+    amount: Amount,
+    logger: &mut impl HasLogger,
+) -> ContractResult<()> {
+    withdraw(ctx, host, amount, logger, true)
+}
+
+fn withdraw<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &mut impl HasHost<State<S>, StateApiType = S>,
+    amount: Amount,
+    logger: &mut impl HasLogger,
+    claim: bool,
 ) -> ContractResult<()> {
     // Check that contract is not paused.
     ensure!(
@@ -867,12 +2152,34 @@ fn contract_withdraw<S: HasStateApi>(
     // Get the sender who invoked this contract function.
     let sender = ctx.sender();
 
+    // Check that neither the sender nor the recipient is blocked.
+    ensure!(
+        !host.state().is_blocked(&BlockedAddress::Concordium(sender))
+            && !host
+                .state()
+                .is_blocked(&BlockedAddress::Ethereum(withdraw_params.eth_address)),
+        ContractError::Custom(CustomContractError::AddressBlocked)
+    );
+
+    // Split the withdrawn amount into the fee of the token and the amount
+    // bridged to Ethereum.
+    let token_fee = host
+        .state()
+        .token_fee(&withdraw_params.token, withdraw_params.amount)?;
+    let bridged_amount = TokenAmountU256(withdraw_params.amount.0 - token_fee.0);
+
     // Transfer fee to treasury.
     let fee = host.state().withdraw_fee;
     ensure!(
         amount >= fee,
         ContractError::Custom(CustomContractError::WithdrawFeeTooLow)
     );
+    if claim {
+        ensure!(
+            amount - fee >= host.state().claim_fee,
+            ContractError::Custom(CustomContractError::ClaimFeeTooLow)
+        );
+    }
     let treasurer = host.state().treasurer_address;
     host.invoke_transfer(&treasurer, amount)?;
 
@@ -891,15 +2198,40 @@ fn contract_withdraw<S: HasStateApi>(
         Amount { micro_ccd: 0 },
     )?;
 
+    // Mint the fee of the token to the treasury, through the same entrypoint as
+    // deposits.
+    if !token_fee.0.is_zero() {
+        let deposit_params = DepositParams {
+            address: Address::Account(treasurer),
+            amount: token_fee,
+            token_id: withdraw_params.token_id,
+            hook: None,
+        };
+        host.invoke_contract(
+            &withdraw_params.token,
+            &deposit_params,
+            EntrypointName::new("deposit").unwrap(),
+            Amount { micro_ccd: 0 },
+        )?;
+    }
+
     logger.log(&BridgeEvent::Withdraw(WithdrawEvent {
-        id: event_index,
+        event_index,
         contract: withdraw_params.token,
-        amount: withdraw_params.amount,
+        amount: bridged_amount,
         ccd_address: sender,
         eth_address: withdraw_params.eth_address,
         token_id: params.token_id,
+        fee: Some(token_fee),
     }))?;
 
+    if claim {
+        logger.log(&BridgeEvent::ClaimRequested(ClaimRequestedEvent {
+            event_index,
+            fee: amount - fee,
+        }))?;
+    }
+
     Ok(())
 }
 
@@ -907,6 +2239,7 @@ fn contract_withdraw<S: HasStateApi>(
 #[concordium_cfg_test]
 mod tests {
     use super::*;
+    use bridge_types::{SkipOperations, TokenMapOperation};
     use test_infrastructure::*;
 
     const ACCOUNT_0: AccountAddress = AccountAddress([0u8; 32]);
@@ -928,6 +2261,10 @@ mod tests {
         index: 42,
         subindex: 0,
     };
+    const BRIDGE_ADDRESS: ContractAddress = ContractAddress {
+        index: 41,
+        subindex: 0,
+    };
 
     fn token_amount(amount: u64) -> ContractTokenAmount {
         TokenAmountU256(amount.into())
@@ -981,6 +2318,7 @@ mod tests {
     fn test_view_configuration() {
         let emit_event_index = 9;
         let withdraw_fee = Amount::from_micro_ccd(4);
+        let claim_fee = Amount::from_micro_ccd(7);
 
         let builder = TestStateBuilder::new();
         let mut state_builder = TestStateBuilder::new();
@@ -994,6 +2332,14 @@ mod tests {
             withdraw_fee,
             treasurer_address: TREASURY_ACCOUNT,
             processed_operations: state_builder.new_set(),
+            claim_fee,
+            processed_watermark: 3,
+            governance_delay: Duration::from_millis(5),
+            next_proposal_id: 0,
+            proposals: state_builder.new_map(),
+            admin_transfer: None,
+            blocklist: state_builder.new_set(),
+            token_fees: state_builder.new_map(),
         };
 
         let host = TestHost::new(state, builder);
@@ -1010,6 +2356,9 @@ mod tests {
                 emit_event_index,
                 withdraw_fee,
                 treasurer_address: TREASURY_ACCOUNT,
+                claim_fee,
+                processed_watermark: 3,
+                governance_delay: Duration::from_millis(5),
             }),
             "Configuration state should be correct"
         );
@@ -1207,6 +2556,50 @@ mod tests {
             ),
             "ACCOUNT_1 should have the role Mapper"
         );
+
+        // Testing the `viewRolesPage` function
+        let parameter = ViewRolesPageParams {
+            start: None,
+            limit: 1,
+        };
+        let parameter_bytes = to_bytes(&parameter);
+        ctx.set_parameter(&parameter_bytes);
+        let roles_page = contract_view_roles_page(&ctx, &host)
+            .expect_report("Calling contract_view_roles_page expected to succeed.");
+        claim_eq!(
+            roles_page.roles,
+            vec![roles.all_roles[0].clone()],
+            "The first page should only contain ACCOUNT_0"
+        );
+        claim_eq!(
+            roles_page.next,
+            Some(concordium_std::Address::Account(ACCOUNT_1))
+        );
+
+        let parameter = ViewRolesPageParams {
+            start: roles_page.next,
+            limit: 1,
+        };
+        let parameter_bytes = to_bytes(&parameter);
+        ctx.set_parameter(&parameter_bytes);
+        let roles_page = contract_view_roles_page(&ctx, &host)
+            .expect_report("Calling contract_view_roles_page expected to succeed.");
+        claim_eq!(
+            roles_page.roles,
+            vec![roles.all_roles[1].clone()],
+            "The second page should only contain ACCOUNT_1"
+        );
+        claim_eq!(roles_page.next, None);
+
+        // Testing the `viewAddressRoles` function
+        let parameter_bytes = to_bytes(&concordium_std::Address::Account(ACCOUNT_1));
+        ctx.set_parameter(&parameter_bytes);
+        claim_eq!(
+            contract_view_address_roles(&ctx, &host),
+            Ok(ViewRolesState {
+                roles: vec![Roles::Mapper],
+            })
+        );
     }
 
     /// Test if token mappings can be viewed in the state.
@@ -1273,6 +2666,47 @@ mod tests {
             vec![(CIS2_ADDRESS, ETH_ADDRESS)],
             "Initiator does not have admin"
         );
+
+        // Check the lookups of a single mapping.
+        let parameter_bytes = to_bytes(&ETH_ADDRESS);
+        ctx.set_parameter(&parameter_bytes);
+        claim_eq!(
+            contract_view_child_token(&ctx, &host),
+            Ok(Some(CIS2_ADDRESS))
+        );
+        let parameter_bytes = to_bytes(&CIS2_ADDRESS);
+        ctx.set_parameter(&parameter_bytes);
+        claim_eq!(contract_view_root_token(&ctx, &host), Ok(Some(ETH_ADDRESS)));
+        let parameter_bytes = to_bytes(&ContractAddress::new(7, 0));
+        ctx.set_parameter(&parameter_bytes);
+        claim_eq!(contract_view_root_token(&ctx, &host), Ok(None));
+    }
+
+    /// Test that `page` returns the entries from the start key, and the key
+    /// to continue from, loading only the values on the page.
+    #[concordium_test]
+    fn test_page() {
+        let entries = || (1u64..=5).map(|key| (key, key * 10));
+
+        claim_eq!(
+            page(entries(), None, 2, |x| x),
+            (vec![(1, 10), (2, 20)], Some(3))
+        );
+        claim_eq!(
+            page(entries(), Some(3), 2, |x| x),
+            (vec![(3, 30), (4, 40)], Some(5))
+        );
+        claim_eq!(page(entries(), Some(5), 2, |x| x), (vec![(5, 50)], None));
+        claim_eq!(page(entries(), Some(6), 2, |x| x), (vec![], None));
+        claim_eq!(
+            page(entries().filter(|(key, _)| *key != 3), Some(3), 1, |x| x),
+            (vec![(4, 40)], Some(5))
+        );
+
+        // Only the values on the page are loaded.
+        let mut loaded = Vec::new();
+        page(entries(), Some(2), 2, |value| loaded.push(value));
+        claim_eq!(loaded, vec![20, 30]);
     }
 
     /// Test deposit flow. Add tokens to token mappings and deposit a token.
@@ -1348,6 +2782,7 @@ mod tests {
                         "Token id should be correct"
                     );
                     claim!(deposit.address == ADDRESS_1, "Address should be correct");
+                    claim!(deposit.hook.is_none(), "No hook should be passed on");
                     Ok((true, ()))
                 },
             ),
@@ -1358,6 +2793,7 @@ mod tests {
             root: ETH_ADDRESS,
             amount: token_amount(42),
             token_id: TokenIdU64(0),
+            hook: None,
         });
 
         let parameter_bytes = to_bytes(&parameter);
@@ -1561,8 +2997,400 @@ mod tests {
         let result: ContractResult<()> =
             contract_withdraw(&ctx, &mut host, Amount { micro_ccd: 0 }, &mut logger);
 
-        claim!(result.is_ok(), "ADDRESS_1  is allowed to withdraw");
+        claim!(result.is_ok(), "ADDRESS_1  is allowed to withdraw");
+    }
+
+    /// Test withdrawing with a claim request checks the claim fee and logs the
+    /// claim request.
+    #[concordium_test]
+    fn test_withdraw_with_claim_flow() {
+        let mut builder = TestStateBuilder::new();
+        let mut state = initial_state(&mut builder);
+        state.map_token(&ETH_ADDRESS, &CIS2_ADDRESS);
+        let mut host = TestHost::new(state, builder);
+        let mut logger = TestLogger::init();
+
+        host.setup_mock_entrypoint(
+            CIS2_ADDRESS,
+            OwnedEntrypointName::new_unchecked("withdraw".into()),
+            MockFn::returning_ok(()),
+        );
+
+        let parameter = SetClaimFeeParams {
+            amount: Amount::from_micro_ccd(10),
+        };
+        let parameter_bytes = to_bytes(&parameter);
+        let mut ctx = TestReceiveContext::empty();
+        ctx.set_sender(ADDRESS_1);
+        ctx.set_parameter(&parameter_bytes);
+        let result: ContractResult<()> = contract_set_claim_fee(&ctx, &mut host, &mut logger);
+        claim_eq!(
+            result,
+            Err(ContractError::Unauthorized),
+            "Only the admin may set the claim fee"
+        );
+
+        ctx.set_sender(ADDRESS_0);
+        let result: ContractResult<()> = contract_set_claim_fee(&ctx, &mut host, &mut logger);
+        claim!(result.is_ok(), "Admin is allowed to set the claim fee");
+        claim_eq!(
+            host.state().claim_fee,
+            Amount::from_micro_ccd(10),
+            "Claim fee should be updated"
+        );
+        claim_eq!(
+            logger.logs,
+            vec![to_bytes(&BridgeEvent::SetClaimFee(SetFeeEvent {
+                fee: Amount::from_micro_ccd(10),
+            }))],
+            "The new claim fee should be logged"
+        );
+        logger.logs.clear();
+
+        let parameter = WithdrawParams {
+            eth_address: ETH_WALLET_ADDRESS,
+            amount: token_amount(42),
+            token_id: TokenIdU64(0),
+            token: CIS2_ADDRESS,
+        };
+        let parameter_bytes = to_bytes(&parameter);
+        let mut ctx = TestReceiveContext::empty();
+        ctx.set_sender(ADDRESS_1);
+        ctx.set_parameter(&parameter_bytes);
+
+        let result: ContractResult<()> =
+            contract_withdraw_with_claim(&ctx, &mut host, Amount::from_micro_ccd(9), &mut logger);
+        claim_eq!(
+            result,
+            Err(ContractError::Custom(CustomContractError::ClaimFeeTooLow)),
+            "Withdrawal should fail when the claim fee is not paid"
+        );
+
+        host.set_self_balance(Amount::from_micro_ccd(12));
+        let result: ContractResult<()> =
+            contract_withdraw_with_claim(&ctx, &mut host, Amount::from_micro_ccd(12), &mut logger);
+        claim!(
+            result.is_ok(),
+            "ADDRESS_1 is allowed to withdraw with a claim"
+        );
+        claim_eq!(
+            host.get_transfers(),
+            [(ACCOUNT_2, Amount::from_micro_ccd(12))],
+            "The full amount should be transferred to the treasury"
+        );
+        claim_eq!(logger.logs.len(), 2, "Exactly two events should be logged");
+        claim_eq!(
+            logger.logs[1],
+            to_bytes(&BridgeEvent::ClaimRequested(ClaimRequestedEvent {
+                event_index: 1,
+                fee: Amount::from_micro_ccd(12),
+            })),
+            "Missing claim request event"
+        );
+    }
+
+    /// Test that the withdrawn amount is burned, and the fee of the token is
+    /// minted to the treasurer.
+    #[concordium_test]
+    fn test_withdraw_with_token_fee() {
+        let mut builder = TestStateBuilder::new();
+        let mut state = initial_state(&mut builder);
+        state.map_token(&ETH_ADDRESS, &CIS2_ADDRESS);
+        let mut host = TestHost::new(state, builder);
+        let mut logger = TestLogger::init();
+
+        // Only the admin sets fees, and at most 10000 basis points.
+        let mut parameter = SetTokenFeeParams {
+            token: CIS2_ADDRESS,
+            fee: TokenFee {
+                flat: token_amount(2),
+                basis_points: 10_001,
+                minimum: token_amount(5),
+            },
+        };
+        let parameter_bytes = to_bytes(&parameter);
+        let mut ctx = TestReceiveContext::empty();
+        ctx.set_sender(ADDRESS_1);
+        ctx.set_parameter(&parameter_bytes);
+        claim_eq!(
+            contract_set_token_fee(&ctx, &mut host, &mut logger),
+            Err(ContractError::Unauthorized),
+            "Only the admin may set the fee of a token"
+        );
+        ctx.set_sender(ADDRESS_0);
+        claim_eq!(
+            contract_set_token_fee(&ctx, &mut host, &mut logger),
+            Err(ContractError::Custom(CustomContractError::InvalidTokenFee)),
+            "The fee cannot be more than 100% of the withdrawn amount"
+        );
+        parameter.fee.basis_points = 100;
+        let parameter_bytes = to_bytes(&parameter);
+        ctx.set_parameter(&parameter_bytes);
+        claim_eq!(contract_set_token_fee(&ctx, &mut host, &mut logger), Ok(()));
+        claim_eq!(
+            logger.logs,
+            vec![to_bytes(&BridgeEvent::SetTokenFee(parameter.clone()))],
+            "The new fee should be logged"
+        );
+        logger.logs.clear();
+        let parameter_bytes = to_bytes(&CIS2_ADDRESS);
+        ctx.set_parameter(&parameter_bytes);
+        claim_eq!(
+            contract_view_token_fee(&ctx, &host),
+            Ok(Some(parameter.fee))
+        );
+
+        // The fee is the flat fee plus 1%, but at least the minimum.
+        let state = host.state();
+        claim_eq!(
+            state.token_fee(&CIS2_ADDRESS, token_amount(1000)),
+            Ok(token_amount(12))
+        );
+        claim_eq!(
+            state.token_fee(&CIS2_ADDRESS, token_amount(100)),
+            Ok(token_amount(5))
+        );
+        claim_eq!(
+            state.token_fee(&CIS2_ADDRESS, token_amount(4)),
+            Err(ContractError::Custom(
+                CustomContractError::WithdrawAmountTooLow
+            ))
+        );
+        claim_eq!(
+            state.token_fee(
+                &ContractAddress {
+                    index: 43,
+                    subindex: 0
+                },
+                token_amount(4)
+            ),
+            Ok(token_amount(0)),
+            "Tokens without a fee are withdrawn in full"
+        );
+
+        host.setup_mock_entrypoint(
+            CIS2_ADDRESS,
+            OwnedEntrypointName::new_unchecked("deposit".into()),
+            MockFn::new_v1(
+                |parameter, _amount, _balance, _state: &mut State<TestStateApi>| {
+                    let params = from_bytes::<DepositParams>(parameter.0).unwrap();
+                    claim_eq!(
+                        params.address,
+                        Address::Account(ACCOUNT_2),
+                        "The fee should be minted to the treasurer"
+                    );
+                    claim_eq!(params.amount, token_amount(12));
+                    Ok((true, ()))
+                },
+            ),
+        );
+        host.setup_mock_entrypoint(
+            CIS2_ADDRESS,
+            OwnedEntrypointName::new_unchecked("withdraw".into()),
+            MockFn::new_v1(
+                |parameter, _amount, _balance, _state: &mut State<TestStateApi>| {
+                    let params = from_bytes::<Cis2WithdrawParams>(parameter.0).unwrap();
+                    claim_eq!(params.amount, token_amount(1000));
+                    Ok((true, ()))
+                },
+            ),
+        );
+
+        let parameter_bytes = to_bytes(&WithdrawParams {
+            eth_address: ETH_WALLET_ADDRESS,
+            amount: token_amount(1000),
+            token_id: TokenIdU64(0),
+            token: CIS2_ADDRESS,
+        });
+        let mut ctx = TestReceiveContext::empty();
+        ctx.set_sender(ADDRESS_1);
+        ctx.set_parameter(&parameter_bytes);
+        let result: ContractResult<()> =
+            contract_withdraw(&ctx, &mut host, Amount::zero(), &mut logger);
+        claim!(result.is_ok(), "ADDRESS_1 is allowed to withdraw");
+        claim_eq!(
+            logger.logs,
+            vec![to_bytes(&BridgeEvent::Withdraw(WithdrawEvent {
+                event_index: 1,
+                contract: CIS2_ADDRESS,
+                amount: token_amount(988),
+                ccd_address: ADDRESS_1,
+                eth_address: ETH_WALLET_ADDRESS,
+                token_id: TokenIdU64(0),
+                fee: Some(token_amount(12)),
+            }))],
+            "The withdraw event should record the burned amount and the fee"
+        );
+    }
+
+    /// Test that corrections are always proposed, wait for at least the
+    /// minimum delay, and mint and burn through the child token.
+    #[concordium_test]
+    fn test_corrections() {
+        let mut builder = TestStateBuilder::new();
+        let mut state = initial_state(&mut builder);
+        state.map_token(&ETH_ADDRESS, &CIS2_ADDRESS);
+        let mut host = TestHost::new(state, builder);
+        let mut logger = TestLogger::init();
+
+        host.setup_mock_entrypoint(
+            CIS2_ADDRESS,
+            OwnedEntrypointName::new_unchecked("deposit".into()),
+            MockFn::new_v1(
+                |parameter, _amount, _balance, _state: &mut State<TestStateApi>| {
+                    let params = from_bytes::<DepositParams>(parameter.0).unwrap();
+                    claim_eq!(params.address, ADDRESS_1);
+                    claim_eq!(params.amount, token_amount(42));
+                    Ok((true, ()))
+                },
+            ),
+        );
+        host.setup_mock_entrypoint(
+            CIS2_ADDRESS,
+            OwnedEntrypointName::new_unchecked("withdraw".into()),
+            MockFn::new_v1(
+                |parameter, _amount, _balance, _state: &mut State<TestStateApi>| {
+                    let params = from_bytes::<Cis2WithdrawParams>(parameter.0).unwrap();
+                    claim_eq!(params.address, ADDRESS_1);
+                    claim_eq!(params.amount, token_amount(42));
+                    Ok((true, ()))
+                },
+            ),
+        );
+        let operator_of = |is_operator: bool| {
+            MockFn::new_v1(
+                move |parameter, _amount, _balance, _state: &mut State<TestStateApi>| {
+                    let query = from_bytes::<OperatorOfQueryParams>(parameter.0).unwrap();
+                    claim_eq!(query.queries[0].owner, ADDRESS_1);
+                    claim_eq!(query.queries[0].address, Address::Contract(BRIDGE_ADDRESS));
+                    Ok((false, OperatorOfQueryResponse(vec![is_operator])))
+                },
+            )
+        };
+        host.setup_mock_entrypoint(
+            CIS2_ADDRESS,
+            OwnedEntrypointName::new_unchecked("operatorOf".into()),
+            operator_of(false),
+        );
+
+        let mut parameter = CorrectionParams {
+            incident_id: 7,
+            token: ContractAddress {
+                index: 43,
+                subindex: 0,
+            },
+            address: ADDRESS_1,
+            amount: token_amount(42),
+        };
+        let parameter_bytes = to_bytes(&parameter);
+        let mut ctx = TestReceiveContext::empty();
+        ctx.set_self_address(BRIDGE_ADDRESS);
+        ctx.set_metadata_slot_time(Timestamp::from_timestamp_millis(1_000));
+        ctx.set_sender(ADDRESS_1);
+        ctx.set_parameter(&parameter_bytes);
+        claim_eq!(
+            contract_corrective_mint(&ctx, &mut host, &mut logger),
+            Err(ContractError::Unauthorized),
+            "Only the admin corrects the supply"
+        );
+        ctx.set_sender(ADDRESS_0);
+        claim_eq!(
+            contract_corrective_mint(&ctx, &mut host, &mut logger),
+            Err(ContractError::Custom(CustomContractError::TokenNotMapped)),
+            "Only mapped tokens are corrected"
+        );
+
+        // Without a governance delay, corrections still wait for the minimum
+        // delay.
+        parameter.token = CIS2_ADDRESS;
+        let parameter_bytes = to_bytes(&parameter);
+        ctx.set_parameter(&parameter_bytes);
+        claim_eq!(
+            contract_corrective_mint(&ctx, &mut host, &mut logger),
+            Ok(0)
+        );
+        let execute_after = Timestamp::from_timestamp_millis(1_000)
+            .checked_add(MIN_CORRECTION_DELAY)
+            .unwrap();
+        claim_eq!(
+            logger.logs,
+            vec![to_bytes(&BridgeEvent::ProposalCreated(
+                ProposalCreatedEvent {
+                    id: 0,
+                    proposer: ADDRESS_0,
+                    action: GovernanceAction::CorrectiveMint(parameter.clone()),
+                    execute_after,
+                }
+            ))]
+        );
+        claim_eq!(
+            contract_corrective_burn(&ctx, &mut host, &mut logger),
+            Err(ContractError::Custom(CustomContractError::NotOperator)),
+            "Burns are from addresses with the bridge manager as an operator"
+        );
+        host.setup_mock_entrypoint(
+            CIS2_ADDRESS,
+            OwnedEntrypointName::new_unchecked("operatorOf".into()),
+            operator_of(true),
+        );
+        claim_eq!(
+            contract_corrective_burn(&ctx, &mut host, &mut logger),
+            Ok(1)
+        );
+
+        let parameter_bytes = to_bytes(&0u64);
+        ctx.set_parameter(&parameter_bytes);
+        claim_eq!(
+            contract_execute(&ctx, &mut host, &mut logger),
+            Err(ContractError::Custom(CustomContractError::ProposalNotReady))
+        );
+        ctx.set_metadata_slot_time(execute_after);
+        logger.logs.clear();
+        claim_eq!(contract_execute(&ctx, &mut host, &mut logger), Ok(()));
+        let parameter_bytes = to_bytes(&1u64);
+        ctx.set_parameter(&parameter_bytes);
+        claim_eq!(contract_execute(&ctx, &mut host, &mut logger), Ok(()));
+        claim_eq!(
+            logger.logs,
+            vec![
+                to_bytes(&BridgeEvent::ProposalExecuted(ProposalEvent { id: 0 })),
+                to_bytes(&BridgeEvent::CorrectiveMint(parameter.clone())),
+                to_bytes(&BridgeEvent::ProposalExecuted(ProposalEvent { id: 1 })),
+                to_bytes(&BridgeEvent::CorrectiveBurn(parameter.clone())),
+            ]
+        );
+
+        // A longer governance delay applies to corrections as well, and the
+        // operator is checked again when the burn is executed.
+        host.state_mut().governance_delay = MIN_CORRECTION_DELAY
+            .checked_add(Duration::from_millis(100))
+            .unwrap();
+        let parameter_bytes = to_bytes(&GovernanceAction::CorrectiveBurn(parameter));
+        ctx.set_parameter(&parameter_bytes);
+        claim_eq!(contract_propose(&ctx, &mut host, &mut logger), Ok(2));
+        host.setup_mock_entrypoint(
+            CIS2_ADDRESS,
+            OwnedEntrypointName::new_unchecked("operatorOf".into()),
+            operator_of(false),
+        );
+        let parameter_bytes = to_bytes(&2u64);
+        ctx.set_parameter(&parameter_bytes);
+        ctx.set_metadata_slot_time(execute_after.checked_add(MIN_CORRECTION_DELAY).unwrap());
+        claim_eq!(
+            contract_execute(&ctx, &mut host, &mut logger),
+            Err(ContractError::Custom(CustomContractError::ProposalNotReady))
+        );
+        ctx.set_metadata_slot_time(
+            execute_after
+                .checked_add(host.state().governance_delay)
+                .unwrap(),
+        );
+        claim_eq!(
+            contract_execute(&ctx, &mut host, &mut logger),
+            Err(ContractError::Custom(CustomContractError::NotOperator))
+        );
     }
+
     /// Test pausing the contract.
     #[concordium_test]
     fn test_pause() {
@@ -1579,14 +3407,22 @@ mod tests {
         let mut builder = TestStateBuilder::new();
         let state = initial_state(&mut builder);
         let mut host = TestHost::new(state, builder);
+        let mut logger = TestLogger::init();
         // Call the contract function.
-        let result: ContractResult<()> = contract_set_paused(&ctx, &mut host);
+        let result: ContractResult<()> = contract_set_paused(&ctx, &mut host, &mut logger);
 
         // Check the result.
         claim!(result.is_ok(), "Results in rejection");
 
         // Check contract is paused.
         claim_eq!(host.state().paused, true, "Smart contract should be paused");
+        claim_eq!(
+            logger.logs,
+            vec![to_bytes(&BridgeEvent::SetPaused(SetPausedEvent {
+                paused: true
+            }))],
+            "Pausing should be logged"
+        );
     }
 
     /// Test unpausing the contract.
@@ -1604,8 +3440,9 @@ mod tests {
         let mut builder = TestStateBuilder::new();
         let state = initial_state(&mut builder);
         let mut host = TestHost::new(state, builder);
+        let mut logger = TestLogger::init();
         // Call the contract function.
-        let result: ContractResult<()> = contract_set_paused(&ctx, &mut host);
+        let result: ContractResult<()> = contract_set_paused(&ctx, &mut host, &mut logger);
 
         // Check the result.
         claim!(result.is_ok(), "Results in rejection");
@@ -1618,7 +3455,7 @@ mod tests {
         ctx.set_parameter(&parameter_bytes);
 
         // Call the contract function.
-        let result: ContractResult<()> = contract_set_paused(&ctx, &mut host);
+        let result: ContractResult<()> = contract_set_paused(&ctx, &mut host, &mut logger);
 
         // Check the result.
         claim!(result.is_ok(), "Results in rejection");
@@ -1647,8 +3484,9 @@ mod tests {
         let mut builder = TestStateBuilder::new();
         let state = initial_state(&mut builder);
         let mut host = TestHost::new(state, builder);
+        let mut logger = TestLogger::init();
         // Call the contract function.
-        let result: ContractResult<()> = contract_set_paused(&ctx, &mut host);
+        let result: ContractResult<()> = contract_set_paused(&ctx, &mut host, &mut logger);
 
         // Check that invoke failed.
         claim_eq!(
@@ -1658,6 +3496,124 @@ mod tests {
         );
     }
 
+    /// Test that a pauser can pause, but not unpause, the contract.
+    #[concordium_test]
+    fn test_pauser_can_only_pause() {
+        // Set up the context.
+        let mut ctx = TestReceiveContext::empty();
+        ctx.set_sender(ADDRESS_1);
+
+        // Set up the state and host, with ADDRESS_1 as a pauser.
+        let mut builder = TestStateBuilder::new();
+        let mut state = initial_state(&mut builder);
+        state.grant_role(&ADDRESS_1, Roles::Pauser, &mut builder);
+        let mut host = TestHost::new(state, builder);
+        let mut logger = TestLogger::init();
+
+        // The pauser pauses the contract.
+        let parameter_bytes = to_bytes(&true);
+        ctx.set_parameter(&parameter_bytes);
+        let result: ContractResult<()> = contract_set_paused(&ctx, &mut host, &mut logger);
+        claim!(result.is_ok(), "Results in rejection");
+        claim_eq!(host.state().paused, true, "Smart contract should be paused");
+
+        // The pauser cannot unpause it.
+        let parameter_bytes = to_bytes(&false);
+        ctx.set_parameter(&parameter_bytes);
+        let result: ContractResult<()> = contract_set_paused(&ctx, &mut host, &mut logger);
+        claim_eq!(
+            result,
+            Err(ContractError::Unauthorized),
+            "Unpausing should fail because the sender is only a pauser"
+        );
+        claim_eq!(
+            host.state().paused,
+            true,
+            "Smart contract should stay paused"
+        );
+    }
+
+    /// Test that only a blocklister can change the blocklist, and that blocked
+    /// senders and recipients cannot withdraw.
+    #[concordium_test]
+    fn test_blocklist() {
+        let mut builder = TestStateBuilder::new();
+        let mut state = initial_state(&mut builder);
+        state.grant_role(&ADDRESS_1, Roles::Blocklister, &mut builder);
+        let mut host = TestHost::new(state, builder);
+        let mut logger = TestLogger::init();
+
+        // The admin is not a blocklister.
+        let parameter = SetBlockedParams {
+            address: BlockedAddress::Concordium(ADDRESS_2),
+            blocked: true,
+        };
+        let parameter_bytes = to_bytes(&parameter);
+        let mut ctx = TestReceiveContext::empty();
+        ctx.set_sender(ADDRESS_0);
+        ctx.set_parameter(&parameter_bytes);
+        let result: ContractResult<()> = contract_set_blocked(&ctx, &mut host, &mut logger);
+        claim_eq!(
+            result,
+            Err(ContractError::Unauthorized),
+            "Only a blocklister can block addresses"
+        );
+
+        // The blocklister blocks ADDRESS_2 and an Ethereum address.
+        ctx.set_sender(ADDRESS_1);
+        let result: ContractResult<()> = contract_set_blocked(&ctx, &mut host, &mut logger);
+        claim!(result.is_ok(), "The blocklister can block addresses");
+        claim_eq!(
+            logger.logs,
+            vec![to_bytes(&BridgeEvent::SetBlocked(parameter.clone()))],
+            "Incorrect event emitted"
+        );
+        let eth_parameter = SetBlockedParams {
+            address: BlockedAddress::Ethereum(ETH_WALLET_ADDRESS),
+            blocked: true,
+        };
+        let eth_parameter_bytes = to_bytes(&eth_parameter);
+        ctx.set_parameter(&eth_parameter_bytes);
+        let result: ContractResult<()> = contract_set_blocked(&ctx, &mut host, &mut logger);
+        claim!(result.is_ok(), "The blocklister can block addresses");
+
+        ctx.set_parameter(&parameter_bytes);
+        claim_eq!(contract_is_blocked(&ctx, &host), Ok(true));
+        ctx.set_parameter(&eth_parameter_bytes);
+        claim_eq!(contract_is_blocked(&ctx, &host), Ok(true));
+
+        // Neither a blocked sender, nor a blocked recipient can withdraw.
+        let withdrawals = [(ADDRESS_2, ETH_ADDRESS), (ADDRESS_0, ETH_WALLET_ADDRESS)];
+        for (sender, eth_address) in withdrawals {
+            let parameter_bytes = to_bytes(&WithdrawParams {
+                eth_address,
+                amount: token_amount(42),
+                token_id: TokenIdU64(0),
+                token: CIS2_ADDRESS,
+            });
+            let mut ctx = TestReceiveContext::empty();
+            ctx.set_sender(sender);
+            ctx.set_parameter(&parameter_bytes);
+            let result: ContractResult<()> =
+                contract_withdraw(&ctx, &mut host, Amount::zero(), &mut logger);
+            claim_eq!(
+                result,
+                Err(ContractError::Custom(CustomContractError::AddressBlocked)),
+                "Blocked addresses cannot withdraw"
+            );
+        }
+
+        // The blocklister unblocks ADDRESS_2.
+        let parameter_bytes = to_bytes(&SetBlockedParams {
+            address: BlockedAddress::Concordium(ADDRESS_2),
+            blocked: false,
+        });
+        ctx.set_parameter(&parameter_bytes);
+        let result: ContractResult<()> = contract_set_blocked(&ctx, &mut host, &mut logger);
+        claim!(result.is_ok(), "The blocklister can unblock addresses");
+        claim_eq!(contract_is_blocked(&ctx, &host), Ok(false));
+    }
+
     /// Test that one can NOT call non-admin state-mutative functions (wrap,
     /// unwrap, transfer, updateOperator) when the contract is paused.
     #[concordium_test]
@@ -1674,8 +3630,9 @@ mod tests {
         let mut builder = TestStateBuilder::new();
         let state = initial_state(&mut builder);
         let mut host = TestHost::new(state, builder);
+        let mut logger = TestLogger::init();
         // Call the contract function.
-        let result: ContractResult<()> = contract_set_paused(&ctx, &mut host);
+        let result: ContractResult<()> = contract_set_paused(&ctx, &mut host, &mut logger);
 
         // Check the result.
         claim!(result.is_ok(), "Results in rejection");
@@ -1683,8 +3640,6 @@ mod tests {
         // Check contract is paused.
         claim_eq!(host.state().paused, true, "Smart contract should be paused");
 
-        let mut logger = TestLogger::init();
-
         // Call the `transfer` function.
         let result: ContractResult<()> =
             contract_withdraw(&ctx, &mut host, Amount::from_ccd(100), &mut logger);
@@ -1707,6 +3662,463 @@ mod tests {
             "Update operator should fail because contract is paused"
         );
     }
+    /// Test that operations processed in order only move the watermark, and
+    /// out of order operations are kept until the gap is filled.
+    #[concordium_test]
+    fn test_processed_operations() {
+        let mut state_builder = TestStateBuilder::new();
+        let mut state = initial_state(&mut state_builder);
+
+        // The `StateSender` numbers its operations from 1.
+        claim_eq!(state.processed_watermark, 1);
+        state.set_operation(1);
+        state.set_operation(2);
+        claim_eq!(state.processed_watermark, 3);
+        claim!(state.processed_operations.is_empty());
+
+        state.set_operation(5);
+        state.set_operation(4);
+        claim_eq!(state.processed_watermark, 3);
+        claim!(!state.has_operation(3), "Operation 3 is not processed");
+        claim!(state.has_operation(4), "Operation 4 is processed");
+        claim!(state.has_operation(5), "Operation 5 is processed");
+
+        state.set_operation(3);
+        claim_eq!(state.processed_watermark, 4);
+        claim_eq!(state.compact_operations(MAX_COMPACTED_OPERATIONS), 2);
+        claim_eq!(state.processed_watermark, 6);
+        claim!(state.processed_operations.is_empty());
+        for op in 1..6 {
+            claim!(state.has_operation(op), "Operations below 6 are processed");
+        }
+        claim!(!state.has_operation(6), "Operation 6 is not processed");
+    }
+
+    /// Test that operations of the `StateSender` that are not state updates
+    /// are skipped by the `StateSyncer`, which moves the watermark past them.
+    #[concordium_test]
+    fn test_skip_operations() {
+        let mut logger = TestLogger::init();
+        let mut state_builder = TestStateBuilder::new();
+        let mut state = initial_state(&mut state_builder);
+        state.grant_role(&ADDRESS_1, Roles::StateSyncer, &mut state_builder);
+        // Operations 1 and 4 are token maps, 2 and 5 are withdrawals, 3 is a
+        // Merkle root and 6 is a deposit that is not yet processed.
+        state.set_operation(1);
+        state.set_operation(4);
+        let mut host = TestHost::new(state, state_builder);
+
+        let skip = |host: &mut TestHost<State<TestStateApi>>,
+                    logger: &mut TestLogger,
+                    sender: Address,
+                    ids: Vec<u64>| {
+            let parameter_bytes = to_bytes(&StateUpdate::Skip(SkipOperations { ids }));
+            let mut ctx = TestReceiveContext::empty();
+            ctx.set_sender(sender);
+            ctx.set_parameter(&parameter_bytes);
+            contract_receive_state_update(&ctx, host, logger)
+        };
+
+        claim_eq!(
+            skip(&mut host, &mut logger, ADDRESS_0, vec![2, 3]),
+            Err(ContractError::Unauthorized),
+            "Only the StateSyncer can skip operations"
+        );
+
+        claim!(skip(&mut host, &mut logger, ADDRESS_1, vec![2, 3]).is_ok());
+        claim_eq!(host.state().processed_watermark, 5);
+        claim!(skip(&mut host, &mut logger, ADDRESS_1, vec![4, 5]).is_ok());
+        claim_eq!(host.state().processed_watermark, 6);
+        claim!(host.state().processed_operations.is_empty());
+        claim!(!host.state().has_operation(6), "The deposit is not skipped");
+        claim!(logger.logs.is_empty(), "Skipping logs no events");
+
+        claim_eq!(
+            skip(&mut host, &mut logger, ADDRESS_1, vec![2, 5]),
+            Err(ContractError::Custom(
+                CustomContractError::OperationAlreadyProcessed
+            )),
+            "Skipping only processed operations is a duplicate"
+        );
+    }
+
+    /// Test that filling a gap after many operations were processed out of
+    /// order only moves the watermark past a bounded number of them per state
+    /// update, and that the rest are removed by the following updates.
+    #[concordium_test]
+    fn test_fill_gap_after_many_operations() {
+        let mut logger = TestLogger::init();
+        let mut state_builder = TestStateBuilder::new();
+        let mut state = initial_state(&mut state_builder);
+        state.grant_role(&ADDRESS_1, Roles::StateSyncer, &mut state_builder);
+        let mut host = TestHost::new(state, state_builder);
+
+        let skip =
+            |host: &mut TestHost<State<TestStateApi>>, logger: &mut TestLogger, ids: Vec<u64>| {
+                let parameter_bytes = to_bytes(&StateUpdate::Skip(SkipOperations { ids }));
+                let mut ctx = TestReceiveContext::empty();
+                ctx.set_sender(ADDRESS_1);
+                ctx.set_parameter(&parameter_bytes);
+                contract_receive_state_update(&ctx, host, logger)
+            };
+
+        // Operation 1, e.g., a quarantined deposit, is processed last.
+        let num_operations = 5 * MAX_COMPACTED_OPERATIONS;
+        for id in 2..=num_operations + 1 {
+            claim!(skip(&mut host, &mut logger, vec![id]).is_ok());
+        }
+        claim_eq!(host.state().processed_watermark, 1);
+        claim!(skip(&mut host, &mut logger, vec![1]).is_ok());
+        claim_eq!(
+            host.state().processed_watermark,
+            2 + MAX_COMPACTED_OPERATIONS,
+            "The update filling the gap removes a bounded number of operations"
+        );
+        for id in 1..=num_operations + 1 {
+            claim!(host.state().has_operation(id), "The operation is processed");
+        }
+
+        // The relayer keeps sending later operations, in order, and each update
+        // moves the watermark further, until it has caught up.
+        let mut next = num_operations + 2;
+        while host.state().processed_watermark < next {
+            let watermark = host.state().processed_watermark;
+            claim!(skip(&mut host, &mut logger, vec![next]).is_ok());
+            next += 1;
+            claim_eq!(
+                host.state().processed_watermark,
+                next.min(watermark + MAX_COMPACTED_OPERATIONS)
+            );
+        }
+        claim_eq!(host.state().processed_watermark, next);
+        claim!(host.state().processed_operations.iter().next().is_none());
+    }
+
+    /// Test that the state as first deployed is migrated to the current
+    /// layout, keeping its contents, and that other layouts are rejected.
+    #[concordium_test]
+    fn test_migrate_state_v0() {
+        let mut state_api = TestStateApi::new();
+        let mut state_builder = StateBuilder::open(state_api.clone());
+        let mut roles = state_builder.new_map();
+        let mut admin = AddressRoleState {
+            roles: state_builder.new_set(),
+        };
+        admin.roles.insert(Roles::Admin);
+        roles.insert(ADDRESS_0, admin);
+        let mut root_mapping = state_builder.new_map();
+        root_mapping.insert(ETH_ADDRESS, CIS2_ADDRESS);
+        let mut child_mapping = state_builder.new_map();
+        child_mapping.insert(CIS2_ADDRESS, ETH_ADDRESS);
+        let mut processed_operations = state_builder.new_set();
+        for op in [1, 2, 4] {
+            processed_operations.insert(op);
+        }
+        let v0 = StateV0 {
+            paused: true,
+            roles,
+            root_mapping,
+            child_mapping,
+            emit_event_index: 9,
+            withdraw_fee: Amount::from_micro_ccd(4),
+            treasurer_address: TREASURY_ACCOUNT,
+            processed_operations,
+        };
+        let mut root = state_api
+            .create_entry(&[])
+            .expect_report("Root entry is created");
+        v0.serial(&mut root).expect_report("State is written");
+
+        let state = State::read(&state_api, &mut root, &mut state_builder)
+            .expect_report("The state is migrated");
+        claim!(state.paused);
+        claim!(state.has_role(&ADDRESS_0, Roles::Admin));
+        claim_eq!(
+            state.root_mapping.get(&ETH_ADDRESS).map(|c| *c),
+            Some(CIS2_ADDRESS)
+        );
+        claim_eq!(
+            state.child_mapping.get(&CIS2_ADDRESS).map(|e| *e),
+            Some(ETH_ADDRESS)
+        );
+        claim_eq!(state.emit_event_index, 9);
+        claim_eq!(state.withdraw_fee, Amount::from_micro_ccd(4));
+        claim_eq!(state.treasurer_address, TREASURY_ACCOUNT);
+        claim_eq!(state.claim_fee, Amount::zero());
+        for op in [1, 2, 4] {
+            claim!(state.has_operation(op), "The operation is still processed");
+        }
+        claim!(!state.has_operation(3), "Operation 3 is not processed");
+        claim_eq!(state.processed_watermark, 1);
+
+        // The migrated state is read back as is.
+        root.seek(SeekFrom::Start(0))
+            .expect_report("Seek to the start");
+        state.serial(&mut root).expect_report("State is written");
+        claim!(State::read(&state_api, &mut root, &mut state_builder).is_ok());
+
+        // Trailing bytes match none of the layouts.
+        0u8.serial(&mut root).expect_report("Byte is written");
+        claim_eq!(
+            State::read(&state_api, &mut root, &mut state_builder).err(),
+            Some(ContractError::Custom(
+                CustomContractError::UnknownStateLayout
+            ))
+        );
+    }
+
+    /// The state as first deployed, with nothing configured.
+    fn empty_state_v0<S: HasStateApi>(state_builder: &mut StateBuilder<S>) -> StateV0<S> {
+        StateV0 {
+            paused: false,
+            roles: state_builder.new_map(),
+            root_mapping: state_builder.new_map(),
+            child_mapping: state_builder.new_map(),
+            emit_event_index: 0,
+            withdraw_fee: Amount::zero(),
+            treasurer_address: TREASURY_ACCOUNT,
+            processed_operations: state_builder.new_set(),
+        }
+    }
+
+    /// Test that the layouts between the first deployment and the current one
+    /// are migrated, keeping the fields that they have.
+    #[concordium_test]
+    fn test_migrate_intermediate_states() {
+        let mut state_api = TestStateApi::new();
+        let mut state_builder = StateBuilder::open(state_api.clone());
+        let mut root = state_api
+            .create_entry(&[])
+            .expect_report("Root entry is created");
+
+        let v2 = StateV2 {
+            v1: StateV1 {
+                v0: empty_state_v0(&mut state_builder),
+                claim_fee: Amount::from_micro_ccd(7),
+            },
+            processed_watermark: 3,
+        };
+        v2.serial(&mut root).expect_report("State is written");
+        let state = State::read(&state_api, &mut root, &mut state_builder)
+            .expect_report("The state is migrated");
+        claim_eq!(state.claim_fee, Amount::from_micro_ccd(7));
+        claim_eq!(state.processed_watermark, 3);
+        claim!(!state.has_governance_delay());
+
+        let mut blocklist = state_builder.new_set();
+        blocklist.insert(BlockedAddress::Concordium(ADDRESS_2));
+        let v4 = StateV4 {
+            v3: StateV3 {
+                v2: StateV2 {
+                    v1: StateV1 {
+                        v0: empty_state_v0(&mut state_builder),
+                        claim_fee: Amount::zero(),
+                    },
+                    processed_watermark: 5,
+                },
+                governance_delay: Duration::from_millis(10),
+                next_proposal_id: 2,
+                proposals: state_builder.new_map(),
+                admin_transfer: None,
+            },
+            blocklist,
+        };
+        root.seek(SeekFrom::Start(0))
+            .expect_report("Seek to the start");
+        v4.serial(&mut root).expect_report("State is written");
+        let state = State::read(&state_api, &mut root, &mut state_builder)
+            .expect_report("The state is migrated");
+        claim_eq!(state.processed_watermark, 5);
+        claim_eq!(state.governance_delay, Duration::from_millis(10));
+        claim_eq!(state.next_proposal_id, 2);
+        claim!(state.is_blocked(&BlockedAddress::Concordium(ADDRESS_2)));
+        claim!(state.token_fees.is_empty());
+    }
+
+    /// Test that compacting a state where every operation is kept in the set,
+    /// as in earlier versions of the contract, is done in batches.
+    #[concordium_test]
+    fn test_compact_operations() {
+        let mut state_builder = TestStateBuilder::new();
+        let mut state = initial_state(&mut state_builder);
+        for op in [1, 2, 3, 4, 6] {
+            state.processed_operations.insert(op);
+        }
+
+        claim_eq!(state.compact_operations(3), 3);
+        claim_eq!(state.processed_watermark, 4);
+        claim_eq!(state.compact_operations(3), 1);
+        claim_eq!(state.processed_watermark, 5);
+        claim_eq!(state.compact_operations(3), 0);
+        claim_eq!(
+            state
+                .processed_operations
+                .iter()
+                .map(|op| *op)
+                .collect::<Vec<_>>(),
+            vec![6]
+        );
+        for op in [1, 2, 3, 4, 6] {
+            claim!(state.has_operation(op), "The operation is still processed");
+        }
+        claim!(!state.has_operation(5), "Operation 5 is not processed");
+    }
+
+    /// Test that a proposal is executed after the governance delay, and that
+    /// the admin cannot configure the contract directly once there is a delay.
+    #[concordium_test]
+    fn test_governance() {
+        let mut logger = TestLogger::init();
+        let mut builder = TestStateBuilder::new();
+        let state = initial_state(&mut builder);
+        let mut host = TestHost::new(state, builder);
+        let mut ctx = TestReceiveContext::empty();
+        ctx.set_sender(ADDRESS_0);
+        ctx.set_metadata_slot_time(Timestamp::from_timestamp_millis(1_000));
+
+        // Without a delay the proposal can be executed right away.
+        let parameter_bytes = to_bytes(&GovernanceAction::SetGovernanceDelay(
+            Duration::from_millis(100),
+        ));
+        ctx.set_parameter(&parameter_bytes);
+        claim_eq!(contract_propose(&ctx, &mut host, &mut logger), Ok(0));
+        let parameter_bytes = to_bytes(&0u64);
+        ctx.set_parameter(&parameter_bytes);
+        claim_eq!(contract_execute(&ctx, &mut host, &mut logger), Ok(()));
+        claim_eq!(host.state().governance_delay, Duration::from_millis(100));
+        claim_eq!(
+            contract_execute(&ctx, &mut host, &mut logger),
+            Err(ContractError::Custom(CustomContractError::ProposalNotFound)),
+            "A proposal is executed once"
+        );
+
+        // The fee can no longer be set directly.
+        let parameter_bytes = to_bytes(&Amount::from_micro_ccd(7));
+        ctx.set_parameter(&parameter_bytes);
+        claim_eq!(
+            contract_set_withdraw_fee(&ctx, &mut host, &mut logger),
+            Err(ContractError::Custom(
+                CustomContractError::GovernanceDelayActive
+            ))
+        );
+
+        // Only admins propose.
+        let parameter_bytes =
+            to_bytes(&GovernanceAction::SetWithdrawFee(Amount::from_micro_ccd(7)));
+        ctx.set_parameter(&parameter_bytes);
+        ctx.set_sender(ADDRESS_1);
+        claim_eq!(
+            contract_propose(&ctx, &mut host, &mut logger),
+            Err(ContractError::Unauthorized)
+        );
+        ctx.set_sender(ADDRESS_0);
+        logger.logs.clear();
+        claim_eq!(contract_propose(&ctx, &mut host, &mut logger), Ok(1));
+        claim_eq!(
+            logger.logs,
+            vec![to_bytes(&BridgeEvent::ProposalCreated(
+                ProposalCreatedEvent {
+                    id: 1,
+                    proposer: ADDRESS_0,
+                    action: GovernanceAction::SetWithdrawFee(Amount::from_micro_ccd(7)),
+                    execute_after: Timestamp::from_timestamp_millis(1_100),
+                }
+            ))]
+        );
+
+        // The proposal is executed after the delay.
+        let parameter_bytes = to_bytes(&1u64);
+        ctx.set_parameter(&parameter_bytes);
+        ctx.set_metadata_slot_time(Timestamp::from_timestamp_millis(1_099));
+        claim_eq!(
+            contract_execute(&ctx, &mut host, &mut logger),
+            Err(ContractError::Custom(CustomContractError::ProposalNotReady))
+        );
+        ctx.set_metadata_slot_time(Timestamp::from_timestamp_millis(1_100));
+        claim_eq!(contract_execute(&ctx, &mut host, &mut logger), Ok(()));
+        claim_eq!(host.state().withdraw_fee, Amount::from_micro_ccd(7));
+
+        // A cancelled proposal cannot be executed.
+        let parameter_bytes = to_bytes(&GovernanceAction::SetTreasurer(ACCOUNT_1));
+        ctx.set_parameter(&parameter_bytes);
+        claim_eq!(contract_propose(&ctx, &mut host, &mut logger), Ok(2));
+        let parameter_bytes = to_bytes(&2u64);
+        ctx.set_parameter(&parameter_bytes);
+        claim_eq!(contract_cancel(&ctx, &mut host, &mut logger), Ok(()));
+        ctx.set_metadata_slot_time(Timestamp::from_timestamp_millis(10_000));
+        claim_eq!(
+            contract_execute(&ctx, &mut host, &mut logger),
+            Err(ContractError::Custom(CustomContractError::ProposalNotFound))
+        );
+        claim_eq!(host.state().treasurer_address, ACCOUNT_2);
+    }
+
+    /// Test the two-step transfer of the `Admin` role.
+    #[concordium_test]
+    fn test_admin_transfer() {
+        let mut logger = TestLogger::init();
+        let mut builder = TestStateBuilder::new();
+        let state = initial_state(&mut builder);
+        let mut host = TestHost::new(state, builder);
+        let mut ctx = TestReceiveContext::empty();
+        ctx.set_sender(ADDRESS_0);
+        ctx.set_metadata_slot_time(Timestamp::from_timestamp_millis(1_000));
+
+        let parameter_bytes = to_bytes(&GovernanceAction::TransferAdmin(ADDRESS_1));
+        ctx.set_parameter(&parameter_bytes);
+        claim_eq!(contract_propose(&ctx, &mut host, &mut logger), Ok(0));
+
+        // Nothing to accept before the transfer is executed.
+        ctx.set_sender(ADDRESS_1);
+        claim_eq!(
+            contract_accept_admin(&ctx, &mut host, &mut logger),
+            Err(ContractError::Custom(CustomContractError::NoAdminTransfer))
+        );
+
+        ctx.set_sender(ADDRESS_0);
+        let parameter_bytes = to_bytes(&0u64);
+        ctx.set_parameter(&parameter_bytes);
+        claim_eq!(contract_execute(&ctx, &mut host, &mut logger), Ok(()));
+        claim!(
+            host.state().has_role(&ADDRESS_0, Roles::Admin),
+            "The role is kept until the transfer is accepted"
+        );
+
+        // Only the new admin can accept.
+        ctx.set_sender(ADDRESS_2);
+        claim_eq!(
+            contract_accept_admin(&ctx, &mut host, &mut logger),
+            Err(ContractError::Custom(CustomContractError::NoAdminTransfer))
+        );
+        ctx.set_sender(ADDRESS_1);
+        logger.logs.clear();
+        claim_eq!(contract_accept_admin(&ctx, &mut host, &mut logger), Ok(()));
+        claim!(host.state().has_role(&ADDRESS_1, Roles::Admin));
+        claim!(!host.state().has_role(&ADDRESS_0, Roles::Admin));
+        claim_eq!(
+            logger.logs,
+            vec![
+                to_bytes(&BridgeEvent::AdminTransferAccepted(AdminTransferEvent {
+                    from: ADDRESS_0,
+                    to: ADDRESS_1,
+                })),
+                to_bytes(&BridgeEvent::GrantRole(GrantRoleEvent {
+                    address: ADDRESS_1,
+                    role: Roles::Admin,
+                })),
+                to_bytes(&BridgeEvent::RevokeRole(RevokeRoleEvent {
+                    address: ADDRESS_0,
+                    role: Roles::Admin,
+                })),
+            ]
+        );
+        claim_eq!(
+            contract_accept_admin(&ctx, &mut host, &mut logger),
+            Err(ContractError::Custom(CustomContractError::NoAdminTransfer)),
+            "A transfer is accepted once"
+        );
+    }
+
     #[concordium_test]
     fn test_upgradability() {
         // Setup the context
@@ -1733,10 +4145,17 @@ mod tests {
         let mut host = TestHost::new(state, builder);
         host.setup_mock_upgrade(new_module_ref, Ok(()));
         host.setup_mock_entrypoint(self_address, migration_entrypoint, MockFn::returning_ok(()));
+        let mut logger = TestLogger::init();
 
-        let result: ContractResult<()> = contract_upgrade(&ctx, &mut host);
+        let result: ContractResult<()> = contract_upgrade(&ctx, &mut host, &mut logger);
 
         claim_eq!(result, Ok(()));
+        claim_eq!(
+            logger.logs,
+            vec![to_bytes(&BridgeEvent::Upgrade(UpgradeEvent {
+                module: new_module_ref,
+            }))]
+        );
     }
 
     #[concordium_test]
@@ -1760,8 +4179,9 @@ mod tests {
         let state = initial_state(&mut builder);
         let mut host = TestHost::new(state, builder);
         host.setup_mock_upgrade(new_module_ref, Err(UpgradeError::MissingModule));
+        let mut logger = TestLogger::init();
 
-        let result: ContractResult<()> = contract_upgrade(&ctx, &mut host);
+        let result: ContractResult<()> = contract_upgrade(&ctx, &mut host, &mut logger);
 
         claim_eq!(
             result,
@@ -1770,4 +4190,77 @@ mod tests {
             ))
         );
     }
+
+    /// Test that the reject codes of the contract match the error codes in
+    /// `bridge-types`, which the relayer relies on.
+    #[concordium_test]
+    fn test_error_codes() {
+        use bridge_types::error_codes::*;
+        let errors = vec![
+            (CustomContractError::ParseParams, PARSE_PARAMS),
+            (CustomContractError::LogFull, LOG_FULL),
+            (CustomContractError::LogMalformed, LOG_MALFORMED),
+            (CustomContractError::ContractPaused, CONTRACT_PAUSED),
+            (
+                CustomContractError::InvokeContractError,
+                INVOKE_CONTRACT_ERROR,
+            ),
+            (
+                CustomContractError::InvokeTransferError,
+                INVOKE_TRANSFER_ERROR,
+            ),
+            (CustomContractError::TokenNotMapped, TOKEN_NOT_MAPPED),
+            (CustomContractError::RoleNotAssigned, ROLE_NOT_ASSIGNED),
+            (CustomContractError::WithdrawFeeTooLow, WITHDRAW_FEE_TOO_LOW),
+            (
+                CustomContractError::OperationAlreadyProcessed,
+                OPERATION_ALREADY_PROCESSED,
+            ),
+            (
+                CustomContractError::FailedUpgradeMissingModule,
+                FAILED_UPGRADE_MISSING_MODULE,
+            ),
+            (
+                CustomContractError::FailedUpgradeMissingContract,
+                FAILED_UPGRADE_MISSING_CONTRACT,
+            ),
+            (
+                CustomContractError::FailedUpgradeUnsupportedModuleVersion,
+                FAILED_UPGRADE_UNSUPPORTED_MODULE_VERSION,
+            ),
+            (
+                CustomContractError::OnlyAccountsCanWithdraw,
+                ONLY_ACCOUNTS_CAN_WITHDRAW,
+            ),
+            (CustomContractError::ClaimFeeTooLow, CLAIM_FEE_TOO_LOW),
+            (
+                CustomContractError::GovernanceDelayActive,
+                GOVERNANCE_DELAY_ACTIVE,
+            ),
+            (CustomContractError::ProposalNotFound, PROPOSAL_NOT_FOUND),
+            (CustomContractError::ProposalNotReady, PROPOSAL_NOT_READY),
+            (CustomContractError::NoAdminTransfer, NO_ADMIN_TRANSFER),
+            (CustomContractError::AddressBlocked, ADDRESS_BLOCKED),
+            (
+                CustomContractError::WithdrawAmountTooLow,
+                WITHDRAW_AMOUNT_TOO_LOW,
+            ),
+            (CustomContractError::InvalidTokenFee, INVALID_TOKEN_FEE),
+            (
+                CustomContractError::UnknownStateLayout,
+                UNKNOWN_STATE_LAYOUT,
+            ),
+            (CustomContractError::NotOperator, NOT_OPERATOR),
+        ];
+        for (error, code) in errors {
+            claim_eq!(
+                Reject::from(ContractError::Custom(error)).error_code.get(),
+                code
+            );
+        }
+        claim_eq!(
+            Reject::from(ContractError::Unauthorized).error_code.get(),
+            UNAUTHORIZED
+        );
+    }
 }
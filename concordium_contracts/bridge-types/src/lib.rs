@@ -0,0 +1,1115 @@
+//! Types of the `bridge-manager` contract that are shared between the
+//! contract, the relayer and the deployment scripts.
+//!
+//! The crate only depends on `concordium-contracts-common`, so that it can be
+//! used both from smart contracts (via `concordium-std`) and off-chain (via
+//! the Rust SDK). Serialization is derived with
+//! `concordium-contracts-common-derive`, whose macros refer to the items of
+//! `concordium-contracts-common` through a `concordium_std` path. That path is
+//! provided by a private `concordium_std` module.
+//!
+//! Token amounts and token ids are type parameters. The contract uses
+//! `TokenAmountU256` and `TokenIdU64`, whereas off-chain users typically use
+//! the arbitrary precision `TokenAmount` and `TokenId` of the SDK. Both have
+//! the same serialization.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use alloc::{string::ToString, vec, vec::Vec};
+use concordium_contracts_common::{
+    schema::{Fields, SchemaType, SizeLength, Type},
+    *,
+};
+use concordium_contracts_common_derive::{SchemaType, Serial, Serialize};
+
+/// The items that the derive macros refer to as `concordium_std`.
+mod concordium_std {
+    pub use alloc::{string::String, vec::Vec};
+    pub use concordium_contracts_common::*;
+}
+
+/// Reject reasons of the `bridge-manager` contract.
+///
+/// These are the error codes of the `CustomContractError` of the contract,
+/// which are determined by the order of its variants.
+pub mod error_codes {
+    /// Failed parsing the parameter.
+    pub const PARSE_PARAMS: i32 = -1;
+    /// Failed logging: Log is full.
+    pub const LOG_FULL: i32 = -2;
+    /// Failed logging: Log is malformed.
+    pub const LOG_MALFORMED: i32 = -3;
+    /// Contract is paused.
+    pub const CONTRACT_PAUSED: i32 = -4;
+    /// Failed to invoke a contract.
+    pub const INVOKE_CONTRACT_ERROR: i32 = -5;
+    /// Failed to invoke a transfer.
+    pub const INVOKE_TRANSFER_ERROR: i32 = -6;
+    /// The root token of a deposit is not mapped.
+    pub const TOKEN_NOT_MAPPED: i32 = -7;
+    /// The role to remove is not assigned.
+    pub const ROLE_NOT_ASSIGNED: i32 = -8;
+    /// The attached amount is smaller than the withdraw fee.
+    pub const WITHDRAW_FEE_TOO_LOW: i32 = -9;
+    /// The operation of a state update was already processed.
+    pub const OPERATION_ALREADY_PROCESSED: i32 = -10;
+    /// Upgrade failed because the new module does not exist.
+    pub const FAILED_UPGRADE_MISSING_MODULE: i32 = -11;
+    /// Upgrade failed because the new module does not contain a contract with
+    /// a matching name.
+    pub const FAILED_UPGRADE_MISSING_CONTRACT: i32 = -12;
+    /// Upgrade failed because the smart contract version of the module is not
+    /// supported.
+    pub const FAILED_UPGRADE_UNSUPPORTED_MODULE_VERSION: i32 = -13;
+    /// Withdrawals can only be made by accounts.
+    pub const ONLY_ACCOUNTS_CAN_WITHDRAW: i32 = -14;
+    /// The attached amount is smaller than the withdraw and claim fees.
+    pub const CLAIM_FEE_TOO_LOW: i32 = -15;
+    /// The action must be proposed and executed after the governance delay.
+    pub const GOVERNANCE_DELAY_ACTIVE: i32 = -16;
+    /// There is no proposal with the given id.
+    pub const PROPOSAL_NOT_FOUND: i32 = -17;
+    /// The governance delay of the proposal has not passed yet.
+    pub const PROPOSAL_NOT_READY: i32 = -18;
+    /// There is no admin transfer to the sender to accept.
+    pub const NO_ADMIN_TRANSFER: i32 = -19;
+    /// The sender or the recipient of a withdrawal is on the blocklist.
+    pub const ADDRESS_BLOCKED: i32 = -20;
+    /// The withdrawn amount is smaller than the fee of the token.
+    pub const WITHDRAW_AMOUNT_TOO_LOW: i32 = -21;
+    /// The fee schedule of a token has more than 10000 basis points.
+    pub const INVALID_TOKEN_FEE: i32 = -22;
+    /// The state has none of the layouts of the current or earlier versions
+    /// of the contract.
+    pub const UNKNOWN_STATE_LAYOUT: i32 = -23;
+    /// A corrective burn is from an address that does not have the bridge
+    /// manager as an operator.
+    pub const NOT_OPERATOR: i32 = -24;
+    /// The sender is not authorized. This is the `Unauthorized` error of
+    /// CIS-2.
+    pub const UNAUTHORIZED: i32 = -42000003;
+}
+
+/// An address on Ethereum.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+pub struct EthAddress {
+    pub eth_address: [u8; 20],
+}
+
+impl From<[u8; 20]> for EthAddress {
+    fn from(eth_address: [u8; 20]) -> Self { Self { eth_address } }
+}
+
+impl From<EthAddress> for [u8; 20] {
+    fn from(address: EthAddress) -> Self { address.eth_address }
+}
+
+/// The address is displayed as a byte array, i.e., in hex, in the schema.
+impl SchemaType for EthAddress {
+    fn get_type() -> Type { Type::ByteArray(20) }
+}
+
+/// Roles of the `bridge-manager` contract.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, SchemaType)]
+pub enum Roles {
+    /// Can grant and revoke roles, and configure the contract.
+    Admin,
+    /// Can add and remove token mappings.
+    Mapper,
+    /// Can send state updates. This role is played by the relayer.
+    StateSyncer,
+    /// Can pause, but not unpause, the contract. This role is meant for
+    /// automated monitoring, e.g., the relayer's guardian.
+    Pauser,
+    /// Can add addresses to and remove addresses from the blocklist.
+    Blocklister,
+}
+
+/// The parameter of the `grantRole` and `removeRole` entrypoints.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, SchemaType)]
+pub struct GrantRoleParams {
+    pub address: Address,
+    pub role:    Roles,
+}
+
+/// The parameter of the `removeRole` entrypoint.
+pub type RemoveRoleParams = GrantRoleParams;
+
+/// The parameter of the `upgrade` entrypoint. Takes the new module and
+/// optionally an entrypoint to call in the new module after triggering the
+/// upgrade. The upgrade is reverted if the entrypoint fails. This is useful
+/// for doing migration in the same transaction triggering the upgrade.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, SchemaType)]
+pub struct UpgradeParams {
+    /// The new module reference.
+    pub module:  ModuleReference,
+    /// Optional entrypoint to call in the new module after upgrade.
+    pub migrate: Option<(OwnedEntrypointName, OwnedParameter)>,
+}
+
+/// An admin action of the `bridge-manager`. It is the parameter of the
+/// `propose` entrypoint, and is executed by `execute` once the governance
+/// delay has passed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, SchemaType)]
+pub enum GovernanceAction<A> {
+    /// Set the fee for withdrawing tokens.
+    SetWithdrawFee(Amount),
+    /// Set the additional fee for the relayer to claim a withdrawal.
+    SetClaimFee(Amount),
+    /// Set the account receiving the fees.
+    SetTreasurer(AccountAddress),
+    /// Grant a role.
+    GrantRole(GrantRoleParams),
+    /// Remove a role.
+    RemoveRole(RemoveRoleParams),
+    /// Upgrade the contract.
+    Upgrade(UpgradeParams),
+    /// Set the time between proposing and executing an action.
+    SetGovernanceDelay(Duration),
+    /// Transfer the `Admin` role of the proposer to the given address, once
+    /// the address accepts it with `acceptAdmin`.
+    TransferAdmin(Address),
+    /// Set the fee for withdrawing a token, which is taken in the token.
+    SetTokenFee(SetTokenFeeParams<A>),
+    /// Mint tokens to correct the supply after an incident. It can be executed
+    /// after at least the [`MIN_CORRECTION_DELAY`].
+    CorrectiveMint(CorrectionParams<A>),
+    /// Burn tokens to correct the supply after an incident. It can be executed
+    /// after at least the [`MIN_CORRECTION_DELAY`].
+    CorrectiveBurn(CorrectionParams<A>),
+}
+
+/// A contract entrypoint to notify after minting a deposit to a contract, and
+/// the data to pass to it. The entrypoint is invoked with the CIS-2
+/// `OnReceivingCis2Params` of the token, like on a transfer.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DepositHook {
+    /// The entrypoint of the receiving contract.
+    pub entrypoint: OwnedEntrypointName,
+    /// Data passed on to the entrypoint. At most `u16::MAX` bytes.
+    #[concordium(size_length = 2)]
+    pub data:       Vec<u8>,
+}
+
+/// The data is displayed as a byte list, i.e., in hex, in the schema.
+impl SchemaType for DepositHook {
+    fn get_type() -> Type {
+        Type::Struct(Fields::Named(vec![
+            ("entrypoint".to_string(), OwnedEntrypointName::get_type()),
+            ("data".to_string(), Type::ByteList(SizeLength::U16)),
+        ]))
+    }
+}
+
+/// Mint tokens in response to a deposit on Ethereum.
+#[derive(Debug, Clone, PartialEq, Eq, Serial, SchemaType)]
+pub struct DepositOperation<A, T> {
+    /// Id of the operation emitted by the `StateSender` on Ethereum.
+    pub id:       u64,
+    /// The address to which the deposit should be made.
+    pub user:     Address,
+    /// Address of the root token on Ethereum.
+    pub root:     EthAddress,
+    /// Amount to be minted.
+    pub amount:   A,
+    /// Id of the token on Concordium.
+    pub token_id: T,
+    /// Entrypoint to invoke when `user` is a contract. The tokens are minted
+    /// regardless of whether the entrypoint accepts them.
+    pub hook:     Option<DepositHook>,
+}
+
+/// Deserialization is not derived, since operations sent before hooks were
+/// introduced have no `hook` field.
+impl<A: Deserial, T: Deserial> Deserial for DepositOperation<A, T> {
+    fn deserial<R: Read>(source: &mut R) -> ParseResult<Self> {
+        Ok(Self {
+            id:       source.get()?,
+            user:     source.get()?,
+            root:     source.get()?,
+            amount:   source.get()?,
+            token_id: source.get()?,
+            hook:     deserial_trailing_option(source)?,
+        })
+    }
+}
+
+/// Deserialize an optional field that was added as the last field of a type,
+/// such as the hook of a deposit. Values serialized before the field was
+/// introduced end right before it, so the end of the input is read as `None`.
+fn deserial_trailing_option<R: Read, V: Deserial>(source: &mut R) -> ParseResult<Option<V>> {
+    let mut tag = [0u8; 1];
+    match source.read(&mut tag) {
+        Ok(0) => Ok(None),
+        Ok(_) => match tag[0] {
+            0 => Ok(None),
+            1 => Ok(Some(source.get()?)),
+            _ => Err(ParseError::default()),
+        },
+        Err(_) => Err(ParseError::default()),
+    }
+}
+
+/// Map a token on Ethereum to a token on Concordium.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, SchemaType)]
+pub struct TokenMapOperation {
+    /// Id of the operation emitted by the `StateSender` on Ethereum.
+    pub id:    u64,
+    /// Address of the root token on Ethereum.
+    pub root:  EthAddress,
+    /// Address of the mapped token on Concordium.
+    pub child: ContractAddress,
+}
+
+/// Mark operations of the `StateSender` that are not state updates of the
+/// bridge manager, e.g., withdrawals and Merkle roots, as processed. The
+/// operations share their ids with the state updates, so the watermark of the
+/// processed operations only moves past them once they are skipped.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, SchemaType)]
+pub struct SkipOperations {
+    /// Ids of the operations emitted by the `StateSender` on Ethereum.
+    pub ids: Vec<u64>,
+}
+
+/// The parameter of the `receiveStateUpdate` entrypoint.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, SchemaType)]
+pub enum StateUpdate<A, T> {
+    /// Deposit an amount of a mapped token.
+    Deposit(DepositOperation<A, T>),
+    /// Add a new token mapping.
+    TokenMap(TokenMapOperation),
+    /// Skip operations that are not state updates.
+    Skip(SkipOperations),
+}
+
+/// A new token was mapped.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, SchemaType)]
+pub struct TokenMapEvent {
+    /// Id of the operation emitted by Ethereum. Used to deduplicate them.
+    pub id:    u64,
+    /// Address of the original token on Ethereum.
+    pub root:  EthAddress,
+    /// Address of the mapped token on Concordium.
+    pub child: ContractAddress,
+}
+
+/// Tokens were minted in response to a deposit on Ethereum.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, SchemaType)]
+pub struct DepositEvent<A, T> {
+    /// Id of the operation emitted by Ethereum. Used to deduplicate them.
+    pub id:       u64,
+    /// Address of the child token that was minted.
+    pub contract: ContractAddress,
+    /// Amount that was minted.
+    pub amount:   A,
+    /// Id of the token on Concordium.
+    pub token_id: T,
+}
+
+/// Tokens were burned to be withdrawn on Ethereum.
+#[derive(Debug, Clone, PartialEq, Eq, Serial, SchemaType)]
+pub struct WithdrawEvent<A, T> {
+    /// Index of the event emitted by the bridge manager. It is called `id` in
+    /// the schema, as in earlier versions of the contract.
+    #[concordium(rename = "id")]
+    pub event_index: u64,
+    /// Address of the child token that is to be withdrawn.
+    pub contract:    ContractAddress,
+    /// Amount to be withdrawn, after the fee of the token is deducted. This
+    /// is the amount released on Ethereum. The full amount, including the
+    /// fee, was burned.
+    pub amount:      A,
+    /// Address that originated the withdrawal.
+    pub ccd_address: Address,
+    /// The recipient of the withdrawal on Ethereum.
+    pub eth_address: EthAddress,
+    /// Id of the token on Concordium.
+    pub token_id:    T,
+    /// The fee in the token that was minted to the treasurer. `None` for
+    /// events logged before fees in tokens were introduced.
+    pub fee:         Option<A>,
+}
+
+/// Deserialization is not derived, since events logged before fees in tokens
+/// were introduced have no `fee` field.
+impl<A: Deserial, T: Deserial> Deserial for WithdrawEvent<A, T> {
+    fn deserial<R: Read>(source: &mut R) -> ParseResult<Self> {
+        Ok(Self {
+            event_index: source.get()?,
+            contract:    source.get()?,
+            amount:      source.get()?,
+            ccd_address: source.get()?,
+            eth_address: source.get()?,
+            token_id:    source.get()?,
+            fee:         deserial_trailing_option(source)?,
+        })
+    }
+}
+
+/// Emitted together with a [`WithdrawEvent`] when the user paid the claim
+/// fee, and thus requested the relayer to complete the withdrawal on Ethereum.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, SchemaType)]
+pub struct ClaimRequestedEvent {
+    /// Index of the withdraw event that is to be claimed. It is called `id`
+    /// in the schema, like the index of the withdraw event.
+    #[concordium(rename = "id")]
+    pub event_index: u64,
+    /// The part of the attached amount that was paid for the claim.
+    pub fee:         Amount,
+}
+
+/// A role was granted to an address.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, SchemaType)]
+pub struct GrantRoleEvent {
+    /// Address that has been granted the role.
+    pub address: Address,
+    /// The role that has been granted.
+    pub role:    Roles,
+}
+
+/// A role was revoked from an address.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, SchemaType)]
+pub struct RevokeRoleEvent {
+    /// Address that has been revoked the role.
+    pub address: Address,
+    /// The role that has been revoked.
+    pub role:    Roles,
+}
+
+/// An admin action was proposed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, SchemaType)]
+pub struct ProposalCreatedEvent<A> {
+    /// Id of the proposal, used to execute or cancel it.
+    pub id:            u64,
+    /// The admin that proposed the action.
+    pub proposer:      Address,
+    /// The proposed action.
+    pub action:        GovernanceAction<A>,
+    /// The time from which the action can be executed.
+    pub execute_after: Timestamp,
+}
+
+/// A proposal was executed or cancelled.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, SchemaType)]
+pub struct ProposalEvent {
+    /// Id of the proposal.
+    pub id: u64,
+}
+
+/// A transfer of the `Admin` role was executed and is waiting to be accepted,
+/// or was accepted.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, SchemaType)]
+pub struct AdminTransferEvent {
+    /// The admin that transfers the role.
+    pub from: Address,
+    /// The address the role is transferred to.
+    pub to:   Address,
+}
+
+/// The contract was paused or unpaused.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, SchemaType)]
+pub struct SetPausedEvent {
+    /// Whether the contract is now paused.
+    pub paused: bool,
+}
+
+/// The withdraw fee or the claim fee was changed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, SchemaType)]
+pub struct SetFeeEvent {
+    /// The new fee.
+    pub fee: Amount,
+}
+
+/// The account receiving the fees was changed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, SchemaType)]
+pub struct SetTreasurerEvent {
+    /// The new treasurer.
+    pub treasurer: AccountAddress,
+}
+
+/// The contract was upgraded. The event is logged before the migration
+/// function, if any, is called.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, SchemaType)]
+pub struct UpgradeEvent {
+    /// The new module of the contract.
+    pub module: ModuleReference,
+}
+
+/// The governance delay was changed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, SchemaType)]
+pub struct SetGovernanceDelayEvent {
+    /// The new delay.
+    pub delay: Duration,
+}
+
+/// An address on the blocklist of the `bridge-manager` contract.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, SchemaType)]
+pub enum BlockedAddress {
+    /// An account or contract on Concordium, which cannot withdraw.
+    Concordium(Address),
+    /// An address on Ethereum, which cannot receive withdrawals.
+    Ethereum(EthAddress),
+}
+
+/// The parameter of the `setBlocked` entrypoint.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, SchemaType)]
+pub struct SetBlockedParams {
+    pub address: BlockedAddress,
+    /// Whether the address is added to, or removed from, the blocklist.
+    pub blocked: bool,
+}
+
+/// An address was added to or removed from the blocklist.
+pub type SetBlockedEvent = SetBlockedParams;
+
+/// The fee for withdrawing a token, which is taken in the withdrawn token: the
+/// full amount is burned and the fee is minted to the treasurer. The fee of a withdrawal of `amount` is
+/// `flat + amount * basis_points / 10000`, but at least `minimum`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, SchemaType)]
+pub struct TokenFee<A> {
+    /// Fee charged on every withdrawal.
+    pub flat:         A,
+    /// Fee proportional to the withdrawn amount, in hundredths of a percent.
+    pub basis_points: u16,
+    /// The smallest fee charged for a withdrawal.
+    pub minimum:      A,
+}
+
+/// The parameter of the `setTokenFee` entrypoint.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, SchemaType)]
+pub struct SetTokenFeeParams<A> {
+    /// Address of the child token on Concordium.
+    pub token: ContractAddress,
+    pub fee:   TokenFee<A>,
+}
+
+/// The fee for withdrawing a token was changed.
+pub type SetTokenFeeEvent<A> = SetTokenFeeParams<A>;
+
+/// The minimum time between proposing a corrective mint or burn and executing
+/// it, which applies even if the governance delay is shorter.
+pub const MIN_CORRECTION_DELAY: Duration = Duration::from_days(1);
+
+/// The parameter of the `correctiveMint` and `correctiveBurn` entrypoints,
+/// which propose to correct the supply of a child token after an incident,
+/// e.g., a deposit that was lost.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, SchemaType)]
+pub struct CorrectionParams<A> {
+    /// Id of the incident the correction belongs to, for the audit trail.
+    pub incident_id: u64,
+    /// Address of the child token on Concordium.
+    pub token:       ContractAddress,
+    /// The address tokens are minted to or burned from.
+    pub address:     Address,
+    pub amount:      A,
+}
+
+/// Tokens were minted or burned to correct the supply.
+pub type CorrectionEvent<A> = CorrectionParams<A>;
+
+/// All events logged by the `bridge-manager` contract. The tag of an event is
+/// the index of its variant.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, SchemaType)]
+pub enum BridgeEvent<A, T> {
+    TokenMap(TokenMapEvent),
+    Deposit(DepositEvent<A, T>),
+    Withdraw(WithdrawEvent<A, T>),
+    GrantRole(GrantRoleEvent),
+    RevokeRole(RevokeRoleEvent),
+    ClaimRequested(ClaimRequestedEvent),
+    ProposalCreated(ProposalCreatedEvent<A>),
+    ProposalExecuted(ProposalEvent),
+    ProposalCancelled(ProposalEvent),
+    AdminTransferPending(AdminTransferEvent),
+    AdminTransferAccepted(AdminTransferEvent),
+    SetPaused(SetPausedEvent),
+    SetWithdrawFee(SetFeeEvent),
+    SetClaimFee(SetFeeEvent),
+    SetTreasurer(SetTreasurerEvent),
+    Upgrade(UpgradeEvent),
+    SetGovernanceDelay(SetGovernanceDelayEvent),
+    SetBlocked(SetBlockedEvent),
+    SetTokenFee(SetTokenFeeEvent<A>),
+    CorrectiveMint(CorrectionEvent<A>),
+    CorrectiveBurn(CorrectionEvent<A>),
+}
+
+impl<A, T> BridgeEvent<A, T> {
+    /// Extract an event index if possible. Event index
+    /// is only emitted by [`WithdrawEvent`].
+    pub fn event_index(&self) -> Option<u64> {
+        match self {
+            BridgeEvent::Withdraw(we) => Some(we.event_index),
+            BridgeEvent::TokenMap(_)
+            | BridgeEvent::Deposit(_)
+            | BridgeEvent::GrantRole(_)
+            | BridgeEvent::RevokeRole(_)
+            | BridgeEvent::ClaimRequested(_)
+            | BridgeEvent::ProposalCreated(_)
+            | BridgeEvent::ProposalExecuted(_)
+            | BridgeEvent::ProposalCancelled(_)
+            | BridgeEvent::AdminTransferPending(_)
+            | BridgeEvent::AdminTransferAccepted(_)
+            | BridgeEvent::SetPaused(_)
+            | BridgeEvent::SetWithdrawFee(_)
+            | BridgeEvent::SetClaimFee(_)
+            | BridgeEvent::SetTreasurer(_)
+            | BridgeEvent::Upgrade(_)
+            | BridgeEvent::SetGovernanceDelay(_)
+            | BridgeEvent::SetBlocked(_)
+            | BridgeEvent::SetTokenFee(_)
+            | BridgeEvent::CorrectiveMint(_)
+            | BridgeEvent::CorrectiveBurn(_) => None,
+        }
+    }
+
+    /// The name of the administrative action the event records, or `None` if
+    /// the event is not caused by an admin, i.e., for token mappings,
+    /// deposits, withdrawals and claim requests.
+    pub fn admin_action(&self) -> Option<&'static str> {
+        match self {
+            BridgeEvent::TokenMap(_)
+            | BridgeEvent::Deposit(_)
+            | BridgeEvent::Withdraw(_)
+            | BridgeEvent::ClaimRequested(_) => None,
+            BridgeEvent::GrantRole(_) => Some("grant_role"),
+            BridgeEvent::RevokeRole(_) => Some("revoke_role"),
+            BridgeEvent::ProposalCreated(_) => Some("proposal_created"),
+            BridgeEvent::ProposalExecuted(_) => Some("proposal_executed"),
+            BridgeEvent::ProposalCancelled(_) => Some("proposal_cancelled"),
+            BridgeEvent::AdminTransferPending(_) => Some("admin_transfer_pending"),
+            BridgeEvent::AdminTransferAccepted(_) => Some("admin_transfer_accepted"),
+            BridgeEvent::SetPaused(_) => Some("set_paused"),
+            BridgeEvent::SetWithdrawFee(_) => Some("set_withdraw_fee"),
+            BridgeEvent::SetClaimFee(_) => Some("set_claim_fee"),
+            BridgeEvent::SetTreasurer(_) => Some("set_treasurer"),
+            BridgeEvent::Upgrade(_) => Some("upgrade"),
+            BridgeEvent::SetGovernanceDelay(_) => Some("set_governance_delay"),
+            BridgeEvent::SetBlocked(_) => Some("set_blocked"),
+            BridgeEvent::SetTokenFee(_) => Some("set_token_fee"),
+            BridgeEvent::CorrectiveMint(_) => Some("corrective_mint"),
+            BridgeEvent::CorrectiveBurn(_) => Some("corrective_burn"),
+        }
+    }
+}
+
+/// Administrative events of the `cis2-bridgeable` token contract, which does
+/// not depend on this crate. The relayer keeps them in its audit log without
+/// parsing them, so only their tags are described here. They must match the
+/// tags of the `BridgeableEvent` of the contract.
+pub mod token_admin_events {
+    /// A role was granted.
+    pub const GRANT_ROLE: u8 = 0;
+    /// A role was revoked.
+    pub const REVOKE_ROLE: u8 = 1;
+    /// An admin action was proposed.
+    pub const PROPOSAL_CREATED: u8 = 2;
+    /// A proposal was executed.
+    pub const PROPOSAL_EXECUTED: u8 = 3;
+    /// A proposal was cancelled.
+    pub const PROPOSAL_CANCELLED: u8 = 4;
+    /// A transfer of the `Admin` role is waiting to be accepted.
+    pub const ADMIN_TRANSFER_PENDING: u8 = 5;
+    /// A transfer of the `Admin` role was accepted.
+    pub const ADMIN_TRANSFER_ACCEPTED: u8 = 6;
+    /// The contract was paused or unpaused.
+    pub const SET_PAUSED: u8 = 7;
+    /// The implementors of a standard were set.
+    pub const SET_IMPLEMENTORS: u8 = 8;
+    /// The contract was upgraded.
+    pub const UPGRADE: u8 = 9;
+    /// The governance delay was changed.
+    pub const SET_GOVERNANCE_DELAY: u8 = 10;
+    /// The receive hook of a deposit to a contract was invoked. Not an
+    /// administrative action.
+    pub const DEPOSIT_HOOK: u8 = 11;
+
+    /// The name of the administrative action of an event with the given tag,
+    /// or `None` if the event is not administrative, e.g., a CIS-2 event.
+    pub fn admin_action(tag: u8) -> Option<&'static str> {
+        match tag {
+            GRANT_ROLE => Some("grant_role"),
+            REVOKE_ROLE => Some("revoke_role"),
+            PROPOSAL_CREATED => Some("proposal_created"),
+            PROPOSAL_EXECUTED => Some("proposal_executed"),
+            PROPOSAL_CANCELLED => Some("proposal_cancelled"),
+            ADMIN_TRANSFER_PENDING => Some("admin_transfer_pending"),
+            ADMIN_TRANSFER_ACCEPTED => Some("admin_transfer_accepted"),
+            SET_PAUSED => Some("set_paused"),
+            SET_IMPLEMENTORS => Some("set_implementors"),
+            UPGRADE => Some("upgrade"),
+            SET_GOVERNANCE_DELAY => Some("set_governance_delay"),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use concordium_cis2::{TokenAmountU256, TokenIdU64};
+
+    type Event = BridgeEvent<TokenAmountU256, TokenIdU64>;
+    type Update = StateUpdate<TokenAmountU256, TokenIdU64>;
+    type Action = GovernanceAction<TokenAmountU256>;
+
+    const ACCOUNT: AccountAddress = AccountAddress([7u8; 32]);
+    const CONTRACT: ContractAddress = ContractAddress {
+        index:    5,
+        subindex: 0,
+    };
+    const ETH_ADDRESS: EthAddress = EthAddress {
+        eth_address: [9u8; 20],
+    };
+
+    /// Check that the value serializes to exactly the given bytes, and that
+    /// the bytes deserialize to the value.
+    fn round_trip<V: Serial + Deserial + PartialEq + core::fmt::Debug>(value: &V, bytes: &[u8]) {
+        assert_eq!(to_bytes(value), bytes, "Serialization changed.");
+        assert_eq!(
+            &from_bytes::<V>(bytes).expect("Deserialization succeeds."),
+            value,
+            "Deserialization changed."
+        );
+    }
+
+    fn account_bytes() -> Vec<u8> { [&[0u8][..], &[7u8; 32]].concat() }
+
+    fn contract_bytes() -> Vec<u8> { [5u64.to_le_bytes(), 0u64.to_le_bytes()].concat() }
+
+    /// `TokenIdU64(3)` is serialized with a length prefix.
+    fn token_id_bytes() -> Vec<u8> { [&[8u8][..], &3u64.to_le_bytes()].concat() }
+
+    /// The amount 1000 is serialized in LEB128.
+    fn amount_bytes() -> Vec<u8> { vec![0xe8, 0x07] }
+
+    #[test]
+    fn test_token_map_update() {
+        let update = Update::TokenMap(TokenMapOperation {
+            id:    1,
+            root:  ETH_ADDRESS,
+            child: CONTRACT,
+        });
+        let bytes = [
+            &[1u8][..],
+            &1u64.to_le_bytes(),
+            &[9u8; 20],
+            &contract_bytes(),
+        ]
+        .concat();
+        round_trip(&update, &bytes);
+    }
+
+    #[test]
+    fn test_skip_update() {
+        let update = Update::Skip(SkipOperations { ids: vec![3, 5] });
+        let bytes = [
+            &[2u8][..],
+            &2u32.to_le_bytes(),
+            &3u64.to_le_bytes(),
+            &5u64.to_le_bytes(),
+        ]
+        .concat();
+        round_trip(&update, &bytes);
+    }
+
+    #[test]
+    fn test_deposit_update() {
+        let update = Update::Deposit(DepositOperation {
+            id:       2,
+            user:     Address::Account(ACCOUNT),
+            root:     ETH_ADDRESS,
+            amount:   TokenAmountU256(1000u64.into()),
+            token_id: TokenIdU64(3),
+            hook:     None,
+        });
+        let bytes = [
+            &[0u8][..],
+            &2u64.to_le_bytes(),
+            &account_bytes(),
+            &[9u8; 20],
+            &amount_bytes(),
+            &token_id_bytes(),
+            &[0u8],
+        ]
+        .concat();
+        round_trip(&update, &bytes);
+    }
+
+    #[test]
+    fn test_deposit_update_with_hook() {
+        let update = Update::Deposit(DepositOperation {
+            id:       2,
+            user:     Address::Contract(CONTRACT),
+            root:     ETH_ADDRESS,
+            amount:   TokenAmountU256(1000u64.into()),
+            token_id: TokenIdU64(3),
+            hook:     Some(DepositHook {
+                entrypoint: OwnedEntrypointName::new_unchecked("onDeposit".into()),
+                data:       vec![1, 2, 3],
+            }),
+        });
+        let bytes = [
+            &[0u8][..],
+            &2u64.to_le_bytes(),
+            &[1u8],
+            &contract_bytes(),
+            &[9u8; 20],
+            &amount_bytes(),
+            &token_id_bytes(),
+            &[1u8],
+            &9u16.to_le_bytes(),
+            b"onDeposit",
+            &3u16.to_le_bytes(),
+            &[1, 2, 3],
+        ]
+        .concat();
+        round_trip(&update, &bytes);
+    }
+
+    #[test]
+    fn test_deposit_update_without_hook_field() {
+        let bytes = [
+            &[0u8][..],
+            &2u64.to_le_bytes(),
+            &account_bytes(),
+            &[9u8; 20],
+            &amount_bytes(),
+            &token_id_bytes(),
+        ]
+        .concat();
+        let update = from_bytes::<Update>(&bytes).expect("Deserialization succeeds.");
+        assert!(matches!(
+            update,
+            Update::Deposit(DepositOperation { hook: None, .. })
+        ));
+    }
+
+    #[test]
+    fn test_token_map_event() {
+        let event = Event::TokenMap(TokenMapEvent {
+            id:    1,
+            root:  ETH_ADDRESS,
+            child: CONTRACT,
+        });
+        let bytes = [
+            &[0u8][..],
+            &1u64.to_le_bytes(),
+            &[9u8; 20],
+            &contract_bytes(),
+        ]
+        .concat();
+        round_trip(&event, &bytes);
+        assert_eq!(event.event_index(), None);
+    }
+
+    #[test]
+    fn test_deposit_event() {
+        let event = Event::Deposit(DepositEvent {
+            id:       2,
+            contract: CONTRACT,
+            amount:   TokenAmountU256(1000u64.into()),
+            token_id: TokenIdU64(3),
+        });
+        let bytes = [
+            &[1u8][..],
+            &2u64.to_le_bytes(),
+            &contract_bytes(),
+            &amount_bytes(),
+            &token_id_bytes(),
+        ]
+        .concat();
+        round_trip(&event, &bytes);
+    }
+
+    #[test]
+    fn test_withdraw_event() {
+        let event = Event::Withdraw(WithdrawEvent {
+            event_index: 4,
+            contract:    CONTRACT,
+            amount:      TokenAmountU256(1000u64.into()),
+            ccd_address: Address::Account(ACCOUNT),
+            eth_address: ETH_ADDRESS,
+            token_id:    TokenIdU64(3),
+            fee:         Some(TokenAmountU256(5u64.into())),
+        });
+        let bytes = [
+            &[2u8][..],
+            &4u64.to_le_bytes(),
+            &contract_bytes(),
+            &amount_bytes(),
+            &account_bytes(),
+            &[9u8; 20],
+            &token_id_bytes(),
+            &[1u8, 5u8],
+        ]
+        .concat();
+        round_trip(&event, &bytes);
+        assert_eq!(event.event_index(), Some(4));
+    }
+
+    #[test]
+    fn test_withdraw_event_without_fee_field() {
+        let bytes = [
+            &[2u8][..],
+            &4u64.to_le_bytes(),
+            &contract_bytes(),
+            &amount_bytes(),
+            &account_bytes(),
+            &[9u8; 20],
+            &token_id_bytes(),
+        ]
+        .concat();
+        let event = from_bytes::<Event>(&bytes).expect("Deserialization succeeds.");
+        assert!(matches!(
+            event,
+            Event::Withdraw(WithdrawEvent { fee: None, .. })
+        ));
+    }
+
+    #[test]
+    fn test_role_events() {
+        let grant = Event::GrantRole(GrantRoleEvent {
+            address: Address::Account(ACCOUNT),
+            role:    Roles::StateSyncer,
+        });
+        round_trip(&grant, &[&[3u8][..], &account_bytes(), &[2u8]].concat());
+        let revoke = Event::RevokeRole(RevokeRoleEvent {
+            address: Address::Contract(CONTRACT),
+            role:    Roles::Mapper,
+        });
+        round_trip(
+            &revoke,
+            &[&[4u8][..], &[1u8], &contract_bytes(), &[1u8]].concat(),
+        );
+    }
+
+    #[test]
+    fn test_claim_requested_event() {
+        let event = Event::ClaimRequested(ClaimRequestedEvent {
+            event_index: 4,
+            fee:         Amount::from_micro_ccd(500),
+        });
+        let bytes = [&[5u8][..], &4u64.to_le_bytes(), &500u64.to_le_bytes()].concat();
+        round_trip(&event, &bytes);
+    }
+
+    #[test]
+    fn test_governance_events() {
+        let created = Event::ProposalCreated(ProposalCreatedEvent {
+            id:            3,
+            proposer:      Address::Account(ACCOUNT),
+            action:        Action::SetWithdrawFee(Amount::from_micro_ccd(500)),
+            execute_after: Timestamp::from_timestamp_millis(1000),
+        });
+        let bytes = [
+            &[6u8][..],
+            &3u64.to_le_bytes(),
+            &account_bytes(),
+            &[0u8],
+            &500u64.to_le_bytes(),
+            &1000u64.to_le_bytes(),
+        ]
+        .concat();
+        round_trip(&created, &bytes);
+        let executed = Event::ProposalExecuted(ProposalEvent { id: 3 });
+        round_trip(&executed, &[&[7u8][..], &3u64.to_le_bytes()].concat());
+        let cancelled = Event::ProposalCancelled(ProposalEvent { id: 3 });
+        round_trip(&cancelled, &[&[8u8][..], &3u64.to_le_bytes()].concat());
+        let transfer = AdminTransferEvent {
+            from: Address::Account(ACCOUNT),
+            to:   Address::Contract(CONTRACT),
+        };
+        let transfer_bytes = [&account_bytes()[..], &[1u8], &contract_bytes()].concat();
+        round_trip(
+            &Event::AdminTransferPending(transfer.clone()),
+            &[&[9u8][..], &transfer_bytes].concat(),
+        );
+        round_trip(
+            &Event::AdminTransferAccepted(transfer),
+            &[&[10u8][..], &transfer_bytes].concat(),
+        );
+    }
+
+    #[test]
+    fn test_configuration_events() {
+        let paused = Event::SetPaused(SetPausedEvent { paused: true });
+        round_trip(&paused, &[11u8, 1u8]);
+        assert_eq!(paused.admin_action(), Some("set_paused"));
+        let fee = SetFeeEvent {
+            fee: Amount::from_micro_ccd(500),
+        };
+        round_trip(
+            &Event::SetWithdrawFee(fee.clone()),
+            &[&[12u8][..], &500u64.to_le_bytes()].concat(),
+        );
+        round_trip(
+            &Event::SetClaimFee(fee),
+            &[&[13u8][..], &500u64.to_le_bytes()].concat(),
+        );
+        round_trip(
+            &Event::SetTreasurer(SetTreasurerEvent { treasurer: ACCOUNT }),
+            &[&[14u8][..], &[7u8; 32]].concat(),
+        );
+        round_trip(
+            &Event::Upgrade(UpgradeEvent {
+                module: ModuleReference::from([1u8; 32]),
+            }),
+            &[&[15u8][..], &[1u8; 32]].concat(),
+        );
+        round_trip(
+            &Event::SetGovernanceDelay(SetGovernanceDelayEvent {
+                delay: Duration::from_millis(1000),
+            }),
+            &[&[16u8][..], &1000u64.to_le_bytes()].concat(),
+        );
+        let claim = Event::ClaimRequested(ClaimRequestedEvent {
+            event_index: 4,
+            fee:         Amount::from_micro_ccd(500),
+        });
+        assert_eq!(claim.admin_action(), None);
+    }
+
+    #[test]
+    fn test_blocklist() {
+        let blocked = Event::SetBlocked(SetBlockedEvent {
+            address: BlockedAddress::Concordium(Address::Account(ACCOUNT)),
+            blocked: true,
+        });
+        round_trip(
+            &blocked,
+            &[&[17u8][..], &[0u8], &account_bytes(), &[1u8]].concat(),
+        );
+        assert_eq!(blocked.admin_action(), Some("set_blocked"));
+        round_trip(
+            &SetBlockedParams {
+                address: BlockedAddress::Ethereum(ETH_ADDRESS),
+                blocked: false,
+            },
+            &[&[1u8][..], &[9u8; 20], &[0u8]].concat(),
+        );
+        round_trip(&Roles::Blocklister, &[4u8]);
+    }
+
+    #[test]
+    fn test_token_fee() {
+        let params = SetTokenFeeParams {
+            token: CONTRACT,
+            fee:   TokenFee {
+                flat:         TokenAmountU256(5u64.into()),
+                basis_points: 30,
+                minimum:      TokenAmountU256(1000u64.into()),
+            },
+        };
+        let bytes = [
+            &contract_bytes()[..],
+            &[5u8],
+            &30u16.to_le_bytes(),
+            &amount_bytes(),
+        ]
+        .concat();
+        round_trip(&params, &bytes);
+        let event = Event::SetTokenFee(params.clone());
+        round_trip(&event, &[&[18u8][..], &bytes].concat());
+        assert_eq!(event.admin_action(), Some("set_token_fee"));
+        assert_eq!(event.event_index(), None);
+        round_trip(&Action::SetTokenFee(params), &[&[8u8][..], &bytes].concat());
+    }
+
+    #[test]
+    fn test_corrections() {
+        let params = CorrectionParams {
+            incident_id: 4,
+            token:       CONTRACT,
+            address:     Address::Account(ACCOUNT),
+            amount:      TokenAmountU256(1000u64.into()),
+        };
+        let bytes = [
+            &4u64.to_le_bytes()[..],
+            &contract_bytes(),
+            &account_bytes(),
+            &amount_bytes(),
+        ]
+        .concat();
+        round_trip(&params, &bytes);
+        let mint = Event::CorrectiveMint(params.clone());
+        round_trip(&mint, &[&[19u8][..], &bytes].concat());
+        assert_eq!(mint.admin_action(), Some("corrective_mint"));
+        assert_eq!(mint.event_index(), None);
+        let burn = Event::CorrectiveBurn(params.clone());
+        round_trip(&burn, &[&[20u8][..], &bytes].concat());
+        assert_eq!(burn.admin_action(), Some("corrective_burn"));
+        round_trip(
+            &Action::CorrectiveMint(params.clone()),
+            &[&[9u8][..], &bytes].concat(),
+        );
+        round_trip(
+            &Action::CorrectiveBurn(params),
+            &[&[10u8][..], &bytes].concat(),
+        );
+    }
+
+    #[test]
+    fn test_governance_actions() {
+        round_trip(
+            &Action::SetTreasurer(ACCOUNT),
+            &[&[2u8][..], &[7u8; 32]].concat(),
+        );
+        round_trip(
+            &Action::GrantRole(GrantRoleParams {
+                address: Address::Account(ACCOUNT),
+                role:    Roles::StateSyncer,
+            }),
+            &[&[3u8][..], &account_bytes(), &[2u8]].concat(),
+        );
+        round_trip(
+            &Action::SetGovernanceDelay(Duration::from_millis(86_400_000)),
+            &[&[6u8][..], &86_400_000u64.to_le_bytes()].concat(),
+        );
+        round_trip(
+            &Action::TransferAdmin(Address::Account(ACCOUNT)),
+            &[&[7u8][..], &account_bytes()].concat(),
+        );
+    }
+
+    #[test]
+    fn test_unknown_tags_are_rejected() {
+        assert!(from_bytes::<Event>(&[21u8]).is_err());
+        assert!(from_bytes::<Action>(&[11u8]).is_err());
+        assert!(from_bytes::<Update>(&[2u8]).is_err());
+        assert!(from_bytes::<Roles>(&[5u8]).is_err());
+        assert!(from_bytes::<BlockedAddress>(&[2u8]).is_err());
+    }
+
+    #[test]
+    fn test_role_params() {
+        let params = GrantRoleParams {
+            address: Address::Account(ACCOUNT),
+            role:    Roles::Admin,
+        };
+        round_trip(&params, &[&account_bytes()[..], &[0u8]].concat());
+        round_trip(&Roles::Pauser, &[3u8]);
+    }
+
+    #[test]
+    fn test_upgrade_params() {
+        let params = UpgradeParams {
+            module:  ModuleReference::from([1u8; 32]),
+            migrate: Some((
+                OwnedEntrypointName::new_unchecked("migrate".to_string()),
+                OwnedParameter::from_serial(&1u8).expect("The parameter is small."),
+            )),
+        };
+        let bytes = [
+            &[1u8; 32][..],
+            &[1u8],
+            &7u16.to_le_bytes(),
+            b"migrate",
+            &1u16.to_le_bytes(),
+            &[1u8],
+        ]
+        .concat();
+        round_trip(&params, &bytes);
+    }
+}
@@ -16,6 +16,7 @@
 //! `bridge-manager` smart contract to burn tokens.
 
 #![cfg_attr(not(feature = "std"), no_std)]
+pub use bridge_types::DepositHook;
 use concordium_cis2::{Cis2Event, *};
 use concordium_std::{collections::BTreeMap, *};
 use primitive_types::U256;
@@ -27,6 +28,26 @@ const TOKEN_ID: ContractTokenId = TokenIdUnit();
 pub const GRANT_ROLE_EVENT_TAG: u8 = 0;
 /// Tag for the RevokeRole event.
 pub const REVOKE_ROLE_EVENT_TAG: u8 = 1;
+/// Tag for the ProposalCreated event.
+pub const PROPOSAL_CREATED_EVENT_TAG: u8 = 2;
+/// Tag for the ProposalExecuted event.
+pub const PROPOSAL_EXECUTED_EVENT_TAG: u8 = 3;
+/// Tag for the ProposalCancelled event.
+pub const PROPOSAL_CANCELLED_EVENT_TAG: u8 = 4;
+/// Tag for the AdminTransferPending event.
+pub const ADMIN_TRANSFER_PENDING_EVENT_TAG: u8 = 5;
+/// Tag for the AdminTransferAccepted event.
+pub const ADMIN_TRANSFER_ACCEPTED_EVENT_TAG: u8 = 6;
+/// Tag for the SetPaused event.
+pub const SET_PAUSED_EVENT_TAG: u8 = 7;
+/// Tag for the SetImplementors event.
+pub const SET_IMPLEMENTORS_EVENT_TAG: u8 = 8;
+/// Tag for the Upgrade event.
+pub const UPGRADE_EVENT_TAG: u8 = 9;
+/// Tag for the SetGovernanceDelay event.
+pub const SET_GOVERNANCE_DELAY_EVENT_TAG: u8 = 10;
+/// Tag for the DepositHook event.
+pub const DEPOSIT_HOOK_EVENT_TAG: u8 = 11;
 
 /// List of supported standards by this contract address.
 const SUPPORTS_STANDARDS: [StandardIdentifier<'static>; 2] =
@@ -66,19 +87,113 @@ struct AddressRoleState<S> {
 #[concordium(state_parameter = "S")]
 struct State<S: HasStateApi> {
     /// Contract is paused if `paused = true` and unpaused if `paused = false`.
-    paused:       bool,
+    paused:           bool,
     /// Map specifying the `AddressState` (balance and operators) for every
     /// address.
-    token:        StateMap<Address, AddressState<S>, S>,
-    roles:        StateMap<Address, AddressRoleState<S>, S>,
+    token:            StateMap<Address, AddressState<S>, S>,
+    roles:            StateMap<Address, AddressRoleState<S>, S>,
     /// The MetadataUrl of the token.
     /// `StateBox` allows for lazy loading data. This is helpful
     /// in the situations when one wants to do a partial update not touching
     /// this field, which can be large.
+    metadata_url:     StateBox<concordium_cis2::MetadataUrl, S>,
+    implementors:     StateMap<StandardIdentifierOwned, Vec<ContractAddress>, S>,
+    /// Time between proposing an admin action and executing it. While it is
+    /// zero the admin can also configure the contract directly.
+    governance_delay: Duration,
+    /// Id of the next proposal.
+    next_proposal_id: u64,
+    /// Admin actions that are proposed and not yet executed or cancelled.
+    proposals:        StateMap<u64, Proposal, S>,
+    /// An executed transfer of the `Admin` role that is not yet accepted.
+    admin_transfer:   Option<AdminTransfer>,
+}
+
+/// The state of earlier versions of the contract, which is migrated by
+/// `migrate`. The fields added since are all at the end.
+#[derive(DeserialWithState)]
+#[concordium(state_parameter = "S")]
+struct StateV0<S: HasStateApi> {
+    paused:       bool,
+    token:        StateMap<Address, AddressState<S>, S>,
+    roles:        StateMap<Address, AddressRoleState<S>, S>,
     metadata_url: StateBox<concordium_cis2::MetadataUrl, S>,
     implementors: StateMap<StandardIdentifierOwned, Vec<ContractAddress>, S>,
 }
 
+/// An admin action, which is proposed with `propose` and executed with
+/// `execute` once the governance delay has passed.
+#[derive(Serialize, Clone)]
+enum GovernanceAction {
+    /// Upgrade the contract.
+    Upgrade(UpgradeParams),
+    /// Set the implementors of a standard.
+    SetImplementors(SetImplementorsParams),
+    /// Grant a role.
+    GrantRole(GrantRoleParams),
+    /// Remove a role.
+    RemoveRole(RemoveRoleParams),
+    /// Set the time between proposing and executing an action.
+    SetGovernanceDelay(Duration),
+    /// Transfer the `Admin` role of the proposer to the given address, once
+    /// the address accepts it with `acceptAdmin`.
+    TransferAdmin(Address),
+}
+
+/// Manual implementation of the `GovernanceAction` schema, since it is part of
+/// the schema of the events. The schemas of the parameters are also written by
+/// hand, since derived schemas only exist when the schema is built.
+impl schema::SchemaType for GovernanceAction {
+    fn get_type() -> schema::Type {
+        schema::Type::Enum(vec![
+            (
+                "Upgrade".to_string(),
+                schema::Fields::Unnamed(vec![UpgradeParams::get_type()]),
+            ),
+            (
+                "SetImplementors".to_string(),
+                schema::Fields::Unnamed(vec![SetImplementorsParams::get_type()]),
+            ),
+            (
+                "GrantRole".to_string(),
+                schema::Fields::Unnamed(vec![GrantRoleParams::get_type()]),
+            ),
+            (
+                "RemoveRole".to_string(),
+                schema::Fields::Unnamed(vec![RemoveRoleParams::get_type()]),
+            ),
+            (
+                "SetGovernanceDelay".to_string(),
+                schema::Fields::Unnamed(vec![Duration::get_type()]),
+            ),
+            (
+                "TransferAdmin".to_string(),
+                schema::Fields::Unnamed(vec![Address::get_type()]),
+            ),
+        ])
+    }
+}
+
+/// An admin action that was proposed.
+#[derive(Serialize, SchemaType, Clone)]
+struct Proposal {
+    /// The admin that proposed the action.
+    proposer:      Address,
+    /// The proposed action.
+    action:        GovernanceAction,
+    /// The time from which the action can be executed.
+    execute_after: Timestamp,
+}
+
+/// A transfer of the `Admin` role, waiting to be accepted.
+#[derive(Serialize, SchemaType, Clone, Copy)]
+struct AdminTransfer {
+    /// The admin that transfers the role.
+    from: Address,
+    /// The address the role is transferred to.
+    to:   Address,
+}
+
 /// The return type for the contract function `view`.
 #[derive(Serialize, SchemaType)]
 struct ReturnBasicState {
@@ -108,6 +223,24 @@ struct ViewTokenOwners {
     token_owners: Vec<Address>,
 }
 
+/// The parameter type for the contract function `viewTokenOwnersPage`.
+#[derive(Serialize, SchemaType)]
+struct ViewTokenOwnersPageParams {
+    /// The address to start from, or `None` to start from the beginning.
+    start: Option<Address>,
+    /// The maximal number of addresses to return.
+    limit: u32,
+}
+
+/// The return type of the `viewTokenOwnersPage` function.
+#[derive(Serialize, SchemaType)]
+struct ViewTokenOwnersPage {
+    token_owners: Vec<Address>,
+    /// The address to start the next page from, or `None` if this is the last
+    /// page.
+    next:         Option<Address>,
+}
+
 /// The parameter type for the contract function `setPaused`.
 #[derive(Serialize, SchemaType)]
 #[repr(transparent)]
@@ -130,10 +263,67 @@ struct RevokeRoleEvent {
     address: Address,
     role:    Roles,
 }
+/// An admin action was proposed.
+#[derive(Serial, SchemaType)]
+struct ProposalCreatedEvent {
+    /// Id of the proposal, used to execute or cancel it.
+    id:            u64,
+    /// The admin that proposed the action.
+    proposer:      Address,
+    /// The proposed action.
+    action:        GovernanceAction,
+    /// The time from which the action can be executed.
+    execute_after: Timestamp,
+}
+/// A proposal was executed or cancelled.
+#[derive(Serial, SchemaType)]
+struct ProposalEvent {
+    /// Id of the proposal.
+    id: u64,
+}
+/// The contract was paused or unpaused.
+#[derive(Serial, SchemaType)]
+struct SetPausedEvent {
+    /// Whether the contract is now paused.
+    paused: bool,
+}
+/// The contract was upgraded. The event is logged before the migration
+/// function, if any, is called.
+#[derive(Serial, SchemaType)]
+struct UpgradeEvent {
+    /// The new module of the contract.
+    module: ModuleReference,
+}
+/// The governance delay was changed.
+#[derive(Serial, SchemaType)]
+struct SetGovernanceDelayEvent {
+    /// The new delay.
+    delay: Duration,
+}
+/// The receive hook of a deposit was invoked.
+#[derive(Serial, SchemaType)]
+struct DepositHookEvent {
+    /// The contract the tokens were minted to.
+    contract:   ContractAddress,
+    /// The invoked entrypoint of the contract.
+    entrypoint: OwnedEntrypointName,
+    /// Whether the entrypoint succeeded. The tokens are minted either way.
+    accepted:   bool,
+}
 /// Tagged events to be serialized for the event log.
 enum BridgeableEvent {
     GrantRole(GrantRoleEvent),
     RevokeRole(RevokeRoleEvent),
+    ProposalCreated(ProposalCreatedEvent),
+    ProposalExecuted(ProposalEvent),
+    ProposalCancelled(ProposalEvent),
+    AdminTransferPending(AdminTransfer),
+    AdminTransferAccepted(AdminTransfer),
+    SetPaused(SetPausedEvent),
+    SetImplementors(SetImplementorsParams),
+    Upgrade(UpgradeEvent),
+    SetGovernanceDelay(SetGovernanceDelayEvent),
+    DepositHook(DepositHookEvent),
     Cis2Event(Cis2Event<ContractTokenId, ContractTokenAmount>),
 }
 
@@ -148,6 +338,46 @@ impl Serial for BridgeableEvent {
                 out.write_u8(REVOKE_ROLE_EVENT_TAG)?;
                 event.serial(out)
             }
+            BridgeableEvent::ProposalCreated(event) => {
+                out.write_u8(PROPOSAL_CREATED_EVENT_TAG)?;
+                event.serial(out)
+            }
+            BridgeableEvent::ProposalExecuted(event) => {
+                out.write_u8(PROPOSAL_EXECUTED_EVENT_TAG)?;
+                event.serial(out)
+            }
+            BridgeableEvent::ProposalCancelled(event) => {
+                out.write_u8(PROPOSAL_CANCELLED_EVENT_TAG)?;
+                event.serial(out)
+            }
+            BridgeableEvent::AdminTransferPending(event) => {
+                out.write_u8(ADMIN_TRANSFER_PENDING_EVENT_TAG)?;
+                event.serial(out)
+            }
+            BridgeableEvent::AdminTransferAccepted(event) => {
+                out.write_u8(ADMIN_TRANSFER_ACCEPTED_EVENT_TAG)?;
+                event.serial(out)
+            }
+            BridgeableEvent::SetPaused(event) => {
+                out.write_u8(SET_PAUSED_EVENT_TAG)?;
+                event.serial(out)
+            }
+            BridgeableEvent::SetImplementors(event) => {
+                out.write_u8(SET_IMPLEMENTORS_EVENT_TAG)?;
+                event.serial(out)
+            }
+            BridgeableEvent::Upgrade(event) => {
+                out.write_u8(UPGRADE_EVENT_TAG)?;
+                event.serial(out)
+            }
+            BridgeableEvent::SetGovernanceDelay(event) => {
+                out.write_u8(SET_GOVERNANCE_DELAY_EVENT_TAG)?;
+                event.serial(out)
+            }
+            BridgeableEvent::DepositHook(event) => {
+                out.write_u8(DEPOSIT_HOOK_EVENT_TAG)?;
+                event.serial(out)
+            }
             BridgeableEvent::Cis2Event(event) => event.serial(out),
         }
     }
@@ -178,6 +408,97 @@ impl schema::SchemaType for BridgeableEvent {
                 ]),
             ),
         );
+        event_map.insert(
+            PROPOSAL_CREATED_EVENT_TAG,
+            (
+                "ProposalCreated".to_string(),
+                schema::Fields::Named(vec![
+                    (String::from("id"), u64::get_type()),
+                    (String::from("proposer"), Address::get_type()),
+                    (String::from("action"), GovernanceAction::get_type()),
+                    (String::from("execute_after"), Timestamp::get_type()),
+                ]),
+            ),
+        );
+        event_map.insert(
+            PROPOSAL_EXECUTED_EVENT_TAG,
+            (
+                "ProposalExecuted".to_string(),
+                schema::Fields::Named(vec![(String::from("id"), u64::get_type())]),
+            ),
+        );
+        event_map.insert(
+            PROPOSAL_CANCELLED_EVENT_TAG,
+            (
+                "ProposalCancelled".to_string(),
+                schema::Fields::Named(vec![(String::from("id"), u64::get_type())]),
+            ),
+        );
+        event_map.insert(
+            ADMIN_TRANSFER_PENDING_EVENT_TAG,
+            (
+                "AdminTransferPending".to_string(),
+                schema::Fields::Named(vec![
+                    (String::from("from"), Address::get_type()),
+                    (String::from("to"), Address::get_type()),
+                ]),
+            ),
+        );
+        event_map.insert(
+            ADMIN_TRANSFER_ACCEPTED_EVENT_TAG,
+            (
+                "AdminTransferAccepted".to_string(),
+                schema::Fields::Named(vec![
+                    (String::from("from"), Address::get_type()),
+                    (String::from("to"), Address::get_type()),
+                ]),
+            ),
+        );
+        event_map.insert(
+            SET_PAUSED_EVENT_TAG,
+            (
+                "SetPaused".to_string(),
+                schema::Fields::Named(vec![(String::from("paused"), bool::get_type())]),
+            ),
+        );
+        event_map.insert(
+            SET_IMPLEMENTORS_EVENT_TAG,
+            (
+                "SetImplementors".to_string(),
+                schema::Fields::Named(vec![
+                    (String::from("id"), StandardIdentifierOwned::get_type()),
+                    (
+                        String::from("implementors"),
+                        Vec::<ContractAddress>::get_type(),
+                    ),
+                ]),
+            ),
+        );
+        event_map.insert(
+            UPGRADE_EVENT_TAG,
+            (
+                "Upgrade".to_string(),
+                schema::Fields::Named(vec![(String::from("module"), ModuleReference::get_type())]),
+            ),
+        );
+        event_map.insert(
+            SET_GOVERNANCE_DELAY_EVENT_TAG,
+            (
+                "SetGovernanceDelay".to_string(),
+                schema::Fields::Named(vec![(String::from("delay"), Duration::get_type())]),
+            ),
+        );
+        event_map.insert(
+            DEPOSIT_HOOK_EVENT_TAG,
+            (
+                "DepositHook".to_string(),
+                schema::Fields::Named(vec![
+                    (String::from("contract"), ContractAddress::get_type()),
+                    (String::from("entrypoint"), OwnedEntrypointName::get_type()),
+                    (String::from("accepted"), bool::get_type()),
+                ]),
+            ),
+        );
         event_map.insert(
             TRANSFER_EVENT_TAG,
             (
@@ -239,7 +560,7 @@ impl schema::SchemaType for BridgeableEvent {
 /// The parameter type for the contract function `setImplementors`.
 /// Takes a standard identifier and list of contract addresses providing
 /// implementations of this standard.
-#[derive(Serialize, SchemaType)]
+#[derive(Serialize)]
 struct SetImplementorsParams {
     /// The identifier for the standard.
     id:           StandardIdentifierOwned,
@@ -247,12 +568,35 @@ struct SetImplementorsParams {
     implementors: Vec<ContractAddress>,
 }
 
+/// Manual implementation of the `SetImplementorsParams` schema.
+impl schema::SchemaType for SetImplementorsParams {
+    fn get_type() -> schema::Type {
+        schema::Type::Struct(schema::Fields::Named(vec![
+            (String::from("id"), StandardIdentifierOwned::get_type()),
+            (
+                String::from("implementors"),
+                Vec::<ContractAddress>::get_type(),
+            ),
+        ]))
+    }
+}
+
+/// `StandardIdentifierOwned` does not implement `Clone`.
+impl Clone for SetImplementorsParams {
+    fn clone(&self) -> Self {
+        Self {
+            id:           self.id.as_standard_identifier().to_owned(),
+            implementors: self.implementors.clone(),
+        }
+    }
+}
+
 /// The parameter type for the contract function `upgrade`.
 /// Takes the new module and optionally an entrypoint to call in the new module
 /// after triggering the upgrade. The upgrade is reverted if the entrypoint
 /// fails. This is useful for doing migration in the same transaction triggering
 /// the upgrade.
-#[derive(Serialize, SchemaType)]
+#[derive(Serialize, Clone)]
 struct UpgradeParams {
     /// The new module reference.
     module:  ModuleReference,
@@ -260,6 +604,19 @@ struct UpgradeParams {
     migrate: Option<(OwnedEntrypointName, OwnedParameter)>,
 }
 
+/// Manual implementation of the `UpgradeParams` schema.
+impl schema::SchemaType for UpgradeParams {
+    fn get_type() -> schema::Type {
+        schema::Type::Struct(schema::Fields::Named(vec![
+            (String::from("module"), ModuleReference::get_type()),
+            (
+                String::from("migrate"),
+                Option::<(OwnedEntrypointName, OwnedParameter)>::get_type(),
+            ),
+        ]))
+    }
+}
+
 /// The different errors the contract can produce.
 #[derive(Serialize, Debug, PartialEq, Eq, Reject, SchemaType)]
 enum CustomContractError {
@@ -286,6 +643,14 @@ enum CustomContractError {
     /// Upgrade failed because the smart contract version of the module is not
     /// supported.
     FailedUpgradeUnsupportedModuleVersion,
+    /// The action must be proposed and executed after the governance delay.
+    GovernanceDelayActive,
+    /// There is no proposal with the given id.
+    ProposalNotFound,
+    /// The governance delay of the proposal has not passed yet.
+    ProposalNotReady,
+    /// There is no admin transfer to the sender to accept.
+    NoAdminTransfer,
 }
 
 type ContractError = Cis2Error<CustomContractError>;
@@ -333,6 +698,8 @@ impl From<CustomContractError> for ContractError {
 pub enum Roles {
     Admin,
     Manager,
+    /// Can pause, but not unpause, the contract.
+    Pauser,
 }
 
 /// Manual implementation of the `Roles` schema.
@@ -341,6 +708,7 @@ impl schema::SchemaType for Roles {
         schema::Type::Enum(vec![
             ("Admin".to_string(), schema::Fields::None),
             ("Manager".to_string(), schema::Fields::None),
+            ("Pauser".to_string(), schema::Fields::None),
         ])
     }
 }
@@ -352,14 +720,37 @@ impl<S: HasStateApi> State<S> {
         metadata_url: concordium_cis2::MetadataUrl,
     ) -> Self {
         State {
-            paused:       false,
-            token:        state_builder.new_map(),
-            roles:        state_builder.new_map(),
-            metadata_url: state_builder.new_box(metadata_url),
-            implementors: state_builder.new_map(),
+            paused:           false,
+            token:            state_builder.new_map(),
+            roles:            state_builder.new_map(),
+            metadata_url:     state_builder.new_box(metadata_url),
+            implementors:     state_builder.new_map(),
+            governance_delay: Duration::from_millis(0),
+            next_proposal_id: 0,
+            proposals:        state_builder.new_map(),
+            admin_transfer:   None,
+        }
+    }
+
+    /// Migrates the state of an earlier version of the contract.
+    fn from_v0(old: StateV0<S>, state_builder: &mut StateBuilder<S>) -> Self {
+        State {
+            paused:           old.paused,
+            token:            old.token,
+            roles:            old.roles,
+            metadata_url:     old.metadata_url,
+            implementors:     old.implementors,
+            governance_delay: Duration::from_millis(0),
+            next_proposal_id: 0,
+            proposals:        state_builder.new_map(),
+            admin_transfer:   None,
         }
     }
 
+    /// Whether admin actions must be proposed, because the governance delay
+    /// is not zero.
+    fn has_governance_delay(&self) -> bool { self.governance_delay.millis() > 0 }
+
     /// Get the current balance of a given token id for a given address.
     /// Results in an error if the token id does not exist in the state.
     fn balance(
@@ -939,11 +1330,13 @@ fn contract_supports<S: HasStateApi>(
     name = "setImplementors",
     error = "ContractError",
     parameter = "SetImplementorsParams",
+    enable_logger,
     mutable
 )]
 fn contract_set_implementor<S: HasStateApi>(
     ctx: &impl HasReceiveContext,
     host: &mut impl HasHost<State<S>, StateApiType = S>,
+    logger: &mut impl HasLogger,
 ) -> ContractResult<()> {
     // Get the sender who invoked this contract function.
     let sender = ctx.sender();
@@ -955,11 +1348,18 @@ fn contract_set_implementor<S: HasStateApi>(
         state.has_role(&sender, Roles::Admin),
         ContractError::Unauthorized
     );
+    ensure!(
+        !state.has_governance_delay(),
+        ContractError::Custom(CustomContractError::GovernanceDelayActive)
+    );
     // Parse the parameter.
     let params: SetImplementorsParams = ctx.parameter_cursor().get()?;
     // Update the implementors in the state
-    host.state_mut()
-        .set_implementors(params.id, params.implementors);
+    host.state_mut().set_implementors(
+        params.id.as_standard_identifier().to_owned(),
+        params.implementors.clone(),
+    );
+    logger.log(&BridgeableEvent::SetImplementors(params))?;
     Ok(())
 }
 
@@ -968,11 +1368,13 @@ fn contract_set_implementor<S: HasStateApi>(
     name = "upgrade",
     parameter = "UpgradeParams",
     error = "ContractError",
+    enable_logger,
     mutable
 )]
 fn contract_upgrade<S: HasStateApi>(
     ctx: &impl HasReceiveContext,
     host: &mut impl HasHost<State<S>, StateApiType = S>,
+    logger: &mut impl HasLogger,
 ) -> ContractResult<()> {
     // Get the sender who invoked this contract function.
     let sender = ctx.sender();
@@ -984,10 +1386,27 @@ fn contract_upgrade<S: HasStateApi>(
         state.has_role(&sender, Roles::Admin),
         ContractError::Unauthorized
     );
+    ensure!(
+        !state.has_governance_delay(),
+        ContractError::Custom(CustomContractError::GovernanceDelayActive)
+    );
     // Parse the parameter.
     let params: UpgradeParams = ctx.parameter_cursor().get()?;
+    upgrade(ctx, host, logger, params)
+}
+
+/// Upgrade the contract, and call the migration function if provided.
+fn upgrade<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &mut impl HasHost<State<S>, StateApiType = S>,
+    logger: &mut impl HasLogger,
+    params: UpgradeParams,
+) -> ContractResult<()> {
     // Trigger the upgrade.
     host.upgrade(params.module)?;
+    logger.log(&BridgeableEvent::Upgrade(UpgradeEvent {
+        module: params.module,
+    }))?;
     // Call the migration function if provided.
     if let Some((func, parameters)) = params.migrate {
         host.invoke_contract_raw(
@@ -1000,40 +1419,349 @@ fn contract_upgrade<S: HasStateApi>(
     Ok(())
 }
 
+/// Migrate the state of an earlier version of the contract. Meant to be used
+/// as the `migrate` function of an upgrade. Calling it on a migrated state does
+/// nothing.
+///
+/// It rejects if:
+/// - Sender is neither the admin nor the contract itself.
+#[receive(
+    contract = "cis2-bridgeable",
+    name = "migrate",
+    error = "ContractError",
+    low_level
+)]
+fn contract_migrate<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &mut impl HasHost<S, StateApiType = S>,
+) -> ContractResult<()> {
+    let state_api = host.state().clone();
+    let mut root = state_api.lookup_entry(&[]).unwrap_abort();
+    let state = match State::<S>::deserial_with_state(&state_api, &mut root) {
+        Ok(state) => state,
+        Err(_) => {
+            // The state of earlier versions is a prefix of the current one.
+            root.seek(SeekFrom::Start(0)).unwrap_abort();
+            let old = StateV0::<S>::deserial_with_state(&state_api, &mut root)?;
+            State::from_v0(old, host.state_builder())
+        }
+    };
+
+    // Get the sender who invoked this contract function.
+    let sender = ctx.sender();
+    ensure!(
+        state.has_role(&sender, Roles::Admin) || sender == Address::Contract(ctx.self_address()),
+        ContractError::Unauthorized
+    );
+
+    root.seek(SeekFrom::Start(0)).unwrap_abort();
+    state.serial(&mut root).unwrap_abort();
+    Ok(())
+}
+
+/// Propose an admin action, which can be executed with `execute` once the
+/// governance delay has passed. Returns the id of the proposal.
+///
+/// It rejects if:
+/// - It fails to parse the parameter.
+/// - Sender is not an admin.
+#[receive(
+    contract = "cis2-bridgeable",
+    name = "propose",
+    parameter = "GovernanceAction",
+    return_value = "u64",
+    error = "ContractError",
+    enable_logger,
+    mutable
+)]
+fn contract_propose<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &mut impl HasHost<State<S>, StateApiType = S>,
+    logger: &mut impl HasLogger,
+) -> ContractResult<u64> {
+    // Parse the parameter.
+    let action: GovernanceAction = ctx.parameter_cursor().get()?;
+
+    // Get the sender who invoked this contract function.
+    let sender = ctx.sender();
+
+    let state = host.state_mut();
+    ensure!(
+        state.has_role(&sender, Roles::Admin),
+        ContractError::Unauthorized
+    );
+
+    let id = state.next_proposal_id;
+    state.next_proposal_id += 1;
+    let execute_after = ctx
+        .metadata()
+        .slot_time()
+        .checked_add(state.governance_delay)
+        .unwrap_abort();
+    state.proposals.insert(id, Proposal {
+        proposer: sender,
+        action: action.clone(),
+        execute_after,
+    });
+    logger.log(&BridgeableEvent::ProposalCreated(ProposalCreatedEvent {
+        id,
+        proposer: sender,
+        action,
+        execute_after,
+    }))?;
+    Ok(id)
+}
+
+/// Execute the proposal with the given id.
+///
+/// It rejects if:
+/// - It fails to parse the parameter.
+/// - Sender is not an admin.
+/// - There is no proposal with the id.
+/// - The governance delay of the proposal has not passed.
+/// - The action fails, e.g., a role to remove is not assigned.
+#[receive(
+    contract = "cis2-bridgeable",
+    name = "execute",
+    parameter = "u64",
+    error = "ContractError",
+    enable_logger,
+    mutable
+)]
+fn contract_execute<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &mut impl HasHost<State<S>, StateApiType = S>,
+    logger: &mut impl HasLogger,
+) -> ContractResult<()> {
+    // Parse the parameter.
+    let id: u64 = ctx.parameter_cursor().get()?;
+
+    // Get the sender who invoked this contract function.
+    let sender = ctx.sender();
+
+    let (state, state_builder) = host.state_and_builder();
+    ensure!(
+        state.has_role(&sender, Roles::Admin),
+        ContractError::Unauthorized
+    );
+
+    let proposal = match state.proposals.get(&id) {
+        Some(proposal) => proposal.clone(),
+        None => bail!(ContractError::Custom(CustomContractError::ProposalNotFound)),
+    };
+    ensure!(
+        ctx.metadata().slot_time() >= proposal.execute_after,
+        ContractError::Custom(CustomContractError::ProposalNotReady)
+    );
+    state.proposals.remove(&id);
+    logger.log(&BridgeableEvent::ProposalExecuted(ProposalEvent { id }))?;
+
+    match proposal.action {
+        GovernanceAction::SetImplementors(params) => {
+            state.set_implementors(
+                params.id.as_standard_identifier().to_owned(),
+                params.implementors.clone(),
+            );
+            logger.log(&BridgeableEvent::SetImplementors(params))?;
+        }
+        GovernanceAction::GrantRole(params) => {
+            state.grant_role(&params.address, params.role, state_builder);
+            logger.log(&BridgeableEvent::GrantRole(GrantRoleEvent {
+                address: params.address,
+                role:    params.role,
+            }))?;
+        }
+        GovernanceAction::RemoveRole(params) => {
+            ensure!(
+                state.has_role(&params.address, params.role),
+                ContractError::Custom(CustomContractError::RoleNotAssigned)
+            );
+            state.remove_role(&params.address, params.role);
+            logger.log(&BridgeableEvent::RevokeRole(RevokeRoleEvent {
+                address: params.address,
+                role:    params.role,
+            }))?;
+        }
+        GovernanceAction::SetGovernanceDelay(delay) => {
+            state.governance_delay = delay;
+            logger.log(&BridgeableEvent::SetGovernanceDelay(
+                SetGovernanceDelayEvent { delay },
+            ))?;
+        }
+        GovernanceAction::TransferAdmin(to) => {
+            let transfer = AdminTransfer {
+                from: proposal.proposer,
+                to,
+            };
+            state.admin_transfer = Some(transfer);
+            logger.log(&BridgeableEvent::AdminTransferPending(transfer))?;
+        }
+        GovernanceAction::Upgrade(params) => upgrade(ctx, host, logger, params)?,
+    }
+    Ok(())
+}
+
+/// Cancel the proposal with the given id.
+///
+/// It rejects if:
+/// - It fails to parse the parameter.
+/// - Sender is not an admin.
+/// - There is no proposal with the id.
+#[receive(
+    contract = "cis2-bridgeable",
+    name = "cancel",
+    parameter = "u64",
+    error = "ContractError",
+    enable_logger,
+    mutable
+)]
+fn contract_cancel<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &mut impl HasHost<State<S>, StateApiType = S>,
+    logger: &mut impl HasLogger,
+) -> ContractResult<()> {
+    // Parse the parameter.
+    let id: u64 = ctx.parameter_cursor().get()?;
+
+    // Get the sender who invoked this contract function.
+    let sender = ctx.sender();
+
+    let state = host.state_mut();
+    ensure!(
+        state.has_role(&sender, Roles::Admin),
+        ContractError::Unauthorized
+    );
+    ensure!(
+        state.proposals.remove_and_get(&id).is_some(),
+        ContractError::Custom(CustomContractError::ProposalNotFound)
+    );
+    logger.log(&BridgeableEvent::ProposalCancelled(ProposalEvent { id }))?;
+    Ok(())
+}
+
+/// Accept the transfer of the `Admin` role to the sender. The role is revoked
+/// from the admin that proposed the transfer.
+///
+/// It rejects if:
+/// - The pending admin transfer is not to the sender.
+#[receive(
+    contract = "cis2-bridgeable",
+    name = "acceptAdmin",
+    error = "ContractError",
+    enable_logger,
+    mutable
+)]
+fn contract_accept_admin<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &mut impl HasHost<State<S>, StateApiType = S>,
+    logger: &mut impl HasLogger,
+) -> ContractResult<()> {
+    // Get the sender who invoked this contract function.
+    let sender = ctx.sender();
+
+    let (state, state_builder) = host.state_and_builder();
+    let transfer = match state.admin_transfer {
+        Some(transfer) if transfer.to == sender => transfer,
+        _ => bail!(ContractError::Custom(CustomContractError::NoAdminTransfer)),
+    };
+    state.admin_transfer = None;
+    logger.log(&BridgeableEvent::AdminTransferAccepted(transfer))?;
+
+    state.grant_role(&transfer.to, Roles::Admin, state_builder);
+    logger.log(&BridgeableEvent::GrantRole(GrantRoleEvent {
+        address: transfer.to,
+        role:    Roles::Admin,
+    }))?;
+    if transfer.from != transfer.to && state.has_role(&transfer.from, Roles::Admin) {
+        state.remove_role(&transfer.from, Roles::Admin);
+        logger.log(&BridgeableEvent::RevokeRole(RevokeRoleEvent {
+            address: transfer.from,
+            role:    Roles::Admin,
+        }))?;
+    }
+    Ok(())
+}
+
+/// Return type of the `viewGovernance` function.
+#[derive(Serialize, SchemaType)]
+struct ViewGovernance {
+    /// Time between proposing an admin action and executing it.
+    governance_delay: Duration,
+    /// The proposals that are not yet executed or cancelled, by id.
+    proposals:        Vec<(u64, Proposal)>,
+    /// An executed transfer of the `Admin` role that is not yet accepted.
+    admin_transfer:   Option<AdminTransfer>,
+}
+
+/// View function that returns the governance delay, the pending proposals and
+/// the pending admin transfer. Meant for monitoring.
+#[receive(
+    contract = "cis2-bridgeable",
+    name = "viewGovernance",
+    return_value = "ViewGovernance"
+)]
+fn contract_view_governance<S: HasStateApi>(
+    _ctx: &impl HasReceiveContext,
+    host: &impl HasHost<State<S>, StateApiType = S>,
+) -> ReceiveResult<ViewGovernance> {
+    let state = host.state();
+
+    let proposals = state
+        .proposals
+        .iter()
+        .map(|(id, proposal)| (*id, proposal.clone()))
+        .collect();
+
+    Ok(ViewGovernance {
+        governance_delay: state.governance_delay,
+        proposals,
+        admin_transfer: state.admin_transfer,
+    })
+}
+
 /// Pause/Unpause this smart contract instance by the admin. All non-admin
 /// state-mutative functions (wrap, unwrap, transfer, updateOperator) cannot be
-/// executed when the contract is paused.
+/// executed when the contract is paused. A pauser can pause, but not unpause,
+/// the contract.
 ///
 /// It rejects if:
-/// - Sender is not the admin of the contract instance.
+/// - Sender is not the admin of the contract instance, and either not a pauser
+///   or unpausing.
 /// - It fails to parse the parameter.
 #[receive(
     contract = "cis2-bridgeable",
     name = "setPaused",
     parameter = "SetPausedParams",
     error = "ContractError",
+    enable_logger,
     mutable
 )]
 fn contract_set_paused<S: HasStateApi>(
     ctx: &impl HasReceiveContext,
     host: &mut impl HasHost<State<S>, StateApiType = S>,
+    logger: &mut impl HasLogger,
 ) -> ContractResult<()> {
     // Get the sender who invoked this contract function.
     let sender = ctx.sender();
 
+    // Parse the parameter.
+    let params: SetPausedParams = ctx.parameter_cursor().get()?;
+
     let (state, _) = host.state_and_builder();
 
-    // Check that only the admin is authorized to set implementors.
+    // Check that only the admin, or a pauser when pausing, is authorized to
+    // change the paused state.
     ensure!(
-        state.has_role(&sender, Roles::Admin),
+        state.has_role(&sender, Roles::Admin)
+            || (params.paused && state.has_role(&sender, Roles::Pauser)),
         ContractError::Unauthorized
     );
 
-    // Parse the parameter.
-    let params: SetPausedParams = ctx.parameter_cursor().get()?;
-
     // Update the paused variable.
     host.state_mut().paused = params.paused;
+    logger.log(&BridgeableEvent::SetPaused(SetPausedEvent {
+        paused: params.paused,
+    }))?;
 
     Ok(())
 }
@@ -1097,6 +1825,45 @@ fn contract_view_token_owners<S: HasStateApi>(
     Ok(ViewTokenOwners { token_owners })
 }
 
+/// View function that returns at most `limit` token owners, starting from
+/// `start`. The owners are ordered by their serialized address, so a `start`
+/// that is no longer an owner is handled as well. Meant for monitoring when
+/// there are too many owners for `viewTokenOwners`.
+///
+/// The balances and operators of the owners are not read, but every owner
+/// before `start` is still iterated over, so the energy cost grows with the
+/// number of owners before `start` as well as with `limit`.
+///
+/// It rejects if:
+/// - It fails to parse the parameter.
+#[receive(
+    contract = "cis2-bridgeable",
+    name = "viewTokenOwnersPage",
+    parameter = "ViewTokenOwnersPageParams",
+    return_value = "ViewTokenOwnersPage"
+)]
+fn contract_view_token_owners_page<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &impl HasHost<State<S>, StateApiType = S>,
+) -> ReceiveResult<ViewTokenOwnersPage> {
+    let params: ViewTokenOwnersPageParams = ctx.parameter_cursor().get()?;
+    let state = host.state();
+
+    let start = params.start.map(|address| to_bytes(&address));
+    let mut owners = state
+        .token
+        .iter()
+        .map(|x| *x.0)
+        .skip_while(|address| match &start {
+            Some(start) => to_bytes(address) < *start,
+            None => false,
+        });
+    let token_owners = owners.by_ref().take(params.limit as usize).collect();
+    let next = owners.next();
+
+    Ok(ViewTokenOwnersPage { token_owners, next })
+}
+
 // Bridge functions
 
 /// The parameter type for the contract function `hasRole`.
@@ -1140,12 +1907,22 @@ fn contract_has_role<S: HasStateApi>(
 }
 
 /// The parameter type for the contract function `grantRole`.
-#[derive(Serialize, SchemaType)]
+#[derive(Serialize, Clone)]
 pub struct GrantRoleParams {
     pub address: Address,
     pub role:    Roles,
 }
 
+/// Manual implementation of the `GrantRoleParams` schema.
+impl schema::SchemaType for GrantRoleParams {
+    fn get_type() -> schema::Type {
+        schema::Type::Struct(schema::Fields::Named(vec![
+            (String::from("address"), Address::get_type()),
+            (String::from("role"), Roles::get_type()),
+        ]))
+    }
+}
+
 /// Grant Permission to an address
 ///
 /// It rejects if:
@@ -1175,6 +1952,10 @@ fn contract_grant_role<S: HasStateApi>(
         state.has_role(&sender, Roles::Admin),
         ContractError::Unauthorized
     );
+    ensure!(
+        !state.has_governance_delay(),
+        ContractError::Custom(CustomContractError::GovernanceDelayActive)
+    );
 
     state.grant_role(&params.address, params.role, state_builder);
     // Log event for grant role.
@@ -1186,12 +1967,22 @@ fn contract_grant_role<S: HasStateApi>(
 }
 
 /// The parameter type for the contract function `removeRole`.
-#[derive(Serialize, SchemaType)]
+#[derive(Serialize, Clone)]
 pub struct RemoveRoleParams {
     pub address: Address,
     pub role:    Roles,
 }
 
+/// Manual implementation of the `RemoveRoleParams` schema.
+impl schema::SchemaType for RemoveRoleParams {
+    fn get_type() -> schema::Type {
+        schema::Type::Struct(schema::Fields::Named(vec![
+            (String::from("address"), Address::get_type()),
+            (String::from("role"), Roles::get_type()),
+        ]))
+    }
+}
+
 /// Remove Permission to an address
 ///
 /// It rejects if:
@@ -1222,6 +2013,10 @@ fn contract_remove_role<S: HasStateApi>(
         state.has_role(&sender, Roles::Admin),
         ContractError::Unauthorized
     );
+    ensure!(
+        !state.has_governance_delay(),
+        ContractError::Custom(CustomContractError::GovernanceDelayActive)
+    );
 
     ensure!(
         state.has_role(&params.address, params.role),
@@ -1238,11 +2033,36 @@ fn contract_remove_role<S: HasStateApi>(
 }
 
 /// The parameter type for the contract function `deposit`.
-#[derive(Serialize, SchemaType)]
+#[derive(Serial, SchemaType)]
 pub struct DepositParams {
     pub address:  Address,
     pub amount:   ContractTokenAmount,
     pub token_id: TokenIdU64,
+    /// Entrypoint to invoke if `address` is a contract.
+    pub hook:     Option<DepositHook>,
+}
+
+/// The hook is the last field of the parameter. Bridge managers that predate
+/// hooks do not send it, so the end of the parameter is read as no hook.
+impl Deserial for DepositParams {
+    fn deserial<R: Read>(source: &mut R) -> ParseResult<Self> {
+        let address = source.get()?;
+        let amount = source.get()?;
+        let token_id = source.get()?;
+        let mut tag = [0u8; 1];
+        let hook = match source.read(&mut tag) {
+            Ok(0) => None,
+            Ok(_) if tag[0] == 0 => None,
+            Ok(_) if tag[0] == 1 => Some(source.get()?),
+            _ => return Err(ParseError::default()),
+        };
+        Ok(Self {
+            address,
+            amount,
+            token_id,
+            hook,
+        })
+    }
 }
 #[receive(
     contract = "cis2-bridgeable",
@@ -1282,6 +2102,38 @@ fn contract_deposit<S: HasStateApi>(
         owner:    params.address,
     })))?;
 
+    // If the receiver is a contract with a hook, we invoke it. A rejecting
+    // hook must not block the bridge, so the minted tokens are kept. Tokens
+    // minted to a contract that does not exist would be lost, so in that case
+    // the deposit is rejected.
+    if let (Address::Contract(contract), Some(hook)) = (params.address, params.hook) {
+        let parameter = OnReceivingCis2Params {
+            token_id: TOKEN_ID,
+            amount:   params.amount,
+            from:     sender,
+            data:     AdditionalData::from(hook.data),
+        };
+        let accepted = match host.invoke_contract(
+            &contract,
+            &parameter,
+            hook.entrypoint.as_entrypoint_name(),
+            Amount::zero(),
+        ) {
+            Ok(_) => true,
+            Err(CallContractError::MissingContract) => {
+                bail!(ContractError::Custom(
+                    CustomContractError::InvokeContractError
+                ))
+            }
+            Err(_) => false,
+        };
+        logger.log(&BridgeableEvent::DepositHook(DepositHookEvent {
+            contract,
+            entrypoint: hook.entrypoint,
+            accepted,
+        }))?;
+    }
+
     Ok(())
 }
 
@@ -1514,6 +2366,7 @@ mod tests {
             address:  ADDRESS_2,
             amount:   token_amount(20),
             token_id: TokenIdU64(0),
+            hook:     None,
         };
         let deposit_param_bytes = to_bytes(&deposit_param);
 
@@ -1528,6 +2381,7 @@ mod tests {
             address:  ADDRESS_1,
             amount:   token_amount(10),
             token_id: TokenIdU64(0),
+            hook:     None,
         };
         let deposit_param_bytes = to_bytes(&deposit_param);
 
@@ -1560,6 +2414,27 @@ mod tests {
             concordium_std::Address::Account(ACCOUNT_2),
             "ACCOUNT_2 should be a token owner."
         );
+
+        // Check the `view_token_owners_page` function, one owner at a time.
+        let mut start = None;
+        let mut pages = Vec::new();
+        loop {
+            let parameter_bytes = to_bytes(&ViewTokenOwnersPageParams { start, limit: 1 });
+            let mut ctx = TestReceiveContext::empty();
+            ctx.set_parameter(&parameter_bytes);
+            let page = contract_view_token_owners_page(&ctx, &host)
+                .expect_report("Calling contract_view_token_owners_page expected to succeed");
+            pages.push(page.token_owners);
+            start = page.next;
+            if start.is_none() {
+                break;
+            }
+        }
+        claim_eq!(
+            pages,
+            vec![vec![token_owners[0]], vec![token_owners[1]]],
+            "Each page should contain one token owner."
+        );
     }
 
     /// Test `view_roles` function displays the `roles` content of the state.
@@ -2002,10 +2877,16 @@ mod tests {
 
         host.setup_mock_upgrade(new_module_ref, Ok(()));
         host.setup_mock_entrypoint(self_address, migration_entrypoint, MockFn::returning_ok(()));
+        let mut logger = TestLogger::init();
 
-        let result: ContractResult<()> = contract_upgrade(&ctx, &mut host);
+        let result: ContractResult<()> = contract_upgrade(&ctx, &mut host, &mut logger);
 
         claim_eq!(result, Ok(()));
+        claim_eq!(logger.logs, vec![to_bytes(&BridgeableEvent::Upgrade(
+            UpgradeEvent {
+                module: new_module_ref,
+            }
+        ))]);
     }
 
     #[concordium_test]
@@ -2030,8 +2911,9 @@ mod tests {
         let mut host = TestHost::new(state, state_builder);
 
         host.setup_mock_upgrade(new_module_ref, Err(UpgradeError::MissingModule));
+        let mut logger = TestLogger::init();
 
-        let result: ContractResult<()> = contract_upgrade(&ctx, &mut host);
+        let result: ContractResult<()> = contract_upgrade(&ctx, &mut host, &mut logger);
 
         claim_eq!(
             result,
@@ -2040,6 +2922,150 @@ mod tests {
             ))
         );
     }
+    /// Test that role changes are proposed and executed once there is a
+    /// governance delay.
+    #[concordium_test]
+    fn test_governance() {
+        let mut logger = TestLogger::init();
+        let mut builder = TestStateBuilder::new();
+        let state = initial_state(&mut builder);
+        let mut host = TestHost::new(state, builder);
+        let mut ctx = TestReceiveContext::empty();
+        ctx.set_sender(ADDRESS_0);
+        ctx.set_metadata_slot_time(Timestamp::from_timestamp_millis(1_000));
+
+        // Without a delay the proposal can be executed right away.
+        let parameter_bytes = to_bytes(&GovernanceAction::SetGovernanceDelay(
+            Duration::from_millis(100),
+        ));
+        ctx.set_parameter(&parameter_bytes);
+        claim_eq!(contract_propose(&ctx, &mut host, &mut logger), Ok(0));
+        let parameter_bytes = to_bytes(&0u64);
+        ctx.set_parameter(&parameter_bytes);
+        claim_eq!(contract_execute(&ctx, &mut host, &mut logger), Ok(()));
+        claim_eq!(host.state().governance_delay, Duration::from_millis(100));
+        claim_eq!(
+            contract_execute(&ctx, &mut host, &mut logger),
+            Err(ContractError::Custom(CustomContractError::ProposalNotFound)),
+            "A proposal is executed once"
+        );
+
+        // Roles can no longer be granted directly.
+        let grant = GrantRoleParams {
+            address: ADDRESS_1,
+            role:    Roles::Manager,
+        };
+        let parameter_bytes = to_bytes(&grant);
+        ctx.set_parameter(&parameter_bytes);
+        claim_eq!(
+            contract_grant_role(&ctx, &mut host, &mut logger),
+            Err(ContractError::Custom(
+                CustomContractError::GovernanceDelayActive
+            ))
+        );
+
+        // Only admins propose.
+        let parameter_bytes = to_bytes(&GovernanceAction::GrantRole(grant.clone()));
+        ctx.set_parameter(&parameter_bytes);
+        ctx.set_sender(ADDRESS_1);
+        claim_eq!(
+            contract_propose(&ctx, &mut host, &mut logger),
+            Err(ContractError::Unauthorized)
+        );
+        ctx.set_sender(ADDRESS_0);
+        logger.logs.clear();
+        claim_eq!(contract_propose(&ctx, &mut host, &mut logger), Ok(1));
+        claim_eq!(logger.logs, vec![to_bytes(
+            &BridgeableEvent::ProposalCreated(ProposalCreatedEvent {
+                id:            1,
+                proposer:      ADDRESS_0,
+                action:        GovernanceAction::GrantRole(grant.clone()),
+                execute_after: Timestamp::from_timestamp_millis(1_100),
+            })
+        )]);
+
+        // The proposal is executed after the delay.
+        let parameter_bytes = to_bytes(&1u64);
+        ctx.set_parameter(&parameter_bytes);
+        ctx.set_metadata_slot_time(Timestamp::from_timestamp_millis(1_099));
+        claim_eq!(
+            contract_execute(&ctx, &mut host, &mut logger),
+            Err(ContractError::Custom(CustomContractError::ProposalNotReady))
+        );
+        ctx.set_metadata_slot_time(Timestamp::from_timestamp_millis(1_100));
+        logger.logs.clear();
+        claim_eq!(contract_execute(&ctx, &mut host, &mut logger), Ok(()));
+        claim!(host.state().has_role(&ADDRESS_1, Roles::Manager));
+        claim_eq!(logger.logs, vec![
+            to_bytes(&BridgeableEvent::ProposalExecuted(ProposalEvent { id: 1 })),
+            to_bytes(&BridgeableEvent::GrantRole(GrantRoleEvent {
+                address: ADDRESS_1,
+                role:    Roles::Manager,
+            })),
+        ]);
+
+        // A cancelled proposal cannot be executed.
+        let parameter_bytes = to_bytes(&GovernanceAction::RemoveRole(RemoveRoleParams {
+            address: ADDRESS_1,
+            role:    Roles::Manager,
+        }));
+        ctx.set_parameter(&parameter_bytes);
+        claim_eq!(contract_propose(&ctx, &mut host, &mut logger), Ok(2));
+        let parameter_bytes = to_bytes(&2u64);
+        ctx.set_parameter(&parameter_bytes);
+        claim_eq!(contract_cancel(&ctx, &mut host, &mut logger), Ok(()));
+        ctx.set_metadata_slot_time(Timestamp::from_timestamp_millis(10_000));
+        claim_eq!(
+            contract_execute(&ctx, &mut host, &mut logger),
+            Err(ContractError::Custom(CustomContractError::ProposalNotFound))
+        );
+        claim!(host.state().has_role(&ADDRESS_1, Roles::Manager));
+    }
+
+    /// Test the two-step transfer of the `Admin` role.
+    #[concordium_test]
+    fn test_admin_transfer() {
+        let mut logger = TestLogger::init();
+        let mut builder = TestStateBuilder::new();
+        let state = initial_state(&mut builder);
+        let mut host = TestHost::new(state, builder);
+        let mut ctx = TestReceiveContext::empty();
+        ctx.set_sender(ADDRESS_0);
+        ctx.set_metadata_slot_time(Timestamp::from_timestamp_millis(1_000));
+
+        let parameter_bytes = to_bytes(&GovernanceAction::TransferAdmin(ADDRESS_1));
+        ctx.set_parameter(&parameter_bytes);
+        claim_eq!(contract_propose(&ctx, &mut host, &mut logger), Ok(0));
+
+        // Nothing to accept before the transfer is executed.
+        ctx.set_sender(ADDRESS_1);
+        claim_eq!(
+            contract_accept_admin(&ctx, &mut host, &mut logger),
+            Err(ContractError::Custom(CustomContractError::NoAdminTransfer))
+        );
+
+        ctx.set_sender(ADDRESS_0);
+        let parameter_bytes = to_bytes(&0u64);
+        ctx.set_parameter(&parameter_bytes);
+        claim_eq!(contract_execute(&ctx, &mut host, &mut logger), Ok(()));
+        claim!(
+            host.state().has_role(&ADDRESS_0, Roles::Admin),
+            "The role is kept until the transfer is accepted"
+        );
+
+        // Only the new admin can accept.
+        ctx.set_sender(ADDRESS_2);
+        claim_eq!(
+            contract_accept_admin(&ctx, &mut host, &mut logger),
+            Err(ContractError::Custom(CustomContractError::NoAdminTransfer))
+        );
+        ctx.set_sender(ADDRESS_1);
+        claim_eq!(contract_accept_admin(&ctx, &mut host, &mut logger), Ok(()));
+        claim!(host.state().has_role(&ADDRESS_1, Roles::Admin));
+        claim!(!host.state().has_role(&ADDRESS_0, Roles::Admin));
+        claim!(host.state().admin_transfer.is_none());
+    }
+
     /// Test adding an operator succeeds and the appropriate event is logged.
     #[concordium_test]
     fn test_roles() {
@@ -2181,6 +3207,7 @@ mod tests {
             address:  ADDRESS_2,
             amount:   token_amount(20),
             token_id: TokenIdU64(0),
+            hook:     None,
         };
         let deposit_param_bytes = to_bytes(&deposit_param);
         ctx.set_sender(ADDRESS_0);
@@ -2215,6 +3242,107 @@ mod tests {
         )
     }
 
+    /// Test that a deposit to a contract invokes the hook, and that the tokens
+    /// are minted whether or not the hook accepts them.
+    #[concordium_test]
+    fn test_deposit_hook() {
+        let mut ctx = TestReceiveContext::empty();
+        ctx.set_sender(ADDRESS_1);
+
+        // Set up the state and host, with ADDRESS_1 as the manager.
+        let mut state_builder = TestStateBuilder::new();
+        let mut state = initial_state(&mut state_builder);
+        state.grant_role(&ADDRESS_1, Roles::Manager, &mut state_builder);
+        let mut host = TestHost::new(state, state_builder);
+        let mut logger = TestLogger::init();
+
+        let receiver = ContractAddress::new(3, 0);
+        let accepting = OwnedEntrypointName::new_unchecked("accept".into());
+        let rejecting = OwnedEntrypointName::new_unchecked("reject".into());
+        host.setup_mock_entrypoint(
+            receiver,
+            accepting.clone(),
+            MockFn::new_v1(
+                |parameter, _amount, _balance, _state: &mut State<TestStateApi>| {
+                    let params = from_bytes::<
+                        OnReceivingCis2Params<ContractTokenId, ContractTokenAmount>,
+                    >(parameter.0)
+                    .unwrap();
+                    claim_eq!(params.amount, token_amount(20), "Amount should be correct");
+                    claim_eq!(params.from, ADDRESS_1, "Sender should be the manager");
+                    claim_eq!(
+                        params.data.as_ref(),
+                        &[1u8, 2, 3][..],
+                        "Data should be passed on"
+                    );
+                    Ok((false, ()))
+                },
+            ),
+        );
+        host.setup_mock_entrypoint(
+            receiver,
+            rejecting.clone(),
+            MockFn::returning_err::<()>(CallContractError::Trap),
+        );
+
+        for (entrypoint, accepted) in [(accepting, true), (rejecting, false)] {
+            let deposit_param = DepositParams {
+                address:  Address::Contract(receiver),
+                amount:   token_amount(20),
+                token_id: TokenIdU64(0),
+                hook:     Some(DepositHook {
+                    entrypoint: entrypoint.clone(),
+                    data:       vec![1, 2, 3],
+                }),
+            };
+            let deposit_param_bytes = to_bytes(&deposit_param);
+            let mut ctx = TestReceiveContext::empty();
+            ctx.set_sender(ADDRESS_1);
+            ctx.set_parameter(&deposit_param_bytes);
+            logger.logs.clear();
+            let result = contract_deposit(&ctx, &mut host, &mut logger);
+            claim!(result.is_ok(), "The deposit should succeed");
+            claim_eq!(
+                logger.logs.len(),
+                2,
+                "Mint and hook events should be logged"
+            );
+            claim_eq!(
+                logger.logs[1],
+                to_bytes(&BridgeableEvent::DepositHook(DepositHookEvent {
+                    contract: receiver,
+                    entrypoint,
+                    accepted,
+                })),
+                "Incorrect event emitted"
+            );
+        }
+
+        // The tokens of both deposits are minted.
+        let balance = host
+            .state()
+            .balance(&TOKEN_ID, &Address::Contract(receiver))
+            .expect_report("Token is expected to exist");
+        claim_eq!(balance, token_amount(40));
+    }
+
+    /// Test that a deposit serialized without the hook is accepted.
+    #[concordium_test]
+    fn test_deposit_params_without_hook() {
+        let deposit_param = DepositParams {
+            address:  ADDRESS_2,
+            amount:   token_amount(20),
+            token_id: TokenIdU64(0),
+            hook:     None,
+        };
+        let mut bytes = to_bytes(&deposit_param);
+        // Drop the `None` tag of the hook.
+        bytes.pop();
+        let params = from_bytes::<DepositParams>(&bytes).expect_report("Parsing should succeed");
+        claim!(params.hook.is_none(), "No hook should be parsed");
+        claim_eq!(params.amount, token_amount(20));
+    }
+
     /// Test adding an operator succeeds and the appropriate event is logged.
     #[concordium_test]
     fn test_withdraw() {
@@ -2258,6 +3386,7 @@ mod tests {
             address:  ADDRESS_2,
             amount:   token_amount(20),
             token_id: TokenIdU64(0),
+            hook:     None,
         };
         let deposit_param_bytes = to_bytes(&deposit_param);
 
@@ -2321,15 +3450,60 @@ mod tests {
         let mut state_builder = TestStateBuilder::new();
         let state = initial_state(&mut state_builder);
         let mut host = TestHost::new(state, state_builder);
+        let mut logger = TestLogger::init();
 
         // Call the contract function.
-        let result: ContractResult<()> = contract_set_paused(&ctx, &mut host);
+        let result: ContractResult<()> = contract_set_paused(&ctx, &mut host, &mut logger);
 
         // Check the result.
         claim!(result.is_ok(), "Results in rejection");
 
         // Check contract is paused.
         claim_eq!(host.state().paused, true, "Smart contract should be paused");
+        claim_eq!(
+            logger.logs,
+            vec![to_bytes(&BridgeableEvent::SetPaused(SetPausedEvent {
+                paused: true,
+            }))],
+            "Pausing should be logged"
+        );
+    }
+
+    /// Test that a pauser can pause, but not unpause, the contract.
+    #[concordium_test]
+    fn test_pauser_can_only_pause() {
+        // Set up the context.
+        let mut ctx = TestReceiveContext::empty();
+        ctx.set_sender(ADDRESS_1);
+
+        // Set up the state and host, with ADDRESS_1 as a pauser.
+        let mut state_builder = TestStateBuilder::new();
+        let mut state = initial_state(&mut state_builder);
+        state.grant_role(&ADDRESS_1, Roles::Pauser, &mut state_builder);
+        let mut host = TestHost::new(state, state_builder);
+        let mut logger = TestLogger::init();
+
+        // The pauser pauses the contract.
+        let parameter_bytes = to_bytes(&true);
+        ctx.set_parameter(&parameter_bytes);
+        let result: ContractResult<()> = contract_set_paused(&ctx, &mut host, &mut logger);
+        claim!(result.is_ok(), "Results in rejection");
+        claim_eq!(host.state().paused, true, "Smart contract should be paused");
+
+        // The pauser cannot unpause it.
+        let parameter_bytes = to_bytes(&false);
+        ctx.set_parameter(&parameter_bytes);
+        let result: ContractResult<()> = contract_set_paused(&ctx, &mut host, &mut logger);
+        claim_eq!(
+            result,
+            Err(ContractError::Unauthorized),
+            "Unpausing should fail because the sender is only a pauser"
+        );
+        claim_eq!(
+            host.state().paused,
+            true,
+            "Smart contract should stay paused"
+        );
     }
 
     /// Test unpausing the contract.
@@ -2347,9 +3521,10 @@ mod tests {
         let mut state_builder = TestStateBuilder::new();
         let state = initial_state(&mut state_builder);
         let mut host = TestHost::new(state, state_builder);
+        let mut logger = TestLogger::init();
 
         // Call the contract function.
-        let result: ContractResult<()> = contract_set_paused(&ctx, &mut host);
+        let result: ContractResult<()> = contract_set_paused(&ctx, &mut host, &mut logger);
 
         // Check the result.
         claim!(result.is_ok(), "Results in rejection");
@@ -2362,7 +3537,7 @@ mod tests {
         ctx.set_parameter(&parameter_bytes);
 
         // Call the contract function.
-        let result: ContractResult<()> = contract_set_paused(&ctx, &mut host);
+        let result: ContractResult<()> = contract_set_paused(&ctx, &mut host, &mut logger);
 
         // Check the result.
         claim!(result.is_ok(), "Results in rejection");
@@ -2391,9 +3566,10 @@ mod tests {
         let mut state_builder = TestStateBuilder::new();
         let state = initial_state(&mut state_builder);
         let mut host = TestHost::new(state, state_builder);
+        let mut logger = TestLogger::init();
 
         // Call the contract function.
-        let result: ContractResult<()> = contract_set_paused(&ctx, &mut host);
+        let result: ContractResult<()> = contract_set_paused(&ctx, &mut host, &mut logger);
 
         // Check that invoke failed.
         claim_eq!(
@@ -2419,9 +3595,10 @@ mod tests {
         let mut state_builder = TestStateBuilder::new();
         let state = initial_state(&mut state_builder);
         let mut host = TestHost::new(state, state_builder);
+        let mut logger = TestLogger::init();
 
         // Call the contract function.
-        let result: ContractResult<()> = contract_set_paused(&ctx, &mut host);
+        let result: ContractResult<()> = contract_set_paused(&ctx, &mut host, &mut logger);
 
         // Check the result.
         claim!(result.is_ok(), "Results in rejection");
@@ -2429,8 +3606,6 @@ mod tests {
         // Check contract is paused.
         claim_eq!(host.state().paused, true, "Smart contract should be paused");
 
-        let mut logger = TestLogger::init();
-
         // Call the `transfer` function.
         let result: ContractResult<()> = contract_transfer(&ctx, &mut host, &mut logger);
 
@@ -0,0 +1,245 @@
+//! Administration of deployed bridge contracts. Each command dry runs the
+//! contract update to estimate its energy before signing it, and only sends
+//! it if the deployer is not in dry run mode.
+use crate::{
+    contracts::{
+        BlockedAddress, BridgeGrantRoleParams, BridgeRoles, CIS2BridgeableGrantRoleParams,
+        CIS2BridgeableRoles, ContractKind, CorrectionParams, SetBlockedParams, SetTokenFeeParams,
+        TokenFee,
+    },
+    deployer::Deployer,
+    get_wasm_module, DeployError,
+};
+use concordium_rust_sdk::{
+    cis2,
+    common::types::Amount,
+    id::types::AccountAddress,
+    smart_contracts::common::{
+        schema::VersionedModuleSchema, Address, OwnedEntrypointName, OwnedParameter,
+    },
+    types::ContractAddress,
+};
+use std::path::Path;
+
+/// Roles of the bridge contracts. Not every role exists on every contract.
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+pub enum Role {
+    Admin,
+    Mapper,
+    StateSyncer,
+    Manager,
+    Pauser,
+    Blocklister,
+}
+
+/// Grant or revoke a role on either contract.
+pub async fn update_role(
+    deployer: &Deployer,
+    contract: ContractAddress,
+    address: Address,
+    role: Role,
+    grant: bool,
+) -> Result<(), DeployError> {
+    let kind = deployer.contract_kind(contract).await?;
+    let method = kind.method(if grant { "grantRole" } else { "removeRole" });
+    match kind {
+        ContractKind::BridgeManager => {
+            let role = match role {
+                Role::Admin => BridgeRoles::Admin,
+                Role::Mapper => BridgeRoles::Mapper,
+                Role::StateSyncer => BridgeRoles::StateSyncer,
+                Role::Pauser => BridgeRoles::Pauser,
+                Role::Blocklister => BridgeRoles::Blocklister,
+                Role::Manager => {
+                    return Err(anyhow::anyhow!("The bridge manager has no {role:?} role.").into())
+                }
+            };
+            let params = BridgeGrantRoleParams { address, role };
+            deployer.send_update(contract, &method, &params).await
+        }
+        ContractKind::CIS2Bridgeable => {
+            let role = match role {
+                Role::Admin => CIS2BridgeableRoles::Admin,
+                Role::Manager => CIS2BridgeableRoles::Manager,
+                Role::Pauser => CIS2BridgeableRoles::Pauser,
+                Role::Mapper | Role::StateSyncer | Role::Blocklister => {
+                    return Err(anyhow::anyhow!("The token has no {role:?} role.").into())
+                }
+            };
+            let params = CIS2BridgeableGrantRoleParams { address, role };
+            deployer.send_update(contract, &method, &params).await
+        }
+    }
+}
+
+/// Upgrade either contract to the module in the given file, deploying the
+/// module if needed, and optionally call a migration entrypoint of the new
+/// module.
+pub async fn upgrade(
+    deployer: &Deployer,
+    contract: ContractAddress,
+    module_source: &Path,
+    migrate: Option<(OwnedEntrypointName, OwnedParameter)>,
+) -> Result<(), DeployError> {
+    let kind = deployer.contract_kind(contract).await?;
+    let wasm_module = get_wasm_module(module_source)?;
+    let module_ref = if deployer.dry_run {
+        // The module must already be deployed for the dry run to succeed.
+        wasm_module.get_module_ref()
+    } else {
+        println!("Deploying {}....", kind.name());
+        deployer.deploy_wasm_module(wasm_module).await?.module_ref
+    };
+    println!(
+        "Upgrading {} at {contract} to {module_ref}....",
+        kind.name()
+    );
+    deployer
+        .upgrade_contract(module_ref, contract, &kind.method("upgrade"), migrate)
+        .await
+}
+
+/// Set the withdraw fee, or the claim fee, of the bridge manager.
+pub async fn set_fee(
+    deployer: &Deployer,
+    contract: ContractAddress,
+    amount: Amount,
+    claim: bool,
+) -> Result<(), DeployError> {
+    let method = ContractKind::BridgeManager.method(if claim {
+        "setClaimFee"
+    } else {
+        "setWithdrawFee"
+    });
+    deployer.send_update(contract, &method, &amount).await
+}
+
+/// Set the fee for withdrawing a token on the bridge manager, which is taken in
+/// the token.
+pub async fn set_token_fee(
+    deployer: &Deployer,
+    contract: ContractAddress,
+    token: ContractAddress,
+    fee: TokenFee<cis2::TokenAmount>,
+) -> Result<(), DeployError> {
+    let method = ContractKind::BridgeManager.method("setTokenFee");
+    let params = SetTokenFeeParams { token, fee };
+    deployer.send_update(contract, &method, &params).await
+}
+
+/// Propose to mint tokens to, or burn tokens from, an address on the bridge
+/// manager to correct the supply of a token after an incident. The proposal is
+/// executed with [`execute`] once its delay has passed.
+pub async fn correct(
+    deployer: &Deployer,
+    contract: ContractAddress,
+    params: CorrectionParams<cis2::TokenAmount>,
+    mint: bool,
+) -> Result<(), DeployError> {
+    let method = ContractKind::BridgeManager.method(if mint {
+        "correctiveMint"
+    } else {
+        "correctiveBurn"
+    });
+    deployer.send_update(contract, &method, &params).await
+}
+
+/// Set the treasurer of the bridge manager.
+pub async fn set_treasurer(
+    deployer: &Deployer,
+    contract: ContractAddress,
+    account: AccountAddress,
+) -> Result<(), DeployError> {
+    let method = ContractKind::BridgeManager.method("setTreasurer");
+    deployer.send_update(contract, &method, &account).await
+}
+
+/// Add an address to, or remove it from, the blocklist of the bridge manager.
+/// This requires the blocklister role.
+pub async fn set_blocked(
+    deployer: &Deployer,
+    contract: ContractAddress,
+    address: BlockedAddress,
+    blocked: bool,
+) -> Result<(), DeployError> {
+    let method = ContractKind::BridgeManager.method("setBlocked");
+    let params = SetBlockedParams { address, blocked };
+    deployer.send_update(contract, &method, &params).await
+}
+
+/// Execute a proposal of an admin action on either contract, once its delay
+/// has passed.
+pub async fn execute(
+    deployer: &Deployer,
+    contract: ContractAddress,
+    id: u64,
+) -> Result<(), DeployError> {
+    let kind = deployer.contract_kind(contract).await?;
+    deployer
+        .send_update(contract, &kind.method("execute"), &id)
+        .await
+}
+
+/// Cancel a proposal of an admin action on either contract.
+pub async fn cancel(
+    deployer: &Deployer,
+    contract: ContractAddress,
+    id: u64,
+) -> Result<(), DeployError> {
+    let kind = deployer.contract_kind(contract).await?;
+    deployer
+        .send_update(contract, &kind.method("cancel"), &id)
+        .await
+}
+
+/// Accept a transfer of the `Admin` role of either contract to the sender.
+pub async fn accept_admin(
+    deployer: &Deployer,
+    contract: ContractAddress,
+) -> Result<(), DeployError> {
+    let kind = deployer.contract_kind(contract).await?;
+    deployer
+        .send_update(contract, &kind.method("acceptAdmin"), &())
+        .await
+}
+
+/// Pause or unpause either contract.
+pub async fn set_paused(
+    deployer: &Deployer,
+    contract: ContractAddress,
+    paused: bool,
+) -> Result<(), DeployError> {
+    let kind = deployer.contract_kind(contract).await?;
+    deployer
+        .send_update(contract, &kind.method("setPaused"), &paused)
+        .await
+}
+
+/// Invoke a view function of either contract and print its return value, as
+/// JSON if the schema of the module is given and as hex otherwise.
+pub async fn view(
+    deployer: &Deployer,
+    contract: ContractAddress,
+    entrypoint: &str,
+    parameter: Vec<u8>,
+    schema: Option<&Path>,
+) -> Result<(), DeployError> {
+    let kind = deployer.contract_kind(contract).await?;
+    let return_value = deployer
+        .view(contract, &kind.method(entrypoint), parameter)
+        .await?;
+    match schema {
+        Some(schema) => {
+            let schema = VersionedModuleSchema::new(&std::fs::read(schema)?, &None)
+                .map_err(|e| anyhow::anyhow!("Invalid schema: {e}"))?;
+            let json = schema
+                .get_receive_return_value_schema(kind.name(), entrypoint)
+                .map_err(|e| anyhow::anyhow!("No return value schema for {entrypoint}: {e}"))?
+                .to_json_string_pretty(&return_value)
+                .map_err(|_| anyhow::anyhow!("The return value does not match the schema."))?;
+            println!("{json}");
+        }
+        None => println!("{}", hex::encode(return_value)),
+    }
+    Ok(())
+}
@@ -1,10 +1,14 @@
+pub use bridge_types::{
+    BlockedAddress, CorrectionParams, EthAddress, GrantRoleParams as BridgeGrantRoleParams,
+    Roles as BridgeRoles, SetBlockedParams, SetTokenFeeParams, TokenFee, UpgradeParams,
+};
 use concordium_rust_sdk::types::smart_contracts::concordium_contracts_common::{
-    Address, ModuleReference, OwnedEntrypointName, OwnedParameter, Serial, Write,
+    Address, Serial, Write,
 };
 
 #[derive(Debug)]
 pub struct CIS2BridgeableInitParams {
-    pub url:  String,
+    pub url: String,
     pub hash: Option<[u8; 32]>,
 }
 
@@ -17,41 +21,10 @@ impl Serial for CIS2BridgeableInitParams {
     }
 }
 
-#[derive(Debug)]
-pub struct BridgeGrantRoleParams {
-    pub address: Address,
-    pub role:    BridgeRoles,
-}
-
-impl Serial for BridgeGrantRoleParams {
-    fn serial<W: Write>(&self, out: &mut W) -> Result<(), W::Err> {
-        self.address.serial(out)?;
-        self.role.serial(out)?;
-        Ok(())
-    }
-}
-
-#[derive(Debug, Clone)]
-pub enum BridgeRoles {
-    Admin,
-    Mapper,
-    StateSyncer,
-}
-
-impl Serial for BridgeRoles {
-    fn serial<W: Write>(&self, out: &mut W) -> Result<(), W::Err> {
-        match self {
-            BridgeRoles::Admin => out.write_u8(0),
-            BridgeRoles::Mapper => out.write_u8(1),
-            BridgeRoles::StateSyncer => out.write_u8(2),
-        }
-    }
-}
-
 #[derive(Debug)]
 pub struct CIS2BridgeableGrantRoleParams {
     pub address: Address,
-    pub role:    CIS2BridgeableRoles,
+    pub role: CIS2BridgeableRoles,
 }
 
 impl Serial for CIS2BridgeableGrantRoleParams {
@@ -66,6 +39,7 @@ impl Serial for CIS2BridgeableGrantRoleParams {
 pub enum CIS2BridgeableRoles {
     Admin,
     Manager,
+    Pauser,
 }
 
 impl Serial for CIS2BridgeableRoles {
@@ -73,22 +47,40 @@ impl Serial for CIS2BridgeableRoles {
         match self {
             CIS2BridgeableRoles::Admin => out.write_u8(0),
             CIS2BridgeableRoles::Manager => out.write_u8(1),
+            CIS2BridgeableRoles::Pauser => out.write_u8(2),
         }
     }
 }
 
-#[derive(Debug)]
-pub struct UpgradeParams {
-    /// The new module reference.
-    pub module:  ModuleReference,
-    /// Optional entrypoint to call in the new module after upgrade.
-    pub migrate: Option<(OwnedEntrypointName, OwnedParameter)>,
+/// The contracts of the bridge, as identified by the name of an instance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContractKind {
+    BridgeManager,
+    CIS2Bridgeable,
 }
 
-impl Serial for UpgradeParams {
-    fn serial<W: Write>(&self, out: &mut W) -> Result<(), W::Err> {
-        self.module.serial(out)?;
-        self.migrate.serial(out)?;
-        Ok(())
+impl ContractKind {
+    /// The kind of the contract with the given name, if it is part of the
+    /// bridge.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "bridge-manager" => Some(ContractKind::BridgeManager),
+            "cis2-bridgeable" => Some(ContractKind::CIS2Bridgeable),
+            _ => None,
+        }
+    }
+
+    /// The name of the contract.
+    pub fn name(self) -> &'static str {
+        match self {
+            ContractKind::BridgeManager => "bridge-manager",
+            ContractKind::CIS2Bridgeable => "cis2-bridgeable",
+        }
+    }
+
+    /// The full name of an entrypoint of the contract, e.g.,
+    /// `bridge-manager.setPaused`.
+    pub fn method(self, entrypoint: &str) -> String {
+        format!("{}.{entrypoint}", self.name())
     }
 }
@@ -2,13 +2,12 @@ use concordium_rust_sdk::{
     common::types::{Amount, TransactionTime},
     id::types::AccountAddress,
     smart_contracts::common::{
-        self as contracts_common, Address, ModuleReference, OwnedContractName, OwnedReceiveName,
+        self as contracts_common, Address, ModuleReference, OwnedContractName, OwnedEntrypointName,
+        OwnedParameter, OwnedReceiveName, Serial,
     },
     types::{
         queries::AccountNonceResponse,
-        smart_contracts::{
-            ContractContext, InvokeContractResult, ModuleRef, Parameter, WasmModule,
-        },
+        smart_contracts::{ContractContext, InvokeContractResult, WasmModule},
         transactions::{
             self,
             send::{deploy_module, init_contract, GivenEnergy},
@@ -24,7 +23,7 @@ use std::{path::Path, str::FromStr};
 use crate::{
     contracts::{
         BridgeGrantRoleParams, BridgeRoles, CIS2BridgeableGrantRoleParams,
-        CIS2BridgeableInitParams, CIS2BridgeableRoles, UpgradeParams,
+        CIS2BridgeableInitParams, CIS2BridgeableRoles, ContractKind, UpgradeParams,
     },
     DeployError,
 };
@@ -38,7 +37,7 @@ const CIS2_INIT_METHOD: &str = "init_cis2-bridgeable";
 
 #[derive(Clone, Debug)]
 pub struct ModuleDeployed {
-    pub module_ref: ModuleRef,
+    pub module_ref: ModuleReference,
 }
 
 #[derive(Clone, Debug)]
@@ -50,18 +49,107 @@ pub struct ContractInitialized {
 pub struct Deployer {
     pub client: v2::Client,
     pub manager_key: WalletAccount,
+    /// Only dry run contract updates, without sending them.
+    pub dry_run: bool,
 }
 
 impl Deployer {
-    pub fn new(client: v2::Client, wallet_account_file: &Path) -> Result<Deployer, DeployError> {
+    pub fn new(
+        client: v2::Client,
+        wallet_account_file: &Path,
+        dry_run: bool,
+    ) -> Result<Deployer, DeployError> {
         let key_data = WalletAccount::from_json_file(wallet_account_file)?;
 
         Ok(Deployer {
             client,
             manager_key: key_data,
+            dry_run,
         })
     }
 
+    /// The kind of the given contract, determined from the name of the
+    /// instance.
+    pub async fn contract_kind(
+        &self,
+        contract: ContractAddress,
+    ) -> Result<ContractKind, DeployError> {
+        let consensus_info = self.client.clone().get_consensus_info().await?;
+
+        let info = self
+            .client
+            .clone()
+            .get_instance_info(contract, &consensus_info.last_finalized_block)
+            .await?;
+
+        let name = info.response.name().as_contract_name().contract_name();
+        ContractKind::from_name(name).ok_or_else(|| DeployError::InvalidContractName(name.into()))
+    }
+
+    /// Check that the given contract is an instance of the given contract
+    /// and module, e.g., before resuming a deployment that created it.
+    pub async fn verify_contract(
+        &self,
+        contract: ContractAddress,
+        kind: ContractKind,
+        module_ref: ModuleReference,
+    ) -> Result<(), DeployError> {
+        let consensus_info = self.client.clone().get_consensus_info().await?;
+
+        let info = self
+            .client
+            .clone()
+            .get_instance_info(contract, &consensus_info.last_finalized_block)
+            .await?;
+
+        let name = info.response.name().as_contract_name().contract_name();
+        if name != kind.name() || info.response.source_module() != module_ref {
+            return Err(DeployError::InvalidContractName(format!(
+                "{contract} is an instance of {name} from module {}, expected {} from module \
+                 {module_ref}",
+                info.response.source_module(),
+                kind.name()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Whether the address has the role on the given contract, according to
+    /// the role views of the contract.
+    pub async fn has_role(
+        &self,
+        contract: ContractAddress,
+        kind: ContractKind,
+        address: Address,
+        role: &impl Serial,
+    ) -> Result<bool, DeployError> {
+        let roles: Vec<u8> = match kind {
+            ContractKind::BridgeManager => {
+                let parameter = contracts_common::to_bytes(&address);
+                let return_value = self
+                    .view(contract, &kind.method("viewAddressRoles"), parameter)
+                    .await?;
+                contracts_common::from_bytes(&return_value)
+                    .map_err(|_| anyhow::anyhow!("Invalid return value of viewAddressRoles"))?
+            }
+            ContractKind::CIS2Bridgeable => {
+                let return_value = self
+                    .view(contract, &kind.method("viewRoles"), Vec::new())
+                    .await?;
+                let all_roles: Vec<(Address, Vec<u8>)> =
+                    contracts_common::from_bytes(&return_value)
+                        .map_err(|_| anyhow::anyhow!("Invalid return value of viewRoles"))?;
+                all_roles
+                    .into_iter()
+                    .find(|(a, _)| *a == address)
+                    .map(|(_, roles)| roles)
+                    .unwrap_or_default()
+            }
+        };
+        // Roles are serialized as a single tag byte.
+        Ok(roles.contains(&contracts_common::to_bytes(role)[0]))
+    }
+
     pub async fn module_exists(&self, wasm_module: WasmModule) -> Result<bool, DeployError> {
         let consensus_info = self.client.clone().get_consensus_info().await?;
 
@@ -135,16 +223,15 @@ impl Deployer {
         &self,
         _name: String,
         metadata: String,
-        // metadata_hash: [u8; 32],
-        module_ref: ModuleRef,
+        metadata_hash: Option<[u8; 32]>,
+        module_ref: ModuleReference,
     ) -> Result<ContractAddress, DeployError> {
         let parameters = CIS2BridgeableInitParams {
             url: metadata,
-            // hash: Some(metadata_hash),
-            hash: None,
+            hash: metadata_hash,
         };
         let bytes = contracts_common::to_bytes(&parameters);
-        let param: Parameter = bytes.try_into()?;
+        let param: OwnedParameter = bytes.try_into()?;
 
         let payload = InitContractPayload {
             init_name: OwnedContractName::new(CIS2_INIT_METHOD.into())?,
@@ -160,9 +247,9 @@ impl Deployer {
 
     pub async fn init_bridge_contract(
         &self,
-        module_ref: ModuleRef,
+        module_ref: ModuleReference,
     ) -> Result<ContractAddress, DeployError> {
-        let param: Parameter = Parameter::default();
+        let param = OwnedParameter::default();
 
         let payload = InitContractPayload {
             init_name: OwnedContractName::new(BRIDGE_INIT_METHOD.into())?,
@@ -178,9 +265,10 @@ impl Deployer {
 
     pub async fn upgrade_contract(
         &self,
-        new_module_ref: ModuleRef,
+        new_module_ref: ModuleReference,
         contract: ContractAddress,
         method: &str,
+        migrate: Option<(OwnedEntrypointName, OwnedParameter)>,
     ) -> Result<(), DeployError> {
         let consensus_info = self.client.clone().get_consensus_info().await?;
 
@@ -211,23 +299,10 @@ impl Deployer {
 
         let params = UpgradeParams {
             module: modref,
-            migrate: None,
-        };
-        let bytes = contracts_common::to_bytes(&params);
-
-        let update_payload = transactions::UpdateContractPayload {
-            amount: Amount::from_ccd(0),
-            address: contract,
-            receive_name: OwnedReceiveName::from_str(method)?,
-            message: bytes.try_into()?,
+            migrate,
         };
 
-        let energy = self.estimate_energy(update_payload.clone()).await?;
-
-        self.update_contract(update_payload, GivenEnergy::Add(energy))
-            .await?;
-
-        Ok(())
+        self.send_update(contract, method, &params).await
     }
 
     pub async fn init_contract(
@@ -282,21 +357,9 @@ impl Deployer {
         let address = Address::Account(self.manager_key.address);
 
         let params = BridgeGrantRoleParams { address, role };
-        let bytes = contracts_common::to_bytes(&params);
-
-        let update_payload = transactions::UpdateContractPayload {
-            amount: Amount::from_ccd(0),
-            address: contract,
-            receive_name: OwnedReceiveName::from_str(BRIDGE_GRANT_ROLE_METHOD)?,
-            message: bytes.try_into()?,
-        };
-
-        let energy = self.estimate_energy(update_payload.clone()).await?;
-
-        self.update_contract(update_payload, GivenEnergy::Add(energy))
-            .await?;
 
-        Ok(())
+        self.send_update(contract, BRIDGE_GRANT_ROLE_METHOD, &params)
+            .await
     }
 
     pub async fn token_grant_role(
@@ -306,21 +369,76 @@ impl Deployer {
         role: CIS2BridgeableRoles,
     ) -> Result<(), DeployError> {
         let params = CIS2BridgeableGrantRoleParams { address, role };
-        let bytes = contracts_common::to_bytes(&params);
+
+        self.send_update(contract, CIS2_GRANT_ROLE_METHOD, &params)
+            .await
+    }
+
+    /// Dry run an update of the contract with the given parameter to estimate
+    /// its energy, and send it unless this is a dry run.
+    pub async fn send_update(
+        &self,
+        contract: ContractAddress,
+        method: &str,
+        params: &impl Serial,
+    ) -> Result<(), DeployError> {
+        let bytes = contracts_common::to_bytes(params);
 
         let update_payload = transactions::UpdateContractPayload {
             amount: Amount::from_ccd(0),
             address: contract,
-            receive_name: OwnedReceiveName::from_str(CIS2_GRANT_ROLE_METHOD)?,
+            receive_name: OwnedReceiveName::from_str(method)?,
             message: bytes.try_into()?,
         };
 
         let energy = self.estimate_energy(update_payload.clone()).await?;
 
+        if self.dry_run {
+            println!("Dry run of {method} on {contract} succeeded, not sending it.");
+            return Ok(());
+        }
+
         self.update_contract(update_payload, GivenEnergy::Add(energy))
+            .await
+    }
+
+    /// Invoke a view function of the contract, and return its return value.
+    pub async fn view(
+        &self,
+        contract: ContractAddress,
+        method: &str,
+        parameter: Vec<u8>,
+    ) -> Result<Vec<u8>, DeployError> {
+        let consensus_info = self.client.clone().get_consensus_info().await?;
+
+        let context = ContractContext {
+            invoker: Some(Address::Account(self.manager_key.address)),
+            contract,
+            amount: Amount::zero(),
+            method: OwnedReceiveName::from_str(method)?,
+            parameter: parameter.try_into()?,
+            energy: 100000.into(),
+        };
+
+        let result = self
+            .client
+            .clone()
+            .invoke_instance(&consensus_info.last_finalized_block, &context)
             .await?;
 
-        Ok(())
+        match result.response {
+            InvokeContractResult::Failure {
+                return_value,
+                reason,
+                used_energy,
+            } => Err(DeployError::InvokeContractFailed(format!(
+                "contract invoke failed: {reason:?}, used_energy={used_energy}, return \
+                 value={return_value:?}"
+            ))),
+            InvokeContractResult::Success { return_value, .. } => {
+                Ok(return_value.map(|r| r.value).unwrap_or_default())
+            }
+        }
     }
 
     pub async fn update_contract(
@@ -470,9 +588,9 @@ impl Deployer {
                         ));
                     }
 
-                    return Err(DeployError::TransactionRejectedR(format!(
+                    Err(DeployError::TransactionRejectedR(format!(
                         "contract init rejected with reason: {reject_reason:?}"
-                    )));
+                    )))
                 }
                 AccountTransactionEffects::ContractInitialized { data } => {
                     Ok(ContractInitialized {
@@ -502,9 +620,9 @@ impl Deployer {
                         ));
                     }
 
-                    return Err(DeployError::TransactionRejectedR(format!(
+                    Err(DeployError::TransactionRejectedR(format!(
                         "contract update rejected with reason: {reject_reason:?}"
-                    )));
+                    )))
                 }
                 AccountTransactionEffects::ContractUpdateIssued { effects: _ } => Ok(()),
                 _ => Err(DeployError::InvalidBlockItem(
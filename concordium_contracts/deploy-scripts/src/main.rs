@@ -1,22 +1,39 @@
+// The errors of the node API are large, and so is `DeployError`.
+#![allow(clippy::result_large_err)]
+
+pub mod admin;
 pub mod contracts;
 pub mod deployer;
+pub mod metadata;
+pub mod state;
 
-use crate::contracts::{BridgeRoles, CIS2BridgeableRoles};
+use crate::contracts::{
+    BlockedAddress, BridgeRoles, CIS2BridgeableRoles, ContractKind, CorrectionParams, EthAddress,
+    TokenFee,
+};
 use anyhow::Context;
 use clap::Parser;
 use concordium_rust_sdk::{
+    cis2,
+    common::types::Amount,
     endpoints::{self, RPCError},
-    smart_contracts::common::{Address, NewContractNameError, NewReceiveNameError},
+    id::types::AccountAddress,
+    smart_contracts::common::{
+        Address, ModuleReference, NewContractNameError, NewReceiveNameError, OwnedEntrypointName,
+        OwnedParameter,
+    },
     types::{
-        hashes::TransactionHash,
-        smart_contracts::{ExceedsParameterSize, ModuleRef, WasmModule},
+        hashes::Hash,
+        smart_contracts::{ExceedsParameterSize, WasmModule},
         ContractAddress,
     },
     v2,
 };
 use deployer::{Deployer, ModuleDeployed};
 use hex::FromHexError;
+use metadata::MetadataOptions;
 use serde::{Deserialize, Serialize};
+use state::DeployState;
 use std::{
     fs::File,
     io::{BufWriter, Cursor, Write},
@@ -28,7 +45,12 @@ use thiserror::Error;
 pub struct WrappedToken {
     pub name: String,
     pub token_metadata_url: String,
-    pub token_metadata_hash: Option<TransactionHash>,
+    /// The expected SHA-256 hash of the metadata.
+    pub token_metadata_hash: Option<Hash>,
+    /// Address of the ERC20 token on Ethereum, used to generate the metadata.
+    pub root_token: Option<String>,
+    /// URL of the thumbnail to include in the generated metadata.
+    pub thumbnail: Option<String>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -92,7 +114,7 @@ fn module_deployed(module_ref: &str) -> Result<ModuleDeployed, DeployError> {
     hex::decode_to_slice(module_ref, &mut bytes)?;
 
     let module_deployed = ModuleDeployed {
-        module_ref: ModuleRef::from(bytes),
+        module_ref: ModuleReference::from(bytes),
     };
 
     Ok(module_deployed)
@@ -106,70 +128,159 @@ fn get_wasm_module(file: &Path) -> Result<WasmModule, DeployError> {
     Ok(wasm_module)
 }
 
+/// Deploy a token, or resume its deployment from the state, and grant the
+/// bridge manager the `Manager` role on it.
 async fn deploy_token(
     deployer: &Deployer,
+    state: &mut DeployState,
     token: &WrappedToken,
-    cis2_bridgeable_module_ref: ModuleRef,
+    metadata: &MetadataOptions,
+    cis2_bridgeable_module_ref: ModuleReference,
     bridge_manager: ContractAddress,
 ) -> Result<OutputToken, DeployError> {
-    println!("Initializing cis2-bridgeable {}....", token.name);
+    let index = state.token(token);
+
+    let contract = match state.tokens[index].contract {
+        Some(contract) => {
+            deployer
+                .verify_contract(
+                    contract,
+                    ContractKind::CIS2Bridgeable,
+                    cis2_bridgeable_module_ref,
+                )
+                .await?;
+            println!(
+                "Cis2-bridgeable {} already initialized at address: ({}, {})",
+                token.name, contract.index, contract.subindex
+            );
+            contract
+        }
+        None => {
+            let metadata_hash = metadata::resolve_metadata_hash(token, metadata).await?;
+
+            println!("Initializing cis2-bridgeable {}....", token.name);
+
+            let contract = deployer
+                .init_token_contract(
+                    token.name.clone(),
+                    token.token_metadata_url.clone(),
+                    Some(metadata_hash.bytes),
+                    cis2_bridgeable_module_ref,
+                )
+                .await?;
+            state.tokens[index].contract = Some(contract);
+            state.save()?;
+            println!(
+                "Initialized cis2-bridgeable {} at address: ({}, {})",
+                token.name, contract.index, contract.subindex
+            );
+            contract
+        }
+    };
 
-    let mock_token = deployer
-        .init_token_contract(
-            token.name.clone(),
-            token.token_metadata_url.clone(),
-            // token.token_metadata_hash.as_ref().try_into().unwrap(),
-            cis2_bridgeable_module_ref,
-        )
-        .await?;
-    println!(
-        "Initialized cis2-bridgeable {} at address: ({}, {})",
-        token.name, mock_token.index, mock_token.subindex
-    );
-    println!(
-        "Granting bridge-manager Manager role on {} token....",
-        token.name
-    );
-    deployer
-        .token_grant_role(
-            mock_token,
+    // The role is checked on chain even if the state records it, since the
+    // deployment may have stopped after granting it but before saving.
+    let has_role = deployer
+        .has_role(
+            contract,
+            ContractKind::CIS2Bridgeable,
             Address::Contract(bridge_manager),
-            CIS2BridgeableRoles::Manager,
+            &CIS2BridgeableRoles::Manager,
         )
         .await?;
-    println!(
-        "Granted bridge-manager Manager role on {} token",
-        token.name.clone()
-    );
+    if has_role {
+        println!(
+            "Bridge-manager already has the Manager role on {} token",
+            token.name
+        );
+    } else {
+        println!(
+            "Granting bridge-manager Manager role on {} token....",
+            token.name
+        );
+        deployer
+            .token_grant_role(
+                contract,
+                Address::Contract(bridge_manager),
+                CIS2BridgeableRoles::Manager,
+            )
+            .await?;
+        println!(
+            "Granted bridge-manager Manager role on {} token",
+            token.name.clone()
+        );
+    }
+    state.tokens[index].manager_granted = true;
+    state.save()?;
 
     let token = OutputToken {
         name: token.name.clone(),
-        token_url: token.token_metadata_url.clone(),
-        contract: mock_token,
+        token_url: state.tokens[index].token_url.clone(),
+        contract,
     };
 
     Ok(token)
 }
 
+/// Initialize the bridge manager and the tokens, skipping the steps that the
+/// state records as completed, and write the addresses to `../latest.json`.
 async fn init_contracts(
     deployer: &Deployer,
+    state: &mut DeployState,
     tokens: &[WrappedToken],
-    bridge_manager_module_ref: ModuleRef,
-    cis2_bridgeable_module_ref: ModuleRef,
+    metadata: &MetadataOptions,
+    bridge_manager_module_ref: ModuleReference,
+    cis2_bridgeable_module_ref: ModuleReference,
 ) -> Result<(), DeployError> {
-    println!("Initializing bridge-manager....");
-    let bridge_manager = deployer
-        .init_bridge_contract(bridge_manager_module_ref)
-        .await?;
-    println!(
-        "Initialized bridge-manager, address: ({}, {})",
-        bridge_manager.index, bridge_manager.subindex
-    );
-    println!("Granting Manager address Manager role on bridge-manager....");
-    deployer
-        .bridge_grant_role(bridge_manager, BridgeRoles::StateSyncer)
+    let bridge_manager = match state.bridge_manager {
+        Some(bridge_manager) => {
+            deployer
+                .verify_contract(
+                    bridge_manager,
+                    ContractKind::BridgeManager,
+                    bridge_manager_module_ref,
+                )
+                .await?;
+            println!(
+                "Bridge-manager already initialized, address: ({}, {})",
+                bridge_manager.index, bridge_manager.subindex
+            );
+            bridge_manager
+        }
+        None => {
+            println!("Initializing bridge-manager....");
+            let bridge_manager = deployer
+                .init_bridge_contract(bridge_manager_module_ref)
+                .await?;
+            state.bridge_manager = Some(bridge_manager);
+            state.save()?;
+            println!(
+                "Initialized bridge-manager, address: ({}, {})",
+                bridge_manager.index, bridge_manager.subindex
+            );
+            bridge_manager
+        }
+    };
+
+    let has_role = deployer
+        .has_role(
+            bridge_manager,
+            ContractKind::BridgeManager,
+            Address::Account(deployer.manager_key.address),
+            &BridgeRoles::StateSyncer,
+        )
         .await?;
-    println!("Granted Manager address Manager role on bridge-manager");
+    if has_role {
+        println!("Manager address already has the Manager role on bridge-manager");
+    } else {
+        println!("Granting Manager address Manager role on bridge-manager....");
+        deployer
+            .bridge_grant_role(bridge_manager, BridgeRoles::StateSyncer)
+            .await?;
+        println!("Granted Manager address Manager role on bridge-manager");
+    }
+    state.state_syncer_granted = true;
+    state.save()?;
 
     println!();
 
@@ -179,8 +290,15 @@ async fn init_contracts(
     };
 
     for token in tokens {
-        let output_token =
-            deploy_token(deployer, token, cis2_bridgeable_module_ref, bridge_manager).await?;
+        let output_token = deploy_token(
+            deployer,
+            state,
+            token,
+            metadata,
+            cis2_bridgeable_module_ref,
+            bridge_manager,
+        )
+        .await?;
         output.tokens.push(output_token);
 
         println!();
@@ -210,61 +328,559 @@ struct DeployScripts {
     concordium_url: v2::Endpoint,
     #[clap(long = "wallet", help = "Location of the Concordium wallet.")]
     concordium_wallet: PathBuf,
-    #[clap(long = "tokens", help = "JSON file with a list of tokens.")]
-    tokens: PathBuf,
     #[clap(
-        long = "manager-source",
-        help = "Location of the compiled BridgeManager contract."
+        long = "dry-run",
+        help = "Only dry run the contract updates, without sending them."
     )]
-    manager_source: PathBuf,
-    #[clap(long = "cis2-bridgeable", help = "Source of the CIS2 token contract.")]
-    cis2_source: PathBuf,
+    dry_run: bool,
+    #[clap(subcommand)]
+    command: Command,
 }
 
-#[tokio::main(flavor = "current_thread")]
-async fn main() -> Result<(), DeployError> {
-    let app: DeployScripts = DeployScripts::parse();
+#[derive(clap::Subcommand, Debug)]
+enum Command {
+    /// Deploy both modules, the bridge manager and the tokens, and write the
+    /// addresses to `../latest.json`. A failed deployment is resumed from its
+    /// state file when rerun.
+    Deploy {
+        #[clap(long = "tokens", help = "JSON file with a list of tokens.")]
+        tokens: PathBuf,
+        #[clap(
+            long = "manager-source",
+            help = "Location of the compiled BridgeManager contract."
+        )]
+        manager_source: PathBuf,
+        #[clap(long = "cis2-bridgeable", help = "Source of the CIS2 token contract.")]
+        cis2_source: PathBuf,
+        #[clap(
+            long = "state",
+            default_value = "deploy-state.json",
+            help = "File recording the completed steps of the deployment."
+        )]
+        state: PathBuf,
+        #[clap(flatten)]
+        metadata: MetadataArgs,
+    },
+    /// Deploy an additional token for an existing bridge manager.
+    DeployToken {
+        #[clap(
+            long = "bridge-manager",
+            help = "Address of the bridge manager, e.g., <605,0>."
+        )]
+        bridge_manager: ContractAddress,
+        #[clap(long = "cis2-bridgeable", help = "Source of the CIS2 token contract.")]
+        cis2_source: PathBuf,
+        #[clap(long = "name", help = "Name of the token, e.g., USDC.eth.")]
+        name: String,
+        #[clap(long = "metadata-url", help = "URL of the token metadata.")]
+        metadata_url: String,
+        #[clap(
+            long = "state",
+            help = "File recording the completed steps of the deployment, to resume it if it \
+                    fails."
+        )]
+        state: Option<PathBuf>,
+        #[clap(
+            long = "root-token",
+            help = "Address of the ERC20 token on Ethereum, used to generate the metadata."
+        )]
+        root_token: Option<String>,
+        #[clap(
+            long = "thumbnail",
+            help = "URL of the thumbnail to include in the generated metadata."
+        )]
+        thumbnail: Option<String>,
+        #[clap(
+            long = "metadata-hash",
+            help = "The expected SHA-256 hash of the metadata."
+        )]
+        metadata_hash: Option<Hash>,
+        #[clap(flatten)]
+        metadata: MetadataArgs,
+    },
+    /// Upgrade a contract, optionally calling a migration entrypoint of the new
+    /// module.
+    Upgrade {
+        #[clap(long = "contract", help = "Address of the contract.")]
+        contract: ContractAddress,
+        #[clap(long = "module", help = "Source of the new module.")]
+        module_source: PathBuf,
+        #[clap(long = "migrate", help = "Entrypoint to call after upgrading.")]
+        migrate: Option<String>,
+        #[clap(
+            long = "migrate-parameter",
+            help = "Hex encoded parameter of the migration entrypoint.",
+            default_value = "",
+            value_parser = parse_hex
+        )]
+        migrate_parameter: Vec<u8>,
+    },
+    /// Grant a role.
+    GrantRole {
+        #[clap(long = "contract", help = "Address of the contract.")]
+        contract: ContractAddress,
+        #[clap(long = "address", help = "Account or contract to grant the role to.")]
+        address: Address,
+        #[clap(long = "role", value_enum, help = "The role.")]
+        role: admin::Role,
+    },
+    /// Revoke a role.
+    RevokeRole {
+        #[clap(long = "contract", help = "Address of the contract.")]
+        contract: ContractAddress,
+        #[clap(
+            long = "address",
+            help = "Account or contract to revoke the role from."
+        )]
+        address: Address,
+        #[clap(long = "role", value_enum, help = "The role.")]
+        role: admin::Role,
+    },
+    /// Set the fee for withdrawing tokens on the bridge manager.
+    SetWithdrawFee {
+        #[clap(long = "contract", help = "Address of the bridge manager.")]
+        contract: ContractAddress,
+        #[clap(long = "amount", help = "The fee in CCD.")]
+        amount: Amount,
+    },
+    /// Set the additional fee for claiming withdrawals on the bridge manager.
+    SetClaimFee {
+        #[clap(long = "contract", help = "Address of the bridge manager.")]
+        contract: ContractAddress,
+        #[clap(long = "amount", help = "The fee in CCD.")]
+        amount: Amount,
+    },
+    /// Set the fee for withdrawing a token on the bridge manager, which is
+    /// taken in the token. The fee is the flat fee plus the basis points of the
+    /// withdrawn amount, but at least the minimum.
+    SetTokenFee {
+        #[clap(long = "contract", help = "Address of the bridge manager.")]
+        contract: ContractAddress,
+        #[clap(long = "token", help = "Address of the token on Concordium.")]
+        token: ContractAddress,
+        #[clap(
+            long = "flat",
+            default_value_t = 0,
+            help = "Flat fee in the smallest unit of the token."
+        )]
+        flat: u128,
+        #[clap(
+            long = "basis-points",
+            default_value_t = 0,
+            help = "Fee in hundredths of a percent of the withdrawn amount."
+        )]
+        basis_points: u16,
+        #[clap(
+            long = "minimum",
+            default_value_t = 0,
+            help = "Minimum fee in the smallest unit of the token."
+        )]
+        minimum: u128,
+    },
+    /// Propose to mint tokens to an address to correct the supply of a token
+    /// after an incident, e.g., a lost deposit. The proposal can be executed
+    /// after the governance delay, but at least one day.
+    CorrectiveMint {
+        #[clap(long = "contract", help = "Address of the bridge manager.")]
+        contract: ContractAddress,
+        #[clap(
+            long = "incident",
+            help = "Id of the incident the correction belongs to."
+        )]
+        incident_id: u64,
+        #[clap(long = "token", help = "Address of the token on Concordium.")]
+        token: ContractAddress,
+        #[clap(long = "address", help = "Account or contract to mint the tokens to.")]
+        address: Address,
+        #[clap(long = "amount", help = "Amount in the smallest unit of the token.")]
+        amount: u128,
+    },
+    /// Propose to burn tokens from an address to correct the supply of a token
+    /// after an incident. The proposal can be executed after the governance
+    /// delay, but at least one day. The address must have the bridge manager
+    /// as an operator, both when proposing and when executing.
+    CorrectiveBurn {
+        #[clap(long = "contract", help = "Address of the bridge manager.")]
+        contract: ContractAddress,
+        #[clap(
+            long = "incident",
+            help = "Id of the incident the correction belongs to."
+        )]
+        incident_id: u64,
+        #[clap(long = "token", help = "Address of the token on Concordium.")]
+        token: ContractAddress,
+        #[clap(
+            long = "address",
+            help = "Account or contract to burn the tokens from."
+        )]
+        address: Address,
+        #[clap(long = "amount", help = "Amount in the smallest unit of the token.")]
+        amount: u128,
+    },
+    /// Set the account receiving the fees of the bridge manager.
+    SetTreasurer {
+        #[clap(long = "contract", help = "Address of the bridge manager.")]
+        contract: ContractAddress,
+        #[clap(long = "account", help = "The treasurer account.")]
+        account: AccountAddress,
+    },
+    /// Block an address from withdrawing, or from receiving withdrawals, on
+    /// the bridge manager.
+    Block {
+        #[clap(long = "contract", help = "Address of the bridge manager.")]
+        contract: ContractAddress,
+        #[clap(
+            long = "address",
+            help = "Concordium address, or 0x prefixed Ethereum address, to block.",
+            value_parser = parse_blocked_address
+        )]
+        address: BlockedAddress,
+    },
+    /// Remove an address from the blocklist of the bridge manager.
+    Unblock {
+        #[clap(long = "contract", help = "Address of the bridge manager.")]
+        contract: ContractAddress,
+        #[clap(
+            long = "address",
+            help = "Concordium address, or 0x prefixed Ethereum address, to unblock.",
+            value_parser = parse_blocked_address
+        )]
+        address: BlockedAddress,
+    },
+    /// Execute a proposal of an admin action once its delay has passed.
+    Execute {
+        #[clap(long = "contract", help = "Address of the contract.")]
+        contract: ContractAddress,
+        #[clap(long = "id", help = "Id of the proposal.")]
+        id: u64,
+    },
+    /// Cancel a proposal of an admin action before it is executed.
+    Cancel {
+        #[clap(long = "contract", help = "Address of the contract.")]
+        contract: ContractAddress,
+        #[clap(long = "id", help = "Id of the proposal.")]
+        id: u64,
+    },
+    /// Accept the `Admin` role of a contract, once a transfer of it to the
+    /// sender is executed.
+    AcceptAdmin {
+        #[clap(long = "contract", help = "Address of the contract.")]
+        contract: ContractAddress,
+    },
+    /// Pause a contract.
+    Pause {
+        #[clap(long = "contract", help = "Address of the contract.")]
+        contract: ContractAddress,
+    },
+    /// Unpause a contract.
+    Unpause {
+        #[clap(long = "contract", help = "Address of the contract.")]
+        contract: ContractAddress,
+    },
+    /// Invoke a view function of a contract and print its return value.
+    View {
+        #[clap(long = "contract", help = "Address of the contract.")]
+        contract: ContractAddress,
+        #[clap(
+            long = "entrypoint",
+            help = "The view function, e.g., viewConfiguration."
+        )]
+        entrypoint: String,
+        #[clap(
+            long = "parameter",
+            help = "Hex encoded parameter.",
+            default_value = "",
+            value_parser = parse_hex
+        )]
+        parameter: Vec<u8>,
+        #[clap(
+            long = "schema",
+            help = "Schema of the module, used to print the return value as JSON."
+        )]
+        schema: Option<PathBuf>,
+    },
+}
 
-    let concordium_client = v2::Client::new(app.concordium_url).await?;
+/// Options for the metadata of the deployed tokens.
+#[derive(clap::Args, Debug)]
+struct MetadataArgs {
+    #[clap(
+        long = "ethereum",
+        help = "JSON-RPC endpoint of an Ethereum node, used to generate the metadata of tokens \
+                with a root token."
+    )]
+    ethereum_url: Option<String>,
+    #[clap(
+        long = "write-metadata",
+        help = "Write the generated metadata to this directory for hosting, instead of verifying \
+                it against the hosted metadata."
+    )]
+    write_dir: Option<PathBuf>,
+}
 
-    let deployer = Deployer::new(concordium_client, &app.concordium_wallet)?;
+impl From<MetadataArgs> for MetadataOptions {
+    fn from(args: MetadataArgs) -> Self {
+        MetadataOptions {
+            ethereum_url: args.ethereum_url,
+            write_dir: args.write_dir,
+        }
+    }
+}
 
-    let tokens: Vec<WrappedToken> = serde_json::from_slice(&std::fs::read(app.tokens)?)?;
+fn parse_hex(value: &str) -> Result<Vec<u8>, FromHexError> {
+    hex::decode(value)
+}
 
-    // make sure we didn't mess up the hashes:
-    // for token in tokens.iter() {
-    //     let _ =
-    //         get_and_compare_metadata_hash(&token.token_metadata_url, &token.token_metadata_hash)
-    //             .await?;
-    // }
+/// Parse a 0x prefixed Ethereum address, or a Concordium account or contract
+/// address.
+fn parse_blocked_address(value: &str) -> Result<BlockedAddress, String> {
+    match value.strip_prefix("0x") {
+        Some(hex) => {
+            let eth_address = hex::decode(hex)
+                .ok()
+                .and_then(|bytes| bytes.try_into().ok())
+                .ok_or_else(|| format!("Invalid Ethereum address {value}."))?;
+            Ok(BlockedAddress::Ethereum(EthAddress { eth_address }))
+        }
+        None => value
+            .parse()
+            .map(BlockedAddress::Concordium)
+            .map_err(|_| format!("Invalid Concordium address {value}.")),
+    }
+}
 
-    let wasm_module = get_wasm_module(app.cis2_source.as_path())?;
-    println!("Deploying cis2-bridgeable....");
-    let cis2_bridgeable_module_ref = deployer.deploy_wasm_module(wasm_module).await?;
-    println!(
-        "Deployed cis2-bridgeable, module_ref: {}",
-        cis2_bridgeable_module_ref.module_ref
-    );
+/// Deploy a module and record it, failing if a different module is recorded.
+async fn deploy_module(
+    deployer: &Deployer,
+    recorded: &mut Option<ModuleReference>,
+    name: &str,
+    source: &Path,
+) -> Result<ModuleReference, DeployError> {
+    let wasm_module = get_wasm_module(source)?;
+    DeployState::set_module(recorded, wasm_module.get_module_ref())?;
+    println!("Deploying {name}....");
+    let module_ref = deployer.deploy_wasm_module(wasm_module).await?.module_ref;
+    println!("Deployed {name}, module_ref: {module_ref}");
+    Ok(module_ref)
+}
+
+async fn deploy(
+    deployer: &Deployer,
+    tokens: &Path,
+    manager_source: &Path,
+    cis2_source: &Path,
+    state: &Path,
+    metadata: &MetadataOptions,
+) -> Result<(), DeployError> {
+    let tokens: Vec<WrappedToken> = serde_json::from_slice(&std::fs::read(tokens)?)?;
+
+    let mut state = DeployState::load(Some(state))?;
+
+    let cis2_bridgeable_module_ref = deploy_module(
+        deployer,
+        &mut state.cis2_bridgeable_module,
+        "cis2-bridgeable",
+        cis2_source,
+    )
+    .await?;
+    state.save()?;
 
     println!();
 
-    let wasm_module = get_wasm_module(app.manager_source.as_path())?;
-    println!("Deploying bridge-manager....");
-    let bridge_manager_module_ref = deployer.deploy_wasm_module(wasm_module).await?;
-    println!(
-        "Deployed bridge-manager, module_ref: {}",
-        bridge_manager_module_ref.module_ref
-    );
+    let bridge_manager_module_ref = deploy_module(
+        deployer,
+        &mut state.bridge_manager_module,
+        "bridge-manager",
+        manager_source,
+    )
+    .await?;
+    state.save()?;
 
     println!();
 
     init_contracts(
-        &deployer,
+        deployer,
+        &mut state,
         &tokens,
-        bridge_manager_module_ref.module_ref,
-        cis2_bridgeable_module_ref.module_ref,
+        metadata,
+        bridge_manager_module_ref,
+        cis2_bridgeable_module_ref,
     )
-    .await?;
+    .await
+}
 
-    Ok(())
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> Result<(), DeployError> {
+    let app: DeployScripts = DeployScripts::parse();
+
+    let concordium_client = v2::Client::new(app.concordium_url).await?;
+
+    let deployer = Deployer::new(concordium_client, &app.concordium_wallet, app.dry_run)?;
+
+    match app.command {
+        Command::Deploy { .. } | Command::DeployToken { .. } if app.dry_run => Err(
+            anyhow::anyhow!("Deployments cannot be dry run, since they initialize new contracts.")
+                .into(),
+        ),
+        Command::Deploy {
+            tokens,
+            manager_source,
+            cis2_source,
+            state,
+            metadata,
+        } => {
+            deploy(
+                &deployer,
+                &tokens,
+                &manager_source,
+                &cis2_source,
+                &state,
+                &metadata.into(),
+            )
+            .await
+        }
+        Command::DeployToken {
+            bridge_manager,
+            cis2_source,
+            name,
+            metadata_url,
+            state,
+            root_token,
+            thumbnail,
+            metadata_hash,
+            metadata,
+        } => {
+            let mut state = DeployState::load(state.as_deref())?;
+            let module_ref = deploy_module(
+                &deployer,
+                &mut state.cis2_bridgeable_module,
+                "cis2-bridgeable",
+                &cis2_source,
+            )
+            .await?;
+            state.save()?;
+            let token = WrappedToken {
+                name,
+                token_metadata_url: metadata_url,
+                token_metadata_hash: metadata_hash,
+                root_token,
+                thumbnail,
+            };
+            let token = deploy_token(
+                &deployer,
+                &mut state,
+                &token,
+                &metadata.into(),
+                module_ref,
+                bridge_manager,
+            )
+            .await?;
+            println!("{}", serde_json::to_string_pretty(&token)?);
+            Ok(())
+        }
+        Command::Upgrade {
+            contract,
+            module_source,
+            migrate,
+            migrate_parameter,
+        } => {
+            let migrate = match migrate {
+                Some(entrypoint) => Some((
+                    OwnedEntrypointName::new(entrypoint)?,
+                    OwnedParameter::try_from(migrate_parameter)?,
+                )),
+                None => None,
+            };
+            admin::upgrade(&deployer, contract, &module_source, migrate).await
+        }
+        Command::GrantRole {
+            contract,
+            address,
+            role,
+        } => admin::update_role(&deployer, contract, address, role, true).await,
+        Command::RevokeRole {
+            contract,
+            address,
+            role,
+        } => admin::update_role(&deployer, contract, address, role, false).await,
+        Command::SetWithdrawFee { contract, amount } => {
+            admin::set_fee(&deployer, contract, amount, false).await
+        }
+        Command::SetClaimFee { contract, amount } => {
+            admin::set_fee(&deployer, contract, amount, true).await
+        }
+        Command::SetTokenFee {
+            contract,
+            token,
+            flat,
+            basis_points,
+            minimum,
+        } => {
+            let fee = TokenFee {
+                flat: cis2::TokenAmount(flat.into()),
+                basis_points,
+                minimum: cis2::TokenAmount(minimum.into()),
+            };
+            admin::set_token_fee(&deployer, contract, token, fee).await
+        }
+        Command::CorrectiveMint {
+            contract,
+            incident_id,
+            token,
+            address,
+            amount,
+        } => {
+            let params = CorrectionParams {
+                incident_id,
+                token,
+                address,
+                amount: cis2::TokenAmount(amount.into()),
+            };
+            admin::correct(&deployer, contract, params, true).await
+        }
+        Command::CorrectiveBurn {
+            contract,
+            incident_id,
+            token,
+            address,
+            amount,
+        } => {
+            let params = CorrectionParams {
+                incident_id,
+                token,
+                address,
+                amount: cis2::TokenAmount(amount.into()),
+            };
+            admin::correct(&deployer, contract, params, false).await
+        }
+        Command::SetTreasurer { contract, account } => {
+            admin::set_treasurer(&deployer, contract, account).await
+        }
+        Command::Block { contract, address } => {
+            admin::set_blocked(&deployer, contract, address, true).await
+        }
+        Command::Unblock { contract, address } => {
+            admin::set_blocked(&deployer, contract, address, false).await
+        }
+        Command::Execute { contract, id } => admin::execute(&deployer, contract, id).await,
+        Command::Cancel { contract, id } => admin::cancel(&deployer, contract, id).await,
+        Command::AcceptAdmin { contract } => admin::accept_admin(&deployer, contract).await,
+        Command::Pause { contract } => admin::set_paused(&deployer, contract, true).await,
+        Command::Unpause { contract } => admin::set_paused(&deployer, contract, false).await,
+        Command::View {
+            contract,
+            entrypoint,
+            parameter,
+            schema,
+        } => {
+            admin::view(
+                &deployer,
+                contract,
+                &entrypoint,
+                parameter,
+                schema.as_deref(),
+            )
+            .await
+        }
+    }
 }
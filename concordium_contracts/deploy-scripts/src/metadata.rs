@@ -0,0 +1,196 @@
+//! CIS-2 token metadata of the wrapped tokens. The metadata is generated from
+//! the ERC20 token on Ethereum, and its SHA-256 hash is recorded in the token
+//! contract together with the URL where it is hosted.
+use crate::{DeployError, WrappedToken};
+use anyhow::Context;
+use concordium_rust_sdk::types::hashes::Hash;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+
+/// Selector of the ERC20 `symbol()` function.
+const SYMBOL_SELECTOR: &str = "0x95d89b41";
+/// Selector of the ERC20 `decimals()` function.
+const DECIMALS_SELECTOR: &str = "0x313ce567";
+
+/// Where to get the metadata of the tokens from.
+#[derive(Clone, Debug, Default)]
+pub struct MetadataOptions {
+    /// JSON-RPC endpoint of an Ethereum node, used to query the ERC20 tokens.
+    pub ethereum_url: Option<String>,
+    /// Directory to write the generated metadata to for hosting, instead of
+    /// verifying it against the hosted metadata.
+    pub write_dir: Option<PathBuf>,
+}
+
+/// The CIS-2 token metadata JSON of a wrapped token.
+#[derive(Serialize, Debug)]
+pub struct TokenMetadata {
+    pub name: String,
+    pub symbol: String,
+    pub decimals: u8,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thumbnail: Option<MetadataUrl>,
+}
+
+/// A URL in the token metadata.
+#[derive(Serialize, Debug)]
+pub struct MetadataUrl {
+    pub url: String,
+}
+
+/// The SHA-256 hash of a metadata document.
+pub fn metadata_hash(document: &[u8]) -> Hash {
+    let bytes: [u8; 32] = Sha256::digest(document).into();
+    Hash::from(bytes)
+}
+
+/// Download the metadata hosted at the URL and return its hash.
+pub async fn hosted_metadata_hash(url: &str) -> Result<Hash, DeployError> {
+    let document = reqwest::get(url).await?.error_for_status()?.bytes().await?;
+    Ok(metadata_hash(&document))
+}
+
+/// Determine the hash of the metadata of the token to record in the contract.
+///
+/// If the token has a root token on Ethereum, its metadata is generated from
+/// the ERC20 token, and either written to the directory for hosting, or
+/// verified against the hosted metadata. Otherwise the hosted metadata is
+/// used as is. In both cases the hash must match the hash given for the token,
+/// if any.
+pub async fn resolve_metadata_hash(
+    token: &WrappedToken,
+    options: &MetadataOptions,
+) -> Result<Hash, DeployError> {
+    let hash = match &token.root_token {
+        Some(root_token) => {
+            let ethereum_url = options.ethereum_url.as_deref().ok_or_else(|| {
+                anyhow::anyhow!(
+                    "--ethereum is required to generate the metadata of {}",
+                    token.name
+                )
+            })?;
+            let metadata = generate_metadata(ethereum_url, token, root_token).await?;
+            let document = serde_json::to_vec_pretty(&metadata)?;
+            let hash = metadata_hash(&document);
+            match &options.write_dir {
+                Some(dir) => {
+                    let file = dir.join(format!("{}.json", metadata.symbol));
+                    std::fs::write(&file, &document).with_context(|| {
+                        format!("Could not write the metadata to {}", file.display())
+                    })?;
+                    println!(
+                        "Wrote the metadata of {} to {}, host it at {}",
+                        token.name,
+                        file.display(),
+                        token.token_metadata_url
+                    );
+                }
+                None => {
+                    let hosted = hosted_metadata_hash(&token.token_metadata_url).await?;
+                    if hosted != hash {
+                        return Err(DeployError::InvalidHash(format!(
+                            "the metadata hosted at {} has hash {hosted}, but the generated \
+                             metadata of {} has hash {hash}",
+                            token.token_metadata_url, token.name
+                        )));
+                    }
+                }
+            }
+            hash
+        }
+        None => hosted_metadata_hash(&token.token_metadata_url).await?,
+    };
+    if let Some(expected) = token.token_metadata_hash {
+        if expected != hash {
+            return Err(DeployError::InvalidHash(format!(
+                "the metadata of {} has hash {hash}, expected {expected}",
+                token.name
+            )));
+        }
+    }
+    println!("Metadata of {} has hash {hash}", token.name);
+    Ok(hash)
+}
+
+/// Generate the metadata of the wrapped token from the ERC20 token.
+async fn generate_metadata(
+    ethereum_url: &str,
+    token: &WrappedToken,
+    root_token: &str,
+) -> Result<TokenMetadata, DeployError> {
+    let symbol = eth_call(ethereum_url, root_token, SYMBOL_SELECTOR).await?;
+    let symbol = decode_symbol(&symbol)
+        .ok_or_else(|| anyhow::anyhow!("Invalid symbol of the ERC20 token {root_token}"))?;
+    let decimals = eth_call(ethereum_url, root_token, DECIMALS_SELECTOR).await?;
+    let decimals = decode_decimals(&decimals)
+        .ok_or_else(|| anyhow::anyhow!("Invalid decimals of the ERC20 token {root_token}"))?;
+    Ok(TokenMetadata {
+        name: token.name.clone(),
+        // The symbol of the wrapped token follows the name, e.g., USDC.eth.
+        symbol: format!("{symbol}.eth"),
+        decimals,
+        thumbnail: token
+            .thumbnail
+            .as_ref()
+            .map(|url| MetadataUrl { url: url.clone() }),
+    })
+}
+
+/// Call a function without arguments of an Ethereum contract at the latest
+/// block, and return its return value.
+async fn eth_call(
+    ethereum_url: &str,
+    contract: &str,
+    selector: &str,
+) -> Result<Vec<u8>, DeployError> {
+    let request = serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "eth_call",
+        "params": [{ "to": contract, "data": selector }, "latest"],
+    });
+    let response: serde_json::Value = reqwest::Client::new()
+        .post(ethereum_url)
+        .json(&request)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let result = response["result"]
+        .as_str()
+        .ok_or_else(|| anyhow::anyhow!("eth_call to {contract} failed: {}", response["error"]))?;
+    Ok(hex::decode(result.trim_start_matches("0x"))?)
+}
+
+/// Decode the return value of `symbol()`, which is an ABI encoded string, or
+/// a `bytes32` for some older tokens.
+fn decode_symbol(value: &[u8]) -> Option<String> {
+    let bytes = if value.len() == 32 {
+        let end = value.iter().position(|b| *b == 0).unwrap_or(32);
+        &value[..end]
+    } else {
+        let offset = usize::try_from(decode_word(value.get(..32)?)?).ok()?;
+        let len =
+            usize::try_from(decode_word(value.get(offset..offset.checked_add(32)?)?)?).ok()?;
+        let start = offset + 32;
+        value.get(start..start.checked_add(len)?)?
+    };
+    let symbol = String::from_utf8(bytes.to_vec()).ok()?;
+    (!symbol.is_empty()).then_some(symbol)
+}
+
+/// Decode the return value of `decimals()`, which is an ABI encoded `uint8`.
+fn decode_decimals(value: &[u8]) -> Option<u8> {
+    u8::try_from(decode_word(value.get(..32)?)?).ok()
+}
+
+/// Decode a 32 byte ABI word that fits in a `u64`.
+fn decode_word(word: &[u8]) -> Option<u64> {
+    let (high, low) = word.split_at(24);
+    if high.iter().any(|b| *b != 0) {
+        return None;
+    }
+    Some(u64::from_be_bytes(low.try_into().ok()?))
+}
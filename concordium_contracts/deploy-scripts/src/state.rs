@@ -0,0 +1,97 @@
+//! The state of a deployment, recording each completed step so that a failed
+//! deployment can be resumed instead of started over.
+use crate::{DeployError, WrappedToken};
+use anyhow::Context;
+use concordium_rust_sdk::{smart_contracts::common::ModuleReference, types::ContractAddress};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// The completed steps of a deployment. It is written to the state file after
+/// every step.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct DeployState {
+    /// Where the state is stored, or `None` if it is only kept in memory.
+    #[serde(skip)]
+    path: Option<PathBuf>,
+    pub cis2_bridgeable_module: Option<ModuleReference>,
+    pub bridge_manager_module: Option<ModuleReference>,
+    pub bridge_manager: Option<ContractAddress>,
+    /// Whether the deployer account was granted the `StateSyncer` role on the
+    /// bridge manager.
+    pub state_syncer_granted: bool,
+    pub tokens: Vec<TokenState>,
+}
+
+/// The completed steps of deploying a single token.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TokenState {
+    pub name: String,
+    pub token_url: String,
+    pub contract: Option<ContractAddress>,
+    /// Whether the bridge manager was granted the `Manager` role on the token.
+    pub manager_granted: bool,
+}
+
+impl DeployState {
+    /// Load the state from the given file, or start a new deployment if the
+    /// file does not exist or no file is given.
+    pub fn load(path: Option<&Path>) -> Result<Self, DeployError> {
+        let mut state: DeployState = match path {
+            Some(path) if path.exists() => {
+                let state = serde_json::from_slice(&std::fs::read(path)?)?;
+                println!("Resuming the deployment in {}.", path.display());
+                state
+            }
+            _ => DeployState::default(),
+        };
+        state.path = path.map(Path::to_path_buf);
+        Ok(state)
+    }
+
+    /// Write the state to its file, if any.
+    pub fn save(&self) -> Result<(), DeployError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let json = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, json)
+            .with_context(|| format!("Could not write the state to {}", path.display()))?;
+        Ok(())
+    }
+
+    /// Record a deployed module, failing if the state records a different
+    /// module, since the deployment would then mix two versions of the
+    /// contract.
+    pub fn set_module(
+        slot: &mut Option<ModuleReference>,
+        module_ref: ModuleReference,
+    ) -> Result<(), DeployError> {
+        match slot {
+            Some(recorded) if *recorded != module_ref => Err(anyhow::anyhow!(
+                "The deployment was started with module {recorded}, not {module_ref}."
+            )
+            .into()),
+            _ => {
+                *slot = Some(module_ref);
+                Ok(())
+            }
+        }
+    }
+
+    /// The index of the state of the given token, adding it if the token was
+    /// not deployed before.
+    pub fn token(&mut self, token: &WrappedToken) -> usize {
+        match self.tokens.iter().position(|t| t.name == token.name) {
+            Some(index) => index,
+            None => {
+                self.tokens.push(TokenState {
+                    name: token.name.clone(),
+                    token_url: token.token_metadata_url.clone(),
+                    contract: None,
+                    manager_granted: false,
+                });
+                self.tokens.len() - 1
+            }
+        }
+    }
+}
@@ -0,0 +1,558 @@
+//! Chain-level integration tests of the bridge contracts.
+//!
+//! The tests deploy the compiled modules of the `bridge-manager` and
+//! `cis2-bridgeable` contracts on a test chain of the smart contract testing
+//! library and run the complete flows of the bridge. This module contains the
+//! setup of the bridge and helpers to interact with it. The modules must be
+//! built before running the tests, e.g., using `make integration-test` in the
+//! `deploy-scripts` directory.
+//!
+//! State updates are constructed, and events are parsed, using the types of
+//! the relayer so that the tests check that the contracts and the relayer
+//! agree on the serialization. The parameters of the other entrypoints use
+//! the same version of the contracts common types, and are converted to the
+//! types of the testing library only when they are sent to the chain.
+pub use bridge_types::{
+    error_codes, BlockedAddress, CorrectionParams, EthAddress, GrantRoleParams, Roles,
+    SetBlockedParams, SetTokenFeeParams, TokenFee, UpgradeParams, MIN_CORRECTION_DELAY,
+};
+use ccdeth_relayer::concordium_contracts as relayer;
+pub use concordium_rust_sdk::{
+    cis2,
+    smart_contracts::common::{
+        from_bytes, to_bytes, AccountAddress, Address, Amount, ContractAddress, Deserial, Duration,
+        ModuleReference, OwnedEntrypointName, OwnedParameter, Serial,
+    },
+};
+use concordium_smart_contract_testing as testing;
+pub use concordium_smart_contract_testing::{
+    ContractEvent, ContractInvokeError, ContractInvokeSuccess, ContractTraceElement,
+};
+
+/// Path of the compiled `bridge-manager` module.
+pub const BRIDGE_MANAGER_MODULE: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../bridge-manager/target/concordium/wasm32-unknown-unknown/release/bridge_manager.wasm.v1"
+);
+/// Path of the compiled `cis2-bridgeable` module.
+pub const CIS2_BRIDGEABLE_MODULE: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../cis2-bridgeable/target/concordium/wasm32-unknown-unknown/release/cis2_bridgeable.wasm.v1"
+);
+/// Path of the `bridge-manager` module as first deployed.
+pub const FIRST_BRIDGE_MANAGER_MODULE: &str =
+    concat!(env!("CARGO_MANIFEST_DIR"), "/../deploy-scripts/manager.v1");
+/// Path of the `cis2-bridgeable` module as first deployed.
+pub const FIRST_CIS2_BRIDGEABLE_MODULE: &str =
+    concat!(env!("CARGO_MANIFEST_DIR"), "/../deploy-scripts/cis2.v1");
+
+/// The account that instantiates the contracts. It is the admin of both
+/// contracts, and the treasurer of the bridge manager.
+pub const ADMIN: AccountAddress = AccountAddress([0u8; 32]);
+/// The account of the relayer, which has the `StateSyncer` role.
+pub const RELAYER: AccountAddress = AccountAddress([1u8; 32]);
+/// A user of the bridge.
+pub const USER: AccountAddress = AccountAddress([2u8; 32]);
+
+/// Address of the token on Ethereum that is mapped to the `cis2-bridgeable`
+/// instance.
+pub const ROOT_TOKEN: EthAddress = EthAddress {
+    eth_address: [1u8; 20],
+};
+/// Address of the user's wallet on Ethereum.
+pub const ETH_WALLET: EthAddress = EthAddress {
+    eth_address: [2u8; 20],
+};
+
+/// Energy reserved for each transaction.
+pub const ENERGY: testing::Energy = testing::Energy { energy: 100_000 };
+
+/// Roles of the `cis2-bridgeable` contract.
+#[derive(Serial, Deserial, Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TokenRole {
+    Admin,
+    Manager,
+    Pauser,
+}
+
+/// The parameter of `grantRole` and `removeRole` of the `cis2-bridgeable`
+/// contract.
+#[derive(Serial)]
+pub struct TokenRoleParams {
+    pub address: Address,
+    pub role:    TokenRole,
+}
+
+/// The parameter of `setPaused` of both contracts.
+#[derive(Serial)]
+pub struct SetPausedParams {
+    pub paused: bool,
+}
+
+/// The parameter of `withdraw` and `withdrawWithClaim` of the bridge manager.
+#[derive(Serial)]
+pub struct WithdrawParams {
+    pub eth_address: EthAddress,
+    pub amount:      cis2::TokenAmount,
+    pub token:       ContractAddress,
+    pub token_id:    cis2::TokenId,
+}
+
+/// The parameter of `init` of the `cis2-bridgeable` contract.
+#[derive(Serial)]
+pub struct InitTokenParams {
+    pub url:  String,
+    pub hash: Option<[u8; 32]>,
+}
+
+/// The parameter of `setWithdrawFee` and `setClaimFee` of the bridge manager.
+#[derive(Serial)]
+pub struct SetFeeParams {
+    pub amount: Amount,
+}
+
+/// The return value of `viewConfiguration` of the bridge manager.
+#[derive(Deserial, Debug)]
+pub struct ViewConfiguration {
+    pub paused:              bool,
+    pub emit_event_index:    u64,
+    pub withdraw_fee:        Amount,
+    pub treasurer_address:   AccountAddress,
+    pub claim_fee:           Amount,
+    pub processed_watermark: u64,
+    pub governance_delay:    Duration,
+}
+
+/// The bridge deployed on a test chain.
+pub struct Bridge {
+    pub chain:                   testing::Chain,
+    /// Reference of the current `bridge-manager` module.
+    pub bridge_manager_module:   ModuleReference,
+    /// Reference of the current `cis2-bridgeable` module.
+    pub cis2_bridgeable_module:  ModuleReference,
+    pub bridge_manager:          ContractAddress,
+    /// An instance of `cis2-bridgeable` which is not yet mapped.
+    pub token:                   ContractAddress,
+    /// Events emitted by the initialization of the bridge manager.
+    pub bridge_manager_init_log: Vec<ContractEvent>,
+}
+
+impl Bridge {
+    /// Deploy and initialize both contracts, and set up the roles. The bridge
+    /// manager is a `Manager` of the token, and [`RELAYER`] is a
+    /// `StateSyncer` of the bridge manager.
+    pub fn new() -> Self { Self::with_instances_of(None) }
+
+    /// Like [`new`](Self::new), but the instances are of the modules as first
+    /// deployed. The current modules are deployed as well, so that the
+    /// instances can be upgraded to them.
+    pub fn first_deployment() -> Self {
+        Self::with_instances_of(Some((
+            FIRST_BRIDGE_MANAGER_MODULE,
+            FIRST_CIS2_BRIDGEABLE_MODULE,
+        )))
+    }
+
+    /// Deploy the current modules, and instantiate the given modules, or the
+    /// current ones if none are given.
+    fn with_instances_of(modules: Option<(&str, &str)>) -> Self {
+        let mut chain = testing::Chain::new();
+        for account in [ADMIN, RELAYER, USER] {
+            chain.create_account(testing::Account::new(
+                chain_account(account),
+                testing::Amount::from_ccd(10_000),
+            ));
+        }
+        let bridge_manager_module = deploy(&mut chain, BRIDGE_MANAGER_MODULE);
+        let cis2_bridgeable_module = deploy(&mut chain, CIS2_BRIDGEABLE_MODULE);
+        let (bridge_manager_instance_module, cis2_bridgeable_instance_module) = match modules {
+            Some((bridge_manager, cis2_bridgeable)) => (
+                deploy(&mut chain, bridge_manager),
+                deploy(&mut chain, cis2_bridgeable),
+            ),
+            None => (bridge_manager_module, cis2_bridgeable_module),
+        };
+
+        let bridge_manager_init = chain
+            .contract_init(
+                testing::Signer::with_one_key(),
+                chain_account(ADMIN),
+                ENERGY,
+                testing::InitContractPayload {
+                    amount:    testing::Amount::zero(),
+                    mod_ref:   bridge_manager_instance_module,
+                    init_name: testing::OwnedContractName::new_unchecked(
+                        "init_bridge-manager".into(),
+                    ),
+                    param:     testing::OwnedParameter::empty(),
+                },
+            )
+            .expect("Initializing the bridge manager succeeds.");
+        let token = chain
+            .contract_init(
+                testing::Signer::with_one_key(),
+                chain_account(ADMIN),
+                ENERGY,
+                testing::InitContractPayload {
+                    amount:    testing::Amount::zero(),
+                    mod_ref:   cis2_bridgeable_instance_module,
+                    init_name: testing::OwnedContractName::new_unchecked(
+                        "init_cis2-bridgeable".into(),
+                    ),
+                    param:     parameter(&InitTokenParams {
+                        url:  "https://example.com/metadata".into(),
+                        hash: None,
+                    }),
+                },
+            )
+            .expect("Initializing the token succeeds.")
+            .contract_address;
+
+        let mut bridge = Self {
+            chain,
+            bridge_manager_module: ModuleReference::from(bridge_manager_module.bytes),
+            cis2_bridgeable_module: ModuleReference::from(cis2_bridgeable_module.bytes),
+            bridge_manager: contract_address(bridge_manager_init.contract_address),
+            token: contract_address(token),
+            bridge_manager_init_log: bridge_manager_init.events,
+        };
+        bridge
+            .update(
+                ADMIN,
+                bridge.bridge_manager,
+                "grantRole",
+                &GrantRoleParams {
+                    address: Address::Account(RELAYER),
+                    role:    Roles::StateSyncer,
+                },
+            )
+            .expect("Granting the StateSyncer role succeeds.");
+        bridge
+            .update(ADMIN, bridge.token, "grantRole", &TokenRoleParams {
+                address: Address::Contract(bridge.bridge_manager),
+                role:    TokenRole::Manager,
+            })
+            .expect("Granting the Manager role succeeds.");
+        bridge
+    }
+
+    /// Send an update transaction to the given contract. The name of the
+    /// contract is looked up from the address.
+    pub fn update<P: Serial>(
+        &mut self,
+        sender: AccountAddress,
+        contract: ContractAddress,
+        entrypoint: &str,
+        param: &P,
+    ) -> Result<ContractInvokeSuccess, ContractInvokeError> {
+        self.update_with_amount(sender, contract, entrypoint, param, Amount::zero())
+    }
+
+    /// Like [`update`](Self::update) but with an amount of CCD attached.
+    pub fn update_with_amount<P: Serial>(
+        &mut self,
+        sender: AccountAddress,
+        contract: ContractAddress,
+        entrypoint: &str,
+        param: &P,
+        amount: Amount,
+    ) -> Result<ContractInvokeSuccess, ContractInvokeError> {
+        let receive_name = self.receive_name(contract, entrypoint);
+        self.chain.contract_update(
+            testing::Signer::with_one_key(),
+            chain_account(sender),
+            testing::Address::Account(chain_account(sender)),
+            ENERGY,
+            testing::UpdateContractPayload {
+                amount: testing::Amount::from_micro_ccd(amount.micro_ccd),
+                address: chain_contract(contract),
+                receive_name,
+                message: parameter(param),
+            },
+        )
+    }
+
+    /// Invoke a view function of the given contract, and parse the return
+    /// value.
+    pub fn view<P: Serial, R: Deserial>(
+        &self,
+        contract: ContractAddress,
+        entrypoint: &str,
+        param: &P,
+    ) -> R {
+        let result = self
+            .chain
+            .contract_invoke(
+                chain_account(ADMIN),
+                testing::Address::Account(chain_account(ADMIN)),
+                ENERGY,
+                testing::UpdateContractPayload {
+                    amount:       testing::Amount::zero(),
+                    address:      chain_contract(contract),
+                    receive_name: self.receive_name(contract, entrypoint),
+                    message:      parameter(param),
+                },
+            )
+            .unwrap_or_else(|e| panic!("Invoking {entrypoint} failed: {e:?}"));
+        from_bytes(&result.return_value).expect("The return value is well-formed.")
+    }
+
+    /// Send a state update as the relayer.
+    pub fn send_state_update(
+        &mut self,
+        update: &relayer::StateUpdate,
+    ) -> Result<ContractInvokeSuccess, ContractInvokeError> {
+        self.send_state_update_as(RELAYER, update)
+    }
+
+    /// Send a state update, serialized as the relayer does, from the given
+    /// account.
+    pub fn send_state_update_as(
+        &mut self,
+        sender: AccountAddress,
+        update: &relayer::StateUpdate,
+    ) -> Result<ContractInvokeSuccess, ContractInvokeError> {
+        self.update(sender, self.bridge_manager, "receiveStateUpdate", update)
+    }
+
+    /// Map [`ROOT_TOKEN`] to the token as operation `id`.
+    pub fn map_token(&mut self, id: u64) -> ContractInvokeSuccess {
+        self.send_state_update(&token_map_operation(id, self.token))
+            .expect("Mapping the token succeeds.")
+    }
+
+    /// Deposit the amount of [`ROOT_TOKEN`] to [`USER`] as operation `id`.
+    pub fn deposit(
+        &mut self,
+        id: u64,
+        amount: u64,
+    ) -> Result<ContractInvokeSuccess, ContractInvokeError> {
+        self.send_state_update(&deposit_operation(id, amount))
+    }
+
+    /// Make the bridge manager an operator of the user's tokens, which is
+    /// required for withdrawing.
+    pub fn add_bridge_manager_as_operator(&mut self) {
+        let update = cis2::UpdateOperatorParams::new(vec![cis2::UpdateOperator {
+            update:   cis2::OperatorUpdate::Add,
+            operator: Address::Contract(self.bridge_manager),
+        }])
+        .expect("A single update is within the limits.");
+        self.update(USER, self.token, "updateOperator", &update)
+            .expect("Adding the bridge manager as an operator succeeds.");
+    }
+
+    /// Withdraw the amount of tokens of the user, paying `fee`. If `claim` is
+    /// set the relayer is requested to claim the withdrawal on Ethereum.
+    pub fn withdraw(
+        &mut self,
+        amount: u64,
+        fee: Amount,
+        claim: bool,
+    ) -> Result<ContractInvokeSuccess, ContractInvokeError> {
+        let entrypoint = if claim {
+            "withdrawWithClaim"
+        } else {
+            "withdraw"
+        };
+        let params = WithdrawParams {
+            eth_address: ETH_WALLET,
+            amount:      cis2::TokenAmount(amount.into()),
+            token:       self.token,
+            token_id:    token_id(),
+        };
+        self.update_with_amount(USER, self.bridge_manager, entrypoint, &params, fee)
+    }
+
+    /// Pause or unpause the given contract as the admin.
+    pub fn set_paused(
+        &mut self,
+        contract: ContractAddress,
+        paused: bool,
+    ) -> Result<ContractInvokeSuccess, ContractInvokeError> {
+        self.update(ADMIN, contract, "setPaused", &SetPausedParams { paused })
+    }
+
+    /// The token balance of the given address.
+    pub fn balance_of(&self, address: Address) -> cis2::TokenAmount {
+        let query = cis2::BalanceOfQueryParams::new(vec![cis2::BalanceOfQuery {
+            token_id: cis2::TokenId::new_unchecked(Vec::new()),
+            address,
+        }])
+        .expect("A single query is within the limits.");
+        let response: cis2::BalanceOfQueryResponse = self.view(self.token, "balanceOf", &query);
+        let mut amounts: Vec<cis2::TokenAmount> = response.into();
+        amounts.remove(0)
+    }
+
+    /// The CCD balance of the given account that is available for transfers.
+    pub fn ccd_balance(&self, account: AccountAddress) -> Amount {
+        let balance = self
+            .chain
+            .account_balance_available(chain_account(account))
+            .expect("The account exists.");
+        Amount::from_micro_ccd(balance.micro_ccd)
+    }
+
+    /// Advance the block time of the chain.
+    pub fn tick_block_time(&mut self, duration: Duration) {
+        self.chain
+            .tick_block_time(testing::Duration::from_millis(duration.millis()))
+            .expect("The block time does not overflow.");
+    }
+
+    /// Whether the bridge manager processed the operation with the given id.
+    pub fn is_processed(&self, id: u64) -> bool {
+        self.view(self.bridge_manager, "isProcessed", &id)
+    }
+
+    /// The configuration of the bridge manager.
+    pub fn configuration(&self) -> ViewConfiguration {
+        self.view(self.bridge_manager, "viewConfiguration", &())
+    }
+
+    fn receive_name(
+        &self,
+        contract: ContractAddress,
+        entrypoint: &str,
+    ) -> testing::OwnedReceiveName {
+        let contract_name = if contract == self.bridge_manager {
+            "bridge-manager"
+        } else {
+            "cis2-bridgeable"
+        };
+        testing::OwnedReceiveName::new_unchecked(format!("{contract_name}.{entrypoint}"))
+    }
+}
+
+impl Default for Bridge {
+    fn default() -> Self { Self::new() }
+}
+
+/// Deploy the module at the given path as [`ADMIN`].
+fn deploy(chain: &mut testing::Chain, path: &str) -> testing::ModuleReference {
+    let module = testing::module_load_v1(path)
+        .unwrap_or_else(|e| panic!("The module {path} has been built: {e}"));
+    chain
+        .module_deploy_v1(
+            testing::Signer::with_one_key(),
+            chain_account(ADMIN),
+            module,
+        )
+        .unwrap_or_else(|e| panic!("Deploying the module {path} succeeds: {e:?}"))
+        .module_reference
+}
+
+/// Serialize a parameter.
+pub fn parameter<P: Serial>(param: &P) -> testing::OwnedParameter {
+    testing::OwnedParameter::try_from(to_bytes(param)).expect("The parameter fits in a parameter.")
+}
+
+/// The reject reason of a contract that rejected the transaction. Panics if
+/// the transaction failed for another reason.
+pub fn reject_code(error: &ContractInvokeError) -> i32 {
+    match &error.kind {
+        testing::ContractInvokeErrorKind::ExecutionError {
+            failure_kind: testing::InvokeFailure::ContractReject { code, .. },
+        } => *code,
+        other => panic!("Expected the contract to reject, but got {other:?}."),
+    }
+}
+
+/// The events logged by the given contract in the transaction.
+pub fn contract_events(
+    contract: ContractAddress,
+    success: &ContractInvokeSuccess,
+) -> Vec<&ContractEvent> {
+    success
+        .events()
+        .filter(|(address, _)| *address == chain_contract(contract))
+        .flat_map(|(_, events)| events.iter())
+        .collect()
+}
+
+/// Parse the events logged by the bridge manager in the transaction, in the
+/// same way as the relayer does.
+pub fn bridge_events(
+    bridge_manager: ContractAddress,
+    success: &ContractInvokeSuccess,
+) -> Vec<relayer::BridgeEvent> {
+    contract_events(bridge_manager, success)
+        .into_iter()
+        .map(parse_bridge_event)
+        .collect()
+}
+
+/// Parse a single event of the bridge manager using the relayer's parser.
+pub fn parse_bridge_event(event: &ContractEvent) -> relayer::BridgeEvent {
+    from_bytes(event.as_ref()).expect("The relayer can parse the events of the bridge manager.")
+}
+
+/// The token map operation that the relayer sends for [`ROOT_TOKEN`].
+pub fn token_map_operation(id: u64, child: ContractAddress) -> relayer::StateUpdate {
+    relayer::StateUpdate::TokenMap(relayer::TokenMapOperation {
+        id,
+        root: ROOT_TOKEN,
+        child,
+    })
+}
+
+/// The state update that the relayer sends to skip operations that are not
+/// state updates.
+pub fn skip_operations(ids: Vec<u64>) -> relayer::StateUpdate {
+    relayer::StateUpdate::Skip(relayer::SkipOperations { ids })
+}
+
+/// The deposit operation that the relayer sends for a deposit of
+/// [`ROOT_TOKEN`] to [`USER`].
+pub fn deposit_operation(id: u64, amount: u64) -> relayer::StateUpdate {
+    relayer::StateUpdate::Deposit(relayer::DepositOperation {
+        id,
+        user: Address::Account(USER),
+        root: ROOT_TOKEN,
+        amount: cis2::TokenAmount(amount.into()),
+        token_id: token_id(),
+        hook: None,
+    })
+}
+
+/// The deposit operation that the relayer sends for a deposit of
+/// [`ROOT_TOKEN`] to a contract, which is notified via `entrypoint`.
+pub fn deposit_to_contract_operation(
+    id: u64,
+    amount: u64,
+    contract: ContractAddress,
+    entrypoint: &str,
+    data: Vec<u8>,
+) -> relayer::StateUpdate {
+    relayer::StateUpdate::Deposit(relayer::DepositOperation {
+        id,
+        user: Address::Contract(contract),
+        root: ROOT_TOKEN,
+        amount: cis2::TokenAmount(amount.into()),
+        token_id: token_id(),
+        hook: Some(relayer::DepositHook {
+            entrypoint: OwnedEntrypointName::new_unchecked(entrypoint.into()),
+            data,
+        }),
+    })
+}
+
+/// The token id used by the relayer. This is `TokenIdU64(0)` in the
+/// contracts.
+pub fn token_id() -> cis2::TokenId { cis2::TokenId::new_unchecked(vec![0u8; 8]) }
+
+/// Convert an account address to the type of the testing library.
+pub fn chain_account(address: AccountAddress) -> testing::AccountAddress {
+    testing::AccountAddress(address.0)
+}
+
+/// Convert a contract address to the type of the testing library.
+pub fn chain_contract(address: ContractAddress) -> testing::ContractAddress {
+    testing::ContractAddress::new(address.index, address.subindex)
+}
+
+/// Convert a contract address of the testing library.
+pub fn contract_address(address: testing::ContractAddress) -> ContractAddress {
+    ContractAddress::new(address.index, address.subindex)
+}
@@ -0,0 +1,996 @@
+//! Complete flows of the bridge on a test chain.
+use bridge_integration_tests::{error_codes::*, *};
+use bridge_types::token_admin_events;
+use ccdeth_relayer::concordium_contracts::{
+    AdminTransferEvent, BridgeEvent, ClaimRequestedEvent, CorrectionEvent, DepositEvent,
+    GovernanceAction, GrantRoleEvent, ProposalCreatedEvent, ProposalEvent, RevokeRoleEvent,
+    SetBlockedEvent, SetFeeEvent, SetGovernanceDelayEvent, SetPausedEvent, SetTokenFeeEvent,
+    TokenMapEvent, WithdrawEvent,
+};
+
+/// A bridge with the token mapped as operation 1, and `amount` deposited to
+/// the user as operation 3. The `StateSender` numbers its operations from 1,
+/// and operation 2 is, e.g., the registration of a vault, which is not a state
+/// update.
+fn bridge_with_deposit(amount: u64) -> Bridge {
+    let mut bridge = Bridge::new();
+    bridge.map_token(1);
+    bridge.deposit(3, amount).expect("The deposit succeeds.");
+    bridge
+}
+
+fn token_amount(amount: u64) -> cis2::TokenAmount { cis2::TokenAmount(amount.into()) }
+
+fn withdraw_event(bridge: &Bridge, event_index: u64, amount: u64) -> BridgeEvent {
+    withdraw_event_with_fee(bridge, event_index, amount, 0)
+}
+
+/// A withdrawal of `amount` after the fee of the token, `fee`, is deducted.
+fn withdraw_event_with_fee(
+    bridge: &Bridge,
+    event_index: u64,
+    amount: u64,
+    fee: u64,
+) -> BridgeEvent {
+    BridgeEvent::Withdraw(WithdrawEvent {
+        event_index,
+        contract: bridge.token,
+        amount: cis2::TokenAmount(amount.into()),
+        ccd_address: Address::Account(USER),
+        eth_address: ETH_WALLET,
+        token_id: token_id(),
+        fee: Some(cis2::TokenAmount(fee.into())),
+    })
+}
+
+#[test]
+fn init_event_is_parsed_by_the_relayer() {
+    let bridge = Bridge::new();
+    assert_eq!(bridge.bridge_manager_init_log.len(), 1);
+    assert_eq!(
+        parse_bridge_event(&bridge.bridge_manager_init_log[0]),
+        BridgeEvent::GrantRole(GrantRoleEvent {
+            address: Address::Account(ADMIN),
+            role:    Roles::Admin,
+        })
+    );
+}
+
+#[test]
+fn map_and_deposit() {
+    let mut bridge = Bridge::new();
+
+    let map = bridge.map_token(1);
+    assert_eq!(bridge_events(bridge.bridge_manager, &map), vec![
+        BridgeEvent::TokenMap(TokenMapEvent {
+            id:    1,
+            root:  ROOT_TOKEN,
+            child: bridge.token,
+        })
+    ]);
+
+    let deposit = bridge.deposit(3, 1_000).expect("The deposit succeeds.");
+    assert_eq!(bridge_events(bridge.bridge_manager, &deposit), vec![
+        BridgeEvent::Deposit(DepositEvent {
+            id:       3,
+            contract: bridge.token,
+            amount:   cis2::TokenAmount(1_000u64.into()),
+            token_id: token_id(),
+        })
+    ]);
+    // The token logs the mint, which the relayer ignores.
+    assert_eq!(contract_events(bridge.token, &deposit).len(), 1);
+    assert_eq!(
+        bridge.balance_of(Address::Account(USER)),
+        token_amount(1_000)
+    );
+    assert!(bridge.is_processed(1));
+    assert!(!bridge.is_processed(2));
+    assert!(bridge.is_processed(3));
+}
+
+#[test]
+fn duplicate_operations_are_rejected() {
+    let mut bridge = bridge_with_deposit(1_000);
+
+    let token = bridge.token;
+    let map_again = bridge
+        .send_state_update(&token_map_operation(1, token))
+        .expect_err("Mapping twice with the same id fails.");
+    assert_eq!(reject_code(&map_again), OPERATION_ALREADY_PROCESSED);
+    let deposit_again = bridge
+        .deposit(3, 1_000)
+        .expect_err("Depositing twice fails.");
+    assert_eq!(reject_code(&deposit_again), OPERATION_ALREADY_PROCESSED);
+    assert_eq!(
+        bridge.balance_of(Address::Account(USER)),
+        token_amount(1_000)
+    );
+}
+
+#[test]
+fn deposit_of_unmapped_token_is_rejected() {
+    let mut bridge = Bridge::new();
+
+    let deposit = bridge
+        .deposit(1, 1_000)
+        .expect_err("The token is not mapped.");
+    assert_eq!(reject_code(&deposit), TOKEN_NOT_MAPPED);
+    // The transaction is reverted, so the operation can be retried once the
+    // token is mapped.
+    assert!(!bridge.is_processed(1));
+}
+
+#[test]
+fn only_the_relayer_sends_state_updates() {
+    let mut bridge = Bridge::new();
+    let token = bridge.token;
+    let update = bridge
+        .send_state_update_as(USER, &token_map_operation(1, token))
+        .expect_err("Only the StateSyncer can send state updates.");
+    assert_eq!(reject_code(&update), UNAUTHORIZED);
+}
+
+#[test]
+fn withdraw_requires_the_bridge_manager_as_operator() {
+    let mut bridge = bridge_with_deposit(1_000);
+
+    let withdraw = bridge
+        .withdraw(400, Amount::zero(), false)
+        .expect_err("The bridge manager is not an operator.");
+    assert_eq!(reject_code(&withdraw), INVOKE_CONTRACT_ERROR);
+
+    bridge.add_bridge_manager_as_operator();
+    let withdraw = bridge
+        .withdraw(400, Amount::zero(), false)
+        .expect("The withdrawal succeeds.");
+    assert_eq!(bridge_events(bridge.bridge_manager, &withdraw), vec![
+        withdraw_event(&bridge, 1, 400)
+    ]);
+    assert_eq!(bridge.balance_of(Address::Account(USER)), token_amount(600));
+
+    // Event indices of withdrawals are consecutive.
+    let withdraw = bridge
+        .withdraw(600, Amount::zero(), false)
+        .expect("The second withdrawal succeeds.");
+    assert_eq!(bridge_events(bridge.bridge_manager, &withdraw), vec![
+        withdraw_event(&bridge, 2, 600)
+    ]);
+    assert_eq!(bridge.balance_of(Address::Account(USER)), token_amount(0));
+}
+
+#[test]
+fn pauser_can_only_pause() {
+    let mut bridge = bridge_with_deposit(1_000);
+    let bridge_manager = bridge.bridge_manager;
+    let token = bridge.token;
+    bridge
+        .update(ADMIN, bridge_manager, "grantRole", &GrantRoleParams {
+            address: Address::Account(USER),
+            role:    Roles::Pauser,
+        })
+        .expect("Granting the pauser role succeeds.");
+    bridge
+        .update(ADMIN, token, "grantRole", &TokenRoleParams {
+            address: Address::Account(USER),
+            role:    TokenRole::Pauser,
+        })
+        .expect("Granting the pauser role of the token succeeds.");
+
+    for contract in [bridge_manager, token] {
+        bridge
+            .update(USER, contract, "setPaused", &SetPausedParams {
+                paused: true,
+            })
+            .expect("The pauser can pause.");
+        bridge
+            .update(USER, contract, "setPaused", &SetPausedParams {
+                paused: false,
+            })
+            .expect_err("The pauser cannot unpause.");
+    }
+    let deposit = bridge
+        .deposit(4, 1_000)
+        .expect_err("The bridge manager is paused.");
+    assert_eq!(reject_code(&deposit), CONTRACT_PAUSED);
+
+    for contract in [bridge_manager, token] {
+        bridge
+            .set_paused(contract, false)
+            .expect("The admin can unpause.");
+    }
+    bridge
+        .deposit(4, 1_000)
+        .expect("The deposit succeeds after unpausing.");
+}
+
+#[test]
+fn blocked_addresses_cannot_withdraw() {
+    let mut bridge = bridge_with_deposit(1_000);
+    bridge.add_bridge_manager_as_operator();
+    let bridge_manager = bridge.bridge_manager;
+    bridge
+        .update(ADMIN, bridge_manager, "grantRole", &GrantRoleParams {
+            address: Address::Account(RELAYER),
+            role:    Roles::Blocklister,
+        })
+        .expect("Granting the blocklister role succeeds.");
+
+    let user = BlockedAddress::Concordium(Address::Account(USER));
+    let wallet = BlockedAddress::Ethereum(ETH_WALLET);
+    let unauthorized = bridge
+        .update(ADMIN, bridge_manager, "setBlocked", &SetBlockedParams {
+            address: user,
+            blocked: true,
+        })
+        .expect_err("Only a blocklister can block addresses.");
+    assert_eq!(reject_code(&unauthorized), UNAUTHORIZED);
+
+    for address in [user, wallet] {
+        let block = bridge
+            .update(RELAYER, bridge_manager, "setBlocked", &SetBlockedParams {
+                address,
+                blocked: true,
+            })
+            .expect("Blocking the address succeeds.");
+        assert_eq!(bridge_events(bridge_manager, &block), vec![
+            BridgeEvent::SetBlocked(SetBlockedEvent {
+                address,
+                blocked: true,
+            })
+        ]);
+        let blocked: bool = bridge.view(bridge_manager, "isBlocked", &address);
+        assert!(blocked);
+        let withdraw = bridge
+            .withdraw(400, Amount::zero(), false)
+            .expect_err("Blocked addresses cannot withdraw.");
+        assert_eq!(reject_code(&withdraw), ADDRESS_BLOCKED);
+
+        bridge
+            .update(RELAYER, bridge_manager, "setBlocked", &SetBlockedParams {
+                address,
+                blocked: false,
+            })
+            .expect("Unblocking the address succeeds.");
+    }
+    bridge
+        .withdraw(400, Amount::zero(), false)
+        .expect("The withdrawal succeeds after unblocking.");
+    assert_eq!(bridge.balance_of(Address::Account(USER)), token_amount(600));
+}
+
+#[test]
+fn withdraw_with_claim_pays_the_fees() {
+    let mut bridge = bridge_with_deposit(1_000);
+    bridge.add_bridge_manager_as_operator();
+    let withdraw_fee = Amount::from_micro_ccd(100);
+    let claim_fee = Amount::from_micro_ccd(1_000);
+    let bridge_manager = bridge.bridge_manager;
+    bridge
+        .update(ADMIN, bridge_manager, "setWithdrawFee", &SetFeeParams {
+            amount: withdraw_fee,
+        })
+        .expect("Setting the withdraw fee succeeds.");
+    bridge
+        .update(ADMIN, bridge_manager, "setClaimFee", &SetFeeParams {
+            amount: claim_fee,
+        })
+        .expect("Setting the claim fee succeeds.");
+
+    let too_low = bridge
+        .withdraw(400, Amount::from_micro_ccd(50), false)
+        .expect_err("The withdraw fee is not paid.");
+    assert_eq!(reject_code(&too_low), WITHDRAW_FEE_TOO_LOW);
+    let too_low = bridge
+        .withdraw(400, withdraw_fee, true)
+        .expect_err("The claim fee is not paid.");
+    assert_eq!(reject_code(&too_low), CLAIM_FEE_TOO_LOW);
+
+    let treasury_before = bridge.ccd_balance(ADMIN);
+    let withdraw = bridge
+        .withdraw(400, withdraw_fee + claim_fee, true)
+        .expect("The withdrawal succeeds.");
+    assert_eq!(bridge_events(bridge.bridge_manager, &withdraw), vec![
+        withdraw_event(&bridge, 1, 400),
+        BridgeEvent::ClaimRequested(ClaimRequestedEvent {
+            event_index: 1,
+            fee:         claim_fee,
+        }),
+    ]);
+    assert_eq!(
+        bridge.ccd_balance(ADMIN),
+        treasury_before + withdraw_fee + claim_fee,
+        "The fees are transferred to the treasurer."
+    );
+}
+
+#[test]
+fn withdraw_pays_the_token_fee_to_the_treasurer() {
+    let mut bridge = bridge_with_deposit(1_000);
+    bridge.add_bridge_manager_as_operator();
+    let bridge_manager = bridge.bridge_manager;
+    let token = bridge.token;
+
+    // A fee of 10 tokens plus 1% of the withdrawn amount.
+    let set_fee = bridge
+        .update(ADMIN, bridge_manager, "setTokenFee", &SetTokenFeeParams {
+            token,
+            fee: TokenFee {
+                flat:         token_amount(10),
+                basis_points: 100,
+                minimum:      token_amount(0),
+            },
+        })
+        .expect("Setting the fee of the token succeeds.");
+    assert_eq!(bridge_events(bridge_manager, &set_fee), vec![
+        BridgeEvent::SetTokenFee(SetTokenFeeEvent {
+            token,
+            fee:   TokenFee {
+                flat:         cis2::TokenAmount(10u64.into()),
+                basis_points: 100,
+                minimum:      cis2::TokenAmount(0u64.into()),
+            },
+        })
+    ]);
+
+    let too_low = bridge
+        .withdraw(5, Amount::zero(), false)
+        .expect_err("The amount does not cover the fee.");
+    assert_eq!(reject_code(&too_low), WITHDRAW_AMOUNT_TOO_LOW);
+
+    let withdraw = bridge
+        .withdraw(400, Amount::zero(), false)
+        .expect("The withdrawal succeeds.");
+    assert_eq!(bridge_events(bridge_manager, &withdraw), vec![
+        withdraw_event_with_fee(&bridge, 1, 386, 14)
+    ]);
+    assert_eq!(bridge.balance_of(Address::Account(USER)), token_amount(600));
+    assert_eq!(
+        bridge.balance_of(Address::Account(ADMIN)),
+        token_amount(14),
+        "The fee is minted to the treasurer."
+    );
+}
+
+#[test]
+fn corrections_are_proposed_and_mint_and_burn_through_the_token() {
+    let mut bridge = bridge_with_deposit(1_000);
+    let bridge_manager = bridge.bridge_manager;
+    let token = bridge.token;
+    let correction = |amount| CorrectionParams {
+        incident_id: 3,
+        token,
+        address: Address::Account(USER),
+        amount: token_amount(amount),
+    };
+    let correction_event = |amount: u64| CorrectionEvent {
+        incident_id: 3,
+        token,
+        address:     Address::Account(USER),
+        amount:      cis2::TokenAmount(amount.into()),
+    };
+    let min_delay = Duration::from_millis(MIN_CORRECTION_DELAY.millis());
+
+    let unauthorized = bridge
+        .update(USER, bridge_manager, "correctiveMint", &correction(250))
+        .expect_err("Only the admin corrects the supply.");
+    assert_eq!(reject_code(&unauthorized), UNAUTHORIZED);
+
+    // Corrections are proposed, even without a governance delay.
+    let mint = bridge
+        .update(ADMIN, bridge_manager, "correctiveMint", &correction(250))
+        .expect("Proposing the corrective mint succeeds.");
+    assert!(matches!(&bridge_events(bridge_manager, &mint)[..], [
+        BridgeEvent::ProposalCreated(ProposalCreatedEvent {
+            id: 0,
+            action: GovernanceAction::CorrectiveMint(_),
+            ..
+        })
+    ]));
+    let not_ready = bridge
+        .update(ADMIN, bridge_manager, "execute", &0u64)
+        .expect_err("Corrections wait for the minimum delay.");
+    assert_eq!(reject_code(&not_ready), PROPOSAL_NOT_READY);
+
+    let not_operator = bridge
+        .update(ADMIN, bridge_manager, "correctiveBurn", &correction(100))
+        .expect_err("The bridge manager is not an operator of the user.");
+    assert_eq!(reject_code(&not_operator), NOT_OPERATOR);
+    bridge.add_bridge_manager_as_operator();
+    bridge
+        .update(ADMIN, bridge_manager, "correctiveBurn", &correction(100))
+        .expect("Proposing the corrective burn succeeds.");
+    bridge
+        .update(
+            ADMIN,
+            bridge_manager,
+            "propose",
+            &GovernanceAction::CorrectiveBurn(correction(2_000)),
+        )
+        .expect("Proposing the corrective burn succeeds.");
+
+    bridge.tick_block_time(min_delay);
+    let mint = bridge
+        .update(ADMIN, bridge_manager, "execute", &0u64)
+        .expect("The corrective mint succeeds.");
+    assert_eq!(bridge_events(bridge_manager, &mint), vec![
+        BridgeEvent::ProposalExecuted(ProposalEvent { id: 0 }),
+        BridgeEvent::CorrectiveMint(correction_event(250)),
+    ]);
+    assert_eq!(
+        bridge.balance_of(Address::Account(USER)),
+        token_amount(1_250)
+    );
+
+    let burn = bridge
+        .update(ADMIN, bridge_manager, "execute", &1u64)
+        .expect("The corrective burn succeeds.");
+    assert_eq!(bridge_events(bridge_manager, &burn), vec![
+        BridgeEvent::ProposalExecuted(ProposalEvent { id: 1 }),
+        BridgeEvent::CorrectiveBurn(correction_event(100)),
+    ]);
+    assert_eq!(
+        bridge.balance_of(Address::Account(USER)),
+        token_amount(1_150)
+    );
+
+    let too_much = bridge
+        .update(ADMIN, bridge_manager, "execute", &2u64)
+        .expect_err("The user does not hold enough tokens.");
+    assert_eq!(reject_code(&too_much), INVOKE_CONTRACT_ERROR);
+}
+
+#[test]
+fn pause_stops_deposits_and_withdrawals() {
+    let mut bridge = bridge_with_deposit(1_000);
+    bridge.add_bridge_manager_as_operator();
+    let bridge_manager = bridge.bridge_manager;
+
+    let unauthorized = bridge
+        .update(USER, bridge_manager, "setPaused", &SetPausedParams {
+            paused: true,
+        })
+        .expect_err("Only the admin can pause.");
+    assert_eq!(reject_code(&unauthorized), UNAUTHORIZED);
+
+    bridge
+        .set_paused(bridge_manager, true)
+        .expect("Pausing succeeds.");
+    let deposit = bridge
+        .deposit(4, 1_000)
+        .expect_err("The bridge manager is paused.");
+    assert_eq!(reject_code(&deposit), CONTRACT_PAUSED);
+    let withdraw = bridge
+        .withdraw(400, Amount::zero(), false)
+        .expect_err("The bridge manager is paused.");
+    assert_eq!(reject_code(&withdraw), CONTRACT_PAUSED);
+    assert!(!bridge.is_processed(4));
+
+    bridge
+        .set_paused(bridge_manager, false)
+        .expect("Unpausing succeeds.");
+    bridge
+        .deposit(4, 1_000)
+        .expect("The deposit succeeds after unpausing.");
+
+    // A paused token makes the bridge manager reject as well.
+    let token = bridge.token;
+    bridge
+        .set_paused(token, true)
+        .expect("Pausing the token succeeds.");
+    let deposit = bridge.deposit(5, 1_000).expect_err("The token is paused.");
+    assert_eq!(reject_code(&deposit), INVOKE_CONTRACT_ERROR);
+    let withdraw = bridge
+        .withdraw(400, Amount::zero(), false)
+        .expect_err("The token is paused.");
+    assert_eq!(reject_code(&withdraw), INVOKE_CONTRACT_ERROR);
+    bridge
+        .set_paused(token, false)
+        .expect("Unpausing the token succeeds.");
+    bridge
+        .withdraw(400, Amount::zero(), false)
+        .expect("The withdrawal succeeds after unpausing.");
+    assert_eq!(
+        bridge.balance_of(Address::Account(USER)),
+        token_amount(1_600)
+    );
+}
+
+#[test]
+fn upgrade_with_migration_keeps_the_state() {
+    let mut bridge = bridge_with_deposit(1_000);
+    bridge.add_bridge_manager_as_operator();
+    let bridge_manager = bridge.bridge_manager;
+    let token = bridge.token;
+    let upgrade = |module, migrate| UpgradeParams { module, migrate };
+
+    let unauthorized = bridge
+        .update(
+            USER,
+            bridge_manager,
+            "upgrade",
+            &upgrade(bridge.bridge_manager_module, None),
+        )
+        .expect_err("Only the admin can upgrade.");
+    assert_eq!(reject_code(&unauthorized), UNAUTHORIZED);
+
+    let missing_module = bridge
+        .update(
+            ADMIN,
+            bridge_manager,
+            "upgrade",
+            &upgrade(ModuleReference::from([0u8; 32]), None),
+        )
+        .expect_err("The module does not exist.");
+    assert_eq!(reject_code(&missing_module), FAILED_UPGRADE_MISSING_MODULE);
+
+    let missing_contract = bridge
+        .update(
+            ADMIN,
+            bridge_manager,
+            "upgrade",
+            &upgrade(bridge.cis2_bridgeable_module, None),
+        )
+        .expect_err("The module does not contain the bridge manager.");
+    assert_eq!(
+        reject_code(&missing_contract),
+        FAILED_UPGRADE_MISSING_CONTRACT
+    );
+
+    // A failing migration reverts the upgrade.
+    let failed_migration = bridge
+        .update(
+            ADMIN,
+            bridge_manager,
+            "upgrade",
+            &upgrade(
+                bridge.bridge_manager_module,
+                Some((
+                    OwnedEntrypointName::new_unchecked("missing".into()),
+                    OwnedParameter::empty(),
+                )),
+            ),
+        )
+        .expect_err("The migration fails.");
+    assert_eq!(reject_code(&failed_migration), INVOKE_CONTRACT_ERROR);
+
+    let upgrade_bridge_manager = bridge
+        .update(
+            ADMIN,
+            bridge_manager,
+            "upgrade",
+            &upgrade(
+                bridge.bridge_manager_module,
+                Some((
+                    OwnedEntrypointName::new_unchecked("migrate".into()),
+                    OwnedParameter::from_serial(&100u64).expect("The parameter is small."),
+                )),
+            ),
+        )
+        .expect("Upgrading the bridge manager succeeds.");
+    let upgraded = upgrade_bridge_manager.effective_trace_elements().any(|e| {
+        matches!(e, ContractTraceElement::Upgraded { address, .. } if *address == chain_contract(bridge_manager))
+    });
+    assert!(upgraded, "The bridge manager is upgraded.");
+    bridge
+        .update(
+            ADMIN,
+            token,
+            "upgrade",
+            &upgrade(bridge.cis2_bridgeable_module, None),
+        )
+        .expect("Upgrading the token succeeds.");
+
+    // The state is kept, so processed operations are still rejected, and the
+    // event index continues.
+    assert!(bridge.is_processed(3));
+    let deposit_again = bridge
+        .deposit(3, 1_000)
+        .expect_err("Depositing twice fails.");
+    assert_eq!(reject_code(&deposit_again), OPERATION_ALREADY_PROCESSED);
+    assert_eq!(
+        bridge.balance_of(Address::Account(USER)),
+        token_amount(1_000)
+    );
+    bridge
+        .withdraw(400, Amount::zero(), false)
+        .expect("The withdrawal succeeds.");
+    let withdraw = bridge
+        .withdraw(600, Amount::zero(), false)
+        .expect("The operator is kept by the token.");
+    assert_eq!(bridge_events(bridge.bridge_manager, &withdraw), vec![
+        withdraw_event(&bridge, 2, 600)
+    ]);
+}
+
+#[test]
+fn out_of_order_operations_are_processed_once() {
+    let mut bridge = bridge_with_deposit(1_000);
+    // Operation 2 is not a state update, so the watermark stops below it.
+    assert_eq!(bridge.configuration().processed_watermark, 2);
+
+    bridge.deposit(6, 300).expect("The deposit succeeds.");
+    assert!(!bridge.is_processed(4));
+    assert!(bridge.is_processed(6));
+    let deposit_again = bridge.deposit(6, 300).expect_err("Depositing twice fails.");
+    assert_eq!(reject_code(&deposit_again), OPERATION_ALREADY_PROCESSED);
+
+    bridge.deposit(4, 200).expect("The deposit succeeds.");
+    assert_eq!(bridge.configuration().processed_watermark, 2);
+    // Operations 2 and 5 are, e.g., a vault registration and a withdrawal,
+    // which the relayer skips.
+    let unauthorized = bridge
+        .send_state_update_as(USER, &skip_operations(vec![2, 5]))
+        .expect_err("Only the StateSyncer can skip operations.");
+    assert_eq!(reject_code(&unauthorized), UNAUTHORIZED);
+    bridge
+        .send_state_update(&skip_operations(vec![2, 5]))
+        .expect("Skipping succeeds.");
+    assert_eq!(bridge.configuration().processed_watermark, 7);
+    for id in 1..7 {
+        assert!(bridge.is_processed(id));
+    }
+    assert!(!bridge.is_processed(7));
+    let skip_again = bridge
+        .send_state_update(&skip_operations(vec![2, 5]))
+        .expect_err("Skipping twice fails.");
+    assert_eq!(reject_code(&skip_again), OPERATION_ALREADY_PROCESSED);
+    for id in [3, 4, 6] {
+        let deposit_again = bridge
+            .deposit(id, 100)
+            .expect_err("Depositing twice fails.");
+        assert_eq!(reject_code(&deposit_again), OPERATION_ALREADY_PROCESSED);
+    }
+    assert_eq!(
+        bridge.balance_of(Address::Account(USER)),
+        token_amount(1_500)
+    );
+
+    // Only the admin can compact the processed operations.
+    let unauthorized = bridge
+        .update(USER, bridge.bridge_manager, "migrate", &100u64)
+        .expect_err("Only the admin can migrate.");
+    assert_eq!(reject_code(&unauthorized), UNAUTHORIZED);
+    bridge
+        .update(ADMIN, bridge.bridge_manager, "migrate", &100u64)
+        .expect("Migrating a migrated state succeeds.");
+    assert!(bridge.is_processed(6));
+    assert!(!bridge.is_processed(7));
+
+    // The relayer skips at most 100 operations in one update.
+    bridge
+        .send_state_update(&skip_operations((7..107).collect()))
+        .expect("Skipping the most operations in one update succeeds.");
+    assert_eq!(bridge.configuration().processed_watermark, 107);
+}
+
+#[test]
+fn the_first_deployment_is_migrated() {
+    let mut bridge = Bridge::first_deployment();
+    bridge.map_token(1);
+    for id in [3, 5] {
+        bridge.deposit(id, 100).expect("The deposit succeeds.");
+    }
+
+    let bridge_manager = bridge.bridge_manager;
+    let module = bridge.bridge_manager_module;
+    bridge
+        .update(ADMIN, bridge_manager, "upgrade", &UpgradeParams {
+            module,
+            migrate: Some((
+                OwnedEntrypointName::new_unchecked("migrate".into()),
+                OwnedParameter::from_serial(&100u64).expect("The parameter is small."),
+            )),
+        })
+        .expect("Upgrading the bridge manager succeeds.");
+
+    // The first deployment kept every operation in the set, so the watermark
+    // only moves up to the first gap.
+    let configuration = bridge.configuration();
+    assert_eq!(configuration.processed_watermark, 2);
+    assert_eq!(configuration.claim_fee, Amount::zero());
+    assert_eq!(configuration.governance_delay, Duration::from_millis(0));
+    for id in [1, 3, 5] {
+        assert!(bridge.is_processed(id));
+    }
+    for id in [2, 4, 6] {
+        assert!(!bridge.is_processed(id));
+    }
+    let deposit_again = bridge.deposit(3, 100).expect_err("Depositing twice fails.");
+    assert_eq!(reject_code(&deposit_again), OPERATION_ALREADY_PROCESSED);
+
+    bridge
+        .send_state_update(&skip_operations(vec![2, 4]))
+        .expect("Skipping succeeds.");
+    assert_eq!(bridge.configuration().processed_watermark, 6);
+    bridge.deposit(6, 100).expect("The deposit succeeds.");
+    assert_eq!(bridge.configuration().processed_watermark, 7);
+    assert_eq!(bridge.balance_of(Address::Account(USER)), token_amount(300));
+}
+
+#[test]
+fn paginated_views_and_lookups() {
+    let bridge = bridge_with_deposit(1_000);
+    let bridge_manager = bridge.bridge_manager;
+
+    // The roles, one address at a time.
+    let (roles, next): (Vec<(Address, Vec<Roles>)>, Option<Address>) =
+        bridge.view(bridge_manager, "viewRolesPage", &(None::<Address>, 1u32));
+    assert_eq!(roles, vec![(Address::Account(ADMIN), vec![Roles::Admin])]);
+    assert_eq!(next, Some(Address::Account(RELAYER)));
+    let (roles, next): (Vec<(Address, Vec<Roles>)>, Option<Address>) =
+        bridge.view(bridge_manager, "viewRolesPage", &(next, 1u32));
+    assert_eq!(roles, vec![(Address::Account(RELAYER), vec![
+        Roles::StateSyncer
+    ])]);
+    assert_eq!(next, None);
+    let roles: Vec<Roles> =
+        bridge.view(bridge_manager, "viewAddressRoles", &Address::Account(USER));
+    assert!(roles.is_empty());
+
+    // The token mappings, and the lookups in both directions.
+    let (mappings, next): (Vec<(EthAddress, ContractAddress)>, Option<EthAddress>) = bridge.view(
+        bridge_manager,
+        "viewTokenMappingsPage",
+        &(None::<EthAddress>, 10u32),
+    );
+    assert_eq!(mappings, vec![(ROOT_TOKEN, bridge.token)]);
+    assert_eq!(next, None);
+    let child: Option<ContractAddress> = bridge.view(bridge_manager, "viewChildToken", &ROOT_TOKEN);
+    assert_eq!(child, Some(bridge.token));
+    let root: Option<EthAddress> = bridge.view(bridge_manager, "viewRootToken", &bridge.token);
+    assert_eq!(root, Some(ROOT_TOKEN));
+    let child: Option<ContractAddress> = bridge.view(bridge_manager, "viewChildToken", &ETH_WALLET);
+    assert_eq!(child, None);
+
+    // The token owners.
+    let (owners, next): (Vec<Address>, Option<Address>) = bridge.view(
+        bridge.token,
+        "viewTokenOwnersPage",
+        &(None::<Address>, 10u32),
+    );
+    assert_eq!(owners, vec![Address::Account(USER)]);
+    assert_eq!(next, None);
+}
+
+#[test]
+fn admin_actions_are_timelocked_once_there_is_a_delay() {
+    let mut bridge = Bridge::new();
+    let bridge_manager = bridge.bridge_manager;
+    let delay = Duration::from_millis(3_600_000);
+
+    // Without a delay a proposal is executed right away.
+    let propose = bridge
+        .update(
+            ADMIN,
+            bridge_manager,
+            "propose",
+            &GovernanceAction::SetGovernanceDelay(delay),
+        )
+        .expect("Proposing succeeds.");
+    assert!(matches!(&bridge_events(bridge_manager, &propose)[..], [
+        BridgeEvent::ProposalCreated(ProposalCreatedEvent { id: 0, .. })
+    ]));
+    let execute = bridge
+        .update(ADMIN, bridge_manager, "execute", &0u64)
+        .expect("Executing succeeds.");
+    assert_eq!(bridge_events(bridge_manager, &execute), vec![
+        BridgeEvent::ProposalExecuted(ProposalEvent { id: 0 }),
+        BridgeEvent::SetGovernanceDelay(SetGovernanceDelayEvent { delay }),
+    ]);
+
+    // Now the admin can only propose.
+    let direct = bridge
+        .update(ADMIN, bridge_manager, "grantRole", &GrantRoleParams {
+            address: Address::Account(USER),
+            role:    Roles::StateSyncer,
+        })
+        .expect_err("Granting a role directly fails.");
+    assert_eq!(reject_code(&direct), GOVERNANCE_DELAY_ACTIVE);
+    let grant = GovernanceAction::GrantRole(GrantRoleParams {
+        address: Address::Account(USER),
+        role:    Roles::StateSyncer,
+    });
+    bridge
+        .update(USER, bridge_manager, "propose", &grant)
+        .expect_err("Only the admin can propose.");
+    bridge
+        .update(ADMIN, bridge_manager, "propose", &grant)
+        .expect("Proposing succeeds.");
+    let early = bridge
+        .update(ADMIN, bridge_manager, "execute", &1u64)
+        .expect_err("Executing before the delay fails.");
+    assert_eq!(reject_code(&early), PROPOSAL_NOT_READY);
+
+    bridge.tick_block_time(delay);
+    let execute = bridge
+        .update(ADMIN, bridge_manager, "execute", &1u64)
+        .expect("Executing after the delay succeeds.");
+    assert_eq!(bridge_events(bridge_manager, &execute), vec![
+        BridgeEvent::ProposalExecuted(ProposalEvent { id: 1 }),
+        BridgeEvent::GrantRole(GrantRoleEvent {
+            address: Address::Account(USER),
+            role:    Roles::StateSyncer,
+        }),
+    ]);
+
+    // A cancelled proposal cannot be executed.
+    bridge
+        .update(ADMIN, bridge_manager, "propose", &grant)
+        .expect("Proposing succeeds.");
+    let cancel = bridge
+        .update(ADMIN, bridge_manager, "cancel", &2u64)
+        .expect("Cancelling succeeds.");
+    assert_eq!(bridge_events(bridge_manager, &cancel), vec![
+        BridgeEvent::ProposalCancelled(ProposalEvent { id: 2 })
+    ]);
+    bridge.tick_block_time(delay);
+    let cancelled = bridge
+        .update(ADMIN, bridge_manager, "execute", &2u64)
+        .expect_err("Executing a cancelled proposal fails.");
+    assert_eq!(reject_code(&cancelled), PROPOSAL_NOT_FOUND);
+}
+
+#[test]
+fn admin_transfer_is_accepted_by_the_new_admin() {
+    let mut bridge = Bridge::new();
+    let bridge_manager = bridge.bridge_manager;
+
+    bridge
+        .update(
+            ADMIN,
+            bridge_manager,
+            "propose",
+            &GovernanceAction::TransferAdmin(Address::Account(USER)),
+        )
+        .expect("Proposing succeeds.");
+    let execute = bridge
+        .update(ADMIN, bridge_manager, "execute", &0u64)
+        .expect("Executing succeeds.");
+    let transfer = AdminTransferEvent {
+        from: Address::Account(ADMIN),
+        to:   Address::Account(USER),
+    };
+    assert_eq!(bridge_events(bridge_manager, &execute), vec![
+        BridgeEvent::ProposalExecuted(ProposalEvent { id: 0 }),
+        BridgeEvent::AdminTransferPending(transfer.clone()),
+    ]);
+
+    let wrong_sender = bridge
+        .update(RELAYER, bridge_manager, "acceptAdmin", &())
+        .expect_err("Only the new admin can accept.");
+    assert_eq!(reject_code(&wrong_sender), NO_ADMIN_TRANSFER);
+    let accept = bridge
+        .update(USER, bridge_manager, "acceptAdmin", &())
+        .expect("Accepting succeeds.");
+    assert_eq!(bridge_events(bridge_manager, &accept), vec![
+        BridgeEvent::AdminTransferAccepted(transfer),
+        BridgeEvent::GrantRole(GrantRoleEvent {
+            address: Address::Account(USER),
+            role:    Roles::Admin,
+        }),
+        BridgeEvent::RevokeRole(RevokeRoleEvent {
+            address: Address::Account(ADMIN),
+            role:    Roles::Admin,
+        }),
+    ]);
+
+    let old_admin = bridge
+        .update(ADMIN, bridge_manager, "setPaused", &SetPausedParams {
+            paused: true,
+        })
+        .expect_err("The old admin is no longer an admin.");
+    assert_eq!(reject_code(&old_admin), UNAUTHORIZED);
+    bridge
+        .update(USER, bridge_manager, "setPaused", &SetPausedParams {
+            paused: true,
+        })
+        .expect("The new admin can pause.");
+}
+
+#[test]
+fn configuration_changes_are_logged() {
+    let mut bridge = Bridge::new();
+    let bridge_manager = bridge.bridge_manager;
+    let token = bridge.token;
+
+    let fee = Amount::from_micro_ccd(100);
+    let set_fee = bridge
+        .update(ADMIN, bridge_manager, "setWithdrawFee", &SetFeeParams {
+            amount: fee,
+        })
+        .expect("Setting the withdraw fee succeeds.");
+    assert_eq!(bridge_events(bridge_manager, &set_fee), vec![
+        BridgeEvent::SetWithdrawFee(SetFeeEvent { fee })
+    ]);
+
+    let pause = bridge
+        .set_paused(bridge_manager, true)
+        .expect("Pausing the bridge manager succeeds.");
+    assert_eq!(bridge_events(bridge_manager, &pause), vec![
+        BridgeEvent::SetPaused(SetPausedEvent { paused: true })
+    ]);
+
+    // The token logs its own events, which the relayer classifies by their
+    // tag.
+    let pause = bridge
+        .set_paused(token, true)
+        .expect("Pausing the token succeeds.");
+    let tags = contract_events(token, &pause)
+        .into_iter()
+        .map(|event| event.as_ref()[0])
+        .collect::<Vec<_>>();
+    assert_eq!(tags, vec![token_admin_events::SET_PAUSED]);
+    assert_eq!(
+        token_admin_events::admin_action(tags[0]),
+        Some("set_paused")
+    );
+}
+
+#[test]
+fn deposit_to_a_contract_calls_the_hook() {
+    let mut bridge = Bridge::new();
+    bridge.map_token(1);
+    let token = bridge.token;
+    let receiver = bridge.bridge_manager;
+
+    // The `accepted` flags of the hook events logged by the token.
+    let hook_events = |result: &ContractInvokeSuccess| {
+        contract_events(token, result)
+            .into_iter()
+            .filter(|event| event.as_ref()[0] == token_admin_events::DEPOSIT_HOOK)
+            .map(|event| *event.as_ref().last().unwrap())
+            .collect::<Vec<_>>()
+    };
+
+    // Any entrypoint that accepts the parameter accepts the deposit.
+    let accepted = bridge
+        .send_state_update(&deposit_to_contract_operation(
+            2,
+            1_000,
+            receiver,
+            "viewGovernance",
+            vec![1, 2, 3],
+        ))
+        .expect("The deposit succeeds.");
+    assert_eq!(hook_events(&accepted), vec![1]);
+    assert_eq!(
+        bridge.balance_of(Address::Contract(receiver)),
+        token_amount(1_000)
+    );
+
+    // A rejecting hook does not block the bridge, and the tokens are minted
+    // anyway.
+    let rejected = bridge
+        .send_state_update(&deposit_to_contract_operation(
+            3,
+            500,
+            receiver,
+            "onReceivingCIS2",
+            Vec::new(),
+        ))
+        .expect("The deposit succeeds.");
+    assert_eq!(hook_events(&rejected), vec![0]);
+    assert_eq!(
+        bridge.balance_of(Address::Contract(receiver)),
+        token_amount(1_500)
+    );
+
+    // Tokens are not minted to contracts that do not exist.
+    let missing = ContractAddress::new(1_000, 0);
+    let deposit = bridge
+        .send_state_update(&deposit_to_contract_operation(
+            4,
+            500,
+            missing,
+            "onReceivingCIS2",
+            Vec::new(),
+        ))
+        .expect_err("The deposit to a missing contract fails.");
+    assert_eq!(reject_code(&deposit), INVOKE_CONTRACT_ERROR);
+    assert!(!bridge.is_processed(4));
+    assert_eq!(
+        bridge.balance_of(Address::Contract(missing)),
+        token_amount(0)
+    );
+}
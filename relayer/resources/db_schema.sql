@@ -20,6 +20,10 @@ EXCEPTION
     WHEN duplicate_object THEN null;
 END $$;
 
+-- A relayer in shadow mode records the transactions it would send with this
+-- status. They are never sent.
+ALTER TYPE concordium_transaction_status ADD VALUE IF NOT EXISTS 'shadow';
+
 -- Type of a concordium event we keep track of.
 DO $$ BEGIN
 CREATE TYPE concordium_event_type AS ENUM (
@@ -27,12 +31,21 @@ CREATE TYPE concordium_event_type AS ENUM (
     'deposit',
     'withdraw',
     'grant_role',
-    'revoke_role'
+    'revoke_role',
+    'claim_requested'
    );
 EXCEPTION
     WHEN duplicate_object THEN null;
 END $$;
 
+-- Databases created before claims were supported do not have the value.
+ALTER TYPE concordium_event_type ADD VALUE IF NOT EXISTS 'claim_requested';
+
+-- Corrective mints and burns of the bridge manager, which fix the supply of a
+-- token after an incident.
+ALTER TYPE concordium_event_type ADD VALUE IF NOT EXISTS 'corrective_mint';
+ALTER TYPE concordium_event_type ADD VALUE IF NOT EXISTS 'corrective_burn';
+
 -- Status of an Ethereum transaction we have submitted.
 DO $$ BEGIN
 CREATE TYPE ethereum_transaction_status AS ENUM (
@@ -44,6 +57,19 @@ EXCEPTION
     WHEN duplicate_object THEN null;
 END $$;
 
+-- Claim transactions that were confirmed, but reverted, have this status.
+ALTER TYPE ethereum_transaction_status ADD VALUE IF NOT EXISTS 'failed';
+
+-- Purpose of an Ethereum transaction we have submitted.
+DO $$ BEGIN
+CREATE TYPE ethereum_transaction_kind AS ENUM (
+    'merkle_root',
+    'claim'
+   );
+EXCEPTION
+    WHEN duplicate_object THEN null;
+END $$;
+
 -- Type of a network, used for checkpointing.
 DO $$ BEGIN
 CREATE TYPE network AS ENUM (
@@ -126,6 +152,31 @@ CREATE TABLE IF NOT EXISTS concordium_events (
 -- the Merkle proof for the current root.
 CREATE INDEX IF NOT EXISTS approved_withdrawals_index ON concordium_events (root);
 
+-- The Merkle root that contained the event before the current value of `root`
+-- was set, if any. The leaves of a root are the events that have it as either
+-- `root` or `previous_root`. This allows constructing proofs against the
+-- previous Merkle root, which the RootChainManager also accepts.
+ALTER TABLE concordium_events ADD COLUMN IF NOT EXISTS previous_root BYTEA;
+
+-- Index for the benefit of the API server, so that it can efficiently construct
+-- the Merkle proof for the previous root.
+CREATE INDEX IF NOT EXISTS previously_approved_withdrawals_index ON concordium_events (previous_root);
+
+-- If withdraw event, and the user requested the relayer to claim the withdrawal
+-- on Ethereum, the fee paid for that in microCCD. Otherwise NULL.
+ALTER TABLE concordium_events ADD COLUMN IF NOT EXISTS claim_fee INT8;
+
+-- Slot time of the Concordium block that contains the event. Used to measure
+-- the latency of withdrawals. NULL for events recorded before the column was
+-- added.
+ALTER TABLE concordium_events ADD COLUMN IF NOT EXISTS block_time timestamp with time zone;
+
+-- If withdraw event, the fee taken in the withdrawn token as a decimal string.
+-- It is not included in `amount`, which is the amount that is withdrawn on
+-- Ethereum. NULL for other events, and for withdrawals logged before the bridge
+-- manager took fees in tokens.
+ALTER TABLE concordium_events ADD COLUMN IF NOT EXISTS fee TEXT;
+
 -- Mapping of tokens 
 CREATE TABLE IF NOT EXISTS token_maps (
        id SERIAL8 PRIMARY KEY UNIQUE,
@@ -185,6 +236,52 @@ CREATE TABLE IF NOT EXISTS ethereum_deposit_events (
        CONSTRAINT ethereum_deposit_events_origin_event_index_unique UNIQUE (origin_event_index)
        );
 
+-- Timestamp of the Ethereum block that contains the deposit. Used to measure
+-- the latency of deposits. NULL for deposits recorded before the column was
+-- added.
+ALTER TABLE ethereum_deposit_events ADD COLUMN IF NOT EXISTS lock_time timestamp with time zone;
+
+-- Whether the deposit is held in quarantine because the depositor or the
+-- receiver is on the blocklist. No transaction is sent to Concordium for a
+-- quarantined deposit.
+ALTER TABLE ethereum_deposit_events ADD COLUMN IF NOT EXISTS quarantined BOOLEAN NOT NULL DEFAULT FALSE;
+-- Concordium account that receives the deposit, or that the deposit falls back
+-- to if it is made to a contract. NULL for deposits recorded before the column
+-- was added.
+ALTER TABLE ethereum_deposit_events ADD COLUMN IF NOT EXISTS receiver BYTEA;
+
+-- Index for the benefit of the API server, so that it can efficiently list the
+-- quarantined deposits.
+CREATE INDEX IF NOT EXISTS ethereum_deposit_events_quarantined_index ON ethereum_deposit_events (id) WHERE quarantined;
+
+-- Decision of a reviewer on a quarantined deposit.
+DO $$ BEGIN
+CREATE TYPE quarantine_decision AS ENUM (
+    -- The deposit is sent to Concordium as it was made.
+    'release',
+    -- The deposit is not sent to Concordium. Its operation is skipped in the
+    -- bridge manager, and the tokens stay locked on Ethereum.
+    'reject'
+   );
+EXCEPTION
+    WHEN duplicate_object THEN null;
+END $$;
+
+-- The deposit operation of a quarantined deposit, serialized as in the
+-- bridge manager's state update, which is sent to Concordium if the deposit is
+-- released. NULL for deposits quarantined before the column was added.
+ALTER TABLE ethereum_deposit_events ADD COLUMN IF NOT EXISTS quarantined_operation BYTEA;
+-- The decision on a quarantined deposit, set with the ccdeth_quarantine tool.
+-- NULL while the deposit is under review.
+ALTER TABLE ethereum_deposit_events ADD COLUMN IF NOT EXISTS quarantine_decision quarantine_decision;
+-- Time when the relayer sent the transaction carrying out the decision. The
+-- decision can be changed until then.
+ALTER TABLE ethereum_deposit_events ADD COLUMN IF NOT EXISTS quarantine_resolved_time timestamp with time zone;
+
+-- Index for the benefit of the relayer, which looks for decisions to carry
+-- out before each batch of Ethereum events.
+CREATE INDEX IF NOT EXISTS ethereum_deposit_events_decided_index ON ethereum_deposit_events (id) WHERE quarantine_decision IS NOT NULL AND quarantine_resolved_time IS NULL;
+
 -- Transactions that we will or have submitted to the Etheruem chain. This is
 -- used to handle restarts of the service, so we don't lose track of any data we
 -- have sent.
@@ -201,6 +298,12 @@ CREATE TABLE IF NOT EXISTS ethereum_transactions (
        CONSTRAINT ethereum_transactions_tx_hash_unique UNIQUE (tx_hash)
        );
 
+-- Whether the transaction sets a Merkle root or claims a withdrawal on behalf
+-- of a user.
+ALTER TABLE ethereum_transactions ADD COLUMN IF NOT EXISTS kind ethereum_transaction_kind NOT NULL DEFAULT 'merkle_root';
+-- If a claim, the event index of the withdrawal that is claimed. Otherwise NULL.
+ALTER TABLE ethereum_transactions ADD COLUMN IF NOT EXISTS claim_event_index INT8;
+
 -- Checkpoints so we know where to continue when restarting.
 CREATE TABLE IF NOT EXISTS checkpoints (
        network network PRIMARY KEY UNIQUE,
@@ -227,3 +330,53 @@ CREATE TABLE IF NOT EXISTS expected_merkle_update (
        tag unit NOT NULL DEFAULT ('') UNIQUE,
        expected_time timestamp with time zone NOT NULL
 );
+
+-- Merkle roots computed by a relayer in shadow mode. They are never set on
+-- Ethereum, and are compared against the roots set by the production relayer.
+CREATE TABLE IF NOT EXISTS shadow_merkle_roots (
+       id SERIAL8 PRIMARY KEY UNIQUE,
+       root BYTEA NOT NULL,
+       -- Number of leaves of the Merkle tree.
+       num_leaves INT8 NOT NULL,
+       -- The largest event index of the leaves.
+       max_event_index INT8 NOT NULL,
+       -- Time when the root was computed.
+       computed_at timestamp with time zone NOT NULL DEFAULT NOW()
+);
+
+-- Audit log of the administrative actions on the bridge manager and on the
+-- mapped tokens, e.g., role changes, proposals, fee changes, pausing and
+-- upgrades. Used to review the history of the bridge's configuration.
+CREATE TABLE IF NOT EXISTS admin_events (
+       id SERIAL8 PRIMARY KEY UNIQUE,
+       -- Hash of the transaction that logged the event.
+       tx_hash BYTEA NOT NULL,
+       -- Address of the contract that logged the event.
+       contract_index INT8 NOT NULL,
+       contract_subindex INT8 NOT NULL,
+       -- Position of the event among all the events logged in the transaction.
+       log_index INT8 NOT NULL,
+       -- Name of the action, e.g., 'set_withdraw_fee'.
+       action TEXT NOT NULL,
+       -- Serialized event, exactly as logged by the contract, including its tag.
+       event_data BYTEA NOT NULL,
+       -- Slot time of the block that contains the event.
+       block_time timestamp with time zone NOT NULL,
+       CONSTRAINT admin_events_log_unique UNIQUE (tx_hash, log_index)
+);
+
+-- Index for the benefit of the API server, so that it can efficiently list the
+-- history of a single contract.
+CREATE INDEX IF NOT EXISTS admin_events_contract_index ON admin_events (contract_index, contract_subindex, id);
+
+-- Addresses whose deposits are held in quarantine, if the relayer reads its
+-- blocklist from the database. An address is either an Ethereum address in
+-- hex, with a 0x prefix, or a Concordium account address in base58. The table
+-- is read before each batch of Ethereum events, so changes take effect without
+-- restarting the relayer.
+CREATE TABLE IF NOT EXISTS blocklist (
+       address TEXT PRIMARY KEY,
+       -- Why the address is blocked, for the reviewers of the quarantine.
+       reason TEXT,
+       insert_time timestamp with time zone NOT NULL DEFAULT NOW()
+);
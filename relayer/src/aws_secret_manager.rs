@@ -1,3 +1,4 @@
+#[cfg(feature = "kms")]
 use aws_sdk_secretsmanager::Client;
 use concordium_rust_sdk::types::WalletAccount;
 use ethers::signers::LocalWallet;
@@ -10,7 +11,7 @@ use ethers::signers::LocalWallet;
 /// - `AWS_REGION`
 #[cfg(feature = "kms")]
 pub async fn get_concordium_keys_aws(secret_name: &str) -> anyhow::Result<WalletAccount> {
-    log::debug!("Loading Concordium keys from AWS secret manager!");
+    tracing::debug!("Loading Concordium keys from AWS secret manager!");
     let shared_config = aws_config::load_from_env().await;
 
     let client = Client::new(&shared_config);
@@ -29,7 +30,7 @@ pub async fn get_concordium_keys_aws(secret_name: &str) -> anyhow::Result<Wallet
 
 #[cfg(feature = "default")]
 pub async fn get_concordium_keys_aws(secret_name: &str) -> anyhow::Result<WalletAccount> {
-    log::debug!("Loading Concordium keys from ENV!");
+    tracing::debug!("Loading Concordium keys from ENV!");
 
     let acc = WalletAccount::from_json_str(
         &std::env::var(format!("CONCORDIUM_KEY_{secret_name}")).unwrap(),
@@ -45,7 +46,7 @@ pub async fn get_concordium_keys_aws(secret_name: &str) -> anyhow::Result<Wallet
 /// - `AWS_REGION`
 #[cfg(feature = "kms")]
 pub async fn get_ethereum_keys_aws(secret_name: &str) -> anyhow::Result<LocalWallet> {
-    log::debug!("Loading Ethereum keys from AWS secret manager!");
+    tracing::debug!("Loading Ethereum keys from AWS secret manager!");
     let shared_config = aws_config::load_from_env().await;
 
     let client = Client::new(&shared_config);
@@ -64,7 +65,7 @@ pub async fn get_ethereum_keys_aws(secret_name: &str) -> anyhow::Result<LocalWal
 
 #[cfg(feature = "default")]
 pub async fn get_ethereum_keys_aws(secret_name: &str) -> anyhow::Result<LocalWallet> {
-    log::debug!("Loading Ethereum keys from ENV!");
+    tracing::debug!("Loading Ethereum keys from ENV!");
 
     let lw = std::env::var(format!("CONCORDIUM_KEY_{secret_name}"))?.parse()?;
     Ok(lw)
@@ -0,0 +1,266 @@
+//! Tiered balance thresholds for the accounts that the relayer sends
+//! transactions from.
+//!
+//! When the balance of an account drops below the minimum the relayer does not
+//! stop. Instead the tasks that send transactions from the account pause
+//! until the account is topped up, while the watchers keep recording
+//! deposits and withdrawals in the database. The levels are
+//! - [`BalanceLevel::Ok`], the balance is above the warning threshold,
+//! - [`BalanceLevel::Low`], the balance is below the warning threshold but
+//!   above the minimum. Transactions are still sent.
+//! - [`BalanceLevel::Degraded`], the balance is below the minimum. No
+//!   transactions are sent from the account.
+//! - [`BalanceLevel::Critical`], the balance is below the critical threshold.
+//!   As for degraded, but reported as an error.
+use crate::{health::Health, metrics::Metrics};
+use prometheus::{
+    core::{AtomicU64, GenericGauge},
+    IntGauge,
+};
+use std::collections::VecDeque;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BalanceLevel {
+    Ok = 0,
+    Low = 1,
+    Degraded = 2,
+    Critical = 3,
+}
+
+impl BalanceLevel {
+    /// Whether transactions may be sent from an account at this level.
+    pub fn can_send(self) -> bool {
+        self <= BalanceLevel::Low
+    }
+}
+
+/// Balance thresholds of an account, in the smallest unit used for the
+/// account's balance in the configuration, i.e., microCCD or microEther.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub struct Thresholds {
+    /// Below this balance warnings are emitted.
+    pub warn: u64,
+    /// Below this balance no transactions are sent.
+    pub min: u64,
+    /// Below this balance errors are emitted.
+    pub critical: u64,
+}
+
+impl Thresholds {
+    /// Construct thresholds, checking that they are ordered.
+    pub fn new(warn: u64, min: u64, critical: u64) -> anyhow::Result<Self> {
+        anyhow::ensure!(
+            critical <= min && min <= warn,
+            "Balance thresholds must satisfy critical ({critical}) <= minimum ({min}) <= warning \
+             ({warn})."
+        );
+        Ok(Self {
+            warn,
+            min,
+            critical,
+        })
+    }
+
+    pub fn level(&self, balance: u64) -> BalanceLevel {
+        if balance < self.critical {
+            BalanceLevel::Critical
+        } else if balance < self.min {
+            BalanceLevel::Degraded
+        } else if balance < self.warn {
+            BalanceLevel::Low
+        } else {
+            BalanceLevel::Ok
+        }
+    }
+}
+
+/// Tracks the balance level of an account, and opens or closes the
+/// [`SendGate`]s of the tasks that send transactions from it.
+pub struct BalanceMonitor {
+    /// Description of the account for logging.
+    account: &'static str,
+    /// Name of the health check of the account's balance.
+    check: &'static str,
+    /// Unit of the balance for logging.
+    unit: &'static str,
+    thresholds: Thresholds,
+    level: tokio::sync::watch::Sender<BalanceLevel>,
+    balance_gauge: GenericGauge<AtomicU64>,
+    level_gauge: IntGauge,
+}
+
+impl BalanceMonitor {
+    /// Construct a new monitor. The returned gate is open until the first
+    /// balance is recorded.
+    pub fn new(
+        account: &'static str,
+        check: &'static str,
+        unit: &'static str,
+        thresholds: Thresholds,
+        balance_gauge: GenericGauge<AtomicU64>,
+        level_gauge: IntGauge,
+    ) -> (Self, SendGate) {
+        let (level, receiver) = tokio::sync::watch::channel(BalanceLevel::Ok);
+        let monitor = Self {
+            account,
+            check,
+            unit,
+            thresholds,
+            level,
+            balance_gauge,
+            level_gauge,
+        };
+        (monitor, SendGate { level: receiver })
+    }
+
+    /// Use new thresholds from the next recorded balance onwards.
+    pub fn set_thresholds(&mut self, thresholds: Thresholds) {
+        if self.thresholds != thresholds {
+            tracing::info!(
+                "Using new balance thresholds for the {}: {thresholds:?}.",
+                self.account
+            );
+            self.thresholds = thresholds;
+        }
+    }
+
+    /// Record a newly queried balance of the account.
+    pub fn update(&self, metrics: &Metrics, health: &Health, balance: u64) -> BalanceLevel {
+        let Self {
+            account,
+            unit,
+            thresholds,
+            ..
+        } = self;
+        let level = thresholds.level(balance);
+        let previous = *self.level.borrow();
+        self.balance_gauge.set(balance);
+        self.level_gauge.set(level as i64);
+        health.set_check(
+            self.check,
+            level.can_send(),
+            serde_json::json!({
+                "level": level,
+                "balance": balance,
+                "thresholds": thresholds,
+            }),
+        );
+        match level {
+            BalanceLevel::Ok => {}
+            BalanceLevel::Low => {
+                metrics.warnings_total.inc();
+                tracing::warn!(
+                    "Balance {balance}{unit} of the {account} is below the warning threshold \
+                     {}{unit}.",
+                    thresholds.warn
+                );
+            }
+            BalanceLevel::Degraded => {
+                metrics.warnings_total.inc();
+                tracing::warn!(
+                    "Balance {balance}{unit} of the {account} is below the minimum {}{unit}. Not \
+                     sending transactions until it is topped up.",
+                    thresholds.min
+                );
+            }
+            BalanceLevel::Critical => {
+                metrics.errors_total.inc();
+                tracing::error!(
+                    "Balance {balance}{unit} of the {account} is below the critical threshold \
+                     {}{unit}. Not sending transactions until it is topped up.",
+                    thresholds.critical
+                );
+            }
+        }
+        if previous.can_send() && !level.can_send() {
+            tracing::warn!("Pausing sending of transactions from the {account}.");
+        } else if !previous.can_send() && level.can_send() {
+            tracing::info!(
+                "Balance of the {account} is {balance}{unit}. Resuming sending of transactions."
+            );
+        }
+        self.level.send_replace(level);
+        level
+    }
+
+    /// Record that the balance of the account could not be queried. The level
+    /// is kept as it is.
+    pub fn query_failed(&self, health: &Health, error: impl std::fmt::Display) {
+        health.set_check(
+            self.check,
+            false,
+            serde_json::json!({
+                "level": *self.level.borrow(),
+                "error": format!("Unable to query balance: {error:#}"),
+                "thresholds": self.thresholds,
+            }),
+        );
+    }
+}
+
+/// A gate that is open while transactions may be sent from an account, see
+/// [`BalanceMonitor`].
+#[derive(Clone)]
+pub struct SendGate {
+    level: tokio::sync::watch::Receiver<BalanceLevel>,
+}
+
+impl SendGate {
+    /// A gate that is always open.
+    pub fn open() -> Self {
+        let (_, level) = tokio::sync::watch::channel(BalanceLevel::Ok);
+        Self { level }
+    }
+
+    pub fn can_send(&self) -> bool {
+        self.level.borrow().can_send()
+    }
+
+    /// Wait until the gate is open. Return `true` if the service was asked
+    /// to stop in the meantime.
+    pub async fn wait(&mut self, stop: &mut tokio::sync::watch::Receiver<()>) -> bool {
+        while !self.can_send() {
+            tokio::select! {
+                _ = stop.changed() => return true,
+                r = self.level.changed() => {
+                    if r.is_err() {
+                        // The gate can no longer open.
+                        let _ = stop.changed().await;
+                        return true;
+                    }
+                }
+            }
+        }
+        false
+    }
+
+    /// Like [`wait`](Self::wait), but keep receiving items from the channel
+    /// into the buffer while waiting, so that the producer is not blocked.
+    pub async fn wait_buffering<T>(
+        &mut self,
+        receiver: &mut tokio::sync::mpsc::Receiver<T>,
+        buffer: &mut VecDeque<T>,
+        stop: &mut tokio::sync::watch::Receiver<()>,
+    ) -> bool {
+        let mut receiver_open = true;
+        while !self.can_send() {
+            tokio::select! {
+                _ = stop.changed() => return true,
+                r = self.level.changed() => {
+                    if r.is_err() {
+                        let _ = stop.changed().await;
+                        return true;
+                    }
+                }
+                x = receiver.recv(), if receiver_open => {
+                    match x {
+                        Some(x) => buffer.push_back(x),
+                        None => receiver_open = false,
+                    }
+                }
+            }
+        }
+        false
+    }
+}
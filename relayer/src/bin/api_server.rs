@@ -1,10 +1,11 @@
 use anyhow::Context;
 use axum::{http::StatusCode, Json};
 use axum_prometheus::PrometheusMetricLayerBuilder;
-use ccdeth_relayer::db::TransactionStatus;
+use ccdeth_relayer::db::{QuarantineDecision, TransactionStatus};
 use clap::Parser;
 use concordium::{
     cis2::TokenId,
+    id::types::AccountAddress,
     types::{hashes::TransactionHash, ContractAddress},
 };
 use concordium_rust_sdk as concordium;
@@ -83,6 +84,8 @@ struct Api {
         wallet_transactions,
         get_merkle_proof,
         expected_merkle_root_update,
+        admin_history,
+        quarantined_deposits,
     ),
     components(schemas(
         WatchTxResponse,
@@ -91,10 +94,14 @@ struct Api {
         WalletTx,
         TransactionStatus,
         EthMerkleProofResponse,
+        MerkleRootProof,
         WithdrawParams,
         WalletDepositTx,
         WalletWithdrawTx,
-        WithdrawalStatus
+        WithdrawalStatus,
+        AdminEventItem,
+        QuarantinedDepositItem,
+        QuarantineDecision
     ))
 )]
 struct ApiDoc;
@@ -119,7 +126,11 @@ async fn main() -> anyhow::Result<()> {
         .with_prefix("ccdeth_api_server")
         .build_pair();
 
-    let db = Database::new(app.db_config, app.max_pool_size).await?;
+    // Metrics that are not recorded by the layer above, rendered together with
+    // its metrics.
+    let registry = prometheus::Registry::new();
+
+    let db = Database::new(app.db_config, app.max_pool_size, &registry).await?;
 
     let openapi = ApiDoc::openapi();
 
@@ -150,6 +161,8 @@ async fn main() -> anyhow::Result<()> {
             "/api/v1/wallet/:wallet",
             axum::routing::get(wallet_transactions),
         )
+        .route("/api/v1/admin/history", axum::routing::get(admin_history))
+        .route("/api/v1/quarantine", axum::routing::get(quarantined_deposits))
         .route(
             "/openapi.json",
             axum::routing::get(|| async move { Json(openapi) }),
@@ -171,7 +184,14 @@ async fn main() -> anyhow::Result<()> {
         let prometheus_api = axum::Router::new()
             .route(
                 "/metrics",
-                axum::routing::get(|| async move { metric_handle.render() }),
+                axum::routing::get(|| async move {
+                    let mut metrics = metric_handle.render();
+                    let db_metrics = prometheus::TextEncoder::new()
+                        .encode_to_string(&registry.gather())
+                        .map_err(|_| "Unable to encode metrics.")?;
+                    metrics.push_str(&db_metrics);
+                    Ok::<_, axum::response::ErrorResponse>(metrics)
+                }),
             )
             .layer(tower_http::timeout::TimeoutLayer::new(
                 std::time::Duration::from_millis(1000),
@@ -302,6 +322,8 @@ struct WalletWithdrawTx {
     origin_tx_hash:     TransactionHash,
     origin_event_index: u64,
     amount:             String,
+    /// The fee taken in the token, which is not included in `amount`.
+    fee:                Option<String>,
     status:             WithdrawalStatus,
     timestamp:          i64,
 }
@@ -343,6 +365,7 @@ async fn wallet_transactions(
     axum::extract::Path(wallet): axum::extract::Path<ethers::types::Address>,
     axum::extract::State(db): axum::extract::State<Database>,
 ) -> Result<axum::Json<Vec<WalletTx>>, Error> {
+    let timer = db.start_query_timer("wallet");
     let client = db.pool.get().await?;
     let (statement, param) = &db.prepared_statements.get_withdrawals_for_address;
     let statement = client
@@ -354,6 +377,7 @@ async fn wallet_transactions(
         .prepare_typed_cached(statement, std::slice::from_ref(param))
         .await?;
     let deposits = client.query(&statement, &[&wallet.as_bytes()]).await?;
+    timer.observe_duration();
     let mut out = Vec::new();
     for withdraw in withdraws {
         let tx_hash = withdraw
@@ -362,6 +386,7 @@ async fn wallet_transactions(
         let origin_tx_hash = TransactionHash::new(withdraw.try_get::<_, Fixed<32>>("tx_hash")?.0);
         let origin_event_index = withdraw.try_get::<_, i64>("event_index")? as u64;
         let amount = withdraw.try_get::<_, String>("amount")?;
+        let fee = withdraw.try_get::<_, Option<String>>("fee")?;
         let index = withdraw.try_get::<_, i64>("child_index")? as u64;
         let subindex = withdraw.try_get::<_, i64>("child_subindex")? as u64;
         let timestamp = withdraw
@@ -372,6 +397,7 @@ async fn wallet_transactions(
             origin_tx_hash,
             origin_event_index,
             amount,
+            fee,
             timestamp,
             status: if tx_hash.is_some() {
                 WithdrawalStatus::Processed
@@ -423,12 +449,32 @@ pub struct WithdrawParams {
     token_id:        TokenId,
 }
 
+#[derive(serde::Serialize, utoipa::ToSchema)]
+/// A Merkle proof of the withdrawal against a specific Merkle root.
+struct MerkleRootProof {
+    // hex string
+    root:       String,
+    // hex string
+    proof:      String,
+    /// Whether the root is the current Merkle root, as opposed to the previous
+    /// one.
+    is_current: bool,
+}
+
 #[derive(serde::Serialize, utoipa::ToSchema)]
 /// Response to the Merkle proof request.
 struct EthMerkleProofResponse {
     params: WithdrawParams,
+    /// Proof against the current Merkle root if the withdrawal is part of it,
+    /// otherwise against the previous one.
+    // hex string
+    proof: String,
+    /// The Merkle root that `proof` is for.
     // hex string
-    proof:  String,
+    root: String,
+    /// Proofs against the current and the previous Merkle root, for those of
+    /// the two that contain the withdrawal. The current root comes first.
+    proofs: Vec<MerkleRootProof>,
 }
 
 #[utoipa::path(
@@ -455,6 +501,9 @@ async fn get_merkle_proof(
     axum::extract::Path((tx_hash, event_id)): axum::extract::Path<(TransactionHash, u64)>,
     axum::extract::State(db): axum::extract::State<Database>,
 ) -> Result<axum::Json<EthMerkleProofResponse>, Error> {
+    // The leaves of the Merkle trees are queried while constructing the proofs, so
+    // the timer is only stopped when returning.
+    let _timer = db.start_query_timer("proof");
     let client = db.pool.get().await?;
     let (statement, params) = &db.prepared_statements.get_event;
     let statement = client.prepare_typed_cached(statement, &params[..]).await?;
@@ -469,28 +518,45 @@ async fn get_merkle_proof(
         let data = row.try_get::<_, Vec<u8>>("event_data")?;
         let we: ccdeth_relayer::concordium_contracts::WithdrawEvent =
             concordium::smart_contracts::common::from_bytes(&data).map_err(|_| Error::Internal)?;
-        let statement = &db.prepared_statements.get_merkle_leafs;
+        let statement = &db.prepared_statements.get_latest_merkle_roots;
         let statement = client.prepare_typed_cached(statement, &[]).await?;
-        let rows = client.query(&statement, &[]).await?;
-        let rows = rows.into_iter().map(|row| {
-            let tx_hash = row.try_get::<_, Fixed<32>>("tx_hash")?;
-            let event_merkle_hash = row.try_get::<_, Fixed<32>>("event_merkle_hash")?.0;
-            let tx_hash = TransactionHash::new(tx_hash.0);
-            Ok::<_, Error>((tx_hash, event_merkle_hash))
-        });
-        let proof = ccdeth_relayer::merkle::make_proof(rows, tx_hash)?;
-        if let Some(proof) = proof {
+        let roots = client.query(&statement, &[]).await?;
+        let (statement, param) = &db.prepared_statements.get_merkle_leafs;
+        let statement = client
+            .prepare_typed_cached(statement, std::slice::from_ref(param))
+            .await?;
+        let mut proofs = Vec::with_capacity(roots.len());
+        for (i, root_row) in roots.into_iter().enumerate() {
+            let root = root_row.try_get::<_, Fixed<32>>("root")?.0;
+            let rows = client.query(&statement, &[&&root[..]]).await?;
+            let rows = rows.into_iter().map(|row| {
+                let tx_hash = row.try_get::<_, Fixed<32>>("tx_hash")?;
+                let event_merkle_hash = row.try_get::<_, Fixed<32>>("event_merkle_hash")?.0;
+                let tx_hash = TransactionHash::new(tx_hash.0);
+                Ok::<_, Error>((tx_hash, event_merkle_hash))
+            });
+            if let Some(proof) = ccdeth_relayer::merkle::make_proof(rows, tx_hash)? {
+                proofs.push(MerkleRootProof {
+                    root:       hex::encode(root),
+                    proof:      hex::encode(proof.to_bytes()),
+                    is_current: i == 0,
+                });
+            }
+        }
+        if let Some(first) = proofs.first() {
             Ok(EthMerkleProofResponse {
                 params: WithdrawParams {
                     ccd_index:       we.contract.index,
                     ccd_sub_index:   we.contract.subindex,
                     amount:          we.amount.to_string(),
-                    user_wallet:     we.eth_address.into(),
+                    user_wallet:     we.eth_address.eth_address.into(),
                     ccd_tx_hash:     tx_hash,
                     ccd_event_index: we.event_index,
                     token_id:        we.token_id,
                 },
-                proof:  hex::encode(proof.to_bytes()),
+                proof: first.proof.clone(),
+                root: first.root.clone(),
+                proofs,
             }
             .into())
         } else {
@@ -517,10 +583,12 @@ async fn get_merkle_proof(
 pub async fn expected_merkle_root_update(
     axum::extract::State(db): axum::extract::State<Database>,
 ) -> Result<axum::Json<Option<i64>>, Error> {
+    let timer = db.start_query_timer("expected_merkle_root_update");
     let client = db.pool.get().await?;
     let statement = &db.prepared_statements.get_next_merkle_root;
     let statement = client.prepare_typed_cached(statement, &[]).await?;
     let row = client.query_opt(&statement, &[]).await?;
+    timer.observe_duration();
     match row {
         None => Ok(None.into()),
         Some(v) => {
@@ -558,12 +626,14 @@ pub async fn watch_deposit(
             return Err(Error::InvalidRequest(e.to_string()));
         }
     };
+    let timer = db.start_query_timer("deposit");
     let client = db.pool.get().await?;
     let (statement, params) = &db.prepared_statements.concordium_tx_status;
     let statement = client
         .prepare_typed_cached(statement, std::slice::from_ref(params))
         .await?;
     let row = client.query(&statement, &[&path.0.as_ref()]).await?;
+    timer.observe_duration();
     // TODO: This is how it is now, but it would be better to
     // not assume there can only be one deposit for one transaction.
     // This is enough for the frontend as it is now though.
@@ -624,12 +694,14 @@ async fn watch_withdraw(
             return Err(Error::InvalidRequest(e.to_string()));
         }
     };
+    let timer = db.start_query_timer("withdraw");
     let client = db.pool.get().await?;
     let (statement, params) = &db.prepared_statements.withdrawal_status;
     let statement = client
         .prepare_typed_cached(statement, std::slice::from_ref(params))
         .await?;
     let row = client.query(&statement, &[&path.0.as_ref()]).await?;
+    timer.observe_duration();
     // TODO: This is how it is now, but it would be better to
     // not assume there can only be one deposit for one transaction.
     if let Some((first, rest)) = row.split_first() {
@@ -669,7 +741,7 @@ pub struct TokenMapItem {
 
 #[derive(utoipa::ToSchema)]
 #[schema(as = u64)]
-struct Index(u64);
+struct Index(#[allow(dead_code)] u64);
 
 fn contract_address() -> utoipa::openapi::Object {
     ObjectBuilder::new()
@@ -694,10 +766,12 @@ fn contract_address() -> utoipa::openapi::Object {
 async fn list_tokens(
     axum::extract::State(db): axum::extract::State<Database>,
 ) -> Result<axum::Json<Vec<TokenMapItem>>, Error> {
+    let timer = db.start_query_timer("tokens");
     let client = db.pool.get().await?;
     let statement = &db.prepared_statements.list_tokens;
     let statement = client.prepare_typed_cached(statement, &[]).await?;
     let rows = client.query(&statement, &[]).await?;
+    timer.observe_duration();
     let mut out = Vec::with_capacity(rows.len());
     for row in rows {
         let root = row.try_get::<_, Vec<u8>>("root")?;
@@ -717,14 +791,235 @@ async fn list_tokens(
     Ok(out.into())
 }
 
+/// Maximum number of events returned by the /admin/history endpoint.
+const MAX_ADMIN_HISTORY_LIMIT: u64 = 1000;
+
+#[derive(Debug, serde::Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+/// Query parameters of the /admin/history endpoint.
+struct AdminHistoryQuery {
+    /// Only return events with a larger id. Used to get the next page.
+    from:     Option<u64>,
+    /// Maximum number of events to return, at most 1000. Defaults to 100.
+    limit:    Option<u64>,
+    /// Only return events of the contract with this index.
+    index:    Option<u64>,
+    /// Subindex of the contract, defaults to 0. Only used with `index`.
+    subindex: Option<u64>,
+}
+
+#[derive(serde::Serialize, utoipa::ToSchema)]
+/// An administrative action returned from the /admin/history endpoint.
+struct AdminEventItem {
+    /// Id of the event, increasing in the order the events were recorded.
+    id:         u64,
+    #[schema(schema_with = hex_string)]
+    tx_hash:    TransactionHash,
+    #[schema(schema_with = contract_address)]
+    contract:   ContractAddress,
+    /// Name of the action, e.g., `set_withdraw_fee`.
+    action:     String,
+    /// The event, exactly as logged by the contract, in hex. It can be
+    /// decoded with the schema of the contract.
+    #[schema(schema_with = hex_string)]
+    event:      String,
+    /// Unix timestamp (in seconds) of the block that contains the event.
+    block_time: i64,
+}
+
+/// List the administrative actions on the bridge manager and the mapped
+/// tokens, e.g., role changes, proposals, fee changes, pausing and upgrades,
+/// ordered by id.
+#[utoipa::path(
+        get,
+        path = "api/v1/admin/history",
+        operation_id = "admin_history",
+        params(AdminHistoryQuery),
+        responses(
+            (status = 200, description = "History of administrative actions.", body = [AdminEventItem]),
+            (status = 400, description = "Invalid request.", body = inline(String), content_type = "application/json"),
+            (status = 500, description = "Internal server error.", body = inline(String), content_type = "application/json")
+        )
+    )]
+#[tracing::instrument(level = "debug", skip(db))]
+async fn admin_history(
+    query: Result<
+        axum::extract::Query<AdminHistoryQuery>,
+        axum::extract::rejection::QueryRejection,
+    >,
+    axum::extract::State(db): axum::extract::State<Database>,
+) -> Result<axum::Json<Vec<AdminEventItem>>, Error> {
+    let query = match query {
+        Ok(q) => q.0,
+        Err(e) => {
+            return Err(Error::InvalidRequest(e.to_string()));
+        }
+    };
+    let limit = query.limit.unwrap_or(100);
+    if limit > MAX_ADMIN_HISTORY_LIMIT {
+        return Err(Error::InvalidRequest(format!(
+            "The limit must be at most {MAX_ADMIN_HISTORY_LIMIT}."
+        )));
+    }
+    let limit = limit as i64;
+    let from = query.from.map_or(0, |f| f as i64);
+    let timer = db.start_query_timer("admin_history");
+    let client = db.pool.get().await?;
+    let rows = if let Some(index) = query.index {
+        let (statement, params) = &db.prepared_statements.get_admin_events_of_contract;
+        let statement = client.prepare_typed_cached(statement, params).await?;
+        client
+            .query(&statement, &[
+                &from,
+                &limit,
+                &(index as i64),
+                &(query.subindex.unwrap_or(0) as i64),
+            ])
+            .await?
+    } else {
+        let (statement, params) = &db.prepared_statements.get_admin_events;
+        let statement = client.prepare_typed_cached(statement, params).await?;
+        client.query(&statement, &[&from, &limit]).await?
+    };
+    timer.observe_duration();
+    let mut out = Vec::with_capacity(rows.len());
+    for row in rows {
+        let id = row.try_get::<_, i64>("id")?;
+        let tx_hash = row.try_get::<_, Fixed<32>>("tx_hash")?;
+        let index = row.try_get::<_, i64>("contract_index")?;
+        let subindex = row.try_get::<_, i64>("contract_subindex")?;
+        let action = row.try_get::<_, String>("action")?;
+        let event = row.try_get::<_, Vec<u8>>("event_data")?;
+        let block_time = row.try_get::<_, chrono::DateTime<chrono::Utc>>("block_time")?;
+        out.push(AdminEventItem {
+            id: id as u64,
+            tx_hash: TransactionHash::new(tx_hash.0),
+            contract: ContractAddress::new(index as u64, subindex as u64),
+            action,
+            event: hex::encode(event),
+            block_time: block_time.timestamp(),
+        })
+    }
+    Ok(out.into())
+}
+
+/// Maximum number of deposits returned by the /quarantine endpoint.
+const MAX_QUARANTINE_LIMIT: u64 = 1000;
+
+#[derive(Debug, serde::Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+/// Query parameters of the /quarantine endpoint.
+struct QuarantineQuery {
+    /// Only return deposits with a larger id. Used to get the next page.
+    from:  Option<u64>,
+    /// Maximum number of deposits to return, at most 1000. Defaults to 100.
+    limit: Option<u64>,
+}
+
+#[derive(serde::Serialize, utoipa::ToSchema)]
+/// A deposit held in quarantine, returned from the /quarantine endpoint.
+struct QuarantinedDepositItem {
+    /// Id of the deposit, increasing in the order the deposits were recorded.
+    id:                 u64,
+    #[schema(schema_with = hex_string)]
+    origin_tx_hash:     TransactionHash,
+    origin_event_index: u64,
+    #[schema(schema_with = hex_string)]
+    depositor:          ethers::types::Address,
+    /// The Concordium account receiving the deposit, if it was recorded.
+    receiver:           Option<String>,
+    #[schema(schema_with = hex_string)]
+    root_token:         ethers::types::Address,
+    amount:             String,
+    /// Unix timestamp (in seconds) of when the relayer recorded the deposit.
+    timestamp:          i64,
+    /// The decision on the deposit, or `null` while it is under review.
+    decision:           Option<QuarantineDecision>,
+    /// Unix timestamp (in seconds) of when the relayer carried out the
+    /// decision, if it has.
+    resolved:           Option<i64>,
+}
+
+/// List the deposits that are held in quarantine, since the depositor, the
+/// receiver or the contract is on the relayer's blocklist, ordered by id.
+#[utoipa::path(
+        get,
+        path = "api/v1/quarantine",
+        operation_id = "quarantined_deposits",
+        params(QuarantineQuery),
+        responses(
+            (status = 200, description = "Deposits held in quarantine.", body = [QuarantinedDepositItem]),
+            (status = 400, description = "Invalid request.", body = inline(String), content_type = "application/json"),
+            (status = 500, description = "Internal server error.", body = inline(String), content_type = "application/json")
+        )
+    )]
+#[tracing::instrument(level = "debug", skip(db))]
+async fn quarantined_deposits(
+    query: Result<axum::extract::Query<QuarantineQuery>, axum::extract::rejection::QueryRejection>,
+    axum::extract::State(db): axum::extract::State<Database>,
+) -> Result<axum::Json<Vec<QuarantinedDepositItem>>, Error> {
+    let query = match query {
+        Ok(q) => q.0,
+        Err(e) => {
+            return Err(Error::InvalidRequest(e.to_string()));
+        }
+    };
+    let limit = query.limit.unwrap_or(100);
+    if limit > MAX_QUARANTINE_LIMIT {
+        return Err(Error::InvalidRequest(format!(
+            "The limit must be at most {MAX_QUARANTINE_LIMIT}."
+        )));
+    }
+    let from = query.from.map_or(0, |f| f as i64);
+    let timer = db.start_query_timer("quarantine");
+    let client = db.pool.get().await?;
+    let (statement, params) = &db.prepared_statements.get_quarantined_deposits;
+    let statement = client.prepare_typed_cached(statement, params).await?;
+    let rows = client.query(&statement, &[&from, &(limit as i64)]).await?;
+    timer.observe_duration();
+    let mut out = Vec::with_capacity(rows.len());
+    for row in rows {
+        let id = row.try_get::<_, i64>("id")?;
+        let origin_tx_hash = row.try_get::<_, Fixed<32>>("origin_tx_hash")?;
+        let origin_event_index = row.try_get::<_, i64>("origin_event_index")?;
+        let depositor = row.try_get::<_, Fixed<20>>("depositor")?;
+        let receiver = row.try_get::<_, Option<Fixed<32>>>("receiver")?;
+        let root_token = row.try_get::<_, Fixed<20>>("root_token")?;
+        let amount = row.try_get::<_, String>("amount")?;
+        let timestamp = row.try_get::<_, chrono::DateTime<chrono::Utc>>("insert_time")?;
+        let decision = row.try_get::<_, Option<QuarantineDecision>>("quarantine_decision")?;
+        let resolved =
+            row.try_get::<_, Option<chrono::DateTime<chrono::Utc>>>("quarantine_resolved_time")?;
+        out.push(QuarantinedDepositItem {
+            id: id as u64,
+            origin_tx_hash: TransactionHash::new(origin_tx_hash.0),
+            origin_event_index: origin_event_index as u64,
+            depositor: depositor.0.into(),
+            receiver: receiver.map(|r| AccountAddress(r.0).to_string()),
+            root_token: root_token.0.into(),
+            amount,
+            timestamp: timestamp.timestamp(),
+            decision,
+            resolved: resolved.map(|r| r.timestamp()),
+        })
+    }
+    Ok(out.into())
+}
+
 #[derive(Clone)]
 pub struct Database {
     pool:                deadpool_postgres::Pool,
     prepared_statements: Arc<QueryStatements>,
+    /// Time spent acquiring a connection and running the queries of a route.
+    query_duration:      prometheus::HistogramVec,
 }
 
 impl Database {
-    pub async fn new(config: tokio_postgres::Config, pool_size: usize) -> anyhow::Result<Self> {
+    pub async fn new(
+        config: tokio_postgres::Config,
+        pool_size: usize,
+        registry: &prometheus::Registry,
+    ) -> anyhow::Result<Self> {
         let manager_config = deadpool_postgres::ManagerConfig {
             recycling_method: deadpool_postgres::RecyclingMethod::Verified,
         };
@@ -736,22 +1031,41 @@ impl Database {
             .max_size(pool_size)
             .runtime(deadpool_postgres::Runtime::Tokio1)
             .build()?;
+        let query_duration = prometheus::HistogramVec::new(
+            prometheus::HistogramOpts::new(
+                "ccdeth_api_server_db_query_duration_seconds",
+                "Time in seconds spent querying the database, by route.",
+            ),
+            &["route"],
+        )?;
+        registry.register(Box::new(query_duration.clone()))?;
         Ok(Self {
             pool,
             prepared_statements: Arc::new(QueryStatements::new()),
+            query_duration,
         })
     }
+
+    /// Start timing the database queries of the given route. The duration is
+    /// recorded when the timer is stopped or dropped.
+    fn start_query_timer(&self, route: &str) -> prometheus::HistogramTimer {
+        self.query_duration.with_label_values(&[route]).start_timer()
+    }
 }
 
 struct QueryStatements {
-    concordium_tx_status:        (String, tokio_postgres::types::Type),
-    withdrawal_status:           (String, tokio_postgres::types::Type),
-    get_event:                   (String, [tokio_postgres::types::Type; 2]),
-    get_merkle_leafs:            String,
-    get_withdrawals_for_address: (String, tokio_postgres::types::Type),
-    get_deposits_for_address:    (String, tokio_postgres::types::Type),
-    list_tokens:                 String,
-    get_next_merkle_root:        String,
+    concordium_tx_status:         (String, tokio_postgres::types::Type),
+    withdrawal_status:            (String, tokio_postgres::types::Type),
+    get_event:                    (String, [tokio_postgres::types::Type; 2]),
+    get_latest_merkle_roots:      String,
+    get_merkle_leafs:             (String, tokio_postgres::types::Type),
+    get_withdrawals_for_address:  (String, tokio_postgres::types::Type),
+    get_deposits_for_address:     (String, tokio_postgres::types::Type),
+    list_tokens:                  String,
+    get_next_merkle_root:         String,
+    get_admin_events:             (String, [tokio_postgres::types::Type; 2]),
+    get_admin_events_of_contract: (String, [tokio_postgres::types::Type; 4]),
+    get_quarantined_deposits:     (String, [tokio_postgres::types::Type; 2]),
 }
 
 impl QueryStatements {
@@ -773,12 +1087,16 @@ impl QueryStatements {
                 tokio_postgres::types::Type::INT8,
             ],
         );
-        let get_merkle_leafs = "SELECT tx_hash, event_merkle_hash FROM concordium_events WHERE \
-                                root IN (SELECT root FROM merkle_roots ORDER BY id DESC LIMIT 1) \
-                                ORDER BY event_index ASC"
-            .into();
+        let get_latest_merkle_roots =
+            "SELECT root FROM merkle_roots ORDER BY id DESC LIMIT 2".into();
+        let get_merkle_leafs = (
+            "SELECT tx_hash, event_merkle_hash FROM concordium_events WHERE root = $1 OR \
+             previous_root = $1 ORDER BY event_index ASC"
+                .into(),
+            tokio_postgres::types::Type::BYTEA,
+        );
         let get_withdrawals_for_address = (
-            "SELECT insert_time, processed, tx_hash, child_index, child_subindex, amount, \
+            "SELECT insert_time, processed, tx_hash, child_index, child_subindex, amount, fee, \
              event_index FROM concordium_events WHERE event_type = 'withdraw' AND receiver = $1"
                 .into(),
             tokio_postgres::types::Type::BYTEA,
@@ -794,15 +1112,50 @@ impl QueryStatements {
             .into();
         let get_next_merkle_root =
             "SELECT expected_time FROM expected_merkle_update WHERE tag = ''".into();
+        let get_admin_events = (
+            "SELECT id, tx_hash, contract_index, contract_subindex, action, event_data, \
+             block_time FROM admin_events WHERE id > $1 ORDER BY id ASC LIMIT $2"
+                .into(),
+            [
+                tokio_postgres::types::Type::INT8,
+                tokio_postgres::types::Type::INT8,
+            ],
+        );
+        let get_admin_events_of_contract = (
+            "SELECT id, tx_hash, contract_index, contract_subindex, action, event_data, \
+             block_time FROM admin_events WHERE id > $1 AND contract_index = $3 AND \
+             contract_subindex = $4 ORDER BY id ASC LIMIT $2"
+                .into(),
+            [
+                tokio_postgres::types::Type::INT8,
+                tokio_postgres::types::Type::INT8,
+                tokio_postgres::types::Type::INT8,
+                tokio_postgres::types::Type::INT8,
+            ],
+        );
+        let get_quarantined_deposits = (
+            "SELECT id, origin_tx_hash, origin_event_index, depositor, receiver, root_token, \
+             amount, insert_time, quarantine_decision, quarantine_resolved_time FROM \
+             ethereum_deposit_events WHERE quarantined AND id > $1 ORDER BY id ASC LIMIT $2"
+                .into(),
+            [
+                tokio_postgres::types::Type::INT8,
+                tokio_postgres::types::Type::INT8,
+            ],
+        );
         Self {
             concordium_tx_status,
             withdrawal_status,
             get_event,
+            get_latest_merkle_roots,
             get_merkle_leafs,
             get_withdrawals_for_address,
             get_deposits_for_address,
             list_tokens,
             get_next_merkle_root,
+            get_admin_events,
+            get_admin_events_of_contract,
+            get_quarantined_deposits,
         }
     }
 }
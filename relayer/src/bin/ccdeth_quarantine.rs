@@ -0,0 +1,113 @@
+//! Review the deposits that the relayer holds in quarantine. The tool lists the
+//! quarantined deposits, and records the decision to release or reject one of
+//! them. The relayer carries out the decision before its next batch of
+//! Ethereum events: a released deposit is sent to Concordium as it was made,
+//! and the operation of a rejected deposit is skipped in the bridge manager,
+//! so that the tokens stay locked on Ethereum.
+use anyhow::Context;
+use ccdeth_relayer::db::{self, QuarantineDecision};
+use clap::Parser;
+use concordium_rust_sdk::id::types::AccountAddress;
+
+#[derive(Parser, Debug)]
+#[clap(author, version, about)]
+struct Quarantine {
+    #[clap(
+        long = "db",
+        default_value = "host=localhost dbname=relayer user=postgres password=password port=5432",
+        help = "Database connection string of the relayer.",
+        env = "ETHCCD_QUARANTINE_DB_STRING"
+    )]
+    db:      tokio_postgres::Config,
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(clap::Subcommand, Debug)]
+enum Command {
+    /// List the quarantined deposits, with the decisions on them.
+    List,
+    /// Release a quarantined deposit, so that it is sent to Concordium.
+    Release {
+        #[clap(help = "Id of the deposit operation of the StateSender.")]
+        id: u64,
+    },
+    /// Reject a quarantined deposit, so that it is never sent to Concordium.
+    Reject {
+        #[clap(help = "Id of the deposit operation of the StateSender.")]
+        id: u64,
+    },
+}
+
+async fn list(client: &tokio_postgres::Client) -> anyhow::Result<()> {
+    let rows = client
+        .query(
+            "SELECT origin_event_index, origin_tx_hash, depositor, receiver, amount, \
+             quarantine_decision, quarantine_resolved_time FROM ethereum_deposit_events WHERE \
+             quarantined ORDER BY id ASC;",
+            &[],
+        )
+        .await?;
+    for row in &rows {
+        let id: i64 = row.try_get("origin_event_index")?;
+        let origin_tx_hash: Vec<u8> = row.try_get("origin_tx_hash")?;
+        let depositor: Vec<u8> = row.try_get("depositor")?;
+        let receiver = match row.try_get::<_, Option<Vec<u8>>>("receiver")? {
+            Some(receiver) => AccountAddress(
+                receiver
+                    .try_into()
+                    .map_err(|_| anyhow::anyhow!("Invalid receiver of deposit {id}."))?,
+            )
+            .to_string(),
+            None => "an unknown receiver".into(),
+        };
+        let amount: String = row.try_get("amount")?;
+        let decision: Option<QuarantineDecision> = row.try_get("quarantine_decision")?;
+        let resolved: Option<chrono::DateTime<chrono::Utc>> =
+            row.try_get("quarantine_resolved_time")?;
+        let status = match (decision, resolved) {
+            (None, _) => "under review".to_string(),
+            (Some(decision), None) => format!("{decision:?}, not yet carried out"),
+            (Some(decision), Some(resolved)) => format!("{decision:?}, carried out at {resolved}"),
+        };
+        println!(
+            "Deposit {id} of {amount} from 0x{} to {receiver} in 0x{}: {status}.",
+            hex::encode(depositor),
+            hex::encode(origin_tx_hash)
+        );
+    }
+    println!("{} quarantined deposits.", rows.len());
+    Ok(())
+}
+
+async fn decide(
+    client: &tokio_postgres::Client,
+    id: u64,
+    decision: QuarantineDecision,
+) -> anyhow::Result<()> {
+    anyhow::ensure!(
+        db::decide_quarantined_deposit(client, id, decision).await?,
+        "Deposit {id} is not held in quarantine, a decision on it was already carried out, or it \
+         was quarantined before its operation was recorded and cannot be released."
+    );
+    println!(
+        "Recorded the decision {decision:?} on deposit {id}. The relayer carries it out before \
+         its next batch of Ethereum events."
+    );
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let app: Quarantine = Quarantine::parse();
+
+    let (client, _handle) = db::connect(&app.db)
+        .await
+        .context("Unable to connect to the database.")?;
+
+    match app.command {
+        Command::List => list(&client).await,
+        Command::Release { id } => decide(&client, id, QuarantineDecision::Release).await,
+        Command::Reject { id } => decide(&client, id, QuarantineDecision::Reject).await,
+    }
+}
@@ -1,15 +1,20 @@
 use anyhow::Context;
 use ccdeth_relayer::{
-    concordium_contracts::{self, BridgeManagerClient},
-    db::{self, Database},
-    ethereum,
-    merkle::{self, MerkleSetterClient},
-    state_sender::StateSender,
+    balance::{BalanceMonitor, Thresholds},
+    blocklist::{Blocklist, BlocklistSource},
+    concordium_node::ConcordiumNode,
+    config::{self, Tunables, TunablesReceiver},
+    db::Database,
+    failover::{ConcordiumEndpoints, EthereumEndpoints},
+    guardian,
+    health::{self, Health},
+    schedule::{MerkleSchedule, TokenPrice},
+    tasks::{self, BalanceMonitors, Signers, TaskConfig, Tasks},
+    telemetry,
 };
-use clap::Parser;
+use clap::{CommandFactory, Parser};
 use concordium::{
     id::types::AccountAddress,
-    smart_contracts::common::Amount,
     types::{AbsoluteBlockHeight, ContractAddress, WalletAccount},
     v2::{self, BlockIdentifier},
 };
@@ -19,8 +24,9 @@ use ethers::prelude::{
     Http, HttpRateLimitRetryPolicy, LocalWallet, Middleware, Provider, RetryClient, Signer,
 };
 use futures::StreamExt;
-use std::{path::PathBuf, sync::Arc};
+use std::{ffi::OsString, path::PathBuf, sync::Arc};
 use tonic::transport::ClientTlsConfig;
+use tracing::Instrument;
 
 #[derive(Parser, Debug)]
 struct EthereumConfig {
@@ -43,13 +49,33 @@ struct EthereumConfig {
         env = "ETHCCD_RELAYER_STATE_SENDER_CREATION_BLOCK_NUMBER"
     )]
     state_sender_creation_block_number: u64,
+    #[clap(
+        long = "skip-operations-from-height",
+        help = "Block number from which the operations of the state sender that are not state \
+                updates are skipped in the bridge manager, if it is before the block at which \
+                monitoring resumes. This is needed once after upgrading a bridge manager that did \
+                not skip operations, and can be the creation height of the state sender.",
+        env = "ETHCCD_RELAYER_SKIP_OPERATIONS_FROM_HEIGHT"
+    )]
+    skip_operations_from: Option<u64>,
     #[clap(
         long = "ethereum-api",
         name = "ethereum-api",
-        help = "JSON-RPC interface of an Ethereum node. Only HTTPS is supported as transport.",
-        env = "ETHCCD_RELAYER_ETHEREUM_API"
+        help = "JSON-RPC interface of an Ethereum node. Only HTTPS is supported as transport. \
+                Can be given multiple times, or as a comma separated list, in which case requests \
+                fail over between the nodes.",
+        env = "ETHCCD_RELAYER_ETHEREUM_API",
+        required = true,
+        value_delimiter = ','
+    )]
+    apis: Vec<url::Url>,
+    #[clap(
+        long = "ethereum-quorum",
+        help = "Number of Ethereum nodes that must return the same events before they are \
+                accepted. If not set the events returned by a single node are accepted.",
+        env = "ETHCCD_RELAYER_ETHEREUM_QUORUM"
     )]
-    api: url::Url,
+    quorum: Option<usize>,
     // Maximum gas price.
     #[clap(
         long,
@@ -74,6 +100,51 @@ struct EthereumConfig {
         default_value = "600"
     )]
     merkle_update_interval: u64,
+    #[clap(
+        long,
+        help = "Minimum time (in seconds) between two Merkle root updates when they are sent \
+                early. If not set updates are never sent early.",
+        env = "ETHCCD_RELAYER_MERKLE_MIN_UPDATE_INTERVAL"
+    )]
+    merkle_min_update_interval: Option<u64>,
+    #[clap(
+        long,
+        help = "Send a Merkle root update once the oldest withdrawal waiting for approval has \
+                waited this long (in seconds).",
+        env = "ETHCCD_RELAYER_MERKLE_MAX_WAIT"
+    )]
+    merkle_max_wait: Option<u64>,
+    #[clap(
+        long,
+        help = "Send a Merkle root update once this many withdrawals are waiting for approval.",
+        env = "ETHCCD_RELAYER_MERKLE_BACKLOG_SIZE"
+    )]
+    merkle_backlog_size: Option<u64>,
+    #[clap(
+        long,
+        help = "Send a Merkle root update once the total value of the withdrawals waiting for \
+                approval is at least this. The value is computed using the --token-price \
+                options.",
+        env = "ETHCCD_RELAYER_MERKLE_BACKLOG_VALUE",
+        value_parser = U256::from_dec_str
+    )]
+    merkle_backlog_value: Option<U256>,
+    #[clap(
+        long,
+        help = "Defer regular Merkle root updates while the gas price is above this, until the \
+                backlog size, backlog value or maximum wait is reached.",
+        env = "ETHCCD_RELAYER_MERKLE_DEFER_GAS_PRICE",
+        value_parser = U256::from_dec_str
+    )]
+    merkle_defer_gas_price: Option<U256>,
+    #[clap(
+        long = "token-price",
+        help = "Value of one whole token in the form SYMBOL=PRICE, used for the value of \
+                withdrawals. Can be given multiple times, or as a comma separated list.",
+        env = "ETHCCD_RELAYER_TOKEN_PRICES",
+        value_delimiter = ','
+    )]
+    token_prices: Vec<TokenPrice>,
     /// Chain ID for the Ethereum network.
     #[clap(
         long,
@@ -116,44 +187,154 @@ struct EthereumConfig {
     #[clap(
         long = "eth-min-balance",
         name = "eth-min-balance",
-        help = "Minimum balance of the Ethereum account. In microEther",
+        help = "Minimum balance of the Ethereum accounts. In microEther. Below this no \
+                transactions are sent from the account until it is topped up.",
         env = "ETHCCD_RELAYER_MIN_ETHEREUM_BALANCE"
     )]
-    min_balance: u128,
+    min_balance: u64,
+    #[clap(
+        long = "eth-warn-balance",
+        name = "eth-warn-balance",
+        help = "Balance of the Ethereum accounts below which warnings are emitted. In microEther. \
+                Defaults to twice the minimum balance.",
+        env = "ETHCCD_RELAYER_WARN_ETHEREUM_BALANCE"
+    )]
+    warn_balance: Option<u64>,
+    #[clap(
+        long = "eth-critical-balance",
+        name = "eth-critical-balance",
+        help = "Balance of the Ethereum accounts below which errors are emitted. In microEther. \
+                Defaults to half the minimum balance.",
+        env = "ETHCCD_RELAYER_CRITICAL_ETHEREUM_BALANCE"
+    )]
+    critical_balance: Option<u64>,
+    #[clap(long = "claim-max-gas",
+           help = "Maximum gas allowed for claiming a withdrawal on Ethereum on behalf of a user.",
+           env = "ETHCCD_RELAYER_CLAIM_MAX_GAS",
+           value_parser = U256::from_dec_str,
+           default_value = "300000")]
+    claim_max_gas: U256,
+    #[clap(
+        long = "claim-interval",
+        help = "How often (in seconds) to check for approved withdrawals that should be claimed \
+                on behalf of users.",
+        env = "ETHCCD_RELAYER_CLAIM_INTERVAL",
+        default_value = "60"
+    )]
+    claim_interval: u64,
 }
 
 impl EthereumConfig {
     fn log(&self) {
-        // Do not log the API since that can be sensitive.
         let EthereumConfig {
             state_sender,
             root_chain_manager,
             state_sender_creation_block_number,
-            api: _,
+            skip_operations_from,
+            apis,
+            quorum,
             max_gas_price,
             max_gas,
             merkle_update_interval,
+            merkle_min_update_interval,
+            merkle_max_wait,
+            merkle_backlog_size,
+            merkle_backlog_value,
+            merkle_defer_gas_price,
+            token_prices,
             chain_id,
             num_confirmations,
             ethereum_request_timeout,
             escalation_interval,
             warn_duration,
             min_balance,
+            warn_balance: _,
+            critical_balance: _,
+            claim_max_gas,
+            claim_interval,
         } = self;
-        log::info!("Using {state_sender:#x} as the state sender address.");
-        log::info!("Using {root_chain_manager:#x} as the root chain manager address.");
-        log::info!(
+        // Do not log the APIs since they can be sensitive.
+        tracing::info!("Using {} Ethereum nodes.", apis.len());
+        if let Some(quorum) = quorum {
+            tracing::info!("Requiring {quorum} Ethereum nodes to agree on events.");
+        }
+        tracing::info!("Using {state_sender:#x} as the state sender address.");
+        tracing::info!("Using {root_chain_manager:#x} as the root chain manager address.");
+        tracing::info!(
             "Using {state_sender_creation_block_number} as the starting height on Ethereum."
         );
-        log::info!("Using {max_gas_price} as the maximum gas price.");
-        log::info!("Using {max_gas} as the maximum allowed gas for transactions.");
-        log::info!("Using {merkle_update_interval}s as the update interval for Merkle roots.");
-        log::info!("Using {chain_id} as the chain id.");
-        log::info!("Requiring {num_confirmations} confirmations for transactions on Ethereum.");
-        log::info!("Using {ethereum_request_timeout}s as the request timeout for Ethereum API.");
-        log::info!("Will escalate price every {escalation_interval}s.");
-        log::info!("Will warn after transaction is not confirmed after {warn_duration}s.");
-        log::info!("Requiring {min_balance} microETH on the sender account balance.");
+        if let Some(height) = skip_operations_from {
+            tracing::info!("Skipping the operations of the state sender from height {height}.");
+        }
+        tracing::info!("Using {max_gas_price} as the maximum gas price.");
+        tracing::info!("Using {max_gas} as the maximum allowed gas for transactions.");
+        tracing::info!("Using {merkle_update_interval}s as the update interval for Merkle roots.");
+        if let Some(interval) = merkle_min_update_interval {
+            tracing::info!("Sending Merkle roots early at most every {interval}s.");
+        }
+        if let Some(max_wait) = merkle_max_wait {
+            tracing::info!("Approving withdrawals that waited for {max_wait}s.");
+        }
+        if let Some(size) = merkle_backlog_size {
+            tracing::info!("Approving withdrawals once {size} are waiting.");
+        }
+        if let Some(value) = merkle_backlog_value {
+            tracing::info!("Approving withdrawals once their value is at least {value}.");
+        }
+        if let Some(price) = merkle_defer_gas_price {
+            tracing::info!("Deferring Merkle root updates while the gas price is above {price}.");
+        }
+        for TokenPrice { symbol, price } in token_prices {
+            tracing::info!("Using {price} as the value of one {symbol}.");
+        }
+        tracing::info!("Using {chain_id} as the chain id.");
+        tracing::info!("Requiring {num_confirmations} confirmations for transactions on Ethereum.");
+        tracing::info!(
+            "Using {ethereum_request_timeout}s as the request timeout for Ethereum API."
+        );
+        tracing::info!("Will escalate price every {escalation_interval}s.");
+        tracing::info!("Will warn after transaction is not confirmed after {warn_duration}s.");
+        tracing::info!("Requiring {min_balance} microETH on the sender account balance.");
+        if let Ok(Thresholds { warn, critical, .. }) = self.thresholds() {
+            tracing::info!(
+                "Warning if the balance of an Ethereum account is below {warn} microETH."
+            );
+            tracing::info!(
+                "Reporting errors if the balance of an Ethereum account is below {critical} \
+                 microETH."
+            );
+        }
+        tracing::info!("Using {claim_max_gas} as the maximum allowed gas for claim transactions.");
+        tracing::info!("Using {claim_interval}s as the interval for checking for claims.");
+    }
+
+    /// The schedule of Merkle root updates.
+    fn merkle_schedule(&self) -> MerkleSchedule {
+        MerkleSchedule {
+            min_interval: self
+                .merkle_min_update_interval
+                .map(std::time::Duration::from_secs),
+            max_wait: self.merkle_max_wait.map(std::time::Duration::from_secs),
+            backlog_size: self.merkle_backlog_size,
+            backlog_value: self.merkle_backlog_value,
+            defer_gas_price: self.merkle_defer_gas_price,
+            token_prices: self
+                .token_prices
+                .iter()
+                .map(|TokenPrice { symbol, price }| (symbol.clone(), *price))
+                .collect(),
+        }
+    }
+
+    /// Balance thresholds of the Ethereum accounts, in microEther.
+    fn thresholds(&self) -> anyhow::Result<Thresholds> {
+        Thresholds::new(
+            self.warn_balance
+                .unwrap_or_else(|| self.min_balance.saturating_mul(2)),
+            self.min_balance,
+            self.critical_balance.unwrap_or(self.min_balance / 2),
+        )
+        .context("Invalid balance thresholds for Ethereum accounts.")
     }
 }
 
@@ -162,11 +343,13 @@ struct ConcordiumConfig {
     #[clap(
         long = "concordium-api",
         name = "concordium-api",
-        help = "GRPC V2 interface of the Concordium node.",
+        help = "GRPC V2 interface of the Concordium node. Can be given multiple times, or as a \
+                comma separated list, in which case requests fail over between the nodes.",
         env = "ETHCCD_RELAYER_CONCORDIUM_API",
-        default_value = "http://localhost:20000"
+        default_value = "http://localhost:20000",
+        value_delimiter = ','
     )]
-    api: v2::Endpoint,
+    apis: Vec<v2::Endpoint>,
     #[clap(
         long = "concordium-max-parallel",
         help = "Maximum number of parallel queries of the Concordium node. This is only useful in \
@@ -209,47 +392,328 @@ struct ConcordiumConfig {
     #[clap(
         long = "ccd-min-balance",
         name = "ccd-min-balance",
-        help = "Minimum balance of the Concordium account. In microCCD",
+        help = "Minimum balance of the Concordium account. In microCCD. Below this no \
+                transactions are sent to Concordium until the account is topped up.",
         env = "ETHCCD_RELAYER_MIN_CONCORDIUM_BALANCE"
     )]
     min_balance: u64,
+    #[clap(
+        long = "ccd-warn-balance",
+        name = "ccd-warn-balance",
+        help = "Balance of the Concordium account below which warnings are emitted. In microCCD. \
+                Defaults to twice the minimum balance.",
+        env = "ETHCCD_RELAYER_WARN_CONCORDIUM_BALANCE"
+    )]
+    warn_balance: Option<u64>,
+    #[clap(
+        long = "ccd-critical-balance",
+        name = "ccd-critical-balance",
+        help = "Balance of the Concordium account below which errors are emitted. In microCCD. \
+                Defaults to half the minimum balance.",
+        env = "ETHCCD_RELAYER_CRITICAL_CONCORDIUM_BALANCE"
+    )]
+    critical_balance: Option<u64>,
 }
 
 impl ConcordiumConfig {
     fn log(&self) {
         let ConcordiumConfig {
-            api,
+            apis,
             max_parallel,
             max_behind,
             request_timeout,
             bridge_manager,
             max_energy,
             min_balance,
+            warn_balance: _,
+            critical_balance: _,
+        } = self;
+        for api in apis {
+            tracing::info!("Using Concordium node at {}", api.uri());
+        }
+        tracing::info!("Allowing up to {max_parallel} parallel queries of the Concordium node.");
+        tracing::info!("Allowing the Concordium node to be at most {max_behind}s behind present.");
+        tracing::info!("Using {request_timeout}s as the request timeout for Concordium.");
+        tracing::info!("Using {bridge_manager} as bridge manager.");
+        tracing::info!("Allowing up to {max_energy}NRG for Concordium tranasactions.");
+        tracing::info!("Requiring  {min_balance} microCCD on the Concordium sender account.");
+        if let Ok(Thresholds { warn, critical, .. }) = self.thresholds() {
+            tracing::info!("Warning if the Concordium account balance is below {warn} microCCD.");
+            tracing::info!(
+                "Reporting errors if the Concordium account balance is below {critical} microCCD."
+            );
+        }
+    }
+
+    /// Balance thresholds of the Concordium sender account, in microCCD.
+    fn thresholds(&self) -> anyhow::Result<Thresholds> {
+        Thresholds::new(
+            self.warn_balance
+                .unwrap_or_else(|| self.min_balance.saturating_mul(2)),
+            self.min_balance,
+            self.critical_balance.unwrap_or(self.min_balance / 2),
+        )
+        .context("Invalid balance thresholds for the Concordium account.")
+    }
+}
+
+#[derive(Parser, Debug)]
+struct HealthConfig {
+    #[clap(
+        long = "health-server",
+        help = "Listen address:port for the server of the /health and /ready endpoints.",
+        env = "ETHCCD_RELAYER_HEALTH_SERVER"
+    )]
+    server: Option<std::net::SocketAddr>,
+    #[clap(
+        long = "health-check-interval",
+        name = "health-check-interval",
+        help = "How often (in seconds) to check the watchers, the database, and pending \
+                transactions for readiness.",
+        env = "ETHCCD_RELAYER_HEALTH_CHECK_INTERVAL",
+        default_value = "30"
+    )]
+    check_interval: u64,
+    #[clap(
+        long = "max-ethereum-lag",
+        help = "Maximum number of blocks, in addition to the number of confirmations, that the \
+                Ethereum watcher may be behind the chain for the relayer to be ready.",
+        env = "ETHCCD_RELAYER_MAX_ETHEREUM_LAG",
+        default_value = "20"
+    )]
+    max_ethereum_lag: u64,
+    #[clap(
+        long = "max-concordium-lag",
+        help = "Maximum number of blocks that the Concordium watcher may be behind the last \
+                finalized block for the relayer to be ready.",
+        env = "ETHCCD_RELAYER_MAX_CONCORDIUM_LAG",
+        default_value = "40"
+    )]
+    max_concordium_lag: u64,
+    #[clap(
+        long = "max-pending-transaction-age",
+        help = "Maximum age (in seconds) of pending Concordium and Ethereum transactions for the \
+                relayer to be ready.",
+        env = "ETHCCD_RELAYER_MAX_PENDING_TRANSACTION_AGE",
+        default_value = "1800"
+    )]
+    max_pending_age: u64,
+}
+
+impl HealthConfig {
+    fn log(&self) {
+        let HealthConfig {
+            server,
+            check_interval,
+            max_ethereum_lag,
+            max_concordium_lag,
+            max_pending_age,
         } = self;
-        log::info!("Using Concordium node at {}", api.uri());
-        log::info!("Allowing up to {max_parallel} parallel queries of the Concordium node.");
-        log::info!("Allowing the Concordium node to be at most {max_behind}s behind present.");
-        log::info!("Using {request_timeout}s as the request timeout for Concordium.");
-        log::info!("Using {bridge_manager} as bridge manager.");
-        log::info!("Allowing up to {max_energy}NRG for Concordium tranasactions.");
-        log::info!("Requiring  {min_balance} microCCD on the Concordium sender account.");
+        if let Some(server) = server {
+            tracing::info!("Serving health and readiness at {server}.");
+        }
+        tracing::info!("Checking readiness every {check_interval}s.");
+        tracing::info!(
+            "Ready if the Ethereum watcher is at most {max_ethereum_lag} blocks behind, in \
+             addition to confirmations."
+        );
+        tracing::info!(
+            "Ready if the Concordium watcher is at most {max_concordium_lag} blocks behind."
+        );
+        tracing::info!("Ready if pending transactions are at most {max_pending_age}s old.");
+    }
+}
+
+#[derive(Parser, Debug)]
+struct GuardianConfig {
+    #[clap(
+        long = "guardian-wallet-file",
+        name = "guardian-wallet-file",
+        help = "File with the Concordium wallet of the guardian in the browser extension wallet \
+                export format. The account must have the Pauser role on the bridge manager and \
+                on all mapped tokens. If neither this nor the secret name is provided then the \
+                guardian is not started.",
+        env = "ETHCCD_RELAYER_GUARDIAN_WALLET_FILE",
+        conflicts_with = "guardian-wallet-secret-name"
+    )]
+    wallet: Option<PathBuf>,
+    #[clap(
+        long = "guardian-wallet-secret-name",
+        name = "guardian-wallet-secret-name",
+        help = "Secret name of the Concordium wallet of the guardian stored in Amazon secret \
+                manager.",
+        env = "ETHCCD_RELAYER_GUARDIAN_WALLET_SECRET_NAME",
+        conflicts_with = "guardian-wallet-file"
+    )]
+    wallet_secret_name: Option<String>,
+    #[clap(
+        long = "guardian-deposit-multiple",
+        help = "Pause the bridge if a deposit is larger than this multiple of the daily average \
+                of the deposits of the same token. If not set deposits are not checked.",
+        env = "ETHCCD_RELAYER_GUARDIAN_DEPOSIT_MULTIPLE"
+    )]
+    deposit_multiple: Option<u64>,
+    #[clap(
+        long = "guardian-average-window",
+        help = "Window (in seconds) over which the daily average of deposits is computed.",
+        env = "ETHCCD_RELAYER_GUARDIAN_AVERAGE_WINDOW",
+        default_value = "604800"
+    )]
+    average_window: u64,
+    #[clap(
+        long = "guardian-reorg-window",
+        help = "Deposits made in this window (in seconds) are checked to still be on Ethereum.",
+        env = "ETHCCD_RELAYER_GUARDIAN_REORG_WINDOW",
+        default_value = "3600"
+    )]
+    reorg_window: u64,
+    #[clap(
+        long = "guardian-check-interval",
+        name = "guardian-check-interval",
+        help = "How often (in seconds) the guardian evaluates its rules.",
+        env = "ETHCCD_RELAYER_GUARDIAN_CHECK_INTERVAL",
+        default_value = "60"
+    )]
+    check_interval: u64,
+}
+
+impl GuardianConfig {
+    fn log(&self) {
+        let GuardianConfig {
+            wallet,
+            wallet_secret_name,
+            deposit_multiple,
+            average_window,
+            reorg_window,
+            check_interval,
+        } = self;
+        if wallet.is_none() && wallet_secret_name.is_none() {
+            tracing::info!("No guardian key provided. The guardian is not started.");
+            return;
+        }
+        tracing::info!("Evaluating the guardian's rules every {check_interval}s.");
+        if let Some(multiple) = deposit_multiple {
+            tracing::info!(
+                "Pausing on deposits larger than {multiple} times the daily average over \
+                 {average_window}s."
+            );
+        }
+        tracing::info!("Checking that deposits of the last {reorg_window}s are on Ethereum.");
+    }
+
+    /// Load the guardian's keys, if any.
+    async fn load_wallet(&self) -> anyhow::Result<Option<WalletAccount>> {
+        match (self.wallet.as_ref(), self.wallet_secret_name.as_ref()) {
+            (Some(_), Some(_)) => anyhow::bail!(
+                "Both file and secret name provided as the key location for the guardian. Choose \
+                 one."
+            ),
+            (Some(w), None) => Ok(Some(
+                WalletAccount::from_json_file(w)
+                    .context("Unable to read the guardian's wallet from the provided file.")?,
+            )),
+            (None, Some(sn)) => Ok(Some(
+                ccdeth_relayer::aws_secret_manager::get_concordium_keys_aws(sn)
+                    .await
+                    .context("Unable to get the guardian's wallet from Amazon secret manager.")?,
+            )),
+            (None, None) => Ok(None),
+        }
+    }
+
+    fn rules(&self) -> guardian::Rules {
+        guardian::Rules {
+            deposit_multiple: self.deposit_multiple,
+            average_window: std::time::Duration::from_secs(self.average_window),
+            reorg_window: std::time::Duration::from_secs(self.reorg_window),
+        }
+    }
+}
+
+#[derive(Parser, Debug)]
+struct BlocklistConfig {
+    #[clap(
+        long = "blocklist-file",
+        name = "blocklist-file",
+        help = "File with one blocked address per line, either a 0x prefixed Ethereum \
+                address, a Concordium account address, or a Concordium contract address as \
+                <index,subindex>. Deposits from a blocked depositor, or to a blocked receiver or \
+                contract, are held in quarantine instead of being sent to Concordium. The file \
+                is read at startup.",
+        env = "ETHCCD_RELAYER_BLOCKLIST_FILE",
+        conflicts_with = "blocklist-table"
+    )]
+    file: Option<PathBuf>,
+    #[clap(
+        long = "blocklist-table",
+        name = "blocklist-table",
+        help = "Read the blocked addresses from the `blocklist` table of the database before \
+                each batch of Ethereum events, instead of from a file.",
+        env = "ETHCCD_RELAYER_BLOCKLIST_TABLE",
+        conflicts_with = "blocklist-file"
+    )]
+    table: bool,
+}
+
+impl BlocklistConfig {
+    /// Load the blocklist, if it is read from a file, and log where it is read
+    /// from.
+    fn source(&self) -> anyhow::Result<BlocklistSource> {
+        if let Some(file) = &self.file {
+            let blocklist = Blocklist::from_file(file)?;
+            tracing::info!(
+                "Quarantining deposits of the {} addresses blocked in {}.",
+                blocklist.len(),
+                file.display()
+            );
+            Ok(BlocklistSource::File(blocklist))
+        } else if self.table {
+            tracing::info!("Quarantining deposits of the addresses in the blocklist table.");
+            Ok(BlocklistSource::Table)
+        } else {
+            tracing::info!("No blocklist provided. Deposits are not quarantined.");
+            Ok(BlocklistSource::None)
+        }
     }
 }
 
 #[derive(Parser, Debug)]
 #[clap(author, version, about)]
 struct Relayer {
+    #[clap(
+        long = "config-file",
+        name = "config-file",
+        help = "TOML file with values of any of the other options, keyed by the option name \
+                without the leading `--`. Options given on the command line or in the \
+                environment take precedence. The gas limits, intervals, balance thresholds and \
+                log level are reloaded from the file on SIGHUP or via the admin server.",
+        env = "ETHCCD_RELAYER_CONFIG_FILE"
+    )]
+    config_file: Option<PathBuf>,
     #[clap(
         long = "log-level",
         default_value = "info",
         help = "Maximum log level.",
         env = "ETHCCD_RELAYER_LOG_LEVEL"
     )]
-    log_level: log::LevelFilter,
+    log_level: tracing_subscriber::filter::LevelFilter,
+    #[clap(
+        long = "otlp-endpoint",
+        help = "URL of an OpenTelemetry collector to export traces to, using OTLP over gRPC. If \
+                not set traces are not exported.",
+        env = "ETHCCD_RELAYER_OTLP_ENDPOINT"
+    )]
+    otlp_endpoint: Option<url::Url>,
     #[clap(flatten)]
     ethereum_config: EthereumConfig,
     #[clap(flatten)]
     concordium_config: ConcordiumConfig,
+    #[clap(flatten)]
+    health_config: HealthConfig,
+    #[clap(flatten)]
+    guardian_config: GuardianConfig,
+    #[clap(flatten)]
+    blocklist_config: BlocklistConfig,
     #[clap(
         long = "concordium-wallet-file",
         name = "concordium-wallet-file",
@@ -282,6 +746,23 @@ struct Relayer {
         conflicts_with = "eth-private-key"
     )]
     eth_private_key_secret_name: Option<String>,
+    #[clap(
+        long = "claim-private-key",
+        name = "claim-private-key",
+        help = "Private key used to sign transactions that claim withdrawals on Ethereum on \
+                behalf of users. This must be different from the key used for Merkle updates. \
+                If neither this nor the secret name is provided then withdrawals are not claimed.",
+        env = "ETHCCD_RELAYER_CLAIM_PRIVATE_KEY"
+    )]
+    claim_private_key: Option<LocalWallet>,
+    #[clap(
+        long = "claim-key-secret-name",
+        name = "claim-key-secret-name",
+        help = "Secret name of the claim key stored in Amazon secret manager.",
+        env = "ETHCCD_RELAYER_CLAIM_PRIVATE_KEY_SECRET_NAME",
+        conflicts_with = "claim-private-key"
+    )]
+    claim_private_key_secret_name: Option<String>,
     #[clap(
         long = "db",
         default_value = "host=localhost dbname=relayer user=postgres password=password port=5432",
@@ -289,111 +770,365 @@ struct Relayer {
         env = "ETHCCD_RELAYER_DB_STRING"
     )]
     db_config: tokio_postgres::Config,
+    #[clap(
+        long = "endpoint-probe-interval",
+        help = "How often (in seconds) to query the heights of all Concordium and Ethereum nodes, \
+                which are used to choose between them.",
+        env = "ETHCCD_RELAYER_ENDPOINT_PROBE_INTERVAL",
+        default_value = "30"
+    )]
+    endpoint_probe_interval: u64,
     #[clap(
         long = "prometheus-server",
         help = "Listen address:port for the Prometheus server.",
         env = "ETHCCD_RELAYER_PROMETHEUS_SERVER"
     )]
     prometheus_server: Option<std::net::SocketAddr>,
+    #[clap(
+        long = "admin-server",
+        help = "Listen address:port for the admin server, which reloads the configuration file \
+                on request.",
+        env = "ETHCCD_RELAYER_ADMIN_SERVER",
+        requires = "admin-token"
+    )]
+    admin_server: Option<std::net::SocketAddr>,
+    #[clap(
+        long = "admin-token",
+        name = "admin-token",
+        help = "Bearer token that requests to the admin server must be authenticated with. At \
+                least 32 characters long.",
+        env = "ETHCCD_RELAYER_ADMIN_TOKEN"
+    )]
+    admin_token: Option<String>,
+    #[clap(
+        long = "shadow",
+        help = "Run in shadow mode. Both chains are watched and the transactions and Merkle roots \
+                that would be sent are recorded in the database, but nothing is signed or sent, \
+                and no keys are needed. The database must not be the one of the production \
+                relayer.",
+        env = "ETHCCD_RELAYER_SHADOW"
+    )]
+    shadow: bool,
+    #[clap(
+        long = "concordium-sender-address",
+        help = "Address of the Concordium account that the production relayer sends \
+                transactions from. Required in shadow mode, where no Concordium wallet is given.",
+        env = "ETHCCD_RELAYER_CONCORDIUM_SENDER_ADDRESS"
+    )]
+    concordium_sender_address: Option<AccountAddress>,
 }
 
-async fn find_start_ethereum_config<M: Middleware>(
-    client: M,
-    last_processed: Option<u64>,
-    creation_height: u64,
-    num_confirmations: u64,
-) -> anyhow::Result<(u64, u64)>
+impl Relayer {
+    /// The settings that are reloaded from the configuration file while the
+    /// relayer is running.
+    fn tunables(&self) -> anyhow::Result<Tunables> {
+        let tunables = Tunables {
+            max_gas_price: self.ethereum_config.max_gas_price,
+            max_gas: self.ethereum_config.max_gas,
+            claim_max_gas: self.ethereum_config.claim_max_gas,
+            merkle_update_interval: std::time::Duration::from_secs(
+                self.ethereum_config.merkle_update_interval,
+            ),
+            merkle_schedule: self.ethereum_config.merkle_schedule(),
+            escalation_interval: std::time::Duration::from_secs(
+                self.ethereum_config.escalation_interval,
+            ),
+            claim_interval: std::time::Duration::from_secs(self.ethereum_config.claim_interval),
+            max_energy: self.concordium_config.max_energy,
+            ethereum_thresholds: self.ethereum_config.thresholds()?,
+            concordium_thresholds: self.concordium_config.thresholds()?,
+            log_level: self.log_level,
+        };
+        tunables.validate()?;
+        Ok(tunables)
+    }
+}
+
+/// Parse the options from the command line `args`, the environment, and the
+/// configuration file if one is given, in that order of precedence.
+fn load_config(args: &[OsString]) -> anyhow::Result<Relayer> {
+    // Find out which options are given before the file is read. Missing
+    // options may be in the file.
+    let matches = Relayer::command()
+        .ignore_errors(true)
+        .try_get_matches_from(args)?;
+    let mut args = args.to_vec();
+    if let Some(path) = matches.get_one::<PathBuf>("config-file") {
+        let file = config::read_file(path).with_context(|| {
+            format!("Unable to read the configuration file {}.", path.display())
+        })?;
+        args.extend(
+            config::file_arguments(&Relayer::command(), &matches, &file)?
+                .into_iter()
+                .map(OsString::from),
+        );
+    }
+    Ok(Relayer::try_parse_from(args)?)
+}
+
+/// Reload the configuration file on SIGHUP, or when requested via the admin
+/// server, and send the new tunables to the tasks. If the file cannot be
+/// loaded, or is invalid, the current tunables are kept. Changes to other
+/// options than the tunables only take effect after a restart.
+async fn reload_config(
+    metrics: ccdeth_relayer::metrics::Metrics,
+    args: Vec<OsString>,
+    tunables: tokio::sync::watch::Sender<Tunables>,
+    log_level: telemetry::LogLevel,
+    mut requests: tokio::sync::mpsc::Receiver<config::ReloadRequest>,
+) -> anyhow::Result<()> {
+    #[cfg(unix)]
+    let mut hangup_stream = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup())?;
+    loop {
+        #[cfg(unix)]
+        let hangup = hangup_stream.recv();
+        #[cfg(not(unix))]
+        let hangup = futures::future::pending::<Option<()>>();
+        let response = tokio::select! {
+            _ = hangup => {
+                tracing::info!("Received SIGHUP. Reloading the configuration file.");
+                None
+            }
+            Some(response) = requests.recv() => {
+                tracing::info!(
+                    "Reloading the configuration file as requested by the admin server."
+                );
+                Some(response)
+            }
+        };
+        let result = load_config(&args).and_then(|app| {
+            anyhow::ensure!(
+                app.config_file.is_some(),
+                "No configuration file is given, so there is nothing to reload."
+            );
+            app.tunables()
+        });
+        match &result {
+            Ok(new) => {
+                let previous = tunables.borrow().log_level;
+                if new.log_level != previous {
+                    if let Err(e) = log_level.set(new.log_level) {
+                        metrics.warnings_total.inc();
+                        tracing::warn!("Unable to change the log level: {e:#}");
+                    }
+                }
+                if tunables.send_if_modified(|current| {
+                    let changed = current != new;
+                    *current = new.clone();
+                    changed
+                }) {
+                    tracing::info!("Using the reloaded settings {new:?}.");
+                } else {
+                    tracing::info!("The reloaded settings are unchanged.");
+                }
+            }
+            Err(e) => {
+                metrics.warnings_total.inc();
+                tracing::warn!(
+                    "Unable to reload the configuration. Keeping the current settings: {e:#}"
+                );
+            }
+        }
+        if let Some(response) = response {
+            // The admin server may have given up on the request.
+            let _ = response.send(result);
+        }
+    }
+}
+
+/// Load the keys from the locations given in the configuration, and look up
+/// the nonces of the Ethereum accounts.
+async fn load_signers<M: Middleware>(app: &Relayer, ethereum_client: M) -> anyhow::Result<Signers>
 where
     M::Error: 'static,
 {
-    let last_finalized: u64 = client
-        .get_block_number()
-        .await?
-        .as_u64()
-        .saturating_sub(num_confirmations);
-    if let Some(last_processed) = last_processed {
-        Ok((
-            last_processed + 1,
-            std::cmp::max(last_processed + 1, last_finalized),
-        ))
+    let concordium_wallet = match (
+        app.concordium_wallet.as_ref(),
+        app.concordium_wallet_secret_name.as_ref(),
+    ) {
+        (Some(_), Some(_)) => {
+            anyhow::bail!(
+                "Both file and secret name provided as the key location for Concordium. Choose \
+                 one."
+            )
+        }
+        (Some(w), None) => WalletAccount::from_json_file(w)
+            .context("Unable to read Concordium wallet from the provided file.")?,
+        (None, Some(sn)) => ccdeth_relayer::aws_secret_manager::get_concordium_keys_aws(sn)
+            .await
+            .context("Unable to get Concordium wallet from Amazon secret manager.")?,
+        (None, None) => {
+            anyhow::bail!("Concordium keys were not provided.")
+        }
+    };
+
+    // Transactions will be signed with the private key below and will be broadcast
+    // via the eth_sendRawTransaction API)
+    let wallet: LocalWallet = match (
+        app.eth_private_key.clone(),
+        app.eth_private_key_secret_name.as_ref(),
+    ) {
+        (Some(_), Some(_)) => {
+            anyhow::bail!(
+                "Both file and secret name provided as the key location for Ethereum. Choose one."
+            )
+        }
+        (Some(w), None) => w.with_chain_id(app.ethereum_config.chain_id),
+        (None, Some(sn)) => ccdeth_relayer::aws_secret_manager::get_ethereum_keys_aws(sn)
+            .await
+            .context("Unable to get Ethereum wallet from Amazon secret manager.")?
+            .with_chain_id(app.ethereum_config.chain_id),
+        (None, None) => {
+            anyhow::bail!("Ethereum keys were not provided.")
+        }
+    };
+
+    let ethereum_sender = wallet.address();
+    tracing::info!("Using {ethereum_sender:#x} as the Ethereum wallet.");
+
+    let balance = ethereum_client
+        .get_balance(ethereum_sender, None)
+        .await
+        .context("Unable to get initial balance of the Ethereum sender")?;
+    tracing::info!(
+        "Balance of the Ethereum sender account is {} microETH.",
+        balance / 1_000_000_000_000u64
+    );
+    let ethereum_nonce = ethereum_client
+        .get_transaction_count(ethereum_sender, None)
+        .await
+        .context("Unable to get nonce for the Ethereum account")?;
+    tracing::info!("Nonce of the Ethereum sender account is {ethereum_nonce}.");
+
+    let claim_wallet: Option<LocalWallet> = match (
+        app.claim_private_key.clone(),
+        app.claim_private_key_secret_name.as_ref(),
+    ) {
+        (Some(_), Some(_)) => {
+            anyhow::bail!(
+                "Both key and secret name provided as the claim key location. Choose one."
+            )
+        }
+        (Some(w), None) => Some(w.with_chain_id(app.ethereum_config.chain_id)),
+        (None, Some(sn)) => Some(
+            ccdeth_relayer::aws_secret_manager::get_ethereum_keys_aws(sn)
+                .await
+                .context("Unable to get claim wallet from Amazon secret manager.")?
+                .with_chain_id(app.ethereum_config.chain_id),
+        ),
+        (None, None) => None,
+    };
+    let claim_nonce = if let Some(claim_wallet) = &claim_wallet {
+        let claim_sender = claim_wallet.address();
+        anyhow::ensure!(
+            claim_sender != ethereum_sender,
+            "The claim key must be different from the key used for Merkle updates."
+        );
+        tracing::info!("Using {claim_sender:#x} as the wallet for claiming withdrawals.");
+        let claim_nonce = ethereum_client
+            .get_transaction_count(claim_sender, None)
+            .await
+            .context("Unable to get nonce for the claim account")?;
+        tracing::info!("Nonce of the claim account is {claim_nonce}.");
+        claim_nonce
     } else {
-        Ok((
-            creation_height,
-            std::cmp::max(last_finalized, creation_height),
-        ))
-    }
+        tracing::info!(
+            "No claim key provided. Withdrawals will not be claimed on behalf of users."
+        );
+        U256::zero()
+    };
+    Ok(Signers {
+        concordium_wallet,
+        wallet,
+        ethereum_nonce,
+        claim_wallet,
+        claim_nonce,
+    })
 }
 
 async fn find_concordium_start_height(
-    mut client: v2::Client,
+    client: ConcordiumEndpoints<v2::Client>,
     last_processed: Option<AbsoluteBlockHeight>,
     manager_address: ContractAddress,
 ) -> anyhow::Result<AbsoluteBlockHeight> {
     if let Some(h) = last_processed {
         Ok(h.next())
     } else {
-        let (height, _, _) = client.find_instance_creation(.., manager_address).await?;
+        let (height, _, _) = client
+            .request(|mut client| async move {
+                client.find_instance_creation(.., manager_address).await
+            })
+            .await?;
         Ok(height)
     }
 }
 
+/// Periodically query the balance of the Concordium sender account and record
+/// it in the `monitor`.
 async fn query_concordium_balance(
     metrics: ccdeth_relayer::metrics::Metrics,
-    mut client: v2::Client,
+    health: Health,
+    mut monitor: BalanceMonitor,
+    client: ConcordiumEndpoints<v2::Client>,
     address: AccountAddress,
-    min_balance: Amount,
+    mut tunables: TunablesReceiver,
 ) -> anyhow::Result<()> {
     let mut interval = tokio::time::interval(std::time::Duration::from_secs(30));
     interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
     loop {
         interval.tick().await;
+        if tunables.has_changed().unwrap_or(false) {
+            monitor.set_thresholds(tunables.borrow_and_update().concordium_thresholds);
+        }
         match client
-            .get_account_info(&address.into(), BlockIdentifier::LastFinal)
+            .request(|mut client| async move {
+                client
+                    .get_account_info(&address.into(), BlockIdentifier::LastFinal)
+                    .await
+            })
             .await
         {
             Ok(ai) => {
-                let balance = ai.response.account_amount;
-                metrics.concordium_balance.set(balance.micro_ccd);
-                if ai.response.account_amount < min_balance {
-                    anyhow::bail!(
-                        "Concordium account balance {balance} is below minimum required \
-                         {min_balance}."
-                    );
-                }
+                monitor.update(&metrics, &health, ai.response.account_amount.micro_ccd);
             }
             Err(e) => {
                 metrics.warnings_total.inc();
-                log::warn!("Unable to query Concordium account balance: {e:#}")
+                tracing::warn!("Unable to query Concordium account balance: {e:#}");
+                monitor.query_failed(&health, e);
             }
         }
     }
 }
 
+/// Periodically query the balance of an Ethereum account and record it, in
+/// microEther, in the `monitor`.
 async fn query_ethereum_balance<M: Middleware>(
     metrics: ccdeth_relayer::metrics::Metrics,
+    health: Health,
+    mut monitor: BalanceMonitor,
     client: M,
     address: ethers::prelude::Address,
-    min_balance: U256,
+    mut tunables: TunablesReceiver,
 ) -> anyhow::Result<()> {
     let mut interval = tokio::time::interval(std::time::Duration::from_secs(30));
     interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
     loop {
         interval.tick().await;
+        if tunables.has_changed().unwrap_or(false) {
+            monitor.set_thresholds(tunables.borrow_and_update().ethereum_thresholds);
+        }
         match client.get_balance(address, None).await {
             Ok(balance) => {
-                metrics
-                    .ethereum_balance
-                    .set((balance / 1_000_000_000_000u64).low_u64());
-                if balance < min_balance {
-                    anyhow::bail!(
-                        "Ethereum account balance {balance} is below minimum required \
-                         {min_balance}."
-                    );
-                }
+                monitor.update(
+                    &metrics,
+                    &health,
+                    (balance / 1_000_000_000_000u64).low_u64(),
+                );
             }
             Err(e) => {
                 metrics.warnings_total.inc();
-                log::warn!("Unable to query Ethereum account balance: {e:#}")
+                tracing::warn!("Unable to query Ethereum account balance {address:#x}: {e:#}");
+                monitor.query_failed(&health, e);
             }
         }
     }
@@ -403,16 +1138,23 @@ async fn query_ethereum_balance<M: Middleware>(
 /// once it terminates it sends a message on the provided channel.
 /// This is sent regardless of how the future terminates, as long as it
 /// terminates normally (i.e., does not panic).
+///
+/// The state of the task is tracked under the given name in `health`, and its
+/// events are logged in a span with the name.
 fn spawn_cancel<T>(
     died_sender: tokio::sync::broadcast::Sender<()>,
+    health: &Health,
+    name: &'static str,
     future: T,
-) -> tokio::task::JoinHandle<T::Output>
+) -> tokio::task::JoinHandle<anyhow::Result<()>>
 where
-    T: futures::Future + Send + 'static,
-    T::Output: Send + 'static,
+    T: futures::Future<Output = anyhow::Result<()>> + Send + 'static,
 {
+    let task = health.task(name);
+    let span = tracing::info_span!("task", name);
     tokio::spawn(async move {
-        let res = future.await;
+        let res = future.instrument(span).await;
+        task.finish(&res);
         // We ignore errors here since this always happens at the end of a task.
         // Since we keep one receiver alive until the end of the `main` function
         // the error should not happen anyhow.
@@ -423,167 +1165,213 @@ where
 
 #[tokio::main(worker_threads = 4)]
 async fn main() -> anyhow::Result<()> {
-    let app: Relayer = Relayer::parse();
+    let args: Vec<OsString> = std::env::args_os().collect();
+    let app = match load_config(&args) {
+        Ok(app) => app,
+        Err(e) => match e.downcast_ref::<clap::Error>() {
+            Some(e) => e.exit(),
+            None => return Err(e),
+        },
+    };
+    let tunables = app.tunables().context("Invalid configuration.")?;
 
-    let mut log_builder = env_logger::Builder::from_env("ETHCCD_RELAYER_LOG");
-    // only log the current module (main).
-    log_builder.filter_module(module_path!(), app.log_level);
-    log_builder.init();
+    let log_level = telemetry::init(app.log_level, app.otlp_endpoint.as_ref())
+        .context("Unable to set up logging.")?;
 
-    log::info!("Using {} as the maximum log level.", app.log_level);
+    if let Some(config_file) = &app.config_file {
+        tracing::info!("Using configuration file {}.", config_file.display());
+    }
+    tracing::info!("Using {} as the maximum log level.", app.log_level);
+    if let Some(endpoint) = &app.otlp_endpoint {
+        tracing::info!("Exporting traces to {endpoint}.");
+    }
     app.ethereum_config.log();
     app.concordium_config.log();
-
-    let concordium_wallet = match (
-        app.concordium_wallet.as_ref(),
-        app.concordium_wallet_secret_name.as_ref(),
-    ) {
-        (Some(_), Some(_)) => {
-            anyhow::bail!(
-                "Both file and secret name provided as the key location for Concordium. Choose \
-                 one."
-            )
-        }
-        (Some(w), None) => WalletAccount::from_json_file(w)
-            .context("Unable to read Concordium wallet from the provided file.")?,
-        (None, Some(sn)) => ccdeth_relayer::aws_secret_manager::get_concordium_keys_aws(sn)
-            .await
-            .context("Unable to get Concordium wallet from Amazon secret manager.")?,
-        (None, None) => {
-            anyhow::bail!("Concordium keys were not provided.")
-        }
-    };
-    let concordium_sender_address = concordium_wallet.address;
-    log::info!(
-        "Using {} as the sender of Concordium transactions.",
-        concordium_sender_address
+    app.health_config.log();
+    app.guardian_config.log();
+    let blocklist = app.blocklist_config.source()?;
+    anyhow::ensure!(
+        app.endpoint_probe_interval > 0,
+        "The endpoint-probe-interval must be positive."
     );
+    if let Some(admin_token) = &app.admin_token {
+        config::validate_admin_token(admin_token)?;
+    }
 
-    let inner_ethereum_client = {
-        let network_client = reqwest::ClientBuilder::new()
-            .timeout(std::time::Duration::from_secs(
-                app.ethereum_config.ethereum_request_timeout,
-            ))
-            .connect_timeout(std::time::Duration::from_secs(10))
-            .https_only(true)
-            .build()
-            .context("Unable to construct network client to access Ethereum API.")?;
-        Http::new_with_client(app.ethereum_config.api, network_client)
-    };
-    let ethereum_client = RetryClient::new(
-        inner_ethereum_client,
-        Box::<HttpRateLimitRetryPolicy>::default(),
-        5,
-        3000,
-    );
+    let (registry, metrics) = ccdeth_relayer::metrics::Metrics::new()
+        .context("Unable to construct new metrics object.")?;
 
-    let provider = Provider::new(ethereum_client);
+    let network_client = reqwest::ClientBuilder::new()
+        .timeout(std::time::Duration::from_secs(
+            app.ethereum_config.ethereum_request_timeout,
+        ))
+        .connect_timeout(std::time::Duration::from_secs(10))
+        .https_only(true)
+        .build()
+        .context("Unable to construct network client to access Ethereum API.")?;
+    // Endpoints are named by their position since the URLs can be sensitive.
+    let ethereum_apis = app
+        .ethereum_config
+        .apis
+        .iter()
+        .enumerate()
+        .map(|(i, api)| {
+            let client = RetryClient::new(
+                Http::new_with_client(api.clone(), network_client.clone()),
+                Box::<HttpRateLimitRetryPolicy>::default(),
+                5,
+                3000,
+            );
+            (i.to_string(), client)
+        })
+        .collect();
+    let ethereum_endpoints =
+        EthereumEndpoints::new(&metrics, ethereum_apis, app.ethereum_config.quorum)
+            .context("Invalid Ethereum endpoints.")?;
+
+    let provider = Provider::new(ethereum_endpoints.clone());
     let ethereum_client = Arc::new(provider);
 
-    // Transactions will be signed with the private key below and will be broadcast
-    // via the eth_sendRawTransaction API)
-    let wallet: LocalWallet = match (
-        app.eth_private_key,
-        app.eth_private_key_secret_name.as_ref(),
-    ) {
-        (Some(_), Some(_)) => {
-            anyhow::bail!(
-                "Both file and secret name provided as the key location for Ethereum. Choose one."
-            )
-        }
-        (Some(w), None) => w.with_chain_id(app.ethereum_config.chain_id),
-        (None, Some(sn)) => ccdeth_relayer::aws_secret_manager::get_ethereum_keys_aws(sn)
-            .await
-            .context("Unable to get Ethereum wallet from Amazon secret manager.")?
-            .with_chain_id(app.ethereum_config.chain_id),
-        (None, None) => {
-            anyhow::bail!("Ethereum keys were not provided.")
-        }
+    let signers = if app.shadow {
+        tracing::info!("Running in shadow mode. No transactions are signed or sent.");
+        None
+    } else {
+        Some(load_signers(&app, ethereum_client.clone()).await?)
     };
-
-    let ethereum_sender = wallet.address();
-    log::info!("Using {ethereum_sender:#x} as the Ethereum wallet.");
-
-    let balance = ethereum_client
-        .get_balance(ethereum_sender, None)
-        .await
-        .context("Unable to get initial balance of the Ethereum sender")?;
-    log::info!(
-        "Balance of the Ethereum sender account is {} microETH.",
-        balance / 1_000_000_000_000u64
+    let concordium_sender_address = match &signers {
+        Some(signers) => signers.concordium_wallet.address,
+        None => app
+            .concordium_sender_address
+            .context("The Concordium sender address is required in shadow mode.")?,
+    };
+    tracing::info!(
+        "Using {} as the sender of Concordium transactions.",
+        concordium_sender_address
     );
-    let ethereum_nonce = ethereum_client
-        .get_transaction_count(ethereum_sender, None)
-        .await
-        .context("Unable to get nonce for the Ethereum account")?;
-    log::info!("Nonce of the Ethereum sender account is {ethereum_nonce}.");
+    // The guardian pauses the bridge, so it is not run in shadow mode.
+    let guardian_wallet = if app.shadow {
+        None
+    } else {
+        app.guardian_config.load_wallet().await?
+    };
+    if let Some(guardian_wallet) = &guardian_wallet {
+        anyhow::ensure!(
+            guardian_wallet.address != concordium_sender_address,
+            "The guardian must use a different account than the sender of Concordium \
+             transactions."
+        );
+        tracing::info!(
+            "Using {} as the guardian's account.",
+            guardian_wallet.address
+        );
+    }
 
     // Set up signal handlers before doing anything non-trivial so we have some sort
     // of graceful shut down during initial database lookups and pending
     // transaction sends.
-    log::info!("Setting up signal handlers.");
+    tracing::info!("Setting up signal handlers.");
     let (stop_sender, mut stop_receiver) = tokio::sync::watch::channel(());
     let (died_sender, died_receiver) = tokio::sync::broadcast::channel(10);
+    let health = Health::new();
+    // In shadow mode no transactions are sent, so the balances do not matter.
+    if let Some(signers) = &signers {
+        health.register_checks(&[
+            health::CONCORDIUM_BALANCE_CHECK,
+            health::ETHEREUM_BALANCE_CHECK,
+        ]);
+        if signers.claim_wallet.is_some() {
+            health.register_checks(&[health::CLAIM_BALANCE_CHECK]);
+        }
+    }
     let shutdown_handler_handle = spawn_cancel(
         died_sender.clone(),
+        &health,
+        "shutdown handler",
         set_shutdown(stop_sender, died_receiver),
     );
 
-    let (registry, metrics) = ccdeth_relayer::metrics::Metrics::new()
-        .context("Unable to construct new metrics object.")?;
     if let Some(prometheus_server) = app.prometheus_server {
-        log::info!("Starting prometheus server at {prometheus_server}.");
+        tracing::info!("Starting prometheus server at {prometheus_server}.");
         spawn_cancel(
             died_sender.clone(),
+            &health,
+            "prometheus server",
             ccdeth_relayer::metrics::start_prometheus_server(prometheus_server, registry),
         );
     }
+    if let Some(health_server) = app.health_config.server {
+        tracing::info!("Starting health server at {health_server}.");
+        spawn_cancel(
+            died_sender.clone(),
+            &health,
+            "health server",
+            health::start_health_server(health_server, health.clone()),
+        );
+    }
 
-    let state_sender_contract =
-        StateSender::new(app.ethereum_config.state_sender, ethereum_client.clone());
-
-    let root_chain_manager_contract = ccdeth_relayer::root_chain_manager::BridgeManager::new(
-        app.ethereum_config.root_chain_manager,
-        Arc::new(ethereum_client.clone()),
+    let (tunables_sender, tunables_receiver) = tokio::sync::watch::channel(tunables.clone());
+    let (reload_sender, reload_receiver) = tokio::sync::mpsc::channel(10);
+    if let (Some(admin_server), Some(admin_token)) = (app.admin_server, app.admin_token) {
+        tracing::info!("Starting admin server at {admin_server}.");
+        spawn_cancel(
+            died_sender.clone(),
+            &health,
+            "admin server",
+            config::start_admin_server(
+                admin_server,
+                admin_token,
+                tunables_receiver.clone(),
+                reload_sender,
+            ),
+        );
+    } else {
+        drop(reload_sender);
+    }
+    let reload_handle = spawn_cancel(
+        died_sender.clone(),
+        &health,
+        "configuration reloader",
+        reload_config(
+            metrics.clone(),
+            args,
+            tunables_sender,
+            log_level,
+            reload_receiver,
+        ),
     );
 
-    let mut concordium_client = {
+    let mut concordium_nodes = Vec::new();
+    for (i, api) in app.concordium_config.apis.into_iter().enumerate() {
         // Use TLS if the URI scheme is HTTPS.
         // This uses whatever system certificates have been installed as trusted roots.
-        let endpoint = if app
-            .concordium_config
-            .api
-            .uri()
-            .scheme()
-            .map_or(false, |x| x == &http::uri::Scheme::HTTPS)
-        {
-            app.concordium_config
-                .api
-                .tls_config(ClientTlsConfig::new())
+        let endpoint = if api.uri().scheme() == Some(&http::uri::Scheme::HTTPS) {
+            api.tls_config(ClientTlsConfig::new())
                 .context("Unable to construct TLS configuration for the Concordium API.")?
         } else {
-            app.concordium_config.api
+            api
         };
         let ep = endpoint
             .timeout(std::time::Duration::from_secs(
                 app.concordium_config.request_timeout,
             ))
             .connect_timeout(std::time::Duration::from_secs(10));
-        v2::Client::new(ep)
-            .await
-            .context("Unable to connect Concordium node.")?
-    };
+        // The client connects when it is constructed, so a node that cannot be
+        // reached now is not used until the relayer is restarted.
+        match v2::Client::new(ep).await {
+            Ok(client) => concordium_nodes.push((i.to_string(), client)),
+            Err(e) => {
+                metrics.warnings_total.inc();
+                tracing::warn!("Unable to connect to Concordium node {i}: {e:#}");
+            }
+        }
+    }
+    let mut concordium_client = ConcordiumEndpoints::new(&metrics, concordium_nodes)
+        .context("Unable to connect to any Concordium node.")?;
     {
-        let lfb = concordium_client
-            .get_consensus_info()
-            .await
-            .context("Unable to get Consensus info.")?
-            .last_finalized_block;
         let bi = concordium_client
-            .get_block_info(lfb)
+            .get_last_finalized_block()
             .await
-            .context("Unable to get block information about last finalized block")?
-            .response;
+            .context("Unable to get block information about last finalized block")?;
         if chrono::Utc::now().signed_duration_since(bi.block_slot_time)
             > chrono::Duration::seconds(app.concordium_config.max_behind.into())
         {
@@ -598,37 +1386,6 @@ async fn main() -> anyhow::Result<()> {
     let (last_ethereum, last_concordium, db) = Database::new(&app.db_config)
         .await
         .context("Unable to connect to the database.")?;
-    let start_nonce = db
-        .submit_missing_txs(concordium_client.clone())
-        .await
-        .context("Unable to submit missing transactions.")?;
-
-    let bridge_manager_client = BridgeManagerClient::new(
-        concordium_client.clone(),
-        concordium_wallet.address,
-        app.concordium_config.bridge_manager,
-    );
-
-    let bridge_manager = concordium_contracts::BridgeManager::new(
-        bridge_manager_client.clone(),
-        concordium_wallet,
-        start_nonce,
-        app.concordium_config.max_energy,
-    )
-    .await
-    .context("Unable to connect to Concordium API.")?;
-
-    let (start_number, upper_number) = find_start_ethereum_config(
-        ethereum_client.clone(),
-        last_ethereum,
-        app.ethereum_config.state_sender_creation_block_number,
-        app.ethereum_config.num_confirmations,
-    )
-    .await
-    .context("Unable to find starting point for Ethereum monitoring")?;
-    log::info!(
-        "Found starting point on Ethereum chain at start = {start_number}, end = {upper_number})"
-    );
     let concordium_start_height = find_concordium_start_height(
         concordium_client.clone(),
         last_concordium,
@@ -637,146 +1394,161 @@ async fn main() -> anyhow::Result<()> {
     .await
     .context("Unable to find starting point for Concordium monitoring.")?;
 
-    log::info!("Starting at {concordium_start_height} on the Concordium chain.");
-
-    let (max_marked_event_index, leaves) = db
-        .pending_withdrawals(bridge_manager_client.clone())
-        .await
-        .context("Unable to get pending withdrawals.")?;
-
-    // To spawn
-
-    let (db_sender, db_receiver) = tokio::sync::mpsc::channel(50);
-    let (ccd_transaction_sender, ccd_transaction_receiver) = tokio::sync::mpsc::channel(50);
-    let (merkle_setter_sender, merkle_setter_receiver) = tokio::sync::mpsc::channel(50);
-
-    let pending_merkle_set = db
-        .pending_ethereum_tx()
-        .await
-        .context("Unable to get pending Merkle tree.")?;
+    tracing::info!("Starting at {concordium_start_height} on the Concordium chain.");
 
     // Now we set up the main service, after we have established a baseline.
-    // The different tasks communicate using channels established above.
     // The shutdown plan is as follows.
     // - Tasks which only query are just aborted.
     // - Tasks which send transactions or write to the database are given an
     //   opportunity to shut down gracefully by sending a signal on the
     //   stop_sender/stop_receiver channel. The same broadcast channel is shared by
     //   all tasks, and the only sender is the signal handler.
-    let tx_sender_handle = spawn_cancel(
-        died_sender.clone(),
-        concordium_contracts::concordium_tx_sender(
-            metrics.clone(),
-            concordium_client.clone(),
-            ccd_transaction_receiver,
-            stop_receiver.clone(),
-        ),
-    );
-    let db_task_handle = spawn_cancel(
-        died_sender.clone(),
-        db::handle_database(
-            metrics.clone(),
-            app.db_config,
-            db,
-            db_receiver,
-            bridge_manager,
-            ccd_transaction_sender,
-            merkle_setter_sender,
-            stop_receiver.clone(),
-        ),
-    );
-    let merkle_updater_handle = {
-        let merkle_client = MerkleSetterClient::new(
-            root_chain_manager_contract,
-            wallet,
-            app.ethereum_config.max_gas_price,
-            app.ethereum_config.max_gas,
-            ethereum_nonce,
-            &pending_merkle_set,
-            std::time::Duration::from_secs(app.ethereum_config.merkle_update_interval),
-            leaves,
-            max_marked_event_index,
-            std::time::Duration::from_secs(app.ethereum_config.escalation_interval),
-            std::time::Duration::from_secs(app.ethereum_config.warn_duration),
-        )
-        .context("Unable to construct the client for setting Merkle roots.")?;
+    let task_config = TaskConfig {
+        db_config: app.db_config.clone(),
+        bridge_manager: app.concordium_config.bridge_manager,
+        concordium_sender: concordium_sender_address,
+        state_sender: app.ethereum_config.state_sender,
+        root_chain_manager: app.ethereum_config.root_chain_manager,
+        state_sender_creation_block_number: app.ethereum_config.state_sender_creation_block_number,
+        skip_operations_from: app.ethereum_config.skip_operations_from,
+        concordium_start_height,
+        num_confirmations: app.ethereum_config.num_confirmations,
+        max_parallel: app.concordium_config.max_parallel,
+        max_behind: app.concordium_config.max_behind,
+        warn_duration: std::time::Duration::from_secs(app.ethereum_config.warn_duration),
+        endpoint_probe_interval: std::time::Duration::from_secs(app.endpoint_probe_interval),
+        health_limits: health::Limits {
+            max_ethereum_lag: app.health_config.max_ethereum_lag,
+            max_concordium_lag: app.health_config.max_concordium_lag,
+            max_pending_age: std::time::Duration::from_secs(app.health_config.max_pending_age),
+        },
+        health_check_interval: std::time::Duration::from_secs(app.health_config.check_interval),
+        blocklist,
+    };
+    let Tasks {
+        senders,
+        watchers,
+        balances,
+    } = tasks::make_tasks(
+        &metrics,
+        &health,
+        task_config,
+        &tunables,
+        tunables_receiver.clone(),
+        concordium_client.clone(),
+        ethereum_endpoints,
+        last_ethereum,
+        db,
+        signers,
+        stop_receiver.clone(),
+    )
+    .await?;
+    let sender_handles = senders
+        .into_iter()
+        .map(|(name, task)| (name, spawn_cancel(died_sender.clone(), &health, name, task)))
+        .collect::<Vec<_>>();
+    let watcher_handles = watchers
+        .into_iter()
+        .map(|(name, task)| (name, spawn_cancel(died_sender.clone(), &health, name, task)))
+        .collect::<Vec<_>>();
+
+    let mut balance_query_handles = Vec::new();
+    if let Some(BalanceMonitors {
+        concordium,
+        ethereum_sender,
+        ethereum,
+        claim,
+    }) = balances
+    {
+        balance_query_handles.push(spawn_cancel(
+            died_sender.clone(),
+            &health,
+            "Concordium balance",
+            query_concordium_balance(
+                metrics.clone(),
+                health.clone(),
+                concordium,
+                concordium_client.clone(),
+                concordium_sender_address,
+                tunables_receiver.clone(),
+            ),
+        ));
+        balance_query_handles.push(spawn_cancel(
+            died_sender.clone(),
+            &health,
+            "Ethereum balance",
+            query_ethereum_balance(
+                metrics.clone(),
+                health.clone(),
+                ethereum,
+                ethereum_client.clone(),
+                ethereum_sender,
+                tunables_receiver.clone(),
+            ),
+        ));
+        if let Some((claim_sender, claim)) = claim {
+            balance_query_handles.push(spawn_cancel(
+                died_sender.clone(),
+                &health,
+                "claim account balance",
+                query_ethereum_balance(
+                    metrics.clone(),
+                    health.clone(),
+                    claim,
+                    ethereum_client.clone(),
+                    claim_sender,
+                    tunables_receiver,
+                ),
+            ));
+        }
+    }
 
+    let guardian_handle = guardian_wallet.map(|wallet| {
         spawn_cancel(
             died_sender.clone(),
-            merkle::send_merkle_root_updates(
+            &health,
+            "guardian",
+            guardian::guard(
                 metrics.clone(),
-                merkle_client,
-                pending_merkle_set,
-                merkle_setter_receiver,
-                db_sender.clone(),
-                app.ethereum_config.num_confirmations,
-                stop_receiver.clone(),
+                health.clone(),
+                guardian::Guardian::new(
+                    concordium_client.clone(),
+                    wallet,
+                    app.concordium_config.bridge_manager,
+                    tunables.max_energy,
+                ),
+                ethereum_client.clone(),
+                app.db_config.clone(),
+                app.guardian_config.rules(),
+                std::time::Duration::from_secs(app.guardian_config.check_interval),
             ),
         )
-    };
-
-    // The remaining tasks only watch so they are aborted on signal received.
-    let watch_concordium_handle = spawn_cancel(
-        died_sender.clone(),
-        concordium_contracts::listen_concordium(
-            metrics.clone(),
-            bridge_manager_client.clone(),
-            db_sender.clone(),
-            concordium_start_height,
-            app.concordium_config.max_parallel,
-            app.concordium_config.max_behind,
-        ),
-    );
-    let watch_ethereum_handle = spawn_cancel(
-        died_sender.clone(),
-        ethereum::watch_eth_blocks(
-            metrics.clone(),
-            state_sender_contract,
-            db_sender.clone(),
-            start_number,
-            upper_number,
-            app.ethereum_config.num_confirmations,
-        ),
-    );
-
-    let balance_query_handle = spawn_cancel(
-        died_sender.clone(),
-        query_concordium_balance(
-            metrics.clone(),
-            concordium_client,
-            concordium_sender_address,
-            Amount::from_micro_ccd(app.concordium_config.min_balance),
-        ),
-    );
-
-    let ethereum_balance_query_handle = spawn_cancel(
-        died_sender.clone(),
-        query_ethereum_balance(
-            metrics.clone(),
-            ethereum_client,
-            ethereum_sender,
-            U256::from(app.ethereum_config.min_balance) * 1_000_000_000_000u64,
-        ),
-    );
+    });
 
     // Wait for signal to be received.
     if let Err(e) = stop_receiver.changed().await {
-        log::error!("The signal handler unexpectedly died with {e}. Shutting off the service.");
+        tracing::error!("The signal handler unexpectedly died with {e}. Shutting off the service.");
     }
 
     // Stop watcher tasks.
-    watch_concordium_handle.abort();
-    watch_ethereum_handle.abort();
-    balance_query_handle.abort();
-    ethereum_balance_query_handle.abort();
+    for (_, handle) in &watcher_handles {
+        handle.abort();
+    }
+    for handle in &balance_query_handles {
+        handle.abort();
+    }
+    if let Some(handle) = &guardian_handle {
+        handle.abort();
+    }
+    reload_handle.abort();
     // And wait for all of them to terminate.
-    let shutdown = [
-        await_and_report("merkle updater", merkle_updater_handle),
-        await_and_report("watch Ethereum", watch_ethereum_handle),
-        await_and_report("watch Concordium", watch_concordium_handle),
-        await_and_report("database handler", db_task_handle),
-        await_and_report("concordium transaction sender", tx_sender_handle),
-    ];
+    let mut shutdown = Vec::new();
+    for (descr, handle) in watcher_handles {
+        shutdown.push(await_and_report(descr, handle));
+    }
+    for (descr, handle) in sender_handles {
+        shutdown.push(await_and_report(descr, handle));
+    }
     shutdown
         .into_iter()
         .collect::<futures::stream::FuturesUnordered<_>>()
@@ -785,6 +1557,7 @@ async fn main() -> anyhow::Result<()> {
     await_and_report("shutdown handler", shutdown_handler_handle).await;
     drop(died_sender); // keep the sender alive until here explicitly so that we don't have spurious
                        // errors when the last task is dying.
+    telemetry::shutdown();
     Ok(())
 }
 
@@ -796,18 +1569,18 @@ async fn await_and_report<E: std::fmt::Display>(
 ) {
     match handle.await {
         Ok(Ok(())) => {
-            log::info!("Task {descr} terminated.");
+            tracing::info!("Task {descr} terminated.");
         }
         Ok(Err(e)) => {
-            log::error!("Task {descr} unexpectedly stopped due to {e:#}.");
+            tracing::error!("Task {descr} unexpectedly stopped due to {e:#}.");
         }
         Err(e) => {
             if e.is_panic() {
-                log::error!("Task panicked.");
+                tracing::error!("Task panicked.");
             } else if e.is_cancelled() {
-                log::info!("Task {descr} was cancelled.");
+                tracing::info!("Task {descr} was cancelled.");
             } else {
-                log::error!("Task {descr} unexpectedly closed.");
+                tracing::error!("Task {descr} unexpectedly closed.");
             }
         }
     }
@@ -830,7 +1603,7 @@ async fn set_shutdown(
         let task_died = Box::pin(task_died.recv());
         futures::future::select(task_died, futures::future::select(terminate, interrupt)).await;
         if stop_sender.send(()).is_err() {
-            log::error!("Unable to send stop signal.");
+            tracing::error!("Unable to send stop signal.");
         }
     }
     #[cfg(windows)]
@@ -843,7 +1616,7 @@ async fn set_shutdown(
         let task_died = Box::pin(task_died.recv());
         futures::future::select(task_died, futures::future::select(ctrl_break, ctrl_c)).await;
         if stop_sender.send(()).is_err() {
-            log::error!("Unable to send stop signal.");
+            tracing::error!("Unable to send stop signal.");
         }
     }
     Ok(())
@@ -0,0 +1,291 @@
+//! Compare the database of a relayer running in shadow mode against the
+//! database of the production relayer. The deposits, the Merkle leaf hashes
+//! of withdrawals, the Merkle roots, and the checkpoint heights are compared,
+//! and the differences are printed. The tool exits with an error if there are
+//! any.
+use anyhow::Context;
+use ccdeth_relayer::{db, merkle::Keccak256Algorithm};
+use clap::Parser;
+use rs_merkle::MerkleTree;
+use std::collections::BTreeMap;
+
+#[derive(Parser, Debug)]
+#[clap(author, version, about)]
+struct CompareShadow {
+    #[clap(
+        long = "production-db",
+        help = "Database connection string of the production relayer.",
+        env = "ETHCCD_COMPARE_PRODUCTION_DB_STRING"
+    )]
+    production_db:             tokio_postgres::Config,
+    #[clap(
+        long = "shadow-db",
+        help = "Database connection string of the relayer in shadow mode.",
+        env = "ETHCCD_COMPARE_SHADOW_DB_STRING"
+    )]
+    shadow_db:                 tokio_postgres::Config,
+    #[clap(
+        long = "max-checkpoint-difference",
+        help = "Number of blocks by which the checkpoints of the two relayers may differ before \
+                it is reported as a difference.",
+        env = "ETHCCD_COMPARE_MAX_CHECKPOINT_DIFFERENCE",
+        default_value = "100"
+    )]
+    max_checkpoint_difference: u64,
+}
+
+/// A deposit as recorded in the `ethereum_deposit_events` table, keyed by the
+/// index of the deposit event.
+#[derive(Debug, PartialEq, Eq)]
+struct Deposit {
+    origin_tx_hash: Vec<u8>,
+    amount:         String,
+    depositor:      Vec<u8>,
+    root_token:     Vec<u8>,
+}
+
+impl std::fmt::Display for Deposit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} of {} by {} in {}",
+            self.amount,
+            hex::encode(&self.root_token),
+            hex::encode(&self.depositor),
+            hex::encode(&self.origin_tx_hash)
+        )
+    }
+}
+
+/// A wrapper to display hashes in hex.
+#[derive(Debug, PartialEq, Eq)]
+struct Hash(Vec<u8>);
+
+impl std::fmt::Display for Hash {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&hex::encode(&self.0))
+    }
+}
+
+async fn checkpoints(client: &tokio_postgres::Client) -> anyhow::Result<BTreeMap<String, i64>> {
+    let rows = client
+        .query(
+            "SELECT network::text, last_processed_height FROM checkpoints;",
+            &[],
+        )
+        .await?;
+    rows.iter()
+        .map(|row| Ok((row.try_get(0)?, row.try_get(1)?)))
+        .collect()
+}
+
+async fn deposits(client: &tokio_postgres::Client) -> anyhow::Result<BTreeMap<i64, Deposit>> {
+    let rows = client
+        .query(
+            "SELECT origin_event_index, origin_tx_hash, amount, depositor, root_token FROM \
+             ethereum_deposit_events;",
+            &[],
+        )
+        .await?;
+    rows.iter()
+        .map(|row| {
+            Ok((row.try_get(0)?, Deposit {
+                origin_tx_hash: row.try_get(1)?,
+                amount:         row.try_get(2)?,
+                depositor:      row.try_get(3)?,
+                root_token:     row.try_get(4)?,
+            }))
+        })
+        .collect()
+}
+
+async fn leaf_hashes(client: &tokio_postgres::Client) -> anyhow::Result<BTreeMap<i64, Hash>> {
+    let rows = client
+        .query(
+            "SELECT event_index, event_merkle_hash FROM concordium_events WHERE event_type = \
+             'withdraw';",
+            &[],
+        )
+        .await?;
+    rows.iter()
+        .map(|row| Ok((row.try_get(0)?, Hash(row.try_get(1)?))))
+        .collect()
+}
+
+/// Compare the rows of the two databases with the same key. A row that is
+/// missing from one database is only reported if that database has a row with
+/// a larger key, since otherwise it may just be behind. Return the number of
+/// differences.
+fn compare<V: PartialEq + std::fmt::Display>(
+    what: &str,
+    production: &BTreeMap<i64, V>,
+    shadow: &BTreeMap<i64, V>,
+) -> usize {
+    let production_max = production.last_key_value().map(|x| *x.0);
+    let shadow_max = shadow.last_key_value().map(|x| *x.0);
+    let mut differences = 0;
+    for (index, value) in production {
+        match shadow.get(index) {
+            Some(shadow_value) if shadow_value != value => {
+                println!("{what} {index} differs. Production: {value}, shadow: {shadow_value}.");
+                differences += 1;
+            }
+            None if Some(*index) < shadow_max => {
+                println!("{what} {index} is missing from the shadow database.");
+                differences += 1;
+            }
+            _ => {}
+        }
+    }
+    for index in shadow.keys() {
+        if !production.contains_key(index) && Some(*index) < production_max {
+            println!("{what} {index} is missing from the production database.");
+            differences += 1;
+        }
+    }
+    println!(
+        "Compared {} {what}s in production and {} in shadow, {differences} differences.",
+        production.len(),
+        shadow.len()
+    );
+    differences
+}
+
+/// Check that the current Merkle root of the production relayer is the root of
+/// the tree built from the leaf hashes in the shadow database. Return the
+/// number of differences.
+async fn compare_roots(
+    production: &tokio_postgres::Client,
+    shadow: &tokio_postgres::Client,
+    shadow_leaves: &BTreeMap<i64, Hash>,
+) -> anyhow::Result<usize> {
+    let Some(row) = production
+        .query_opt(
+            "SELECT root FROM merkle_roots ORDER BY id DESC LIMIT 1;",
+            &[],
+        )
+        .await?
+    else {
+        println!("No Merkle root is set by the production relayer.");
+        return Ok(0);
+    };
+    let root: Vec<u8> = row.try_get(0)?;
+    let indices = production
+        .query(
+            "SELECT event_index FROM concordium_events WHERE root = $1 OR previous_root = $1 \
+             ORDER BY event_index ASC;",
+            &[&root],
+        )
+        .await?;
+    let mut tree = MerkleTree::<Keccak256Algorithm>::new();
+    for row in indices {
+        let index: i64 = row.try_get(0)?;
+        let Some(Hash(hash)) = shadow_leaves.get(&index) else {
+            println!(
+                "Withdrawal {index} in the Merkle root {} is missing from the shadow database.",
+                hex::encode(&root)
+            );
+            return Ok(1);
+        };
+        tree.insert(
+            hash.as_slice()
+                .try_into()
+                .context("Leaf hash in the shadow database is not 32 bytes.")?,
+        );
+    }
+    tree.commit();
+    let differences = match tree.root() {
+        Some(shadow_root) if shadow_root[..] == root[..] => {
+            println!(
+                "The Merkle root {} of the production relayer matches the shadow leaves.",
+                hex::encode(&root)
+            );
+            0
+        }
+        shadow_root => {
+            println!(
+                "The Merkle root {} of the production relayer differs from the root {} computed \
+                 from the shadow leaves.",
+                hex::encode(&root),
+                shadow_root.map_or_else(|| "(none)".into(), hex::encode)
+            );
+            1
+        }
+    };
+    // The relayers compute roots at different times, so the shadow relayer
+    // need not have computed the same root.
+    let recorded = shadow
+        .query_opt(
+            "SELECT computed_at FROM shadow_merkle_roots WHERE root = $1 ORDER BY id ASC LIMIT 1;",
+            &[&root],
+        )
+        .await?;
+    match recorded {
+        Some(row) => {
+            let computed_at: chrono::DateTime<chrono::Utc> = row.try_get(0)?;
+            println!("The shadow relayer computed the same root at {computed_at}.");
+        }
+        None => println!("The shadow relayer did not compute the same root."),
+    }
+    Ok(differences)
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let app: CompareShadow = CompareShadow::parse();
+
+    let (production, _production_handle) = db::connect(&app.production_db)
+        .await
+        .context("Unable to connect to the production database.")?;
+    let (shadow, _shadow_handle) = db::connect(&app.shadow_db)
+        .await
+        .context("Unable to connect to the shadow database.")?;
+
+    let mut differences = 0;
+
+    let production_checkpoints = checkpoints(&production).await?;
+    let shadow_checkpoints = checkpoints(&shadow).await?;
+    for network in ["ethereum", "concordium"] {
+        match (
+            production_checkpoints.get(network),
+            shadow_checkpoints.get(network),
+        ) {
+            (Some(p), Some(s)) => {
+                println!("Checkpoint on {network} is {p} in production and {s} in shadow.");
+                if p.abs_diff(*s) > app.max_checkpoint_difference {
+                    println!(
+                        "Checkpoints on {network} differ by more than {} blocks.",
+                        app.max_checkpoint_difference
+                    );
+                    differences += 1;
+                }
+            }
+            (p, s) => {
+                println!("Checkpoint on {network} is missing. Production: {p:?}, shadow: {s:?}.");
+                differences += 1;
+            }
+        }
+    }
+
+    differences += compare(
+        "Deposit",
+        &deposits(&production).await?,
+        &deposits(&shadow).await?,
+    );
+
+    let shadow_leaves = leaf_hashes(&shadow).await?;
+    differences += compare(
+        "Withdrawal leaf hash",
+        &leaf_hashes(&production).await?,
+        &shadow_leaves,
+    );
+
+    differences += compare_roots(&production, &shadow, &shadow_leaves).await?;
+
+    anyhow::ensure!(
+        differences == 0,
+        "Found {differences} differences between the databases."
+    );
+    println!("No differences found.");
+    Ok(())
+}
@@ -0,0 +1,115 @@
+//! Blocklist of addresses whose deposits are held in quarantine. The relayer
+//! does not send a transaction to Concordium for a deposit from a blocked
+//! depositor, to a blocked receiver, or to a blocked contract via its hook. The
+//! deposit is recorded as quarantined in the database instead, and listed by
+//! the API server, so that it can be reviewed. A reviewer then releases or
+//! rejects it with the `ccdeth_quarantine` tool.
+//!
+//! The blocklist is either read from a file when the relayer starts, or read
+//! from the `blocklist` table of the database before each batch of Ethereum
+//! events, so that it can be changed while the relayer is running. In both
+//! cases an entry is either an Ethereum address in hex, with a `0x` prefix, a
+//! Concordium account address in base58, or a Concordium contract address as
+//! `<index,subindex>`.
+use anyhow::Context;
+use concordium_rust_sdk::{id::types::AccountAddress, types::ContractAddress};
+use ethabi::ethereum_types::H160;
+use std::{collections::HashSet, path::Path};
+
+/// Where the relayer reads the blocklist from.
+#[derive(Debug, Clone)]
+pub enum BlocklistSource {
+    /// No deposits are quarantined.
+    None,
+    /// A fixed blocklist, read from a file at startup.
+    File(Blocklist),
+    /// The `blocklist` table of the database.
+    Table,
+}
+
+/// A set of blocked addresses on both chains.
+#[derive(Debug, Clone, Default)]
+pub struct Blocklist {
+    ethereum: HashSet<H160>,
+    concordium: HashSet<AccountAddress>,
+    contracts: HashSet<ContractAddress>,
+}
+
+impl Blocklist {
+    /// Add an entry to the blocklist, failing if it is not a valid address.
+    pub fn insert(&mut self, entry: &str) -> anyhow::Result<()> {
+        if let Some(hex) = entry.strip_prefix("0x") {
+            let address = hex
+                .parse::<H160>()
+                .with_context(|| format!("Invalid Ethereum address {entry}."))?;
+            self.ethereum.insert(address);
+        } else if let Some(contract) = entry
+            .strip_prefix('<')
+            .and_then(|entry| entry.strip_suffix('>'))
+        {
+            let (index, subindex) = contract
+                .split_once(',')
+                .with_context(|| format!("Invalid contract address {entry}."))?;
+            let address = ContractAddress::new(
+                index
+                    .trim()
+                    .parse()
+                    .with_context(|| format!("Invalid contract address {entry}."))?,
+                subindex
+                    .trim()
+                    .parse()
+                    .with_context(|| format!("Invalid contract address {entry}."))?,
+            );
+            self.contracts.insert(address);
+        } else {
+            let address = entry
+                .parse::<AccountAddress>()
+                .map_err(|e| anyhow::anyhow!("Invalid Concordium address {entry}: {e}"))?;
+            self.concordium.insert(address);
+        }
+        Ok(())
+    }
+
+    /// Read the blocklist from a file with one address per line. Empty lines
+    /// and lines starting with `#` are ignored.
+    pub fn from_file(path: &Path) -> anyhow::Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Unable to read the blocklist from {}.", path.display()))?;
+        let mut blocklist = Self::default();
+        for (number, line) in contents.lines().enumerate() {
+            let entry = line.trim();
+            if entry.is_empty() || entry.starts_with('#') {
+                continue;
+            }
+            blocklist
+                .insert(entry)
+                .with_context(|| format!("Line {} of {}", number + 1, path.display()))?;
+        }
+        Ok(blocklist)
+    }
+
+    /// Whether a deposit from the depositor on Ethereum to the receiver on
+    /// Concordium must be quarantined. The `contract` is the target of the
+    /// deposit's hook, if any, which receives the deposit instead of the
+    /// receiver.
+    pub fn is_blocked(
+        &self,
+        depositor: &H160,
+        receiver: &AccountAddress,
+        contract: Option<&ContractAddress>,
+    ) -> bool {
+        self.ethereum.contains(depositor)
+            || self.concordium.contains(receiver)
+            || contract.is_some_and(|contract| self.contracts.contains(contract))
+    }
+
+    /// The number of blocked addresses.
+    pub fn len(&self) -> usize {
+        self.ethereum.len() + self.concordium.len() + self.contracts.len()
+    }
+
+    /// Whether no address is blocked.
+    pub fn is_empty(&self) -> bool {
+        self.ethereum.is_empty() && self.concordium.is_empty() && self.contracts.is_empty()
+    }
+}
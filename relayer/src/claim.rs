@@ -0,0 +1,655 @@
+//! Claiming of approved withdrawals on Ethereum on behalf of users.
+//!
+//! Users opt in to this by withdrawing via the `withdrawWithClaim` entrypoint
+//! of the bridge manager and paying the claim fee. Once the withdrawal is
+//! approved by a Merkle root set on Ethereum the relayer sends the `withdraw`
+//! transaction, together with the Merkle proof, to the `RootChainManager`.
+//!
+//! Claims are sent from a separate account from the one that sets Merkle roots
+//! so that the two do not compete for nonces.
+use anyhow::Context;
+use ethabi::ethereum_types::{H256, U256};
+use ethers::{
+    prelude::{types::transaction::eip2718::TypedTransaction, Middleware, Signer},
+    utils::rlp::Rlp,
+};
+
+use crate::{
+    balance::SendGate,
+    config::{self, Tunables, TunablesReceiver},
+    db::{ClaimableWithdrawal, DatabaseOperation, PendingClaimTransactions},
+    merkle::{
+        convert_from_token_amount, escalate_transaction, make_event_leaf_hash, make_proof,
+        EscalationResult, EthereumSenderError,
+    },
+    root_chain_manager::{BridgeManager, WithdrawParams},
+};
+
+pub struct ClaimClient<M: Middleware, S> {
+    pub root_manager: BridgeManager<M>,
+    /// The signer used to sign claim transactions.
+    pub signer: S,
+    /// Maximum gas price allowed. If the current gas price is above this
+    /// no claims are sent until it decreases.
+    pub max_gas_price: U256,
+    /// Maximum gas for a single claim transaction.
+    pub max_gas: U256,
+    /// Next nonce used for sending transactions. This is updated **after** a
+    /// pending transaction is confirmed.
+    pub next_nonce: U256,
+    /// How often to check for new withdrawals to claim.
+    pub claim_interval: std::time::Duration,
+    /// Interval when we escalate the transaction price.
+    pub escalate_interval: std::time::Duration,
+    /// Duration after which we start warning that the transaction is not
+    /// confirmed.
+    pub warn_duration: std::time::Duration,
+}
+
+impl<M: Middleware, S: Signer> ClaimClient<M, S> {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        root_manager: BridgeManager<M>,
+        signer: S,
+        max_gas_price: U256,
+        max_gas: U256,
+        next_nonce: U256,
+        pending_claim: &Option<PendingClaimTransactions>,
+        claim_interval: std::time::Duration,
+        escalate_interval: std::time::Duration,
+        warn_duration: std::time::Duration,
+    ) -> anyhow::Result<Self> {
+        let next_nonce = if let Some(PendingClaimTransactions { txs, .. }) = pending_claim {
+            let Some((tx_hash, data)) = txs.last() else {
+                anyhow::bail!(
+                    "Invariant violation. There are pending claims, but we cannot find their \
+                     transactions."
+                );
+            };
+            let (tx, _) = TypedTransaction::decode_signed(&Rlp::new(data))?;
+            tracing::debug!(
+                "There is a pending claim transaction with hash {:#x}. Using its nonce as the \
+                 next nonce.",
+                tx_hash,
+            );
+            anyhow::ensure!(
+                tx.from() == Some(&signer.address()),
+                "Pending claim transaction is incorrectly signed."
+            );
+            *tx.nonce().context("Nonce must have been set.")?
+        } else {
+            next_nonce
+        };
+        Ok(Self {
+            root_manager,
+            signer,
+            max_gas_price,
+            max_gas,
+            next_nonce,
+            claim_interval,
+            escalate_interval,
+            warn_duration,
+        })
+    }
+}
+
+impl<M: Middleware, S> ClaimClient<M, S> {
+    /// Use the new tunables for the next claims.
+    pub fn update_tunables(&mut self, tunables: &Tunables) {
+        self.max_gas_price = tunables.max_gas_price;
+        self.max_gas = tunables.claim_max_gas;
+        self.claim_interval = tunables.claim_interval;
+        self.escalate_interval = tunables.escalation_interval;
+    }
+}
+
+/// Construct the parameters and the proof for the `withdraw` call on Ethereum.
+fn make_withdraw_params(
+    claim: &ClaimableWithdrawal,
+) -> anyhow::Result<(WithdrawParams, Vec<[u8; 32]>)> {
+    let we = &claim.event;
+    let leaf = make_event_leaf_hash(claim.tx_hash, we)?;
+    anyhow::ensure!(
+        claim.leaves.contains(&(we.event_index, leaf)),
+        "The withdrawal does not match the leaf stored in the database."
+    );
+    let proof = make_proof(
+        claim.leaves.iter().map(|&l| Ok::<_, anyhow::Error>(l)),
+        we.event_index,
+    )?
+    .context("The withdrawal is not part of the current Merkle root.")?;
+    let amount = convert_from_token_amount(&we.amount);
+    anyhow::ensure!(
+        amount <= U256::from(u64::MAX),
+        "The amount {amount} cannot be claimed via the RootChainManager."
+    );
+    let params = WithdrawParams {
+        ccd_index: we.contract.index,
+        ccd_sub_index: we.contract.subindex,
+        amount: amount.as_u64(),
+        user_wallet: we.eth_address.eth_address.into(),
+        ccd_tx_hash: claim.tx_hash.to_string(),
+        ccd_event_index: we.event_index,
+        token_id: u64::from_le_bytes(
+            Vec::from(we.token_id.clone())
+                .try_into()
+                .map_err(|_| anyhow::anyhow!("Invalid token id."))?,
+        ),
+    };
+    Ok((params, proof.proof_hashes().to_vec()))
+}
+
+enum MakeClaimResult {
+    /// A claim transaction was constructed and signed.
+    Claim {
+        tx_hash: H256,
+        raw_tx: ethers::prelude::Bytes,
+        event_index: u64,
+    },
+    /// The current gas price is too high.
+    GasTooHigh {
+        max_gas_price: U256,
+        current_gas_price: U256,
+    },
+}
+
+impl<M: Middleware, S: Signer> ClaimClient<M, S>
+where
+    S::Error: 'static,
+{
+    async fn make_claim_transaction(
+        &self,
+        claim: &ClaimableWithdrawal,
+        params: WithdrawParams,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<MakeClaimResult, EthereumSenderError<M>> {
+        let current_gas_price = self
+            .root_manager
+            .client()
+            .get_gas_price()
+            .await
+            .map_err(EthereumSenderError::Retryable)?;
+        if current_gas_price > self.max_gas_price {
+            return Ok(MakeClaimResult::GasTooHigh {
+                max_gas_price: self.max_gas_price,
+                current_gas_price,
+            });
+        }
+        // The RootChainManager charges a fee for each withdrawal, which is paid
+        // by the relayer out of the claim fee the user paid on Concordium.
+        let withdraw_fee = self
+            .root_manager
+            .withdraw_fee()
+            .call()
+            .await
+            .map_err(|e| match e {
+                ethers::contract::ContractError::MiddlewareError(e) => {
+                    EthereumSenderError::Retryable(e)
+                }
+                e => EthereumSenderError::Internal(anyhow::anyhow!(
+                    "Unable to query the withdraw fee: {e}"
+                )),
+            })?;
+        let call = self.root_manager.withdraw(params, proof);
+        let tx = call.tx;
+        let tx = TypedTransaction::Legacy(ethers::types::TransactionRequest {
+            from: Some(self.signer.address()),
+            to: tx.to().cloned(),
+            gas: Some(self.max_gas),
+            gas_price: Some(current_gas_price),
+            value: Some(withdraw_fee),
+            data: tx.data().cloned(),
+            nonce: Some(self.next_nonce),
+            chain_id: Some(self.signer.chain_id().into()),
+        });
+        let signature = self
+            .signer
+            .sign_transaction(&tx)
+            .await
+            .map_err(|e| EthereumSenderError::Internal(e.into()))?;
+        Ok(MakeClaimResult::Claim {
+            tx_hash: tx.hash(&signature),
+            raw_tx: tx.rlp_signed(&signature),
+            event_index: claim.event.event_index,
+        })
+    }
+}
+
+struct PendingClaim {
+    /// Event index of the withdrawal that is being claimed.
+    event_index: u64,
+    /// The list of transactions hashes and transactions.
+    /// This list is never empty and is ordered by increasing gas price.
+    pending_txs: Vec<(H256, ethers::prelude::Bytes)>,
+}
+
+/// A task that claims approved withdrawals on behalf of users.
+///
+/// Upon start it will check if it needs to resubmit any pending transactions.
+/// After that it periodically checks the database for withdrawals that should
+/// be claimed, and sends the claim transactions one at a time. Each
+/// transaction is stored in the database before it is sent, and is escalated
+/// if it is not confirmed in time, in the same way as Merkle root updates.
+#[allow(clippy::too_many_arguments)]
+pub async fn send_claims<M: Middleware + 'static, S: Signer + 'static>(
+    metrics: crate::metrics::Metrics,
+    mut client: ClaimClient<M, S>,
+    pending_claim: Option<PendingClaimTransactions>,
+    db_sender: tokio::sync::mpsc::Sender<DatabaseOperation>,
+    num_confirmations: u64,
+    mut gate: SendGate,
+    mut tunables: TunablesReceiver,
+    mut stop: tokio::sync::watch::Receiver<()>,
+) -> anyhow::Result<()>
+where
+    S::Error: 'static,
+{
+    let mut pending = None;
+    // Check if we need to resubmit the pending transactions.
+    if let Some(PendingClaimTransactions { txs, event_index }) = pending_claim {
+        let ethereum_client = client.root_manager.client();
+        for (tx_hash, raw_tx) in &txs {
+            let status = ethereum_client
+                .get_transaction(*tx_hash)
+                .await
+                .context("Unable to get transaction status.")?;
+            if status.is_none() {
+                tracing::info!(
+                    "Claim transaction with hash {tx_hash:#x} is in the database, but not known \
+                     to the Ethereum chain. Submitting it."
+                );
+                ethereum_client
+                    .send_raw_transaction(raw_tx.clone())
+                    .await
+                    .context("Unable to send raw transaction.")?;
+                metrics.sent_ethereum_transactions.inc();
+            }
+        }
+        pending = Some(PendingClaim {
+            event_index,
+            pending_txs: txs,
+        });
+    }
+    while let Err(e) = send_claims_worker(
+        &metrics,
+        &mut client,
+        &db_sender,
+        &mut pending,
+        num_confirmations,
+        &mut gate,
+        &mut tunables,
+        &mut stop,
+    )
+    .await
+    {
+        match e {
+            EthereumSenderError::Retryable(e) => {
+                metrics.warnings_total.inc();
+                tracing::warn!(
+                    "An error occurred when trying to send claims to Ethereum or query the sent \
+                     transaction. Will attempt again in 10s: {e}"
+                );
+                tokio::time::sleep(std::time::Duration::from_secs(10)).await;
+            }
+            EthereumSenderError::InternalABI(e) => {
+                metrics.errors_total.inc();
+                tracing::error!(
+                    "Unable to parse responses from Ethereum. This indicates a configuration \
+                     error: {e:#}"
+                );
+                return Err(e.into());
+            }
+            EthereumSenderError::Internal(e) => {
+                metrics.errors_total.inc();
+                tracing::error!("An unrecoverable error occurred when sending claims: {e:#}.");
+                return Err(e);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The response is `Ok(())` if the service was asked to stop, otherwise it is
+/// one of the errors if some part of the job was interrupted.
+/// The client and `pending` are always left in a consistent state, so that a
+/// retry can be made. Changed `tunables` are applied to the client as they
+/// arrive.
+#[allow(clippy::too_many_arguments)]
+async fn send_claims_worker<M: Middleware, S: Signer>(
+    metrics: &crate::metrics::Metrics,
+    client: &mut ClaimClient<M, S>,
+    db_sender: &tokio::sync::mpsc::Sender<DatabaseOperation>,
+    pending: &mut Option<PendingClaim>,
+    num_confirmations: u64,
+    gate: &mut SendGate,
+    tunables: &mut TunablesReceiver,
+    stop: &mut tokio::sync::watch::Receiver<()>,
+) -> Result<(), EthereumSenderError<M>>
+where
+    S::Error: 'static,
+{
+    client.update_tunables(&tunables.borrow_and_update());
+    let mut claim_interval = tokio::time::interval(client.claim_interval);
+    claim_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    // Withdrawals with a smaller event index than this are skipped since they
+    // cannot be claimed.
+    let mut from_event_index = 0;
+    loop {
+        let should_stop = match wait_pending_claim(
+            metrics,
+            client,
+            db_sender,
+            pending,
+            num_confirmations,
+            tunables,
+            stop,
+        )
+        .await?
+        {
+            WaitPendingResult::Stop => true,
+            WaitPendingResult::Ok => loop {
+                if client.claim_interval != claim_interval.period() {
+                    tracing::info!(
+                        "Checking for withdrawals to claim every {}s.",
+                        client.claim_interval.as_secs()
+                    );
+                    claim_interval = tokio::time::interval_at(
+                        tokio::time::Instant::now() + client.claim_interval,
+                        client.claim_interval,
+                    );
+                    claim_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+                }
+                tokio::select! {
+                    _ = stop.changed() => break true,
+                    _ = claim_interval.tick() => break false,
+                    _ = config::changed(tunables) => {
+                        client.update_tunables(&tunables.borrow_and_update());
+                    }
+                }
+            },
+            // Check for further claims, or escalate, immediately.
+            WaitPendingResult::Confirmed | WaitPendingResult::Escalate => false,
+        };
+        if should_stop {
+            break;
+        }
+        // Do not claim, or escalate, while the balance of the claim account is too low.
+        if !gate.can_send() {
+            tracing::warn!("Not claiming withdrawals until the claim account is topped up.");
+            if gate.wait(stop).await {
+                break;
+            }
+            tracing::info!("Resuming claiming of withdrawals.");
+        }
+        if send_claim(metrics, client, db_sender, pending, &mut from_event_index).await? {
+            break;
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy)]
+enum WaitPendingResult {
+    /// The service should stop.
+    Stop,
+    /// There was no pending claim.
+    Ok,
+    /// A pending claim was confirmed.
+    Confirmed,
+    /// Timeout for escalation reached. Send a new transaction.
+    Escalate,
+}
+
+/// Wait until the pending claim, if any, is confirmed.
+async fn wait_pending_claim<M: Middleware, S: Signer>(
+    metrics: &crate::metrics::Metrics,
+    client: &mut ClaimClient<M, S>,
+    db_sender: &tokio::sync::mpsc::Sender<DatabaseOperation>,
+    pending: &mut Option<PendingClaim>,
+    num_confirmations: u64,
+    tunables: &mut TunablesReceiver,
+    stop: &mut tokio::sync::watch::Receiver<()>,
+) -> Result<WaitPendingResult, EthereumSenderError<M>> {
+    let mut check_interval = tokio::time::interval(std::time::Duration::from_secs(10));
+    check_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    let start = tokio::time::Instant::now();
+    let mut result = WaitPendingResult::Ok;
+    'outer: while let Some(PendingClaim {
+        event_index,
+        pending_txs,
+    }) = pending
+    {
+        let stop = tokio::select! {
+            _ = stop.changed() => true,
+            _ = check_interval.tick() => false,
+        };
+        if stop {
+            return Ok(WaitPendingResult::Stop);
+        }
+        if tunables.has_changed().unwrap_or(false) {
+            client.update_tunables(&tunables.borrow_and_update());
+        }
+        for (i, (pending_hash, _)) in pending_txs.iter().enumerate() {
+            let elapsed = start.elapsed();
+            if elapsed > client.escalate_interval {
+                return Ok(WaitPendingResult::Escalate);
+            } else if elapsed > client.warn_duration {
+                metrics.warnings_total.inc();
+                tracing::warn!(
+                    "More than {}s elapsed waiting for claim {pending_hash:#x} to be confirmed.",
+                    elapsed.as_secs()
+                );
+            }
+            let maybe_receipt = client
+                .root_manager
+                .client()
+                .get_transaction_receipt(*pending_hash)
+                .await
+                .map_err(EthereumSenderError::Retryable)?;
+            let Some(receipt) = maybe_receipt else {
+                tracing::debug!("Claim transaction {pending_hash:#x} has no receipt.");
+                continue;
+            };
+            let Some(bn) = receipt.block_number else {
+                return Err(EthereumSenderError::Internal(anyhow::anyhow!(
+                    "A submitted transaction is confirmed, but without a block hash. This \
+                     indicates a configuration error."
+                )));
+            };
+            let current_block = client
+                .root_manager
+                .client()
+                .get_block_number()
+                .await
+                .map_err(EthereumSenderError::Retryable)?;
+            if bn.saturating_add(num_confirmations.into()) <= current_block {
+                let success = receipt.status == Some(1u64.into());
+                let (response, receiver) = tokio::sync::oneshot::channel();
+                if db_sender
+                    .send(DatabaseOperation::MarkClaimCompleted {
+                        tx_hash: *pending_hash,
+                        success,
+                        failed_hashes: pending_txs
+                            .iter()
+                            .enumerate()
+                            .flat_map(|(j, (hash, _))| if i != j { Some(*hash) } else { None })
+                            .collect(),
+                        response,
+                    })
+                    .await
+                    .is_err()
+                {
+                    tracing::debug!("The database has been shut down. Stopping the claim sender.");
+                    return Ok(WaitPendingResult::Stop);
+                }
+                if receiver.await.is_err() {
+                    metrics.warnings_total.inc();
+                    tracing::warn!("The database has been shut down. Stopping the claim sender.");
+                    return Ok(WaitPendingResult::Stop);
+                }
+                if success {
+                    metrics.num_claims.inc();
+                    tracing::info!(
+                        "Withdrawal with event index {event_index} claimed in transaction \
+                         {pending_hash:#x}."
+                    );
+                } else {
+                    metrics.errors_total.inc();
+                    tracing::error!(
+                        "Claim transaction {pending_hash:#x} for withdrawal with event index \
+                         {event_index} reverted. The claim is marked as failed and will not be \
+                         attempted again."
+                    );
+                }
+                // Transaction is confirmed, update the nonce for the next claim.
+                client.next_nonce += 1.into();
+                result = WaitPendingResult::Confirmed;
+                break 'outer;
+            } else {
+                tracing::debug!(
+                    "Claim transaction {pending_hash:#x} is in block {bn}, but not yet confirmed."
+                );
+            }
+        }
+    }
+    *pending = None;
+    Ok(result)
+}
+
+/// Escalate the pending claim, or if there is none send a new claim if there is
+/// a withdrawal to claim.
+/// Return whether the process should be stopped.
+async fn send_claim<M: Middleware, S: Signer>(
+    metrics: &crate::metrics::Metrics,
+    client: &mut ClaimClient<M, S>,
+    db_sender: &tokio::sync::mpsc::Sender<DatabaseOperation>,
+    pending: &mut Option<PendingClaim>,
+    from_event_index: &mut u64,
+) -> Result<bool, EthereumSenderError<M>>
+where
+    S::Error: 'static,
+{
+    let (tx_hash, raw_tx, event_index) = if let Some(PendingClaim {
+        event_index,
+        pending_txs,
+    }) = pending
+    {
+        let Some((_, tx)) = pending_txs.last() else
+        {
+            return Ok(false);
+        };
+        match escalate_transaction(
+            &*client.root_manager.client(),
+            &client.signer,
+            tx,
+            client.max_gas_price,
+        )
+        .await?
+        {
+            EscalationResult::GasTooHigh { new_gas_price } => {
+                metrics.warnings_total.inc();
+                tracing::warn!(
+                    "Escalating claim would lead to transaction price that is too high \
+                     {new_gas_price} > {}. Waiting for next iteration.",
+                    client.max_gas_price,
+                );
+                return Ok(false);
+            }
+            EscalationResult::Escalated { tx_hash, raw_tx } => {
+                tracing::debug!("Sending escalation claim transaction with hash {tx_hash:#x}.");
+                pending_txs.push((tx_hash, raw_tx.clone()));
+                (tx_hash, raw_tx, *event_index)
+            }
+        }
+    } else {
+        let (response, receiver) = tokio::sync::oneshot::channel();
+        if db_sender
+            .send(DatabaseOperation::GetClaimableWithdrawal {
+                from_event_index: *from_event_index,
+                response,
+            })
+            .await
+            .is_err()
+        {
+            tracing::info!("The database has been shut down. Stopping the claim sender.");
+            return Ok(true);
+        }
+        let Ok(claimable) = receiver.await else {
+            metrics.warnings_total.inc();
+            tracing::warn!("The database has been shut down. Stopping the claim sender.");
+            return Ok(true);
+        };
+        let Some(claim) = claimable else {
+            tracing::debug!("No withdrawals to claim. Doing nothing.");
+            return Ok(false);
+        };
+        let (params, proof) = match make_withdraw_params(&claim) {
+            Ok(x) => x,
+            Err(e) => {
+                metrics.errors_total.inc();
+                tracing::error!(
+                    "Unable to claim withdrawal with event index {}. Skipping it: {e:#}",
+                    claim.event.event_index
+                );
+                *from_event_index = claim.event.event_index + 1;
+                return Ok(false);
+            }
+        };
+        match client.make_claim_transaction(&claim, params, proof).await? {
+            MakeClaimResult::Claim {
+                tx_hash,
+                raw_tx,
+                event_index,
+            } => {
+                *pending = Some(PendingClaim {
+                    event_index,
+                    pending_txs: vec![(tx_hash, raw_tx.clone())],
+                });
+                (tx_hash, raw_tx, event_index)
+            }
+            MakeClaimResult::GasTooHigh {
+                max_gas_price,
+                current_gas_price,
+            } => {
+                metrics.warnings_total.inc();
+                tracing::warn!(
+                    "Ethereum transaction price is too high {current_gas_price} > \
+                     {max_gas_price}. Not claiming withdrawals in this iteration."
+                );
+                return Ok(false);
+            }
+        }
+    };
+    // Store the transaction before sending it.
+    let (response, receiver) = tokio::sync::oneshot::channel();
+    if db_sender
+        .send(DatabaseOperation::StoreClaimTransaction {
+            tx_hash,
+            tx: raw_tx.clone(),
+            event_index,
+            response,
+        })
+        .await
+        .is_err()
+    {
+        tracing::info!("The database has been shut down. Stopping the claim sender.");
+        return Ok(true);
+    }
+    if receiver.await.is_err() {
+        metrics.warnings_total.inc();
+        tracing::warn!("The database has been shut down. Stopping the claim sender.");
+        return Ok(true);
+    }
+    tracing::debug!(
+        "Sending claim transaction with hash {tx_hash:#x} for withdrawal with event index \
+         {event_index}."
+    );
+    client
+        .root_manager
+        .client()
+        .send_raw_transaction(raw_tx)
+        .await
+        .map_err(EthereumSenderError::Retryable)?;
+    metrics.sent_ethereum_transactions.inc();
+    Ok(false)
+}
@@ -1,11 +1,17 @@
 //! This module deal with interaction with the bridge manager contract
 //! on Concordium. It deals with parsing events emitted by the contract,
 //! and sending updates to it.
-use crate::db;
+use crate::{
+    balance::SendGate,
+    concordium_node::{ConcordiumNode, FinalizedBlocks},
+    config::Tunables,
+    db,
+};
 use anyhow::Context;
+use bridge_types::{error_codes, token_admin_events};
 use concordium_rust_sdk::{
     cis2::{self, TokenId},
-    common::types::{Amount, TransactionTime},
+    common::types::{Amount, TransactionSignature, TransactionTime},
     endpoints::QueryError,
     id::types::AccountAddress,
     smart_contracts::common as contracts_common,
@@ -16,49 +22,40 @@ use concordium_rust_sdk::{
             ContractContext, InvokeContractResult, OwnedParameter, OwnedReceiveName,
         },
         transactions::{self, BlockItem, EncodedPayload, UpdateContractPayload},
-        AbsoluteBlockHeight, Address, BlockItemSummary, ContractAddress, Energy, Nonce,
-        RejectReason, WalletAccount,
+        AbsoluteBlockHeight, BlockItemSummary, ContractAddress, Energy, Nonce, RejectReason,
+        WalletAccount,
     },
-    v2::{self, BlockIdentifier},
+    v2::{self, BlockIdentifier, IntoBlockIdentifier},
 };
-use futures::{StreamExt, TryStreamExt};
-use std::sync::Arc;
+use futures::StreamExt;
+use std::{collections::BTreeSet, sync::Arc};
+use tracing::Instrument;
 
-/// Type of Ethereum addresses.
-type EthAddress = [u8; 20];
+pub use bridge_types::{
+    AdminTransferEvent, ClaimRequestedEvent, DepositHook, EthAddress, GrantRoleEvent,
+    ProposalEvent, RevokeRoleEvent, Roles, SetBlockedEvent, SetFeeEvent, SetGovernanceDelayEvent,
+    SetPausedEvent, SetTreasurerEvent, SkipOperations, TokenMapEvent, TokenMapOperation,
+    UpgradeEvent,
+};
 
-#[derive(contracts_common::Serialize, Debug)]
 /// Mint new token in response to a deposit on Ethereum.
-pub struct DepositOperation {
-    /// Id of the event on Ethereum that emitted the deposit.
-    pub id: u64,
-    /// The address to which the deposit should be made.
-    pub user: Address,
-    /// Address of the root token to be mapped.
-    pub root: EthAddress,
-    /// Amount to be minted.
-    pub amount: cis2::TokenAmount,
-    /// Token ID of the Concordium token to be minted.
-    pub token_id: TokenId,
-}
-
-#[derive(contracts_common::Serialize, Debug)]
-pub struct TokenMapOperation {
-    /// Id of the operation emitted by Ethereum StateSender.
-    pub id: u64,
-    /// Address of the origin token on Ethereum.
-    pub root: EthAddress,
-    /// Address of the mapped token on Concordium.
-    pub child: ContractAddress,
-}
-#[derive(contracts_common::Serialize, Debug)]
+pub type DepositOperation = bridge_types::DepositOperation<cis2::TokenAmount, TokenId>;
 /// State updates supported by the Bridge Manager contract.
-pub enum StateUpdate {
-    /// Deposit an amount of a mapped token.
-    Deposit(DepositOperation),
-    /// Add a new token mapping.
-    TokenMap(TokenMapOperation),
-}
+pub type StateUpdate = bridge_types::StateUpdate<cis2::TokenAmount, TokenId>;
+/// A deposit was made on Concordium.
+pub type DepositEvent = bridge_types::DepositEvent<cis2::TokenAmount, TokenId>;
+/// A withdrawal was initiated on Concordium.
+pub type WithdrawEvent = bridge_types::WithdrawEvent<cis2::TokenAmount, TokenId>;
+/// All possible events emitted by the bridge.
+pub type BridgeEvent = bridge_types::BridgeEvent<cis2::TokenAmount, TokenId>;
+/// An admin action of the Bridge Manager contract.
+pub type GovernanceAction = bridge_types::GovernanceAction<cis2::TokenAmount>;
+/// An admin action was proposed.
+pub type ProposalCreatedEvent = bridge_types::ProposalCreatedEvent<cis2::TokenAmount>;
+/// The fee for withdrawing a token was changed.
+pub type SetTokenFeeEvent = bridge_types::SetTokenFeeEvent<cis2::TokenAmount>;
+/// Tokens were minted or burned to correct the supply after an incident.
+pub type CorrectionEvent = bridge_types::CorrectionEvent<cis2::TokenAmount>;
 
 #[derive(Debug, Clone)]
 /// A wrapper around [`BridgeManagerClient`] that adds ability to send
@@ -68,10 +65,12 @@ pub enum StateUpdate {
 /// The nonce is created when the client is created, and it is updated by
 /// [`make_state_update_tx`](BridgeManager::make_state_update_tx).
 /// This means that the [`BridgeManager`] assumes exclusive access to the
-/// account.
-pub struct BridgeManager {
-    pub client: BridgeManagerClient,
-    sender: std::sync::Arc<WalletAccount>,
+/// account, unless it is constructed with [`shadow`](BridgeManager::shadow).
+pub struct BridgeManager<C = v2::Client> {
+    pub client: BridgeManagerClient<C>,
+    /// Keys of the sender account. If `None` transactions are not signed, and
+    /// can never be sent.
+    sender: Option<std::sync::Arc<WalletAccount>>,
     /// Maximum NRG allowed for state updates on Concordium.
     pub max_energy: Energy,
     /// Next nonce to be used for sending the transaction.
@@ -81,9 +80,6 @@ pub struct BridgeManager {
 /// Maximum allowed dry run energy.
 const ALLOWED_DRY_RUN_NRG: Energy = Energy { energy: 1_000_000 };
 
-// TODO: See how to keep this more easily in sync with the contracts.
-const DUPLICATE_OPERATION: i32 = -10;
-
 #[derive(Debug, Clone)]
 /// Return value from dry-running a transaction.
 pub enum DryRunReturn {
@@ -99,15 +95,15 @@ pub enum DryRunReturn {
     OtherError { reason: RejectReason },
 }
 
-impl BridgeManager {
+impl<C: ConcordiumNode> BridgeManager<C> {
     /// Construct a new [`Self`].
     ///
     /// If the `start_nonce` is not supplied it will be queried
-    /// using [`get_next_account_sequence_number`](v2::Client::
+    /// using [`get_next_account_sequence_number`](ConcordiumNode::
     /// get_next_account_sequence_number). In such a case, if there are
     /// non-finalized transactions the invocation will fail.
     pub async fn new(
-        mut client: BridgeManagerClient,
+        mut client: BridgeManagerClient<C>,
         sender: WalletAccount,
         start_nonce: Option<Nonce>,
         max_energy: Energy,
@@ -127,12 +123,46 @@ impl BridgeManager {
         };
         Ok(Self {
             client,
-            sender: Arc::new(sender),
+            sender: Some(Arc::new(sender)),
+            next_nonce,
+            max_energy,
+        })
+    }
+
+    /// Construct a [`Self`] for a relayer in shadow mode. No keys are needed
+    /// since the transactions are only recorded. They are constructed exactly
+    /// as for sending from the
+    /// [`sender_account`](BridgeManagerClient::sender_account), with its
+    /// current nonce, but with no signatures.
+    pub async fn shadow(
+        mut client: BridgeManagerClient<C>,
+        max_energy: Energy,
+    ) -> anyhow::Result<Self> {
+        let sender_account = client.sender_account;
+        let next_nonce = client
+            .client
+            .get_next_account_sequence_number(&sender_account)
+            .await?
+            .nonce;
+        Ok(Self {
+            client,
+            sender: None,
             next_nonce,
             max_energy,
         })
     }
 
+    /// Use the new tunables for the next state updates.
+    pub fn update_tunables(&mut self, tunables: &Tunables) {
+        if self.max_energy != tunables.max_energy {
+            tracing::info!(
+                "Allowing up to {}NRG for Concordium transactions.",
+                tunables.max_energy
+            );
+            self.max_energy = tunables.max_energy;
+        }
+    }
+
     /// Make the payload corresponding to the desired [`StateUpdate`].
     fn make_payload(&self, update: &StateUpdate) -> UpdateContractPayload {
         UpdateContractPayload {
@@ -149,18 +179,22 @@ impl BridgeManager {
     pub async fn check_operation_used(
         &mut self,
         id: u64,
-        bi: impl v2::IntoBlockIdentifier,
+        bi: impl IntoBlockIdentifier,
     ) -> anyhow::Result<bool> {
         let ctx = ContractContext {
-            invoker: Some(self.sender.address.into()),
+            invoker: Some(self.client.sender_account.into()),
             contract: self.client.contract,
             amount: Amount::from_micro_ccd(0),
             method: OwnedReceiveName::new_unchecked("bridge-manager.checkOperationUsed".into()),
             parameter: OwnedParameter::new_unchecked(id.to_le_bytes().into()),
             energy: 10_000.into(),
         };
-        let result = self.client.client.invoke_instance(bi, &ctx).await?;
-        match result.response {
+        let result = self
+            .client
+            .client
+            .invoke_instance(bi.into_block_identifier(), &ctx)
+            .await?;
+        match result {
             InvokeContractResult::Success { return_value, .. } => {
                 let rv = return_value.context("Unexpected response.")?.value;
                 let Some(&first) = rv.first() else {
@@ -174,28 +208,98 @@ impl BridgeManager {
         }
     }
 
+    /// Dry run a state update in the last finalized block, retrying with
+    /// exponential backoff while the node cannot be queried.
+    async fn dry_run_with_retries(&mut self, update: &StateUpdate) -> anyhow::Result<DryRunReturn> {
+        let mut iter_num = 0;
+        loop {
+            match self
+                .dry_run_state_update(update, BlockIdentifier::LastFinal)
+                .await
+            {
+                Ok(v) => return Ok(v),
+                Err(e) => {
+                    tracing::warn!("Unable to dry run state update due to: {e:#}");
+                }
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(1000 << iter_num)).await;
+            iter_num += 1;
+            anyhow::ensure!(
+                iter_num <= 6,
+                "Too many retries trying to run state update."
+            );
+        }
+    }
+
+    /// The energy to reserve for a transaction that used the given energy
+    /// when dry run.
+    fn execution_energy(used_energy: Energy) -> Energy {
+        // Add an extra 1000 NRG to prevent race conditions in case the cost changes
+        // slightly due to withdrawals.
+        used_energy.energy.saturating_add(1000).into()
+    }
+
+    /// Make a transaction for a deposit, like
+    /// [`make_state_update_tx`](BridgeManager::make_state_update_tx). A deposit
+    /// with a hook is made to the `fallback` account instead, without the hook,
+    /// if dry running it fails or exceeds the maximum energy, e.g., because the
+    /// receiving contract does not exist or its hook is too expensive.
+    pub async fn make_deposit_tx(
+        &mut self,
+        mut deposit: DepositOperation,
+        fallback: AccountAddress,
+    ) -> anyhow::Result<Option<BlockItem<EncodedPayload>>> {
+        if deposit.hook.is_some() {
+            let update = StateUpdate::Deposit(deposit.clone());
+            let reason = match self.dry_run_with_retries(&update).await? {
+                DryRunReturn::Success { used_energy, .. } => {
+                    if Self::execution_energy(used_energy) <= self.max_energy {
+                        return self.make_state_update_tx(&update).await;
+                    }
+                    "the estimated energy exceeds the maximum allowed".to_string()
+                }
+                DryRunReturn::DuplicateOperation => return Ok(None),
+                DryRunReturn::OtherError { reason } => format!("{reason:?}"),
+            };
+            tracing::warn!(
+                "Deposit {} to contract {} cannot be executed ({reason}). Depositing to {fallback} \
+                 instead.",
+                deposit.id,
+                deposit.user,
+            );
+            deposit.user = fallback.into();
+            deposit.hook = None;
+        }
+        self.make_state_update_tx(&StateUpdate::Deposit(deposit))
+            .await
+    }
+
     /// Dry run a state update transaction in the provided block.
     pub async fn dry_run_state_update(
         &mut self,
         update: &StateUpdate,
-        bi: impl v2::IntoBlockIdentifier,
+        bi: impl IntoBlockIdentifier,
     ) -> anyhow::Result<DryRunReturn> {
         let payload = self.make_payload(update);
-        log::info!("{:#?}", update);
+        tracing::info!("{:#?}", update);
         let ctx = ContractContext::new_from_payload(
-            self.sender.address,
+            self.client.sender_account,
             ALLOWED_DRY_RUN_NRG,
             payload.clone(),
         );
-        let result = self.client.client.invoke_instance(bi, &ctx).await?;
-        match result.response {
+        let result = self
+            .client
+            .client
+            .invoke_instance(bi.into_block_identifier(), &ctx)
+            .await?;
+        match result {
             InvokeContractResult::Success { used_energy, .. } => Ok(DryRunReturn::Success {
                 used_energy,
                 payload,
             }),
             InvokeContractResult::Failure { reason, .. } => {
                 if let RejectReason::RejectedReceive { reject_reason, .. } = reason {
-                    if reject_reason == DUPLICATE_OPERATION {
+                    if reject_reason == error_codes::OPERATION_ALREADY_PROCESSED {
                         Ok(DryRunReturn::DuplicateOperation)
                     } else {
                         Ok(DryRunReturn::OtherError { reason })
@@ -218,50 +322,31 @@ impl BridgeManager {
     ///
     /// Dry run is attempted in the last finalized block.
     ///
-    /// This **does not** send the transaction, but does dry run the update.
+    /// This **does not** send the transaction, but does dry run the update. If
+    /// there are no keys the transaction is not signed.
     pub async fn make_state_update_tx(
         &mut self,
         update: &StateUpdate,
     ) -> anyhow::Result<Option<BlockItem<EncodedPayload>>> {
-        let mut iter_num = 0;
-        let (mut execution_energy, payload) = loop {
-            match self
-                .dry_run_state_update(update, BlockIdentifier::LastFinal)
-                .await
-            {
-                Ok(v) => match v {
-                    DryRunReturn::Success {
-                        used_energy,
-                        payload,
-                    } => break (used_energy, payload),
-                    DryRunReturn::DuplicateOperation => {
-                        return Ok(None);
-                    }
-                    DryRunReturn::OtherError { reason } => {
-                        log::error!(
-                            "Unexpected response from dry running state update. This is a \
-                             configuration error: {reason:#?}"
-                        );
-                        anyhow::bail!(
-                            "Unexpected response from dry running state update. This is a \
-                             configuration error: {reason:#?}"
-                        );
-                    }
-                },
-                Err(e) => {
-                    log::warn!("Unable to dry run state update due to: {e:#}");
-                }
+        let (execution_energy, payload) = match self.dry_run_with_retries(update).await? {
+            DryRunReturn::Success {
+                used_energy,
+                payload,
+            } => (Self::execution_energy(used_energy), payload),
+            DryRunReturn::DuplicateOperation => {
+                return Ok(None);
+            }
+            DryRunReturn::OtherError { reason } => {
+                tracing::error!(
+                    "Unexpected response from dry running state update. This is a \
+                     configuration error: {reason:#?}"
+                );
+                anyhow::bail!(
+                    "Unexpected response from dry running state update. This is a \
+                     configuration error: {reason:#?}"
+                );
             }
-            tokio::time::sleep(std::time::Duration::from_millis(1000 << iter_num)).await;
-            iter_num += 1;
-            anyhow::ensure!(
-                iter_num <= 6,
-                "Too many retries trying to run state update."
-            );
         };
-        // Add an extra 1000 NRG to prevent race conditions in case the cost changes
-        // slightly due to withdrawals.
-        execution_energy = execution_energy.energy.saturating_add(1000).into();
         anyhow::ensure!(
             execution_energy <= self.max_energy,
             "Estimated energy exceeds maximum allowed"
@@ -273,131 +358,81 @@ impl BridgeManager {
         // increase the nonce.
         self.next_nonce.next_mut();
 
-        let tx = transactions::send::update_contract(
-            &*self.sender,
-            self.sender.address,
-            nonce,
-            expiry,
-            payload,
-            execution_energy,
-        );
+        let sender_account = self.client.sender_account;
+        let tx = match &self.sender {
+            Some(sender) => transactions::send::update_contract(
+                &**sender,
+                sender_account,
+                nonce,
+                expiry,
+                payload,
+                execution_energy,
+            ),
+            None => {
+                let pre_tx = transactions::construct::update_contract(
+                    1,
+                    sender_account,
+                    nonce,
+                    expiry,
+                    payload,
+                    execution_energy,
+                );
+                transactions::AccountTransaction {
+                    signature: TransactionSignature {
+                        signatures: std::collections::BTreeMap::new(),
+                    },
+                    header: pre_tx.header,
+                    payload: pre_tx.encoded,
+                }
+            }
+        };
         Ok(Some(tx.into()))
     }
 }
 
-#[derive(contracts_common::Serialize, PartialEq, Eq, Debug, Copy, Clone)]
-/// Roles that can be set for the `BridgeManager` contracg
-pub enum Roles {
-    /// Admin, can set other roles, including admins.
-    Admin,
-    /// Mapper can add and remove token mappings.
-    Mapper,
-    /// `StateSyncher` can send deposits. This role is played by the relayer.
-    StateSyncer,
-}
-
-#[derive(Debug, PartialEq, Eq, contracts_common::Serialize)]
-/// A new token was mapped.
-pub struct TokenMapEvent {
-    /// Id of the operation emitted by Ethereum. Used to deduplicate them.
-    pub id: u64,
-    /// Address of the original token on Ethereum.
-    pub root: EthAddress,
-    /// Address of the mapped token on Concordium.
-    pub child: ContractAddress,
-}
-
-#[derive(Debug, PartialEq, Eq, contracts_common::Serialize)]
-pub struct DepositEvent {
-    /// Id of the operation emitted by Ethereum. Used to deduplicate them.
-    pub id: u64,
-    /// Address of the child token that is to be minted.
-    pub contract: ContractAddress,
-    /// Amount to be minted.
-    pub amount: cis2::TokenAmount,
-    /// Id of the token on Concordium.
-    pub token_id: TokenId,
-}
-
-#[derive(Debug, PartialEq, Eq, contracts_common::Serialize)]
-pub struct WithdrawEvent {
-    /// Index of the event emitted by the BridgeManager.
-    pub event_index: u64,
-    /// Address of the child token that is to be withdrawn.
+#[derive(Debug, Clone)]
+/// An event logged by the bridge manager or by a mapped token that records an
+/// administrative action, e.g., a role change, a fee change or an upgrade.
+pub struct AdminEvent {
+    /// The contract that logged the event.
     pub contract: ContractAddress,
-    /// Amount to be withdrawn.
-    pub amount: cis2::TokenAmount,
-    /// Address that originated the withdrawal.
-    pub ccd_address: Address,
-    /// The recepient of the withdrawal on Ethereum.
-    pub eth_address: EthAddress,
-    /// Id of the token on Concordium.
-    pub token_id: TokenId,
-}
-
-#[derive(Debug, PartialEq, Eq, contracts_common::Serialize)]
-/// An event emitted when a role has been granted.
-pub struct GrantRoleEvent {
-    /// Address that has been granted the role.
-    address: Address,
-    /// The role that has been granted.
-    role: Roles,
-}
-
-#[derive(Debug, PartialEq, Eq, contracts_common::Serialize)]
-/// An event emitted when a role has been revoked/removed.
-pub struct RevokeRoleEvent {
-    /// Address that has been revoked the role
-    address: Address,
-    /// The role that has been revoked.
-    role: Roles,
-}
-
-#[derive(Debug, PartialEq, Eq, contracts_common::Serialize)]
-/// All possible events emitted by the bridge.
-pub enum BridgeEvent {
-    TokenMap(TokenMapEvent),
-    Deposit(DepositEvent),
-    Withdraw(WithdrawEvent),
-    GrantRole(GrantRoleEvent),
-    RevokeRole(RevokeRoleEvent),
-}
-
-impl BridgeEvent {
-    /// Extract an event index if possible. Event index
-    /// is only emitted by [`WithdrawEvent`].
-    pub fn event_index(&self) -> Option<u64> {
-        match self {
-            BridgeEvent::TokenMap(_) => None,
-            BridgeEvent::Deposit(_) => None,
-            BridgeEvent::Withdraw(we) => Some(we.event_index),
-            BridgeEvent::GrantRole(_) => None,
-            BridgeEvent::RevokeRole(_) => None,
-        }
-    }
+    /// Position of the event among all the events logged in the transaction.
+    pub log_index: u64,
+    /// The name of the action, e.g., `set_withdraw_fee`.
+    pub action: &'static str,
+    /// The event, exactly as logged by the contract.
+    pub data: Vec<u8>,
 }
 
 #[derive(Clone, Debug)]
 /// A client for querying and looking at events of the bridge manager contract.
-pub struct BridgeManagerClient {
-    pub client: v2::Client,
+pub struct BridgeManagerClient<C = v2::Client> {
+    pub client: C,
     pub sender_account: AccountAddress,
     contract: ContractAddress,
+    /// The tokens mapped by the bridge manager, whose administrative events
+    /// are extracted as well.
+    tokens: BTreeSet<ContractAddress>,
 }
 
-impl BridgeManagerClient {
-    pub fn new(
-        client: v2::Client,
-        sender_account: AccountAddress,
-        contract: ContractAddress,
-    ) -> Self {
+impl<C> BridgeManagerClient<C> {
+    pub fn new(client: C, sender_account: AccountAddress, contract: ContractAddress) -> Self {
         Self {
             client,
             sender_account,
             contract,
+            tokens: BTreeSet::new(),
         }
     }
 
+    /// Also extract the administrative events of the given tokens. Tokens
+    /// mapped in the events extracted by
+    /// [`extract_events`](Self::extract_events) are added automatically.
+    pub fn with_tokens(mut self, tokens: impl IntoIterator<Item = ContractAddress>) -> Self {
+        self.tokens.extend(tokens);
+        self
+    }
+
     /// Get all the bridge manager event logs.
     pub fn extract_events(
         &mut self,
@@ -412,6 +447,9 @@ impl BridgeManagerClient {
                         // Parsing should never fail. If it does that indicates a configuration
                         // error.
                         let event = contracts_common::from_bytes(log.as_ref())?;
+                        if let BridgeEvent::TokenMap(tm) = &event {
+                            self.tokens.insert(tm.child);
+                        }
                         out.push(event)
                     }
                 }
@@ -421,6 +459,44 @@ impl BridgeManagerClient {
             Ok(Vec::new())
         }
     }
+
+    /// Get the administrative events of the bridge manager and of the mapped
+    /// tokens. The events of a token that are not administrative, e.g., CIS-2
+    /// events, are skipped.
+    pub fn extract_admin_events(
+        &self,
+        summary: &BlockItemSummary,
+    ) -> anyhow::Result<Vec<AdminEvent>> {
+        let mut out = Vec::new();
+        let Some(logs) = summary.contract_update_logs() else {
+            return Ok(out);
+        };
+        let mut log_index = 0;
+        for (ca, section_logs) in logs {
+            for log in section_logs {
+                let action = if ca == self.contract {
+                    let event: BridgeEvent = contracts_common::from_bytes(log.as_ref())?;
+                    event.admin_action()
+                } else if self.tokens.contains(&ca) {
+                    log.as_ref()
+                        .first()
+                        .and_then(|tag| token_admin_events::admin_action(*tag))
+                } else {
+                    None
+                };
+                if let Some(action) = action {
+                    out.push(AdminEvent {
+                        contract: ca,
+                        log_index,
+                        action,
+                        data: log.as_ref().to_vec(),
+                    });
+                }
+                log_index += 1;
+            }
+        }
+        Ok(out)
+    }
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -436,10 +512,10 @@ pub enum NodeError {
     Internal(anyhow::Error),
 }
 
-pub async fn listen_concordium(
+pub async fn listen_concordium<C: ConcordiumNode>(
     metrics: crate::metrics::Metrics,
     // The client used to query the chain.
-    mut bridge_manager: BridgeManagerClient,
+    mut bridge_manager: BridgeManagerClient<C>,
     // A channel used to insert into the database.
     sender: tokio::sync::mpsc::Sender<db::DatabaseOperation>,
     // Height at which to start querying.
@@ -469,18 +545,18 @@ pub async fn listen_concordium(
         }
         match res {
             Ok(()) => {
-                log::info!("Terminated listening for new Concordium events.");
+                tracing::info!("Terminated listening for new Concordium events.");
                 return Ok(());
             }
             Err(e) => match e {
                 NodeError::Timeout => {
                     retry_attempt += 1;
                     if retry_attempt > 6 {
-                        log::error!("Too many failures attempting to reconnect. Aborting.");
+                        tracing::error!("Too many failures attempting to reconnect. Aborting.");
                         anyhow::bail!("Too many failures attempting to reconnect. Aborting.");
                     }
                     let delay = std::time::Duration::from_secs(5 << retry_attempt);
-                    log::warn!(
+                    tracing::warn!(
                         "Querying the node timed out. Will attempt again in {} seconds..",
                         delay.as_secs()
                     );
@@ -489,11 +565,11 @@ pub async fn listen_concordium(
                 NodeError::QueryError(e) => {
                     retry_attempt += 1;
                     if retry_attempt > 6 {
-                        log::error!("Too many failures attempting to reconnect. Aborting.");
+                        tracing::error!("Too many failures attempting to reconnect. Aborting.");
                         anyhow::bail!("Too many failures attempting to reconnect. Aborting.");
                     }
                     let delay = std::time::Duration::from_secs(5 << retry_attempt);
-                    log::warn!(
+                    tracing::warn!(
                         "Querying the node failed due to {:#}. Will attempt again in {} seconds.",
                         e,
                         delay.as_secs()
@@ -501,7 +577,9 @@ pub async fn listen_concordium(
                     tokio::time::sleep(delay).await;
                 }
                 NodeError::Internal(e) => {
-                    log::error!("Internal configuration error: {e}. Terminating the query task.");
+                    tracing::error!(
+                        "Internal configuration error: {e}. Terminating the query task."
+                    );
                     return Err(e);
                 }
             },
@@ -511,10 +589,10 @@ pub async fn listen_concordium(
 
 /// Return Err if querying the node failed.
 /// Return Ok(()) if the channel to the database was closed.
-async fn listen_concordium_worker(
+async fn listen_concordium_worker<C: ConcordiumNode>(
     metrics: &crate::metrics::Metrics,
     // The client used to query the chain.
-    bridge_manager: &mut BridgeManagerClient,
+    bridge_manager: &mut BridgeManagerClient<C>,
     // A channel used to insert into the database.
     sender: &tokio::sync::mpsc::Sender<db::DatabaseOperation>,
     // Height at which to start querying.
@@ -540,41 +618,60 @@ async fn listen_concordium_worker(
             // A future to query the block at the given hash.
             let poller = async move {
                 let binfo = node.get_block_info(fb.block_hash).await?;
-                let events = if binfo.response.transaction_count == 0 {
+                let events = if binfo.transaction_count == 0 {
                     Vec::new()
                 } else {
-                    node.get_block_transaction_events(fb.block_hash)
-                        .await?
-                        .response
-                        .try_collect()
-                        .await?
+                    node.get_block_transaction_events(fb.block_hash).await?
                 };
-                Ok::<(BlockInfo, Vec<BlockItemSummary>), QueryError>((binfo.response, events))
+                Ok::<(BlockInfo, Vec<BlockItemSummary>), QueryError>((binfo, events))
             };
             futures.push_back(poller);
         }
 
         while let Some(result) = futures.next().await {
             let (block, summaries) = result?;
-            log::debug!(
+            tracing::debug!(
                 "Processing Concordium block {} at height {}",
                 block.block_hash,
                 block.block_height
             );
             let mut transaction_events = Vec::new();
+            let mut admin_events = Vec::new();
             for summary in summaries {
                 let events = bridge_manager
                     .extract_events(&summary)
                     .map_err(NodeError::Internal)?;
+                // Extracted after the events, so that tokens mapped in the same
+                // transaction are included.
+                let admins = bridge_manager
+                    .extract_admin_events(&summary)
+                    .map_err(NodeError::Internal)?;
+                if !admins.is_empty() {
+                    admin_events.push((summary.hash, admins));
+                }
+                let num_deposits = events
+                    .iter()
+                    .filter(|e| matches!(e, BridgeEvent::Deposit(_)))
+                    .count() as u64;
+                if let Some(energy) = summary.energy_cost.energy.checked_div(num_deposits) {
+                    metrics.deposit_energy.set(energy);
+                }
                 if !events.is_empty() {
-                    transaction_events.push((summary.hash, events));
+                    // Each transaction starts a new trace, separate from the watcher.
+                    let span = tracing::info_span!(
+                        parent: None,
+                        "concordium_transaction",
+                        tx_hash = %summary.hash,
+                        block_height = block.block_height.height,
+                    );
+                    transaction_events.push((summary.hash, events, span));
                 }
                 // Also check for any other transactions from the sender account.
                 // So we can mark transactions we have sent as failed.
                 if summary.is_rejected_account_transaction().is_some() {
                     if let Some(acc) = summary.sender_account() {
                         if acc.is_alias(&bridge_manager.sender_account) {
-                            log::warn!(
+                            tracing::warn!(
                                 "Discovered a failed transaction {} sent by Concordium relayer \
                                  account.",
                                 summary.hash
@@ -587,7 +684,9 @@ async fn listen_concordium_worker(
                                 .await
                                 .is_err()
                             {
-                                log::info!("The channel to the database writer has been closed.");
+                                tracing::info!(
+                                    "The channel to the database writer has been closed."
+                                );
                                 return Ok(());
                             }
                         }
@@ -601,11 +700,12 @@ async fn listen_concordium_worker(
                 .send(db::DatabaseOperation::ConcordiumEvents {
                     block,
                     transaction_events,
+                    admin_events,
                 })
                 .await
                 .is_err()
             {
-                log::info!("The channel to the database writer has been closed.");
+                tracing::info!("The channel to the database writer has been closed.");
                 return Ok(());
             }
             *height = height.next();
@@ -624,10 +724,13 @@ async fn listen_concordium_worker(
 ///
 /// The transactions in the channel should be in increasing order of nonces,
 /// otherwise sending will fail.
-pub async fn concordium_tx_sender(
+pub async fn concordium_tx_sender<C: ConcordiumNode>(
     metrics: crate::metrics::Metrics,
-    mut client: v2::Client,
-    mut receiver: tokio::sync::mpsc::Receiver<BlockItem<EncodedPayload>>,
+    mut client: C,
+    // Transactions to send, and the spans in which to send them.
+    mut receiver: tokio::sync::mpsc::Receiver<(BlockItem<EncodedPayload>, tracing::Span)>,
+    // Closed while the balance of the sender account is too low.
+    mut gate: SendGate,
     // Flag to signal stopping the task gracefully.
     mut stop: tokio::sync::watch::Receiver<()>,
 ) -> anyhow::Result<()> {
@@ -639,18 +742,18 @@ pub async fn concordium_tx_sender(
     // if submission succeeded.
     let process_response = |hash, response: v2::RPCResult<TransactionHash>| match response {
         Ok(hash) => {
-            log::info!("Transaction {hash} sent to the Concordium node.");
+            tracing::info!("Transaction {hash} sent to the Concordium node.");
             metrics.sent_concordium_transactions.inc();
             Ok(false)
         }
         Err(e) => {
             if e.is_duplicate() {
                 metrics.warnings_total.inc();
-                log::warn!("Transaction {hash} already exists at the node.");
+                tracing::warn!("Transaction {hash} already exists at the node.");
                 Ok(false)
             } else if e.is_invalid_argument() {
                 metrics.errors_total.inc();
-                log::error!(
+                tracing::error!(
                     "Transaction {hash} is not valid for the current state of the node: {e:#}. \
                      Aborting."
                 );
@@ -660,23 +763,50 @@ pub async fn concordium_tx_sender(
                 )
             } else {
                 metrics.warnings_total.inc();
-                log::warn!("Sending transaction to Concordium failed due to {e:#}. Will retry.");
+                tracing::warn!(
+                    "Sending transaction to Concordium failed due to {e:#}. Will retry."
+                );
                 Ok(true)
             }
         }
     };
 
-    'outer: while let Some(bi) = tokio::select! {
-        // Make sure to process all events that are in the queue before shutting down.
-        // Thus prioritize getting things from the channel.
-        // This only works in combination with the fact that we shut down senders
-        // upon receving a kill signal, so the receiver will be drained eventually.
-        biased;
-            x = receiver.recv() => x,
-            _ = stop.changed() => None,
+    // Transactions received while sending is paused. They are sent in order
+    // before any new ones once sending resumes.
+    let mut paused = std::collections::VecDeque::new();
+    'outer: while let Some((bi, span)) = if let Some(item) = paused.pop_front() {
+        Some(item)
+    } else {
+        tokio::select! {
+            // Make sure to process all events that are in the queue before shutting down.
+            // Thus prioritize getting things from the channel.
+            // This only works in combination with the fact that we shut down senders
+            // upon receving a kill signal, so the receiver will be drained eventually.
+            biased;
+                x = receiver.recv() => x,
+                _ = stop.changed() => None,
+        }
     } {
+        if !gate.can_send() {
+            tracing::warn!(
+                "Not sending transactions to Concordium until the sender account is topped up."
+            );
+            // Keep receiving transactions so that the database is not blocked. The
+            // transactions are stored in the database, so if we stop before sending
+            // them they are resubmitted on restart.
+            paused.push_front((bi, span));
+            if gate
+                .wait_buffering(&mut receiver, &mut paused, &mut stop)
+                .await
+            {
+                break 'outer;
+            }
+            tracing::info!("Resuming sending of transactions to Concordium.");
+            continue;
+        }
         let hash = bi.hash();
-        let retry = process_response(hash, client.send_block_item(&bi).await)?;
+        let response = client.send_block_item(&bi).instrument(span.clone()).await;
+        let retry = span.in_scope(|| process_response(hash, response))?;
         if retry {
             // Retry at most 5 times, waiting at most 32 * 5 = 160s
             let mut success = false;
@@ -688,12 +818,14 @@ pub async fn concordium_tx_sender(
                 }
                 let delay = std::time::Duration::from_secs(5 << i);
                 metrics.warnings_total.inc();
-                log::warn!(
+                tracing::warn!(
+                    parent: &span,
                     "Waiting for {} seconds before resubmitting {hash}.",
                     delay.as_secs()
                 );
                 tokio::time::sleep(delay).await;
-                let retry = process_response(hash, client.send_block_item(&bi).await)?;
+                let response = client.send_block_item(&bi).instrument(span.clone()).await;
+                let retry = span.in_scope(|| process_response(hash, response))?;
                 if !retry {
                     success = true;
                     break;
@@ -702,6 +834,6 @@ pub async fn concordium_tx_sender(
             anyhow::ensure!(success, "Unable to reconnect in 6 attempts.");
         }
     }
-    log::info!("Concordium transaction sender terminated.");
+    tracing::info!("Concordium transaction sender terminated.");
     Ok(())
 }
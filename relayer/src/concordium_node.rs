@@ -0,0 +1,154 @@
+//! An abstraction over the queries the relayer makes to a Concordium node.
+//!
+//! In production this is implemented by [`v2::Client`]. The abstraction exists
+//! so that the relayer tasks can be run against the in-memory chain in the
+//! `simulator` module, which is enabled by the `test-utils` feature.
+use concordium_rust_sdk::{
+    endpoints::{QueryResult, RPCResult},
+    id::types::AccountAddress,
+    types::{
+        hashes::{BlockHash, TransactionHash},
+        queries::{AccountNonceResponse, BlockInfo},
+        smart_contracts::{ContractContext, InvokeContractResult},
+        transactions::{BlockItem, EncodedPayload},
+        AbsoluteBlockHeight, BlockItemSummary, TransactionStatus,
+    },
+    v2::{self, BlockIdentifier, FinalizedBlockInfo},
+};
+use futures::TryStreamExt;
+
+/// A stream of finalized blocks, in increasing order of height.
+#[async_trait::async_trait]
+pub trait FinalizedBlocks: Send {
+    /// Get the next chunk of at most `n`, and at least one, blocks. Wait no
+    /// more than the given duration for the first block. The boolean
+    /// signifies whether an error occurred while getting blocks, in which case
+    /// further calls will always yield an error.
+    ///
+    /// See [`v2::FinalizedBlocksStream::next_chunk_timeout`].
+    async fn next_chunk_timeout(
+        &mut self,
+        n: usize,
+        duration: std::time::Duration,
+    ) -> Result<(bool, Vec<FinalizedBlockInfo>), tokio::time::error::Elapsed>;
+}
+
+#[async_trait::async_trait]
+impl FinalizedBlocks for v2::FinalizedBlocksStream {
+    async fn next_chunk_timeout(
+        &mut self,
+        n: usize,
+        duration: std::time::Duration,
+    ) -> Result<(bool, Vec<FinalizedBlockInfo>), tokio::time::error::Elapsed> {
+        v2::FinalizedBlocksStream::next_chunk_timeout(self, n, duration).await
+    }
+}
+
+/// The queries to a Concordium node used by the relayer.
+#[async_trait::async_trait]
+pub trait ConcordiumNode: Clone + std::fmt::Debug + Send + Sync + 'static {
+    type FinalizedBlocks: FinalizedBlocks;
+
+    /// Stream finalized blocks starting at the given height.
+    async fn get_finalized_blocks_from(
+        &mut self,
+        start_height: AbsoluteBlockHeight,
+    ) -> QueryResult<Self::FinalizedBlocks>;
+
+    async fn get_block_info(&mut self, block_hash: BlockHash) -> QueryResult<BlockInfo>;
+
+    /// Get information about the last finalized block.
+    async fn get_last_finalized_block(&mut self) -> QueryResult<BlockInfo>;
+
+    /// Get summaries of all transactions in the given block.
+    async fn get_block_transaction_events(
+        &mut self,
+        block_hash: BlockHash,
+    ) -> QueryResult<Vec<BlockItemSummary>>;
+
+    async fn invoke_instance(
+        &mut self,
+        bi: BlockIdentifier,
+        context: &ContractContext,
+    ) -> QueryResult<InvokeContractResult>;
+
+    async fn send_block_item(
+        &mut self,
+        bi: &BlockItem<EncodedPayload>,
+    ) -> RPCResult<TransactionHash>;
+
+    async fn get_block_item_status(
+        &mut self,
+        tx_hash: &TransactionHash,
+    ) -> QueryResult<TransactionStatus>;
+
+    async fn get_next_account_sequence_number(
+        &mut self,
+        address: &AccountAddress,
+    ) -> QueryResult<AccountNonceResponse>;
+}
+
+#[async_trait::async_trait]
+impl ConcordiumNode for v2::Client {
+    type FinalizedBlocks = v2::FinalizedBlocksStream;
+
+    async fn get_finalized_blocks_from(
+        &mut self,
+        start_height: AbsoluteBlockHeight,
+    ) -> QueryResult<Self::FinalizedBlocks> {
+        v2::Client::get_finalized_blocks_from(self, start_height).await
+    }
+
+    async fn get_block_info(&mut self, block_hash: BlockHash) -> QueryResult<BlockInfo> {
+        Ok(v2::Client::get_block_info(self, block_hash).await?.response)
+    }
+
+    async fn get_last_finalized_block(&mut self) -> QueryResult<BlockInfo> {
+        Ok(v2::Client::get_block_info(self, BlockIdentifier::LastFinal)
+            .await?
+            .response)
+    }
+
+    async fn get_block_transaction_events(
+        &mut self,
+        block_hash: BlockHash,
+    ) -> QueryResult<Vec<BlockItemSummary>> {
+        let events = v2::Client::get_block_transaction_events(self, block_hash)
+            .await?
+            .response
+            .try_collect()
+            .await?;
+        Ok(events)
+    }
+
+    async fn invoke_instance(
+        &mut self,
+        bi: BlockIdentifier,
+        context: &ContractContext,
+    ) -> QueryResult<InvokeContractResult> {
+        Ok(v2::Client::invoke_instance(self, bi, context)
+            .await?
+            .response)
+    }
+
+    async fn send_block_item(
+        &mut self,
+        bi: &BlockItem<EncodedPayload>,
+    ) -> RPCResult<TransactionHash> {
+        v2::Client::send_block_item(self, bi).await
+    }
+
+    async fn get_block_item_status(
+        &mut self,
+        tx_hash: &TransactionHash,
+    ) -> QueryResult<TransactionStatus> {
+        v2::Client::get_block_item_status(self, tx_hash).await
+    }
+
+    async fn get_next_account_sequence_number(
+        &mut self,
+        address: &AccountAddress,
+    ) -> QueryResult<AccountNonceResponse> {
+        v2::Client::get_next_account_sequence_number(self, address).await
+    }
+}
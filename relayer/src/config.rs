@@ -0,0 +1,298 @@
+//! Configuration file and the tunables of the relayer that can be changed
+//! while it is running.
+//!
+//! Every command line option can alternatively be set in a TOML configuration
+//! file, whose keys are the names of the options without the leading `--`.
+//! Options given on the command line or in the environment take precedence
+//! over the file.
+//!
+//! A subset of the options, the [`Tunables`], is reloaded from the file on
+//! `SIGHUP`, or when requested via the admin server started by
+//! [`start_admin_server`]. The new values are distributed to the tasks via a
+//! [`TunablesReceiver`], and take effect without a restart. Changes to other
+//! options in the file require a restart.
+use crate::{balance::Thresholds, schedule::MerkleSchedule};
+use concordium_rust_sdk::types::Energy;
+use ethers::prelude::U256;
+use std::path::Path;
+use tracing_subscriber::filter::LevelFilter;
+
+/// Operational settings that can be changed while the relayer is running.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct Tunables {
+    /// Maximum gas price for Ethereum transactions.
+    pub max_gas_price: U256,
+    /// Maximum gas for setting the Merkle root.
+    pub max_gas: U256,
+    /// Maximum gas for claiming a withdrawal on behalf of a user.
+    pub claim_max_gas: U256,
+    /// Regular time between Merkle root updates.
+    #[serde(serialize_with = "serialize_secs")]
+    pub merkle_update_interval: std::time::Duration,
+    /// When to send Merkle root updates early, or defer them.
+    pub merkle_schedule: MerkleSchedule,
+    /// Time after which the price of pending Ethereum transactions is
+    /// escalated.
+    #[serde(serialize_with = "serialize_secs")]
+    pub escalation_interval: std::time::Duration,
+    /// How often to check for withdrawals to claim.
+    #[serde(serialize_with = "serialize_secs")]
+    pub claim_interval: std::time::Duration,
+    /// Maximum energy for transactions on Concordium.
+    pub max_energy: Energy,
+    /// Balance thresholds of the Ethereum accounts, in microEther.
+    pub ethereum_thresholds: Thresholds,
+    /// Balance thresholds of the Concordium sender account, in microCCD.
+    pub concordium_thresholds: Thresholds,
+    #[serde(serialize_with = "serialize_display")]
+    pub log_level: LevelFilter,
+}
+
+fn serialize_secs<S: serde::Serializer>(
+    duration: &std::time::Duration,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u64(duration.as_secs())
+}
+
+pub(crate) fn serialize_opt_secs<S: serde::Serializer>(
+    duration: &Option<std::time::Duration>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match duration {
+        Some(duration) => serializer.serialize_some(&duration.as_secs()),
+        None => serializer.serialize_none(),
+    }
+}
+
+fn serialize_display<S: serde::Serializer>(
+    value: &impl std::fmt::Display,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(value)
+}
+
+impl Tunables {
+    /// Check that the tunables are usable by the tasks.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        for (name, interval) in [
+            ("merkle-update-interval", self.merkle_update_interval),
+            ("escalation-interval", self.escalation_interval),
+            ("claim-interval", self.claim_interval),
+        ] {
+            anyhow::ensure!(!interval.is_zero(), "The {name} must be positive.");
+        }
+        anyhow::ensure!(
+            !self.max_gas_price.is_zero(),
+            "The max-gas-price must be positive."
+        );
+        anyhow::ensure!(!self.max_gas.is_zero(), "The max-gas must be positive.");
+        anyhow::ensure!(
+            !self.claim_max_gas.is_zero(),
+            "The claim-max-gas must be positive."
+        );
+        anyhow::ensure!(
+            self.max_energy.energy > 0,
+            "The max-energy must be positive."
+        );
+        self.merkle_schedule.validate(self.merkle_update_interval)?;
+        Ok(())
+    }
+}
+
+/// The receiving end of the current tunables.
+pub type TunablesReceiver = tokio::sync::watch::Receiver<Tunables>;
+
+/// Wait until the tunables change. If they can no longer change, because the
+/// sender is gone, this never returns.
+pub async fn changed(tunables: &mut TunablesReceiver) {
+    if tunables.changed().await.is_err() {
+        futures::future::pending::<()>().await
+    }
+}
+
+/// Read a configuration file.
+pub fn read_file(path: &Path) -> anyhow::Result<toml::value::Table> {
+    let contents = std::fs::read_to_string(path)?;
+    let table = toml::from_str(&contents)?;
+    Ok(table)
+}
+
+/// Convert the options in the configuration `file` to command line arguments
+/// of the `command`. Options that are set on the command line or in the
+/// environment according to `matches` are skipped, so that those take
+/// precedence over the file. Keys may use either `-` or `_` as separator.
+///
+/// Fails if the file contains an option the command does not have.
+pub fn file_arguments(
+    command: &clap::Command,
+    matches: &clap::ArgMatches,
+    file: &toml::value::Table,
+) -> anyhow::Result<Vec<String>> {
+    let mut args = Vec::new();
+    for (key, value) in file {
+        let name = key.replace('_', "-");
+        let Some(arg) = command
+            .get_arguments()
+            .find(|arg| arg.get_long() == Some(name.as_str()))
+        else {
+            anyhow::bail!("Unknown option `{key}` in the configuration file.");
+        };
+        if matches!(
+            matches.value_source(arg.get_id().as_str()),
+            Some(clap::parser::ValueSource::CommandLine | clap::parser::ValueSource::EnvVariable)
+        ) {
+            continue;
+        }
+        let values = match value {
+            toml::Value::Array(values) => values.iter().collect(),
+            value => vec![value],
+        };
+        for value in values {
+            let value = match value {
+                toml::Value::String(s) => s.clone(),
+                toml::Value::Integer(i) => i.to_string(),
+                toml::Value::Float(f) => f.to_string(),
+                toml::Value::Datetime(d) => d.to_string(),
+                toml::Value::Boolean(b) if matches!(arg.get_action(), clap::ArgAction::SetTrue) => {
+                    if *b {
+                        args.push(format!("--{name}"));
+                    }
+                    continue;
+                }
+                toml::Value::Boolean(b) => b.to_string(),
+                toml::Value::Array(_) | toml::Value::Table(_) => {
+                    anyhow::bail!("Unsupported value of option `{key}` in the configuration file.")
+                }
+            };
+            args.push(format!("--{name}={value}"));
+        }
+    }
+    Ok(args)
+}
+
+/// A request to reload the configuration file. The new tunables, or the
+/// reason they could not be loaded, are sent back on the channel.
+pub type ReloadRequest = tokio::sync::oneshot::Sender<anyhow::Result<Tunables>>;
+
+/// The minimal length of the bearer token of the admin server.
+pub const MIN_ADMIN_TOKEN_LENGTH: usize = 32;
+
+/// Check that the bearer token of the admin server is long enough that it
+/// cannot be guessed. In particular it must not be empty.
+pub fn validate_admin_token(token: &str) -> anyhow::Result<()> {
+    anyhow::ensure!(
+        token.len() >= MIN_ADMIN_TOKEN_LENGTH,
+        "The admin-token must be at least {MIN_ADMIN_TOKEN_LENGTH} characters long."
+    );
+    Ok(())
+}
+
+#[derive(Clone)]
+struct AdminState {
+    token: std::sync::Arc<str>,
+    tunables: TunablesReceiver,
+    reload: tokio::sync::mpsc::Sender<ReloadRequest>,
+}
+
+/// Check the bearer token of the request. The comparison takes the same time
+/// regardless of where the tokens differ. An empty token authorizes nothing.
+fn authorized(headers: &axum::http::HeaderMap, token: &str) -> bool {
+    let Some(provided) = headers
+        .get(axum::http::header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+    else {
+        return false;
+    };
+    !token.is_empty()
+        && provided.len() == token.len()
+        && provided
+            .bytes()
+            .zip(token.bytes())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+type AdminResponse = (axum::http::StatusCode, axum::Json<serde_json::Value>);
+
+fn unauthorized() -> AdminResponse {
+    (
+        axum::http::StatusCode::UNAUTHORIZED,
+        axum::Json(serde_json::json!({ "error": "Missing or invalid bearer token." })),
+    )
+}
+
+async fn tunables_handler(
+    axum::extract::State(state): axum::extract::State<AdminState>,
+    headers: axum::http::HeaderMap,
+) -> AdminResponse {
+    if !authorized(&headers, &state.token) {
+        return unauthorized();
+    }
+    let tunables = state.tunables.borrow().clone();
+    (
+        axum::http::StatusCode::OK,
+        axum::Json(serde_json::json!(tunables)),
+    )
+}
+
+async fn reload_handler(
+    axum::extract::State(state): axum::extract::State<AdminState>,
+    headers: axum::http::HeaderMap,
+) -> AdminResponse {
+    if !authorized(&headers, &state.token) {
+        return unauthorized();
+    }
+    let (sender, receiver) = tokio::sync::oneshot::channel();
+    if state.reload.send(sender).await.is_err() {
+        return (
+            axum::http::StatusCode::SERVICE_UNAVAILABLE,
+            axum::Json(serde_json::json!({ "error": "The relayer is shutting down." })),
+        );
+    }
+    match receiver.await {
+        Ok(Ok(tunables)) => (
+            axum::http::StatusCode::OK,
+            axum::Json(serde_json::json!(tunables)),
+        ),
+        Ok(Err(e)) => (
+            axum::http::StatusCode::UNPROCESSABLE_ENTITY,
+            axum::Json(serde_json::json!({ "error": format!("{e:#}") })),
+        ),
+        Err(_) => (
+            axum::http::StatusCode::SERVICE_UNAVAILABLE,
+            axum::Json(serde_json::json!({ "error": "The relayer is shutting down." })),
+        ),
+    }
+}
+
+/// Start the admin server. `GET /tunables` responds with the current
+/// tunables, and `POST /reload` reloads the configuration file via the
+/// `reload` channel and responds with the new tunables. Both require the
+/// `Authorization: Bearer <token>` header. The task only terminates if the
+/// server terminates, i.e., if it crashes.
+pub async fn start_admin_server(
+    addr: std::net::SocketAddr,
+    token: String,
+    tunables: TunablesReceiver,
+    reload: tokio::sync::mpsc::Sender<ReloadRequest>,
+) -> anyhow::Result<()> {
+    let state = AdminState {
+        token: token.into(),
+        tunables,
+        reload,
+    };
+    let app = axum::Router::new()
+        .route("/tunables", axum::routing::get(tunables_handler))
+        .route("/reload", axum::routing::post(reload_handler))
+        .with_state(state)
+        .layer(tower_http::timeout::TimeoutLayer::new(
+            std::time::Duration::from_secs(10),
+        ))
+        .layer(tower_http::limit::RequestBodyLimitLayer::new(0)); // no bodies are accepted.
+    axum::Server::bind(&addr)
+        .serve(app.into_make_service())
+        .await?;
+    Ok(())
+}
@@ -1,27 +1,53 @@
 use crate::{
-    concordium_contracts::{self, BridgeEvent, BridgeManager, BridgeManagerClient, WithdrawEvent},
+    blocklist::{Blocklist, BlocklistSource},
+    concordium_contracts::{
+        self, AdminEvent, BridgeEvent, BridgeManager, BridgeManagerClient, WithdrawEvent,
+    },
+    concordium_node::ConcordiumNode,
     ethereum,
+    merkle::Leaf,
 };
 use anyhow::Context;
 use concordium_rust_sdk::{
     cis2,
     common::{self, to_bytes},
+    id::types::AccountAddress,
     smart_contracts::common as contracts_common,
     types::{
         hashes::TransactionHash,
         queries::BlockInfo,
         transactions::{BlockItem, EncodedPayload, PayloadLike},
-        AbsoluteBlockHeight, ContractAddress, Nonce,
+        AbsoluteBlockHeight, Address, ContractAddress, Nonce,
     },
-    v2,
 };
 use ethabi::ethereum_types::{H160, H256, U256};
 use num_bigint::BigUint;
+use std::borrow::Cow;
 use tokio::task::JoinHandle;
 use tokio_postgres::{NoTls, Statement, Transaction};
+use tracing::Instrument;
 
 const SCHEMA: &str = include_str!("../resources/db_schema.sql");
 
+/// Label of per-token metrics for tokens that are not in the `token_maps`
+/// table.
+const UNKNOWN_TOKEN: &str = "unknown";
+
+/// Maximum number of operations skipped by a single state update, so that the
+/// update stays within the energy limit.
+const MAX_SKIPPED_PER_UPDATE: usize = 100;
+
+/// Record the time from `start` to `end` in a latency histogram. Negative
+/// durations, due to clock skew between the chains, are recorded as 0.
+fn observe_latency(
+    histogram: &prometheus::Histogram,
+    start: chrono::DateTime<chrono::Utc>,
+    end: chrono::DateTime<chrono::Utc>,
+) {
+    let latency = end.signed_duration_since(start).num_milliseconds().max(0);
+    histogram.observe(latency as f64 / 1000.0);
+}
+
 #[derive(Debug, Copy, Clone, tokio_postgres::types::ToSql, tokio_postgres::types::FromSql)]
 #[postgres(name = "network")]
 pub enum Network {
@@ -42,6 +68,9 @@ pub enum EthTransactionStatus {
     Confirmed,
     #[postgres(name = "missing")]
     Missing,
+    /// Transaction was finalized, but reverted. Only used for claims.
+    #[postgres(name = "failed")]
+    Failed,
 }
 
 #[derive(
@@ -74,6 +103,11 @@ pub enum TransactionStatus {
     #[schema(rename = "missing")]
     #[serde(rename = "missing")]
     Missing,
+    /// Transaction was computed by a relayer in shadow mode and never sent.
+    #[postgres(name = "shadow")]
+    #[schema(rename = "shadow")]
+    #[serde(rename = "shadow")]
+    Shadow,
 }
 
 struct PreparedStatements {
@@ -88,6 +122,12 @@ struct PreparedStatements {
     get_pending_withdrawals: Statement,
     get_max_event_index: Statement,
     set_expected_merkle_time: Statement,
+    insert_claim_tx: Statement,
+    get_pending_claim_txs: Statement,
+    get_claimable_withdrawal: Statement,
+    get_merkle_leaves: Statement,
+    insert_shadow_merkle_root: Statement,
+    insert_admin_event: Statement,
 }
 
 impl PreparedStatements {
@@ -99,7 +139,7 @@ impl PreparedStatements {
         bi: &BlockItem<Payload>,
     ) -> anyhow::Result<i64> {
         let hash = bi.hash();
-        log::debug!(
+        tracing::debug!(
             "Inserting Concordium transaction with hash {hash} in response to {origin_tx_hash:#x}"
         );
         let timestamp = chrono::Utc::now().timestamp();
@@ -120,7 +160,10 @@ impl PreparedStatements {
     }
 
     /// Insert the event. If the event is a Withdraw event
-    /// return whether it has already been processed or not.
+    /// return whether it has already been processed or not, and the symbol and
+    /// number of decimals of the withdrawn token if the token is known.
+    /// Administrative actions are not inserted, since they are recorded in the
+    /// `admin_events` table.
     pub async fn insert_concordium_event<'a, 'b>(
         &'a self,
         metrics: &crate::metrics::Metrics,
@@ -128,8 +171,9 @@ impl PreparedStatements {
         tx_hash: &TransactionHash,
         event: &BridgeEvent,
         merkle_hash: Option<[u8; 32]>,
-    ) -> anyhow::Result<bool> {
-        log::debug!("Inserting Concordium event for transaction {tx_hash}.");
+        block_time: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<(bool, Option<(String, u8)>)> {
+        tracing::debug!("Inserting Concordium event for transaction {tx_hash}.");
         let (event_type, origin_event_index, data) = match event {
             BridgeEvent::TokenMap(tm) => {
                 let rows = db_tx
@@ -140,7 +184,7 @@ impl PreparedStatements {
                     .await?;
                 if rows.len() != 1 {
                     metrics.warnings_total.inc();
-                    log::warn!(
+                    tracing::warn!(
                         "A TokenMap event was emitted by a transaction not submitted by the \
                          relayer."
                     );
@@ -153,17 +197,24 @@ impl PreparedStatements {
             }
             BridgeEvent::Deposit(de) => {
                 metrics.num_completed_deposits.inc();
-                log::debug!("Marking a deposit with event index {} as completed.", de.id);
+                tracing::debug!("Marking a deposit with event index {} as completed.", de.id);
                 let rows = db_tx
                     .query(
                         "UPDATE ethereum_deposit_events SET tx_hash = $2 WHERE origin_event_index \
-                         = $1 RETURNING id",
+                         = $1 RETURNING lock_time",
                         &[&(de.id as i64), &tx_hash.as_ref()],
                     )
                     .await?;
                 if rows.len() != 1 {
                     metrics.warnings_total.inc();
-                    log::warn!("Deposited an event that was not emitted on Ethereum.");
+                    tracing::warn!("Deposited an event that was not emitted on Ethereum.");
+                }
+                for row in rows {
+                    if let Some(lock_time) =
+                        row.get::<_, Option<chrono::DateTime<chrono::Utc>>>("lock_time")
+                    {
+                        observe_latency(&metrics.deposit_latency, lock_time, block_time);
+                    }
                 }
                 let rows = db_tx
                     .query(
@@ -173,7 +224,7 @@ impl PreparedStatements {
                     .await?;
                 if rows.len() != 1 {
                     metrics.warnings_total.inc();
-                    log::warn!(
+                    tracing::warn!(
                         "A deposit event was emitted by a transaction not submitted by the \
                          relayer."
                     );
@@ -185,7 +236,7 @@ impl PreparedStatements {
                 )
             }
             BridgeEvent::Withdraw(we) => {
-                log::debug!(
+                tracing::debug!(
                     "Inserting new withdrawal event with event index {}.",
                     we.event_index,
                 );
@@ -199,25 +250,99 @@ impl PreparedStatements {
                             &ConcordiumEventType::Withdraw,
                             &Some(we.contract.index as i64),
                             &Some(we.contract.subindex as i64),
-                            &Some(&we.eth_address[..]),
+                            &Some(&we.eth_address.eth_address[..]),
                             &Some(&we.amount.to_string()),
                             &contracts_common::to_bytes(we),
                             &merkle_hash.as_ref().map(|x| &x[..]),
+                            &block_time,
+                            &we.fee.as_ref().map(|fee| fee.to_string()),
                         ],
                     )
                     .await?;
-                return Ok(res.get::<_, bool>(0));
+                let token = res.get::<_, Option<String>>(1);
+                metrics
+                    .token_withdrawals
+                    .with_label_values(&[token.as_deref().unwrap_or(UNKNOWN_TOKEN)])
+                    .inc();
+                let decimals = res.get::<_, Option<i16>>(2);
+                let token = token.zip(decimals).map(|(name, d)| (name, d as u8));
+                return Ok((res.get::<_, bool>(0), token));
+            }
+            BridgeEvent::ClaimRequested(cr) => {
+                tracing::debug!(
+                    "Marking withdrawal with event index {} to be claimed.",
+                    cr.event_index
+                );
+                let rows = db_tx
+                    .query(
+                        "UPDATE concordium_events SET claim_fee = $2 WHERE event_index = $1 AND \
+                         event_type = 'withdraw' RETURNING id",
+                        &[&(cr.event_index as i64), &(cr.fee.micro_ccd as i64)],
+                    )
+                    .await?;
+                if rows.len() != 1 {
+                    metrics.warnings_total.inc();
+                    tracing::warn!(
+                        "A claim was requested for an unknown withdrawal with event index {}.",
+                        cr.event_index
+                    );
+                }
+                (
+                    ConcordiumEventType::ClaimRequested,
+                    None,
+                    contracts_common::to_bytes(cr),
+                )
+            }
+            // Administrative actions are only recorded in the `admin_events`
+            // table. Proposals are logged as well.
+            BridgeEvent::ProposalCreated(pc) => {
+                tracing::info!(
+                    "Admin action {:?} proposed as proposal {}, executable from {}.",
+                    pc.action,
+                    pc.id,
+                    pc.execute_after
+                );
+                return Ok((false, None));
+            }
+            BridgeEvent::GrantRole(_)
+            | BridgeEvent::RevokeRole(_)
+            | BridgeEvent::ProposalExecuted(_)
+            | BridgeEvent::ProposalCancelled(_)
+            | BridgeEvent::AdminTransferPending(_)
+            | BridgeEvent::AdminTransferAccepted(_) => return Ok((false, None)),
+            BridgeEvent::SetPaused(_)
+            | BridgeEvent::SetWithdrawFee(_)
+            | BridgeEvent::SetClaimFee(_)
+            | BridgeEvent::SetTreasurer(_)
+            | BridgeEvent::Upgrade(_)
+            | BridgeEvent::SetGovernanceDelay(_)
+            | BridgeEvent::SetBlocked(_)
+            | BridgeEvent::SetTokenFee(_) => {
+                tracing::info!("Configuration of the bridge manager changed: {event:?}.");
+                return Ok((false, None));
+            }
+            // Corrections are stored with the token and the amount, so that the
+            // guardian includes them in the supply.
+            BridgeEvent::CorrectiveMint(ce) => {
+                tracing::warn!("The supply of a token was corrected: {event:?}.");
+                (
+                    ConcordiumEventType::CorrectiveMint,
+                    None,
+                    contracts_common::to_bytes(ce),
+                )
+            }
+            BridgeEvent::CorrectiveBurn(ce) => {
+                tracing::warn!("The supply of a token was corrected: {event:?}.");
+                (
+                    ConcordiumEventType::CorrectiveBurn,
+                    None,
+                    contracts_common::to_bytes(ce),
+                )
             }
-            BridgeEvent::GrantRole(gr) => (
-                ConcordiumEventType::GrantRole,
-                None,
-                contracts_common::to_bytes(gr),
-            ),
-            BridgeEvent::RevokeRole(rr) => (
-                ConcordiumEventType::RevokeRole,
-                None,
-                contracts_common::to_bytes(rr),
-            ),
+        };
+        let correction = match event {
+            BridgeEvent::CorrectiveMint(ce) | BridgeEvent::CorrectiveBurn(ce) => Some(ce),
+            _ => None,
         };
         let res = db_tx
             .query_one(
@@ -227,16 +352,18 @@ impl PreparedStatements {
                     &event.event_index().map(|x| x as i64),
                     &origin_event_index,
                     &event_type,
-                    &None::<i64>,
-                    &None::<i64>,
+                    &correction.map(|ce| ce.token.index as i64),
+                    &correction.map(|ce| ce.token.subindex as i64),
                     &None::<Vec<u8>>,
-                    &None::<String>,
+                    &correction.map(|ce| ce.amount.to_string()),
                     &data,
                     &merkle_hash.as_ref().map(|x| &x[..]),
+                    &block_time,
+                    &None::<String>,
                 ],
             )
             .await?;
-        Ok(res.get::<_, bool>(0))
+        Ok((res.get::<_, bool>(0), None))
     }
 }
 
@@ -253,12 +380,12 @@ impl Database {
         match self.connection_handle.await {
             Ok(v) => {
                 if let Err(e) = v {
-                    log::error!("Database connection task terminated with an error {e:#}.");
+                    tracing::error!("Database connection task terminated with an error {e:#}.");
                 }
             }
             Err(e) => {
                 if !e.is_cancelled() {
-                    log::error!("Error {e:#} shutting down database connection task.");
+                    tracing::error!("Error {e:#} shutting down database connection task.");
                 }
             }
         }
@@ -273,8 +400,12 @@ pub enum DatabaseOperation {
     ConcordiumEvents {
         /// Events are from this block.
         block: BlockInfo,
-        /// Events for the given transactions.
-        transaction_events: Vec<(TransactionHash, Vec<BridgeEvent>)>,
+        /// Events for the given transactions, and the span in which each
+        /// transaction's events are processed.
+        transaction_events: Vec<(TransactionHash, Vec<BridgeEvent>, tracing::Span)>,
+        /// Administrative events of the bridge manager and the mapped tokens
+        /// for the given transactions.
+        admin_events: Vec<(TransactionHash, Vec<AdminEvent>)>,
     },
     EthereumEvents {
         /// Insert these Ethereum events.
@@ -328,6 +459,45 @@ pub enum DatabaseOperation {
     SetNextMerkleUpdateTime {
         next_time: chrono::DateTime<chrono::Utc>,
     },
+    /// Record a Merkle root computed in shadow mode.
+    RecordShadowMerkleRoot {
+        /// The root.
+        root: [u8; 32],
+        /// Number of leaves of the tree.
+        num_leaves: u64,
+        /// The largest event index of the leaves.
+        max_event_index: u64,
+    },
+    /// Look up the next withdrawal that should be claimed on behalf of the
+    /// user, if any.
+    GetClaimableWithdrawal {
+        /// Only consider withdrawals with event index at least this.
+        from_event_index: u64,
+        response: tokio::sync::oneshot::Sender<Option<ClaimableWithdrawal>>,
+    },
+    StoreClaimTransaction {
+        /// Hash of the transaction to store.
+        tx_hash: H256,
+        /// The transaction, signed so cannot be tampered with.
+        tx: ethers::prelude::Bytes,
+        /// The event index of the withdrawal that is claimed.
+        event_index: u64,
+        /// The channel where we reply to when the operation is completed.
+        response: tokio::sync::oneshot::Sender<ethers::prelude::Bytes>,
+    },
+    /// Mark the claim transaction as confirmed, or as failed if it reverted.
+    MarkClaimCompleted {
+        /// The hash of the transaction that is being marked.
+        tx_hash: H256,
+        /// Whether the transaction succeeded. A reverted claim is marked as
+        /// failed and is not attempted again.
+        success: bool,
+        /// The remaining transactions for the same claim (and nonce) which
+        /// have been made obsolete. Mark these as gone.
+        failed_hashes: Vec<H256>,
+        /// Respond when the update is completed.
+        response: tokio::sync::oneshot::Sender<()>,
+    },
 }
 
 /// A pending Ethereum transaction stored in the Database.
@@ -340,6 +510,94 @@ pub struct PendingEthereumTransactions {
     pub idxs: std::sync::Arc<[u64]>,
 }
 
+/// Pending claim transactions stored in the Database.
+pub struct PendingClaimTransactions {
+    /// A pair of hash and signed transaction.
+    pub txs: Vec<(H256, ethers::prelude::Bytes)>,
+    /// Event index of the withdrawal claimed by the transactions.
+    pub event_index: u64,
+}
+
+#[derive(Debug)]
+/// A withdrawal that the user requested to be claimed by the relayer, and which
+/// is approved by the current Merkle root.
+pub struct ClaimableWithdrawal {
+    /// Hash of the transaction that emitted the withdraw event.
+    pub tx_hash: TransactionHash,
+    /// The withdraw event.
+    pub event: WithdrawEvent,
+    /// Event indices and hashes of all the leaves of the current Merkle root,
+    /// ordered by increasing event index.
+    pub leaves: Vec<(u64, [u8; 32])>,
+}
+
+/// Decision of a reviewer on a quarantined deposit.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    tokio_postgres::types::ToSql,
+    tokio_postgres::types::FromSql,
+    serde::Serialize,
+    utoipa::ToSchema,
+)]
+#[postgres(name = "quarantine_decision")]
+pub enum QuarantineDecision {
+    /// Send the deposit to Concordium as it was made.
+    #[postgres(name = "release")]
+    #[serde(rename = "release")]
+    #[schema(rename = "release")]
+    Release,
+    /// Skip the operation of the deposit in the bridge manager, so that it is
+    /// never sent to Concordium. The tokens stay locked on Ethereum.
+    #[postgres(name = "reject")]
+    #[serde(rename = "reject")]
+    #[schema(rename = "reject")]
+    Reject,
+}
+
+#[derive(Debug)]
+/// A quarantined deposit with a decision the relayer has not carried out yet.
+pub struct DecidedDeposit {
+    /// Id of the deposit in the `ethereum_deposit_events` table.
+    pub id: i64,
+    /// Hash of the transaction on Ethereum that made the deposit.
+    pub origin_tx_hash: H256,
+    /// Id of the deposit operation of the `StateSender`.
+    pub origin_event_index: u64,
+    /// The Concordium account that receives the deposit, or that the deposit
+    /// falls back to.
+    pub receiver: Option<AccountAddress>,
+    /// The deposit operation, unless the deposit was quarantined before the
+    /// operation was recorded.
+    pub operation: Option<concordium_contracts::DepositOperation>,
+    pub decision: QuarantineDecision,
+}
+
+/// Record the decision on the quarantined deposit with the given id of the
+/// `StateSender`. The relayer carries it out before its next batch of Ethereum
+/// events. Return whether the decision was recorded, which it is not if the
+/// deposit is not quarantined, if an earlier decision on it was already carried
+/// out, or if it is released but was quarantined before its operation was
+/// recorded.
+pub async fn decide_quarantined_deposit(
+    client: &tokio_postgres::Client,
+    origin_event_index: u64,
+    decision: QuarantineDecision,
+) -> anyhow::Result<bool> {
+    let rows = client
+        .execute(
+            "UPDATE ethereum_deposit_events SET quarantine_decision = $2 WHERE \
+             origin_event_index = $1 AND quarantined AND quarantine_resolved_time IS NULL AND \
+             ($2 = 'reject'::quarantine_decision OR quarantined_operation IS NOT NULL);",
+            &[&(origin_event_index as i64), &decision],
+        )
+        .await?;
+    Ok(rows == 1)
+}
+
 #[derive(Debug, tokio_postgres::types::ToSql, tokio_postgres::types::FromSql)]
 #[postgres(name = "concordium_event_type")]
 pub enum ConcordiumEventType {
@@ -349,40 +607,59 @@ pub enum ConcordiumEventType {
     Deposit,
     #[postgres(name = "withdraw")]
     Withdraw,
+    /// Only in databases from before role changes were recorded in the
+    /// `admin_events` table instead.
     #[postgres(name = "grant_role")]
     GrantRole,
+    /// Only in databases from before role changes were recorded in the
+    /// `admin_events` table instead.
     #[postgres(name = "revoke_role")]
     RevokeRole,
+    #[postgres(name = "claim_requested")]
+    ClaimRequested,
+    #[postgres(name = "corrective_mint")]
+    CorrectiveMint,
+    #[postgres(name = "corrective_burn")]
+    CorrectiveBurn,
+}
+
+/// Connect to the database. TLS is used if the configuration prefers or
+/// requires it, with the system certificates as trusted roots. The returned
+/// handle is the task that drives the connection.
+pub async fn connect(
+    config: &tokio_postgres::Config,
+) -> anyhow::Result<(
+    tokio_postgres::Client,
+    JoinHandle<Result<(), tokio_postgres::Error>>,
+)> {
+    match config.get_ssl_mode() {
+        tokio_postgres::config::SslMode::Prefer | tokio_postgres::config::SslMode::Require => {
+            let mut root_certs = rustls::RootCertStore::empty();
+            for cert in
+                rustls_native_certs::load_native_certs().context("Unable to load certificates")?
+            {
+                root_certs.add(&rustls::Certificate(cert.0))?;
+            }
+            let tls_config = rustls::ClientConfig::builder()
+                .with_safe_defaults()
+                .with_root_certificates(root_certs)
+                .with_no_client_auth();
+            let tls = tokio_postgres_rustls::MakeRustlsConnect::new(tls_config);
+            let (client, connection) = config.connect(tls).await?;
+            Ok((client, tokio::spawn(connection)))
+        }
+        _ => {
+            let (client, connection) = config.connect(NoTls).await?;
+            Ok((client, tokio::spawn(connection)))
+        }
+    }
 }
 
 impl Database {
     pub async fn new(
         config: &tokio_postgres::Config,
     ) -> anyhow::Result<(Option<u64>, Option<AbsoluteBlockHeight>, Self)> {
-        let (client, connection_handle) = {
-            match config.get_ssl_mode() {
-                tokio_postgres::config::SslMode::Prefer
-                | tokio_postgres::config::SslMode::Require => {
-                    let mut root_certs = rustls::RootCertStore::empty();
-                    for cert in rustls_native_certs::load_native_certs()
-                        .context("Unable to load certificates")?
-                    {
-                        root_certs.add(&rustls::Certificate(cert.0))?;
-                    }
-                    let tls_config = rustls::ClientConfig::builder()
-                        .with_safe_defaults()
-                        .with_root_certificates(root_certs)
-                        .with_no_client_auth();
-                    let tls = tokio_postgres_rustls::MakeRustlsConnect::new(tls_config);
-                    let (client, connection) = config.connect(tls).await?;
-                    (client, tokio::spawn(connection))
-                }
-                _ => {
-                    let (client, connection) = config.connect(NoTls).await?;
-                    (client, tokio::spawn(connection))
-                }
-            }
-        };
+        let (client, connection_handle) = connect(config).await?;
         client.batch_execute(SCHEMA).await?;
         let insert_concordium_tx = client
             .prepare(
@@ -399,7 +676,7 @@ WHERE status = 'pending' ORDER BY id ASC;",
         let get_pending_ethereum_txs = client
             .prepare(
                 "SELECT tx_hash, tx, timestamp FROM ethereum_transactions WHERE status = \
-                 'pending' ORDER BY id ASC;",
+                 'pending' AND kind = 'merkle_root' ORDER BY id ASC;",
             )
             .await?;
         let mark_concordium_tx = client
@@ -417,27 +694,37 @@ VALUES ($1, $2, $3, $4) RETURNING id",
             .prepare(
                 "INSERT INTO concordium_events (tx_hash, event_index, origin_event_index, \
                  event_type, child_index, child_subindex, receiver, amount, event_data, \
-                 event_merkle_hash, processed)
-VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10,
+                 event_merkle_hash, block_time, fee, processed)
+VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12,
         (CASE WHEN $4 = ('withdraw' :: concordium_event_type)
               THEN (SELECT tx_hash FROM ethereum_withdraw_events
                     WHERE ethereum_withdraw_events.origin_event_index = $2
                     LIMIT 1)
               ELSE NULL END))
-RETURNING (CASE WHEN processed IS NULL THEN FALSE ELSE TRUE END)",
+RETURNING (CASE WHEN processed IS NULL THEN FALSE ELSE TRUE END),
+          (SELECT eth_name FROM token_maps
+           WHERE token_maps.child_index = $5 AND token_maps.child_subindex = $6
+           LIMIT 1),
+          (SELECT decimals FROM token_maps
+           WHERE token_maps.child_index = $5 AND token_maps.child_subindex = $6
+           LIMIT 1)",
             )
             .await?;
 
         let mark_withdrawal_as_completed = client
             .prepare(
-                "UPDATE concordium_events SET processed = $1 WHERE event_index = $2 RETURNING id;",
+                "UPDATE concordium_events SET processed = $1 WHERE event_index = $2 RETURNING id, \
+                 block_time;",
             )
             .await?;
 
         let get_pending_withdrawals = client
             .prepare(
-                "SELECT tx_hash, event_index, event_data FROM concordium_events
-WHERE (processed IS NULL) AND event_type = 'withdraw' ORDER BY id ASC;",
+                "SELECT concordium_events.tx_hash, event_index, event_data, block_time, eth_name, \
+                 decimals FROM concordium_events
+LEFT JOIN token_maps ON token_maps.child_index = concordium_events.child_index
+                    AND token_maps.child_subindex = concordium_events.child_subindex
+WHERE (processed IS NULL) AND event_type = 'withdraw' ORDER BY concordium_events.id ASC;",
             )
             .await?;
         let get_max_event_index = client
@@ -471,6 +758,51 @@ ON CONFLICT (tag) DO UPDATE SET expected_time = $1;
             .prepare("UPDATE ethereum_transactions SET tx_hash = $1 WHERE tx_hash = $2;")
             .await?;
 
+        let insert_claim_tx = client
+            .prepare(
+                "INSERT INTO ethereum_transactions (tx_hash, tx, timestamp, status, kind, \
+                 claim_event_index)
+VALUES ($1, $2, $3, $4, 'claim', $5) RETURNING id",
+            )
+            .await?;
+        let get_pending_claim_txs = client
+            .prepare(
+                "SELECT tx_hash, tx, claim_event_index FROM ethereum_transactions WHERE status = \
+                 'pending' AND kind = 'claim' ORDER BY id ASC;",
+            )
+            .await?;
+        let get_claimable_withdrawal = client
+            .prepare(
+                "SELECT tx_hash, event_index, event_data FROM concordium_events
+WHERE claim_fee IS NOT NULL AND processed IS NULL AND event_index >= $1
+      AND root IN (SELECT root FROM merkle_roots ORDER BY id DESC LIMIT 1)
+      AND NOT EXISTS (SELECT 1 FROM ethereum_transactions
+                      WHERE ethereum_transactions.claim_event_index = concordium_events.event_index
+                      AND ethereum_transactions.status <> 'missing')
+ORDER BY event_index ASC LIMIT 1;",
+            )
+            .await?;
+        let get_merkle_leaves = client
+            .prepare(
+                "SELECT event_index, event_merkle_hash FROM concordium_events WHERE root IN \
+                 (SELECT root FROM merkle_roots ORDER BY id DESC LIMIT 1) ORDER BY event_index \
+                 ASC;",
+            )
+            .await?;
+        let insert_shadow_merkle_root = client
+            .prepare(
+                "INSERT INTO shadow_merkle_roots (root, num_leaves, max_event_index) VALUES ($1, \
+                 $2, $3);",
+            )
+            .await?;
+        let insert_admin_event = client
+            .prepare(
+                "INSERT INTO admin_events (tx_hash, contract_index, contract_subindex, log_index, \
+                 action, event_data, block_time) VALUES ($1, $2, $3, $4, $5, $6, $7) ON CONFLICT \
+                 DO NOTHING;",
+            )
+            .await?;
+
         let db = Database {
             client,
             connection_handle,
@@ -486,6 +818,12 @@ ON CONFLICT (tag) DO UPDATE SET expected_time = $1;
                 mark_withdrawal_as_completed,
                 get_max_event_index,
                 set_expected_merkle_time,
+                insert_claim_tx,
+                get_pending_claim_txs,
+                get_claimable_withdrawal,
+                get_merkle_leaves,
+                insert_shadow_merkle_root,
+                insert_admin_event,
             },
         };
         Ok((
@@ -500,7 +838,7 @@ ON CONFLICT (tag) DO UPDATE SET expected_time = $1;
         old_tx_hash: H256,
         new_tx_hash: H256,
     ) -> anyhow::Result<()> {
-        log::debug!("Updating Ethereum transaction {:#x}.", old_tx_hash);
+        tracing::debug!("Updating Ethereum transaction {:#x}.", old_tx_hash);
         let statements = &self.prepared_statements;
         let db_tx = self.client.transaction().await?;
         db_tx
@@ -522,7 +860,7 @@ ON CONFLICT (tag) DO UPDATE SET expected_time = $1;
         ids: &[u64],
     ) -> anyhow::Result<u64> {
         let timestamp = chrono::Utc::now().timestamp();
-        log::debug!("Inserting Ethereum transaction {:#x}.", tx_hash);
+        tracing::debug!("Inserting Ethereum transaction {:#x}.", tx_hash);
         let statements = &self.prepared_statements;
         let db_tx = self.client.transaction().await?;
         let row = db_tx
@@ -553,6 +891,7 @@ ON CONFLICT (tag) DO UPDATE SET expected_time = $1;
 
     pub async fn mark_merkle_root_set(
         &mut self,
+        metrics: &crate::metrics::Metrics,
         root: [u8; 32],
         ids: &[u64],
         success: bool,
@@ -561,15 +900,25 @@ ON CONFLICT (tag) DO UPDATE SET expected_time = $1;
     ) -> anyhow::Result<()> {
         let db_tx = self.client.transaction().await?;
         if success {
+            let now = chrono::Utc::now();
             for &id in ids {
                 // TODO: Make prepared statement for this.
-                db_tx
+                let row = db_tx
                     .query_opt(
-                        "UPDATE concordium_events SET pending_root = NULL, root = $1 WHERE \
-                         event_index = $2 RETURNING id",
+                        "UPDATE concordium_events SET pending_root = NULL, previous_root = root, \
+                         root = $1 WHERE event_index = $2 RETURNING previous_root, block_time",
                         &[&&root[..], &(id as i64)],
                     )
                     .await?;
+                // Only the first root that contains a withdrawal approves it.
+                if let Some(row) = row {
+                    let previous_root = row.get::<_, Option<Vec<u8>>>("previous_root");
+                    let block_time =
+                        row.get::<_, Option<chrono::DateTime<chrono::Utc>>>("block_time");
+                    if let (None, Some(block_time)) = (previous_root, block_time) {
+                        observe_latency(&metrics.withdrawal_approval_latency, block_time, now);
+                    }
+                }
             }
             db_tx
                 .query_one(
@@ -609,6 +958,153 @@ ON CONFLICT (tag) DO UPDATE SET expected_time = $1;
         Ok(())
     }
 
+    pub async fn insert_claim_tx(
+        &mut self,
+        tx_hash: H256,
+        tx: &ethers::prelude::Bytes,
+        event_index: u64,
+    ) -> anyhow::Result<u64> {
+        let timestamp = chrono::Utc::now().timestamp();
+        tracing::debug!(
+            "Inserting Ethereum transaction {:#x} claiming withdrawal {}.",
+            tx_hash,
+            event_index
+        );
+        let row = self
+            .client
+            .query_one(
+                &self.prepared_statements.insert_claim_tx,
+                &[
+                    &tx_hash.as_bytes(),
+                    &tx.as_ref(),
+                    &timestamp,
+                    &EthTransactionStatus::Pending,
+                    &(event_index as i64),
+                ],
+            )
+            .await
+            .context("Unable to insert claim transaction.")?;
+        Ok(row.get::<_, i64>("id") as u64)
+    }
+
+    pub async fn mark_claim_completed(
+        &mut self,
+        tx_hash: H256,
+        success: bool,
+        failed_hashes: &[H256],
+    ) -> anyhow::Result<()> {
+        let status = if success {
+            EthTransactionStatus::Confirmed
+        } else {
+            EthTransactionStatus::Failed
+        };
+        let db_tx = self.client.transaction().await?;
+        db_tx
+            .query_one(
+                "UPDATE ethereum_transactions SET status = $2 WHERE tx_hash = $1 RETURNING id;",
+                &[&tx_hash.as_bytes(), &status],
+            )
+            .await?;
+        for failed_tx in failed_hashes {
+            db_tx
+                .query_one(
+                    "UPDATE ethereum_transactions SET status = 'missing' WHERE tx_hash = $1 \
+                     RETURNING id;",
+                    &[&failed_tx.as_bytes()],
+                )
+                .await?;
+        }
+        db_tx.commit().await?;
+        Ok(())
+    }
+
+    /// Get the pending claim transactions, if any. These are all for the same
+    /// withdrawal and the same nonce.
+    pub async fn pending_claim_txs(&self) -> anyhow::Result<Option<PendingClaimTransactions>> {
+        let rows = self
+            .client
+            .query(&self.prepared_statements.get_pending_claim_txs, &[])
+            .await?;
+        let mut event_index = None;
+        let mut txs = Vec::with_capacity(rows.len());
+        for row in rows {
+            let tx_hash: Vec<u8> = row.try_get("tx_hash")?;
+            let tx: Vec<u8> = row.try_get("tx")?;
+            let claimed =
+                row.try_get::<_, Option<i64>>("claim_event_index")?
+                    .context("A claim transaction without an event index.")? as u64;
+            anyhow::ensure!(
+                event_index.is_none() || event_index == Some(claimed),
+                "Multiple pending claims. Database invariant violation"
+            );
+            event_index = Some(claimed);
+            txs.push((
+                H256(tx_hash.try_into().map_err(|_| {
+                    anyhow::anyhow!("Database invariant violation. Hash not 32 bytes")
+                })?),
+                tx.into(),
+            ));
+        }
+        Ok(event_index.map(|event_index| PendingClaimTransactions { txs, event_index }))
+    }
+
+    /// Get the first withdrawal with event index at least `from_event_index`
+    /// that is approved by the current Merkle root, that the user requested
+    /// to be claimed, and which has not yet been claimed or attempted to be
+    /// claimed.
+    pub async fn claimable_withdrawal(
+        &self,
+        from_event_index: u64,
+    ) -> anyhow::Result<Option<ClaimableWithdrawal>> {
+        let Some(row) = self
+            .client
+            .query_opt(
+                &self.prepared_statements.get_claimable_withdrawal,
+                &[&(from_event_index as i64)],
+            )
+            .await?
+        else {
+            return Ok(None);
+        };
+        let tx_hash: Vec<u8> = row.try_get("tx_hash")?;
+        let tx_hash = tx_hash[..].try_into()?;
+        let data: Vec<u8> = row.try_get("event_data")?;
+        let event: WithdrawEvent = contracts_common::from_bytes(&data[..])?;
+        let rows = self
+            .client
+            .query(&self.prepared_statements.get_merkle_leaves, &[])
+            .await?;
+        let mut leaves = Vec::with_capacity(rows.len());
+        for row in rows {
+            let event_index = row.try_get::<_, i64>("event_index")? as u64;
+            let hash: [u8; 32] = row
+                .try_get::<_, Vec<u8>>("event_merkle_hash")?
+                .try_into()
+                .map_err(|_| anyhow::anyhow!("Stored value is not a Merkle leaf hash"))?;
+            leaves.push((event_index, hash));
+        }
+        Ok(Some(ClaimableWithdrawal {
+            tx_hash,
+            event,
+            leaves,
+        }))
+    }
+
+    /// Get the child tokens of all token mappings.
+    pub async fn mapped_tokens(&self) -> anyhow::Result<Vec<ContractAddress>> {
+        let rows = self
+            .client
+            .query("SELECT child_index, child_subindex FROM token_maps;", &[])
+            .await?;
+        let mut result = Vec::with_capacity(rows.len());
+        for row in rows {
+            let index: i64 = row.try_get("child_index")?;
+            let subindex: i64 = row.try_get("child_subindex")?;
+            result.push(ContractAddress::new(index as u64, subindex as u64));
+        }
+        Ok(result)
+    }
+
     pub async fn pending_concordium_txs(
         &self,
     ) -> anyhow::Result<Vec<(TransactionHash, BlockItem<EncodedPayload>)>> {
@@ -685,10 +1181,10 @@ ON CONFLICT (tag) DO UPDATE SET expected_time = $1;
     /// Get pending withdrawals and check that they indeed exist on the chain.
     /// Additionally return the maximum event index of an event that has been
     /// part of a merkle root.
-    pub async fn pending_withdrawals(
+    pub async fn pending_withdrawals<C: ConcordiumNode>(
         &self,
-        mut client: BridgeManagerClient,
-    ) -> anyhow::Result<(Option<u64>, Vec<(TransactionHash, WithdrawEvent)>)> {
+        mut client: BridgeManagerClient<C>,
+    ) -> anyhow::Result<(Option<u64>, Vec<(u64, Leaf)>)> {
         let rows = self
             .client
             .query(&self.prepared_statements.get_pending_withdrawals, &[])
@@ -724,11 +1220,80 @@ ON CONFLICT (tag) DO UPDATE SET expected_time = $1;
                 chain_we == we,
                 "Mismatching withdraw event. The database was tampered with. Aborting."
             );
-            result.push((tx_hash, we))
+            // Withdrawals recorded before the block times were stored are
+            // treated as if they were made now.
+            let time = row
+                .try_get::<_, Option<chrono::DateTime<chrono::Utc>>>("block_time")?
+                .unwrap_or_else(chrono::Utc::now);
+            let token = row
+                .try_get::<_, Option<String>>("eth_name")?
+                .zip(row.try_get::<_, Option<i16>>("decimals")?)
+                .map(|(name, decimals)| (name, decimals as u8));
+            result.push((event_index, Leaf::new(tx_hash, &we, token, time)?))
         }
         Ok((max_sent_event_index, result))
     }
 
+    /// Read the blocklist from the `blocklist` table. Invalid entries are
+    /// skipped with a warning, so that a mistake in the table does not stop
+    /// the relayer.
+    pub async fn blocklist(&self, metrics: &crate::metrics::Metrics) -> anyhow::Result<Blocklist> {
+        let rows = self
+            .client
+            .query("SELECT address FROM blocklist", &[])
+            .await?;
+        let mut blocklist = Blocklist::default();
+        for row in rows {
+            let address = row.try_get::<_, String>("address")?;
+            if let Err(e) = blocklist.insert(address.trim()) {
+                metrics.warnings_total.inc();
+                tracing::warn!("Ignoring the blocklist entry {address}: {e:#}");
+            }
+        }
+        Ok(blocklist)
+    }
+
+    /// The quarantined deposits with a decision that is not carried out yet,
+    /// ordered by id.
+    pub async fn decided_deposits(&self) -> anyhow::Result<Vec<DecidedDeposit>> {
+        let rows = self
+            .client
+            .query(
+                "SELECT id, origin_tx_hash, origin_event_index, receiver, quarantined_operation, \
+                 quarantine_decision FROM ethereum_deposit_events WHERE quarantine_decision IS \
+                 NOT NULL AND quarantine_resolved_time IS NULL ORDER BY id ASC;",
+                &[],
+            )
+            .await?;
+        let mut out = Vec::with_capacity(rows.len());
+        for row in rows {
+            let origin_tx_hash = row.try_get::<_, Vec<u8>>("origin_tx_hash")?;
+            let receiver = row
+                .try_get::<_, Option<Vec<u8>>>("receiver")?
+                .map(|receiver| {
+                    receiver
+                        .try_into()
+                        .map(AccountAddress)
+                        .map_err(|_| anyhow::anyhow!("Invalid receiver of a deposit."))
+                })
+                .transpose()?;
+            let operation = row
+                .try_get::<_, Option<Vec<u8>>>("quarantined_operation")?
+                .map(|operation| contracts_common::from_bytes(&operation))
+                .transpose()
+                .context("Invalid operation of a quarantined deposit.")?;
+            out.push(DecidedDeposit {
+                id: row.try_get("id")?,
+                origin_tx_hash: H256::from_slice(&origin_tx_hash),
+                origin_event_index: row.try_get::<_, i64>("origin_event_index")? as u64,
+                receiver,
+                operation,
+                decision: row.try_get("quarantine_decision")?,
+            });
+        }
+        Ok(out)
+    }
+
     pub async fn mark_concordium_tx(
         &self,
         tx_hash: TransactionHash,
@@ -744,58 +1309,145 @@ ON CONFLICT (tag) DO UPDATE SET expected_time = $1;
         Ok(rows.is_some())
     }
 
-    #[allow(clippy::too_many_arguments)]
+    #[allow(clippy::too_many_arguments, clippy::type_complexity)]
     pub async fn insert_transactions<P: PayloadLike>(
         &mut self,
         metrics: &crate::metrics::Metrics,
         last_block_number: u64,
-        txs: &[(H256, BlockItem<P>)],
+        txs: &[(H256, BlockItem<P>, tracing::Span)],
         // List of event indexes to mark as "done"
-        wes: &[(H256, u64, U256, TransactionHash, u64, H160, u64)],
-        deposits: &[(H256, u64, U256, H160, H160)],
+        wes: &[(
+            H256,
+            u64,
+            U256,
+            TransactionHash,
+            u64,
+            H160,
+            u64,
+            Option<chrono::DateTime<chrono::Utc>>,
+        )],
+        deposits: &[(
+            H256,
+            u64,
+            U256,
+            H160,
+            AccountAddress,
+            H160,
+            Option<chrono::DateTime<chrono::Utc>>,
+            Option<Vec<u8>>,
+        )],
         // New token maps.
         maps: &[(H160, ContractAddress, String, u8)],
         // Removed token maps.
         unmaps: &[(H160, ContractAddress)],
+        // Ids of the quarantined deposits whose decisions are carried out by
+        // `txs`.
+        resolved: &[i64],
     ) -> anyhow::Result<()> {
         let statements = &self.prepared_statements;
         let db_tx = self.client.transaction().await?;
-        for (origin_tx_hash, tx) in txs {
+        for (origin_tx_hash, tx, _) in txs {
             statements
                 .insert_concordium_tx(&db_tx, origin_tx_hash, tx)
                 .await?;
         }
-        for (origin_tx_hash, origin_event_index, amount, depositor, root_token) in deposits {
+        // Token maps are inserted first so that deposits of newly mapped tokens
+        // are labelled in metrics.
+        for (root, child, eth_name, decimals) in maps {
             db_tx
                 .query(
+                    "INSERT INTO token_maps (root, child_index, child_subindex, eth_name, \
+                     decimals) VALUES ($1 , $2, $3, $4, $5);",
+                    &[
+                        &root.as_bytes(),
+                        &(child.index as i64),
+                        &(child.subindex as i64),
+                        &eth_name,
+                        &(*decimals as i16),
+                    ],
+                )
+                .await?;
+        }
+        let mut deposited_tokens = Vec::with_capacity(deposits.len());
+        for (
+            origin_tx_hash,
+            origin_event_index,
+            amount,
+            depositor,
+            receiver,
+            root_token,
+            lock_time,
+            quarantined_operation,
+        ) in deposits
+        {
+            let row = db_tx
+                .query_one(
                     "INSERT INTO ethereum_deposit_events (origin_tx_hash, origin_event_index, \
-                     amount, depositor, root_token, tx_hash)
-VALUES ($1, $2, $3, $4, $5, (SELECT tx_hash FROM concordium_events
+                     amount, depositor, root_token, lock_time, receiver, quarantined, \
+                     quarantined_operation, tx_hash)
+VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, (SELECT tx_hash FROM concordium_events
                     WHERE concordium_events.origin_event_index = $2
-                    LIMIT 1));",
+                    LIMIT 1))
+RETURNING (SELECT eth_name FROM token_maps WHERE token_maps.root = $5 LIMIT 1);",
                     &[
                         &origin_tx_hash.as_bytes(),
                         &(*origin_event_index as i64),
                         &(amount.to_string()),
                         &depositor.as_bytes(),
                         &root_token.as_bytes(),
+                        lock_time,
+                        &receiver.0.as_slice(),
+                        &quarantined_operation.is_some(),
+                        quarantined_operation,
                     ],
                 )
                 .await?;
+            deposited_tokens.push(row.get::<_, Option<String>>(0));
         }
-        for (tx_hash, id, amount, origin_tx_hash, origin_event_id, receiver, event_index) in wes {
+        if !resolved.is_empty() {
+            db_tx
+                .execute(
+                    "UPDATE ethereum_deposit_events SET quarantine_resolved_time = NOW() WHERE id \
+                     = ANY($1);",
+                    &[&resolved],
+                )
+                .await?;
+        }
+        let mut completed_withdrawals = Vec::with_capacity(wes.len());
+        for (
+            tx_hash,
+            id,
+            amount,
+            origin_tx_hash,
+            origin_event_id,
+            receiver,
+            event_index,
+            claim_time,
+        ) in wes
+        {
             let rv = db_tx
                 .query_opt(
                     &statements.mark_withdrawal_as_completed,
                     &[&tx_hash.as_bytes(), &(*event_index as i64)],
                 )
                 .await?;
-            if rv.is_none() {
-                metrics.errors_total.inc();
-                log::error!(
-                    "Event index {} not in the database. This is a database invariant violation.",
-                    event_index
-                );
+            match rv {
+                None => {
+                    metrics.errors_total.inc();
+                    tracing::error!(
+                        "Event index {} not in the database. This is a database invariant \
+                         violation.",
+                        event_index
+                    );
+                }
+                Some(row) => {
+                    if let (Some(block_time), Some(claim_time)) = (
+                        row.get::<_, Option<chrono::DateTime<chrono::Utc>>>("block_time"),
+                        claim_time,
+                    ) {
+                        completed_withdrawals.push((block_time, *claim_time));
+                    }
+                }
             }
             db_tx
                 .query_opt(
@@ -813,21 +1465,6 @@ VALUES ($1, $2, $3, $4, $5, (SELECT tx_hash FROM concordium_events
                 )
                 .await?;
         }
-        for (root, child, eth_name, decimals) in maps {
-            db_tx
-                .query(
-                    "INSERT INTO token_maps (root, child_index, child_subindex, eth_name, \
-                     decimals) VALUES ($1 , $2, $3, $4, $5);",
-                    &[
-                        &root.as_bytes(),
-                        &(child.index as i64),
-                        &(child.subindex as i64),
-                        &eth_name,
-                        &(*decimals as i16),
-                    ],
-                )
-                .await?;
-        }
         for (root, child) in unmaps {
             db_tx
                 .query(
@@ -850,6 +1487,19 @@ VALUES ($1, $2, $3, $4, $5, (SELECT tx_hash FROM concordium_events
             .await
             .context("Unable to insert processed block.")?;
         db_tx.commit().await?;
+        for token in deposited_tokens {
+            metrics
+                .token_deposits
+                .with_label_values(&[token.as_deref().unwrap_or(UNKNOWN_TOKEN)])
+                .inc();
+        }
+        for (block_time, claim_time) in completed_withdrawals {
+            observe_latency(
+                &metrics.withdrawal_completion_latency,
+                block_time,
+                claim_time,
+            );
+        }
         Ok(())
     }
 
@@ -857,31 +1507,63 @@ VALUES ($1, $2, $3, $4, $5, (SELECT tx_hash FROM concordium_events
         &mut self,
         metrics: &crate::metrics::Metrics,
         block: &BlockInfo,
-        events: &[(TransactionHash, Vec<BridgeEvent>)],
-    ) -> anyhow::Result<Vec<(u64, [u8; 32])>> {
+        events: &[(TransactionHash, Vec<BridgeEvent>, tracing::Span)],
+        admin_events: &[(TransactionHash, Vec<AdminEvent>)],
+    ) -> anyhow::Result<Vec<(u64, Leaf, tracing::Span)>> {
         let statements = &self.prepared_statements;
         let db_tx = self.client.transaction().await?;
         let mut withdraws = Vec::new();
-        for (tx_hash, events) in events {
+        for (tx_hash, events, span) in events {
             for event in events {
-                let mh = if let BridgeEvent::Withdraw(we) = &event {
+                let event_span = tracing::info_span!(
+                    parent: span,
+                    "concordium_event",
+                    event_index = event.event_index()
+                );
+                let leaf = if let BridgeEvent::Withdraw(we) = &event {
                     Some((
                         we.event_index,
-                        crate::merkle::make_event_leaf_hash(*tx_hash, we)?,
+                        Leaf::new(*tx_hash, we, None, block.block_slot_time)?,
                     ))
                 } else {
                     None
                 };
-                let processed = statements
-                    .insert_concordium_event(metrics, &db_tx, tx_hash, event, mh.map(|x| x.1))
+                let (processed, token) = statements
+                    .insert_concordium_event(
+                        metrics,
+                        &db_tx,
+                        tx_hash,
+                        event,
+                        leaf.as_ref().map(|x| x.1.hash),
+                        block.block_slot_time,
+                    )
+                    .instrument(event_span.clone())
                     .await?;
                 if !processed {
-                    if let Some(p) = mh {
-                        withdraws.push(p);
+                    if let Some((event_index, leaf)) = leaf {
+                        withdraws.push((event_index, Leaf { token, ..leaf }, event_span));
                     };
                 }
             }
         }
+        for (tx_hash, events) in admin_events {
+            for event in events {
+                db_tx
+                    .execute(
+                        &statements.insert_admin_event,
+                        &[
+                            &tx_hash.as_ref(),
+                            &(event.contract.index as i64),
+                            &(event.contract.subindex as i64),
+                            &(event.log_index as i64),
+                            &event.action,
+                            &event.data,
+                            &block.block_slot_time,
+                        ],
+                    )
+                    .await?;
+            }
+        }
         db_tx
             .query_opt(
                 "INSERT INTO checkpoints VALUES ('concordium', $1) ON CONFLICT (network) DO \
@@ -897,9 +1579,9 @@ VALUES ($1, $2, $3, $4, $5, (SELECT tx_hash FROM concordium_events
     /// Return the maximum nonce of a pending transaction.
     /// This is only intended to be used at program startup and does not handle
     /// disconnects, etc.
-    pub async fn submit_missing_txs(
+    pub async fn submit_missing_txs<C: ConcordiumNode>(
         &self,
-        mut client: v2::Client,
+        mut client: C,
     ) -> anyhow::Result<Option<Nonce>> {
         let txs = self.pending_concordium_txs().await?;
         let mut next_nonce = None;
@@ -910,11 +1592,11 @@ VALUES ($1, $2, $3, $4, $5, (SELECT tx_hash FROM concordium_events
                     match status {
                         Ok(_) => (),
                         Err(e) if e.is_not_found() => {
-                            log::debug!("Submitting missing transaction {}.", tx_hash);
+                            tracing::debug!("Submitting missing transaction {}.", tx_hash);
                             if let Err(e) = client.send_block_item(&tx).await {
                                 if e.is_invalid_argument() {
                                     // Something is wrong with this transaction
-                                    log::error!(
+                                    tracing::error!(
                                         "Unable to resubmit transaction {e:#?}. Marking it as \
                                          failed."
                                     );
@@ -948,11 +1630,14 @@ fn convert_to_token_amount(a: U256) -> cis2::TokenAmount {
 #[derive(Debug)]
 pub enum MerkleUpdate {
     NewWithdraws {
-        withdraws: Vec<(u64, [u8; 32])>,
+        /// Event index, Merkle leaf, and span of the new withdrawals.
+        withdraws: Vec<(u64, Leaf, tracing::Span)>,
     },
     WithdrawalCompleted {
         receiver: H160,
         original_event_index: u64,
+        /// Span of the withdraw event on Ethereum.
+        span: tracing::Span,
     },
 }
 
@@ -970,7 +1655,7 @@ async fn try_reconnect(
             Err(e) if i < MAX_CONNECT_ATTEMPTS => {
                 let delay = std::time::Duration::from_millis(500 * (1 << i));
                 metrics.warnings_total.inc();
-                log::warn!(
+                tracing::warn!(
                     "Could not connect to the database due to {:#}. Reconnecting in {}ms.",
                     e,
                     delay.as_millis()
@@ -980,7 +1665,7 @@ async fn try_reconnect(
             }
             Err(e) => {
                 metrics.errors_total.inc();
-                log::error!(
+                tracing::error!(
                     "Could not connect to the database in {} attempts. Last attempt failed with \
                      reason {:#}.",
                     MAX_CONNECT_ATTEMPTS,
@@ -994,14 +1679,16 @@ async fn try_reconnect(
 }
 
 #[allow(clippy::too_many_arguments)]
-pub async fn handle_database(
+pub async fn handle_database<C: ConcordiumNode>(
     metrics: crate::metrics::Metrics,
     config: tokio_postgres::Config,
     mut db: Database,
     mut blocks: tokio::sync::mpsc::Receiver<DatabaseOperation>,
-    mut bridge_manager: BridgeManager,
-    ccd_transaction_sender: tokio::sync::mpsc::Sender<BlockItem<EncodedPayload>>,
+    mut bridge_manager: BridgeManager<C>,
+    ccd_transaction_sender: tokio::sync::mpsc::Sender<(BlockItem<EncodedPayload>, tracing::Span)>,
     merkle_setter_sender: tokio::sync::mpsc::Sender<MerkleUpdate>,
+    blocklist: BlocklistSource,
+    mut tunables: crate::config::TunablesReceiver,
     mut stop_flag: tokio::sync::watch::Receiver<()>,
 ) -> anyhow::Result<()> {
     let mut retry = None;
@@ -1023,6 +1710,9 @@ pub async fn handle_database(
             }
         };
         let Some(action) = next_item else {break};
+        if tunables.has_changed().unwrap_or(false) {
+            bridge_manager.update_tunables(&tunables.borrow_and_update());
+        }
         match insert_into_db(
             &metrics,
             &mut db,
@@ -1030,16 +1720,17 @@ pub async fn handle_database(
             &merkle_setter_sender,
             &ccd_transaction_sender,
             &mut bridge_manager,
+            &blocklist,
         )
         .await
         {
             Ok(()) => {
-                log::trace!("Processed database operation.");
+                tracing::trace!("Processed database operation.");
             }
             Err(InsertError::Retry(action)) => {
                 let delay = std::time::Duration::from_millis(5000);
                 metrics.warnings_total.inc();
-                log::warn!(
+                tracing::warn!(
                     "Could not insert into the database. Reconnecting in {}ms.",
                     delay.as_millis()
                 );
@@ -1058,7 +1749,7 @@ pub async fn handle_database(
                     Ok(v) => {
                         if let Err(e) = v {
                             metrics.warnings_total.inc();
-                            log::warn!(
+                            tracing::warn!(
                                 "Could not correctly stop the old database connection due to: {}.",
                                 e
                             );
@@ -1067,20 +1758,20 @@ pub async fn handle_database(
                     Err(e) => {
                         if e.is_panic() {
                             metrics.warnings_total.inc();
-                            log::warn!(
+                            tracing::warn!(
                                 "Could not correctly stop the old database connection. The \
                                  connection thread panicked: {e:#}."
                             );
                         } else if !e.is_cancelled() {
                             metrics.warnings_total.inc();
-                            log::warn!("Could not correctly stop the old database connection.");
+                            tracing::warn!("Could not correctly stop the old database connection.");
                         }
                     }
                 }
                 retry = Some(action);
             }
             Err(other) => {
-                log::debug!(
+                tracing::debug!(
                     "One of the internal channels was closed ({:#}). Closing the database worker.",
                     other
                 );
@@ -1095,6 +1786,7 @@ pub async fn handle_database(
 }
 
 #[derive(Debug, thiserror::Error)]
+#[allow(clippy::large_enum_variant)]
 enum InsertError {
     #[error("Other error {0:#}")]
     Other(#[from] anyhow::Error),
@@ -1103,21 +1795,23 @@ enum InsertError {
 }
 
 /// The main worker that does all database operations.
-async fn insert_into_db(
+async fn insert_into_db<C: ConcordiumNode>(
     metrics: &crate::metrics::Metrics,
     db: &mut Database,
     action: DatabaseOperation,
     merkle_setter_sender: &tokio::sync::mpsc::Sender<MerkleUpdate>,
-    ccd_transaction_sender: &tokio::sync::mpsc::Sender<BlockItem<EncodedPayload>>,
-    bridge_manager: &mut BridgeManager,
+    ccd_transaction_sender: &tokio::sync::mpsc::Sender<(BlockItem<EncodedPayload>, tracing::Span)>,
+    bridge_manager: &mut BridgeManager<C>,
+    blocklist: &BlocklistSource,
 ) -> Result<(), InsertError> {
     match action {
         DatabaseOperation::ConcordiumEvents {
             block,
             transaction_events,
+            admin_events,
         } => {
             match db
-                .insert_concordium_events(metrics, &block, &transaction_events)
+                .insert_concordium_events(metrics, &block, &transaction_events, &admin_events)
                 .await
             {
                 Ok(withdraws) => {
@@ -1128,7 +1822,7 @@ async fn insert_into_db(
                             .is_err()
                     {
                         metrics.warnings_total.inc();
-                        log::warn!(
+                        tracing::warn!(
                             "Unable to send new withdraw events to the Merkle updated since the \
                              channel is closed."
                         )
@@ -1136,20 +1830,55 @@ async fn insert_into_db(
                 }
                 Err(e) => {
                     metrics.warnings_total.inc();
-                    log::warn!("Database error when trying to insert Concordium events: {e}.");
+                    tracing::warn!("Database error when trying to insert Concordium events: {e}.");
                     return Err(InsertError::Retry(DatabaseOperation::ConcordiumEvents {
                         block,
                         transaction_events,
+                        admin_events,
                     }));
                 }
             }
         }
         DatabaseOperation::EthereumEvents { events } => {
+            let has_deposits = events
+                .events
+                .iter()
+                .any(|e| matches!(e.event, ethereum::EthEvent::TokenLocked { .. }));
+            let blocklist = match blocklist {
+                BlocklistSource::None => Cow::Owned(Blocklist::default()),
+                BlocklistSource::File(blocklist) => Cow::Borrowed(blocklist),
+                BlocklistSource::Table if !has_deposits => Cow::Owned(Blocklist::default()),
+                BlocklistSource::Table => match db.blocklist(metrics).await {
+                    Ok(blocklist) => Cow::Owned(blocklist),
+                    Err(e) => {
+                        metrics.warnings_total.inc();
+                        tracing::warn!("Database error when trying to read the blocklist: {e}.");
+                        return Err(InsertError::Retry(DatabaseOperation::EthereumEvents {
+                            events,
+                        }));
+                    }
+                },
+            };
+            let decided = match db.decided_deposits().await {
+                Ok(decided) => decided,
+                Err(e) => {
+                    metrics.warnings_total.inc();
+                    tracing::warn!(
+                        "Database error when trying to read the decisions on quarantined \
+                         deposits: {e}."
+                    );
+                    return Err(InsertError::Retry(DatabaseOperation::EthereumEvents {
+                        events,
+                    }));
+                }
+            };
             let mut wes = Vec::new();
             let mut txs = Vec::with_capacity(events.events.len());
             let mut maps = Vec::new();
             let mut unmaps = Vec::new();
             let mut deposits = Vec::new();
+            // Spans of the withdrawals in `wes`, in the same order.
+            let mut withdraw_spans = Vec::new();
             for event in &events.events {
                 match event.event {
                     ethereum::EthEvent::TokenLocked {
@@ -1159,25 +1888,74 @@ async fn insert_into_db(
                         root_token,
                         vault: _,
                         amount,
+                        ref call,
                     } => {
                         metrics.num_deposits.inc();
-                        log::info!("Root Token address {:#?}", root_token);
-                        // Send transaction to Concordium.
+                        tracing::info!(parent: &event.span, "Root Token address {:#?}", root_token);
+                        // Deposits to a contract notify it via the hook.
+                        let (user, hook) = match call {
+                            Some(call) => (
+                                Address::Contract(call.contract),
+                                Some(concordium_contracts::DepositHook {
+                                    entrypoint: call.entrypoint.clone(),
+                                    data: call.data.clone(),
+                                }),
+                            ),
+                            None => (deposit_receiver.into(), None),
+                        };
                         let deposit = concordium_contracts::DepositOperation {
                             id: id.low_u64(),
-                            user: deposit_receiver.into(),
-                            root: root_token.into(),
+                            user,
+                            root: root_token.0.into(),
                             amount: convert_to_token_amount(amount),
                             // TODO: Hardcoded token ID. Works with contracts as they are
                             // now, but is not ideal. But until those contracts are changed not
                             // much to do here.
                             token_id: cis2::TokenId::new_unchecked(vec![0u8; 8]),
+                            hook,
                         };
-                        let update = concordium_contracts::StateUpdate::Deposit(deposit);
-                        if let Some(tx) = bridge_manager.make_state_update_tx(&update).await? {
-                            txs.push((event.tx_hash, tx));
+                        let contract = call.as_ref().map(|call| &call.contract);
+                        if blocklist.is_blocked(&depositor, &deposit_receiver, contract) {
+                            metrics.num_quarantined_deposits.inc();
+                            metrics.warnings_total.inc();
+                            tracing::warn!(
+                                parent: &event.span,
+                                "Deposit {id} from {depositor:#x} to {} is held in quarantine \
+                                 since an address is on the blocklist.",
+                                deposit.user
+                            );
+                            // The operation is kept, so that it is sent as it was made if
+                            // the deposit is released.
+                            deposits.push((
+                                event.tx_hash,
+                                id.low_u64(),
+                                amount,
+                                depositor,
+                                deposit_receiver,
+                                root_token,
+                                events.block_times.get(&event.block_number).copied(),
+                                Some(contracts_common::to_bytes(&deposit)),
+                            ));
+                            continue;
+                        }
+                        // Send transaction to Concordium.
+                        if let Some(tx) = bridge_manager
+                            .make_deposit_tx(deposit, deposit_receiver)
+                            .instrument(event.span.clone())
+                            .await?
+                        {
+                            txs.push((event.tx_hash, tx, event.span.clone()));
                         }
-                        deposits.push((event.tx_hash, id.low_u64(), amount, depositor, root_token));
+                        deposits.push((
+                            event.tx_hash,
+                            id.low_u64(),
+                            amount,
+                            depositor,
+                            deposit_receiver,
+                            root_token,
+                            events.block_times.get(&event.block_number).copied(),
+                            None,
+                        ));
                     }
                     ethereum::EthEvent::TokenMapped {
                         id,
@@ -1190,12 +1968,16 @@ async fn insert_into_db(
                         // Send transaction to Concordium.
                         let map = concordium_contracts::TokenMapOperation {
                             id: id.low_u64(),
-                            root: root_token.into(),
+                            root: root_token.0.into(),
                             child: child_token,
                         };
                         let update = concordium_contracts::StateUpdate::TokenMap(map);
-                        if let Some(tx) = bridge_manager.make_state_update_tx(&update).await? {
-                            txs.push((event.tx_hash, tx));
+                        if let Some(tx) = bridge_manager
+                            .make_state_update_tx(&update)
+                            .instrument(event.span.clone())
+                            .await?
+                        {
+                            txs.push((event.tx_hash, tx, event.span.clone()));
                         }
                         maps.push((root_token, child_token, name.clone(), decimals));
                     }
@@ -1207,7 +1989,10 @@ async fn insert_into_db(
                     } => {
                         // Do nothing at present. Manual intervention needed.
                         metrics.errors_total.inc();
-                        log::error!("Token {id} ({root_token} -> {child_token}) unmapped.");
+                        tracing::error!(
+                            parent: &event.span,
+                            "Token {id} ({root_token} -> {child_token}) unmapped."
+                        );
                         unmaps.push((root_token, child_token));
                     }
                     ethereum::EthEvent::Withdraw {
@@ -1227,10 +2012,82 @@ async fn insert_into_db(
                             origin_event_index,
                             receiver,
                             origin_event_index,
+                            events.block_times.get(&event.block_number).copied(),
                         ));
+                        withdraw_spans.push(event.span.clone());
                     }
                 }
             }
+            // The other operations of the state sender are skipped, so that the
+            // bridge manager moves the watermark of the processed operations
+            // past them.
+            for chunk in events.skipped.chunks(MAX_SKIPPED_PER_UPDATE) {
+                let ids: Vec<u64> = chunk.iter().map(|(_, id)| *id).collect();
+                let span = tracing::info_span!(
+                    parent: None,
+                    "skip_operations",
+                    first_id = ids[0],
+                    last_id = ids[ids.len() - 1],
+                );
+                let update =
+                    concordium_contracts::StateUpdate::Skip(concordium_contracts::SkipOperations {
+                        ids,
+                    });
+                if let Some(tx) = bridge_manager
+                    .make_state_update_tx(&update)
+                    .instrument(span.clone())
+                    .await?
+                {
+                    // The transaction is recorded as caused by the last skipped operation.
+                    txs.push((chunk[chunk.len() - 1].0, tx, span));
+                }
+            }
+            // Carry out the decisions on quarantined deposits. A released deposit
+            // is sent as it was made, and the operation of a rejected deposit is
+            // skipped.
+            let mut resolved = Vec::with_capacity(decided.len());
+            for deposit in decided {
+                let span = tracing::info_span!(
+                    parent: None,
+                    "quarantine_decision",
+                    id = deposit.origin_event_index,
+                    decision = ?deposit.decision,
+                );
+                tracing::info!(parent: &span, "Carrying out the decision on a quarantined deposit.");
+                let tx = match (deposit.decision, deposit.operation, deposit.receiver) {
+                    (QuarantineDecision::Release, Some(operation), Some(receiver)) => {
+                        bridge_manager
+                            .make_deposit_tx(operation, receiver)
+                            .instrument(span.clone())
+                            .await?
+                    }
+                    (QuarantineDecision::Release, ..) => {
+                        metrics.errors_total.inc();
+                        tracing::error!(
+                            parent: &span,
+                            "The released deposit {} was quarantined before its operation was \
+                             recorded, and must be sent manually.",
+                            deposit.origin_event_index
+                        );
+                        None
+                    }
+                    (QuarantineDecision::Reject, ..) => {
+                        let update = concordium_contracts::StateUpdate::Skip(
+                            concordium_contracts::SkipOperations {
+                                ids: vec![deposit.origin_event_index],
+                            },
+                        );
+                        bridge_manager
+                            .make_state_update_tx(&update)
+                            .instrument(span.clone())
+                            .await?
+                    }
+                };
+                if let Some(tx) = tx {
+                    txs.push((deposit.origin_tx_hash, tx, span));
+                }
+                resolved.push(deposit.id);
+            }
 
             match db
                 .insert_transactions(
@@ -1241,22 +2098,26 @@ async fn insert_into_db(
                     &deposits,
                     &maps,
                     &unmaps,
+                    &resolved,
                 )
                 .await
             {
                 Ok(()) => {
-                    for (_, _, _, _, _, receiver, we) in wes {
+                    for ((_, _, _, _, _, receiver, we, _), span) in
+                        wes.into_iter().zip(withdraw_spans)
+                    {
                         if merkle_setter_sender
                             .send(MerkleUpdate::WithdrawalCompleted {
                                 original_event_index: we,
                                 receiver,
+                                span,
                             })
                             .await
                             .is_err()
                         {
                             {
                                 metrics.warnings_total.inc();
-                                log::warn!(
+                                tracing::warn!(
                                     "Unable to send completed withdrawal to the Merkle updater. \
                                      The channel is closed."
                                 )
@@ -1266,7 +2127,7 @@ async fn insert_into_db(
                 }
                 Err(e) => {
                     metrics.warnings_total.inc();
-                    log::warn!("Database error when trying to insert transactions: {e}.");
+                    tracing::warn!("Database error when trying to insert transactions: {e}.");
                     return Err(InsertError::Retry(DatabaseOperation::EthereumEvents {
                         events,
                     }));
@@ -1275,26 +2136,30 @@ async fn insert_into_db(
 
             // We have now written all the transactions to the database. Now send them to
             // the Concordium node.
-            for (_, tx) in txs {
+            for (_, tx, span) in txs {
                 let hash = tx.hash();
-                if ccd_transaction_sender.send(tx).await.is_err() {
+                if ccd_transaction_sender
+                    .send((tx, span.clone()))
+                    .await
+                    .is_err()
+                {
                     {
                         metrics.warnings_total.inc();
-                        log::warn!(
+                        tracing::warn!(
                             "Unable to send transctions stored in the database to the node since \
                              the channel is closed."
                         )
                     }
                 } else {
-                    log::info!("Enqueued transaction {}.", hash);
+                    tracing::info!(parent: &span, "Enqueued transaction {}.", hash);
                 }
             }
         }
         DatabaseOperation::MarkConcordiumTransaction { tx_hash, state } => {
-            log::debug!("Marking {} as {:?}.", tx_hash, state);
+            tracing::debug!("Marking {} as {:?}.", tx_hash, state);
             if let Err(e) = db.mark_concordium_tx(tx_hash, state).await {
                 metrics.warnings_total.inc();
-                log::warn!("Database error: {e}");
+                tracing::warn!("Database error: {e}");
                 return Err(InsertError::Retry(
                     DatabaseOperation::MarkConcordiumTransaction { tx_hash, state },
                 ));
@@ -1305,7 +2170,7 @@ async fn insert_into_db(
                 Ok(txs) => {
                     if response.send(txs).is_err() {
                         metrics.errors_total.inc();
-                        log::error!(
+                        tracing::error!(
                             "Unable to send response to the sender of \
                              GetPendingConcordiumTransactions, indicating they have stopped."
                         );
@@ -1313,7 +2178,7 @@ async fn insert_into_db(
                 }
                 Err(e) => {
                     metrics.warnings_total.inc();
-                    log::warn!(
+                    tracing::warn!(
                         "Database error when trying to get pending Concordium transactions: {e}."
                     );
                     return Err(InsertError::Retry(
@@ -1353,7 +2218,9 @@ async fn insert_into_db(
             {
                 if response.send(tx).is_err() {
                     metrics.errors_total.inc();
-                    log::error!("Unable to send response StoreEthereumTransaction. Continuing.");
+                    tracing::error!(
+                        "Unable to send response StoreEthereumTransaction. Continuing."
+                    );
                 }
             } else {
                 return Err(InsertError::Retry(
@@ -1376,13 +2243,13 @@ async fn insert_into_db(
             failed_hashes,
         } => {
             if db
-                .mark_merkle_root_set(root, &ids, success, tx_hash, &failed_hashes)
+                .mark_merkle_root_set(metrics, root, &ids, success, tx_hash, &failed_hashes)
                 .await
                 .is_ok()
             {
                 if response.send(()).is_err() {
                     metrics.errors_total.inc();
-                    log::error!("Unable to send response MarkSetMerkleCompleted. Continuing.")
+                    tracing::error!("Unable to send response MarkSetMerkleCompleted. Continuing.")
                 }
             } else {
                 return Err(InsertError::Retry(
@@ -1397,6 +2264,76 @@ async fn insert_into_db(
                 ));
             }
         }
+        DatabaseOperation::GetClaimableWithdrawal {
+            from_event_index,
+            response,
+        } => match db.claimable_withdrawal(from_event_index).await {
+            Ok(claim) => {
+                if response.send(claim).is_err() {
+                    metrics.errors_total.inc();
+                    tracing::error!(
+                        "Unable to send response to the sender of GetClaimableWithdrawal, \
+                             indicating they have stopped."
+                    );
+                }
+            }
+            Err(e) => {
+                metrics.warnings_total.inc();
+                tracing::warn!("Database error when trying to get claimable withdrawals: {e}.");
+                return Err(InsertError::Retry(
+                    DatabaseOperation::GetClaimableWithdrawal {
+                        from_event_index,
+                        response,
+                    },
+                ));
+            }
+        },
+        DatabaseOperation::StoreClaimTransaction {
+            tx_hash,
+            tx,
+            event_index,
+            response,
+        } => {
+            if db.insert_claim_tx(tx_hash, &tx, event_index).await.is_ok() {
+                if response.send(tx).is_err() {
+                    metrics.errors_total.inc();
+                    tracing::error!("Unable to send response StoreClaimTransaction. Continuing.");
+                }
+            } else {
+                return Err(InsertError::Retry(
+                    DatabaseOperation::StoreClaimTransaction {
+                        tx_hash,
+                        tx,
+                        event_index,
+                        response,
+                    },
+                ));
+            }
+        }
+        DatabaseOperation::MarkClaimCompleted {
+            tx_hash,
+            success,
+            failed_hashes,
+            response,
+        } => {
+            if db
+                .mark_claim_completed(tx_hash, success, &failed_hashes)
+                .await
+                .is_ok()
+            {
+                if response.send(()).is_err() {
+                    metrics.errors_total.inc();
+                    tracing::error!("Unable to send response MarkClaimCompleted. Continuing.")
+                }
+            } else {
+                return Err(InsertError::Retry(DatabaseOperation::MarkClaimCompleted {
+                    tx_hash,
+                    success,
+                    failed_hashes,
+                    response,
+                }));
+            }
+        }
         DatabaseOperation::SetNextMerkleUpdateTime { next_time } => {
             if db
                 .client
@@ -1412,6 +2349,30 @@ async fn insert_into_db(
                 ));
             }
         }
+        DatabaseOperation::RecordShadowMerkleRoot {
+            root,
+            num_leaves,
+            max_event_index,
+        } => {
+            if let Err(e) = db
+                .client
+                .execute(
+                    &db.prepared_statements.insert_shadow_merkle_root,
+                    &[&&root[..], &(num_leaves as i64), &(max_event_index as i64)],
+                )
+                .await
+            {
+                metrics.warnings_total.inc();
+                tracing::warn!("Database error: {e}");
+                return Err(InsertError::Retry(
+                    DatabaseOperation::RecordShadowMerkleRoot {
+                        root,
+                        num_leaves,
+                        max_event_index,
+                    },
+                ));
+            }
+        }
     }
     Ok(())
 }
@@ -2,7 +2,7 @@ use anyhow::Context;
 use concordium_rust_sdk as concordium;
 use ethabi::{
     ethereum_types::{Address, H256, U256},
-    RawLog,
+    RawLog, Token,
 };
 use ethers::{
     abi::AbiDecode,
@@ -10,12 +10,13 @@ use ethers::{
     types::Log,
 };
 use sha2::Digest;
+use std::collections::BTreeMap;
 
 use crate::{
     db::DatabaseOperation,
     state_sender::{
-        LockedTokenFilter, StateSender, TokenMapAddedFilter, TokenMapRemovedFilter,
-        WithdrawEventFilter,
+        LockedTokenFilter, MerkleRootFilter, StateSender, TokenMapAddedFilter,
+        TokenMapRemovedFilter, VaultRegisteredFilter, WithdrawEventFilter,
     },
 };
 
@@ -27,6 +28,28 @@ pub struct EthBlockEvent {
     pub block_number: u64,
     /// The event.
     pub event: EthEvent,
+    /// Span in which the event is processed by the relayer.
+    pub span: tracing::Span,
+}
+
+impl EthBlockEvent {
+    fn new(tx_hash: H256, block_number: u64, event: EthEvent) -> Self {
+        // Each event starts a new trace, separate from the task that discovered it.
+        let span = tracing::info_span!(
+            parent: None,
+            "ethereum_event",
+            kind = event.kind(),
+            origin_tx_hash = %format_args!("{tx_hash:#x}"),
+            event_id = %event.id(),
+            block_number,
+        );
+        Self {
+            tx_hash,
+            block_number,
+            event,
+            span,
+        }
+    }
 }
 
 /// The relevant events from a single block on Ethereum.
@@ -36,6 +59,81 @@ pub struct EthBlockEvents {
     pub last_number: u64,
     /// Events.
     pub events: Vec<EthBlockEvent>,
+    /// Timestamps of the blocks that contain the events.
+    pub block_times: BTreeMap<u64, chrono::DateTime<chrono::Utc>>,
+    /// Ids of the operations of the state sender that are not state updates
+    /// of the bridge manager, with the hashes of the transactions that
+    /// emitted them, in increasing order of ids. See
+    /// [`get_skipped_operations`].
+    pub skipped: Vec<(H256, u64)>,
+}
+
+/// A contract on Concordium that a deposit is made to, and the entrypoint of
+/// the contract that is notified of the deposit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DepositCall {
+    /// The contract receiving the tokens.
+    pub contract: concordium::types::ContractAddress,
+    /// The entrypoint invoked by the token after minting.
+    pub entrypoint: concordium::smart_contracts::common::OwnedEntrypointName,
+    /// Data passed on to the entrypoint.
+    pub data: Vec<u8>,
+}
+
+/// Encode the `depositData` of a deposit on Ethereum. A plain deposit is
+/// `abi.encode(amount)`. A deposit to a contract on Concordium is
+/// `abi.encode(amount, index, subindex, entrypoint, data)` with types
+/// `(uint256, uint64, uint64, string, bytes)`. Since the vaults only decode
+/// the amount, the additional fields are ignored on Ethereum.
+pub fn encode_deposit_data(amount: U256, call: Option<&DepositCall>) -> Vec<u8> {
+    let mut tokens = vec![Token::Uint(amount)];
+    if let Some(call) = call {
+        tokens.extend([
+            Token::Uint(call.contract.index.into()),
+            Token::Uint(call.contract.subindex.into()),
+            Token::String(call.entrypoint.as_entrypoint_name().to_string()),
+            Token::Bytes(call.data.clone()),
+        ]);
+    }
+    ethabi::encode(&tokens)
+}
+
+/// Decode the `depositData` of a deposit, see [`encode_deposit_data`]. A
+/// malformed call is logged and ignored, so that the tokens are deposited to
+/// the receiving account instead of being stuck.
+fn decode_deposit_data(
+    data: &[u8],
+) -> Result<(U256, Option<DepositCall>), ethers::core::abi::Error> {
+    let amount = data
+        .get(..32)
+        .and_then(|word| U256::decode(word).ok())
+        .ok_or(ethers::core::abi::Error::InvalidData)?;
+    if data.len() == 32 {
+        return Ok((amount, None));
+    }
+    let call = <(U256, u64, u64, String, ethers::types::Bytes)>::decode(data)
+        .map_err(anyhow::Error::from)
+        .and_then(|(_, index, subindex, entrypoint, bytes)| {
+            anyhow::ensure!(
+                bytes.len() <= usize::from(u16::MAX),
+                "The data of the call is too long."
+            );
+            let entrypoint =
+                concordium::smart_contracts::common::OwnedEntrypointName::new(entrypoint)
+                    .map_err(|e| anyhow::anyhow!("Invalid entrypoint: {e}"))?;
+            Ok(DepositCall {
+                contract: concordium::types::ContractAddress::new(index, subindex),
+                entrypoint,
+                data: bytes.to_vec(),
+            })
+        });
+    match call {
+        Ok(call) => Ok((amount, Some(call))),
+        Err(e) => {
+            tracing::warn!("Ignoring the malformed contract call of a deposit: {e:#}");
+            Ok((amount, None))
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -47,6 +145,10 @@ pub enum EthEvent {
         root_token: Address,
         vault: Address,
         amount: U256,
+        /// The contract to deposit to instead of `deposit_receiver`, if any.
+        /// The deposit falls back to `deposit_receiver` if the contract
+        /// cannot receive it.
+        call: Option<DepositCall>,
     },
     TokenMapped {
         id: U256,
@@ -91,6 +193,16 @@ pub enum EthEvent {
 }
 
 impl EthEvent {
+    /// Name of the event for logging.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            EthEvent::TokenLocked { .. } => "deposit",
+            EthEvent::TokenMapped { .. } => "token_map",
+            EthEvent::TokenUnmapped { .. } => "token_unmap",
+            EthEvent::Withdraw { .. } => "withdraw",
+        }
+    }
+
     pub fn id(&self) -> U256 {
         match self {
             EthEvent::TokenLocked { id, .. } => *id,
@@ -105,14 +217,15 @@ impl TryFrom<LockedTokenFilter> for EthEvent {
     type Error = ethers::core::abi::Error;
 
     fn try_from(value: LockedTokenFilter) -> Result<Self, Self::Error> {
+        let (amount, call) = decode_deposit_data(&value.deposit_data)?;
         Ok(Self::TokenLocked {
             id: value.id,
             depositor: value.depositor,
             deposit_receiver: concordium::id::types::AccountAddress(value.deposit_receiver),
             root_token: value.root_token,
             vault: value.vault,
-            amount: U256::decode(value.deposit_data)
-                .map_err(|_| ethers::core::abi::Error::InvalidData)?,
+            amount,
+            call,
         })
     }
 }
@@ -166,14 +279,22 @@ impl TryFrom<WithdrawEventFilter> for EthEvent {
     }
 }
 
+/// Get the events in the blocks `block_number..=upper_block`, and the skipped
+/// operations in the blocks `skip_from_block..=upper_block`.
 async fn get_eth_block_events<M: Middleware + 'static>(
     contract: &StateSender<M>,
     block_number: u64,
     upper_block: u64,
-) -> anyhow::Result<EthBlockEvents> {
+    skip_from_block: u64,
+) -> anyhow::Result<EthBlockEvents>
+where
+    M::Error: 'static,
+{
     let mut retry_num = 0;
     loop {
-        match get_eth_block_events_worker(contract, block_number, upper_block).await {
+        match get_eth_block_events_worker(contract, block_number, upper_block, skip_from_block)
+            .await
+        {
             Ok(x) => return Ok(x),
             Err(EthereumQueryError::Inconsistency) => {
                 anyhow::bail!(
@@ -187,13 +308,15 @@ async fn get_eth_block_events<M: Middleware + 'static>(
             }
             Err(EthereumQueryError::Retryable(e)) => {
                 if retry_num > 6 {
-                    log::error!("Too many failures attempting to query Ethereum events. Aborting.");
+                    tracing::error!(
+                        "Too many failures attempting to query Ethereum events. Aborting."
+                    );
                     anyhow::bail!(
                         "Too many failures attempting to query Ethereum events. Aborting."
                     );
                 } else {
                     let delay = std::time::Duration::from_secs(5 << retry_num);
-                    log::warn!(
+                    tracing::warn!(
                         "Failed getting Ethereum block events due to {e}. Retrying in {} seconds.",
                         delay.as_secs()
                     );
@@ -233,27 +356,75 @@ async fn get_logs_partial<M: Send + std::fmt::Debug + Sync + Middleware + 'stati
     }
     let mut logs = vec![];
     let step = 10_000u64;
-    for step in (from_block..to_block).step_by(step as usize) {
+    for start in (from_block..=to_block).step_by(step as usize) {
         let partial_logs = filter
             .clone()
-            .from_block(from_block + step)
-            .to_block(to_block - step);
-        // dbg!("From Block {} to Block", from_block + step, to_block - step);
+            .from_block(start)
+            .to_block(std::cmp::min(start + step - 1, to_block));
         let mut partial_logs = client.get_logs(&partial_logs).await?;
         logs.append(&mut partial_logs)
     }
     Ok(logs)
 }
 
+/// Get the ids of the operations of the state sender in the given range of
+/// blocks that are not state updates of the bridge manager, i.e., removed token
+/// maps, registrations of vaults, withdrawals and Merkle roots, with the hashes
+/// of the transactions that emitted them. The bridge manager is told to skip
+/// them, since the state sender numbers all its operations with the same
+/// counter, and the bridge manager only moves the watermark of the processed
+/// operations past ids without gaps.
+async fn get_skipped_operations<M: Middleware + 'static>(
+    contract: &StateSender<M>,
+    from_block: u64,
+    to_block: u64,
+) -> Result<Vec<(H256, u64)>, EthereumQueryError>
+where
+    M::Error: 'static,
+{
+    use ethers::contract::EthEvent;
+    let filter = contract.merkle_root_filter().filter.topic0(vec![
+        TokenMapRemovedFilter::signature(),
+        VaultRegisteredFilter::signature(),
+        WithdrawEventFilter::signature(),
+        MerkleRootFilter::signature(),
+    ]);
+    let logs = get_logs_partial(contract, filter, from_block, to_block)
+        .await
+        .context("Unable to get the logs of skipped operations.")?;
+    let mut skipped = Vec::with_capacity(logs.len());
+    for log in logs {
+        if log.removed.unwrap_or(true) {
+            tracing::error!("An event in a confirmed block was removed.");
+            return Err(EthereumQueryError::Inconsistency);
+        }
+        // The id is the first field of all the events, and it is not indexed.
+        let id =
+            match ethers::core::abi::decode(&[ethers::core::abi::ParamType::Uint(256)], &log.data)?
+                .pop()
+            {
+                Some(ethers::core::abi::Token::Uint(id)) => id.low_u64(),
+                _ => return Err(EthereumQueryError::Inconsistency),
+            };
+        let tx_hash = log
+            .transaction_hash
+            .context("The block is confirmed, so transaction should not be pending.")?;
+        skipped.push((tx_hash, id));
+    }
+    skipped.sort_by_key(|(_, id)| *id);
+    Ok(skipped)
+}
+
 async fn get_eth_block_events_worker<M: Middleware + 'static>(
     contract: &StateSender<M>,
     block_number: u64,
     upper_block: u64,
+    skip_from_block: u64,
 ) -> Result<EthBlockEvents, EthereumQueryError>
 where
     M::Error: 'static,
 {
-    log::debug!("Getting block events for blocks at heights {block_number}..={upper_block}.");
+    tracing::debug!("Getting block events for blocks at heights {block_number}..={upper_block}.");
     let client = contract.client();
     let mut events = Vec::new();
     use ethers::contract::EthEvent;
@@ -280,7 +451,7 @@ where
 
         for log in logs {
             if log.removed.unwrap_or(true) {
-                log::error!("An event in a confirmed block was removed.");
+                tracing::error!("An event in a confirmed block was removed.");
                 return Err(EthereumQueryError::Inconsistency);
             }
             let decoded = LockedTokenFilter::decode_log(&RawLog {
@@ -288,17 +459,15 @@ where
                 data: log.data.0.into(),
             })?;
             let root_token = decoded.root_token;
-            let event = EthBlockEvent {
-                tx_hash: log
-                    .transaction_hash
+            let event = EthBlockEvent::new(
+                log.transaction_hash
                     .context("The block is confirmed, so transaction should not be pending.")?,
-                block_number: log
-                    .block_number
+                log.block_number
                     .context("Transaction is confirmed, so must have block number.")?
                     .as_u64(),
-                event: decoded.try_into()?,
-            };
-            log::debug!(
+                decoded.try_into()?,
+            );
+            tracing::debug!(
                 "Discovered new `Locked` event emitted by {:#x} in block number {}. Token = {:#x}.",
                 log.address,
                 event.block_number,
@@ -320,7 +489,7 @@ where
         .context("Unable to MapAdded logs.")?;
         for log in logs {
             if log.removed.unwrap_or(true) {
-                log::error!("An event in a confirmed block was removed.");
+                tracing::error!("An event in a confirmed block was removed.");
                 return Err(EthereumQueryError::Inconsistency);
             }
             let decoded = TokenMapAddedFilter::decode_log(&RawLog {
@@ -328,10 +497,10 @@ where
                 data: log.data.0.into(),
             })?;
             let (name, decimals) = if decoded.token_type == sha3::Keccak256::digest("Ether")[..] {
-                log::debug!("New mapping for ETH.");
+                tracing::debug!("New mapping for ETH.");
                 ("ETH".into(), 18)
             } else {
-                log::debug!("New mapping for ERC20 token at {:#x}.", decoded.root_token);
+                tracing::debug!("New mapping for ERC20 token at {:#x}.", decoded.root_token);
                 let contract = crate::erc20::Erc20::new(decoded.root_token, client.clone());
                 let name = contract
                     .symbol()
@@ -345,17 +514,15 @@ where
                     .context("Unable to get decimals of a token.")?;
                 (name, decimals)
             };
-            let event = EthBlockEvent {
-                tx_hash: log
-                    .transaction_hash
+            let event = EthBlockEvent::new(
+                log.transaction_hash
                     .context("The block is confirmed, so transaction should not be pending.")?,
-                block_number: log
-                    .block_number
+                log.block_number
                     .context("Transaction is confirmed, so must have block number.")?
                     .as_u64(),
-                event: (decoded, name, decimals).into(),
-            };
-            log::debug!(
+                (decoded, name, decimals).into(),
+            );
+            tracing::debug!(
                 "Discovered new `TokenMapAdded` event emitted by {:#x} in block {}.",
                 log.address,
                 event.block_number
@@ -376,24 +543,22 @@ where
         .context("Unable to get MapRemoved logs.")?;
         for log in logs {
             if log.removed.unwrap_or(true) {
-                log::error!("An event in a confirmed block was removed.");
+                tracing::error!("An event in a confirmed block was removed.");
                 return Err(EthereumQueryError::Inconsistency);
             }
             let decoded = TokenMapRemovedFilter::decode_log(&RawLog {
                 topics: log.topics,
                 data: log.data.0.into(),
             })?;
-            let event = EthBlockEvent {
-                tx_hash: log
-                    .transaction_hash
+            let event = EthBlockEvent::new(
+                log.transaction_hash
                     .context("The block is confirmed, so transaction should not be pending.")?,
-                block_number: log
-                    .block_number
+                log.block_number
                     .context("Transaction is confirmed, so must have block number.")?
                     .as_u64(),
-                event: decoded.into(),
-            };
-            log::debug!(
+                decoded.into(),
+            );
+            tracing::debug!(
                 "Discovered new `TokenMapRemoved` event emitted by {:#x} in block {}.",
                 log.address,
                 event.block_number
@@ -413,24 +578,22 @@ where
         .context("Unable to get Withdraw logs.")?;
         for log in logs {
             if log.removed.unwrap_or(true) {
-                log::error!("An event in a confirmed block was removed.");
+                tracing::error!("An event in a confirmed block was removed.");
                 return Err(EthereumQueryError::Inconsistency);
             }
             let decoded = WithdrawEventFilter::decode_log(&RawLog {
                 topics: log.topics,
                 data: log.data.0.into(),
             })?;
-            let event = EthBlockEvent {
-                tx_hash: log
-                    .transaction_hash
+            let event = EthBlockEvent::new(
+                log.transaction_hash
                     .context("The block is confirmed, so transaction should not be pending.")?,
-                block_number: log
-                    .block_number
+                log.block_number
                     .context("Transaction is confirmed, so must have block number.")?
                     .as_u64(),
-                event: decoded.try_into()?,
-            };
-            log::debug!(
+                decoded.try_into()?,
+            );
+            tracing::debug!(
                 "Discovered new `WithdrawEvent` event emitted by {:#x} in block {}.",
                 log.address,
                 event.block_number
@@ -439,16 +602,45 @@ where
         }
     }
     // Sort events by increasing ids so we have a consistent view in the database.
-    events.sort_by(|x, y| x.event.id().cmp(&y.event.id()));
+    events.sort_by_key(|x| x.event.id());
+    // Timestamps of the blocks are used for latency metrics.
+    let mut block_times = BTreeMap::new();
+    for event in &events {
+        if let std::collections::btree_map::Entry::Vacant(entry) =
+            block_times.entry(event.block_number)
+        {
+            let block = client
+                .get_block(event.block_number)
+                .await
+                .context("Unable to get block.")?
+                .context("The block is confirmed, so it should exist.")?;
+            let Some(time) =
+                chrono::NaiveDateTime::from_timestamp_opt(block.timestamp.low_u64() as i64, 0)
+            else {
+                tracing::error!("Block {} has an invalid timestamp.", event.block_number);
+                return Err(EthereumQueryError::Inconsistency);
+            };
+            entry.insert(chrono::DateTime::from_utc(time, chrono::Utc));
+        }
+    }
+    let skipped = get_skipped_operations(contract, skip_from_block, upper_block).await?;
     Ok(EthBlockEvents {
         events,
         last_number: upper_block,
+        block_times,
+        skipped,
     })
 }
 
 /// Write "finalized" ethereum blocks to the provided channel.
 /// Finalized is determined by `num_confirmations`, which counts the number of
 /// descentants that must exist before a block is considered final.
+///
+/// The operations to skip in the bridge manager are looked up from
+/// `skip_from_block`, if it is earlier than `block_number`, in the first
+/// query. This is used to skip the operations emitted before the bridge manager
+/// kept a watermark of the processed operations.
+#[allow(clippy::too_many_arguments)]
 pub async fn watch_eth_blocks<M: Middleware + 'static>(
     metrics: crate::metrics::Metrics,
     contract: StateSender<M>,
@@ -456,6 +648,7 @@ pub async fn watch_eth_blocks<M: Middleware + 'static>(
     mut block_number: u64,
     mut upper_block: u64,
     num_confirmations: u64,
+    skip_from_block: Option<u64>,
 ) -> anyhow::Result<()>
 where
     M::Error: 'static,
@@ -463,6 +656,7 @@ where
     let mut interval = tokio::time::interval(std::time::Duration::from_millis(5000));
     interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
     let client = contract.client();
+    let mut skip_from_block = skip_from_block.map_or(block_number, |b| b.min(block_number));
     loop {
         let mut retry_num = 0;
         let number = loop {
@@ -471,19 +665,20 @@ where
                 Err(e) => {
                     if retry_num <= 6 {
                         metrics.warnings_total.inc();
-                        log::warn!("Failed querying block number. Will retry.");
+                        tracing::warn!("Failed querying block number. Will retry.");
                         tokio::time::sleep(std::time::Duration::from_secs(1 << retry_num)).await;
                         retry_num += 1;
                     } else {
                         metrics.errors_total.inc();
-                        log::error!("Too many retries trying to get block number.");
+                        tracing::error!("Too many retries trying to get block number.");
                         return Err(e.into());
                     }
                 }
             }
         };
         if block_number.saturating_add(num_confirmations) <= number.as_u64() {
-            let block_events = get_eth_block_events(&contract, block_number, upper_block).await?;
+            let block_events =
+                get_eth_block_events(&contract, block_number, upper_block, skip_from_block).await?;
             metrics.ethereum_height.set(upper_block as i64);
             actions_channel
                 .send(DatabaseOperation::EthereumEvents {
@@ -492,6 +687,7 @@ where
                 .await?;
             block_number = upper_block + 1;
             upper_block = block_number;
+            skip_from_block = block_number;
         } else {
             // else wait for the next block.
             interval.tick().await;
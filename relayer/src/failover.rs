@@ -0,0 +1,825 @@
+//! Failover between several endpoints of the same chain.
+//!
+//! The relayer can be given several Concordium nodes and several Ethereum
+//! APIs. Each endpoint is scored by
+//! - how many blocks it is behind the endpoint with the highest block,
+//! - the fraction of recent requests to it that failed, and
+//! - the average latency of recent requests to it,
+//!
+//! and requests are sent to the endpoint with the lowest score. If a request
+//! fails because of the endpoint, e.g., because it is unreachable, the request
+//! is retried on the endpoint with the next lowest score, so a request only
+//! fails if it fails on all endpoints. Errors that do not depend on the
+//! endpoint, such as a rejected transaction, are returned immediately.
+//!
+//! - [`ConcordiumEndpoints`] implements [`ConcordiumNode`].
+//! - [`EthereumEndpoints`] implements [`JsonRpcClient`], so it can be used
+//!   with [`Provider`](ethers::providers::Provider). In quorum mode the
+//!   `eth_getLogs` requests are sent to all endpoints, and the logs are only
+//!   accepted if enough endpoints return the same ones.
+//!
+//! An `eth_getLogs` request is only sent to Ethereum endpoints that have
+//! reached its `toBlock`, since an endpoint that lags behind returns no logs
+//! for the blocks it does not have yet. Such an answer is indistinguishable
+//! from the blocks having no logs, so the events in them would be skipped.
+//!
+//! The heights of the endpoints are updated by [`probe_endpoints`], and by the
+//! answers to `eth_blockNumber` requests.
+use crate::{
+    concordium_node::{ConcordiumNode, FinalizedBlocks},
+    metrics::Metrics,
+};
+use concordium_rust_sdk::{
+    endpoints::{QueryError, QueryResult, RPCError, RPCResult},
+    id::types::AccountAddress,
+    types::{
+        hashes::{BlockHash, TransactionHash},
+        queries::{AccountNonceResponse, BlockInfo},
+        smart_contracts::{ContractContext, InvokeContractResult},
+        transactions::{BlockItem, EncodedPayload},
+        AbsoluteBlockHeight, BlockItemSummary, TransactionStatus,
+    },
+    v2::{BlockIdentifier, FinalizedBlockInfo},
+};
+use ethers::{
+    providers::{
+        HttpClientError, HttpRateLimitRetryPolicy, JsonRpcClient, ProviderError, RetryClientError,
+        RetryPolicy,
+    },
+    types::U64,
+};
+use std::{
+    future::Future,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex, MutexGuard,
+    },
+    time::{Duration, Instant},
+};
+
+/// Weight of the latest request in the averages of latency and error rate.
+const SMOOTHING: f64 = 0.2;
+
+/// Penalty, in seconds of latency, for each block an endpoint is behind.
+const LAG_PENALTY: f64 = 1.0;
+
+/// Penalty, in seconds of latency, of an endpoint whose requests all fail.
+const ERROR_PENALTY: f64 = 60.0;
+
+/// Advantage, in seconds of latency, that the endpoint used for the last
+/// request has over the others. This keeps requests from alternating between
+/// endpoints with similar scores.
+const SWITCH_MARGIN: f64 = 0.5;
+
+/// Errors of requests to an endpoint.
+pub trait EndpointError: std::fmt::Display {
+    /// Whether the request failed because of the endpoint, so that it might
+    /// succeed on another one.
+    fn is_endpoint_failure(&self) -> bool;
+}
+
+impl EndpointError for RPCError {
+    fn is_endpoint_failure(&self) -> bool {
+        // These are answers of a working node.
+        let is_not_found =
+            matches!(self, RPCError::CallError(e) if e.code() == tonic::Code::NotFound);
+        !(is_not_found || self.is_invalid_argument() || self.is_duplicate())
+    }
+}
+
+impl EndpointError for QueryError {
+    fn is_endpoint_failure(&self) -> bool {
+        match self {
+            QueryError::RPCError(e) => e.is_endpoint_failure(),
+            QueryError::NotFound => false,
+        }
+    }
+}
+
+impl EndpointError for ProviderError {
+    fn is_endpoint_failure(&self) -> bool {
+        match self {
+            // Error responses are answers of a working node, unless the node is
+            // rate limiting the relayer.
+            ProviderError::JsonRpcClientError(e) => match e.downcast_ref::<HttpClientError>() {
+                Some(e @ HttpClientError::JsonRpcError(_)) => {
+                    HttpRateLimitRetryPolicy.should_retry(e)
+                }
+                _ => true,
+            },
+            _ => true,
+        }
+    }
+}
+
+impl EndpointError for RetryClientError {
+    fn is_endpoint_failure(&self) -> bool {
+        match self {
+            RetryClientError::ProviderError(e) => e.is_endpoint_failure(),
+            _ => true,
+        }
+    }
+}
+
+/// Recent behaviour of an endpoint.
+#[derive(Debug, Default)]
+struct Stats {
+    /// Average latency of requests, in seconds.
+    latency: f64,
+    /// Average fraction of requests that failed.
+    error_rate: f64,
+    /// The last height reported by the endpoint, if any.
+    height: Option<u64>,
+}
+
+impl Stats {
+    /// The score of the endpoint, lower is better. An endpoint that has not
+    /// reported its height is not penalized for lag, but it will have a high
+    /// error rate if it is unreachable.
+    fn score(&self, best_height: Option<u64>) -> f64 {
+        let lag = match (best_height, self.height) {
+            (Some(best), Some(height)) => best.saturating_sub(height),
+            _ => 0,
+        };
+        self.latency + LAG_PENALTY * lag as f64 + ERROR_PENALTY * self.error_rate
+    }
+}
+
+#[derive(Debug)]
+struct Endpoint<C> {
+    /// Name of the endpoint in logs and metrics. This is not the address of
+    /// the endpoint, since that can contain credentials.
+    name: String,
+    client: C,
+    stats: Mutex<Stats>,
+}
+
+impl<C> Endpoint<C> {
+    fn stats(&self) -> MutexGuard<'_, Stats> {
+        // The lock is never held across panics.
+        self.stats
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// The endpoints of a chain, with the statistics used to choose between them.
+struct Endpoints<C> {
+    /// Name of the chain, for logs and metrics.
+    chain: &'static str,
+    endpoints: Vec<Endpoint<C>>,
+    /// Index of the endpoint that answered the last request.
+    current: AtomicUsize,
+    metrics: Metrics,
+}
+
+impl<C> std::fmt::Debug for Endpoints<C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Endpoints")
+            .field("chain", &self.chain)
+            .field(
+                "endpoints",
+                &self.endpoints.iter().map(|e| &e.name).collect::<Vec<_>>(),
+            )
+            .field("current", &self.current)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<C: Clone> Endpoints<C> {
+    fn new(
+        chain: &'static str,
+        metrics: &Metrics,
+        clients: Vec<(String, C)>,
+    ) -> anyhow::Result<Self> {
+        anyhow::ensure!(
+            !clients.is_empty(),
+            "At least one {chain} endpoint is required."
+        );
+        let endpoints = clients
+            .into_iter()
+            .map(|(name, client)| Endpoint {
+                name,
+                client,
+                stats: Mutex::default(),
+            })
+            .collect();
+        let endpoints = Self {
+            chain,
+            endpoints,
+            current: AtomicUsize::new(0),
+            metrics: metrics.clone(),
+        };
+        endpoints.update_scores();
+        Ok(endpoints)
+    }
+
+    /// The scores of the endpoints, in the order they are configured.
+    fn scores(&self) -> Vec<f64> {
+        let best_height = self.endpoints.iter().filter_map(|e| e.stats().height).max();
+        self.endpoints
+            .iter()
+            .map(|e| e.stats().score(best_height))
+            .collect()
+    }
+
+    fn update_scores(&self) {
+        for (endpoint, score) in self.endpoints.iter().zip(self.scores()) {
+            self.metrics
+                .endpoint_score
+                .with_label_values(&[self.chain, &endpoint.name])
+                .set(score);
+        }
+    }
+
+    /// Indices of the endpoints, in the order they are configured.
+    fn indices(&self) -> Vec<usize> {
+        (0..self.endpoints.len()).collect()
+    }
+
+    /// Indices of the endpoints, best first.
+    fn ranked(&self) -> Vec<usize> {
+        let mut scores = self.scores();
+        scores[self.current.load(Ordering::Relaxed)] -= SWITCH_MARGIN;
+        let mut ranked = (0..scores.len()).collect::<Vec<_>>();
+        ranked.sort_by(|&a, &b| scores[a].total_cmp(&scores[b]));
+        ranked
+    }
+
+    /// Record the outcome of a request to the endpoint with the given index.
+    /// The latency is not known if the endpoint failed outside of a request,
+    /// e.g., if a stream of blocks from it stalled.
+    fn record(&self, index: usize, latency: Option<Duration>, failed: bool) {
+        {
+            let mut stats = self.endpoints[index].stats();
+            if let Some(latency) = latency {
+                stats.latency += SMOOTHING * (latency.as_secs_f64() - stats.latency);
+            }
+            let failed = if failed { 1.0 } else { 0.0 };
+            stats.error_rate += SMOOTHING * (failed - stats.error_rate);
+        }
+        self.update_scores();
+    }
+
+    /// The last height reported by the endpoint with the given index.
+    fn height(&self, index: usize) -> Option<u64> {
+        self.endpoints[index].stats().height
+    }
+
+    /// Record the height reported by the endpoint with the given index.
+    fn record_height(&self, index: usize, height: u64) {
+        self.endpoints[index].stats().height = Some(height);
+        self.update_scores();
+    }
+
+    /// Record the heights reported by the endpoints, in the order they are
+    /// configured.
+    fn record_heights(&self, heights: impl IntoIterator<Item = Option<u64>>) {
+        for (endpoint, height) in self.endpoints.iter().zip(heights) {
+            if height.is_some() {
+                endpoint.stats().height = height;
+            }
+        }
+        self.update_scores();
+    }
+
+    fn switch_to(&self, index: usize) {
+        let previous = self.current.swap(index, Ordering::Relaxed);
+        if previous != index {
+            self.metrics
+                .endpoint_switches
+                .with_label_values(&[self.chain])
+                .inc();
+            tracing::info!(
+                "Switched from {} endpoint {} to endpoint {}.",
+                self.chain,
+                self.endpoints[previous].name,
+                self.endpoints[index].name
+            );
+        }
+    }
+
+    /// Make the request on the endpoints, best first, until it does not fail
+    /// because of the endpoint. Return the result together with the index of
+    /// the endpoint that answered.
+    async fn request<A, E, F, Fut>(&self, request: F) -> Result<(usize, A), E>
+    where
+        E: EndpointError,
+        F: FnMut(C) -> Fut,
+        Fut: Future<Output = Result<A, E>>,
+    {
+        self.request_ranked(self.ranked(), request).await
+    }
+
+    /// Make the request on the endpoints with the given indices, in the given
+    /// order, until it does not fail because of the endpoint. There must be at
+    /// least one index.
+    async fn request_ranked<A, E, F, Fut>(
+        &self,
+        ranked: Vec<usize>,
+        mut request: F,
+    ) -> Result<(usize, A), E>
+    where
+        E: EndpointError,
+        F: FnMut(C) -> Fut,
+        Fut: Future<Output = Result<A, E>>,
+    {
+        let mut last_error = None;
+        for (attempt, &index) in ranked.iter().enumerate() {
+            let endpoint = &self.endpoints[index];
+            let start = Instant::now();
+            match request(endpoint.client.clone()).await {
+                Err(e) if e.is_endpoint_failure() => {
+                    self.record(index, Some(start.elapsed()), true);
+                    if let Some(&next) = ranked.get(attempt + 1) {
+                        self.metrics.warnings_total.inc();
+                        tracing::warn!(
+                            "Request to {} endpoint {} failed: {e:#}. Trying endpoint {}.",
+                            self.chain,
+                            endpoint.name,
+                            self.endpoints[next].name
+                        );
+                    }
+                    last_error = Some(e);
+                }
+                result => {
+                    self.record(index, Some(start.elapsed()), false);
+                    self.switch_to(index);
+                    return result.map(|a| (index, a));
+                }
+            }
+        }
+        Err(last_error.expect("There is at least one endpoint, so there is an error."))
+    }
+
+    /// Make the request on the endpoints with the given indices concurrently.
+    /// The results are in the order of the indices.
+    async fn request_all<A, E, F, Fut>(
+        &self,
+        indices: &[usize],
+        mut request: F,
+    ) -> Vec<Result<A, E>>
+    where
+        E: EndpointError,
+        F: FnMut(C) -> Fut,
+        Fut: Future<Output = Result<A, E>>,
+    {
+        let requests = indices.iter().map(|&index| {
+            let response = request(self.endpoints[index].client.clone());
+            async move {
+                let start = Instant::now();
+                let response = response.await;
+                (start.elapsed(), response)
+            }
+        });
+        let responses = futures::future::join_all(requests).await;
+        responses
+            .into_iter()
+            .zip(indices)
+            .map(|((latency, response), &index)| {
+                let failed = matches!(&response, Err(e) if e.is_endpoint_failure());
+                self.record(index, Some(latency), failed);
+                response
+            })
+            .collect()
+    }
+}
+
+/// Concordium nodes that requests fail over between.
+#[derive(Debug, Clone)]
+pub struct ConcordiumEndpoints<C> {
+    endpoints: Arc<Endpoints<C>>,
+}
+
+impl<C: ConcordiumNode> ConcordiumEndpoints<C> {
+    /// Construct from the named nodes. Nodes with equal scores are preferred
+    /// in the given order.
+    pub fn new(metrics: &Metrics, nodes: Vec<(String, C)>) -> anyhow::Result<Self> {
+        Ok(Self {
+            endpoints: Arc::new(Endpoints::new("Concordium", metrics, nodes)?),
+        })
+    }
+
+    /// Make a request that is not part of [`ConcordiumNode`], failing over
+    /// between the nodes.
+    pub async fn request<A, E, F, Fut>(&self, request: F) -> Result<A, E>
+    where
+        E: EndpointError,
+        F: FnMut(C) -> Fut,
+        Fut: Future<Output = Result<A, E>>,
+    {
+        Ok(self.endpoints.request(request).await?.1)
+    }
+
+    /// Query the last finalized height of all nodes.
+    async fn probe(&self) {
+        let responses = self
+            .endpoints
+            .request_all(&self.endpoints.indices(), |mut node| async move {
+                node.get_last_finalized_block().await
+            })
+            .await;
+        self.endpoints.record_heights(
+            responses
+                .iter()
+                .map(|r| r.as_ref().ok().map(|bi| bi.block_height.height)),
+        );
+    }
+}
+
+/// A stream of finalized blocks from one of the [`ConcordiumEndpoints`]. If
+/// the stream fails or stalls it counts against the node, so that the next
+/// stream is requested from another node if there is a better one.
+pub struct EndpointFinalizedBlocks<C: ConcordiumNode> {
+    blocks: C::FinalizedBlocks,
+    endpoints: Arc<Endpoints<C>>,
+    index: usize,
+}
+
+#[async_trait::async_trait]
+impl<C: ConcordiumNode> FinalizedBlocks for EndpointFinalizedBlocks<C> {
+    async fn next_chunk_timeout(
+        &mut self,
+        n: usize,
+        duration: std::time::Duration,
+    ) -> Result<(bool, Vec<FinalizedBlockInfo>), tokio::time::error::Elapsed> {
+        let result = self.blocks.next_chunk_timeout(n, duration).await;
+        if matches!(result, Ok((true, _)) | Err(_)) {
+            self.endpoints.record(self.index, None, true);
+        }
+        result
+    }
+}
+
+#[async_trait::async_trait]
+impl<C: ConcordiumNode> ConcordiumNode for ConcordiumEndpoints<C> {
+    type FinalizedBlocks = EndpointFinalizedBlocks<C>;
+
+    async fn get_finalized_blocks_from(
+        &mut self,
+        start_height: AbsoluteBlockHeight,
+    ) -> QueryResult<Self::FinalizedBlocks> {
+        let (index, blocks) = self
+            .endpoints
+            .request(|mut node| async move { node.get_finalized_blocks_from(start_height).await })
+            .await?;
+        Ok(EndpointFinalizedBlocks {
+            blocks,
+            endpoints: self.endpoints.clone(),
+            index,
+        })
+    }
+
+    async fn get_block_info(&mut self, block_hash: BlockHash) -> QueryResult<BlockInfo> {
+        self.request(|mut node| async move { node.get_block_info(block_hash).await })
+            .await
+    }
+
+    async fn get_last_finalized_block(&mut self) -> QueryResult<BlockInfo> {
+        self.request(|mut node| async move { node.get_last_finalized_block().await })
+            .await
+    }
+
+    async fn get_block_transaction_events(
+        &mut self,
+        block_hash: BlockHash,
+    ) -> QueryResult<Vec<BlockItemSummary>> {
+        self.request(|mut node| async move { node.get_block_transaction_events(block_hash).await })
+            .await
+    }
+
+    async fn invoke_instance(
+        &mut self,
+        bi: BlockIdentifier,
+        context: &ContractContext,
+    ) -> QueryResult<InvokeContractResult> {
+        self.request(|mut node| async move { node.invoke_instance(bi, context).await })
+            .await
+    }
+
+    async fn send_block_item(
+        &mut self,
+        bi: &BlockItem<EncodedPayload>,
+    ) -> RPCResult<TransactionHash> {
+        self.request(|mut node| async move { node.send_block_item(bi).await })
+            .await
+    }
+
+    async fn get_block_item_status(
+        &mut self,
+        tx_hash: &TransactionHash,
+    ) -> QueryResult<TransactionStatus> {
+        self.request(|mut node| async move { node.get_block_item_status(tx_hash).await })
+            .await
+    }
+
+    async fn get_next_account_sequence_number(
+        &mut self,
+        address: &AccountAddress,
+    ) -> QueryResult<AccountNonceResponse> {
+        self.request(|mut node| async move { node.get_next_account_sequence_number(address).await })
+            .await
+    }
+}
+
+/// Ethereum APIs that requests fail over between.
+#[derive(Debug)]
+pub struct EthereumEndpoints<C> {
+    endpoints: Arc<Endpoints<Arc<C>>>,
+    /// Number of APIs that must return the same logs for them to be accepted.
+    quorum: Option<usize>,
+}
+
+impl<C> Clone for EthereumEndpoints<C> {
+    fn clone(&self) -> Self {
+        Self {
+            endpoints: self.endpoints.clone(),
+            quorum: self.quorum,
+        }
+    }
+}
+
+/// The logs returned by the Ethereum APIs differ, and not enough of them agree.
+#[derive(Debug, thiserror::Error)]
+#[error(
+    "Only {agreeing} of the {quorum} Ethereum endpoints required for a quorum agree on the result \
+     of {method}."
+)]
+pub struct NoQuorum {
+    method: String,
+    agreeing: usize,
+    quorum: usize,
+}
+
+/// None of the Ethereum APIs has reached the last block of a request for logs.
+#[derive(Debug, thiserror::Error)]
+#[error("No Ethereum endpoint has reached block {to_block}, the last block queried by {method}.")]
+pub struct Behind {
+    method: String,
+    to_block: u64,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum EthereumEndpointsError<E> {
+    #[error(transparent)]
+    Endpoint(E),
+    #[error("Unable to (de)serialize the request: {0}")]
+    SerdeJson(#[from] serde_json::Error),
+    #[error(transparent)]
+    NoQuorum(#[from] NoQuorum),
+    #[error(transparent)]
+    Behind(#[from] Behind),
+}
+
+impl<E: Into<ProviderError>> From<EthereumEndpointsError<E>> for ProviderError {
+    fn from(e: EthereumEndpointsError<E>) -> Self {
+        match e {
+            EthereumEndpointsError::Endpoint(e) => e.into(),
+            EthereumEndpointsError::SerdeJson(e) => e.into(),
+            EthereumEndpointsError::NoQuorum(e) => ProviderError::JsonRpcClientError(Box::new(e)),
+            EthereumEndpointsError::Behind(e) => ProviderError::JsonRpcClientError(Box::new(e)),
+        }
+    }
+}
+
+impl<C: JsonRpcClient + 'static> EthereumEndpoints<C>
+where
+    C::Error: EndpointError + Send,
+{
+    /// Construct from the named APIs. APIs with equal scores are preferred in
+    /// the given order. If a `quorum` is given the logs are only accepted if
+    /// at least that many APIs return the same logs.
+    pub fn new(
+        metrics: &Metrics,
+        apis: Vec<(String, C)>,
+        quorum: Option<usize>,
+    ) -> anyhow::Result<Self> {
+        if let Some(quorum) = quorum {
+            anyhow::ensure!(
+                (1..=apis.len()).contains(&quorum),
+                "The Ethereum quorum must be between 1 and the number of Ethereum endpoints, {}.",
+                apis.len()
+            );
+        }
+        let apis = apis
+            .into_iter()
+            .map(|(name, api)| (name, Arc::new(api)))
+            .collect();
+        Ok(Self {
+            endpoints: Arc::new(Endpoints::new("Ethereum", metrics, apis)?),
+            quorum,
+        })
+    }
+
+    /// Make the request on the APIs with the given indices, and return the
+    /// response that at least `quorum` of them agree on. APIs that disagree
+    /// with it count as failed.
+    async fn quorum_request(
+        &self,
+        method: &str,
+        params: &serde_json::Value,
+        indices: &[usize],
+        quorum: usize,
+    ) -> Result<serde_json::Value, EthereumEndpointsError<C::Error>> {
+        let responses = self
+            .endpoints
+            .request_all(indices, |api| {
+                let params = params.clone();
+                async move { api.request::<_, serde_json::Value>(method, params).await }
+            })
+            .await;
+        // The distinct responses, and the APIs that returned them.
+        let mut distinct: Vec<(serde_json::Value, Vec<usize>)> = Vec::new();
+        let mut last_error = None;
+        for (&index, response) in indices.iter().zip(responses) {
+            match response {
+                Ok(value) => match distinct.iter_mut().find(|(v, _)| *v == value) {
+                    Some((_, indices)) => indices.push(index),
+                    None => distinct.push((value, vec![index])),
+                },
+                Err(e) => last_error = Some(e),
+            }
+        }
+        distinct.sort_by_key(|(_, indices)| std::cmp::Reverse(indices.len()));
+        let mut distinct = distinct.into_iter();
+        match distinct.next() {
+            Some((value, agreeing)) if agreeing.len() >= quorum => {
+                for index in distinct.flat_map(|(_, indices)| indices) {
+                    self.endpoints.record(index, None, true);
+                    self.endpoints.metrics.warnings_total.inc();
+                    tracing::warn!(
+                        "Ethereum endpoint {} disagrees with the quorum on the result of {method}.",
+                        self.endpoints.endpoints[index].name
+                    );
+                }
+                Ok(value)
+            }
+            best => {
+                let agreeing = best.map_or(0, |(_, indices)| indices.len());
+                self.endpoints.metrics.quorum_failures.inc();
+                self.endpoints.metrics.warnings_total.inc();
+                tracing::warn!(
+                    "Only {agreeing} of the {quorum} Ethereum endpoints required for a quorum \
+                     agree on the result of {method}."
+                );
+                match last_error {
+                    // All APIs failed, so there is nothing to disagree on.
+                    Some(e) if agreeing == 0 => Err(EthereumEndpointsError::Endpoint(e)),
+                    _ => Err(NoQuorum {
+                        method: method.into(),
+                        agreeing,
+                        quorum,
+                    }
+                    .into()),
+                }
+            }
+        }
+    }
+
+    /// Query the block number of all APIs.
+    async fn probe(&self) {
+        let responses = self
+            .endpoints
+            .request_all(&self.endpoints.indices(), |api| async move {
+                api.request::<_, U64>("eth_blockNumber", ()).await
+            })
+            .await;
+        self.endpoints.record_heights(
+            responses
+                .iter()
+                .map(|r| r.as_ref().ok().map(|n| n.as_u64())),
+        );
+    }
+
+    /// The indices of the APIs, best first, that have reached the given
+    /// height. The heights of the APIs that have not reached it by their last
+    /// probe are queried again, since they are only probed periodically.
+    async fn reached(&self, height: u64) -> Vec<usize> {
+        let has_reached = |index| self.endpoints.height(index).is_some_and(|h| h >= height);
+        let behind = self
+            .endpoints
+            .indices()
+            .into_iter()
+            .filter(|&index| !has_reached(index))
+            .collect::<Vec<_>>();
+        if !behind.is_empty() {
+            let responses = self
+                .endpoints
+                .request_all(&behind, |api| async move {
+                    api.request::<_, U64>("eth_blockNumber", ()).await
+                })
+                .await;
+            for (&index, response) in behind.iter().zip(responses) {
+                if let Ok(number) = response {
+                    self.endpoints.record_height(index, number.as_u64());
+                }
+            }
+        }
+        self.endpoints
+            .ranked()
+            .into_iter()
+            .filter(|&index| has_reached(index))
+            .collect()
+    }
+}
+
+#[async_trait::async_trait]
+impl<C: JsonRpcClient + 'static> JsonRpcClient for EthereumEndpoints<C>
+where
+    C::Error: EndpointError + Send,
+{
+    type Error = EthereumEndpointsError<C::Error>;
+
+    async fn request<T, R>(&self, method: &str, params: T) -> Result<R, Self::Error>
+    where
+        T: std::fmt::Debug + serde::Serialize + Send + Sync,
+        R: serde::de::DeserializeOwned,
+    {
+        let params = serde_json::to_value(params)?;
+        let request = |api: Arc<C>| {
+            let params = params.clone();
+            async move { api.request::<_, serde_json::Value>(method, params).await }
+        };
+        let to_block = match method {
+            "eth_getLogs" => logs_to_block(&params),
+            _ => None,
+        };
+        let value = match (self.quorum, to_block) {
+            (Some(quorum), Some(to_block)) if quorum > 1 => {
+                // APIs that are behind are left out rather than counted as
+                // disagreeing, so they neither make up a quorum on missing
+                // logs nor are penalized for lagging.
+                let reached = self.reached(to_block).await;
+                self.quorum_request(method, &params, &reached, quorum)
+                    .await?
+            }
+            (_, Some(to_block)) => {
+                let reached = self.reached(to_block).await;
+                if reached.is_empty() {
+                    self.endpoints.metrics.warnings_total.inc();
+                    tracing::warn!(
+                        "No Ethereum endpoint has reached block {to_block}, the last block \
+                         queried by {method}."
+                    );
+                    return Err(Behind {
+                        method: method.into(),
+                        to_block,
+                    }
+                    .into());
+                }
+                self.endpoints
+                    .request_ranked(reached, request)
+                    .await
+                    .map_err(EthereumEndpointsError::Endpoint)?
+                    .1
+            }
+            (Some(quorum), None) if quorum > 1 && method == "eth_getLogs" => {
+                let indices = self.endpoints.ranked();
+                self.quorum_request(method, &params, &indices, quorum)
+                    .await?
+            }
+            _ => {
+                let (index, value) = self
+                    .endpoints
+                    .request(request)
+                    .await
+                    .map_err(EthereumEndpointsError::Endpoint)?;
+                if method == "eth_blockNumber" {
+                    if let Ok(number) = serde_json::from_value::<U64>(value.clone()) {
+                        self.endpoints.record_height(index, number.as_u64());
+                    }
+                }
+                value
+            }
+        };
+        Ok(serde_json::from_value(value)?)
+    }
+}
+
+/// The last block queried by the filter of an `eth_getLogs` request, if it is
+/// given by number. Filters by block hash, and tags such as `latest`, do not
+/// refer to a block an endpoint might not have reached.
+fn logs_to_block(params: &serde_json::Value) -> Option<u64> {
+    let to_block = params.get(0)?.get("toBlock")?.as_str()?;
+    u64::from_str_radix(to_block.strip_prefix("0x")?, 16).ok()
+}
+
+/// Periodically query the heights of all endpoints. This keeps their scores up
+/// to date, so that endpoints that fall behind are avoided, and endpoints that
+/// have recovered are used again.
+pub async fn probe_endpoints<C: ConcordiumNode, E: JsonRpcClient + 'static>(
+    concordium: ConcordiumEndpoints<C>,
+    ethereum: EthereumEndpoints<E>,
+    probe_interval: Duration,
+) -> anyhow::Result<()>
+where
+    E::Error: EndpointError + Send,
+{
+    let mut interval = tokio::time::interval(probe_interval);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        interval.tick().await;
+        concordium.probe().await;
+        ethereum.probe().await;
+    }
+}
@@ -0,0 +1,579 @@
+//! The guardian pauses the bridge when something looks wrong, so that a
+//! problem does not get worse while the on-call engineer investigates.
+//!
+//! It uses the keys of an account with the `Pauser` role on the bridge manager
+//! and on the mapped tokens. That role can only pause the contracts, so the
+//! key can be kept online without risking the bridge. Unpausing the contracts
+//! requires the `Admin` role.
+//!
+//! The guardian periodically evaluates the following rules, using the
+//! relayer's database and the Ethereum API.
+//! - `supply`: more tokens were minted on Concordium than were locked on
+//!   Ethereum, counting corrective mints and deducting corrective burns, or
+//!   more tokens were released on Ethereum than were burned on Concordium, or
+//!   a withdrawal was released on Ethereum that was never made on Concordium.
+//! - `reorg`: a deposit that was processed is no longer on Ethereum, or its
+//!   transaction failed, in two consecutive checks.
+//! - `large_deposit`: a deposit is larger than a multiple of the daily
+//!   average of the deposits of the same token.
+//!
+//! When a rule fires, the bridge manager and all mapped tokens are paused, and
+//! the guardian stops evaluating the rules.
+use crate::{
+    concordium_contracts::{CorrectionEvent, DepositEvent},
+    concordium_node::ConcordiumNode,
+    db,
+    health::{Health, GUARDIAN_CHECK},
+    metrics::Metrics,
+};
+use anyhow::Context;
+use concordium_rust_sdk::{
+    common::types::{Amount, TransactionTime},
+    smart_contracts::common as contracts_common,
+    types::{
+        hashes::TransactionHash,
+        smart_contracts::{OwnedParameter, OwnedReceiveName},
+        transactions::{self, UpdateContractPayload},
+        ContractAddress, Energy, WalletAccount,
+    },
+};
+use ethers::prelude::{Middleware, H256};
+use num_bigint::BigUint;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    str::FromStr,
+    sync::Arc,
+    time::Duration,
+};
+
+/// The rules that make the guardian pause the bridge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rule {
+    /// The supply of a token on Concordium does not match Ethereum.
+    Supply,
+    /// A processed deposit is no longer on Ethereum.
+    Reorg,
+    /// A deposit is much larger than usual.
+    LargeDeposit,
+}
+
+impl Rule {
+    /// The name of the rule, used as the label of the alarm metric.
+    pub fn name(self) -> &'static str {
+        match self {
+            Rule::Supply => "supply",
+            Rule::Reorg => "reorg",
+            Rule::LargeDeposit => "large_deposit",
+        }
+    }
+}
+
+/// A rule that fired, and why.
+#[derive(Debug, Clone)]
+pub struct Alarm {
+    pub rule: Rule,
+    pub reason: String,
+}
+
+impl std::fmt::Display for Alarm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.rule.name(), self.reason)
+    }
+}
+
+/// Parameters of the rules.
+#[derive(Debug, Clone)]
+pub struct Rules {
+    /// A deposit fires the `large_deposit` rule if it is larger than this
+    /// multiple of the daily average of the deposits of the same token. The
+    /// rule is not evaluated if this is `None`.
+    pub deposit_multiple: Option<u64>,
+    /// The window over which the daily average of deposits is computed.
+    pub average_window: Duration,
+    /// Deposits in this window are checked to still be on Ethereum.
+    pub reorg_window: Duration,
+}
+
+/// Sends the transactions that pause the bridge.
+#[derive(Debug)]
+pub struct Guardian<C> {
+    client: C,
+    /// Keys of an account with the `Pauser` role on the bridge manager and on
+    /// the mapped tokens.
+    wallet: Arc<WalletAccount>,
+    bridge_manager: ContractAddress,
+    /// Energy of each of the transactions.
+    max_energy: Energy,
+}
+
+impl<C: ConcordiumNode> Guardian<C> {
+    pub fn new(
+        client: C,
+        wallet: WalletAccount,
+        bridge_manager: ContractAddress,
+        max_energy: Energy,
+    ) -> Self {
+        Self {
+            client,
+            wallet: Arc::new(wallet),
+            bridge_manager,
+            max_energy,
+        }
+    }
+
+    /// Pause the bridge manager and the given tokens. Returns the hashes of
+    /// the transactions that were sent.
+    ///
+    /// The nonce is looked up every time, so the account may be used by other
+    /// tools as well.
+    pub async fn pause(
+        &mut self,
+        tokens: &[ContractAddress],
+    ) -> anyhow::Result<Vec<TransactionHash>> {
+        let mut nonce = self
+            .client
+            .get_next_account_sequence_number(&self.wallet.address)
+            .await
+            .context("Unable to get the nonce of the guardian's account.")?
+            .nonce;
+        // Set 1h expiry. The transactions are resent if they do not make it.
+        let expiry =
+            TransactionTime::from_seconds((chrono::Utc::now().timestamp() + 60 * 60) as u64);
+        let contracts = std::iter::once((self.bridge_manager, "bridge-manager.setPaused")).chain(
+            tokens
+                .iter()
+                .map(|token| (*token, "cis2-bridgeable.setPaused")),
+        );
+        let mut hashes = Vec::new();
+        for (address, receive_name) in contracts {
+            let payload = UpdateContractPayload {
+                amount: Amount::from_micro_ccd(0),
+                address,
+                receive_name: OwnedReceiveName::new_unchecked(receive_name.into()),
+                message: OwnedParameter::new_unchecked(contracts_common::to_bytes(&true)),
+            };
+            let tx = transactions::send::update_contract(
+                &*self.wallet,
+                self.wallet.address,
+                nonce,
+                expiry,
+                payload,
+                self.max_energy,
+            );
+            let hash = self
+                .client
+                .send_block_item(&tx.into())
+                .await
+                .with_context(|| format!("Unable to send the transaction pausing {address}."))?;
+            nonce.next_mut();
+            hashes.push(hash);
+        }
+        Ok(hashes)
+    }
+}
+
+/// Totals of the tokens that moved over the bridge, built up incrementally
+/// from the database.
+#[derive(Debug, Default)]
+struct Totals {
+    /// Mapped tokens, by the address of the root token.
+    token_maps: BTreeMap<Vec<u8>, ContractAddress>,
+    /// Amounts locked on Ethereum, by root token.
+    locked: BTreeMap<Vec<u8>, BigUint>,
+    /// Amounts minted on Concordium, by child token. Corrective mints are
+    /// included.
+    minted: BTreeMap<ContractAddress, BigUint>,
+    /// Amounts burned by corrections on Concordium, by child token. They undo
+    /// mints, and are not released on Ethereum.
+    corrected: BTreeMap<ContractAddress, BigUint>,
+    /// Amounts burned on Concordium, by child token.
+    burned: BTreeMap<ContractAddress, BigUint>,
+    /// Amounts released on Ethereum, by child token.
+    released: BTreeMap<ContractAddress, BigUint>,
+    /// Ids of the last rows included in the totals.
+    last_deposit: i64,
+    last_event: i64,
+    last_release: i64,
+}
+
+fn parse_amount(amount: &str) -> anyhow::Result<BigUint> {
+    BigUint::from_str(amount).with_context(|| format!("Invalid amount {amount}."))
+}
+
+fn child_address(index: i64, subindex: i64) -> ContractAddress {
+    ContractAddress::new(index as u64, subindex as u64)
+}
+
+impl Totals {
+    /// Add the rows that were inserted since the last update. Releases that
+    /// do not match a withdrawal are reported as alarms.
+    ///
+    /// The rows are read in a single snapshot so that every mint that is
+    /// seen also has its deposit on Ethereum, and every release has its
+    /// withdrawal on Concordium.
+    async fn update(
+        &mut self,
+        client: &mut tokio_postgres::Client,
+        alarms: &mut Vec<Alarm>,
+    ) -> anyhow::Result<()> {
+        let db_tx = client
+            .build_transaction()
+            .isolation_level(tokio_postgres::IsolationLevel::RepeatableRead)
+            .read_only(true)
+            .start()
+            .await?;
+        let maps = db_tx
+            .query(
+                "SELECT root, child_index, child_subindex FROM token_maps;",
+                &[],
+            )
+            .await?;
+        for row in maps {
+            self.token_maps.insert(
+                row.get("root"),
+                child_address(row.get("child_index"), row.get("child_subindex")),
+            );
+        }
+        let deposits = db_tx
+            .query(
+                "SELECT id, root_token, amount FROM ethereum_deposit_events WHERE id > $1 ORDER \
+                 BY id;",
+                &[&self.last_deposit],
+            )
+            .await?;
+        for row in deposits {
+            *self.locked.entry(row.get("root_token")).or_default() +=
+                parse_amount(row.get("amount"))?;
+            self.last_deposit = row.get("id");
+        }
+        let events = db_tx
+            .query(
+                "SELECT id, event_type::TEXT, event_data, child_index, child_subindex, amount \
+                 FROM concordium_events WHERE id > $1 AND event_type IN ('deposit', 'withdraw', \
+                 'corrective_mint', 'corrective_burn') ORDER BY id;",
+                &[&self.last_event],
+            )
+            .await?;
+        for row in events {
+            let event_type = row.get::<_, String>("event_type");
+            if event_type == "deposit" {
+                // Deposits are stored without the tag of the event.
+                let deposit: DepositEvent =
+                    contracts_common::from_bytes(row.get::<_, &[u8]>("event_data"))
+                        .context("Invalid deposit event in the database.")?;
+                *self.minted.entry(deposit.contract).or_default() += deposit.amount.0;
+            } else if event_type == "corrective_mint" || event_type == "corrective_burn" {
+                let correction: CorrectionEvent =
+                    contracts_common::from_bytes(row.get::<_, &[u8]>("event_data"))
+                        .context("Invalid correction event in the database.")?;
+                let amounts = if event_type == "corrective_mint" {
+                    &mut self.minted
+                } else {
+                    &mut self.corrected
+                };
+                *amounts.entry(correction.token).or_default() += correction.amount.0;
+            } else {
+                let (Some(index), Some(subindex), Some(amount)) = (
+                    row.get::<_, Option<i64>>("child_index"),
+                    row.get::<_, Option<i64>>("child_subindex"),
+                    row.get::<_, Option<&str>>("amount"),
+                ) else {
+                    anyhow::bail!("Withdrawal without a token or an amount in the database.");
+                };
+                *self
+                    .burned
+                    .entry(child_address(index, subindex))
+                    .or_default() += parse_amount(amount)?;
+            }
+            self.last_event = row.get("id");
+        }
+        let releases = db_tx
+            .query(
+                "SELECT w.id, w.amount, w.origin_event_index, c.child_index, c.child_subindex \
+                 FROM ethereum_withdraw_events w LEFT JOIN concordium_events c ON c.event_index \
+                 = w.origin_event_index AND c.event_type = 'withdraw' WHERE w.id > $1 ORDER BY \
+                 w.id;",
+                &[&self.last_release],
+            )
+            .await?;
+        for row in releases {
+            let amount = parse_amount(row.get("amount"))?;
+            match (
+                row.get::<_, Option<i64>>("child_index"),
+                row.get::<_, Option<i64>>("child_subindex"),
+            ) {
+                (Some(index), Some(subindex)) => {
+                    *self
+                        .released
+                        .entry(child_address(index, subindex))
+                        .or_default() += amount;
+                }
+                _ => alarms.push(Alarm {
+                    rule: Rule::Supply,
+                    reason: format!(
+                        "{amount} tokens were released on Ethereum for the withdrawal with event \
+                         index {}, which was not made on Concordium.",
+                        row.get::<_, i64>("origin_event_index")
+                    ),
+                }),
+            }
+            self.last_release = row.get("id");
+        }
+        db_tx.commit().await?;
+        Ok(())
+    }
+
+    /// Compare the totals of the mapped tokens on both chains.
+    fn check(&self, alarms: &mut Vec<Alarm>) {
+        let zero = BigUint::default();
+        for (root, child) in &self.token_maps {
+            let locked = self.locked.get(root).unwrap_or(&zero);
+            let minted = self.minted.get(child).unwrap_or(&zero);
+            let corrected = self.corrected.get(child).unwrap_or(&zero);
+            if minted > &(locked + corrected) {
+                alarms.push(Alarm {
+                    rule: Rule::Supply,
+                    reason: format!(
+                        "{minted} tokens of {child} were minted on Concordium and {corrected} \
+                         were burned by corrections, but only {locked} were locked on Ethereum."
+                    ),
+                });
+            }
+            let burned = self.burned.get(child).unwrap_or(&zero);
+            let released = self.released.get(child).unwrap_or(&zero);
+            if released > burned {
+                alarms.push(Alarm {
+                    rule: Rule::Supply,
+                    reason: format!(
+                        "{released} tokens of {child} were released on Ethereum, but only \
+                         {burned} were burned on Concordium."
+                    ),
+                });
+            }
+        }
+    }
+}
+
+/// State of the guardian between checks.
+#[derive(Debug, Default)]
+struct Checks {
+    totals: Totals,
+    /// Whether the rules were evaluated before. Deposits that were made
+    /// before the guardian started are not checked by the `large_deposit`
+    /// rule.
+    started: bool,
+    /// Transactions of deposits that were missing from Ethereum in the last
+    /// check.
+    missing: BTreeSet<H256>,
+}
+
+impl Checks {
+    /// Evaluate all the rules, and return the alarms.
+    async fn run<M: Middleware>(
+        &mut self,
+        client: &mut tokio_postgres::Client,
+        ethereum_client: &M,
+        rules: &Rules,
+    ) -> anyhow::Result<Vec<Alarm>>
+    where
+        M::Error: 'static,
+    {
+        let mut alarms = Vec::new();
+        let checked_deposit = self.totals.last_deposit;
+        self.totals.update(client, &mut alarms).await?;
+        self.totals.check(&mut alarms);
+        if let Some(multiple) = rules.deposit_multiple {
+            if self.started {
+                large_deposits(
+                    client,
+                    checked_deposit,
+                    self.totals.last_deposit,
+                    multiple,
+                    rules.average_window,
+                    &mut alarms,
+                )
+                .await?;
+            }
+        }
+        self.started = true;
+        let missing = missing_deposits(client, ethereum_client, rules.reorg_window).await?;
+        for hash in missing.intersection(&self.missing) {
+            alarms.push(Alarm {
+                rule: Rule::Reorg,
+                reason: format!(
+                    "The deposit in transaction {hash:#x} was processed, but is no longer on \
+                     Ethereum."
+                ),
+            });
+        }
+        self.missing = missing;
+        Ok(alarms)
+    }
+}
+
+/// Check the deposits with ids in `(from, to]` against the daily average of
+/// the deposits of the same token in the `window` before them. If there are
+/// no earlier deposits in the window the deposit is not checked.
+async fn large_deposits(
+    client: &tokio_postgres::Client,
+    from: i64,
+    to: i64,
+    multiple: u64,
+    window: Duration,
+    alarms: &mut Vec<Alarm>,
+) -> anyhow::Result<()> {
+    let rows = client
+        .query(
+            "SELECT d.origin_tx_hash, d.amount, (SELECT SUM(p.amount::NUMERIC)::TEXT FROM \
+             ethereum_deposit_events p WHERE p.root_token = d.root_token AND p.id < d.id AND \
+             COALESCE(p.lock_time, p.insert_time) >= COALESCE(d.lock_time, d.insert_time) - \
+             make_interval(secs => $3)) AS previous FROM ethereum_deposit_events d WHERE d.id > \
+             $1 AND d.id <= $2 ORDER BY d.id;",
+            &[&from, &to, &window.as_secs_f64()],
+        )
+        .await?;
+    let window_secs = BigUint::from(window.as_secs());
+    for row in rows {
+        let Some(previous) = row.get::<_, Option<&str>>("previous") else {
+            continue;
+        };
+        let previous = parse_amount(previous)?;
+        let amount = parse_amount(row.get("amount"))?;
+        // The deposit is compared with `multiple * previous / days`, where
+        // `days` is the length of the window in days.
+        if &amount * &window_secs > BigUint::from(multiple) * previous * 86400u32 {
+            let hash = H256::from_slice(row.get::<_, &[u8]>("origin_tx_hash"));
+            alarms.push(Alarm {
+                rule: Rule::LargeDeposit,
+                reason: format!(
+                    "The deposit of {amount} in transaction {hash:#x} is more than {multiple} \
+                     times the daily average."
+                ),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Look up the transactions of the recent deposits on Ethereum, and return
+/// those that are no longer there, or failed.
+async fn missing_deposits<M: Middleware>(
+    client: &tokio_postgres::Client,
+    ethereum_client: &M,
+    window: Duration,
+) -> anyhow::Result<BTreeSet<H256>>
+where
+    M::Error: 'static,
+{
+    let rows = client
+        .query(
+            "SELECT DISTINCT origin_tx_hash FROM ethereum_deposit_events WHERE \
+             COALESCE(lock_time, insert_time) >= NOW() - make_interval(secs => $1);",
+            &[&window.as_secs_f64()],
+        )
+        .await?;
+    let mut missing = BTreeSet::new();
+    for row in rows {
+        let hash = H256::from_slice(row.get::<_, &[u8]>("origin_tx_hash"));
+        let receipt = ethereum_client
+            .get_transaction_receipt(hash)
+            .await
+            .context("Unable to look up a deposit on Ethereum.")?;
+        if receipt.is_none_or(|r| r.status == Some(0.into())) {
+            missing.insert(hash);
+        }
+    }
+    Ok(missing)
+}
+
+/// Periodically evaluate the rules, and pause the bridge if any of them fires.
+/// Failures to evaluate the rules or to pause the bridge are retried in the
+/// next interval.
+///
+/// After the bridge is paused the alarms are kept in the readiness report,
+/// and this task does nothing until it is aborted.
+pub async fn guard<C: ConcordiumNode, M: Middleware>(
+    metrics: Metrics,
+    health: Health,
+    mut guardian: Guardian<C>,
+    ethereum_client: M,
+    db_config: tokio_postgres::Config,
+    rules: Rules,
+    check_interval: Duration,
+) -> anyhow::Result<()>
+where
+    M::Error: 'static,
+{
+    health.register_checks(&[GUARDIAN_CHECK]);
+    let mut db_client: Option<tokio_postgres::Client> = None;
+    let mut checks = Checks::default();
+    let mut interval = tokio::time::interval(check_interval);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    let alarms = loop {
+        interval.tick().await;
+        if db_client.as_ref().is_none_or(|c| c.is_closed()) {
+            match db::connect(&db_config).await {
+                // Dropping the handle detaches the task driving the connection.
+                Ok((client, _)) => db_client = Some(client),
+                Err(e) => {
+                    metrics.warnings_total.inc();
+                    tracing::warn!("The guardian is unable to connect to the database: {e:#}");
+                    continue;
+                }
+            }
+        }
+        let Some(client) = db_client.as_mut() else {
+            continue;
+        };
+        match checks.run(client, &ethereum_client, &rules).await {
+            Ok(alarms) if alarms.is_empty() => {
+                health.set_check(GUARDIAN_CHECK, true, serde_json::json!({}));
+            }
+            Ok(alarms) => break alarms,
+            Err(e) => {
+                metrics.warnings_total.inc();
+                tracing::warn!("The guardian is unable to evaluate the rules: {e:#}");
+            }
+        }
+    };
+    for alarm in &alarms {
+        metrics
+            .guardian_alarms
+            .with_label_values(&[alarm.rule.name()])
+            .inc();
+        metrics.errors_total.inc();
+        tracing::error!("Guardian rule {alarm}");
+    }
+    health.set_check(
+        GUARDIAN_CHECK,
+        false,
+        serde_json::json!({
+            "alarms": alarms.iter().map(|a| a.to_string()).collect::<Vec<_>>(),
+        }),
+    );
+    let tokens = checks
+        .totals
+        .token_maps
+        .values()
+        .copied()
+        .collect::<Vec<_>>();
+    loop {
+        match guardian.pause(&tokens).await {
+            Ok(hashes) => {
+                metrics.guardian_paused.set(1);
+                tracing::error!(
+                    "Paused the bridge manager and {} tokens in transactions {hashes:?}. The \
+                     bridge stays paused until an admin unpauses it.",
+                    tokens.len()
+                );
+                break;
+            }
+            Err(e) => {
+                metrics.errors_total.inc();
+                tracing::error!("Unable to pause the bridge: {e:#}");
+            }
+        }
+        interval.tick().await;
+    }
+    std::future::pending().await
+}
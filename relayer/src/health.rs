@@ -0,0 +1,447 @@
+//! Health and readiness of the relayer.
+//!
+//! The state of the relayer is collected in a [`Health`] value shared by all
+//! the tasks. It consists of
+//! - the state of each spawned task, which is tracked by a [`TaskHandle`],
+//! - the outcome of the most recent run of each check. Checks are run
+//!   periodically by [`monitor`], and the balance checks by the tasks that
+//!   query the balances.
+//!
+//! The state is served by [`start_health_server`]. The `/health` endpoint
+//! reports whether all tasks are running, i.e., whether the relayer is alive.
+//! The `/ready` endpoint additionally requires all checks to pass, i.e., that
+//! the relayer is keeping up with both chains.
+use crate::{concordium_node::ConcordiumNode, db, metrics::Metrics};
+use ethers::prelude::Middleware;
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+};
+
+/// Name of the database connectivity check.
+pub const DATABASE_CHECK: &str = "database";
+/// Name of the check of the lag of the Ethereum watcher.
+pub const ETHEREUM_WATCHER_CHECK: &str = "ethereum_watcher";
+/// Name of the check of the lag of the Concordium watcher.
+pub const CONCORDIUM_WATCHER_CHECK: &str = "concordium_watcher";
+/// Name of the check of the age of the oldest pending Concordium transaction.
+pub const PENDING_CONCORDIUM_TXS_CHECK: &str = "pending_concordium_transactions";
+/// Name of the check of the age of the pending Merkle root transaction.
+pub const PENDING_MERKLE_TX_CHECK: &str = "pending_merkle_transaction";
+/// Name of the check of the age of the pending claim transaction.
+pub const PENDING_CLAIM_TX_CHECK: &str = "pending_claim_transaction";
+/// Name of the check of the balance of the Concordium sender account.
+pub const CONCORDIUM_BALANCE_CHECK: &str = "concordium_balance";
+/// Name of the check of the balance of the Ethereum sender account.
+pub const ETHEREUM_BALANCE_CHECK: &str = "ethereum_balance";
+/// Name of the check of the balance of the account used for claiming
+/// withdrawals.
+pub const CLAIM_BALANCE_CHECK: &str = "claim_balance";
+/// Name of the check of the guardian's rules. It fails once a rule fires.
+pub const GUARDIAN_CHECK: &str = "guardian";
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+/// State of a spawned task.
+pub enum TaskStatus {
+    Running,
+    /// The task terminated without an error.
+    Stopped,
+    /// The task terminated with the given error.
+    Failed {
+        error: String,
+    },
+    /// The task was aborted, e.g., during shutdown.
+    Cancelled,
+    Panicked,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+/// The outcome of the most recent run of a check.
+pub struct Check {
+    pub ok: bool,
+    /// Unix timestamp in seconds of the last run. This is `None` if the check
+    /// has not been run yet.
+    pub last_checked: Option<i64>,
+    /// Check specific details, e.g., heights of the chains or the error that
+    /// made the check fail.
+    pub details: serde_json::Value,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+/// The response of the `/health` and `/ready` endpoints.
+pub struct Report {
+    /// Whether the relayer is alive, respectively ready.
+    pub ok: bool,
+    pub tasks: BTreeMap<&'static str, TaskStatus>,
+    /// The checks. These are only included in the readiness report.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checks: Option<BTreeMap<&'static str, Check>>,
+}
+
+#[derive(Default)]
+struct HealthState {
+    tasks: BTreeMap<&'static str, TaskStatus>,
+    checks: BTreeMap<&'static str, Check>,
+}
+
+#[derive(Clone, Default)]
+/// The health of the relayer. This is cheap to clone and all clones share the
+/// same state.
+pub struct Health {
+    inner: Arc<Mutex<HealthState>>,
+}
+
+impl Health {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HealthState> {
+        // The state is always consistent, so we can ignore poisoning.
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Start tracking the task with the given name. The task is reported as
+    /// running until the returned handle is finished or dropped.
+    pub fn task(&self, name: &'static str) -> TaskHandle {
+        self.lock().tasks.insert(name, TaskStatus::Running);
+        TaskHandle {
+            health: self.clone(),
+            name,
+            finished: false,
+        }
+    }
+
+    /// Register checks that are required for readiness. Until they are run
+    /// for the first time they are failing.
+    pub fn register_checks(&self, names: &[&'static str]) {
+        let mut state = self.lock();
+        for name in names {
+            state.checks.entry(*name).or_insert_with(|| Check {
+                ok: false,
+                last_checked: None,
+                details: serde_json::json!({ "error": "Not checked yet." }),
+            });
+        }
+    }
+
+    /// Record the outcome of a check.
+    pub fn set_check(&self, name: &'static str, ok: bool, details: serde_json::Value) {
+        self.lock().checks.insert(
+            name,
+            Check {
+                ok,
+                last_checked: Some(chrono::Utc::now().timestamp()),
+                details,
+            },
+        );
+    }
+
+    /// Whether all the tracked tasks are running.
+    pub fn liveness(&self) -> Report {
+        let state = self.lock();
+        Report {
+            ok: state.tasks.values().all(|s| *s == TaskStatus::Running),
+            tasks: state.tasks.clone(),
+            checks: None,
+        }
+    }
+
+    /// Whether all the tracked tasks are running and all checks pass.
+    pub fn readiness(&self) -> Report {
+        let state = self.lock();
+        let ok = state.tasks.values().all(|s| *s == TaskStatus::Running)
+            && state.checks.values().all(|c| c.ok);
+        Report {
+            ok,
+            tasks: state.tasks.clone(),
+            checks: Some(state.checks.clone()),
+        }
+    }
+}
+
+/// A handle to a tracked task, see [`Health::task`].
+pub struct TaskHandle {
+    health: Health,
+    name: &'static str,
+    finished: bool,
+}
+
+impl TaskHandle {
+    /// Record the result of the task.
+    pub fn finish<E: std::fmt::Display>(mut self, result: &Result<(), E>) {
+        let status = match result {
+            Ok(()) => TaskStatus::Stopped,
+            Err(e) => TaskStatus::Failed {
+                error: format!("{e:#}"),
+            },
+        };
+        self.health.lock().tasks.insert(self.name, status);
+        self.finished = true;
+    }
+}
+
+impl Drop for TaskHandle {
+    /// If the task did not finish then it was either aborted, or it panicked.
+    fn drop(&mut self) {
+        if !self.finished {
+            let status = if std::thread::panicking() {
+                TaskStatus::Panicked
+            } else {
+                TaskStatus::Cancelled
+            };
+            self.health.lock().tasks.insert(self.name, status);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+/// Limits beyond which the checks run by [`monitor`] fail.
+pub struct Limits {
+    /// Maximum number of blocks the Ethereum watcher may be behind the chain,
+    /// in addition to the number of confirmations.
+    pub max_ethereum_lag: u64,
+    /// Maximum number of blocks the Concordium watcher may be behind the last
+    /// finalized block.
+    pub max_concordium_lag: u64,
+    /// Maximum age of pending transactions.
+    pub max_pending_age: std::time::Duration,
+}
+
+/// Periodically check the lag of the watchers, the connection to the database,
+/// and the age of pending transactions, and record the outcomes in `health`.
+///
+/// The heights processed by the watchers are read from the metrics, so the
+/// same `metrics` that are passed to the watchers must be used.
+/// This task only terminates if it is aborted.
+#[allow(clippy::too_many_arguments)]
+pub async fn monitor<C: ConcordiumNode, M: Middleware>(
+    health: Health,
+    metrics: Metrics,
+    mut concordium_client: C,
+    ethereum_client: M,
+    db_config: tokio_postgres::Config,
+    num_confirmations: u64,
+    limits: Limits,
+    check_interval: std::time::Duration,
+) -> anyhow::Result<()> {
+    health.register_checks(&[
+        DATABASE_CHECK,
+        ETHEREUM_WATCHER_CHECK,
+        CONCORDIUM_WATCHER_CHECK,
+        PENDING_CONCORDIUM_TXS_CHECK,
+        PENDING_MERKLE_TX_CHECK,
+        PENDING_CLAIM_TX_CHECK,
+    ]);
+    let mut db_client = None;
+    let mut interval = tokio::time::interval(check_interval);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        interval.tick().await;
+        check_ethereum_watcher(
+            &health,
+            &metrics,
+            &ethereum_client,
+            num_confirmations,
+            &limits,
+        )
+        .await;
+        check_concordium_watcher(&health, &metrics, &mut concordium_client, &limits).await;
+        check_database(&health, &mut db_client, &db_config, &limits).await;
+    }
+}
+
+async fn check_ethereum_watcher<M: Middleware>(
+    health: &Health,
+    metrics: &Metrics,
+    client: &M,
+    num_confirmations: u64,
+    limits: &Limits,
+) {
+    match client.get_block_number().await {
+        Ok(tip) => {
+            let tip = tip.as_u64();
+            let processed = metrics.ethereum_height.get() as u64;
+            let lag = tip.saturating_sub(processed);
+            let max_lag = num_confirmations + limits.max_ethereum_lag;
+            health.set_check(
+                ETHEREUM_WATCHER_CHECK,
+                lag <= max_lag,
+                serde_json::json!({
+                    "tip": tip,
+                    "processed": processed,
+                    "lag": lag,
+                    "max_lag": max_lag,
+                }),
+            );
+        }
+        Err(e) => {
+            health.set_check(
+                ETHEREUM_WATCHER_CHECK,
+                false,
+                serde_json::json!({ "error": format!("Unable to query block number: {e:#}") }),
+            );
+        }
+    }
+}
+
+async fn check_concordium_watcher<C: ConcordiumNode>(
+    health: &Health,
+    metrics: &Metrics,
+    client: &mut C,
+    limits: &Limits,
+) {
+    match client.get_last_finalized_block().await {
+        Ok(bi) => {
+            let tip = bi.block_height.height;
+            let processed = metrics.concordium_height.get() as u64;
+            let lag = tip.saturating_sub(processed);
+            health.set_check(
+                CONCORDIUM_WATCHER_CHECK,
+                lag <= limits.max_concordium_lag,
+                serde_json::json!({
+                    "tip": tip,
+                    "tip_time": bi.block_slot_time.timestamp(),
+                    "processed": processed,
+                    "lag": lag,
+                    "max_lag": limits.max_concordium_lag,
+                }),
+            );
+        }
+        Err(e) => {
+            health.set_check(
+                CONCORDIUM_WATCHER_CHECK,
+                false,
+                serde_json::json!({
+                    "error": format!("Unable to query last finalized block: {e:#}")
+                }),
+            );
+        }
+    }
+}
+
+/// Check that the database is reachable, and record the age of the oldest
+/// pending transactions. The connection is kept between runs, and
+/// re-established if it is closed.
+async fn check_database(
+    health: &Health,
+    db_client: &mut Option<tokio_postgres::Client>,
+    db_config: &tokio_postgres::Config,
+    limits: &Limits,
+) {
+    let pending_checks = [
+        PENDING_CONCORDIUM_TXS_CHECK,
+        PENDING_MERKLE_TX_CHECK,
+        PENDING_CLAIM_TX_CHECK,
+    ];
+    let fail = |error: String| {
+        let details = serde_json::json!({ "error": error });
+        health.set_check(DATABASE_CHECK, false, details.clone());
+        for name in pending_checks {
+            health.set_check(name, false, details.clone());
+        }
+    };
+    if db_client.as_ref().is_none_or(|c| c.is_closed()) {
+        match db::connect(db_config).await {
+            // Dropping the handle detaches the task driving the connection. It
+            // terminates when the client is dropped.
+            Ok((client, _)) => *db_client = Some(client),
+            Err(e) => {
+                *db_client = None;
+                fail(format!("Unable to connect to the database: {e:#}"));
+                return;
+            }
+        }
+    }
+    let Some(client) = db_client.as_ref() else {
+        return;
+    };
+    let row = match client
+        .query_one(
+            "SELECT (SELECT MIN(timestamp) FROM concordium_transactions WHERE status = \
+             'pending'), (SELECT MIN(timestamp) FROM ethereum_transactions WHERE status = \
+             'pending' AND kind = 'merkle_root'), (SELECT MIN(timestamp) FROM \
+             ethereum_transactions WHERE status = 'pending' AND kind = 'claim');",
+            &[],
+        )
+        .await
+    {
+        Ok(row) => row,
+        Err(e) => {
+            fail(format!("Unable to query the database: {e:#}"));
+            return;
+        }
+    };
+    health.set_check(DATABASE_CHECK, true, serde_json::json!({}));
+    let now = chrono::Utc::now().timestamp();
+    let max_age = limits.max_pending_age.as_secs();
+    for (i, name) in pending_checks.into_iter().enumerate() {
+        match row.try_get::<_, Option<i64>>(i) {
+            Ok(Some(timestamp)) => {
+                let age = now.saturating_sub(timestamp).max(0) as u64;
+                health.set_check(
+                    name,
+                    age <= max_age,
+                    serde_json::json!({ "oldest_age": age, "max_age": max_age }),
+                );
+            }
+            Ok(None) => {
+                health.set_check(
+                    name,
+                    true,
+                    serde_json::json!({ "oldest_age": null, "max_age": max_age }),
+                );
+            }
+            Err(e) => {
+                health.set_check(
+                    name,
+                    false,
+                    serde_json::json!({ "error": format!("Unexpected response: {e:#}") }),
+                );
+            }
+        }
+    }
+}
+
+/// Respond with the report, and a status code that reflects whether the report
+/// is ok.
+fn respond(report: Report) -> (axum::http::StatusCode, axum::Json<Report>) {
+    let status = if report.ok {
+        axum::http::StatusCode::OK
+    } else {
+        axum::http::StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, axum::Json(report))
+}
+
+async fn health_handler(
+    axum::extract::State(health): axum::extract::State<Health>,
+) -> (axum::http::StatusCode, axum::Json<Report>) {
+    respond(health.liveness())
+}
+
+async fn ready_handler(
+    axum::extract::State(health): axum::extract::State<Health>,
+) -> (axum::http::StatusCode, axum::Json<Report>) {
+    respond(health.readiness())
+}
+
+/// Start the server for the `/health` and `/ready` endpoints. They respond
+/// with status 200 if the relayer is alive, respectively ready, and 503
+/// otherwise. The body is a JSON [`Report`] in both cases. The task only
+/// terminates if the server terminates, i.e., if it crashes.
+pub async fn start_health_server(addr: std::net::SocketAddr, health: Health) -> anyhow::Result<()> {
+    let app = axum::Router::new()
+        .route("/health", axum::routing::get(health_handler))
+        .route("/ready", axum::routing::get(ready_handler))
+        .with_state(health)
+        .layer(tower_http::timeout::TimeoutLayer::new(
+            std::time::Duration::from_millis(1000),
+        ))
+        .layer(tower_http::limit::RequestBodyLimitLayer::new(0)); // no bodies, we only have GET requests.
+    axum::Server::bind(&addr)
+        .serve(app.into_make_service())
+        .await?;
+    Ok(())
+}
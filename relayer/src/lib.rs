@@ -1,14 +1,28 @@
 pub mod aws_secret_manager;
+pub mod balance;
+pub mod blocklist;
+pub mod claim;
 pub mod concordium_contracts;
+pub mod concordium_node;
+pub mod config;
 pub mod db;
 pub mod ethereum;
+pub mod failover;
+pub mod guardian;
+pub mod health;
 pub mod merkle;
 pub mod metrics;
+pub mod schedule;
+pub mod shadow;
+#[cfg(feature = "test-utils")]
+pub mod simulator;
+pub mod tasks;
+pub mod telemetry;
 
 // These modules are auto-generated, so we don't bother with clippy.
-#[allow(clippy::all)]
+#[allow(clippy::all, mismatched_lifetime_syntaxes)]
 pub mod erc20;
-#[allow(clippy::all)]
+#[allow(clippy::all, mismatched_lifetime_syntaxes)]
 pub mod root_chain_manager;
-#[allow(clippy::all)]
+#[allow(clippy::all, mismatched_lifetime_syntaxes)]
 pub mod state_sender;
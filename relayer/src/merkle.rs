@@ -17,9 +17,12 @@ use rs_merkle::{Hasher, MerkleProof, MerkleTree};
 use std::{collections::BTreeMap, sync::Arc};
 
 use crate::{
+    balance::SendGate,
     concordium_contracts::WithdrawEvent,
+    config::{self, Tunables, TunablesReceiver},
     db::{self, DatabaseOperation, MerkleUpdate, PendingEthereumTransactions},
     root_chain_manager::BridgeManager,
+    schedule::{Backlog, Decision, MerkleSchedule, Reason},
     state_sender,
 };
 
@@ -47,7 +50,9 @@ pub fn make_proof<A: PartialEq + Eq, E>(
 pub struct MerkleData {
     /// The child token address that is being withdrawn.
     pub child_token: ContractAddress,
-    /// The amount of the token that is being withdrawn.
+    /// The amount of the token that is being withdrawn. This is the net
+    /// amount of the withdraw event, without the fee that was taken on
+    /// Concordium.
     pub amount: U256,
     /// The target address of the withdrawal. The Ethereum wallet.
     pub user_wallet: H160,
@@ -69,7 +74,7 @@ impl MerkleData {
         let transaction_event_id = Token::Uint(U256::from(self.transaction_event_id));
         let token_id = Token::Uint(U256::from(self.token_id));
 
-        ethabi::encode(&vec![
+        ethabi::encode(&[
             ccd_index,
             ccd_sub_index,
             amount,
@@ -130,7 +135,7 @@ pub fn build_merkle_tree(
         let data = MerkleData {
             child_token: we.contract,
             amount: convert_from_token_amount(&we.amount),
-            user_wallet: we.eth_address.into(),
+            user_wallet: we.eth_address.eth_address.into(),
             transaction_hash: *transaction_hash,
             transaction_event_id: we.event_index,
             token_id: u64::from_le_bytes(
@@ -159,14 +164,16 @@ pub struct MerkleSetterClient<M: ethers::providers::Middleware, S> {
     /// Next nonce used for sending transactions. This is updated **after** a
     /// pending transaction is confirmed.
     pub next_nonce: U256,
-    /// Minimum number of seconds between merkle root updates.
+    /// Regular time between merkle root updates.
     pub update_interval: std::time::Duration,
+    /// When to send updates early, or defer them.
+    pub schedule: MerkleSchedule,
     /// Interval when we escalate the transaction price.
     pub escalate_interval: std::time::Duration,
     /// Interval when we escalate the transaction price.
     pub warn_duration: std::time::Duration,
-    /// List of event indices and the hashes
-    pub current_leaves: Arc<std::sync::Mutex<BTreeMap<u64, [u8; 32]>>>,
+    /// The leaves of the tree by their event indices.
+    pub current_leaves: Arc<std::sync::Mutex<BTreeMap<u64, Leaf>>>,
     /// The high water mark. The last event that was set in the merkle root.
     /// This is used to skip sending updates when there are no new
     /// withdrawals to be approved.
@@ -180,7 +187,7 @@ pub fn make_event_leaf_hash(
     let data = MerkleData {
         child_token: we.contract,
         amount: convert_from_token_amount(&we.amount),
-        user_wallet: we.eth_address.into(),
+        user_wallet: we.eth_address.eth_address.into(),
         transaction_hash,
         transaction_event_id: we.event_index,
         token_id: u64::from_le_bytes(
@@ -192,6 +199,36 @@ pub fn make_event_leaf_hash(
     Ok(Keccak256Algorithm::hash(&data.encode()))
 }
 
+/// A withdrawal in the Merkle tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Leaf {
+    /// The hash of the leaf, see [`make_event_leaf_hash`].
+    pub hash: [u8; 32],
+    /// The symbol and number of decimals of the withdrawn token, if the token
+    /// is known.
+    pub token: Option<(String, u8)>,
+    /// The withdrawn amount, in the smallest unit of the token.
+    pub amount: U256,
+    /// Time of the block on Concordium that contains the withdrawal.
+    pub time: chrono::DateTime<chrono::Utc>,
+}
+
+impl Leaf {
+    pub fn new(
+        transaction_hash: TransactionHash,
+        we: &WithdrawEvent,
+        token: Option<(String, u8)>,
+        time: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
+            hash: make_event_leaf_hash(transaction_hash, we)?,
+            token,
+            amount: convert_from_token_amount(&we.amount),
+            time,
+        })
+    }
+}
+
 impl<M: ethers::providers::Middleware, S: Signer + Clone> MerkleSetterClient<M, S> {
     #[allow(clippy::too_many_arguments)]
     pub fn new(
@@ -202,7 +239,7 @@ impl<M: ethers::providers::Middleware, S: Signer + Clone> MerkleSetterClient<M,
         next_nonce: U256,
         pending_merkle_set: &Option<db::PendingEthereumTransactions>,
         update_interval: std::time::Duration,
-        pending_withdrawals: Vec<(TransactionHash, WithdrawEvent)>,
+        pending_withdrawals: Vec<(u64, Leaf)>,
         max_marked_event_index: Option<u64>,
         escalate_interval: std::time::Duration,
         warn_duration: std::time::Duration,
@@ -214,7 +251,7 @@ impl<M: ethers::providers::Middleware, S: Signer + Clone> MerkleSetterClient<M,
             let (tx, _) = ethers::types::transaction::eip2718::TypedTransaction::decode_signed(
                 &Rlp::new(data),
             )?;
-            log::debug!(
+            tracing::debug!(
                 "There is a pending Ethereum transaction with hash {:#x}. Using it's nonce as the \
                  next nonce.",
                 tx_hash,
@@ -235,42 +272,64 @@ impl<M: ethers::providers::Middleware, S: Signer + Clone> MerkleSetterClient<M,
             max_gas,
             next_nonce,
             update_interval,
+            schedule: MerkleSchedule::default(),
             current_leaves: Arc::new(std::sync::Mutex::new(BTreeMap::new())),
             max_marked_event_index,
             escalate_interval,
             warn_duration,
         };
-        for (tx_hash, we) in pending_withdrawals {
-            let event_index = we.event_index;
-            let merkle_event_hash = make_event_leaf_hash(tx_hash, &we)?;
-            add_withdraw_event(&msc.current_leaves, event_index, merkle_event_hash)?;
+        for (event_index, leaf) in pending_withdrawals {
+            add_withdraw_event(&msc.current_leaves, event_index, leaf)?;
         }
         Ok(msc)
     }
 }
 
-fn add_withdraw_event(
-    leaves: &Arc<std::sync::Mutex<BTreeMap<u64, [u8; 32]>>>,
+impl<M: ethers::providers::Middleware, S> MerkleSetterClient<M, S> {
+    /// Use the new tunables for the next transactions.
+    pub fn update_tunables(&mut self, tunables: &Tunables) {
+        self.max_gas_price = tunables.max_gas_price;
+        self.max_gas = tunables.max_gas;
+        self.update_interval = tunables.merkle_update_interval;
+        self.schedule = tunables.merkle_schedule.clone();
+        self.escalate_interval = tunables.escalation_interval;
+    }
+
+    /// The withdrawals that are not yet approved.
+    fn backlog(&self) -> anyhow::Result<Backlog> {
+        let leaves = self
+            .current_leaves
+            .lock()
+            .map_err(|_| anyhow::anyhow!("Unable to acquire lock."))?;
+        Ok(self
+            .schedule
+            .backlog(leaves.iter(), self.max_marked_event_index))
+    }
+}
+
+pub(crate) fn add_withdraw_event(
+    leaves: &Arc<std::sync::Mutex<BTreeMap<u64, Leaf>>>,
     event_index: u64,
-    hash: [u8; 32],
-) -> anyhow::Result<(Option<[u8; 32]>, usize)> {
+    leaf: Leaf,
+) -> anyhow::Result<(Option<Leaf>, usize)> {
     let mut lock = leaves
         .lock()
         .map_err(|_| anyhow::anyhow!("Unable to acquire lock."))?;
-    let r = lock.insert(event_index, hash);
+    let r = lock.insert(event_index, leaf);
     Ok((r, lock.len()))
 }
 
 fn remove_withdraw_event(
-    leaves: &Arc<std::sync::Mutex<BTreeMap<u64, [u8; 32]>>>,
+    leaves: &Arc<std::sync::Mutex<BTreeMap<u64, Leaf>>>,
     event_index: u64,
-) -> anyhow::Result<(Option<[u8; 32]>, usize)> {
+) -> anyhow::Result<(Option<Leaf>, usize)> {
     let mut lock = leaves
         .lock()
         .map_err(|_| anyhow::anyhow!("Unable to acquire lock."))?;
     let r = lock.remove(&event_index);
     Ok((r, lock.len()))
 }
+#[allow(clippy::large_enum_variant)]
 pub enum SetMerkleRootResult {
     SetTransaction {
         tx_hash: H256,
@@ -315,8 +374,8 @@ where
             }
             leaves
                 .iter()
-                .map(|(&id, hash)| {
-                    tree.insert(*hash);
+                .map(|(&id, leaf)| {
+                    tree.insert(leaf.hash);
                     id
                 })
                 .collect::<Arc<[_]>>()
@@ -328,7 +387,7 @@ where
                 .get_gas_price()
                 .await
                 .map_err(SetMerkleRootError::Network)?;
-            log::debug!("Current gas price is {}.", current_gas_price);
+            tracing::debug!("Current gas price is {}.", current_gas_price);
             if current_gas_price <= self.max_gas_price {
                 let call = self.root_manager.set_merkle_root(new_root);
                 let tx = call.tx;
@@ -385,6 +444,7 @@ where
 ///
 /// This worker instead monitors the provided `receiver` channel for new Merkle
 /// tree updates to update its in-memory state.
+#[allow(clippy::too_many_arguments)]
 pub async fn send_merkle_root_updates<M: Middleware + 'static, S: Signer + 'static>(
     metrics: crate::metrics::Metrics,
     client: MerkleSetterClient<M, S>,
@@ -392,6 +452,8 @@ pub async fn send_merkle_root_updates<M: Middleware + 'static, S: Signer + 'stat
     mut receiver: tokio::sync::mpsc::Receiver<MerkleUpdate>,
     db_sender: tokio::sync::mpsc::Sender<DatabaseOperation>,
     num_confirmations: u64,
+    gate: SendGate,
+    tunables: TunablesReceiver,
     mut stop: tokio::sync::watch::Receiver<()>,
 ) -> anyhow::Result<()>
 where
@@ -403,13 +465,13 @@ where
     if let Some(PendingEthereumTransactions { txs, root, idxs }) = pending_merkle_set {
         let ethereum_client = client.root_manager.client();
         let mut _txs = vec![];
-        for (mut tx_hash, raw_tx) in txs.into_iter().rev() {
+        for (tx_hash, raw_tx) in txs.into_iter().rev() {
             let status = ethereum_client
                 .get_transaction(tx_hash)
                 .await
                 .context("Unable to get transaction status.")?;
             if status.is_none() {
-                log::info!(
+                tracing::info!(
                     "Transaction with hash {tx_hash:#x} is in the database, but not known to the \
                      Ethereum chain. Submitting it."
                 );
@@ -434,6 +496,8 @@ where
         db_sender,
         pending,
         num_confirmations,
+        gate,
+        tunables,
         stop.clone(),
     ));
     metrics
@@ -449,43 +513,61 @@ where
         v = receiver.recv() => v,
         _ = stop.changed() => None,
     } {
-        match mu {
-            MerkleUpdate::NewWithdraws { withdraws } => {
-                metrics.num_withdrawals.inc_by(withdraws.len() as u64);
-                for (event_index, merkle_hash) in withdraws {
-                    log::debug!("New withdraw event with index {event_index}.");
-                    let (r, new_size) = add_withdraw_event(&leaves, event_index, merkle_hash)?;
-                    metrics.merkle_tree_size.set(new_size as i64);
-                    if r.is_some() {
-                        metrics.warnings_total.inc();
-                        log::warn!(
-                            "Duplicate event index {event_index} added to the withdraw events."
-                        );
-                    }
-                }
-            }
-            MerkleUpdate::WithdrawalCompleted {
-                receiver: _,
-                original_event_index,
-            } => {
-                metrics.num_completed_withdrawals.inc();
-                let (r, new_size) = remove_withdraw_event(&leaves, original_event_index)?;
+        handle_merkle_update(&metrics, &leaves, mu)?;
+    }
+    sender_handle.await??;
+    Ok(())
+}
+
+/// Update the leaves of the Merkle tree with a new withdrawal or a completed
+/// one.
+pub(crate) fn handle_merkle_update(
+    metrics: &crate::metrics::Metrics,
+    leaves: &Arc<std::sync::Mutex<BTreeMap<u64, Leaf>>>,
+    mu: MerkleUpdate,
+) -> anyhow::Result<()> {
+    match mu {
+        MerkleUpdate::NewWithdraws { withdraws } => {
+            metrics.num_withdrawals.inc_by(withdraws.len() as u64);
+            for (event_index, leaf, span) in withdraws {
+                tracing::debug!(parent: &span, "New withdraw event with index {event_index}.");
+                let (r, new_size) = add_withdraw_event(leaves, event_index, leaf)?;
                 metrics.merkle_tree_size.set(new_size as i64);
-                if r.is_none() {
-                    metrics.errors_total.inc();
-                    log::error!(
-                        "An event {original_event_index} marked as withdrawn, but was not known."
+                if r.is_some() {
+                    metrics.warnings_total.inc();
+                    tracing::warn!(
+                        parent: &span,
+                        "Duplicate event index {event_index} added to the withdraw events."
                     );
                 }
             }
         }
+        MerkleUpdate::WithdrawalCompleted {
+            receiver: _,
+            original_event_index,
+            span,
+        } => {
+            metrics.num_completed_withdrawals.inc();
+            tracing::debug!(
+                parent: &span,
+                "Withdrawal with event index {original_event_index} completed."
+            );
+            let (r, new_size) = remove_withdraw_event(leaves, original_event_index)?;
+            metrics.merkle_tree_size.set(new_size as i64);
+            if r.is_none() {
+                metrics.errors_total.inc();
+                tracing::error!(
+                    parent: &span,
+                    "An event {original_event_index} marked as withdrawn, but was not known."
+                );
+            }
+        }
     }
-    sender_handle.await??;
     Ok(())
 }
 
 #[derive(Debug, thiserror::Error)]
-enum EthereumSenderError<M: Middleware> {
+pub(crate) enum EthereumSenderError<M: Middleware> {
     #[error("A network error occurred: {0}")]
     Retryable(<M as Middleware>::Error),
     #[error("An internal parsing error occurred. Aborting: {0}.")]
@@ -519,12 +601,15 @@ struct EthereumPendingTransactions {
     pending_txs: Vec<(H256, ethers::prelude::Bytes)>,
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn ethereum_tx_sender<M: Middleware, S: Signer>(
     metrics: crate::metrics::Metrics,
     mut client: MerkleSetterClient<M, S>,
     db_sender: tokio::sync::mpsc::Sender<DatabaseOperation>,
     mut pending: Option<EthereumPendingTransactions>,
     num_confirmations: u64,
+    mut gate: SendGate,
+    mut tunables: TunablesReceiver,
     mut stop: tokio::sync::watch::Receiver<()>,
 ) -> anyhow::Result<()>
 where
@@ -537,6 +622,8 @@ where
         &db_sender,
         &mut pending,
         num_confirmations,
+        &mut gate,
+        &mut tunables,
         &mut stop,
     )
     .await
@@ -544,7 +631,7 @@ where
         match e {
             EthereumSenderError::Retryable(e) => {
                 metrics.warnings_total.inc();
-                log::warn!(
+                tracing::warn!(
                     "An error occurred when trying to send transactions to Ethereum or query the \
                      sent transaciton. Will attempt again in 10s: {e}"
                 );
@@ -554,7 +641,7 @@ where
             }
             EthereumSenderError::InternalABI(e) => {
                 metrics.errors_total.inc();
-                log::error!(
+                tracing::error!(
                     "Unable to parse responses from Ethereum. This indicates a configuration \
                      error: {e:#}"
                 );
@@ -562,7 +649,7 @@ where
             }
             EthereumSenderError::Internal(e) => {
                 metrics.errors_total.inc();
-                log::error!(
+                tracing::error!(
                     "An unrecoverable error occurred when sending transactions to Ethereum: {e:#}."
                 );
                 return Err(e);
@@ -572,6 +659,44 @@ where
     Ok(())
 }
 
+/// Construct the interval of Merkle root updates, starting one period from
+/// now.
+pub(crate) fn new_send_interval(period: std::time::Duration) -> tokio::time::Interval {
+    let mut send_interval = tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+    send_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    send_interval
+}
+
+/// Record in the database when the next Merkle root update is expected
+/// according to the schedule of the client, given the time of the `last`
+/// update and whether the last regular update was `deferred`. Nothing is
+/// recorded if the estimate is the same as the `recorded` one.
+/// Return `false` if the database has been shut down.
+async fn record_next_update_time<M: Middleware, S>(
+    db_sender: &tokio::sync::mpsc::Sender<DatabaseOperation>,
+    client: &MerkleSetterClient<M, S>,
+    last: chrono::DateTime<chrono::Utc>,
+    deferred: bool,
+    recorded: &mut Option<chrono::DateTime<chrono::Utc>>,
+) -> Result<bool, EthereumSenderError<M>> {
+    let backlog = client.backlog().map_err(EthereumSenderError::Internal)?;
+    let next_time = client.schedule.next_update(
+        client.update_interval,
+        last,
+        &backlog,
+        chrono::Utc::now(),
+        deferred,
+    );
+    if *recorded == Some(next_time) {
+        return Ok(true);
+    }
+    *recorded = Some(next_time);
+    Ok(db_sender
+        .send(db::DatabaseOperation::SetNextMerkleUpdateTime { next_time })
+        .await
+        .is_ok())
+}
+
 /// The task that sends updates to the Ethereum chain.
 /// - Check if there are any non-approved withdrawals, and if so makes a merkle
 ///   proof and submits it to the Ethereum chain. Before transaction submission
@@ -581,84 +706,154 @@ where
 ///   this is done, i.e., the transaction is confirmed, it uses the provided
 ///   `db_sender` channel to notify the database to mark the
 ///
+/// When to send a new root is decided by the [`MerkleSchedule`] of the client,
+/// which is checked every minimum update interval.
+///
+/// Changed `tunables` are applied to the client as they arrive. If the
+/// minimum update interval changes the checks are rescheduled one new period
+/// from then.
+///
 /// The response is `Ok(())` if the service was asked to stop, otherwise it is
 /// one of the errors if some part of the job was interrupted.
 /// The client and `pending` are always left in a consistent state, so that a
 /// retry can be made.
+#[allow(clippy::too_many_arguments)]
 async fn ethereum_tx_sender_worker<M: Middleware, S: Signer>(
     metrics: &crate::metrics::Metrics,
     client: &mut MerkleSetterClient<M, S>,
     db_sender: &tokio::sync::mpsc::Sender<DatabaseOperation>,
     pending: &mut Option<EthereumPendingTransactions>,
     num_confirmations: u64,
+    gate: &mut SendGate,
+    tunables: &mut TunablesReceiver,
     stop: &mut tokio::sync::watch::Receiver<()>,
 ) -> Result<(), EthereumSenderError<M>>
 where
     M::Error: 'static,
     S::Error: 'static,
 {
-    let mut send_interval = tokio::time::interval_at(
-        tokio::time::Instant::now() + client.update_interval,
-        client.update_interval,
-    );
-    send_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
-    if db_sender
-        .send(db::DatabaseOperation::SetNextMerkleUpdateTime {
-            next_time: chrono::Utc::now()
-                + chrono::Duration::from_std(send_interval.period())
-                    .map_err(|e| EthereumSenderError::Internal(e.into()))?,
-        })
-        .await
-        .is_err()
+    client.update_tunables(&tunables.borrow_and_update());
+    let mut check_interval =
+        new_send_interval(client.schedule.min_interval(client.update_interval));
+    let mut last_update = tokio::time::Instant::now();
+    let mut last_update_time = chrono::Utc::now();
+    let mut deferred = false;
+    let mut recorded = None;
+    if !record_next_update_time(db_sender, client, last_update_time, deferred, &mut recorded)
+        .await?
     {
-        {
-            log::debug!("The database has been shut down. Stopping the transaction sender.");
-            return Ok(());
-        }
+        tracing::debug!("The database has been shut down. Stopping the transaction sender.");
+        return Ok(());
     }
     'outer: loop {
         // Handle followup for any pending transaction first.
-        let pending_result =
-            wait_pending_ethereum_tx(metrics, client, db_sender, pending, num_confirmations, stop)
-                .await?;
-        let stop = match pending_result {
+        let pending_result = wait_pending_ethereum_tx(
+            metrics,
+            client,
+            db_sender,
+            pending,
+            num_confirmations,
+            tunables,
+            stop,
+        )
+        .await?;
+        let reason = match pending_result {
             WaitPendingResult::Stop => {
                 // if told to stop then propagate.
                 break 'outer;
             }
             WaitPendingResult::Ok => {
-                // wait for next scheduled send if nothing is pending.
-                tokio::select! {
-                    _ = stop.changed() => true,
-                    _ = send_interval.tick() => false,
+                // wait until the schedule says to send if nothing is pending.
+                loop {
+                    let period = client.schedule.min_interval(client.update_interval);
+                    if period != check_interval.period() {
+                        tracing::info!(
+                            "Checking for Merkle root updates every {}s.",
+                            period.as_secs()
+                        );
+                        check_interval = new_send_interval(period);
+                    }
+                    if !record_next_update_time(
+                        db_sender,
+                        client,
+                        last_update_time,
+                        deferred,
+                        &mut recorded,
+                    )
+                    .await?
+                    {
+                        tracing::debug!(
+                            "The database has been shut down. Stopping the transaction sender."
+                        );
+                        return Ok(());
+                    }
+                    tokio::select! {
+                        _ = stop.changed() => break 'outer,
+                        _ = check_interval.tick() => {}
+                        _ = config::changed(tunables) => {
+                            client.update_tunables(&tunables.borrow_and_update());
+                            continue;
+                        }
+                    }
+                    let backlog = client.backlog().map_err(EthereumSenderError::Internal)?;
+                    match client.schedule.decide(
+                        client.update_interval,
+                        last_update.elapsed(),
+                        &backlog,
+                        chrono::Utc::now(),
+                    ) {
+                        Decision::Send(reason) => break Some(reason),
+                        Decision::SendUnlessGasAbove(defer_gas_price) => {
+                            let gas_price = client
+                                .root_manager
+                                .client()
+                                .get_gas_price()
+                                .await
+                                .map_err(EthereumSenderError::Retryable)?;
+                            if gas_price <= defer_gas_price {
+                                break Some(Reason::Interval);
+                            }
+                            metrics.merkle_updates_deferred.inc();
+                            tracing::info!(
+                                "Deferring the Merkle root update for {} withdrawals since the \
+                                 gas price {gas_price} is above {defer_gas_price}.",
+                                backlog.count
+                            );
+                            deferred = true;
+                        }
+                        Decision::Wait => {}
+                    }
                 }
             }
             WaitPendingResult::Escalate => {
                 // don't wait, immediately send an escalation transaction.
-                false
+                None
             }
         };
-        if stop {
-            break 'outer;
+        // Do not send, or escalate, while the balance of the sender account is too
+        // low. The new withdrawals are included in the next root once it is topped up.
+        if !gate.can_send() {
+            tracing::warn!("Not sending Merkle roots until the sender account is topped up.");
+            if gate.wait(stop).await {
+                break 'outer;
+            }
+            tracing::info!("Resuming sending of Merkle roots.");
         }
         // Now check if we have to send a new one
         let stop_loop = send_ethereum_tx(metrics, client, db_sender, pending).await?;
         if stop_loop {
             break 'outer;
         }
-        // Record in the database for next time we are going to attempt an update.
-        if db_sender
-            .send(db::DatabaseOperation::SetNextMerkleUpdateTime {
-                next_time: chrono::Utc::now()
-                    + chrono::Duration::from_std(send_interval.period())
-                        .map_err(|e| EthereumSenderError::Internal(e.into()))?,
-            })
-            .await
-            .is_err()
-        {
-            {
-                log::debug!("The database has been shut down. Stopping the transaction sender.");
-                return Ok(());
+        if let Some(reason) = reason {
+            if pending.is_some() {
+                tracing::debug!("Sent a Merkle root update, reason: {}.", reason.label());
+                metrics
+                    .merkle_updates
+                    .with_label_values(&[reason.label()])
+                    .inc();
+                last_update = tokio::time::Instant::now();
+                last_update_time = chrono::Utc::now();
+                deferred = false;
             }
         }
     }
@@ -683,6 +878,7 @@ async fn wait_pending_ethereum_tx<M: Middleware, S: Signer>(
     db_sender: &tokio::sync::mpsc::Sender<DatabaseOperation>,
     pending: &mut Option<EthereumPendingTransactions>,
     num_confirmations: u64,
+    tunables: &mut TunablesReceiver,
     stop: &mut tokio::sync::watch::Receiver<()>,
 ) -> Result<WaitPendingResult, EthereumSenderError<M>>
 where
@@ -705,13 +901,17 @@ where
         if stop {
             return Ok(WaitPendingResult::Stop);
         }
+        // Pick up a changed escalation interval or gas price cap while waiting.
+        if tunables.has_changed().unwrap_or(false) {
+            client.update_tunables(&tunables.borrow_and_update());
+        }
         for (i, (pending_hash, _)) in pending_txs.iter().enumerate() {
             let elapsed = start.elapsed();
             if elapsed > client.escalate_interval {
                 return Ok(WaitPendingResult::Escalate);
             } else if elapsed > client.warn_duration {
                 metrics.warnings_total.inc();
-                log::warn!(
+                tracing::warn!(
                     "More than {}s elapsed waiting for {pending_hash:#x} to be confirmed.",
                     elapsed.as_secs()
                 );
@@ -723,7 +923,7 @@ where
                 .await
                 .map_err(EthereumSenderError::Retryable)?;
             let Some(receipt) = result else {
-                log::debug!("Ethereum transaction {pending_hash:#x} has no receipt.");
+                tracing::debug!("Ethereum transaction {pending_hash:#x} has no receipt.");
                 continue;
             };
             let Some(bn) = receipt.block_number else {
@@ -739,11 +939,19 @@ where
                 .await
                 .map_err(EthereumSenderError::Retryable)?;
             if bn.saturating_add(num_confirmations.into()) <= current_block {
+                if let Some(gas_used) = receipt.gas_used {
+                    metrics.merkle_update_gas.set(gas_used.low_u64());
+                    if let Some(price) = receipt.effective_gas_price {
+                        metrics
+                            .merkle_update_cost
+                            .set((gas_used.saturating_mul(price) / 1_000_000_000_000u64).low_u64());
+                    }
+                }
                 let mut found = false;
                 for log in receipt.logs {
                     use ethers::contract::EthEvent;
                     let sig = state_sender::MerkleRootFilter::signature();
-                    if log.topics.first().map_or(false, |s| s == &sig) {
+                    if log.topics.first() == Some(&sig) {
                         let emitted_root = state_sender::MerkleRootFilter::decode_log(&RawLog {
                             topics: log.topics,
                             data: log.data.0.into(),
@@ -756,10 +964,10 @@ where
                         found = true;
                     }
                 }
-                log::info!("Withdrawal transaction confirmed in block number: {bn}.");
+                tracing::info!("Withdrawal transaction confirmed in block number: {bn}.");
                 if !found {
                     metrics.errors_total.inc();
-                    log::error!(
+                    tracing::error!(
                         "A transaction with hash {pending_hash:#x} did not set a Merkle root. \
                          This means it failed."
                     )
@@ -784,7 +992,7 @@ where
                     .await
                     .is_err()
                 {
-                    log::debug!(
+                    tracing::debug!(
                         "The database has been shut down. Stopping the transaction sender."
                     );
                     return Ok(WaitPendingResult::Stop);
@@ -795,11 +1003,13 @@ where
                 // Wait until the database operation completes.
                 if receiver.await.is_err() {
                     metrics.warnings_total.inc();
-                    log::warn!("The database has been shut down. Stopping the transaction sender.");
+                    tracing::warn!(
+                        "The database has been shut down. Stopping the transaction sender."
+                    );
                     return Ok(WaitPendingResult::Stop);
                 }
                 if found {
-                    log::info!(
+                    tracing::info!(
                         "New merkle root set to {} and marked in the database.",
                         TransactionHash::from(*root)
                     );
@@ -813,7 +1023,7 @@ where
                 // we have completed this nonce/root setting. Terminate normally.
                 break 'outer;
             } else {
-                log::debug!(
+                tracing::debug!(
                     "Ethereum transaction {pending_hash:#x} is in block {bn}, but not yet \
                      confirmed."
                 );
@@ -848,55 +1058,30 @@ where
         {
             return Ok(false);
         };
-        let (mut tx, _) =
-            ethers::types::transaction::eip2718::TypedTransaction::decode_signed(&Rlp::new(tx))
-                .map_err(|e| {
-                    EthereumSenderError::Internal(anyhow::anyhow!(
-                        "Error decoding pending transaction {e}"
-                    ))
-                })?;
-        // Make sure the transaction was not tampered with.
-        if tx.from() != Some(&client.signer.address()) {
-            return Err(EthereumSenderError::Internal(anyhow::anyhow!(
-                "The pending transaction is not correctly signed."
-            )));
-        }
-        let Some(existing_gas_price) = tx.gas_price() else {
-                return Err(EthereumSenderError::Internal(anyhow::anyhow!(
-                    "Pending transaction with an unset gas price. That is a bug."
-                )));
-            };
-        // Increase the gas price by 5%.
-        let current_gas_price = client
-            .root_manager
-            .client()
-            .get_gas_price()
-            .await
-            .map_err(EthereumSenderError::Retryable)?;
-        let new_gas_price = std::cmp::max(
-            existing_gas_price + existing_gas_price / 20,
-            current_gas_price,
-        );
-        if new_gas_price > client.max_gas_price {
-            metrics.warnings_total.inc();
-            log::warn!(
-                "Escalating would lead to transaction price that is too high {new_gas_price} > \
-                 {}. Waiting for next iteration.",
-                client.max_gas_price,
-            );
-            return Ok(false);
-        } else {
-            tx.set_gas_price(new_gas_price);
-            let signature = client
-                .signer
-                .sign_transaction(&tx)
-                .await
-                .map_err(|e| EthereumSenderError::Internal(e.into()))?;
-            let tx_hash = tx.hash(&signature);
-            let raw_tx = tx.rlp_signed(&signature);
-            log::debug!("Sending escalation SetMerkleRoot transaction with hash {tx_hash:#x}.");
-            pending_txs.push((tx_hash, raw_tx.clone()));
-            (tx_hash, raw_tx, ids.clone(), *root)
+        match escalate_transaction(
+            &*client.root_manager.client(),
+            &client.signer,
+            tx,
+            client.max_gas_price,
+        )
+        .await?
+        {
+            EscalationResult::GasTooHigh { new_gas_price } => {
+                metrics.warnings_total.inc();
+                tracing::warn!(
+                    "Escalating would lead to transaction price that is too high \
+                     {new_gas_price} > {}. Waiting for next iteration.",
+                    client.max_gas_price,
+                );
+                return Ok(false);
+            }
+            EscalationResult::Escalated { tx_hash, raw_tx } => {
+                tracing::debug!(
+                    "Sending escalation SetMerkleRoot transaction with hash {tx_hash:#x}."
+                );
+                pending_txs.push((tx_hash, raw_tx.clone()));
+                (tx_hash, raw_tx, ids.clone(), *root)
+            }
         }
     } else {
         match client.set_merkle_root().await? {
@@ -907,7 +1092,7 @@ where
                 root,
                 ids,
             } => {
-                log::debug!(
+                tracing::debug!(
                     "New merkle root to be set to {} using transaction {tx_hash:#x}.",
                     TransactionHash::from(root)
                 );
@@ -923,14 +1108,14 @@ where
                 current_gas_price,
             } => {
                 metrics.warnings_total.inc();
-                log::warn!(
+                tracing::warn!(
                     "Ethereum transaction price is too high {current_gas_price} > \
                      {max_gas_price}. Waiting for next iteration."
                 );
                 return Ok(false);
             }
             SetMerkleRootResult::NoPendingWithdrawals => {
-                log::debug!("No pending withdrawals. Doing nothing.");
+                tracing::debug!("No pending withdrawals. Doing nothing.");
                 return Ok(false);
             }
         }
@@ -948,19 +1133,19 @@ where
         .await
         .is_err()
     {
-        log::info!("The database has been shut down. Stopping the transaction sender.");
+        tracing::info!("The database has been shut down. Stopping the transaction sender.");
         return Ok(true);
     }
     match receiver.await {
         Ok(x) => x,
         Err(_) => {
             metrics.warnings_total.inc();
-            log::warn!("The database has been shut down. Stopping the transaction sender.");
+            tracing::warn!("The database has been shut down. Stopping the transaction sender.");
             return Ok(true);
         }
     };
     let ethereum_client = client.root_manager.client();
-    log::debug!(
+    tracing::debug!(
         "Sending SetMerkleRoot transaction with hash {tx_hash:#x} to set Merkle root to {}.",
         TransactionHash::from(root)
     );
@@ -971,3 +1156,66 @@ where
     metrics.sent_ethereum_transactions.inc();
     Ok(false)
 }
+
+/// The result of [`escalate_transaction`].
+pub(crate) enum EscalationResult {
+    /// The transaction was signed again with a higher gas price.
+    Escalated {
+        tx_hash: H256,
+        raw_tx: ethers::prelude::Bytes,
+    },
+    /// Escalating would exceed the maximum allowed gas price.
+    GasTooHigh { new_gas_price: U256 },
+}
+
+/// Sign the given pending transaction again with the gas price increased by
+/// 5%, or the current gas price if that is higher. The transaction must have
+/// been signed by the provided `signer`.
+pub(crate) async fn escalate_transaction<M: Middleware, S: Signer>(
+    ethereum_client: &M,
+    signer: &S,
+    raw_tx: &ethers::prelude::Bytes,
+    max_gas_price: U256,
+) -> Result<EscalationResult, EthereumSenderError<M>>
+where
+    S::Error: 'static,
+{
+    let (mut tx, _) =
+        ethers::types::transaction::eip2718::TypedTransaction::decode_signed(&Rlp::new(raw_tx))
+            .map_err(|e| {
+                EthereumSenderError::Internal(anyhow::anyhow!(
+                    "Error decoding pending transaction {e}"
+                ))
+            })?;
+    // Make sure the transaction was not tampered with.
+    if tx.from() != Some(&signer.address()) {
+        return Err(EthereumSenderError::Internal(anyhow::anyhow!(
+            "The pending transaction is not correctly signed."
+        )));
+    }
+    let Some(existing_gas_price) = tx.gas_price() else {
+        return Err(EthereumSenderError::Internal(anyhow::anyhow!(
+            "Pending transaction with an unset gas price. That is a bug."
+        )));
+    };
+    // Increase the gas price by 5%.
+    let current_gas_price = ethereum_client
+        .get_gas_price()
+        .await
+        .map_err(EthereumSenderError::Retryable)?;
+    let new_gas_price = std::cmp::max(
+        existing_gas_price + existing_gas_price / 20,
+        current_gas_price,
+    );
+    if new_gas_price > max_gas_price {
+        return Ok(EscalationResult::GasTooHigh { new_gas_price });
+    }
+    tx.set_gas_price(new_gas_price);
+    let signature = signer
+        .sign_transaction(&tx)
+        .await
+        .map_err(|e| EthereumSenderError::Internal(e.into()))?;
+    let tx_hash = tx.hash(&signature);
+    let raw_tx = tx.rlp_signed(&signature);
+    Ok(EscalationResult::Escalated { tx_hash, raw_tx })
+}
@@ -1,8 +1,16 @@
 use prometheus::{
     core::{AtomicU64, GenericGauge},
-    IntCounter, IntGauge, Registry, TextEncoder,
+    GaugeVec, Histogram, HistogramOpts, IntCounter, IntCounterVec, IntGauge, IntGaugeVec, Opts,
+    Registry, TextEncoder,
 };
 
+/// Buckets, in seconds, of the deposit and withdrawal latency histograms. They
+/// range from a few seconds to a day.
+const LATENCY_BUCKETS: &[f64] = &[
+    5.0, 15.0, 30.0, 60.0, 120.0, 300.0, 600.0, 1200.0, 1800.0, 3600.0, 7200.0, 14400.0, 43200.0,
+    86400.0,
+];
+
 #[derive(Clone)]
 pub struct Metrics {
     pub(crate) merkle_tree_size: IntGauge,
@@ -10,15 +18,39 @@ pub struct Metrics {
     pub(crate) errors_total: IntCounter,
     pub(crate) num_deposits: IntCounter,
     pub(crate) num_completed_deposits: IntCounter,
+    pub num_quarantined_deposits: IntCounter,
     pub(crate) num_withdrawals: IntCounter,
     pub(crate) num_completed_withdrawals: IntCounter,
+    pub(crate) num_claims: IntCounter,
     pub(crate) concordium_height: IntGauge,
     pub(crate) ethereum_height: IntGauge,
     pub(crate) sent_concordium_transactions: IntCounter,
     pub(crate) sent_ethereum_transactions: IntCounter,
+    pub recorded_concordium_transactions: IntCounter,
+    pub recorded_merkle_roots: IntCounter,
     pub(crate) time_last_merkle_root: IntGauge,
     pub concordium_balance: GenericGauge<AtomicU64>,
     pub ethereum_balance: GenericGauge<AtomicU64>,
+    pub claim_balance: GenericGauge<AtomicU64>,
+    pub concordium_balance_level: IntGauge,
+    pub ethereum_balance_level: IntGauge,
+    pub claim_balance_level: IntGauge,
+    pub deposit_latency: Histogram,
+    pub withdrawal_approval_latency: Histogram,
+    pub withdrawal_completion_latency: Histogram,
+    pub token_deposits: IntCounterVec,
+    pub token_withdrawals: IntCounterVec,
+    pub queue_depth: IntGaugeVec,
+    pub(crate) merkle_update_gas: GenericGauge<AtomicU64>,
+    pub(crate) merkle_update_cost: GenericGauge<AtomicU64>,
+    pub(crate) deposit_energy: GenericGauge<AtomicU64>,
+    pub endpoint_score: GaugeVec,
+    pub endpoint_switches: IntCounterVec,
+    pub quorum_failures: IntCounter,
+    pub merkle_updates: IntCounterVec,
+    pub merkle_updates_deferred: IntCounter,
+    pub guardian_alarms: IntCounterVec,
+    pub guardian_paused: IntGauge,
 }
 
 impl Metrics {
@@ -51,6 +83,13 @@ impl Metrics {
         )?;
         registry.register(Box::new(num_completed_deposits.clone()))?;
 
+        let num_quarantined_deposits = IntCounter::new(
+            "num_quarantined_deposits",
+            "Number deposits held in quarantine since start, since the depositor, the receiver \
+             or the contract is on the blocklist.",
+        )?;
+        registry.register(Box::new(num_quarantined_deposits.clone()))?;
+
         let num_withdrawals = IntCounter::new(
             "num_withdrawals",
             "Number of started withdrawals detected since start.",
@@ -63,6 +102,12 @@ impl Metrics {
         )?;
         registry.register(Box::new(num_completed_withdrawals.clone()))?;
 
+        let num_claims = IntCounter::new(
+            "num_claims",
+            "Number of withdrawals claimed on behalf of users since start.",
+        )?;
+        registry.register(Box::new(num_claims.clone()))?;
+
         let concordium_height = IntGauge::new(
             "concordium_height",
             "Largest processed height for Concordium.",
@@ -85,6 +130,19 @@ impl Metrics {
         )?;
         registry.register(Box::new(sent_ethereum_transactions.clone()))?;
 
+        let recorded_concordium_transactions = IntCounter::new(
+            "recorded_concordium_transactions",
+            "Number of transactions for Concordium recorded, but not sent, in shadow mode since \
+             start.",
+        )?;
+        registry.register(Box::new(recorded_concordium_transactions.clone()))?;
+
+        let recorded_merkle_roots = IntCounter::new(
+            "recorded_merkle_roots",
+            "Number of Merkle roots recorded, but not set, in shadow mode since start.",
+        )?;
+        registry.register(Box::new(recorded_merkle_roots.clone()))?;
+
         let time_last_merkle_root = IntGauge::new(
             "timestamp_last_merkle_root",
             "Unix timestamp in seconds of the last time a Merkle root was set.",
@@ -103,6 +161,163 @@ impl Metrics {
         )?;
         registry.register(Box::new(ethereum_balance.clone()))?;
 
+        let claim_balance = GenericGauge::new(
+            "claim_account_balance",
+            "Balance, in microEther, of the account used for claiming withdrawals on Ethereum.",
+        )?;
+        registry.register(Box::new(claim_balance.clone()))?;
+
+        let concordium_balance_level = IntGauge::new(
+            "concordium_balance_level",
+            "Balance level of the sender account for Concordium. 0 is ok, 1 is low, 2 is \
+             degraded, and 3 is critical.",
+        )?;
+        registry.register(Box::new(concordium_balance_level.clone()))?;
+
+        let ethereum_balance_level = IntGauge::new(
+            "ethereum_balance_level",
+            "Balance level of the sender account for Ethereum. 0 is ok, 1 is low, 2 is degraded, \
+             and 3 is critical.",
+        )?;
+        registry.register(Box::new(ethereum_balance_level.clone()))?;
+
+        let claim_balance_level = IntGauge::new(
+            "claim_balance_level",
+            "Balance level of the account used for claiming withdrawals on Ethereum. 0 is ok, 1 \
+             is low, 2 is degraded, and 3 is critical.",
+        )?;
+        registry.register(Box::new(claim_balance_level.clone()))?;
+
+        let deposit_latency = Histogram::with_opts(
+            HistogramOpts::new(
+                "deposit_latency_seconds",
+                "Time in seconds from locking tokens on Ethereum until the deposit is finalized \
+                 on Concordium.",
+            )
+            .buckets(LATENCY_BUCKETS.to_vec()),
+        )?;
+        registry.register(Box::new(deposit_latency.clone()))?;
+
+        let withdrawal_approval_latency = Histogram::with_opts(
+            HistogramOpts::new(
+                "withdrawal_approval_latency_seconds",
+                "Time in seconds from a withdrawal on Concordium until it is approved by a Merkle \
+                 root update on Ethereum.",
+            )
+            .buckets(LATENCY_BUCKETS.to_vec()),
+        )?;
+        registry.register(Box::new(withdrawal_approval_latency.clone()))?;
+
+        let withdrawal_completion_latency = Histogram::with_opts(
+            HistogramOpts::new(
+                "withdrawal_completion_latency_seconds",
+                "Time in seconds from a withdrawal on Concordium until it is claimed on Ethereum.",
+            )
+            .buckets(LATENCY_BUCKETS.to_vec()),
+        )?;
+        registry.register(Box::new(withdrawal_completion_latency.clone()))?;
+
+        let token_deposits = IntCounterVec::new(
+            Opts::new(
+                "token_deposits_total",
+                "Number of deposits detected since start, by token.",
+            ),
+            &["token"],
+        )?;
+        registry.register(Box::new(token_deposits.clone()))?;
+
+        let token_withdrawals = IntCounterVec::new(
+            Opts::new(
+                "token_withdrawals_total",
+                "Number of started withdrawals detected since start, by token.",
+            ),
+            &["token"],
+        )?;
+        registry.register(Box::new(token_withdrawals.clone()))?;
+
+        let queue_depth = IntGaugeVec::new(
+            Opts::new(
+                "channel_queue_depth",
+                "Number of items waiting in an internal channel of the relayer.",
+            ),
+            &["channel"],
+        )?;
+        registry.register(Box::new(queue_depth.clone()))?;
+
+        let merkle_update_gas = GenericGauge::new(
+            "merkle_update_gas_used",
+            "Gas used by the last Merkle root update on Ethereum.",
+        )?;
+        registry.register(Box::new(merkle_update_gas.clone()))?;
+
+        let merkle_update_cost = GenericGauge::new(
+            "merkle_update_cost",
+            "Cost, in microEther, of the last Merkle root update on Ethereum.",
+        )?;
+        registry.register(Box::new(merkle_update_cost.clone()))?;
+
+        let deposit_energy = GenericGauge::new(
+            "deposit_energy_cost",
+            "Energy (NRG) used by the last deposit transaction on Concordium.",
+        )?;
+        registry.register(Box::new(deposit_energy.clone()))?;
+
+        let endpoint_score = GaugeVec::new(
+            Opts::new(
+                "endpoint_score",
+                "Score of a node endpoint, from its lag, error rate and latency. Requests are \
+                 sent to the endpoint with the lowest score.",
+            ),
+            &["chain", "endpoint"],
+        )?;
+        registry.register(Box::new(endpoint_score.clone()))?;
+
+        let endpoint_switches = IntCounterVec::new(
+            Opts::new(
+                "endpoint_switches_total",
+                "Number of times requests switched to a different endpoint since start, by chain.",
+            ),
+            &["chain"],
+        )?;
+        registry.register(Box::new(endpoint_switches.clone()))?;
+
+        let quorum_failures = IntCounter::new(
+            "ethereum_quorum_failures_total",
+            "Number of Ethereum log queries on which not enough endpoints agreed since start.",
+        )?;
+        registry.register(Box::new(quorum_failures.clone()))?;
+
+        let merkle_updates = IntCounterVec::new(
+            Opts::new(
+                "merkle_updates_total",
+                "Number of Merkle root updates sent since start, by the reason they were sent.",
+            ),
+            &["reason"],
+        )?;
+        registry.register(Box::new(merkle_updates.clone()))?;
+
+        let merkle_updates_deferred = IntCounter::new(
+            "merkle_updates_deferred_total",
+            "Number of times a Merkle root update was deferred because of the gas price since \
+             start.",
+        )?;
+        registry.register(Box::new(merkle_updates_deferred.clone()))?;
+
+        let guardian_alarms = IntCounterVec::new(
+            Opts::new(
+                "guardian_alarms_total",
+                "Number of times a rule of the guardian fired since start, by rule.",
+            ),
+            &["rule"],
+        )?;
+        registry.register(Box::new(guardian_alarms.clone()))?;
+
+        let guardian_paused = IntGauge::new(
+            "guardian_paused",
+            "1 if the guardian paused the bridge, 0 otherwise.",
+        )?;
+        registry.register(Box::new(guardian_paused.clone()))?;
+
         Ok((registry, Self {
             merkle_tree_size,
             warnings_total,
@@ -110,14 +325,38 @@ impl Metrics {
             num_deposits,
             num_withdrawals,
             num_completed_withdrawals,
+            num_claims,
             concordium_height,
             ethereum_height,
             sent_concordium_transactions,
             sent_ethereum_transactions,
+            recorded_concordium_transactions,
+            recorded_merkle_roots,
             time_last_merkle_root,
             num_completed_deposits,
+            num_quarantined_deposits,
             concordium_balance,
             ethereum_balance,
+            claim_balance,
+            concordium_balance_level,
+            ethereum_balance_level,
+            claim_balance_level,
+            deposit_latency,
+            withdrawal_approval_latency,
+            withdrawal_completion_latency,
+            token_deposits,
+            token_withdrawals,
+            queue_depth,
+            merkle_update_gas,
+            merkle_update_cost,
+            deposit_energy,
+            endpoint_score,
+            endpoint_switches,
+            quorum_failures,
+            merkle_updates,
+            merkle_updates_deferred,
+            guardian_alarms,
+            guardian_paused,
         }))
     }
 }
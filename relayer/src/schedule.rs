@@ -0,0 +1,254 @@
+//! Scheduling of Merkle root updates.
+//!
+//! By default a Merkle root update is sent every `merkle-update-interval` if
+//! there are new withdrawals. The [`MerkleSchedule`] adjusts this based on the
+//! withdrawals that are waiting for approval, and on the gas price.
+//! - An update is sent early, but never sooner than the minimum interval after
+//!   the previous one, if enough withdrawals are waiting, if their total value
+//!   is high enough, or if the oldest one has waited for the maximum wait.
+//! - A regular update is deferred while the gas price is above a limit, until
+//!   one of the conditions for sending early holds.
+//!
+//! The value of a withdrawal is its amount, in whole tokens, times the price
+//! of the token configured with [`TokenPrice`]. Withdrawals of tokens without a
+//! price have no value.
+use crate::merkle::Leaf;
+use ethers::prelude::U256;
+use std::{collections::BTreeMap, str::FromStr, time::Duration};
+
+/// Policy for when to send Merkle root updates, in addition to the regular
+/// update interval.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize)]
+pub struct MerkleSchedule {
+    /// Minimum time between two updates. If not set this is the regular
+    /// update interval, in which case updates are never sent early.
+    #[serde(serialize_with = "crate::config::serialize_opt_secs")]
+    pub min_interval: Option<Duration>,
+    /// Send an update once the oldest waiting withdrawal has waited this
+    /// long, regardless of the gas price.
+    #[serde(serialize_with = "crate::config::serialize_opt_secs")]
+    pub max_wait: Option<Duration>,
+    /// Send an update once this many withdrawals are waiting.
+    pub backlog_size: Option<u64>,
+    /// Send an update once the total value of the waiting withdrawals is at
+    /// least this.
+    pub backlog_value: Option<U256>,
+    /// Defer regular updates while the gas price is above this.
+    pub defer_gas_price: Option<U256>,
+    /// The value of one whole token, by the symbol of the token.
+    pub token_prices: BTreeMap<String, u64>,
+}
+
+/// The price of a token, given as `SYMBOL=PRICE`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenPrice {
+    pub symbol: String,
+    pub price: u64,
+}
+
+impl FromStr for TokenPrice {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((symbol, price)) = s.split_once('=') else {
+            anyhow::bail!("A token price must be given as SYMBOL=PRICE.");
+        };
+        Ok(Self {
+            symbol: symbol.trim().to_owned(),
+            price: price.trim().parse()?,
+        })
+    }
+}
+
+/// The withdrawals that are not yet approved by a Merkle root.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Backlog {
+    /// Number of withdrawals.
+    pub count: u64,
+    /// Total value of the withdrawals.
+    pub value: U256,
+    /// Time of the oldest withdrawal.
+    pub oldest: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Why an update is sent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reason {
+    /// The regular update interval elapsed.
+    Interval,
+    /// Enough withdrawals are waiting.
+    BacklogSize,
+    /// The waiting withdrawals are valuable enough.
+    BacklogValue,
+    /// The oldest withdrawal waited for the maximum wait.
+    MaxWait,
+}
+
+impl Reason {
+    /// The label of the reason in metrics.
+    pub fn label(self) -> &'static str {
+        match self {
+            Reason::Interval => "interval",
+            Reason::BacklogSize => "backlog_size",
+            Reason::BacklogValue => "backlog_value",
+            Reason::MaxWait => "max_wait",
+        }
+    }
+}
+
+/// The result of [`MerkleSchedule::decide`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decision {
+    /// Send an update now.
+    Send(Reason),
+    /// A regular update is due, but it should only be sent if the gas price
+    /// is at most the given one.
+    SendUnlessGasAbove(U256),
+    /// Do not send an update yet.
+    Wait,
+}
+
+impl MerkleSchedule {
+    /// The minimum time between two updates, given the regular `interval`.
+    pub fn min_interval(&self, interval: Duration) -> Duration {
+        self.min_interval.unwrap_or(interval)
+    }
+
+    /// Check that the schedule is usable with the regular update `interval`.
+    pub fn validate(&self, interval: Duration) -> anyhow::Result<()> {
+        if let Some(min_interval) = self.min_interval {
+            anyhow::ensure!(
+                !min_interval.is_zero(),
+                "The merkle-min-update-interval must be positive."
+            );
+            anyhow::ensure!(
+                min_interval <= interval,
+                "The merkle-min-update-interval must be at most the merkle-update-interval."
+            );
+        }
+        if let Some(max_wait) = self.max_wait {
+            anyhow::ensure!(!max_wait.is_zero(), "The merkle-max-wait must be positive.");
+        }
+        Ok(())
+    }
+
+    /// The value of `amount` of the token with the given symbol and decimals.
+    fn value(&self, token: &Option<(String, u8)>, amount: U256) -> U256 {
+        let Some((symbol, decimals)) = token else {
+            return U256::zero();
+        };
+        let Some(&price) = self.token_prices.get(symbol) else {
+            return U256::zero();
+        };
+        amount.saturating_mul(price.into()) / U256::exp10((*decimals).into())
+    }
+
+    /// Summarize the leaves with an event index above `max_marked_event_index`,
+    /// i.e., the ones that are not yet approved.
+    pub fn backlog<'a>(
+        &self,
+        leaves: impl IntoIterator<Item = (&'a u64, &'a Leaf)>,
+        max_marked_event_index: Option<u64>,
+    ) -> Backlog {
+        let mut backlog = Backlog {
+            count: 0,
+            value: U256::zero(),
+            oldest: None,
+        };
+        for (&index, leaf) in leaves {
+            if Some(index) <= max_marked_event_index {
+                continue;
+            }
+            backlog.count += 1;
+            backlog.value = backlog
+                .value
+                .saturating_add(self.value(&leaf.token, leaf.amount));
+            backlog.oldest = Some(backlog.oldest.map_or(leaf.time, |t| t.min(leaf.time)));
+        }
+        backlog
+    }
+
+    /// The reason for sending an update early, if there is one.
+    fn urgency(&self, backlog: &Backlog, now: chrono::DateTime<chrono::Utc>) -> Option<Reason> {
+        if let (Some(max_wait), Some(oldest)) = (self.max_wait, backlog.oldest) {
+            if now
+                .signed_duration_since(oldest)
+                .to_std()
+                .unwrap_or_default()
+                >= max_wait
+            {
+                return Some(Reason::MaxWait);
+            }
+        }
+        if self.backlog_size.is_some_and(|size| backlog.count >= size) {
+            return Some(Reason::BacklogSize);
+        }
+        if self
+            .backlog_value
+            .is_some_and(|value| backlog.value >= value)
+        {
+            return Some(Reason::BacklogValue);
+        }
+        None
+    }
+
+    /// Decide whether to send an update, `since_last` the previous one, with
+    /// the regular update `interval`.
+    pub fn decide(
+        &self,
+        interval: Duration,
+        since_last: Duration,
+        backlog: &Backlog,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> Decision {
+        if backlog.count == 0 || since_last < self.min_interval(interval) {
+            return Decision::Wait;
+        }
+        if let Some(reason) = self.urgency(backlog, now) {
+            return Decision::Send(reason);
+        }
+        if since_last < interval {
+            return Decision::Wait;
+        }
+        match self.defer_gas_price {
+            Some(price) => Decision::SendUnlessGasAbove(price),
+            None => Decision::Send(Reason::Interval),
+        }
+    }
+
+    /// Estimate when the next update is sent, given the time of the previous
+    /// update `last`. If `deferred` the last regular update was deferred
+    /// because of the gas price, and is attempted again after the minimum
+    /// interval.
+    pub fn next_update(
+        &self,
+        interval: Duration,
+        last: chrono::DateTime<chrono::Utc>,
+        backlog: &Backlog,
+        now: chrono::DateTime<chrono::Utc>,
+        deferred: bool,
+    ) -> chrono::DateTime<chrono::Utc> {
+        let min_interval = self.min_interval(interval);
+        let earliest = add(last, min_interval);
+        if backlog.count > 0 && self.urgency(backlog, now).is_some() {
+            return earliest.max(now);
+        }
+        let regular = if deferred {
+            add(now, min_interval)
+        } else {
+            add(last, interval)
+        };
+        match (self.max_wait, backlog.oldest) {
+            (Some(max_wait), Some(oldest)) => regular.min(add(oldest, max_wait).max(earliest)),
+            _ => regular,
+        }
+    }
+}
+
+/// Add the duration to the time, saturating at the maximum time.
+fn add(time: chrono::DateTime<chrono::Utc>, duration: Duration) -> chrono::DateTime<chrono::Utc> {
+    chrono::Duration::from_std(duration)
+        .ok()
+        .and_then(|d| time.checked_add_signed(d))
+        .unwrap_or(chrono::DateTime::<chrono::Utc>::MAX_UTC)
+}
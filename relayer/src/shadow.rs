@@ -0,0 +1,166 @@
+//! Recorders that replace the transaction senders when the relayer runs in
+//! shadow mode.
+//!
+//! A relayer in shadow mode watches both chains and computes the same
+//! transactions and Merkle roots as the production relayer, but it never
+//! signs or sends anything. Concordium transactions are kept in the database
+//! with status [`Shadow`](db::TransactionStatus::Shadow), and Merkle roots are
+//! recorded in the `shadow_merkle_roots` table, so that the database can be
+//! compared against the production one with the `compare_shadow` tool.
+use crate::{
+    config::{self, TunablesReceiver},
+    db::{self, DatabaseOperation, MerkleUpdate},
+    merkle::{self, Keccak256Algorithm, Leaf},
+};
+use concordium_rust_sdk::types::{
+    hashes::TransactionHash,
+    transactions::{BlockItem, EncodedPayload},
+};
+use rs_merkle::MerkleTree;
+use std::{collections::BTreeMap, sync::Arc};
+
+/// A worker that replaces
+/// [`concordium_tx_sender`](crate::concordium_contracts::concordium_tx_sender).
+/// It marks the transactions it receives as
+/// [`Shadow`](db::TransactionStatus::Shadow) in the database instead of
+/// sending them.
+pub async fn record_concordium_transactions(
+    metrics: crate::metrics::Metrics,
+    // Transactions to record, and the spans in which they were constructed.
+    mut receiver: tokio::sync::mpsc::Receiver<(BlockItem<EncodedPayload>, tracing::Span)>,
+    db_sender: tokio::sync::mpsc::Sender<DatabaseOperation>,
+    // Flag to signal stopping the task gracefully.
+    mut stop: tokio::sync::watch::Receiver<()>,
+) -> anyhow::Result<()> {
+    while let Some((bi, span)) = tokio::select! {
+        // Make sure to process all transactions that are in the queue before shutting down.
+        biased;
+            x = receiver.recv() => x,
+            _ = stop.changed() => None,
+    } {
+        let tx_hash = bi.hash();
+        tracing::info!(parent: &span, "Recording transaction {tx_hash} without sending it.");
+        metrics.recorded_concordium_transactions.inc();
+        if db_sender
+            .send(DatabaseOperation::MarkConcordiumTransaction {
+                tx_hash,
+                state: db::TransactionStatus::Shadow,
+            })
+            .await
+            .is_err()
+        {
+            tracing::info!("The database has been shut down. Stopping the transaction recorder.");
+            break;
+        }
+    }
+    tracing::info!("Concordium transaction recorder terminated.");
+    Ok(())
+}
+
+/// A worker that replaces
+/// [`send_merkle_root_updates`](merkle::send_merkle_root_updates). It
+/// maintains the leaves of the Merkle tree in the same way, and every Merkle
+/// update interval records the root of the tree in the database if there are
+/// new withdrawals since the last recorded root. The
+/// [`MerkleSchedule`](crate::schedule::MerkleSchedule) is not used, since
+/// it depends on the gas price.
+///
+/// The `pending_withdrawals` and `max_marked_event_index` are the initial
+/// state, as returned by
+/// [`Database::pending_withdrawals`](db::Database::pending_withdrawals).
+#[allow(clippy::too_many_arguments)]
+pub async fn record_merkle_roots(
+    metrics: crate::metrics::Metrics,
+    pending_withdrawals: Vec<(u64, Leaf)>,
+    mut max_marked_event_index: Option<u64>,
+    mut receiver: tokio::sync::mpsc::Receiver<MerkleUpdate>,
+    db_sender: tokio::sync::mpsc::Sender<DatabaseOperation>,
+    mut tunables: TunablesReceiver,
+    mut stop: tokio::sync::watch::Receiver<()>,
+) -> anyhow::Result<()> {
+    let leaves = Arc::new(std::sync::Mutex::new(BTreeMap::new()));
+    for (event_index, leaf) in pending_withdrawals {
+        merkle::add_withdraw_event(&leaves, event_index, leaf)?;
+    }
+    metrics
+        .merkle_tree_size
+        .set(leaves.lock().map_or(0, |x| x.len()) as i64);
+    let mut send_interval =
+        merkle::new_send_interval(tunables.borrow_and_update().merkle_update_interval);
+    loop {
+        tokio::select! {
+            biased;
+
+            mu = receiver.recv() => {
+                let Some(mu) = mu else {
+                    break;
+                };
+                merkle::handle_merkle_update(&metrics, &leaves, mu)?;
+            }
+            _ = stop.changed() => break,
+            _ = send_interval.tick() => {
+                let Some((root, num_leaves, max_event_index)) =
+                    compute_root(&leaves, max_marked_event_index)?
+                else {
+                    tracing::debug!("No pending withdrawals. Doing nothing.");
+                    continue;
+                };
+                tracing::info!(
+                    "Recording Merkle root {} with {num_leaves} leaves without setting it.",
+                    TransactionHash::from(root)
+                );
+                metrics.recorded_merkle_roots.inc();
+                max_marked_event_index = Some(max_event_index);
+                if db_sender
+                    .send(DatabaseOperation::RecordShadowMerkleRoot {
+                        root,
+                        num_leaves,
+                        max_event_index,
+                    })
+                    .await
+                    .is_err()
+                {
+                    tracing::info!(
+                        "The database has been shut down. Stopping the Merkle root recorder."
+                    );
+                    break;
+                }
+            }
+            _ = config::changed(&mut tunables) => {
+                let update_interval = tunables.borrow_and_update().merkle_update_interval;
+                if update_interval != send_interval.period() {
+                    tracing::info!("Recording Merkle roots every {}s.", update_interval.as_secs());
+                    send_interval = merkle::new_send_interval(update_interval);
+                }
+            }
+        }
+    }
+    tracing::info!("Merkle root recorder terminated.");
+    Ok(())
+}
+
+/// Compute the root of the tree with the given leaves, together with the
+/// number of leaves and the largest event index. Return `None` if there are
+/// no withdrawals after `max_marked_event_index`.
+fn compute_root(
+    leaves: &std::sync::Mutex<BTreeMap<u64, Leaf>>,
+    max_marked_event_index: Option<u64>,
+) -> anyhow::Result<Option<([u8; 32], u64, u64)>> {
+    let leaves = leaves
+        .lock()
+        .map_err(|_| anyhow::anyhow!("Unable to acquire lock."))?;
+    let Some((&max_event_index, _)) = leaves.last_key_value() else {
+        return Ok(None);
+    };
+    if Some(max_event_index) <= max_marked_event_index {
+        return Ok(None);
+    }
+    let mut tree = MerkleTree::<Keccak256Algorithm>::new();
+    for leaf in leaves.values() {
+        tree.insert(leaf.hash);
+    }
+    tree.commit();
+    Ok(tree
+        .root()
+        .map(|root| (root, leaves.len() as u64, max_event_index)))
+}
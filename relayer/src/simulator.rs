@@ -0,0 +1,1738 @@
+//! In-memory simulators of the Concordium and Ethereum chains, as seen by the
+//! relayer. They allow running the relayer's tasks end-to-end without access
+//! to a Concordium node or an Ethereum API.
+//!
+//! The simulators only model the behaviour the relayer relies on.
+//! - [`ConcordiumSimulator`] implements [`ConcordiumNode`]. It executes state
+//!   updates of the bridge manager contract and emits its events, and pauses
+//!   the bridge manager and the mapped tokens.
+//! - [`EthereumSimulator`] implements [`JsonRpcClient`], so it can be used
+//!   with [`Provider`](ethers::providers::Provider). It emits the events of the
+//!   state sender contract, and accepts Merkle roots and withdrawals sent to
+//!   the root chain manager.
+//!
+//! Both are cheap to clone and all clones share the same chain. A test can
+//! thus keep a handle to drive the chain, inject faults, and inspect its state
+//! while the relayer's tasks use the other clones.
+use crate::{
+    concordium_contracts::{
+        BridgeEvent, ClaimRequestedEvent, DepositEvent, DepositOperation, SetPausedEvent,
+        StateUpdate, TokenMapEvent, WithdrawEvent,
+    },
+    concordium_node::{ConcordiumNode, FinalizedBlocks},
+    erc20::Erc20Calls,
+    ethereum::{encode_deposit_data, DepositCall},
+    merkle::{Keccak256Algorithm, MerkleData},
+    root_chain_manager::BridgeManagerCalls,
+    state_sender::{LockedTokenFilter, MerkleRootFilter, TokenMapAddedFilter, WithdrawEventFilter},
+};
+use bridge_types::{
+    error_codes::{
+        CONTRACT_PAUSED, INVOKE_CONTRACT_ERROR, OPERATION_ALREADY_PROCESSED, PARSE_PARAMS,
+        TOKEN_NOT_MAPPED,
+    },
+    token_admin_events,
+};
+use concordium_rust_sdk::{
+    cis2,
+    common::types::Amount,
+    endpoints::{QueryError, QueryResult, RPCResult},
+    id::types::AccountAddress,
+    smart_contracts::common as contracts_common,
+    types::{
+        hashes::{BlockHash, TransactionHash},
+        queries::{AccountNonceResponse, BlockInfo},
+        smart_contracts::{
+            ContractContext, ContractEvent, InvokeContractResult, OwnedParameter, OwnedReceiveName,
+            ReturnValue, WasmVersion,
+        },
+        transactions::{BlockItem, EncodedPayload, Payload, UpdateContractPayload},
+        AbsoluteBlockHeight, AccountTransactionDetails, AccountTransactionEffects, BlockHeight,
+        BlockItemSummary, BlockItemSummaryDetails, ContractAddress, ContractTraceElement, Energy,
+        GenesisIndex, InstanceUpdatedEvent, Nonce, RejectReason, Slot, TransactionIndex,
+        TransactionStatus, TransactionType,
+    },
+    v2::{self, BlockIdentifier, FinalizedBlockInfo},
+};
+use ethabi::{
+    ethereum_types::{Address, H256, U256, U64},
+    Token,
+};
+use ethers::{
+    abi::{AbiDecode, AbiEncode},
+    contract::EthEvent,
+    providers::{JsonRpcClient, ProviderError},
+    types::{
+        transaction::eip2718::TypedTransaction, Block, Bytes, Log, NameOrAddress, Transaction,
+        TransactionReceipt, TransactionRequest,
+    },
+    utils::rlp::Rlp,
+};
+use rs_merkle::Hasher;
+use sha2::Digest;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::{Arc, Mutex, MutexGuard},
+};
+use tokio::sync::Notify;
+
+/// Type of Ethereum addresses in the interface of the simulators.
+type EthAddress = [u8; 20];
+
+/// Energy charged for every execution of the bridge manager.
+const BRIDGE_MANAGER_ENERGY: Energy = Energy { energy: 2_000 };
+
+/// Derive a deterministic hash from a domain and a counter. Used for the
+/// hashes of simulated blocks and transactions.
+fn derive_hash(domain: &str, counter: u64) -> [u8; 32] {
+    let mut hasher = sha2::Sha256::new();
+    hasher.update(domain.as_bytes());
+    hasher.update(counter.to_be_bytes());
+    hasher.finalize().into()
+}
+
+fn unavailable_status() -> v2::Status {
+    v2::Status::unavailable("The simulated Concordium node is unavailable.")
+}
+
+#[derive(Debug)]
+/// A finalized block on the simulated Concordium chain.
+struct ConcordiumBlock {
+    hash: BlockHash,
+    parent: BlockHash,
+    height: AbsoluteBlockHeight,
+    slot_time: chrono::DateTime<chrono::Utc>,
+    summaries: Vec<BlockItemSummary>,
+}
+
+impl ConcordiumBlock {
+    fn info(&self) -> BlockInfo {
+        BlockInfo {
+            transactions_size: 0,
+            block_parent: self.parent,
+            block_hash: self.hash,
+            finalized: true,
+            block_state_hash: derive_hash("concordium-state", self.height.height).into(),
+            block_arrive_time: self.slot_time,
+            block_receive_time: self.slot_time,
+            transaction_count: self.summaries.len() as u64,
+            transaction_energy_cost: self
+                .summaries
+                .iter()
+                .map(|s| s.energy_cost.energy)
+                .sum::<u64>()
+                .into(),
+            block_slot: Slot {
+                slot: self.height.height,
+            },
+            block_last_finalized: self.parent,
+            block_slot_time: self.slot_time,
+            block_height: self.height,
+            era_block_height: BlockHeight {
+                height: self.height.height,
+            },
+            genesis_index: GenesisIndex { height: 0 },
+            block_baker: None,
+        }
+    }
+}
+
+#[derive(Debug)]
+/// A transaction that was received, but is not yet in a block.
+enum PendingConcordiumTransaction {
+    /// A contract update sent via [`ConcordiumNode::send_block_item`].
+    Update {
+        hash: TransactionHash,
+        sender: AccountAddress,
+        payload: UpdateContractPayload,
+    },
+    /// A transaction made by a user of the bridge, with its outcome.
+    User(BlockItemSummary),
+}
+
+impl PendingConcordiumTransaction {
+    fn hash(&self) -> TransactionHash {
+        match self {
+            PendingConcordiumTransaction::Update { hash, .. } => *hash,
+            PendingConcordiumTransaction::User(summary) => summary.hash,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct ConcordiumChain {
+    /// Address of the simulated bridge manager contract.
+    bridge_manager: ContractAddress,
+    /// Finalized blocks, indexed by height.
+    blocks: Vec<ConcordiumBlock>,
+    /// Transactions that will be put in the next block.
+    pending: Vec<PendingConcordiumTransaction>,
+    /// Next nonce of accounts, taking into account pending transactions.
+    nonces: BTreeMap<AccountAddress, Nonce>,
+    /// Operation ids that were already executed by the bridge manager.
+    used_operations: BTreeSet<u64>,
+    /// Token mappings of the bridge manager.
+    token_maps: BTreeMap<EthAddress, ContractAddress>,
+    /// The bridge manager and the mapped tokens that are paused.
+    paused: BTreeSet<ContractAddress>,
+    /// Other contracts, which can receive deposits.
+    contracts: BTreeSet<ContractAddress>,
+    /// Deposits executed by the bridge manager, by operation id.
+    deposits: BTreeMap<u64, DepositOperation>,
+    /// Index of the next withdraw event emitted by the bridge manager.
+    next_event_index: u64,
+    /// Counter used for hashes of simulated user transactions.
+    next_user_transaction: u64,
+    /// Number of state update transactions received.
+    num_state_updates: u64,
+    /// Whether the node responds to queries.
+    available: bool,
+}
+
+// The errors are those of the node API.
+#[allow(clippy::result_large_err)]
+impl ConcordiumChain {
+    fn ensure_available(&self) -> QueryResult<()> {
+        if self.available {
+            Ok(())
+        } else {
+            Err(QueryError::RPCError(unavailable_status().into()))
+        }
+    }
+
+    fn find_block(&self, block_hash: &BlockHash) -> QueryResult<&ConcordiumBlock> {
+        self.blocks
+            .iter()
+            .find(|b| &b.hash == block_hash)
+            .ok_or(QueryError::NotFound)
+    }
+
+    fn last_final(&self) -> &ConcordiumBlock {
+        // The genesis block is always present.
+        &self.blocks[self.blocks.len() - 1]
+    }
+
+    /// Check whether the update can be executed, and return the event the
+    /// bridge manager would emit, if any.
+    fn check_state_update(&self, update: &StateUpdate) -> Result<Option<BridgeEvent>, i32> {
+        if self.paused.contains(&self.bridge_manager) {
+            return Err(CONTRACT_PAUSED);
+        }
+        match update {
+            StateUpdate::Deposit(deposit) => {
+                if self.used_operations.contains(&deposit.id) {
+                    return Err(OPERATION_ALREADY_PROCESSED);
+                }
+                let contract = *self
+                    .token_maps
+                    .get(&deposit.root.eth_address)
+                    .ok_or(TOKEN_NOT_MAPPED)?;
+                // The token rejects deposits with a hook to contracts that do not exist. The
+                // hook itself always accepts the deposit.
+                if let concordium_rust_sdk::types::Address::Contract(receiver) = deposit.user {
+                    if deposit.hook.is_some()
+                        && receiver != self.bridge_manager
+                        && !self.contracts.contains(&receiver)
+                        && !self.token_maps.values().any(|token| *token == receiver)
+                    {
+                        return Err(INVOKE_CONTRACT_ERROR);
+                    }
+                }
+                Ok(Some(BridgeEvent::Deposit(DepositEvent {
+                    id: deposit.id,
+                    contract,
+                    amount: deposit.amount.clone(),
+                    token_id: deposit.token_id.clone(),
+                })))
+            }
+            StateUpdate::TokenMap(map) => {
+                if self.used_operations.contains(&map.id) {
+                    return Err(OPERATION_ALREADY_PROCESSED);
+                }
+                Ok(Some(BridgeEvent::TokenMap(TokenMapEvent {
+                    id: map.id,
+                    root: map.root,
+                    child: map.child,
+                })))
+            }
+            StateUpdate::Skip(skip) => {
+                if skip.ids.iter().all(|id| self.used_operations.contains(id)) {
+                    return Err(OPERATION_ALREADY_PROCESSED);
+                }
+                Ok(None)
+            }
+        }
+    }
+
+    /// Execute a state update on the bridge manager, returning the emitted
+    /// event, if any, or the reject reason of the contract.
+    fn execute_state_update(
+        &mut self,
+        payload: &UpdateContractPayload,
+    ) -> Result<Option<BridgeEvent>, i32> {
+        let update: StateUpdate =
+            contracts_common::from_bytes(payload.message.as_ref()).map_err(|_| PARSE_PARAMS)?;
+        let event = self.check_state_update(&update)?;
+        match update {
+            StateUpdate::Deposit(deposit) => {
+                self.used_operations.insert(deposit.id);
+                self.deposits.insert(deposit.id, deposit);
+            }
+            StateUpdate::TokenMap(map) => {
+                self.used_operations.insert(map.id);
+                self.token_maps.insert(map.root.eth_address, map.child);
+            }
+            StateUpdate::Skip(skip) => self.used_operations.extend(skip.ids),
+        }
+        Ok(event)
+    }
+
+    /// Pause or unpause the bridge manager or a mapped token, returning the
+    /// emitted event. The roles of the sender are not checked.
+    fn execute_set_paused(&mut self, payload: &UpdateContractPayload) -> Result<Vec<u8>, i32> {
+        let paused: bool =
+            contracts_common::from_bytes(payload.message.as_ref()).map_err(|_| PARSE_PARAMS)?;
+        if paused {
+            self.paused.insert(payload.address);
+        } else {
+            self.paused.remove(&payload.address);
+        }
+        if payload.address == self.bridge_manager {
+            Ok(contracts_common::to_bytes(&BridgeEvent::SetPaused(
+                SetPausedEvent { paused },
+            )))
+        } else {
+            Ok(vec![token_admin_events::SET_PAUSED, u8::from(paused)])
+        }
+    }
+
+    /// Execute a pending transaction and produce its summary.
+    fn execute(&mut self, index: u64, tx: PendingConcordiumTransaction) -> BlockItemSummary {
+        let (hash, sender, payload) = match tx {
+            PendingConcordiumTransaction::Update {
+                hash,
+                sender,
+                payload,
+            } => (hash, sender, payload),
+            PendingConcordiumTransaction::User(mut summary) => {
+                summary.index = TransactionIndex { index };
+                return summary;
+            }
+        };
+        let is_bridge_manager = payload.address == self.bridge_manager;
+        let is_token = self.token_maps.values().any(|t| *t == payload.address);
+        let result = match payload.receive_name.as_receive_name().get_chain_name() {
+            "bridge-manager.receiveStateUpdate" if is_bridge_manager => Some(
+                self.execute_state_update(&payload)
+                    .map(|event| event.map(|event| contracts_common::to_bytes(&event))),
+            ),
+            "bridge-manager.setPaused" if is_bridge_manager => {
+                Some(self.execute_set_paused(&payload).map(Some))
+            }
+            "cis2-bridgeable.setPaused" if is_token => {
+                Some(self.execute_set_paused(&payload).map(Some))
+            }
+            _ => None,
+        };
+        let effects = match result {
+            None => AccountTransactionEffects::None {
+                transaction_type: Some(TransactionType::Update),
+                reject_reason: RejectReason::InvalidContractAddress {
+                    contents: payload.address,
+                },
+            },
+            Some(Ok(event)) => AccountTransactionEffects::ContractUpdateIssued {
+                effects: vec![ContractTraceElement::Updated {
+                    data: InstanceUpdatedEvent {
+                        contract_version: WasmVersion::V1,
+                        address: payload.address,
+                        instigator: sender.into(),
+                        amount: payload.amount,
+                        message: payload.message,
+                        receive_name: payload.receive_name,
+                        events: event.into_iter().map(ContractEvent::from).collect(),
+                    },
+                }],
+            },
+            Some(Err(reject_reason)) => AccountTransactionEffects::None {
+                transaction_type: Some(TransactionType::Update),
+                reject_reason: RejectReason::RejectedReceive {
+                    reject_reason,
+                    contract_address: payload.address,
+                    receive_name: payload.receive_name,
+                    parameter: payload.message,
+                },
+            },
+        };
+        BlockItemSummary {
+            index: TransactionIndex { index },
+            energy_cost: BRIDGE_MANAGER_ENERGY,
+            hash,
+            details: BlockItemSummaryDetails::AccountTransaction(AccountTransactionDetails {
+                cost: Amount::from_micro_ccd(0),
+                sender,
+                effects,
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+/// A simulated Concordium chain with a bridge manager contract.
+///
+/// Blocks are only produced by [`produce_block`](Self::produce_block), and
+/// are immediately finalized. Transactions that are sent to the node are
+/// executed when they are put in a block.
+pub struct ConcordiumSimulator {
+    chain: Arc<Mutex<ConcordiumChain>>,
+    /// Notified when a new block is produced.
+    new_block: Arc<Notify>,
+}
+
+impl ConcordiumSimulator {
+    /// Construct a new chain with only a genesis block, and the bridge
+    /// manager at the given address.
+    pub fn new(bridge_manager: ContractAddress) -> Self {
+        let genesis_hash = derive_hash("concordium-block", 0).into();
+        let genesis = ConcordiumBlock {
+            hash: genesis_hash,
+            parent: genesis_hash,
+            height: AbsoluteBlockHeight { height: 0 },
+            slot_time: chrono::Utc::now(),
+            summaries: Vec::new(),
+        };
+        Self {
+            chain: Arc::new(Mutex::new(ConcordiumChain {
+                bridge_manager,
+                blocks: vec![genesis],
+                pending: Vec::new(),
+                nonces: BTreeMap::new(),
+                used_operations: BTreeSet::new(),
+                token_maps: BTreeMap::new(),
+                paused: BTreeSet::new(),
+                contracts: BTreeSet::new(),
+                deposits: BTreeMap::new(),
+                next_event_index: 0,
+                next_user_transaction: 0,
+                num_state_updates: 0,
+                available: true,
+            })),
+            new_block: Arc::new(Notify::new()),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, ConcordiumChain> {
+        // The lock is never held across panics, except in failing tests.
+        self.chain
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Put all pending transactions in a new finalized block.
+    /// Returns the height of the new block.
+    pub fn produce_block(&self) -> AbsoluteBlockHeight {
+        let height = {
+            let mut chain = self.lock();
+            let pending = std::mem::take(&mut chain.pending);
+            let summaries = pending
+                .into_iter()
+                .enumerate()
+                .map(|(i, tx)| chain.execute(i as u64, tx))
+                .collect();
+            let parent = chain.last_final().hash;
+            let height = AbsoluteBlockHeight {
+                height: chain.blocks.len() as u64,
+            };
+            chain.blocks.push(ConcordiumBlock {
+                hash: derive_hash("concordium-block", height.height).into(),
+                parent,
+                height,
+                slot_time: chrono::Utc::now(),
+                summaries,
+            });
+            height
+        };
+        self.new_block.notify_waiters();
+        height
+    }
+
+    /// Produce a new block at every tick of the given interval. This never
+    /// terminates, so it should be spawned as a separate task.
+    pub async fn produce_blocks(self, interval: std::time::Duration) {
+        let mut interval = tokio::time::interval(interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            self.produce_block();
+        }
+    }
+
+    /// Make the node fail (or succeed again) all queries.
+    pub fn set_available(&self, available: bool) {
+        self.lock().available = available;
+    }
+
+    /// Make a withdrawal of the given token by the given user. The
+    /// transaction is put in the next block. If `claim_fee` is set the user
+    /// also requests the relayer to claim the withdrawal on Ethereum.
+    ///
+    /// Returns the hash of the transaction and the index of the emitted
+    /// withdraw event.
+    pub fn withdraw(
+        &self,
+        sender: AccountAddress,
+        contract: ContractAddress,
+        amount: cis2::TokenAmount,
+        eth_address: EthAddress,
+        claim_fee: Option<Amount>,
+    ) -> (TransactionHash, u64) {
+        let mut chain = self.lock();
+        let event_index = chain.next_event_index;
+        chain.next_event_index += 1;
+        let hash = derive_hash("concordium-user-transaction", chain.next_user_transaction).into();
+        chain.next_user_transaction += 1;
+        let mut events = vec![ContractEvent::from(contracts_common::to_bytes(
+            &BridgeEvent::Withdraw(WithdrawEvent {
+                event_index,
+                contract,
+                amount,
+                ccd_address: sender.into(),
+                eth_address: eth_address.into(),
+                token_id: cis2::TokenId::new_unchecked(vec![0u8; 8]),
+                fee: Some(cis2::TokenAmount(0u64.into())),
+            }),
+        ))];
+        if let Some(fee) = claim_fee {
+            events.push(ContractEvent::from(contracts_common::to_bytes(
+                &BridgeEvent::ClaimRequested(ClaimRequestedEvent { event_index, fee }),
+            )));
+        }
+        let (receive_name, amount) = if let Some(fee) = claim_fee {
+            ("bridge-manager.withdrawWithClaim", fee)
+        } else {
+            ("bridge-manager.withdraw", Amount::from_micro_ccd(0))
+        };
+        let summary = BlockItemSummary {
+            index: TransactionIndex { index: 0 },
+            energy_cost: BRIDGE_MANAGER_ENERGY,
+            hash,
+            details: BlockItemSummaryDetails::AccountTransaction(AccountTransactionDetails {
+                cost: Amount::from_micro_ccd(0),
+                sender,
+                effects: AccountTransactionEffects::ContractUpdateIssued {
+                    effects: vec![ContractTraceElement::Updated {
+                        data: InstanceUpdatedEvent {
+                            contract_version: WasmVersion::V1,
+                            address: chain.bridge_manager,
+                            instigator: sender.into(),
+                            amount,
+                            message: OwnedParameter::empty(),
+                            receive_name: OwnedReceiveName::new_unchecked(receive_name.into()),
+                            events,
+                        },
+                    }],
+                },
+            }),
+        };
+        chain
+            .pending
+            .push(PendingConcordiumTransaction::User(summary));
+        (hash, event_index)
+    }
+
+    /// Make an update of the given contract by the given account that logs
+    /// the given events, e.g., an administrative action. The transaction is
+    /// put in the next block. Returns the hash of the transaction.
+    pub fn update(
+        &self,
+        sender: AccountAddress,
+        contract: ContractAddress,
+        receive_name: &str,
+        events: Vec<Vec<u8>>,
+    ) -> TransactionHash {
+        let mut chain = self.lock();
+        let hash = derive_hash("concordium-user-transaction", chain.next_user_transaction).into();
+        chain.next_user_transaction += 1;
+        let summary = BlockItemSummary {
+            index: TransactionIndex { index: 0 },
+            energy_cost: BRIDGE_MANAGER_ENERGY,
+            hash,
+            details: BlockItemSummaryDetails::AccountTransaction(AccountTransactionDetails {
+                cost: Amount::from_micro_ccd(0),
+                sender,
+                effects: AccountTransactionEffects::ContractUpdateIssued {
+                    effects: vec![ContractTraceElement::Updated {
+                        data: InstanceUpdatedEvent {
+                            contract_version: WasmVersion::V1,
+                            address: contract,
+                            instigator: sender.into(),
+                            amount: Amount::from_micro_ccd(0),
+                            message: OwnedParameter::empty(),
+                            receive_name: OwnedReceiveName::new_unchecked(receive_name.into()),
+                            events: events.into_iter().map(ContractEvent::from).collect(),
+                        },
+                    }],
+                },
+            }),
+        };
+        chain
+            .pending
+            .push(PendingConcordiumTransaction::User(summary));
+        hash
+    }
+
+    /// Whether the operation with the given id was executed by the bridge
+    /// manager.
+    pub fn is_operation_used(&self, id: u64) -> bool {
+        self.lock().used_operations.contains(&id)
+    }
+
+    /// The token on Concordium the given Ethereum token is mapped to.
+    pub fn token_map(&self, root: EthAddress) -> Option<ContractAddress> {
+        self.lock().token_maps.get(&root).copied()
+    }
+
+    /// The number of state update transactions that were sent to the node.
+    /// Transactions rejected by the node, e.g., duplicates, are not counted.
+    pub fn num_state_updates(&self) -> u64 {
+        self.lock().num_state_updates
+    }
+
+    /// Whether the bridge manager or a mapped token is paused.
+    pub fn is_paused(&self, contract: ContractAddress) -> bool {
+        self.lock().paused.contains(&contract)
+    }
+
+    /// Create a contract, which can then receive deposits with a hook.
+    pub fn add_contract(&self, contract: ContractAddress) {
+        self.lock().contracts.insert(contract);
+    }
+
+    /// The deposit executed by the bridge manager as the given operation.
+    pub fn deposit_operation(&self, id: u64) -> Option<DepositOperation> {
+        self.lock().deposits.get(&id).cloned()
+    }
+
+    /// The height of the last finalized block.
+    pub fn last_finalized_height(&self) -> AbsoluteBlockHeight {
+        self.lock().last_final().height
+    }
+}
+
+/// A stream of finalized blocks of a [`ConcordiumSimulator`].
+pub struct SimulatedFinalizedBlocks {
+    node: ConcordiumSimulator,
+    next_height: u64,
+}
+
+#[async_trait::async_trait]
+impl FinalizedBlocks for SimulatedFinalizedBlocks {
+    async fn next_chunk_timeout(
+        &mut self,
+        n: usize,
+        duration: std::time::Duration,
+    ) -> Result<(bool, Vec<FinalizedBlockInfo>), tokio::time::error::Elapsed> {
+        tokio::time::timeout(duration, async {
+            loop {
+                // Register for notifications before checking so that no new
+                // block is missed.
+                let notified = self.node.new_block.notified();
+                {
+                    let chain = self.node.lock();
+                    if !chain.available {
+                        return (true, Vec::new());
+                    }
+                    let chunk = chain
+                        .blocks
+                        .iter()
+                        .skip(self.next_height as usize)
+                        .take(n)
+                        .map(|b| FinalizedBlockInfo {
+                            block_hash: b.hash,
+                            height: b.height,
+                        })
+                        .collect::<Vec<_>>();
+                    if !chunk.is_empty() {
+                        self.next_height += chunk.len() as u64;
+                        return (false, chunk);
+                    }
+                }
+                notified.await;
+            }
+        })
+        .await
+    }
+}
+
+#[async_trait::async_trait]
+impl ConcordiumNode for ConcordiumSimulator {
+    type FinalizedBlocks = SimulatedFinalizedBlocks;
+
+    async fn get_finalized_blocks_from(
+        &mut self,
+        start_height: AbsoluteBlockHeight,
+    ) -> QueryResult<Self::FinalizedBlocks> {
+        self.lock().ensure_available()?;
+        Ok(SimulatedFinalizedBlocks {
+            node: self.clone(),
+            next_height: start_height.height,
+        })
+    }
+
+    async fn get_block_info(&mut self, block_hash: BlockHash) -> QueryResult<BlockInfo> {
+        let chain = self.lock();
+        chain.ensure_available()?;
+        Ok(chain.find_block(&block_hash)?.info())
+    }
+
+    async fn get_last_finalized_block(&mut self) -> QueryResult<BlockInfo> {
+        let chain = self.lock();
+        chain.ensure_available()?;
+        Ok(chain.last_final().info())
+    }
+
+    async fn get_block_transaction_events(
+        &mut self,
+        block_hash: BlockHash,
+    ) -> QueryResult<Vec<BlockItemSummary>> {
+        let chain = self.lock();
+        chain.ensure_available()?;
+        Ok(chain.find_block(&block_hash)?.summaries.clone())
+    }
+
+    /// Invocations are always evaluated in the last finalized block.
+    async fn invoke_instance(
+        &mut self,
+        _bi: BlockIdentifier,
+        context: &ContractContext,
+    ) -> QueryResult<InvokeContractResult> {
+        let chain = self.lock();
+        chain.ensure_available()?;
+        if context.contract != chain.bridge_manager {
+            return Ok(InvokeContractResult::Failure {
+                return_value: None,
+                reason: RejectReason::InvalidContractAddress {
+                    contents: context.contract,
+                },
+                used_energy: 0.into(),
+            });
+        }
+        let reject = |reject_reason| InvokeContractResult::Failure {
+            return_value: None,
+            reason: RejectReason::RejectedReceive {
+                reject_reason,
+                contract_address: context.contract,
+                receive_name: context.method.clone(),
+                parameter: context.parameter.clone(),
+            },
+            used_energy: BRIDGE_MANAGER_ENERGY,
+        };
+        let result = match context.method.as_receive_name().get_chain_name() {
+            "bridge-manager.checkOperationUsed" => {
+                let Ok(id) = contracts_common::from_bytes::<u64>(context.parameter.as_ref()) else {
+                    return Ok(reject(PARSE_PARAMS));
+                };
+                InvokeContractResult::Success {
+                    return_value: Some(ReturnValue {
+                        value: vec![u8::from(chain.used_operations.contains(&id))],
+                    }),
+                    events: Vec::new(),
+                    used_energy: BRIDGE_MANAGER_ENERGY,
+                }
+            }
+            "bridge-manager.receiveStateUpdate" => {
+                let Ok(update) =
+                    contracts_common::from_bytes::<StateUpdate>(context.parameter.as_ref())
+                else {
+                    return Ok(reject(PARSE_PARAMS));
+                };
+                match chain.check_state_update(&update) {
+                    Ok(event) => InvokeContractResult::Success {
+                        return_value: None,
+                        events: vec![ContractTraceElement::Updated {
+                            data: InstanceUpdatedEvent {
+                                contract_version: WasmVersion::V1,
+                                address: context.contract,
+                                instigator: context.invoker.unwrap_or(
+                                    concordium_rust_sdk::types::Address::Contract(context.contract),
+                                ),
+                                amount: context.amount,
+                                message: context.parameter.clone(),
+                                receive_name: context.method.clone(),
+                                events: event
+                                    .iter()
+                                    .map(|event| {
+                                        ContractEvent::from(contracts_common::to_bytes(event))
+                                    })
+                                    .collect(),
+                            },
+                        }],
+                        used_energy: BRIDGE_MANAGER_ENERGY,
+                    },
+                    Err(reject_reason) => reject(reject_reason),
+                }
+            }
+            _ => InvokeContractResult::Failure {
+                return_value: None,
+                reason: RejectReason::InvalidContractAddress {
+                    contents: context.contract,
+                },
+                used_energy: 0.into(),
+            },
+        };
+        Ok(result)
+    }
+
+    async fn send_block_item(
+        &mut self,
+        bi: &BlockItem<EncodedPayload>,
+    ) -> RPCResult<TransactionHash> {
+        let mut chain = self.lock();
+        if !chain.available {
+            return Err(unavailable_status().into());
+        }
+        let hash = bi.hash();
+        let known = chain.pending.iter().any(|tx| tx.hash() == hash)
+            || chain
+                .blocks
+                .iter()
+                .any(|b| b.summaries.iter().any(|s| s.hash == hash));
+        if known {
+            return Err(v2::Status::already_exists("Duplicate transaction.").into());
+        }
+        let BlockItem::AccountTransaction(at) = bi else {
+            return Err(
+                v2::Status::invalid_argument("Only account transactions are supported.").into(),
+            );
+        };
+        let next_nonce = chain
+            .nonces
+            .get(&at.header.sender)
+            .copied()
+            .unwrap_or(Nonce { nonce: 1 });
+        if at.header.nonce != next_nonce {
+            return Err(v2::Status::invalid_argument(format!(
+                "Invalid nonce {}, expected {next_nonce}.",
+                at.header.nonce
+            ))
+            .into());
+        }
+        let Ok(Payload::Update { payload }) = at.payload.decode() else {
+            return Err(
+                v2::Status::invalid_argument("Only contract updates are supported.").into(),
+            );
+        };
+        chain.nonces.insert(at.header.sender, next_nonce.next());
+        if payload.receive_name.as_receive_name().get_chain_name()
+            == "bridge-manager.receiveStateUpdate"
+        {
+            chain.num_state_updates += 1;
+        }
+        chain.pending.push(PendingConcordiumTransaction::Update {
+            hash,
+            sender: at.header.sender,
+            payload,
+        });
+        Ok(hash)
+    }
+
+    async fn get_block_item_status(
+        &mut self,
+        tx_hash: &TransactionHash,
+    ) -> QueryResult<TransactionStatus> {
+        let chain = self.lock();
+        chain.ensure_available()?;
+        if chain.pending.iter().any(|tx| &tx.hash() == tx_hash) {
+            return Ok(TransactionStatus::Received);
+        }
+        for block in &chain.blocks {
+            if let Some(summary) = block.summaries.iter().find(|s| &s.hash == tx_hash) {
+                return Ok(TransactionStatus::Finalized(
+                    [(block.hash, summary.clone())].into(),
+                ));
+            }
+        }
+        Err(QueryError::NotFound)
+    }
+
+    async fn get_next_account_sequence_number(
+        &mut self,
+        address: &AccountAddress,
+    ) -> QueryResult<AccountNonceResponse> {
+        let chain = self.lock();
+        chain.ensure_available()?;
+        let all_final = !chain.pending.iter().any(|tx| {
+            matches!(tx, PendingConcordiumTransaction::Update { sender, .. } if sender == address)
+        });
+        Ok(AccountNonceResponse {
+            nonce: chain
+                .nonces
+                .get(address)
+                .copied()
+                .unwrap_or(Nonce { nonce: 1 }),
+            all_final,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+/// State of the simulated Ethereum contracts.
+struct EthereumContracts {
+    /// The current Merkle root of the root chain manager, if set.
+    merkle_root: Option<[u8; 32]>,
+    /// The previous Merkle root. Withdrawals are accepted against both.
+    previous_merkle_root: Option<[u8; 32]>,
+    /// Id of the next event emitted by the state sender.
+    next_id: u64,
+    /// Leaf hashes of withdrawals that were completed.
+    processed_exits: BTreeSet<[u8; 32]>,
+    /// Mapped tokens, indexed by the address of the token on Concordium.
+    child_to_root: BTreeMap<(u64, u64), Address>,
+    /// Nonces of accounts.
+    nonces: BTreeMap<Address, U256>,
+}
+
+#[derive(Debug, Clone)]
+/// An operation that is executed when a block is mined.
+#[allow(clippy::large_enum_variant)]
+enum EthereumOperation {
+    /// A signed transaction sent via `eth_sendRawTransaction`.
+    Transaction { hash: H256, tx: TypedTransaction },
+    /// A deposit of tokens by a user.
+    Deposit {
+        hash: H256,
+        depositor: Address,
+        receiver: AccountAddress,
+        root_token: Address,
+        vault: Address,
+        deposit_data: Vec<u8>,
+    },
+    /// A new token mapping made by the mapper.
+    MapToken {
+        hash: H256,
+        root_token: Address,
+        child_token: ContractAddress,
+        token_type: [u8; 32],
+    },
+}
+
+impl EthereumOperation {
+    fn hash(&self) -> H256 {
+        match self {
+            EthereumOperation::Transaction { hash, .. } => *hash,
+            EthereumOperation::Deposit { hash, .. } => *hash,
+            EthereumOperation::MapToken { hash, .. } => *hash,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct EthereumBlock {
+    number: u64,
+    hash: H256,
+    /// Unix timestamp of the block in seconds.
+    timestamp: u64,
+    /// Operations included in the block, in order.
+    operations: Vec<EthereumOperation>,
+    /// Receipts of the operations in the block.
+    receipts: Vec<TransactionReceipt>,
+    /// Logs emitted in the block.
+    logs: Vec<Log>,
+    /// State of the contracts after the block.
+    contracts: EthereumContracts,
+}
+
+#[derive(Debug, Clone)]
+/// An ERC20 token known to the simulated chain.
+struct Erc20Token {
+    name: String,
+    symbol: String,
+    decimals: u8,
+}
+
+#[derive(Debug)]
+struct EthereumChain {
+    chain_id: u64,
+    state_sender: Address,
+    root_chain_manager: Address,
+    /// Blocks of the canonical chain, indexed by number.
+    blocks: Vec<EthereumBlock>,
+    /// Operations that will be put in the next block.
+    mempool: Vec<EthereumOperation>,
+    /// ERC20 tokens that can be queried via `eth_call`.
+    tokens: BTreeMap<Address, Erc20Token>,
+    gas_price: U256,
+    balance: U256,
+    /// Fee charged by the root chain manager for each withdrawal.
+    withdraw_fee: U256,
+    /// Number of reorganizations so far. Used to derive fresh block hashes.
+    num_reorgs: u64,
+    /// Counter used for hashes of simulated user transactions.
+    next_user_transaction: u64,
+    /// Whether the API responds to requests.
+    available: bool,
+}
+
+/// Make a log in the format the state sender emits it.
+fn state_sender_log(address: Address, topics: Vec<H256>, data: Vec<Token>) -> Log {
+    Log {
+        address,
+        topics,
+        data: ethabi::encode(&data).into(),
+        removed: Some(false),
+        ..Default::default()
+    }
+}
+
+/// Convert a value to an indexed topic.
+fn topic(value: impl Into<U256>) -> H256 {
+    let mut bytes = [0u8; 32];
+    value.into().to_big_endian(&mut bytes);
+    H256(bytes)
+}
+
+impl EthereumChain {
+    fn head(&self) -> &EthereumBlock {
+        // The genesis block is always present.
+        &self.blocks[self.blocks.len() - 1]
+    }
+
+    fn block_hash(&self, number: u64) -> H256 {
+        let mut hasher = sha3::Keccak256::new();
+        hasher.update(number.to_be_bytes());
+        hasher.update(self.num_reorgs.to_be_bytes());
+        H256(hasher.finalize().into())
+    }
+
+    fn next_user_hash(&mut self) -> H256 {
+        let hash = derive_hash("ethereum-user-transaction", self.next_user_transaction);
+        self.next_user_transaction += 1;
+        H256(hash)
+    }
+
+    /// Execute an operation, returning the emitted logs or `None` if the
+    /// operation reverted.
+    fn execute(
+        &self,
+        contracts: &mut EthereumContracts,
+        operation: &EthereumOperation,
+    ) -> Option<Vec<Log>> {
+        match operation {
+            EthereumOperation::Deposit {
+                depositor,
+                receiver,
+                root_token,
+                vault,
+                deposit_data,
+                ..
+            } => {
+                let id = contracts.next_id;
+                contracts.next_id += 1;
+                Some(vec![state_sender_log(
+                    self.state_sender,
+                    vec![
+                        LockedTokenFilter::signature(),
+                        H256::from(*depositor),
+                        H256::from(*root_token),
+                        H256::from(*vault),
+                    ],
+                    vec![
+                        Token::Uint(id.into()),
+                        Token::FixedBytes(receiver.0.to_vec()),
+                        Token::Bytes(deposit_data.clone()),
+                    ],
+                )])
+            }
+            EthereumOperation::MapToken {
+                root_token,
+                child_token,
+                token_type,
+                ..
+            } => {
+                let id = contracts.next_id;
+                contracts.next_id += 1;
+                contracts
+                    .child_to_root
+                    .insert((child_token.index, child_token.subindex), *root_token);
+                Some(vec![state_sender_log(
+                    self.state_sender,
+                    vec![
+                        TokenMapAddedFilter::signature(),
+                        H256::from(*root_token),
+                        H256(*token_type),
+                    ],
+                    vec![
+                        Token::Uint(id.into()),
+                        Token::Uint(child_token.index.into()),
+                        Token::Uint(child_token.subindex.into()),
+                    ],
+                )])
+            }
+            EthereumOperation::Transaction { tx, .. } => {
+                let to = match tx.to() {
+                    Some(NameOrAddress::Address(to)) => *to,
+                    _ => return None,
+                };
+                if to != self.root_chain_manager {
+                    // Plain transfers and calls to other contracts have no effect.
+                    return Some(Vec::new());
+                }
+                let data = tx.data()?;
+                match BridgeManagerCalls::decode(data).ok()? {
+                    BridgeManagerCalls::SetMerkleRoot(call) => {
+                        contracts.previous_merkle_root = contracts.merkle_root;
+                        contracts.merkle_root = Some(call.merkle_root);
+                        let id = contracts.next_id;
+                        contracts.next_id += 1;
+                        Some(vec![state_sender_log(
+                            self.state_sender,
+                            vec![MerkleRootFilter::signature()],
+                            vec![
+                                Token::Uint(id.into()),
+                                Token::FixedBytes(call.merkle_root.to_vec()),
+                            ],
+                        )])
+                    }
+                    BridgeManagerCalls::Withdraw(call) => {
+                        if tx.value().copied().unwrap_or_default() < self.withdraw_fee {
+                            return None;
+                        }
+                        let params = call.withdraw;
+                        let transaction_hash: TransactionHash = params.ccd_tx_hash.parse().ok()?;
+                        // The leaf is computed the same way the relayer computes it.
+                        let exit_hash = Keccak256Algorithm::hash(
+                            &MerkleData {
+                                child_token: ContractAddress::new(
+                                    params.ccd_index,
+                                    params.ccd_sub_index,
+                                ),
+                                amount: params.amount.into(),
+                                user_wallet: params.user_wallet,
+                                transaction_hash,
+                                transaction_event_id: params.ccd_event_index,
+                                token_id: params.token_id,
+                            }
+                            .encode(),
+                        );
+                        if contracts.processed_exits.contains(&exit_hash) {
+                            return None;
+                        }
+                        let root = call.proof.iter().fold(exit_hash, |acc, node| {
+                            Keccak256Algorithm::concat_and_hash(&acc, Some(node))
+                        });
+                        if contracts.merkle_root != Some(root)
+                            && contracts.previous_merkle_root != Some(root)
+                        {
+                            return None;
+                        }
+                        if !contracts
+                            .child_to_root
+                            .contains_key(&(params.ccd_index, params.ccd_sub_index))
+                        {
+                            return None;
+                        }
+                        contracts.processed_exits.insert(exit_hash);
+                        let id = contracts.next_id;
+                        contracts.next_id += 1;
+                        Some(vec![state_sender_log(
+                            self.state_sender,
+                            vec![
+                                WithdrawEventFilter::signature(),
+                                topic(params.ccd_index),
+                                topic(params.ccd_sub_index),
+                                H256::from(params.user_wallet),
+                            ],
+                            vec![
+                                Token::Uint(id.into()),
+                                Token::Uint(params.amount.into()),
+                                Token::FixedBytes(transaction_hash.as_ref().to_vec()),
+                                Token::Uint(params.ccd_event_index.into()),
+                                Token::Uint(params.token_id.into()),
+                            ],
+                        )])
+                    }
+                    _ => None,
+                }
+            }
+        }
+    }
+
+    /// Mine a new block with all operations from the mempool that can be
+    /// executed. Transactions whose nonce is not the next nonce of the sender
+    /// stay in the mempool.
+    fn mine(&mut self) -> u64 {
+        let number = self.blocks.len() as u64;
+        let hash = self.block_hash(number);
+        let mut contracts = self.head().contracts.clone();
+        let mut operations = Vec::new();
+        let mut receipts = Vec::new();
+        let mut logs = Vec::new();
+        let mut mempool = std::mem::take(&mut self.mempool);
+        // Loop until no more transactions can be included. Transactions from the
+        // same sender might be in the mempool in any order.
+        loop {
+            let next = mempool.iter().position(|op| match op {
+                EthereumOperation::Transaction { tx, .. } => {
+                    let from = tx.from().copied().unwrap_or_default();
+                    tx.nonce() == Some(&contracts.nonces.get(&from).copied().unwrap_or_default())
+                }
+                _ => true,
+            });
+            let Some(next) = next else { break };
+            let operation = mempool.remove(next);
+            let result = self.execute(&mut contracts, &operation);
+            let transaction_index = U64::from(operations.len());
+            let new_logs = result
+                .clone()
+                .unwrap_or_default()
+                .into_iter()
+                .enumerate()
+                .map(|(i, log)| Log {
+                    block_hash: Some(hash),
+                    block_number: Some(number.into()),
+                    transaction_hash: Some(operation.hash()),
+                    transaction_index: Some(transaction_index),
+                    log_index: Some((logs.len() + i).into()),
+                    ..log
+                })
+                .collect::<Vec<_>>();
+            if let EthereumOperation::Transaction { tx, .. } = &operation {
+                let from = tx.from().copied().unwrap_or_default();
+                let nonce = contracts.nonces.entry(from).or_default();
+                *nonce += U256::one();
+                // Transactions that were replaced by this one are dropped.
+                let nonce = *nonce;
+                mempool.retain(|op| match op {
+                    EthereumOperation::Transaction { tx, .. } => {
+                        tx.from() != Some(&from) || tx.nonce().is_none_or(|n| *n >= nonce)
+                    }
+                    _ => true,
+                });
+            }
+            // Deposits and token mappings are sent by accounts that are not
+            // simulated, so their receipts only record the sender, if known.
+            let (from, to, gas_used, effective_gas_price) = match &operation {
+                EthereumOperation::Transaction { tx, .. } => (
+                    tx.from().copied().unwrap_or_default(),
+                    match tx.to() {
+                        Some(NameOrAddress::Address(to)) => Some(*to),
+                        _ => None,
+                    },
+                    tx.gas().copied(),
+                    tx.gas_price(),
+                ),
+                EthereumOperation::Deposit { depositor, .. } => (*depositor, None, None, None),
+                EthereumOperation::MapToken { .. } => (Address::zero(), None, None, None),
+            };
+            receipts.push(TransactionReceipt {
+                transaction_hash: operation.hash(),
+                transaction_index,
+                block_hash: Some(hash),
+                block_number: Some(number.into()),
+                from,
+                to,
+                gas_used,
+                effective_gas_price,
+                logs: new_logs.clone(),
+                status: Some(u64::from(result.is_some()).into()),
+                ..Default::default()
+            });
+            logs.extend(new_logs);
+            operations.push(operation);
+        }
+        self.mempool = mempool;
+        self.blocks.push(EthereumBlock {
+            number,
+            hash,
+            timestamp: chrono::Utc::now().timestamp() as u64,
+            operations,
+            receipts,
+            logs,
+            contracts,
+        });
+        number
+    }
+
+    fn find_transaction(&self, hash: H256) -> Option<Transaction> {
+        let in_block = self.blocks.iter().find_map(|b| {
+            b.operations
+                .iter()
+                .enumerate()
+                .find_map(|(i, op)| match op {
+                    EthereumOperation::Transaction { hash: h, tx } if *h == hash => {
+                        Some((tx, Some((b.hash, b.number, i))))
+                    }
+                    _ => None,
+                })
+        });
+        let in_mempool = || {
+            self.mempool.iter().find_map(|op| match op {
+                EthereumOperation::Transaction { hash: h, tx } if *h == hash => Some((tx, None)),
+                _ => None,
+            })
+        };
+        let (tx, location) = in_block.or_else(in_mempool)?;
+        Some(Transaction {
+            hash,
+            nonce: tx.nonce().copied().unwrap_or_default(),
+            block_hash: location.map(|l| l.0),
+            block_number: location.map(|l| l.1.into()),
+            transaction_index: location.map(|l| l.2.into()),
+            from: tx.from().copied().unwrap_or_default(),
+            to: match tx.to() {
+                Some(NameOrAddress::Address(to)) => Some(*to),
+                _ => None,
+            },
+            value: tx.value().copied().unwrap_or_default(),
+            gas_price: tx.gas_price(),
+            gas: tx.gas().copied().unwrap_or_default(),
+            input: tx.data().cloned().unwrap_or_default(),
+            chain_id: tx.chain_id().map(|c| c.as_u64().into()),
+            ..Default::default()
+        })
+    }
+
+    fn send_raw_transaction(&mut self, raw: Bytes) -> Result<H256, ProviderError> {
+        let (tx, _) = TypedTransaction::decode_signed(&Rlp::new(&raw))
+            .map_err(|e| ProviderError::CustomError(format!("Invalid transaction: {e}")))?;
+        let hash = H256(ethers::utils::keccak256(&raw));
+        let known = self.mempool.iter().any(|op| op.hash() == hash)
+            || self
+                .blocks
+                .iter()
+                .any(|b| b.operations.iter().any(|op| op.hash() == hash));
+        if known {
+            return Err(ProviderError::CustomError("already known".into()));
+        }
+        let from = tx.from().copied().unwrap_or_default();
+        let next_nonce = self
+            .head()
+            .contracts
+            .nonces
+            .get(&from)
+            .copied()
+            .unwrap_or_default();
+        if tx.nonce().is_none_or(|n| *n < next_nonce) {
+            return Err(ProviderError::CustomError("nonce too low".into()));
+        }
+        self.mempool
+            .push(EthereumOperation::Transaction { hash, tx });
+        Ok(hash)
+    }
+
+    fn get_logs(&self, filter: &LogFilter) -> Vec<Log> {
+        self.blocks
+            .iter()
+            .filter(|b| filter.from_block <= b.number && b.number <= filter.to_block)
+            .flat_map(|b| b.logs.iter())
+            .filter(|log| filter.matches(log))
+            .cloned()
+            .collect()
+    }
+
+    fn call(&self, request: TransactionRequest) -> Result<Bytes, ProviderError> {
+        let revert = || ProviderError::CustomError("execution reverted".into());
+        let Some(NameOrAddress::Address(to)) = request.to else {
+            return Err(revert());
+        };
+        let data = request.data.ok_or_else(revert)?;
+        if to == self.root_chain_manager {
+            return match BridgeManagerCalls::decode(data).map_err(|_| revert())? {
+                BridgeManagerCalls::WithdrawFee(_) => Ok(self.withdraw_fee.encode().into()),
+                _ => Err(revert()),
+            };
+        }
+        let token = self.tokens.get(&to).ok_or_else(revert)?;
+        let result = match Erc20Calls::decode(data).map_err(|_| revert())? {
+            Erc20Calls::Name(_) => token.name.clone().encode(),
+            Erc20Calls::Symbol(_) => token.symbol.clone().encode(),
+            Erc20Calls::Decimals(_) => token.decimals.encode(),
+            _ => return Err(revert()),
+        };
+        Ok(result.into())
+    }
+
+    fn handle(
+        &mut self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<serde_json::Value, ProviderError> {
+        if !self.available {
+            return Err(ProviderError::CustomError(
+                "The simulated Ethereum API is unavailable.".into(),
+            ));
+        }
+        let params = match params {
+            serde_json::Value::Array(params) => params,
+            serde_json::Value::Null => Vec::new(),
+            param => vec![param],
+        };
+        let param = |i: usize| params.get(i).cloned().unwrap_or_default();
+        let value = match method {
+            "eth_chainId" => serde_json::to_value(U64::from(self.chain_id))?,
+            "eth_blockNumber" => serde_json::to_value(U64::from(self.head().number))?,
+            "eth_gasPrice" => serde_json::to_value(self.gas_price)?,
+            "eth_getBalance" => serde_json::to_value(self.balance)?,
+            "eth_getTransactionCount" => {
+                let address: Address = serde_json::from_value(param(0))?;
+                let nonce = self
+                    .head()
+                    .contracts
+                    .nonces
+                    .get(&address)
+                    .copied()
+                    .unwrap_or_default();
+                serde_json::to_value(nonce)?
+            }
+            "eth_getBlockByNumber" => {
+                let number = parse_block_number(param(0).as_str(), self.head().number)?;
+                let block = self.blocks.iter().find(|b| b.number == number);
+                let block = block.map(|b| Block::<H256> {
+                    number: Some(b.number.into()),
+                    hash: Some(b.hash),
+                    timestamp: b.timestamp.into(),
+                    transactions: b.receipts.iter().map(|r| r.transaction_hash).collect(),
+                    ..Default::default()
+                });
+                serde_json::to_value(block)?
+            }
+            "eth_getLogs" => {
+                let filter = LogFilter::parse(&param(0), self.head().number)?;
+                serde_json::to_value(self.get_logs(&filter))?
+            }
+            "eth_call" => {
+                let request: TransactionRequest = serde_json::from_value(param(0))?;
+                serde_json::to_value(self.call(request)?)?
+            }
+            "eth_sendRawTransaction" => {
+                let raw: Bytes = serde_json::from_value(param(0))?;
+                serde_json::to_value(self.send_raw_transaction(raw)?)?
+            }
+            "eth_getTransactionByHash" => {
+                let hash: H256 = serde_json::from_value(param(0))?;
+                serde_json::to_value(self.find_transaction(hash))?
+            }
+            "eth_getTransactionReceipt" => {
+                let hash: H256 = serde_json::from_value(param(0))?;
+                let receipt = self
+                    .blocks
+                    .iter()
+                    .flat_map(|b| b.receipts.iter())
+                    .find(|r| r.transaction_hash == hash);
+                serde_json::to_value(receipt)?
+            }
+            _ => return Err(ProviderError::UnsupportedRPC),
+        };
+        Ok(value)
+    }
+}
+
+/// Parse a block number parameter of the JSON-RPC API. Tags other than
+/// `earliest` refer to the head of the chain.
+fn parse_block_number(number: Option<&str>, head: u64) -> Result<u64, ProviderError> {
+    match number {
+        None | Some("latest") | Some("pending") | Some("safe") | Some("finalized") => Ok(head),
+        Some("earliest") => Ok(0),
+        Some(n) => u64::from_str_radix(n.trim_start_matches("0x"), 16)
+            .map_err(|_| ProviderError::CustomError(format!("Invalid block number {n}."))),
+    }
+}
+
+/// The parts of an `eth_getLogs` filter supported by the simulator.
+struct LogFilter {
+    from_block: u64,
+    to_block: u64,
+    /// If set, only logs emitted by one of these addresses match.
+    addresses: Option<Vec<Address>>,
+    /// For each position, the topics that match. `None` matches any topic.
+    topics: Vec<Option<Vec<H256>>>,
+}
+
+impl LogFilter {
+    fn parse(filter: &serde_json::Value, head: u64) -> Result<Self, ProviderError> {
+        let block_number = |key: &str| -> Result<u64, ProviderError> {
+            parse_block_number(filter.get(key).and_then(|v| v.as_str()), head)
+        };
+        let one_or_many = |value: &serde_json::Value| -> Result<Vec<H256>, ProviderError> {
+            if value.is_array() {
+                Ok(serde_json::from_value(value.clone())?)
+            } else {
+                Ok(vec![serde_json::from_value(value.clone())?])
+            }
+        };
+        if filter.get("blockHash").is_some() {
+            return Err(ProviderError::CustomError(
+                "Filtering logs by block hash is not supported.".into(),
+            ));
+        }
+        let addresses: Option<Vec<Address>> = match filter.get("address") {
+            None | Some(serde_json::Value::Null) => None,
+            Some(value) if value.is_array() => Some(serde_json::from_value(value.clone())?),
+            Some(value) => Some(vec![serde_json::from_value(value.clone())?]),
+        };
+        let topics = match filter.get("topics").and_then(|t| t.as_array()) {
+            None => Vec::new(),
+            Some(topics) => topics
+                .iter()
+                .map(|t| {
+                    if t.is_null() {
+                        Ok(None)
+                    } else {
+                        one_or_many(t).map(Some)
+                    }
+                })
+                .collect::<Result<_, ProviderError>>()?,
+        };
+        Ok(Self {
+            from_block: block_number("fromBlock")?,
+            to_block: block_number("toBlock")?,
+            addresses,
+            topics,
+        })
+    }
+
+    fn matches(&self, log: &Log) -> bool {
+        if let Some(addresses) = &self.addresses {
+            if !addresses.contains(&log.address) {
+                return false;
+            }
+        }
+        self.topics
+            .iter()
+            .enumerate()
+            .all(|(i, topics)| match topics {
+                None => true,
+                Some(topics) => log.topics.get(i).is_some_and(|t| topics.contains(t)),
+            })
+    }
+}
+
+#[derive(Debug, Clone)]
+/// A simulated Ethereum chain with the state sender and root chain manager
+/// contracts, and any number of ERC20 tokens.
+///
+/// Blocks are only produced by [`mine_block`](Self::mine_block).
+/// Transactions are included in the next block if their nonce is the next
+/// nonce of the sender. A transaction with the same nonce as a pending
+/// transaction replaces it once either is mined.
+pub struct EthereumSimulator {
+    chain: Arc<Mutex<EthereumChain>>,
+}
+
+impl EthereumSimulator {
+    /// Construct a new chain with only a genesis block. The state sender and
+    /// root chain manager contracts are at the given addresses.
+    pub fn new(chain_id: u64, state_sender: Address, root_chain_manager: Address) -> Self {
+        let mut chain = EthereumChain {
+            chain_id,
+            state_sender,
+            root_chain_manager,
+            blocks: Vec::new(),
+            mempool: Vec::new(),
+            tokens: BTreeMap::new(),
+            gas_price: U256::from(1_000_000_000u64),
+            balance: U256::exp10(20),
+            withdraw_fee: U256::zero(),
+            num_reorgs: 0,
+            next_user_transaction: 0,
+            available: true,
+        };
+        chain.blocks.push(EthereumBlock {
+            number: 0,
+            hash: chain.block_hash(0),
+            timestamp: chrono::Utc::now().timestamp() as u64,
+            operations: Vec::new(),
+            receipts: Vec::new(),
+            logs: Vec::new(),
+            // The state sender numbers its operations from 1.
+            contracts: EthereumContracts {
+                next_id: 1,
+                ..EthereumContracts::default()
+            },
+        });
+        Self {
+            chain: Arc::new(Mutex::new(chain)),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, EthereumChain> {
+        // The lock is never held across panics, except in failing tests.
+        self.chain
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Mine a new block with the executable operations in the mempool.
+    /// Returns the number of the new block.
+    pub fn mine_block(&self) -> u64 {
+        self.lock().mine()
+    }
+
+    /// Mine a new block at every tick of the given interval. This never
+    /// terminates, so it should be spawned as a separate task.
+    pub async fn mine_blocks(self, interval: std::time::Duration) {
+        let mut interval = tokio::time::interval(interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            self.mine_block();
+        }
+    }
+
+    /// Drop the last `depth` blocks. The operations in them are put back in
+    /// the mempool, so they are included again in the following blocks,
+    /// which will have different hashes.
+    pub fn reorg(&self, depth: u64) {
+        let mut chain = self.lock();
+        // Never drop the genesis block.
+        let keep = std::cmp::max(1, chain.blocks.len().saturating_sub(depth as usize));
+        let dropped = chain.blocks.split_off(keep);
+        let mut operations = dropped
+            .into_iter()
+            .flat_map(|b| b.operations)
+            .collect::<Vec<_>>();
+        operations.append(&mut chain.mempool);
+        chain.mempool = operations;
+        chain.num_reorgs += 1;
+    }
+
+    /// Make the API fail (or succeed again) all requests.
+    pub fn set_available(&self, available: bool) {
+        self.lock().available = available;
+    }
+
+    /// Set the gas price returned by the API.
+    pub fn set_gas_price(&self, gas_price: U256) {
+        self.lock().gas_price = gas_price;
+    }
+
+    /// Set the fee the root chain manager charges for each withdrawal. Claims
+    /// that pay less revert.
+    pub fn set_withdraw_fee(&self, withdraw_fee: U256) {
+        self.lock().withdraw_fee = withdraw_fee;
+    }
+
+    /// Register an ERC20 token whose name, symbol, and decimals can be
+    /// queried.
+    pub fn add_erc20_token(&self, address: Address, name: &str, symbol: &str, decimals: u8) {
+        self.lock().tokens.insert(
+            address,
+            Erc20Token {
+                name: name.into(),
+                symbol: symbol.into(),
+                decimals,
+            },
+        );
+    }
+
+    /// Map a token to the given token on Concordium. The mapping is included
+    /// in the next block. Returns the hash of the transaction.
+    pub fn map_token(
+        &self,
+        root_token: Address,
+        child_token: ContractAddress,
+        token_type: [u8; 32],
+    ) -> H256 {
+        let mut chain = self.lock();
+        let hash = chain.next_user_hash();
+        chain.mempool.push(EthereumOperation::MapToken {
+            hash,
+            root_token,
+            child_token,
+            token_type,
+        });
+        hash
+    }
+
+    /// Deposit tokens for the given Concordium account. The deposit is
+    /// included in the next block. Returns the hash of the transaction.
+    pub fn deposit(
+        &self,
+        depositor: Address,
+        receiver: AccountAddress,
+        root_token: Address,
+        vault: Address,
+        amount: U256,
+    ) -> H256 {
+        self.push_deposit(
+            depositor,
+            receiver,
+            root_token,
+            vault,
+            encode_deposit_data(amount, None),
+        )
+    }
+
+    /// Deposit tokens for the given contract on Concordium, which is notified
+    /// via its hook. The `receiver` gets the tokens if the contract cannot
+    /// receive them. The deposit is included in the next block. Returns the
+    /// hash of the transaction.
+    pub fn deposit_to_contract(
+        &self,
+        depositor: Address,
+        receiver: AccountAddress,
+        root_token: Address,
+        vault: Address,
+        amount: U256,
+        call: &DepositCall,
+    ) -> H256 {
+        self.push_deposit(
+            depositor,
+            receiver,
+            root_token,
+            vault,
+            encode_deposit_data(amount, Some(call)),
+        )
+    }
+
+    fn push_deposit(
+        &self,
+        depositor: Address,
+        receiver: AccountAddress,
+        root_token: Address,
+        vault: Address,
+        deposit_data: Vec<u8>,
+    ) -> H256 {
+        let mut chain = self.lock();
+        let hash = chain.next_user_hash();
+        chain.mempool.push(EthereumOperation::Deposit {
+            hash,
+            depositor,
+            receiver,
+            root_token,
+            vault,
+            deposit_data,
+        });
+        hash
+    }
+
+    /// The number of the latest block.
+    pub fn block_number(&self) -> u64 {
+        self.lock().head().number
+    }
+
+    /// The current Merkle root of the root chain manager, if set.
+    pub fn merkle_root(&self) -> Option<[u8; 32]> {
+        self.lock().head().contracts.merkle_root
+    }
+
+    /// The number of completed withdrawals.
+    pub fn num_withdrawals(&self) -> usize {
+        self.lock().head().contracts.processed_exits.len()
+    }
+}
+
+#[async_trait::async_trait]
+impl JsonRpcClient for EthereumSimulator {
+    type Error = ProviderError;
+
+    async fn request<T, R>(&self, method: &str, params: T) -> Result<R, Self::Error>
+    where
+        T: std::fmt::Debug + serde::Serialize + Send + Sync,
+        R: serde::de::DeserializeOwned,
+    {
+        let params = serde_json::to_value(params)?;
+        let value = self.lock().handle(method, params)?;
+        Ok(serde_json::from_value(value)?)
+    }
+}
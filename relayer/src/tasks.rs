@@ -0,0 +1,507 @@
+//! Wiring of the relayer's tasks.
+//!
+//! The tasks communicate via channels, which are set up here. The
+//! `ccdeth_relayer` binary and the end-to-end tests both construct the tasks
+//! with [`make_tasks`], so the tests run the same task graph as production,
+//! only against different clients.
+//!
+//! The tasks are returned unspawned, so that the caller decides how to run
+//! them. The shutdown plan is as follows.
+//! - [`Tasks::watchers`] only query, or sample, and are just aborted.
+//! - [`Tasks::senders`] send transactions or write to the database. They are
+//!   given an opportunity to shut down gracefully when the stop signal is sent.
+use crate::{
+    balance::BalanceMonitor,
+    blocklist::BlocklistSource,
+    claim::{self, ClaimClient},
+    concordium_contracts::{self, BridgeManagerClient},
+    concordium_node::ConcordiumNode,
+    config::{Tunables, TunablesReceiver},
+    db::{self, Database, DatabaseOperation, MerkleUpdate},
+    ethereum,
+    failover::{self, ConcordiumEndpoints, EndpointError, EthereumEndpoints},
+    health::{self, Health},
+    merkle::{self, MerkleSetterClient},
+    metrics::Metrics,
+    root_chain_manager, shadow,
+    state_sender::StateSender,
+};
+use anyhow::Context;
+use concordium_rust_sdk::{
+    id::types::AccountAddress,
+    types::{
+        transactions::{BlockItem, EncodedPayload},
+        AbsoluteBlockHeight, ContractAddress, WalletAccount,
+    },
+};
+use ethers::{
+    prelude::{JsonRpcClient, LocalWallet, Middleware, Provider, Signer},
+    types::{Address, U256},
+};
+use futures::{future::BoxFuture, FutureExt};
+use std::{sync::Arc, time::Duration};
+
+/// Capacity of the channels between the tasks of the relayer.
+pub const CHANNEL_CAPACITY: usize = 50;
+
+/// A named task that is not yet spawned.
+pub type Task = (&'static str, BoxFuture<'static, anyhow::Result<()>>);
+
+/// The keys of the accounts that the relayer sends transactions from,
+/// together with the next nonces of the Ethereum accounts. Without them the
+/// relayer runs in shadow mode.
+pub struct Signers {
+    pub concordium_wallet: WalletAccount,
+    /// Key used for setting Merkle roots.
+    pub wallet: LocalWallet,
+    pub ethereum_nonce: U256,
+    /// Key used for claiming withdrawals on behalf of users, if any.
+    pub claim_wallet: Option<LocalWallet>,
+    pub claim_nonce: U256,
+}
+
+/// The settings of the tasks that do not change while the relayer runs.
+pub struct TaskConfig {
+    pub db_config: tokio_postgres::Config,
+    pub bridge_manager: ContractAddress,
+    /// The sender of Concordium transactions. In shadow mode it is only used
+    /// to invoke the bridge manager.
+    pub concordium_sender: AccountAddress,
+    pub state_sender: Address,
+    pub root_chain_manager: Address,
+    /// Block from which the Ethereum watcher starts if nothing was processed
+    /// yet.
+    pub state_sender_creation_block_number: u64,
+    /// Block from which the operations to skip in the bridge manager are
+    /// looked up when the Ethereum watcher starts, if it is earlier than the
+    /// block the watcher starts from.
+    pub skip_operations_from: Option<u64>,
+    /// Height from which the Concordium watcher starts.
+    pub concordium_start_height: AbsoluteBlockHeight,
+    pub num_confirmations: u64,
+    /// Maximum number of parallel queries of the Concordium node.
+    pub max_parallel: u32,
+    /// Maximum number of seconds to wait for a new finalized Concordium block.
+    pub max_behind: u32,
+    /// Duration after which a pending Ethereum transaction is warned about.
+    pub warn_duration: Duration,
+    pub endpoint_probe_interval: Duration,
+    pub health_limits: health::Limits,
+    pub health_check_interval: Duration,
+    pub blocklist: BlocklistSource,
+}
+
+/// Monitors of the balances of the accounts the relayer sends transactions
+/// from. Recording a balance that is too low stops the sending from the
+/// account.
+pub struct BalanceMonitors {
+    pub concordium: BalanceMonitor,
+    /// The account that sets Merkle roots.
+    pub ethereum_sender: Address,
+    pub ethereum: BalanceMonitor,
+    /// The account that claims withdrawals, if any, and its monitor.
+    pub claim: Option<(Address, BalanceMonitor)>,
+}
+
+/// The tasks of the relayer.
+pub struct Tasks {
+    /// Tasks that send transactions or write to the database. They stop
+    /// gracefully once the stop signal is sent.
+    pub senders: Vec<Task>,
+    /// Tasks that only watch the chains, or sample, and are aborted.
+    pub watchers: Vec<Task>,
+    /// The monitors of the sender accounts, or `None` in shadow mode where
+    /// nothing is sent.
+    pub balances: Option<BalanceMonitors>,
+}
+
+/// Find the range of Ethereum blocks the watcher starts with.
+pub async fn find_start_ethereum_config<M: Middleware>(
+    client: M,
+    last_processed: Option<u64>,
+    creation_height: u64,
+    num_confirmations: u64,
+) -> anyhow::Result<(u64, u64)>
+where
+    M::Error: 'static,
+{
+    let last_finalized: u64 = client
+        .get_block_number()
+        .await?
+        .as_u64()
+        .saturating_sub(num_confirmations);
+    if let Some(last_processed) = last_processed {
+        Ok((
+            last_processed + 1,
+            std::cmp::max(last_processed + 1, last_finalized),
+        ))
+    } else {
+        Ok((
+            creation_height,
+            std::cmp::max(last_finalized, creation_height),
+        ))
+    }
+}
+
+/// Number of items waiting in the channel of the given sender, or `None` if the
+/// channel is closed.
+fn queue_depth<T>(sender: &tokio::sync::mpsc::WeakSender<T>) -> Option<usize> {
+    let sender = sender.upgrade()?;
+    Some(CHANNEL_CAPACITY.saturating_sub(sender.capacity()))
+}
+
+/// Periodically record the number of items waiting in the channels between
+/// the tasks. The weak senders do not keep the channels open.
+async fn sample_queue_depths(
+    metrics: Metrics,
+    db_sender: tokio::sync::mpsc::WeakSender<DatabaseOperation>,
+    ccd_transaction_sender: tokio::sync::mpsc::WeakSender<(
+        BlockItem<EncodedPayload>,
+        tracing::Span,
+    )>,
+    merkle_setter_sender: tokio::sync::mpsc::WeakSender<MerkleUpdate>,
+) -> anyhow::Result<()> {
+    let mut interval = tokio::time::interval(Duration::from_secs(5));
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        interval.tick().await;
+        for (channel, depth) in [
+            ("db_sender", queue_depth(&db_sender)),
+            (
+                "ccd_transaction_sender",
+                queue_depth(&ccd_transaction_sender),
+            ),
+            ("merkle_setter_sender", queue_depth(&merkle_setter_sender)),
+        ] {
+            if let Some(depth) = depth {
+                metrics
+                    .queue_depth
+                    .with_label_values(&[channel])
+                    .set(depth as i64);
+            }
+        }
+    }
+}
+
+/// Construct the tasks of the relayer, resuming from the state in the
+/// database. Pending transactions are resubmitted by the tasks when they
+/// start. In shadow mode, i.e., without `signers`, the tasks that send
+/// transactions are replaced by tasks that only record them in the database.
+#[allow(clippy::too_many_arguments)]
+pub async fn make_tasks<C: ConcordiumNode, P: JsonRpcClient + 'static>(
+    metrics: &Metrics,
+    health: &Health,
+    config: TaskConfig,
+    tunables: &Tunables,
+    tunables_receiver: TunablesReceiver,
+    concordium_client: ConcordiumEndpoints<C>,
+    ethereum_endpoints: EthereumEndpoints<P>,
+    last_ethereum: Option<u64>,
+    db: Database,
+    signers: Option<Signers>,
+    stop: tokio::sync::watch::Receiver<()>,
+) -> anyhow::Result<Tasks>
+where
+    P::Error: EndpointError + Send,
+{
+    let ethereum_client = Arc::new(Provider::new(ethereum_endpoints.clone()));
+    let state_sender_contract = StateSender::new(config.state_sender, ethereum_client.clone());
+    let root_chain_manager_contract = root_chain_manager::BridgeManager::new(
+        config.root_chain_manager,
+        Arc::new(ethereum_client.clone()),
+    );
+
+    let mapped_tokens = db
+        .mapped_tokens()
+        .await
+        .context("Unable to get the mapped tokens.")?;
+    let bridge_manager_client = BridgeManagerClient::new(
+        concordium_client.clone(),
+        config.concordium_sender,
+        config.bridge_manager,
+    )
+    .with_tokens(mapped_tokens);
+
+    let (start_number, upper_number) = find_start_ethereum_config(
+        ethereum_client.clone(),
+        last_ethereum,
+        config.state_sender_creation_block_number,
+        config.num_confirmations,
+    )
+    .await
+    .context("Unable to find starting point for Ethereum monitoring")?;
+    tracing::info!(
+        "Found starting point on Ethereum chain at start = {start_number}, end = {upper_number})"
+    );
+
+    let (max_marked_event_index, leaves) = db
+        .pending_withdrawals(bridge_manager_client.clone())
+        .await
+        .context("Unable to get pending withdrawals.")?;
+
+    let (db_sender, db_receiver) = tokio::sync::mpsc::channel(CHANNEL_CAPACITY);
+    let (ccd_transaction_sender, ccd_transaction_receiver) =
+        tokio::sync::mpsc::channel(CHANNEL_CAPACITY);
+    let (merkle_setter_sender, merkle_setter_receiver) =
+        tokio::sync::mpsc::channel(CHANNEL_CAPACITY);
+
+    let mut senders: Vec<Task> = Vec::new();
+    let mut watchers: Vec<Task> = vec![(
+        "queue depth sampler",
+        sample_queue_depths(
+            metrics.clone(),
+            db_sender.downgrade(),
+            ccd_transaction_sender.downgrade(),
+            merkle_setter_sender.downgrade(),
+        )
+        .boxed(),
+    )];
+    let (bridge_manager, balances) = if let Some(Signers {
+        concordium_wallet,
+        wallet,
+        ethereum_nonce,
+        claim_wallet,
+        claim_nonce,
+    }) = signers
+    {
+        let start_nonce = db
+            .submit_missing_txs(concordium_client.clone())
+            .await
+            .context("Unable to submit missing transactions.")?;
+        let bridge_manager = concordium_contracts::BridgeManager::new(
+            bridge_manager_client.clone(),
+            concordium_wallet,
+            start_nonce,
+            tunables.max_energy,
+        )
+        .await
+        .context("Unable to connect to Concordium API.")?;
+        let pending_merkle_set = db
+            .pending_ethereum_tx()
+            .await
+            .context("Unable to get pending Merkle tree.")?;
+        let pending_claim = db
+            .pending_claim_txs()
+            .await
+            .context("Unable to get pending claim transactions.")?;
+        if pending_claim.is_some() && claim_wallet.is_none() {
+            tracing::warn!(
+                "There are pending claim transactions, but no claim key is provided. They will \
+                 not be monitored."
+            );
+        }
+
+        // Sending of transactions from an account is paused while its balance is
+        // below the minimum. Watchers keep running and the operations are queued.
+        let (concordium_balance, concordium_gate) = BalanceMonitor::new(
+            "Concordium sender account",
+            health::CONCORDIUM_BALANCE_CHECK,
+            "microCCD",
+            tunables.concordium_thresholds,
+            metrics.concordium_balance.clone(),
+            metrics.concordium_balance_level.clone(),
+        );
+        let (ethereum_balance, ethereum_gate) = BalanceMonitor::new(
+            "Ethereum sender account",
+            health::ETHEREUM_BALANCE_CHECK,
+            "microETH",
+            tunables.ethereum_thresholds,
+            metrics.ethereum_balance.clone(),
+            metrics.ethereum_balance_level.clone(),
+        );
+
+        senders.push((
+            "concordium transaction sender",
+            concordium_contracts::concordium_tx_sender(
+                metrics.clone(),
+                concordium_client.clone(),
+                ccd_transaction_receiver,
+                concordium_gate,
+                stop.clone(),
+            )
+            .boxed(),
+        ));
+
+        let ethereum_sender = wallet.address();
+        let merkle_client = MerkleSetterClient::new(
+            root_chain_manager_contract.clone(),
+            wallet,
+            tunables.max_gas_price,
+            tunables.max_gas,
+            ethereum_nonce,
+            &pending_merkle_set,
+            tunables.merkle_update_interval,
+            leaves,
+            max_marked_event_index,
+            tunables.escalation_interval,
+            config.warn_duration,
+        )
+        .context("Unable to construct the client for setting Merkle roots.")?;
+        senders.push((
+            "merkle updater",
+            merkle::send_merkle_root_updates(
+                metrics.clone(),
+                merkle_client,
+                pending_merkle_set,
+                merkle_setter_receiver,
+                db_sender.clone(),
+                config.num_confirmations,
+                ethereum_gate,
+                tunables_receiver.clone(),
+                stop.clone(),
+            )
+            .boxed(),
+        ));
+
+        let claim_balance = if let Some(claim_wallet) = claim_wallet {
+            let claim_sender = claim_wallet.address();
+            let claim_client = ClaimClient::new(
+                root_chain_manager_contract,
+                claim_wallet,
+                tunables.max_gas_price,
+                tunables.claim_max_gas,
+                claim_nonce,
+                &pending_claim,
+                tunables.claim_interval,
+                tunables.escalation_interval,
+                config.warn_duration,
+            )
+            .context("Unable to construct the client for claiming withdrawals.")?;
+            let (claim_balance, claim_gate) = BalanceMonitor::new(
+                "claim account",
+                health::CLAIM_BALANCE_CHECK,
+                "microETH",
+                tunables.ethereum_thresholds,
+                metrics.claim_balance.clone(),
+                metrics.claim_balance_level.clone(),
+            );
+            senders.push((
+                "withdrawal claimer",
+                claim::send_claims(
+                    metrics.clone(),
+                    claim_client,
+                    pending_claim,
+                    db_sender.clone(),
+                    config.num_confirmations,
+                    claim_gate,
+                    tunables_receiver.clone(),
+                    stop.clone(),
+                )
+                .boxed(),
+            ));
+            Some((claim_sender, claim_balance))
+        } else {
+            None
+        };
+        let balances = BalanceMonitors {
+            concordium: concordium_balance,
+            ethereum_sender,
+            ethereum: ethereum_balance,
+            claim: claim_balance,
+        };
+        (bridge_manager, Some(balances))
+    } else {
+        // The transactions stored in the database in shadow mode were never sent,
+        // so there is nothing to resubmit.
+        let bridge_manager = concordium_contracts::BridgeManager::shadow(
+            bridge_manager_client.clone(),
+            tunables.max_energy,
+        )
+        .await
+        .context("Unable to connect to Concordium API.")?;
+        senders.push((
+            "concordium transaction recorder",
+            shadow::record_concordium_transactions(
+                metrics.clone(),
+                ccd_transaction_receiver,
+                db_sender.clone(),
+                stop.clone(),
+            )
+            .boxed(),
+        ));
+        senders.push((
+            "merkle root recorder",
+            shadow::record_merkle_roots(
+                metrics.clone(),
+                leaves,
+                max_marked_event_index,
+                merkle_setter_receiver,
+                db_sender.clone(),
+                tunables_receiver.clone(),
+                stop.clone(),
+            )
+            .boxed(),
+        ));
+        (bridge_manager, None)
+    };
+    senders.push((
+        "database handler",
+        db::handle_database(
+            metrics.clone(),
+            config.db_config.clone(),
+            db,
+            db_receiver,
+            bridge_manager,
+            ccd_transaction_sender,
+            merkle_setter_sender,
+            config.blocklist,
+            tunables_receiver,
+            stop,
+        )
+        .boxed(),
+    ));
+
+    watchers.push((
+        "watch Concordium",
+        concordium_contracts::listen_concordium(
+            metrics.clone(),
+            bridge_manager_client,
+            db_sender.clone(),
+            config.concordium_start_height,
+            config.max_parallel,
+            config.max_behind,
+        )
+        .boxed(),
+    ));
+    watchers.push((
+        "watch Ethereum",
+        ethereum::watch_eth_blocks(
+            metrics.clone(),
+            state_sender_contract,
+            db_sender,
+            start_number,
+            upper_number,
+            config.num_confirmations,
+            config.skip_operations_from,
+        )
+        .boxed(),
+    ));
+    watchers.push((
+        "endpoint prober",
+        failover::probe_endpoints(
+            concordium_client.clone(),
+            ethereum_endpoints,
+            config.endpoint_probe_interval,
+        )
+        .boxed(),
+    ));
+    watchers.push((
+        "health monitor",
+        health::monitor(
+            health.clone(),
+            metrics.clone(),
+            concordium_client,
+            ethereum_client,
+            config.db_config,
+            config.num_confirmations,
+            config.health_limits,
+            config.health_check_interval,
+        )
+        .boxed(),
+    ));
+    Ok(Tasks {
+        senders,
+        watchers,
+        balances,
+    })
+}
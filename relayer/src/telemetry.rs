@@ -0,0 +1,73 @@
+//! Logging and tracing of the relayer.
+//!
+//! Logs are written to stderr. If an OTLP endpoint is configured then spans
+//! are additionally exported to it, e.g., to a local OpenTelemetry collector.
+//! Each deposit and withdrawal is traced in its own span which carries the hash
+//! of the transaction and the id of the event that started it. The span is
+//! passed along with the event through the channels between the tasks, so that
+//! all log messages about the same deposit or withdrawal can be found.
+use opentelemetry::sdk::{trace, Resource};
+use opentelemetry_otlp::WithExportConfig;
+use tracing_subscriber::{
+    filter::{LevelFilter, Targets},
+    prelude::*,
+    reload, Registry,
+};
+
+/// Name of the service in exported traces.
+const SERVICE_NAME: &str = "ccdeth-relayer";
+
+/// Only events of this crate, up to the given level, are logged.
+fn filter(log_level: LevelFilter) -> Targets {
+    // The library and the binary have the same name.
+    Targets::new().with_target("ccdeth_relayer", log_level)
+}
+
+/// Handle for changing the log level after [`init`].
+#[derive(Clone)]
+pub struct LogLevel {
+    handle: reload::Handle<Targets, Registry>,
+}
+
+impl LogLevel {
+    pub fn set(&self, log_level: LevelFilter) -> anyhow::Result<()> {
+        self.handle.reload(filter(log_level))?;
+        Ok(())
+    }
+}
+
+/// Set up logging, and the export of traces if `otlp_endpoint` is set. Only
+/// events of this crate are logged. The returned handle can be used to change
+/// the log level.
+pub fn init(log_level: LevelFilter, otlp_endpoint: Option<&url::Url>) -> anyhow::Result<LogLevel> {
+    let (filter, handle) = reload::Layer::new(filter(log_level));
+    let otlp = match otlp_endpoint {
+        Some(endpoint) => {
+            let tracer = opentelemetry_otlp::new_pipeline()
+                .tracing()
+                .with_exporter(
+                    opentelemetry_otlp::new_exporter()
+                        .tonic()
+                        .with_endpoint(endpoint.as_str()),
+                )
+                .with_trace_config(trace::config().with_resource(Resource::new([
+                    opentelemetry::KeyValue::new("service.name", SERVICE_NAME),
+                ])))
+                .install_batch(opentelemetry::runtime::Tokio)?;
+            Some(tracing_opentelemetry::layer().with_tracer(tracer))
+        }
+        None => None,
+    };
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(tracing_subscriber::fmt::layer())
+        .with(otlp)
+        .try_init()?;
+    Ok(LogLevel { handle })
+}
+
+/// Export the remaining spans. This should be called before the process
+/// exits.
+pub fn shutdown() {
+    opentelemetry::global::shutdown_tracer_provider();
+}
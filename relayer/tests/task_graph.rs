@@ -0,0 +1,1885 @@
+//! End-to-end tests of the relayer's tasks against the simulated chains in
+//! [`ccdeth_relayer::simulator`].
+//!
+//! The tests need a PostgreSQL server. The connection string is read from the
+//! `ETHCCD_RELAYER_TEST_DB_STRING` environment variable, and the tests are
+//! skipped if it is not set, e.g.,
+//! `host=localhost port=5432 user=postgres password=password sslmode=disable`.
+//! Each test runs in a fresh schema which is dropped at the end of the test.
+use ccdeth_relayer::{
+    balance::Thresholds,
+    blocklist::{Blocklist, BlocklistSource},
+    concordium_contracts,
+    config::Tunables,
+    db::{self, Database, QuarantineDecision},
+    ethereum,
+    failover::{ConcordiumEndpoints, EthereumEndpoints},
+    guardian::{self, Guardian},
+    health::{self, Health},
+    merkle,
+    metrics::Metrics,
+    root_chain_manager,
+    schedule::MerkleSchedule,
+    simulator::{ConcordiumSimulator, EthereumSimulator},
+    tasks::{self, BalanceMonitors, Signers, TaskConfig, Tasks},
+};
+use concordium_rust_sdk::{
+    cis2,
+    common::types::Amount,
+    id::types::AccountAddress,
+    smart_contracts::common as contracts_common,
+    types::{hashes::TransactionHash, ContractAddress, WalletAccount},
+};
+use ethers::{
+    abi::AbiEncode,
+    prelude::{Middleware, Provider, U256},
+    providers::{JsonRpcClient, ProviderError},
+    signers::{LocalWallet, Signer},
+    types::{transaction::eip2718::TypedTransaction, Address, TransactionRequest, U64},
+};
+use sha3::Digest;
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+const CHAIN_ID: u64 = 31337;
+const NUM_CONFIRMATIONS: u64 = 3;
+/// Maximum (simulated) time to wait for the relayer to make progress.
+const TIMEOUT: Duration = Duration::from_secs(600);
+/// How much faster the simulated time passes than real time.
+const CLOCK_SPEEDUP: u32 = 20;
+
+/// Keys of the well-known development accounts used by local Ethereum nodes.
+const MERKLE_KEY: &str = "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+const CLAIM_KEY: &str = "59c6995e998f97a5a0044966f0945389dc9e86dae88c7a8412f4603b6b78690d";
+const USER_KEY: &str = "5de4111afa1a4b94908f83103eb1f1706367c2e68ca870fc3fb9a804cdab365a";
+
+const BRIDGE_MANAGER: ContractAddress = ContractAddress {
+    index: 1000,
+    subindex: 0,
+};
+const CHILD_TOKEN: ContractAddress = ContractAddress {
+    index: 1001,
+    subindex: 0,
+};
+const RELAYER_ACCOUNT: AccountAddress = AccountAddress([1u8; 32]);
+const USER_ACCOUNT: AccountAddress = AccountAddress([2u8; 32]);
+const GUARDIAN_ACCOUNT: AccountAddress = AccountAddress([3u8; 32]);
+
+fn state_sender() -> Address {
+    Address::repeat_byte(0x11)
+}
+
+fn root_chain_manager() -> Address {
+    Address::repeat_byte(0x22)
+}
+
+fn root_token() -> Address {
+    Address::repeat_byte(0x33)
+}
+
+fn vault() -> Address {
+    Address::repeat_byte(0x44)
+}
+
+fn user_wallet() -> Address {
+    Address::repeat_byte(0x55)
+}
+
+/// Keys of the given Concordium account. The simulator does not check
+/// signatures, so the keys are those of the first Ed25519 test vector of
+/// RFC 8032.
+fn concordium_wallet(address: AccountAddress) -> WalletAccount {
+    WalletAccount::from_json_value(serde_json::json!({
+        "address": address.to_string(),
+        "accountKeys": {
+            "keys": {
+                "0": {
+                    "keys": {
+                        "0": {
+                            "signKey": "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60",
+                            "verifyKey": "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a"
+                        }
+                    },
+                    "threshold": 1
+                }
+            },
+            "threshold": 1
+        }
+    }))
+    .expect("The wallet is well-formed.")
+}
+
+fn ethereum_wallet(key: &str) -> LocalWallet {
+    key.parse::<LocalWallet>()
+        .expect("The key is well-formed.")
+        .with_chain_id(CHAIN_ID)
+}
+
+/// A fresh schema in the test database.
+struct TestDatabase {
+    /// Configuration that uses the schema.
+    config: tokio_postgres::Config,
+    client: tokio_postgres::Client,
+    schema: String,
+}
+
+impl TestDatabase {
+    /// Create a new schema, or return `None` if no test database is
+    /// configured.
+    async fn new() -> Option<Self> {
+        let Ok(db_string) = std::env::var("ETHCCD_RELAYER_TEST_DB_STRING") else {
+            eprintln!("ETHCCD_RELAYER_TEST_DB_STRING is not set. Skipping the test.");
+            return None;
+        };
+        let mut config: tokio_postgres::Config = db_string
+            .parse()
+            .expect("Invalid database connection string.");
+        let (client, connection) = config
+            .connect(tokio_postgres::NoTls)
+            .await
+            .expect("Unable to connect to the test database.");
+        tokio::spawn(connection);
+        let schema = format!(
+            "relayer_test_{}_{}",
+            std::process::id(),
+            chrono::Utc::now().timestamp_nanos()
+        );
+        client
+            .batch_execute(&format!("CREATE SCHEMA {schema};"))
+            .await
+            .expect("Unable to create the test schema.");
+        config.options(&format!("-c search_path={schema}"));
+        Some(Self {
+            config,
+            client,
+            schema,
+        })
+    }
+
+    async fn drop(self) {
+        self.client
+            .batch_execute(&format!("DROP SCHEMA {} CASCADE;", self.schema))
+            .await
+            .expect("Unable to drop the test schema.");
+    }
+}
+
+/// Advance the paused clock of the test in step with real time.
+///
+/// Left alone, the runtime advances a paused clock to the next timer whenever
+/// all tasks are waiting, including when they wait for the database, and the
+/// simulated chains then produce blocks far faster than the relayer can
+/// process them. This task keeps the runtime busy so that it never advances the
+/// clock by itself, and advances it [`CLOCK_SPEEDUP`] times faster than real
+/// time instead.
+async fn drive_clock() {
+    let mut last = std::time::Instant::now();
+    loop {
+        tokio::task::yield_now().await;
+        let elapsed = last.elapsed();
+        if elapsed >= Duration::from_millis(1) {
+            last += elapsed;
+            tokio::time::advance(elapsed * CLOCK_SPEEDUP).await;
+        }
+    }
+}
+
+/// The simulated chains, and the tasks producing blocks on them.
+struct Chains {
+    concordium: ConcordiumSimulator,
+    ethereum: EthereumSimulator,
+    producers: Vec<tokio::task::JoinHandle<()>>,
+}
+
+impl Chains {
+    fn new() -> Self {
+        let concordium = ConcordiumSimulator::new(BRIDGE_MANAGER);
+        let ethereum = EthereumSimulator::new(CHAIN_ID, state_sender(), root_chain_manager());
+        ethereum.add_erc20_token(root_token(), "Test token", "TST", 6);
+        let producers = vec![
+            tokio::spawn(drive_clock()),
+            tokio::spawn(concordium.clone().produce_blocks(Duration::from_secs(2))),
+            tokio::spawn(ethereum.clone().mine_blocks(Duration::from_secs(3))),
+        ];
+        Self {
+            concordium,
+            ethereum,
+            producers,
+        }
+    }
+
+    fn stop(self) {
+        for producer in self.producers {
+            producer.abort();
+        }
+    }
+}
+
+/// An Ethereum API in front of the simulated chain, which can be made
+/// unavailable, to fail requests for logs, to lag behind the chain, to omit all
+/// logs, or to report an out of date withdraw fee.
+#[derive(Debug, Clone)]
+struct TestApi {
+    ethereum: EthereumSimulator,
+    available: Arc<AtomicBool>,
+    logs_available: Arc<AtomicBool>,
+    /// The height the API is stuck at, if it lags behind the chain. It reports
+    /// that height, and returns no logs of later blocks.
+    stuck_at: Arc<Mutex<Option<u64>>>,
+    omit_logs: bool,
+    /// The fee reported for the root chain manager, if not the actual fee.
+    withdraw_fee: Option<U256>,
+}
+
+impl TestApi {
+    fn new(ethereum: &EthereumSimulator) -> Self {
+        Self {
+            ethereum: ethereum.clone(),
+            available: Arc::new(AtomicBool::new(true)),
+            logs_available: Arc::new(AtomicBool::new(true)),
+            stuck_at: Arc::default(),
+            omit_logs: false,
+            withdraw_fee: None,
+        }
+    }
+
+    fn set_available(&self, available: bool) {
+        self.available.store(available, Ordering::Relaxed);
+    }
+
+    fn set_logs_available(&self, available: bool) {
+        self.logs_available.store(available, Ordering::Relaxed);
+    }
+
+    /// Stop following the chain at its current height, or catch up with it.
+    fn set_lagging(&self, lagging: bool) {
+        *self.stuck_at.lock().unwrap() = lagging.then(|| self.ethereum.block_number());
+    }
+}
+
+#[async_trait::async_trait]
+impl JsonRpcClient for TestApi {
+    type Error = ProviderError;
+
+    async fn request<T, R>(&self, method: &str, params: T) -> Result<R, Self::Error>
+    where
+        T: std::fmt::Debug + serde::Serialize + Send + Sync,
+        R: serde::de::DeserializeOwned,
+    {
+        if !self.available.load(Ordering::Relaxed) {
+            return Err(ProviderError::CustomError(
+                "The test API is unavailable.".into(),
+            ));
+        }
+        if !self.logs_available.load(Ordering::Relaxed) && method == "eth_getLogs" {
+            return Err(ProviderError::CustomError(
+                "The test API does not return logs.".into(),
+            ));
+        }
+        if self.omit_logs && method == "eth_getLogs" {
+            return Ok(serde_json::from_value(serde_json::json!([]))?);
+        }
+        let stuck_at = *self.stuck_at.lock().unwrap();
+        if let Some(height) = stuck_at {
+            match method {
+                "eth_blockNumber" => {
+                    return Ok(serde_json::from_value(serde_json::to_value(U64::from(
+                        height,
+                    ))?)?);
+                }
+                "eth_getLogs" => {
+                    let logs: Vec<ethers::types::Log> =
+                        self.ethereum.request(method, params).await?;
+                    let logs = logs
+                        .into_iter()
+                        .filter(|log| log.block_number.is_some_and(|n| n.as_u64() <= height))
+                        .collect::<Vec<_>>();
+                    return Ok(serde_json::from_value(serde_json::to_value(logs)?)?);
+                }
+                _ => {}
+            }
+        }
+        if let Some(withdraw_fee) = self.withdraw_fee {
+            // The only calls to the root chain manager query the withdraw fee.
+            let request = serde_json::to_value(&params)?;
+            if method == "eth_call"
+                && request[0]["to"] == serde_json::to_value(root_chain_manager())?
+            {
+                let fee = ethers::types::Bytes::from(withdraw_fee.encode());
+                return Ok(serde_json::from_value(serde_json::to_value(fee)?)?);
+            }
+        }
+        self.ethereum.request(method, params).await
+    }
+}
+
+/// The running tasks of the relayer.
+struct Relayer {
+    metrics: Metrics,
+    health: Health,
+    /// Balances of the accounts the relayer sends transactions from.
+    balances: Option<BalanceMonitors>,
+    /// The tunables that the tasks use.
+    tunables: tokio::sync::watch::Sender<Tunables>,
+    stop_sender: tokio::sync::watch::Sender<()>,
+    /// Tasks that are stopped gracefully.
+    tasks: Vec<tokio::task::JoinHandle<anyhow::Result<()>>>,
+    /// Tasks that only watch the chains and are aborted.
+    watchers: Vec<tokio::task::JoinHandle<anyhow::Result<()>>>,
+}
+
+/// The tunables the relayers in the tests start with.
+fn tunables() -> anyhow::Result<Tunables> {
+    let thresholds = Thresholds::new(2_000, 1_000, 500)?;
+    Ok(Tunables {
+        max_gas_price: U256::from(100_000_000_000u64),
+        max_gas: U256::from(100_000),
+        claim_max_gas: U256::from(300_000),
+        merkle_update_interval: Duration::from_secs(10),
+        merkle_schedule: MerkleSchedule::default(),
+        escalation_interval: Duration::from_secs(120),
+        claim_interval: Duration::from_secs(5),
+        max_energy: 100_000.into(),
+        ethereum_thresholds: thresholds,
+        concordium_thresholds: thresholds,
+        log_level: tracing_subscriber::filter::LevelFilter::INFO,
+    })
+}
+
+impl Relayer {
+    /// Start the relayer in the same way as the `ccdeth_relayer` binary does.
+    async fn start(
+        db_config: &tokio_postgres::Config,
+        chains: &Chains,
+        claim: bool,
+    ) -> anyhow::Result<Self> {
+        Self::start_with_apis(
+            db_config,
+            chains,
+            claim,
+            vec![TestApi::new(&chains.ethereum)],
+            None,
+            BlocklistSource::None,
+        )
+        .await
+    }
+
+    /// Start the relayer using the given Ethereum APIs, optionally requiring a
+    /// quorum of them to agree on events, and quarantining the deposits of the
+    /// addresses on the blocklist.
+    async fn start_with_apis(
+        db_config: &tokio_postgres::Config,
+        chains: &Chains,
+        claim: bool,
+        apis: Vec<TestApi>,
+        quorum: Option<usize>,
+        blocklist: BlocklistSource,
+    ) -> anyhow::Result<Self> {
+        let apis = apis
+            .into_iter()
+            .enumerate()
+            .map(|(i, api)| (i.to_string(), api))
+            .collect();
+        let (_, metrics) = Metrics::new()?;
+        let ethereum_endpoints = EthereumEndpoints::new(&metrics, apis, quorum)?;
+        let ethereum_client = Provider::new(ethereum_endpoints.clone());
+        let wallet = ethereum_wallet(MERKLE_KEY);
+        let ethereum_nonce = ethereum_client
+            .get_transaction_count(wallet.address(), None)
+            .await?;
+        let claim_wallet = claim.then(|| ethereum_wallet(CLAIM_KEY));
+        let claim_nonce = match &claim_wallet {
+            Some(claim_wallet) => {
+                ethereum_client
+                    .get_transaction_count(claim_wallet.address(), None)
+                    .await?
+            }
+            None => U256::zero(),
+        };
+        let signers = Signers {
+            concordium_wallet: concordium_wallet(RELAYER_ACCOUNT),
+            wallet,
+            ethereum_nonce,
+            claim_wallet,
+            claim_nonce,
+        };
+        Self::start_tasks(
+            db_config,
+            chains,
+            metrics,
+            ethereum_endpoints,
+            Some(signers),
+            blocklist,
+        )
+        .await
+    }
+
+    /// Start the relayer in shadow mode in the same way as the `ccdeth_relayer`
+    /// binary does with `--shadow`.
+    async fn start_shadow(
+        db_config: &tokio_postgres::Config,
+        chains: &Chains,
+    ) -> anyhow::Result<Self> {
+        let (_, metrics) = Metrics::new()?;
+        let ethereum_endpoints = EthereumEndpoints::new(
+            &metrics,
+            vec![("0".into(), TestApi::new(&chains.ethereum))],
+            None,
+        )?;
+        Self::start_tasks(
+            db_config,
+            chains,
+            metrics,
+            ethereum_endpoints,
+            None,
+            BlocklistSource::None,
+        )
+        .await
+    }
+
+    /// Construct the tasks with [`tasks::make_tasks`] and spawn them.
+    async fn start_tasks(
+        db_config: &tokio_postgres::Config,
+        chains: &Chains,
+        metrics: Metrics,
+        ethereum_endpoints: EthereumEndpoints<TestApi>,
+        signers: Option<Signers>,
+        blocklist: BlocklistSource,
+    ) -> anyhow::Result<Self> {
+        let tunables = tunables()?;
+        let concordium =
+            ConcordiumEndpoints::new(&metrics, vec![("0".into(), chains.concordium.clone())])?;
+        let (last_ethereum, last_concordium, db) = Database::new(db_config).await?;
+        let config = TaskConfig {
+            db_config: db_config.clone(),
+            bridge_manager: BRIDGE_MANAGER,
+            concordium_sender: RELAYER_ACCOUNT,
+            state_sender: state_sender(),
+            root_chain_manager: root_chain_manager(),
+            state_sender_creation_block_number: 0,
+            skip_operations_from: None,
+            concordium_start_height: last_concordium.map_or(0.into(), |h| h.next()),
+            num_confirmations: NUM_CONFIRMATIONS,
+            max_parallel: 4,
+            max_behind: 60,
+            warn_duration: Duration::from_secs(60),
+            endpoint_probe_interval: Duration::from_secs(30),
+            health_limits: health::Limits {
+                max_ethereum_lag: 5,
+                max_concordium_lag: 5,
+                max_pending_age: Duration::from_secs(300),
+            },
+            health_check_interval: Duration::from_secs(5),
+            blocklist,
+        };
+        let health = Health::new();
+        let (stop_sender, stop_receiver) = tokio::sync::watch::channel(());
+        let (tunables_sender, tunables_receiver) = tokio::sync::watch::channel(tunables.clone());
+        let Tasks {
+            senders,
+            watchers,
+            balances,
+        } = tasks::make_tasks(
+            &metrics,
+            &health,
+            config,
+            &tunables,
+            tunables_receiver,
+            concordium,
+            ethereum_endpoints,
+            last_ethereum,
+            db,
+            signers,
+            stop_receiver,
+        )
+        .await?;
+        Ok(Self {
+            metrics,
+            health,
+            balances,
+            tunables: tunables_sender,
+            stop_sender,
+            tasks: senders
+                .into_iter()
+                .map(|(_, task)| tokio::spawn(task))
+                .collect(),
+            watchers: watchers
+                .into_iter()
+                .map(|(_, task)| tokio::spawn(task))
+                .collect(),
+        })
+    }
+
+    /// The balance monitors of the sender accounts. They are only used when
+    /// the relayer is not in shadow mode.
+    fn balances(&self) -> &BalanceMonitors {
+        self.balances
+            .as_ref()
+            .expect("The relayer is not in shadow mode.")
+    }
+
+    /// Stop all tasks, and check that none of them failed.
+    async fn stop(self) {
+        for watcher in self.watchers {
+            watcher.abort();
+            match watcher.await {
+                Ok(result) => result.expect("A watcher task failed."),
+                Err(e) => assert!(e.is_cancelled(), "A watcher task panicked: {e}"),
+            }
+        }
+        // The receivers may all be gone if the tasks stopped already.
+        let _ = self.stop_sender.send(());
+        for task in self.tasks {
+            task.await
+                .expect("A task panicked.")
+                .expect("A task failed.");
+        }
+    }
+}
+
+/// Wait until the condition holds. Fail if it does not hold within
+/// [`TIMEOUT`].
+async fn wait_for(what: &str, mut condition: impl FnMut() -> bool) {
+    let start = tokio::time::Instant::now();
+    while !condition() {
+        assert!(start.elapsed() < TIMEOUT, "Timed out waiting for {what}.");
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+}
+
+fn erc20_token_type() -> [u8; 32] {
+    sha3::Keccak256::digest("ERC20").into()
+}
+
+/// Map the test token and wait until the mapping is on Concordium.
+async fn map_token(chains: &Chains) {
+    chains
+        .ethereum
+        .map_token(root_token(), CHILD_TOKEN, erc20_token_type());
+    wait_for("the token map", || {
+        chains.concordium.token_map(root_token().0) == Some(CHILD_TOKEN)
+    })
+    .await;
+}
+
+fn deposit(chains: &Chains, amount: u64) {
+    chains.ethereum.deposit(
+        user_wallet(),
+        USER_ACCOUNT,
+        root_token(),
+        vault(),
+        amount.into(),
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn deposit_and_claimed_withdrawal() {
+    let Some(db) = TestDatabase::new().await else {
+        return;
+    };
+    let chains = Chains::new();
+    // Claims that do not pay the fee revert.
+    chains.ethereum.set_withdraw_fee(1_000u64.into());
+    let relayer = Relayer::start(&db.config, &chains, true)
+        .await
+        .expect("Unable to start the relayer.");
+
+    // The token map has id 1 and the deposit id 2.
+    map_token(&chains).await;
+    deposit(&chains, 1_000_000);
+    wait_for("the deposit", || chains.concordium.is_operation_used(2)).await;
+    assert_eq!(
+        chains.concordium.num_state_updates(),
+        2,
+        "Each operation is sent to Concordium exactly once."
+    );
+
+    chains.concordium.withdraw(
+        USER_ACCOUNT,
+        CHILD_TOKEN,
+        cis2::TokenAmount(500_000u64.into()),
+        user_wallet().0,
+        Some(Amount::from_micro_ccd(1_000)),
+    );
+    wait_for("the Merkle root", || {
+        chains.ethereum.merkle_root().is_some()
+    })
+    .await;
+    wait_for("the claim", || chains.ethereum.num_withdrawals() == 1).await;
+    let metrics = &relayer.metrics;
+    wait_for("the completed withdrawal", || {
+        metrics.withdrawal_completion_latency.get_sample_count() == 1
+    })
+    .await;
+    assert_eq!(metrics.deposit_latency.get_sample_count(), 1);
+    assert_eq!(metrics.withdrawal_approval_latency.get_sample_count(), 1);
+    // The Merkle root has id 3 and the withdrawal id 4. They are not state
+    // updates, and are skipped in the bridge manager.
+    wait_for("the skipped operations", || {
+        chains.concordium.is_operation_used(3) && chains.concordium.is_operation_used(4)
+    })
+    .await;
+    assert_eq!(
+        metrics.token_deposits.with_label_values(&["TST"]).get(),
+        1,
+        "Deposits are labelled by the symbol of the token."
+    );
+    assert_eq!(
+        metrics.token_withdrawals.with_label_values(&["TST"]).get(),
+        1
+    );
+    let withdrawals = db
+        .client
+        .query(
+            &format!(
+                "SELECT amount, fee FROM {}.concordium_events WHERE event_type = 'withdraw';",
+                db.schema
+            ),
+            &[],
+        )
+        .await
+        .expect("Unable to query the withdrawals.")
+        .iter()
+        .map(|row| (row.get::<_, String>(0), row.get::<_, Option<String>>(1)))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        withdrawals,
+        vec![("500000".to_string(), Some("0".to_string()))],
+        "The withdrawn amount and the fee in the token are recorded."
+    );
+
+    relayer.stop().await;
+    chains.stop();
+    db.drop().await;
+}
+
+/// Wait until the relayer recorded the given number of Merkle roots in the
+/// database, and return the latest two, the latest first.
+async fn latest_merkle_roots(db: &TestDatabase, num_roots: usize) -> Vec<[u8; 32]> {
+    let start = tokio::time::Instant::now();
+    loop {
+        let rows = db
+            .client
+            .query(
+                &format!(
+                    "SELECT root FROM {}.merkle_roots ORDER BY id DESC;",
+                    db.schema
+                ),
+                &[],
+            )
+            .await
+            .expect("Unable to query the Merkle roots.");
+        if rows.len() >= num_roots {
+            return rows
+                .iter()
+                .take(2)
+                .map(|row| {
+                    row.get::<_, Vec<u8>>(0)
+                        .try_into()
+                        .expect("Roots are 32 bytes.")
+                })
+                .collect();
+        }
+        assert!(
+            start.elapsed() < TIMEOUT,
+            "Timed out waiting for the Merkle roots."
+        );
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+}
+
+/// Construct the parameters of the `withdraw` call of the withdrawal with
+/// the given event index, and its proof against the given Merkle root. The
+/// leaves of the root are looked up in the same way as the API server does.
+async fn withdraw_params(
+    db: &TestDatabase,
+    event_index: u64,
+    root: [u8; 32],
+) -> (root_chain_manager::WithdrawParams, Vec<[u8; 32]>) {
+    let row = db
+        .client
+        .query_one(
+            &format!(
+                "SELECT tx_hash, event_data FROM {}.concordium_events WHERE event_index = $1;",
+                db.schema
+            ),
+            &[&(event_index as i64)],
+        )
+        .await
+        .expect("Unable to query the withdrawal.");
+    let tx_hash = TransactionHash::new(
+        row.get::<_, Vec<u8>>(0)
+            .try_into()
+            .expect("Transaction hashes are 32 bytes."),
+    );
+    let we: concordium_contracts::WithdrawEvent =
+        contracts_common::from_bytes(&row.get::<_, Vec<u8>>(1)).expect("Invalid withdraw event.");
+    let leaves = db
+        .client
+        .query(
+            &format!(
+                "SELECT event_index, event_merkle_hash FROM {}.concordium_events WHERE root = $1 \
+                 OR previous_root = $1 ORDER BY event_index ASC;",
+                db.schema
+            ),
+            &[&&root[..]],
+        )
+        .await
+        .expect("Unable to query the Merkle leaves.")
+        .iter()
+        .map(|row| {
+            let leaf: [u8; 32] = row
+                .get::<_, Vec<u8>>(1)
+                .try_into()
+                .expect("Leaves are 32 bytes.");
+            Ok::<_, anyhow::Error>((row.get::<_, i64>(0) as u64, leaf))
+        })
+        .collect::<Vec<_>>();
+    let proof = merkle::make_proof(leaves, event_index)
+        .expect("The leaves are valid.")
+        .expect("The withdrawal is part of the root.");
+    let params = root_chain_manager::WithdrawParams {
+        ccd_index: we.contract.index,
+        ccd_sub_index: we.contract.subindex,
+        amount: merkle::convert_from_token_amount(&we.amount).as_u64(),
+        user_wallet: we.eth_address.eth_address.into(),
+        ccd_tx_hash: tx_hash.to_string(),
+        ccd_event_index: we.event_index,
+        token_id: u64::from_le_bytes(
+            Vec::from(we.token_id)
+                .try_into()
+                .expect("Token ids are 8 bytes."),
+        ),
+    };
+    (params, proof.proof_hashes().to_vec())
+}
+
+#[tokio::test(start_paused = true)]
+async fn claims_against_the_previous_merkle_root() {
+    let Some(db) = TestDatabase::new().await else {
+        return;
+    };
+    let chains = Chains::new();
+    let relayer = Relayer::start(&db.config, &chains, false)
+        .await
+        .expect("Unable to start the relayer.");
+
+    map_token(&chains).await;
+    deposit(&chains, 1_000_000);
+    wait_for("the deposit", || chains.concordium.is_operation_used(2)).await;
+    // Each withdrawal is approved by a new Merkle root.
+    for (amount, num_roots) in [(100_000u64, 1), (200_000, 2)] {
+        chains.concordium.withdraw(
+            USER_ACCOUNT,
+            CHILD_TOKEN,
+            cis2::TokenAmount(amount.into()),
+            user_wallet().0,
+            None,
+        );
+        latest_merkle_roots(&db, num_roots).await;
+    }
+    let roots = latest_merkle_roots(&db, 2).await;
+    let (current_root, previous_root) = (roots[0], roots[1]);
+    assert_eq!(chains.ethereum.merkle_root(), Some(current_root));
+
+    // The first withdrawal is part of both roots, and the user claims it with
+    // the proof against the previous one.
+    let event_index = db
+        .client
+        .query_one(
+            &format!(
+                "SELECT MIN(event_index) FROM {}.concordium_events WHERE event_type = \
+                 'withdraw';",
+                db.schema
+            ),
+            &[],
+        )
+        .await
+        .expect("Unable to query the withdrawals.")
+        .get::<_, i64>(0) as u64;
+    let (params, proof) = withdraw_params(&db, event_index, previous_root).await;
+    let (_, current_proof) = withdraw_params(&db, event_index, current_root).await;
+    assert_ne!(
+        proof, current_proof,
+        "The proofs against the two roots differ."
+    );
+    let client = Arc::new(Provider::new(TestApi::new(&chains.ethereum)));
+    let manager = root_chain_manager::BridgeManager::new(root_chain_manager(), client.clone());
+    let wallet = ethereum_wallet(USER_KEY);
+    let call = manager.withdraw(params, proof).tx;
+    let tx = TypedTransaction::Legacy(TransactionRequest {
+        from: Some(wallet.address()),
+        to: call.to().cloned(),
+        gas: Some(U256::from(300_000)),
+        gas_price: Some(U256::from(1_000_000_000u64)),
+        value: None,
+        data: call.data().cloned(),
+        nonce: Some(U256::zero()),
+        chain_id: Some(CHAIN_ID.into()),
+    });
+    let signature = wallet
+        .sign_transaction(&tx)
+        .await
+        .expect("Unable to sign the claim.");
+    client
+        .send_raw_transaction(tx.rlp_signed(&signature))
+        .await
+        .expect("Unable to send the claim.");
+    wait_for("the claim", || chains.ethereum.num_withdrawals() == 1).await;
+    assert_eq!(chains.ethereum.merkle_root(), Some(current_root));
+
+    relayer.stop().await;
+    chains.stop();
+    db.drop().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn fails_reverted_claims() {
+    let Some(db) = TestDatabase::new().await else {
+        return;
+    };
+    let chains = Chains::new();
+    // The fee was raised after the relayer queried it, so the claim reverts.
+    chains.ethereum.set_withdraw_fee(1_000u64.into());
+    let stale_fee = TestApi {
+        withdraw_fee: Some(U256::zero()),
+        ..TestApi::new(&chains.ethereum)
+    };
+    let relayer = Relayer::start_with_apis(
+        &db.config,
+        &chains,
+        true,
+        vec![stale_fee],
+        None,
+        BlocklistSource::None,
+    )
+    .await
+    .expect("Unable to start the relayer.");
+
+    map_token(&chains).await;
+    deposit(&chains, 1_000_000);
+    wait_for("the deposit", || chains.concordium.is_operation_used(2)).await;
+    chains.concordium.withdraw(
+        USER_ACCOUNT,
+        CHILD_TOKEN,
+        cis2::TokenAmount(500_000u64.into()),
+        user_wallet().0,
+        Some(Amount::from_micro_ccd(1_000)),
+    );
+    let query = format!(
+        "SELECT status::text FROM {}.ethereum_transactions WHERE kind = 'claim';",
+        db.schema
+    );
+    let start = tokio::time::Instant::now();
+    let statuses = loop {
+        let statuses = db
+            .client
+            .query(&query, &[])
+            .await
+            .expect("Unable to query the claims.")
+            .iter()
+            .map(|row| row.get::<_, String>(0))
+            .collect::<Vec<_>>();
+        if statuses.iter().any(|status| status != "pending") {
+            break statuses;
+        }
+        assert!(
+            start.elapsed() < TIMEOUT,
+            "Timed out waiting for the claim."
+        );
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    };
+    assert_eq!(
+        statuses,
+        vec!["failed".to_string()],
+        "A reverted claim is marked as failed, not as confirmed."
+    );
+    assert_eq!(chains.ethereum.num_withdrawals(), 0);
+
+    // The failed claim is not attempted again.
+    tokio::time::sleep(Duration::from_secs(60)).await;
+    let num_claims = db
+        .client
+        .query(&query, &[])
+        .await
+        .expect("Unable to query the claims.")
+        .len();
+    assert_eq!(num_claims, 1);
+
+    relayer.stop().await;
+    chains.stop();
+    db.drop().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn deposits_to_contracts_and_falls_back_to_the_account() {
+    let Some(db) = TestDatabase::new().await else {
+        return;
+    };
+    let chains = Chains::new();
+    let relayer = Relayer::start(&db.config, &chains, true)
+        .await
+        .expect("Unable to start the relayer.");
+    map_token(&chains).await;
+
+    let dex = ContractAddress::new(10, 0);
+    chains.concordium.add_contract(dex);
+    let call = |contract| ethereum::DepositCall {
+        contract,
+        entrypoint: contracts_common::OwnedEntrypointName::new_unchecked("onDeposit".into()),
+        data: vec![1, 2, 3],
+    };
+    // The deposit to the existing contract has id 2, and the deposit to a
+    // contract that does not exist has id 3.
+    for contract in [dex, ContractAddress::new(11, 0)] {
+        chains.ethereum.deposit_to_contract(
+            user_wallet(),
+            USER_ACCOUNT,
+            root_token(),
+            vault(),
+            1_000u64.into(),
+            &call(contract),
+        );
+    }
+    wait_for("the deposits", || {
+        chains.concordium.is_operation_used(2) && chains.concordium.is_operation_used(3)
+    })
+    .await;
+
+    let to_contract = chains
+        .concordium
+        .deposit_operation(2)
+        .expect("The deposit was executed.");
+    assert_eq!(
+        to_contract.user,
+        concordium_rust_sdk::types::Address::Contract(dex)
+    );
+    assert_eq!(
+        to_contract.hook,
+        Some(concordium_contracts::DepositHook {
+            entrypoint: call(dex).entrypoint,
+            data: vec![1, 2, 3],
+        })
+    );
+    let fallback = chains
+        .concordium
+        .deposit_operation(3)
+        .expect("The deposit was executed.");
+    assert_eq!(
+        fallback.user,
+        concordium_rust_sdk::types::Address::Account(USER_ACCOUNT),
+        "The deposit falls back to the receiving account."
+    );
+    assert_eq!(fallback.hook, None);
+    assert_eq!(chains.concordium.num_state_updates(), 3);
+
+    relayer.stop().await;
+    chains.stop();
+    db.drop().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn quarantines_deposits_of_blocked_addresses() {
+    let Some(db) = TestDatabase::new().await else {
+        return;
+    };
+    let chains = Chains::new();
+    let relayer = Relayer::start_with_apis(
+        &db.config,
+        &chains,
+        false,
+        vec![TestApi::new(&chains.ethereum)],
+        None,
+        BlocklistSource::Table,
+    )
+    .await
+    .expect("Unable to start the relayer.");
+    db.client
+        .execute(
+            &format!(
+                "INSERT INTO {}.blocklist (address, reason) VALUES ($1, 'test');",
+                db.schema
+            ),
+            &[&format!("{:#x}", user_wallet())],
+        )
+        .await
+        .expect("Unable to block the user's wallet.");
+    map_token(&chains).await;
+
+    // The token map has id 1, the deposits from the blocked wallet ids 2 and
+    // 4, and the deposit from another wallet id 3.
+    deposit(&chains, 1_000);
+    chains.ethereum.deposit(
+        Address::repeat_byte(0x66),
+        USER_ACCOUNT,
+        root_token(),
+        vault(),
+        2_000u64.into(),
+    );
+    deposit(&chains, 3_000);
+    wait_for("the deposit", || chains.concordium.is_operation_used(3)).await;
+    let query = format!(
+        "SELECT origin_event_index, receiver FROM {}.ethereum_deposit_events WHERE quarantined \
+         ORDER BY id;",
+        db.schema
+    );
+    let start = tokio::time::Instant::now();
+    let quarantined = loop {
+        let rows = db
+            .client
+            .query(&query, &[])
+            .await
+            .expect("Unable to query the quarantined deposits.");
+        if rows.len() >= 2 {
+            break rows
+                .iter()
+                .map(|row| (row.get::<_, i64>(0), row.get::<_, Vec<u8>>(1)))
+                .collect::<Vec<_>>();
+        }
+        assert!(
+            start.elapsed() < TIMEOUT,
+            "Timed out waiting for the quarantined deposits."
+        );
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    };
+    assert_eq!(
+        quarantined,
+        vec![(2, USER_ACCOUNT.0.to_vec()), (4, USER_ACCOUNT.0.to_vec())]
+    );
+    assert!(
+        !chains.concordium.is_operation_used(2) && !chains.concordium.is_operation_used(4),
+        "The deposits from the blocked wallet are not sent to Concordium."
+    );
+    assert_eq!(chains.concordium.num_state_updates(), 2);
+    assert_eq!(relayer.metrics.num_quarantined_deposits.get(), 2);
+
+    // The released deposit is sent as it was made, and the operation of the
+    // rejected one is skipped.
+    let (client, _handle) = db::connect(&db.config)
+        .await
+        .expect("Unable to connect to the database.");
+    for (id, decision) in [
+        (2, QuarantineDecision::Release),
+        (4, QuarantineDecision::Reject),
+    ] {
+        assert!(db::decide_quarantined_deposit(&client, id, decision)
+            .await
+            .expect("Unable to record the decision."));
+    }
+    assert!(
+        !db::decide_quarantined_deposit(&client, 3, QuarantineDecision::Release)
+            .await
+            .expect("Unable to record the decision."),
+        "Only quarantined deposits are decided on."
+    );
+    wait_for("the decisions", || {
+        chains.concordium.is_operation_used(2) && chains.concordium.is_operation_used(4)
+    })
+    .await;
+    let released = chains
+        .concordium
+        .deposit_operation(2)
+        .expect("The released deposit was executed.");
+    assert_eq!(released.amount, cis2::TokenAmount::from(1_000u64));
+    assert_eq!(chains.concordium.deposit_operation(4), None);
+    assert_eq!(chains.concordium.num_state_updates(), 4);
+
+    let start = tokio::time::Instant::now();
+    while client
+        .query_one(
+            "SELECT COUNT(*) FROM ethereum_deposit_events WHERE quarantine_resolved_time IS NOT \
+             NULL;",
+            &[],
+        )
+        .await
+        .expect("Unable to query the resolved deposits.")
+        .get::<_, i64>(0)
+        < 2
+    {
+        assert!(
+            start.elapsed() < TIMEOUT,
+            "Timed out waiting for the decisions to be recorded."
+        );
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+    assert!(
+        !db::decide_quarantined_deposit(&client, 4, QuarantineDecision::Release)
+            .await
+            .expect("Unable to record the decision."),
+        "A decision that was carried out is final."
+    );
+
+    relayer.stop().await;
+    chains.stop();
+    db.drop().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn quarantines_deposits_to_blocked_contracts() {
+    let Some(db) = TestDatabase::new().await else {
+        return;
+    };
+    let chains = Chains::new();
+    let dex = ContractAddress::new(10, 0);
+    let mut blocklist = Blocklist::default();
+    blocklist
+        .insert(&format!("<{},{}>", dex.index, dex.subindex))
+        .expect("Valid contract address.");
+    let relayer = Relayer::start_with_apis(
+        &db.config,
+        &chains,
+        false,
+        vec![TestApi::new(&chains.ethereum)],
+        None,
+        BlocklistSource::File(blocklist),
+    )
+    .await
+    .expect("Unable to start the relayer.");
+    map_token(&chains).await;
+
+    // The deposit to the blocked contract has id 2, and the deposit to another
+    // contract id 3.
+    let other = ContractAddress::new(11, 0);
+    for contract in [dex, other] {
+        chains.concordium.add_contract(contract);
+        chains.ethereum.deposit_to_contract(
+            user_wallet(),
+            USER_ACCOUNT,
+            root_token(),
+            vault(),
+            1_000u64.into(),
+            &ethereum::DepositCall {
+                contract,
+                entrypoint: contracts_common::OwnedEntrypointName::new_unchecked(
+                    "onDeposit".into(),
+                ),
+                data: Vec::new(),
+            },
+        );
+    }
+    wait_for("the deposit", || chains.concordium.is_operation_used(3)).await;
+    assert!(
+        !chains.concordium.is_operation_used(2),
+        "The deposit to the blocked contract is not sent to Concordium."
+    );
+    assert_eq!(
+        chains
+            .concordium
+            .deposit_operation(3)
+            .expect("The deposit was executed.")
+            .user,
+        concordium_rust_sdk::types::Address::Contract(other)
+    );
+    assert_eq!(relayer.metrics.num_quarantined_deposits.get(), 1);
+
+    relayer.stop().await;
+    chains.stop();
+    db.drop().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn indexes_administrative_actions() {
+    let Some(db) = TestDatabase::new().await else {
+        return;
+    };
+    let chains = Chains::new();
+    let relayer = Relayer::start(&db.config, &chains, false)
+        .await
+        .expect("Unable to start the relayer.");
+    map_token(&chains).await;
+
+    chains.concordium.update(
+        USER_ACCOUNT,
+        BRIDGE_MANAGER,
+        "bridge-manager.setPaused",
+        vec![contracts_common::to_bytes(
+            &concordium_contracts::BridgeEvent::SetPaused(concordium_contracts::SetPausedEvent {
+                paused: true,
+            }),
+        )],
+    );
+    chains.concordium.update(
+        USER_ACCOUNT,
+        CHILD_TOKEN,
+        "cis2-bridgeable.setPaused",
+        vec![vec![bridge_types::token_admin_events::SET_PAUSED, 1]],
+    );
+    // Logs of contracts that are neither the bridge manager nor a mapped
+    // token are ignored.
+    chains.concordium.update(
+        USER_ACCOUNT,
+        ContractAddress::new(4242, 0),
+        "other.setPaused",
+        vec![vec![bridge_types::token_admin_events::SET_PAUSED, 1]],
+    );
+
+    let query = format!(
+        "SELECT contract_index, action FROM {}.admin_events ORDER BY id;",
+        db.schema
+    );
+    let start = tokio::time::Instant::now();
+    let rows = loop {
+        let rows = db
+            .client
+            .query(&query, &[])
+            .await
+            .expect("Unable to query the administrative events.");
+        if rows.len() >= 2 {
+            break rows;
+        }
+        assert!(
+            start.elapsed() < TIMEOUT,
+            "Timed out waiting for the administrative events."
+        );
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    };
+    let actions = rows
+        .iter()
+        .map(|row| (row.get::<_, i64>(0) as u64, row.get::<_, String>(1)))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        actions,
+        vec![
+            (BRIDGE_MANAGER.index, "set_paused".to_string()),
+            (CHILD_TOKEN.index, "set_paused".to_string()),
+        ]
+    );
+    // The administrative events are only recorded in the audit log.
+    let event_types = db
+        .client
+        .query(
+            &format!(
+                "SELECT event_type::TEXT FROM {}.concordium_events ORDER BY id;",
+                db.schema
+            ),
+            &[],
+        )
+        .await
+        .expect("Unable to query the Concordium events.")
+        .iter()
+        .map(|row| row.get::<_, String>(0))
+        .collect::<Vec<_>>();
+    assert_eq!(event_types, vec!["token_map".to_string()]);
+
+    relayer.stop().await;
+    chains.stop();
+    db.drop().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn guardian_pauses_the_bridge_on_a_large_deposit() {
+    let Some(db) = TestDatabase::new().await else {
+        return;
+    };
+    let chains = Chains::new();
+    let relayer = Relayer::start(&db.config, &chains, false)
+        .await
+        .expect("Unable to start the relayer.");
+    map_token(&chains).await;
+    deposit(&chains, 1_000);
+    deposit(&chains, 2_000);
+    wait_for("the deposits", || {
+        chains.concordium.is_operation_used(2) && chains.concordium.is_operation_used(3)
+    })
+    .await;
+
+    let guardian = tokio::spawn(guardian::guard(
+        relayer.metrics.clone(),
+        relayer.health.clone(),
+        Guardian::new(
+            chains.concordium.clone(),
+            concordium_wallet(GUARDIAN_ACCOUNT),
+            BRIDGE_MANAGER,
+            100_000.into(),
+        ),
+        Provider::new(chains.ethereum.clone()),
+        db.config.clone(),
+        guardian::Rules {
+            deposit_multiple: Some(10),
+            average_window: Duration::from_secs(7 * 24 * 60 * 60),
+            reorg_window: Duration::from_secs(60 * 60),
+        },
+        Duration::from_secs(5),
+    ));
+    let health = &relayer.health;
+    wait_for("the first check of the guardian", || {
+        health
+            .readiness()
+            .checks
+            .and_then(|checks| checks.get(health::GUARDIAN_CHECK).map(|c| c.ok))
+            .unwrap_or(false)
+    })
+    .await;
+    assert!(!chains.concordium.is_paused(BRIDGE_MANAGER));
+
+    // The daily average is 3_000 / 7 tokens, so 1_000_000 is far above 10
+    // times that.
+    deposit(&chains, 1_000_000);
+    wait_for("the pause", || {
+        chains.concordium.is_paused(BRIDGE_MANAGER) && chains.concordium.is_paused(CHILD_TOKEN)
+    })
+    .await;
+    let metrics = &relayer.metrics;
+    assert_eq!(
+        metrics
+            .guardian_alarms
+            .with_label_values(&["large_deposit"])
+            .get(),
+        1
+    );
+    assert_eq!(metrics.guardian_paused.get(), 1);
+    assert!(!relayer.health.readiness().ok);
+
+    guardian.abort();
+    relayer.stop().await;
+    chains.stop();
+    db.drop().await;
+}
+
+/// Log a corrective mint or burn of the child token by the bridge manager.
+fn correct(chains: &Chains, mint: bool, amount: u64) {
+    let correction = concordium_contracts::CorrectionEvent {
+        incident_id: 1,
+        token: CHILD_TOKEN,
+        address: contracts_common::Address::Account(USER_ACCOUNT),
+        amount: cis2::TokenAmount(amount.into()),
+    };
+    let (entrypoint, event) = if mint {
+        (
+            "bridge-manager.execute",
+            concordium_contracts::BridgeEvent::CorrectiveMint(correction),
+        )
+    } else {
+        (
+            "bridge-manager.execute",
+            concordium_contracts::BridgeEvent::CorrectiveBurn(correction),
+        )
+    };
+    chains.concordium.update(
+        USER_ACCOUNT,
+        BRIDGE_MANAGER,
+        entrypoint,
+        vec![contracts_common::to_bytes(&event)],
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn guardian_reconciles_corrections() {
+    let Some(db) = TestDatabase::new().await else {
+        return;
+    };
+    let chains = Chains::new();
+    let relayer = Relayer::start(&db.config, &chains, false)
+        .await
+        .expect("Unable to start the relayer.");
+    map_token(&chains).await;
+    deposit(&chains, 1_000);
+    wait_for("the deposit", || chains.concordium.is_operation_used(2)).await;
+
+    // A burn that is minted again leaves the supply matching Ethereum.
+    correct(&chains, false, 300);
+    correct(&chains, true, 300);
+    let query = format!(
+        "SELECT event_type::TEXT, child_index, amount, event_data FROM {}.concordium_events \
+         WHERE event_type IN ('corrective_mint', 'corrective_burn') ORDER BY id;",
+        db.schema
+    );
+    let start = tokio::time::Instant::now();
+    let rows = loop {
+        let rows = db
+            .client
+            .query(&query, &[])
+            .await
+            .expect("Unable to query the corrections.");
+        if rows.len() >= 2 {
+            break rows;
+        }
+        assert!(
+            start.elapsed() < TIMEOUT,
+            "Timed out waiting for the corrections."
+        );
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    };
+    let event_types = rows
+        .iter()
+        .map(|row| row.get::<_, String>(0))
+        .collect::<Vec<_>>();
+    assert_eq!(event_types, vec!["corrective_burn", "corrective_mint"]);
+    for row in &rows {
+        assert_eq!(row.get::<_, Option<i64>>(1), Some(CHILD_TOKEN.index as i64));
+        assert_eq!(row.get::<_, Option<String>>(2).as_deref(), Some("300"));
+        // Corrections are stored without the tag of the event, like deposits.
+        let correction =
+            contracts_common::from_bytes::<concordium_contracts::CorrectionEvent>(row.get(3))
+                .expect("The correction is stored without its tag.");
+        assert_eq!(correction.token, CHILD_TOKEN);
+    }
+
+    let guardian = tokio::spawn(guardian::guard(
+        relayer.metrics.clone(),
+        relayer.health.clone(),
+        Guardian::new(
+            chains.concordium.clone(),
+            concordium_wallet(GUARDIAN_ACCOUNT),
+            BRIDGE_MANAGER,
+            100_000.into(),
+        ),
+        Provider::new(chains.ethereum.clone()),
+        db.config.clone(),
+        guardian::Rules {
+            deposit_multiple: None,
+            average_window: Duration::from_secs(7 * 24 * 60 * 60),
+            reorg_window: Duration::from_secs(60 * 60),
+        },
+        Duration::from_secs(5),
+    ));
+    let health = &relayer.health;
+    wait_for("the first check of the guardian", || {
+        health
+            .readiness()
+            .checks
+            .and_then(|checks| checks.get(health::GUARDIAN_CHECK).map(|c| c.ok))
+            .unwrap_or(false)
+    })
+    .await;
+    assert!(!chains.concordium.is_paused(BRIDGE_MANAGER));
+
+    // A corrective mint that is not backed by Ethereum is a supply alarm.
+    correct(&chains, true, 1);
+    wait_for("the pause", || chains.concordium.is_paused(BRIDGE_MANAGER)).await;
+    assert_eq!(
+        relayer
+            .metrics
+            .guardian_alarms
+            .with_label_values(&["supply"])
+            .get(),
+        1
+    );
+
+    guardian.abort();
+    relayer.stop().await;
+    chains.stop();
+    db.drop().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn recovers_from_faults_and_restarts() {
+    let Some(db) = TestDatabase::new().await else {
+        return;
+    };
+    let chains = Chains::new();
+    let relayer = Relayer::start(&db.config, &chains, false)
+        .await
+        .expect("Unable to start the relayer.");
+    map_token(&chains).await;
+
+    // A reorganization shallower than the number of confirmations is not
+    // observed by the relayer.
+    deposit(&chains, 1_000_000);
+    let deposit_block = chains.ethereum.block_number() + 1;
+    wait_for("the deposit to be mined", || {
+        chains.ethereum.block_number() >= deposit_block
+    })
+    .await;
+    chains.ethereum.reorg(1);
+    wait_for("the first deposit", || {
+        chains.concordium.is_operation_used(2)
+    })
+    .await;
+
+    wait_for("readiness", || relayer.health.readiness().ok).await;
+
+    // Outages of both chains are retried, and reported as not ready.
+    chains.concordium.set_available(false);
+    chains.ethereum.set_available(false);
+    deposit(&chains, 2_000_000);
+    tokio::time::sleep(Duration::from_secs(20)).await;
+    assert!(!relayer.health.readiness().ok);
+    chains.concordium.set_available(true);
+    chains.ethereum.set_available(true);
+    wait_for("the second deposit", || {
+        chains.concordium.is_operation_used(3)
+    })
+    .await;
+    assert_eq!(chains.concordium.num_state_updates(), 3);
+
+    // After a restart the relayer continues where it stopped, without
+    // resending any operations.
+    relayer.stop().await;
+    let relayer = Relayer::start(&db.config, &chains, false)
+        .await
+        .expect("Unable to restart the relayer.");
+    chains.concordium.withdraw(
+        USER_ACCOUNT,
+        CHILD_TOKEN,
+        cis2::TokenAmount(500_000u64.into()),
+        user_wallet().0,
+        None,
+    );
+    wait_for("the Merkle root", || {
+        chains.ethereum.merkle_root().is_some()
+    })
+    .await;
+    assert_eq!(chains.concordium.num_state_updates(), 3);
+
+    relayer.stop().await;
+    chains.stop();
+    db.drop().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn pauses_sending_while_balances_are_low() {
+    let Some(db) = TestDatabase::new().await else {
+        return;
+    };
+    let chains = Chains::new();
+    let relayer = Relayer::start(&db.config, &chains, false)
+        .await
+        .expect("Unable to start the relayer.");
+    map_token(&chains).await;
+
+    // Deposits are recorded while the Concordium sender account is below the
+    // minimum, and sent once it is topped up.
+    relayer
+        .balances()
+        .concordium
+        .update(&relayer.metrics, &relayer.health, 700);
+    deposit(&chains, 1_000_000);
+    deposit(&chains, 2_000_000);
+    tokio::time::sleep(Duration::from_secs(120)).await;
+    assert!(!chains.concordium.is_operation_used(2));
+    assert!(!chains.concordium.is_operation_used(3));
+    assert!(!relayer.health.readiness().ok);
+    relayer
+        .balances()
+        .concordium
+        .update(&relayer.metrics, &relayer.health, 1_500);
+    wait_for("the deposits", || {
+        chains.concordium.is_operation_used(2) && chains.concordium.is_operation_used(3)
+    })
+    .await;
+    assert_eq!(chains.concordium.num_state_updates(), 3);
+
+    // Likewise no Merkle roots are set while the Ethereum sender account is
+    // below the critical threshold.
+    relayer
+        .balances()
+        .ethereum
+        .update(&relayer.metrics, &relayer.health, 100);
+    chains.concordium.withdraw(
+        USER_ACCOUNT,
+        CHILD_TOKEN,
+        cis2::TokenAmount(500_000u64.into()),
+        user_wallet().0,
+        None,
+    );
+    tokio::time::sleep(Duration::from_secs(120)).await;
+    assert!(chains.ethereum.merkle_root().is_none());
+    relayer
+        .balances()
+        .ethereum
+        .update(&relayer.metrics, &relayer.health, 5_000);
+    wait_for("the Merkle root", || {
+        chains.ethereum.merkle_root().is_some()
+    })
+    .await;
+
+    relayer.stop().await;
+    chains.stop();
+    db.drop().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn applies_changed_tunables() {
+    let Some(db) = TestDatabase::new().await else {
+        return;
+    };
+    let chains = Chains::new();
+    let relayer = Relayer::start(&db.config, &chains, false)
+        .await
+        .expect("Unable to start the relayer.");
+    map_token(&chains).await;
+    deposit(&chains, 1_000_000);
+    wait_for("the deposit", || chains.concordium.is_operation_used(2)).await;
+
+    // No Merkle root is set while the gas price is above the maximum.
+    chains
+        .ethereum
+        .set_gas_price(U256::from(200_000_000_000u64));
+    chains.concordium.withdraw(
+        USER_ACCOUNT,
+        CHILD_TOKEN,
+        cis2::TokenAmount(500_000u64.into()),
+        user_wallet().0,
+        None,
+    );
+    tokio::time::sleep(Duration::from_secs(120)).await;
+    assert!(chains.ethereum.merkle_root().is_none());
+
+    // Raising the maximum takes effect without a restart, and a longer update
+    // interval reschedules the next update.
+    relayer.tunables.send_modify(|tunables| {
+        tunables.max_gas_price = U256::from(300_000_000_000u64);
+        tunables.merkle_update_interval = Duration::from_secs(600);
+    });
+    tokio::time::sleep(Duration::from_secs(300)).await;
+    assert!(chains.ethereum.merkle_root().is_none());
+    wait_for("the Merkle root", || {
+        chains.ethereum.merkle_root().is_some()
+    })
+    .await;
+
+    relayer.stop().await;
+    chains.stop();
+    db.drop().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn defers_and_sends_early_merkle_roots() {
+    let Some(db) = TestDatabase::new().await else {
+        return;
+    };
+    let chains = Chains::new();
+    let relayer = Relayer::start(&db.config, &chains, false)
+        .await
+        .expect("Unable to start the relayer.");
+    let metrics = &relayer.metrics;
+    map_token(&chains).await;
+    deposit(&chains, 1_000_000);
+    wait_for("the deposit", || chains.concordium.is_operation_used(2)).await;
+
+    // With the gas price above the deferral price a single withdrawal is not
+    // approved by the regular updates.
+    relayer.tunables.send_modify(|tunables| {
+        tunables.merkle_update_interval = Duration::from_secs(30);
+        tunables.merkle_schedule = MerkleSchedule {
+            min_interval: Some(Duration::from_secs(10)),
+            backlog_size: Some(2),
+            defer_gas_price: Some(U256::from(500_000_000u64)),
+            ..MerkleSchedule::default()
+        };
+    });
+    let withdraw = || {
+        chains.concordium.withdraw(
+            USER_ACCOUNT,
+            CHILD_TOKEN,
+            cis2::TokenAmount(100_000u64.into()),
+            user_wallet().0,
+            None,
+        )
+    };
+    withdraw();
+    wait_for("the deferred update", || {
+        metrics.merkle_updates_deferred.get() > 0
+    })
+    .await;
+    tokio::time::sleep(Duration::from_secs(120)).await;
+    assert!(chains.ethereum.merkle_root().is_none());
+
+    // A second withdrawal fills the backlog, which is approved regardless of the
+    // gas price.
+    withdraw();
+    wait_for("the Merkle root", || {
+        chains.ethereum.merkle_root().is_some()
+    })
+    .await;
+    assert_eq!(
+        metrics
+            .merkle_updates
+            .with_label_values(&["backlog_size"])
+            .get(),
+        1
+    );
+    assert_eq!(
+        metrics
+            .merkle_updates
+            .with_label_values(&["interval"])
+            .get(),
+        0
+    );
+
+    relayer.stop().await;
+    chains.stop();
+    db.drop().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn fails_over_and_requires_a_quorum_of_ethereum_apis() {
+    let Some(db) = TestDatabase::new().await else {
+        return;
+    };
+    let chains = Chains::new();
+    let down = TestApi::new(&chains.ethereum);
+    let omitting = TestApi {
+        omit_logs: true,
+        ..TestApi::new(&chains.ethereum)
+    };
+    let honest = TestApi::new(&chains.ethereum);
+    down.set_available(false);
+    let relayer = Relayer::start_with_apis(
+        &db.config,
+        &chains,
+        false,
+        vec![down.clone(), omitting, honest],
+        Some(2),
+        BlocklistSource::None,
+    )
+    .await
+    .expect("Unable to start the relayer while one API is down.");
+    let metrics = &relayer.metrics;
+
+    // Only one of the available APIs returns the token map, so it is not
+    // accepted.
+    chains
+        .ethereum
+        .map_token(root_token(), CHILD_TOKEN, erc20_token_type());
+    wait_for("the missing quorum", || metrics.quorum_failures.get() > 0).await;
+    assert_eq!(chains.concordium.token_map(root_token().0), None);
+    assert!(
+        metrics
+            .endpoint_switches
+            .with_label_values(&["Ethereum"])
+            .get()
+            > 0,
+        "The relayer switched away from the API that is down."
+    );
+
+    // Once a second API returns the same events they are accepted.
+    down.set_available(true);
+    wait_for("the token map", || {
+        chains.concordium.token_map(root_token().0) == Some(CHILD_TOKEN)
+    })
+    .await;
+
+    relayer.stop().await;
+    chains.stop();
+    db.drop().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn does_not_query_logs_from_lagging_ethereum_apis() {
+    let Some(db) = TestDatabase::new().await else {
+        return;
+    };
+    let chains = Chains::new();
+    let current = TestApi::new(&chains.ethereum);
+    let lagging = TestApi::new(&chains.ethereum);
+    let relayer = Relayer::start_with_apis(
+        &db.config,
+        &chains,
+        false,
+        vec![current.clone(), lagging.clone()],
+        None,
+        BlocklistSource::None,
+    )
+    .await
+    .expect("Unable to start the relayer.");
+    map_token(&chains).await;
+
+    // The lagging API returns no logs for the block of the deposit, so the
+    // deposit would be skipped if the logs were queried from it.
+    lagging.set_lagging(true);
+    current.set_logs_available(false);
+    deposit(&chains, 1_000_000);
+    tokio::time::sleep(Duration::from_secs(120)).await;
+    assert!(!chains.concordium.is_operation_used(2));
+
+    // Once the current API returns logs again the deposit is found.
+    current.set_logs_available(true);
+    wait_for("the deposit", || chains.concordium.is_operation_used(2)).await;
+
+    relayer.stop().await;
+    chains.stop();
+    db.drop().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn shadow_mode_records_without_sending() {
+    let Some(db) = TestDatabase::new().await else {
+        return;
+    };
+    let Some(shadow_db) = TestDatabase::new().await else {
+        return;
+    };
+    let chains = Chains::new();
+    let shadow = Relayer::start_shadow(&shadow_db.config, &chains)
+        .await
+        .expect("Unable to start the shadow relayer.");
+    let metrics = &shadow.metrics;
+
+    // The token map is recorded, but not sent.
+    chains
+        .ethereum
+        .map_token(root_token(), CHILD_TOKEN, erc20_token_type());
+    wait_for("the recorded token map", || {
+        metrics.recorded_concordium_transactions.get() == 1
+    })
+    .await;
+    tokio::time::sleep(Duration::from_secs(60)).await;
+    assert_eq!(chains.concordium.num_state_updates(), 0);
+
+    // The production relayer sends it, and sets the same Merkle root as the
+    // shadow relayer records.
+    let relayer = Relayer::start(&db.config, &chains, false)
+        .await
+        .expect("Unable to start the relayer.");
+    wait_for("the token map", || {
+        chains.concordium.token_map(root_token().0) == Some(CHILD_TOKEN)
+    })
+    .await;
+    deposit(&chains, 1_000_000);
+    wait_for("the deposit", || chains.concordium.is_operation_used(2)).await;
+    chains.concordium.withdraw(
+        USER_ACCOUNT,
+        CHILD_TOKEN,
+        cis2::TokenAmount(500_000u64.into()),
+        user_wallet().0,
+        None,
+    );
+    wait_for("the Merkle root", || {
+        chains.ethereum.merkle_root().is_some()
+    })
+    .await;
+    wait_for("the recorded Merkle root", || {
+        metrics.recorded_merkle_roots.get() == 1
+    })
+    .await;
+    let root = chains.ethereum.merkle_root().expect("The root is set.");
+    let shadow_root: Vec<u8> = shadow_db
+        .client
+        .query_one(
+            &format!("SELECT root FROM {}.shadow_merkle_roots;", shadow_db.schema),
+            &[],
+        )
+        .await
+        .expect("The shadow relayer recorded one root.")
+        .get(0);
+    assert_eq!(shadow_root, root);
+    let statuses = shadow_db
+        .client
+        .query(
+            &format!(
+                "SELECT status::text FROM {}.concordium_transactions;",
+                shadow_db.schema
+            ),
+            &[],
+        )
+        .await
+        .expect("Unable to query the shadow transactions.");
+    assert!(!statuses.is_empty());
+    assert!(
+        statuses.iter().all(|row| row.get::<_, &str>(0) == "shadow"),
+        "The shadow relayer never sends transactions."
+    );
+
+    relayer.stop().await;
+    shadow.stop().await;
+    chains.stop();
+    db.drop().await;
+    shadow_db.drop().await;
+}